@@ -0,0 +1,56 @@
+//! Archive commands - hide items without deleting them.
+
+use super::{get_database, resolve};
+use anyhow::Result;
+use colored::Colorize;
+
+/// Archive an item so it no longer shows up in search, recent or ask.
+pub fn archive(id: &str) -> Result<()> {
+    let db = get_database()?;
+    let item = resolve::item(&db, id)?;
+
+    if item.is_archived() {
+        println!(
+            "{}",
+            format!("'{}' is already archived.", item.title).dimmed()
+        );
+        return Ok(());
+    }
+
+    db.archive_item(&item.id)?;
+
+    println!(
+        "{} Archived '{}' [{}]",
+        "✓".green(),
+        item.title.white(),
+        item.id.chars().take(8).collect::<String>()
+    );
+    println!(
+        "{}",
+        "Browse with 'olal recent --archived', restore with 'olal unarchive <id>'.".dimmed()
+    );
+
+    Ok(())
+}
+
+/// Restore an archived item.
+pub fn unarchive(id: &str) -> Result<()> {
+    let db = get_database()?;
+    let item = resolve::item(&db, id)?;
+
+    if !item.is_archived() {
+        println!("{}", format!("'{}' is not archived.", item.title).dimmed());
+        return Ok(());
+    }
+
+    db.unarchive_item(&item.id)?;
+
+    println!(
+        "{} Restored '{}' [{}]",
+        "✓".green(),
+        item.title.white(),
+        item.id.chars().take(8).collect::<String>()
+    );
+
+    Ok(())
+}
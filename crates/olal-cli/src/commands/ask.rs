@@ -1,52 +1,72 @@
 //! Ask command - RAG-based question answering.
 
-use super::get_database;
+use super::{get_search_database, search, warn_model_use, Ollama};
 use anyhow::{Context, Result};
 use olal_config::Config;
-use olal_ollama::{rag::ContextItem, OllamaClient, RagConfig};
+use olal_db::SearchFilter;
+use olal_ollama::rag::{cited_numbers, cited_sources, fuse_rankings, ContextItem, Exchange};
+use olal_ollama::{ModelUse, OllamaClient, RagConfig, SourceReference};
 use colored::Colorize;
 use std::io::{self, Write};
 use tokio::runtime::Runtime;
 
 /// Run the ask command.
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     question: &str,
     model: Option<String>,
     show_sources: bool,
-    max_context: usize,
+    max_context: Option<usize>,
     stream: bool,
+    rerank: bool,
+    expand: bool,
+    interactive: bool,
+    filter: SearchFilter,
 ) -> Result<()> {
-    let db = get_database()?;
+    let db = get_search_database()?;
     let config = Config::load().context("Failed to load configuration")?;
-    run_with_db(&db, &config, question, model, show_sources, max_context, stream)
+    let filter = search::resolve_project(&db, filter)?;
+    let filter = search::resolve_item(&db, filter)?;
+    let ollama = Ollama::connect(&config.ollama)?;
+    run_with_db(
+        &db,
+        &config,
+        &ollama,
+        question,
+        model,
+        show_sources,
+        max_context,
+        stream,
+        rerank,
+        expand,
+        interactive,
+        &filter,
+    )
 }
 
-/// Run ask with an existing database connection and config.
+/// Run ask with existing database and Ollama connections. Chunks are
+/// reranked if `rerank` is set or `ollama.rerank` is on, and also searched
+/// for with sub-queries of the question if `expand` is set or
+/// `ollama.multi_query` is on; only items matching `filter` are used as
+/// context. Without `max_context`, as many chunks are used as fit the
+/// model's context window. With `interactive`, follow-up questions are read
+/// from stdin until a blank line.
+#[allow(clippy::too_many_arguments)]
 pub fn run_with_db(
     db: &olal_db::Database,
     config: &Config,
+    ollama: &Ollama,
     question: &str,
     model: Option<String>,
     show_sources: bool,
-    max_context: usize,
+    max_context: Option<usize>,
     stream: bool,
+    rerank: bool,
+    expand: bool,
+    interactive: bool,
+    filter: &SearchFilter,
 ) -> Result<()> {
-
-    // Create Ollama client
-    let client = OllamaClient::from_config(&config.ollama)
-        .context("Failed to create Ollama client")?;
-
-    // Create async runtime
-    let rt = Runtime::new().context("Failed to create async runtime")?;
-
-    // Check if Ollama is available
-    let is_available = rt.block_on(client.is_available());
-    if !is_available {
-        anyhow::bail!(
-            "Ollama is not running at {}. Start it with 'ollama serve'.",
-            config.ollama.host
-        );
-    }
+    let Ollama { rt, client } = ollama;
 
     // Check embedding stats
     let (embedded, total) = db.embedding_stats()?;
@@ -65,25 +85,48 @@ pub fn run_with_db(
     // First, embed the question
     let model_name = model.as_deref().unwrap_or(&config.ollama.model);
     let embedding_model = &config.ollama.embedding_model;
+    warn_model_use(rt, client, model_name, ModelUse::Generate);
+    warn_model_use(rt, client, embedding_model, ModelUse::Embed);
+    let max_context = match max_context {
+        Some(max_context) => max_context,
+        None => rt
+            .block_on(client.context_budget(model_name, config.ollama.context_tokens))
+            .suggested_chunks(config.processing.chunk_size),
+    };
 
     println!(
         "{} {}",
         "Question:".cyan().bold(),
         question
     );
+    search::print_filter(filter);
     println!("{}", "─".repeat(70));
     println!();
 
-    // Generate embedding for the question
-    let query_embedding = rt
-        .block_on(client.embed(embedding_model, question))
-        .context("Failed to embed question")?;
-
-    // Search for similar chunks
+    // Build RAG config
     let min_similarity = 0.3;
-    let results = db.vector_search(&query_embedding, max_context, Some(min_similarity))?;
+    let rerank_model = &config.ollama.rerank_model;
+    let rag_config = RagConfig {
+        model: model_name.to_string(),
+        embedding_model: embedding_model.to_string(),
+        max_context_chunks: max_context,
+        min_similarity,
+        temperature: 0.7,
+        rerank: rerank || config.ollama.rerank,
+        rerank_model: (!rerank_model.is_empty()).then(|| rerank_model.clone()),
+        multi_query: expand || config.ollama.multi_query,
+        context_tokens: config.ollama.context_tokens,
+    };
 
-    if results.is_empty() {
+    let retriever = Retriever {
+        rt,
+        client,
+        db,
+        config: &rag_config,
+        filter,
+    };
+    let mut context = retriever.retrieve(question)?;
+    if context.is_empty() {
         println!(
             "{} No relevant content found in your knowledge base for this question.",
             "Note:".yellow()
@@ -96,86 +139,200 @@ pub fn run_with_db(
         return Ok(());
     }
 
-    // Convert to context items
-    let context: Vec<ContextItem> = results
-        .iter()
-        .map(|r| ContextItem {
-            content: r.chunk.content.clone(),
-            similarity: r.similarity,
-            item_id: r.item_id.clone(),
-            item_title: r.item_title.clone(),
-        })
-        .collect();
+    let mut history = Vec::new();
+    let mut question = question.to_string();
+    loop {
+        let answer = answer(rt, client, &question, &context, &history, &rag_config, stream, show_sources)?;
+        history.push(Exchange { question, answer });
+        if !interactive {
+            return Ok(());
+        }
 
-    // Build RAG config
-    let rag_config = RagConfig {
-        model: model_name.to_string(),
-        embedding_model: embedding_model.to_string(),
-        max_context_chunks: max_context,
-        min_similarity,
-        temperature: 0.7,
-    };
+        let Some(follow_up) = read_follow_up()? else {
+            return Ok(());
+        };
+        // Keep the chunks already found unless the follow-up finds
+        // entirely different ones
+        let found = retriever.search(std::slice::from_ref(&follow_up))?;
+        let shared = found
+            .iter()
+            .any(|f| context.iter().any(|c| c.item_id == f.item_id && c.content == f.content));
+        if !found.is_empty() && !shared {
+            println!("{}", "New topic, searching again...".dimmed());
+            context = retriever.retrieve(&follow_up)?;
+        }
+        question = follow_up;
+    }
+}
+
+/// Finds the chunks to answer a question from.
+struct Retriever<'a> {
+    rt: &'a Runtime,
+    client: &'a OllamaClient,
+    db: &'a olal_db::Database,
+    config: &'a RagConfig,
+    filter: &'a SearchFilter,
+}
 
-    // Generate answer
+impl Retriever<'_> {
+    /// Search for `question`, expanded into sub-queries and reranked as
+    /// configured.
+    fn retrieve(&self, question: &str) -> Result<Vec<ContextItem>> {
+        // Broad questions are searched for in parts too
+        let mut queries = vec![question.to_string()];
+        if self.config.multi_query {
+            let sub_queries = self
+                .rt
+                .block_on(self.client.expand_query(question, self.config))
+                .context("Failed to expand question")?;
+            for query in &sub_queries {
+                println!("{}", format!("Also searching: {}", query).dimmed());
+            }
+            queries.extend(sub_queries);
+        }
+        let context = self.search(&queries)?;
+
+        // Let the model pick the most relevant of the candidates
+        if self.config.rerank && !context.is_empty() {
+            println!(
+                "{}",
+                format!("Reranking {} chunks...", context.len()).dimmed()
+            );
+            return self
+                .rt
+                .block_on(self.client.rerank(question, context, self.config))
+                .context("Failed to rerank context");
+        }
+        Ok(context)
+    }
+
+    /// Chunks similar to any of `queries`, fused into one ranking.
+    fn search(&self, queries: &[String]) -> Result<Vec<ContextItem>> {
+        let mut rankings = Vec::new();
+        for query in queries {
+            let query_embedding = self
+                .rt
+                .block_on(self.client.embed(&self.config.embedding_model, query))
+                .context("Failed to embed question")?;
+            let results = self.db.vector_search_filtered(
+                &query_embedding,
+                self.config.candidate_count(),
+                Some(self.config.min_similarity),
+                self.filter,
+            )?;
+            rankings.push(
+                results
+                    .into_iter()
+                    .map(|r| ContextItem {
+                        pages: r.chunk.page_label(),
+                        content: r.chunk.content,
+                        similarity: r.similarity,
+                        item_id: r.item_id,
+                        item_title: r.item_title,
+                    })
+                    .collect(),
+            );
+        }
+        let mut context = fuse_rankings(rankings);
+        context.truncate(self.config.candidate_count());
+        Ok(context)
+    }
+}
+
+/// Answer `question` from `context`, printing the answer and, with
+/// `show_sources`, the sources it cites. Returns the answer.
+#[allow(clippy::too_many_arguments)]
+fn answer(
+    rt: &Runtime,
+    client: &OllamaClient,
+    question: &str,
+    context: &[ContextItem],
+    history: &[Exchange],
+    rag_config: &RagConfig,
+    stream: bool,
+    show_sources: bool,
+) -> Result<String> {
     if stream {
         // Streaming response
         let (mut rx, sources) = rt
-            .block_on(client.rag_query_stream(question, &context, &rag_config))
+            .block_on(client.rag_query_stream(question, context, history, rag_config))
             .context("Failed to generate answer")?;
 
         print!("{} ", "Answer:".green().bold());
         io::stdout().flush()?;
 
-        rt.block_on(async {
+        let answer = rt.block_on(async {
+            let mut answer = String::new();
             while let Some(chunk) = rx.recv().await {
                 print!("{}", chunk);
                 io::stdout().flush().ok();
+                answer.push_str(&chunk);
             }
+            answer
         });
 
         println!();
         println!();
 
-        // Show sources
-        if show_sources && !sources.is_empty() {
-            println!("{}", "─".repeat(70));
-            println!("{}", "Sources:".cyan().bold());
-            for (i, source) in sources.iter().enumerate() {
-                println!(
-                    "  {}. {} {} (similarity: {:.0}%)",
-                    i + 1,
-                    source.item_title.white(),
-                    format!("[{}]", &source.item_id[..8]).dimmed(),
-                    source.similarity * 100.0
-                );
-            }
+        if show_sources {
+            print_sources(&cited_sources(&answer, sources));
         }
+        Ok(answer)
     } else {
         // Non-streaming response
         let response = rt
-            .block_on(client.rag_query(question, &context, &rag_config))
+            .block_on(client.rag_query(question, context, history, rag_config))
             .context("Failed to generate answer")?;
 
         println!("{}", "Answer:".green().bold());
         println!();
-        println!("{}", response.answer);
+        println!("{}", highlight_citations(&response.answer));
         println!();
 
-        // Show sources
-        if show_sources && !response.sources.is_empty() {
-            println!("{}", "─".repeat(70));
-            println!("{}", "Sources:".cyan().bold());
-            for (i, source) in response.sources.iter().enumerate() {
-                println!(
-                    "  {}. {} {} (similarity: {:.0}%)",
-                    i + 1,
-                    source.item_title.white(),
-                    format!("[{}]", &source.item_id[..8]).dimmed(),
-                    source.similarity * 100.0
-                );
-            }
+        if show_sources {
+            print_sources(&response.sources);
         }
+        Ok(response.answer)
+    }
+}
+
+/// Prompt for a follow-up question. `None` on a blank line or end of input.
+fn read_follow_up() -> Result<Option<String>> {
+    println!();
+    print!("{} ", "Follow-up (blank to finish):".cyan().bold());
+    io::stdout().flush()?;
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    let line = line.trim();
+    Ok((!line.is_empty()).then(|| line.to_string()))
+}
+
+/// Sources, numbered as the answer cites them.
+fn print_sources(sources: &[SourceReference]) {
+    if sources.is_empty() {
+        return;
     }
+    println!("{}", "─".repeat(70));
+    println!("{}", "Sources:".cyan().bold());
+    for source in sources {
+        let pages = source.pages.as_ref().map(|p| format!(", {}", p)).unwrap_or_default();
+        println!(
+            "  {} {}{} {} (similarity: {:.0}%)",
+            format!("[{}]", source.number).cyan(),
+            source.item_title.white(),
+            pages,
+            format!("[{}]", &source.item_id[..8]).dimmed(),
+            source.similarity * 100.0
+        );
+    }
+}
 
-    Ok(())
+/// Color the `[n]` citations in an answer so they stand out.
+fn highlight_citations(answer: &str) -> String {
+    let mut highlighted = answer.to_string();
+    for number in cited_numbers(answer) {
+        let citation = format!("[{}]", number);
+        highlighted = highlighted.replace(&citation, &citation.cyan().to_string());
+    }
+    highlighted
 }
@@ -0,0 +1,301 @@
+//! Backup and restore commands - single-file archives of the knowledge base.
+//!
+//! A backup is a gzip-compressed tarball containing:
+//! - `manifest.json` - olal version, schema version, creation time
+//! - `olal.db` - a consistent snapshot taken with SQLite's online backup API
+//!   (encrypted with the same key if the knowledge base is encrypted)
+//! - `config.toml` - the configuration file, if present
+//! - `artifacts/` - derived files (thumbnails, clips, audio), with `--artifacts`
+
+use super::encrypt::database_key;
+use super::{get_paths, open_database};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use olal_db::{Database, SCHEMA_VERSION};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const DB_ENTRY: &str = "olal.db";
+const CONFIG_ENTRY: &str = "config.toml";
+const ARTIFACTS_ENTRY: &str = "artifacts";
+
+/// Run the backup command.
+pub fn run(output: Option<PathBuf>, artifacts: bool) -> Result<()> {
+    let paths = get_paths()?;
+    if !paths.is_initialized() {
+        return Err(olal_core::Error::NotInitialized.into());
+    }
+    let key = database_key(&paths.database_file)?;
+    let db = Database::open_with_key(&paths.database_file, key.as_deref())
+        .context("Failed to open database")?;
+
+    let output = output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "olal-backup-{}.tar.gz",
+            Utc::now().format("%Y%m%d-%H%M%S")
+        ))
+    });
+
+    println!("{}", "Creating backup...".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    let snapshot = std::env::temp_dir().join(format!("olal-backup-{}.db", std::process::id()));
+    if snapshot.exists() {
+        fs::remove_file(&snapshot)?;
+    }
+    db.backup_to(&snapshot)
+        .context("Failed to snapshot database")?;
+
+    let config_file = paths.config_file.exists().then_some(paths.config_file.as_path());
+    let artifacts_dir =
+        (artifacts && paths.artifacts_dir.exists()).then_some(paths.artifacts_dir.as_path());
+
+    let result = write_archive(&output, &snapshot, key.as_deref(), config_file, artifacts_dir);
+    let _ = fs::remove_file(&snapshot);
+    result.with_context(|| format!("Failed to write {}", output.display()))?;
+
+    println!("  {} {}", "Database:".cyan(), paths.database_file.display());
+    if key.is_some() {
+        println!("  {} yes", "Encrypted:".cyan());
+    }
+    if let Some(config) = config_file {
+        println!("  {} {}", "Config:".cyan(), config.display());
+    }
+    if let Some(dir) = artifacts_dir {
+        println!("  {} {}", "Artifacts:".cyan(), dir.display());
+    } else if artifacts {
+        println!("  {} {}", "Artifacts:".cyan(), "none".dimmed());
+    }
+
+    let size = fs::metadata(&output).map(|m| m.len()).unwrap_or(0);
+    println!();
+    println!(
+        "{} Backup written to {} ({:.1} MB)",
+        "✓".green(),
+        output.display(),
+        size as f64 / 1_048_576.0
+    );
+
+    Ok(())
+}
+
+/// Run the restore command.
+pub fn restore(archive: &Path, force: bool) -> Result<()> {
+    let paths = get_paths()?;
+
+    if paths.database_file.exists() && !force {
+        anyhow::bail!(
+            "A knowledge base already exists at {}. Use --force to replace it.",
+            paths.database_file.display()
+        );
+    }
+
+    println!("{}", "Restoring backup...".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    paths.ensure_dirs()?;
+    let staging = paths.data_dir.join(".restore");
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+
+    let result = unpack_archive(archive, &staging).and_then(|version| {
+        install(&staging, &paths)?;
+        Ok(version)
+    });
+    let _ = fs::remove_dir_all(&staging);
+    let version = result?;
+
+    // Opening runs any pending migrations
+    let db = open_database(&paths.database_file).context("Failed to open restored database")?;
+    if !db.integrity_check()? {
+        anyhow::bail!("Restored database failed its integrity check");
+    }
+
+    println!("  {} {}", "Database:".cyan(), paths.database_file.display());
+    if version < SCHEMA_VERSION {
+        println!(
+            "  {} v{} → v{}",
+            "Migrated:".cyan(),
+            version,
+            SCHEMA_VERSION
+        );
+    }
+    println!();
+    println!("{} Restored from {}", "✓".green(), archive.display());
+
+    Ok(())
+}
+
+/// Write a backup archive from a database snapshot and optional extras.
+fn write_archive(
+    output: &Path,
+    snapshot: &Path,
+    key: Option<&str>,
+    config_file: Option<&Path>,
+    artifacts_dir: Option<&Path>,
+) -> Result<()> {
+    let manifest = serde_json::json!({
+        "olal_version": env!("CARGO_PKG_VERSION"),
+        "schema_version": Database::schema_version_of(snapshot, key)?,
+        "created_at": Utc::now().to_rfc3339(),
+        "encrypted": key.is_some(),
+        "artifacts": artifacts_dir.is_some(),
+    });
+    let manifest = serde_json::to_vec_pretty(&manifest)?;
+
+    let encoder = GzEncoder::new(File::create(output)?, Compression::default());
+    let mut tar = tar::Builder::new(encoder);
+
+    let mut header = tar::Header::new_gnu();
+    header.set_size(manifest.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(Utc::now().timestamp() as u64);
+    header.set_cksum();
+    tar.append_data(&mut header, MANIFEST_ENTRY, manifest.as_slice())?;
+
+    tar.append_path_with_name(snapshot, DB_ENTRY)?;
+    if let Some(config) = config_file {
+        tar.append_path_with_name(config, CONFIG_ENTRY)?;
+    }
+    if let Some(dir) = artifacts_dir {
+        tar.append_dir_all(ARTIFACTS_ENTRY, dir)?;
+    }
+
+    tar.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// Unpack an archive into `staging` and validate it.
+///
+/// Returns the schema version of the backed-up database. Backups from a
+/// newer olal (higher schema version) are rejected.
+fn unpack_archive(archive: &Path, staging: &Path) -> Result<i32> {
+    let file = File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    tar::Archive::new(GzDecoder::new(file))
+        .unpack(staging)
+        .context("Failed to unpack backup (is this an olal backup?)")?;
+
+    let manifest_path = staging.join(MANIFEST_ENTRY);
+    let db_path = staging.join(DB_ENTRY);
+    if !manifest_path.exists() || !db_path.exists() {
+        anyhow::bail!("Not an olal backup: missing {} or {}", MANIFEST_ENTRY, DB_ENTRY);
+    }
+
+    let manifest: serde_json::Value = serde_json::from_str(&fs::read_to_string(&manifest_path)?)
+        .context("Invalid backup manifest")?;
+    let recorded = manifest["schema_version"].as_i64().unwrap_or(0) as i32;
+    let key = database_key(&db_path)?;
+    let actual = Database::schema_version_of(&db_path, key.as_deref())
+        .context("Backup database is unreadable")?;
+
+    if actual != recorded {
+        anyhow::bail!(
+            "Backup is inconsistent: manifest says schema v{}, database is v{}",
+            recorded,
+            actual
+        );
+    }
+    if actual == 0 {
+        anyhow::bail!("Backup database has no olal schema");
+    }
+    if actual > SCHEMA_VERSION {
+        anyhow::bail!(
+            "Backup uses schema v{} but this olal supports up to v{}. Upgrade olal first.",
+            actual,
+            SCHEMA_VERSION
+        );
+    }
+
+    Ok(actual)
+}
+
+/// Move unpacked files from `staging` into the application layout.
+fn install(staging: &Path, paths: &olal_config::AppPaths) -> Result<()> {
+    // Stale WAL files from a replaced database would be replayed over the restore
+    for suffix in ["-wal", "-shm"] {
+        let sidecar = PathBuf::from(format!("{}{}", paths.database_file.display(), suffix));
+        if sidecar.exists() {
+            fs::remove_file(&sidecar)?;
+        }
+    }
+    fs::copy(staging.join(DB_ENTRY), &paths.database_file)?;
+
+    let config = staging.join(CONFIG_ENTRY);
+    if config.exists() {
+        fs::copy(&config, &paths.config_file)?;
+    }
+
+    let artifacts = staging.join(ARTIFACTS_ENTRY);
+    if artifacts.exists() {
+        if paths.artifacts_dir.exists() {
+            fs::remove_dir_all(&paths.artifacts_dir)?;
+        }
+        fs::rename(&artifacts, &paths.artifacts_dir)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, ItemType};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let dir = tempdir().unwrap();
+
+        let db = Database::open(dir.path().join("source.db")).unwrap();
+        db.create_item(&Item::new(ItemType::Note, "Backed up")).unwrap();
+        let snapshot = dir.path().join("snapshot.db");
+        db.backup_to(&snapshot).unwrap();
+
+        let config = dir.path().join("config.toml");
+        fs::write(&config, "[ollama]\n").unwrap();
+        let artifacts = dir.path().join("artifacts");
+        fs::create_dir_all(artifacts.join("item-1")).unwrap();
+        fs::write(artifacts.join("item-1/thumb.jpg"), b"jpg").unwrap();
+
+        let archive = dir.path().join("backup.tar.gz");
+        write_archive(&archive, &snapshot, None, Some(&config), Some(&artifacts)).unwrap();
+
+        let staging = dir.path().join("staging");
+        assert_eq!(unpack_archive(&archive, &staging).unwrap(), SCHEMA_VERSION);
+        assert!(staging.join(CONFIG_ENTRY).exists());
+        assert!(staging.join("artifacts/item-1/thumb.jpg").exists());
+
+        let restored = Database::open(staging.join(DB_ENTRY)).unwrap();
+        assert_eq!(restored.recent_items(None).unwrap()[0].title, "Backed up");
+    }
+
+    #[test]
+    fn test_rejects_newer_schema() {
+        let dir = tempdir().unwrap();
+
+        let snapshot = dir.path().join("future.db");
+        drop(future_schema_db(&snapshot));
+
+        let archive = dir.path().join("future.tar.gz");
+        write_archive(&archive, &snapshot, None, None, None).unwrap();
+
+        let err = unpack_archive(&archive, &dir.path().join("staging")).unwrap_err();
+        assert!(err.to_string().contains("Upgrade olal"));
+    }
+
+    /// Create a database claiming a schema version newer than this build's.
+    fn future_schema_db(path: &Path) -> Database {
+        let db = Database::open(path).unwrap();
+        db.conn()
+            .unwrap()
+            .execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION + 1))
+            .unwrap();
+        db
+    }
+}
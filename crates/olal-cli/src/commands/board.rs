@@ -0,0 +1,272 @@
+//! Kanban board view of tasks.
+//!
+//! Tasks are laid out in Pending / In Progress / Done columns. Run in a
+//! terminal, the board is interactive: the arrow keys (or h/j/k/l) select a
+//! card and H/L (or </>) move it to the neighbouring column.
+
+use super::{dates, get_database, resolve, task};
+use anyhow::Result;
+use chrono::Utc;
+use colored::{ColoredString, Colorize};
+use console::{pad_str, truncate_str, Alignment, Key, Term};
+use olal_core::{Project, Task, TaskStatus};
+use olal_db::Database;
+use std::fmt::Write;
+use std::io::{self, IsTerminal};
+
+const COLUMNS: [TaskStatus; 3] = [TaskStatus::Pending, TaskStatus::InProgress, TaskStatus::Done];
+
+/// Most recently completed tasks shown in the Done column.
+const DONE_SHOWN: usize = 10;
+
+const GAP: &str = "   ";
+
+pub fn run(project: Option<String>) -> Result<()> {
+    let db = get_database()?;
+    let project = project.map(|name| resolve::project(&db, &name)).transpose()?;
+
+    let board = Board::load(&db, project.as_ref())?;
+    if board.is_empty() {
+        println!(
+            "{}",
+            "No tasks found. Use 'olal task add <description>' to create one.".dimmed()
+        );
+        return Ok(());
+    }
+
+    let term = Term::stdout();
+    if !term.is_term() || !io::stdin().is_terminal() {
+        print!("{}", board.render(board_width(&term), None, project.as_ref())?);
+        return Ok(());
+    }
+
+    term.hide_cursor()?;
+    let result = interact(&db, project.as_ref(), board, &term);
+    term.show_cursor()?;
+    result
+}
+
+fn board_width(term: &Term) -> usize {
+    term.size_checked().map(|(_, cols)| cols as usize).unwrap_or(100)
+}
+
+/// Redraw the board after every key until the user quits.
+fn interact(db: &Database, project: Option<&Project>, mut board: Board, term: &Term) -> Result<()> {
+    let (mut column, mut row) = (0, 0);
+
+    loop {
+        row = row.min(board.columns[column].len().saturating_sub(1));
+        term.clear_screen()?;
+        term.write_str(&board.render(board_width(term), Some((column, row)), project)?)?;
+        term.write_line("")?;
+        term.write_line(
+            &"←/→ column · ↑/↓ card · H/L move card · q quit"
+                .dimmed()
+                .to_string(),
+        )?;
+
+        let target = match term.read_key()? {
+            Key::Char('q') | Key::Escape | Key::CtrlC => break,
+            Key::ArrowLeft | Key::Char('h') => {
+                column = column.saturating_sub(1);
+                continue;
+            }
+            Key::ArrowRight | Key::Char('l') => {
+                column = (column + 1).min(COLUMNS.len() - 1);
+                continue;
+            }
+            Key::ArrowUp | Key::Char('k') => {
+                row = row.saturating_sub(1);
+                continue;
+            }
+            Key::ArrowDown | Key::Char('j') => {
+                row += 1;
+                continue;
+            }
+            Key::Char('H') | Key::Char('<') if column > 0 => column - 1,
+            Key::Char('L') | Key::Char('>') if column + 1 < COLUMNS.len() => column + 1,
+            _ => continue,
+        };
+
+        let Some(card) = board.columns[column].get(row).cloned() else {
+            continue;
+        };
+        move_task(db, card.clone(), COLUMNS[target])?;
+
+        // Follow the card to its new column
+        board = Board::load(db, project)?;
+        column = target;
+        row = board.columns[column]
+            .iter()
+            .position(|t| t.id == card.id)
+            .unwrap_or(0);
+    }
+
+    term.clear_screen()?;
+    Ok(())
+}
+
+/// Give `task` the status of the column it was moved to.
+fn move_task(db: &Database, mut task: Task, status: TaskStatus) -> Result<()> {
+    match status {
+        TaskStatus::Done => {
+            task::complete(db, &task)?;
+        }
+        TaskStatus::InProgress => {
+            task.mark_in_progress();
+            db.update_task(&task)?;
+        }
+        _ => {
+            task.status = TaskStatus::Pending;
+            task.completed_at = None;
+            db.update_task(&task)?;
+        }
+    }
+    Ok(())
+}
+
+/// Tasks by column, in the order their cards are shown.
+struct Board {
+    columns: [Vec<Task>; 3],
+    /// Done tasks beyond the ones shown.
+    done_hidden: usize,
+}
+
+impl Board {
+    fn load(db: &Database, project: Option<&Project>) -> Result<Self> {
+        let tasks = match project {
+            Some(project) => db.list_tasks_by_project(&project.id)?,
+            None => db.list_tasks(None)?,
+        };
+
+        let mut columns: [Vec<Task>; 3] = Default::default();
+        for task in tasks {
+            if let Some(index) = COLUMNS.iter().position(|s| *s == task.status) {
+                columns[index].push(task);
+            }
+        }
+
+        // Open work by priority, then soonest due; Done newest first
+        for open in &mut columns[..2] {
+            open.sort_by_key(|t| (std::cmp::Reverse(t.priority), t.due_date.is_none(), t.due_date));
+        }
+        columns[2].sort_by_key(|t| std::cmp::Reverse(t.completed_at));
+        let done_hidden = columns[2].len().saturating_sub(DONE_SHOWN);
+        columns[2].truncate(DONE_SHOWN);
+
+        Ok(Self { columns, done_hidden })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.columns.iter().all(Vec::is_empty) && self.done_hidden == 0
+    }
+
+    /// The board laid out to `width` columns, marking the `selected`
+    /// (column, row) card.
+    fn render(
+        &self,
+        width: usize,
+        selected: Option<(usize, usize)>,
+        project: Option<&Project>,
+    ) -> Result<String> {
+        let card_width = (width.saturating_sub(GAP.len() * 2) / 3).max(20);
+        let mut out = String::new();
+
+        let heading = match project {
+            Some(project) => format!("Task Board · {}", project.name),
+            None => "Task Board".to_string(),
+        };
+        writeln!(out, "{}", heading.cyan().bold())?;
+        writeln!(out)?;
+
+        let headers: Vec<String> = COLUMNS
+            .iter()
+            .enumerate()
+            .map(|(i, status)| {
+                let count = self.columns[i].len() + if i == 2 { self.done_hidden } else { 0 };
+                let label = format!("{} ({})", column_name(*status), count);
+                pad(&paint(*status, &label).bold().to_string(), card_width)
+            })
+            .collect();
+        writeln!(out, "{}", headers.join(GAP).trim_end())?;
+        let rule = "─".repeat(card_width);
+        writeln!(out, "{}", vec![rule; 3].join(GAP))?;
+
+        let rows = self.columns.iter().map(Vec::len).max().unwrap_or(0);
+        for row in 0..rows {
+            let mut lines = [Vec::new(), Vec::new()];
+            for (column, status) in COLUMNS.iter().enumerate() {
+                let (title, meta) = match self.columns[column].get(row) {
+                    Some(task) => {
+                        let chosen = selected == Some((column, row));
+                        card(task, *status, chosen, card_width)
+                    }
+                    None => (String::new(), String::new()),
+                };
+                lines[0].push(pad(&title, card_width));
+                lines[1].push(pad(&meta, card_width));
+            }
+            for line in lines {
+                writeln!(out, "{}", line.join(GAP).trim_end())?;
+            }
+        }
+
+        if self.done_hidden > 0 {
+            let more = format!("… {} more", self.done_hidden).dimmed().to_string();
+            let indent = " ".repeat((card_width + GAP.len()) * 2);
+            writeln!(out, "{}{}", indent, more)?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// The two lines of a task's card: its title and a dimmed detail line.
+fn card(task: &Task, status: TaskStatus, selected: bool, width: usize) -> (String, String) {
+    let marker = if selected { "▸ ".cyan().bold().to_string() } else { "  ".to_string() };
+    let text_width = width.saturating_sub(2);
+
+    let title = truncate_str(&task.title, text_width, "…");
+    let title = if selected {
+        paint(status, &title).bold().reversed()
+    } else {
+        paint(status, &title)
+    };
+
+    let mut details = vec![task.id.chars().take(8).collect::<String>()];
+    if task.priority > 0 {
+        details.push(format!("P{}", task.priority));
+    }
+    let mut overdue = false;
+    if let Some(due) = task.due_date.filter(|_| status != TaskStatus::Done) {
+        details.push(format!("due {}", dates::format_due(due)));
+        overdue = due < Utc::now();
+    }
+    let details = truncate_str(&details.join(" · "), text_width, "…").to_string();
+    let details = if overdue { details.red() } else { details.dimmed() };
+
+    (format!("{}{}", marker, title), format!("  {}", details))
+}
+
+fn column_name(status: TaskStatus) -> &'static str {
+    match status {
+        TaskStatus::Pending => "Pending",
+        TaskStatus::InProgress => "In Progress",
+        TaskStatus::Done => "Done",
+        TaskStatus::Cancelled => "Cancelled",
+    }
+}
+
+fn paint(status: TaskStatus, text: &str) -> ColoredString {
+    match status {
+        TaskStatus::Pending => text.yellow(),
+        TaskStatus::InProgress => text.blue(),
+        TaskStatus::Done => text.green(),
+        TaskStatus::Cancelled => text.dimmed(),
+    }
+}
+
+/// Pad colored `text` to `width` visible columns.
+fn pad(text: &str, width: usize) -> String {
+    pad_str(text, width, Alignment::Left, None).into_owned()
+}
@@ -1,20 +1,53 @@
 //! Capture command - quick thought/note capture.
 
-use super::get_database;
-use anyhow::Result;
-use olal_core::{Chunk, Item, ItemType};
+use super::{get_database, read_stdin};
+use anyhow::{Context, Result};
+use olal_config::Config;
+use olal_core::{Chunk, Item, ItemType, Link, LinkType};
+use olal_db::Database;
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{GenerateRequest, OllamaClient, OllamaError};
 use chrono::Utc;
 use colored::Colorize;
+use serde_json::json;
+use std::collections::HashSet;
+use tokio::runtime::Runtime;
+
+/// Most related items to connect a thought to.
+const MAX_RELATED: usize = 5;
+
+/// Minimum similarity for an item to count as related.
+const MIN_SIMILARITY: f32 = 0.3;
+
+/// Run the capture command. A thought of `-` is read from stdin. With
+/// `ask`, the thought is then connected to related items in one go.
+pub fn run(thought: &str, title: Option<String>, tags: Vec<String>, ask: Option<String>) -> Result<()> {
+    let piped;
+    let thought = if thought == "-" {
+        piped = read_stdin()?;
+        piped.trim()
+    } else {
+        thought
+    };
+    if thought.is_empty() {
+        anyhow::bail!("Nothing to capture");
+    }
 
-/// Run the capture command.
-pub fn run(thought: &str, title: Option<String>, tags: Vec<String>) -> Result<()> {
     let db = get_database()?;
 
+    // Connecting needs Ollama: check before saving anything, so a failed
+    // capture-and-ask leaves nothing half done
+    let ollama = match ask {
+        Some(_) => Some(connect_ollama()?),
+        None => None,
+    };
+
     // Generate a title if not provided
     let title = title.unwrap_or_else(|| {
-        // Use first 50 chars of thought or timestamp
-        let preview: String = thought.chars().take(50).collect();
-        if preview.len() < thought.len() {
+        // Use first 50 chars of the first line or timestamp
+        let first_line = thought.lines().next().unwrap_or_default();
+        let preview: String = first_line.chars().take(50).collect();
+        if preview.len() < first_line.len() {
             format!("{}...", preview)
         } else if preview.is_empty() {
             format!("Note {}", Utc::now().format("%Y-%m-%d %H:%M"))
@@ -32,10 +65,11 @@ pub fn run(thought: &str, title: Option<String>, tags: Vec<String>) -> Result<()
     });
 
     db.create_item(&item)?;
+    db.set_item_content(&item.id, thought)?;
 
     // Create a single chunk with the content
     let chunk = Chunk::new(item.id.clone(), 0, thought);
-    db.create_chunks(&[chunk])?;
+    db.create_chunks(std::slice::from_ref(&chunk))?;
 
     // Add tags if provided
     for tag_name in &tags {
@@ -61,10 +95,120 @@ pub fn run(thought: &str, title: Option<String>, tags: Vec<String>) -> Result<()
     }
 
     println!();
-    println!(
-        "{}",
-        "Use 'olal show <id>' to view or 'olal search' to find it.".dimmed()
-    );
+
+    match (ask, ollama) {
+        (Some(question), Some((config, client, rt))) => {
+            connect(&db, &config, &client, &rt, &item, &chunk, &question)
+        }
+        _ => {
+            println!(
+                "{}",
+                "Use 'olal show <id>' to view or 'olal search' to find it.".dimmed()
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Config, client and runtime for talking to Ollama, once it's up.
+fn connect_ollama() -> Result<(Config, OllamaClient, Runtime)> {
+    let config = Config::load().context("Failed to load configuration")?;
+    let client = OllamaClient::from_config(&config.ollama)
+        .context("Failed to create Ollama client")?;
+    let rt = Runtime::new().context("Failed to create async runtime")?;
+    if !rt.block_on(client.is_available()) {
+        return Err(OllamaError::ServerNotRunning {
+            host: config.ollama.host.clone(),
+        }
+        .into());
+    }
+    Ok((config, client, rt))
+}
+
+/// Embed the captured thought, find related items, answer `question`
+/// about how they connect, and link the thought to them.
+fn connect(
+    db: &Database,
+    config: &Config,
+    client: &OllamaClient,
+    rt: &Runtime,
+    item: &Item,
+    chunk: &Chunk,
+    question: &str,
+) -> Result<()> {
+    let model = &config.ollama.embedding_model;
+    let cached = db
+        .reuse_embedding(chunk, model)?
+        .then(|| db.get_embedding(&chunk.id))
+        .transpose()?
+        .flatten();
+    let embedding = match cached {
+        Some(embedding) => embedding,
+        None => {
+            let embedding = rt
+                .block_on(client.embed(model, &chunk.content))
+                .context("Failed to embed thought")?;
+            db.store_embedding(&chunk.id, &embedding, model)?;
+            embedding
+        }
+    };
+
+    // Best chunk per other item
+    let mut seen = HashSet::new();
+    let related: Vec<_> = db
+        .vector_search(&embedding, MAX_RELATED * 4, Some(MIN_SIMILARITY))?
+        .into_iter()
+        .filter(|r| r.item_id != item.id && seen.insert(r.item_id.clone()))
+        .take(MAX_RELATED)
+        .collect();
+
+    if related.is_empty() {
+        println!(
+            "{}",
+            "Nothing related in your knowledge base yet (are other items embedded?).".dimmed()
+        );
+        return Ok(());
+    }
+
+    let context: Vec<_> = related
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            json!({
+                "number": i + 1,
+                "title": r.item_title,
+                "content": r.chunk.content,
+            })
+        })
+        .collect();
+    let prompt = prompts::render(
+        Prompt::CaptureAsk,
+        &json!({ "thought": chunk.content, "question": question, "related": context }),
+    )?;
+    let response = rt
+        .block_on(client.generate(GenerateRequest::new(&config.ollama.model, prompt)))
+        .context("Failed to generate answer")?;
+
+    for r in &related {
+        let link = Link::new(item.id.clone(), r.item_id.clone(), LinkType::Related)
+            .with_strength(r.similarity as f64);
+        db.create_link(&link)?;
+    }
+
+    println!("{} {}", "Question:".cyan().bold(), question);
+    println!("{}", "─".repeat(50));
+    println!("{}", response.response.trim());
+    println!();
+    println!("{}", "Linked to:".cyan().bold());
+    for (i, r) in related.iter().enumerate() {
+        println!(
+            "  {}. {} {} (similarity: {:.0}%)",
+            i + 1,
+            r.item_title.white(),
+            format!("[{}]", r.item_id.chars().take(8).collect::<String>()).dimmed(),
+            r.similarity * 100.0
+        );
+    }
 
     Ok(())
 }
@@ -1,11 +1,17 @@
 //! Clips command - AI-based clip detection from timestamped content.
 
-use super::get_database;
+use super::{get_database, resolve};
 use anyhow::{Context, Result};
+use chrono::Utc;
 use olal_config::Config;
-use olal_core::ItemType;
-use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient};
+use olal_core::{Chunk, Item, ItemType, Link, LinkType};
+use olal_db::Database;
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient, OllamaError};
+use serde_json::json;
 use colored::Colorize;
+use olal_process::{ClipMethod, TranscriptSegment, VerticalFit};
+use std::path::{Path, PathBuf};
 use tokio::runtime::Runtime;
 
 /// A suggested clip from the content.
@@ -17,19 +23,29 @@ pub struct ClipSuggestion {
     pub reason: String,
 }
 
-/// Run the clips command.
+/// Where and how `--export` writes clips.
+#[derive(Debug)]
+pub struct ClipExport {
+    pub dir: PathBuf,
+    /// Render 9:16 with burned-in subtitles instead of cutting as-is.
+    pub vertical: Option<VerticalFit>,
+}
+
+/// Run the clips command. With `export`, the clips are cut with ffmpeg
+/// instead of printing ffmpeg commands.
 pub fn run(
     item_id: &str,
     count: usize,
     min_duration: u32,
     max_duration: u32,
     model: Option<String>,
+    export: Option<ClipExport>,
 ) -> Result<()> {
     let db = get_database()?;
     let config = Config::load().context("Failed to load configuration")?;
 
     // Get the item
-    let item = db.get_item_by_prefix(item_id)?;
+    let item = resolve::item(&db, item_id)?;
 
     // Check if it's a video or audio (has timestamps)
     if item.item_type != ItemType::Video && item.item_type != ItemType::Audio {
@@ -41,6 +57,19 @@ pub fn run(
         );
     }
 
+    if export.as_ref().is_some_and(|e| e.vertical.is_some()) && item.item_type != ItemType::Video {
+        anyhow::bail!("Vertical clips need a video; '{}' is {}.", item.title, item.item_type);
+    }
+
+    // Exporting needs the original recording
+    let source = item.source_path.as_deref().map(PathBuf::from);
+    if export.is_some() && !source.as_deref().is_some_and(Path::exists) {
+        anyhow::bail!(
+            "Can't export clips of '{}': its source file is missing.",
+            item.title
+        );
+    }
+
     // Get chunks with timestamps
     let chunks = db.get_chunks_by_item(&item.id)?;
 
@@ -63,10 +92,10 @@ pub fn run(
     // Check if Ollama is available
     let is_available = rt.block_on(client.is_available());
     if !is_available {
-        anyhow::bail!(
-            "Ollama is not running at {}. Start it with 'ollama serve'.",
-            config.ollama.host
-        );
+        return Err(OllamaError::ServerNotRunning {
+            host: config.ollama.host.clone(),
+        }
+        .into());
     }
 
     let model_name = model.as_deref().unwrap_or(&config.ollama.model);
@@ -102,25 +131,15 @@ pub fn run(
     };
 
     // Build prompt
-    let prompt = format!(
-        r#"Analyze the following timestamped transcript and identify {} engaging clips that would work well as short-form content (like YouTube Shorts or TikTok).
-
-Requirements:
-- Each clip should be between {} and {} seconds
-- Look for: surprising moments, key insights, funny/entertaining moments, controversial statements, quotable phrases, dramatic reveals
-- Provide start and end timestamps that capture complete thoughts
-- Give each clip a catchy title
-
-Transcript:
-{}
-
-Respond in this exact format for each clip (one per line):
-CLIP: [start_seconds]-[end_seconds] | Title: [catchy title] | Reason: [why this is engaging]
-
-Example:
-CLIP: 45.0-75.0 | Title: The Shocking Truth About AI | Reason: Speaker reveals unexpected insight that challenges common assumptions"#,
-        count, min_duration, max_duration, content_for_prompt
-    );
+    let prompt = prompts::render(
+        Prompt::Clips,
+        &json!({
+            "count": count,
+            "min_duration": min_duration,
+            "max_duration": max_duration,
+            "transcript": content_for_prompt,
+        }),
+    )?;
 
     let request = GenerateRequest::new(model_name, prompt)
         .with_options(GenerateOptions::new().with_temperature(0.7).with_num_predict(1000));
@@ -165,6 +184,10 @@ CLIP: 45.0-75.0 | Title: The Shocking Truth About AI | Reason: Speaker reveals u
         println!();
     }
 
+    if let (Some(export), Some(source)) = (export, source) {
+        return export_clips(&db, &item, &chunks, &source, &suggestions, &export);
+    }
+
     // Show ffmpeg commands
     println!("{}", "─".repeat(70));
     println!("{}", "FFmpeg Commands:".cyan().bold());
@@ -172,34 +195,275 @@ CLIP: 45.0-75.0 | Title: The Shocking Truth About AI | Reason: Speaker reveals u
 
     let source = item.source_path.as_deref().unwrap_or("<source_file>");
     for (i, clip) in suggestions.iter().enumerate() {
-        let output_name = clip
-            .title
-            .to_lowercase()
-            .replace(' ', "_")
-            .chars()
-            .filter(|c| c.is_alphanumeric() || *c == '_')
-            .take(30)
-            .collect::<String>();
-
         println!(
             "# Clip {}: {}",
             i + 1,
             clip.title
         );
         println!(
-            "ffmpeg -i \"{}\" -ss {:.1} -t {:.1} -c copy \"clip_{}_{}.mp4\"",
+            "ffmpeg -i \"{}\" -ss {:.1} -t {:.1} -c copy \"{}\"",
             source,
             clip.start_time,
             clip.end_time - clip.start_time,
-            i + 1,
-            output_name
+            clip_file_name(i + 1, &clip.title, "mp4")
         );
         println!();
     }
+    println!("Cut them automatically with: olal clips {} --export", item_id);
+
+    Ok(())
+}
+
+/// Cut each clip out of `source`, and record it as an item linked to the
+/// original.
+fn export_clips(
+    db: &Database,
+    item: &Item,
+    chunks: &[Chunk],
+    source: &Path,
+    suggestions: &[ClipSuggestion],
+    export: &ClipExport,
+) -> Result<()> {
+    let dir = &export.dir;
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+    let extension = match export.vertical {
+        Some(_) => "mp4",
+        None => source.extension().and_then(|e| e.to_str()).unwrap_or("mp4"),
+    };
+
+    println!("{}", "─".repeat(70));
+    println!("{}", "Exporting Clips:".cyan().bold());
+    println!();
+
+    let mut exported = 0;
+    for (i, clip) in suggestions.iter().enumerate() {
+        let path = dir.join(clip_file_name(i + 1, &clip.title, extension));
+        let segments = clip_segments(chunks, clip);
+        let cut = match export.vertical {
+            Some(fit) => render_short(item, i + 1, source, &path, clip, &segments, fit)
+                .map(|()| ClipMethod::Reencode),
+            None => olal_process::cut_clip(source, &path, clip.start_time, clip.end_time)
+                .map_err(anyhow::Error::from),
+        };
+        let method = match cut {
+            Ok(method) => method,
+            Err(e) => {
+                println!("  {} {}: {}", "✗".red(), clip.title, e);
+                continue;
+            }
+        };
+        let path = path.canonicalize().unwrap_or(path);
+        record_clip(db, item, clip, &segments, &path, method, export.vertical.is_some())?;
+        exported += 1;
+
+        let note = match (export.vertical, method) {
+            (Some(_), _) => " (vertical)".dimmed().to_string(),
+            (None, ClipMethod::Copy) => String::new(),
+            (None, ClipMethod::Reencode) => " (re-encoded)".dimmed().to_string(),
+        };
+        println!("  {} {}{}", "✓".green(), path.display(), note);
+    }
 
+    println!();
+    println!(
+        "Exported {} of {} clips to {}",
+        exported.to_string().green(),
+        suggestions.len(),
+        dir.display()
+    );
     Ok(())
 }
 
+/// Render a 9:16 short with the clip's transcript burned in as captions.
+fn render_short(
+    item: &Item,
+    number: usize,
+    source: &Path,
+    path: &Path,
+    clip: &ClipSuggestion,
+    segments: &[TranscriptSegment],
+    fit: VerticalFit,
+) -> Result<()> {
+    let captions = olal_process::word_captions(segments, olal_process::WORDS_PER_CAPTION);
+
+    let srt = std::env::temp_dir().join(format!(
+        "olal-clip-{}-{}.srt",
+        item.id.chars().take(8).collect::<String>(),
+        number
+    ));
+    std::fs::write(&srt, olal_process::to_srt(&captions))
+        .context("Failed to write subtitles")?;
+    let subtitles = (!captions.is_empty()).then_some(srt.as_path());
+    let result = olal_process::render_vertical(
+        source,
+        path,
+        clip.start_time,
+        clip.end_time,
+        fit,
+        subtitles,
+    );
+    let _ = std::fs::remove_file(&srt);
+    Ok(result?)
+}
+
+/// The spoken words of transcript text, without speaker labels. On-screen
+/// text is skipped: a `[Screen]` line, or from `[Screen]` to the next label
+/// in chunks whose segments were joined on one line.
+fn spoken_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    for line in text.lines() {
+        let mut screen = false;
+        for word in line.split_whitespace() {
+            if word == "[Screen]" {
+                screen = true;
+            } else if word.starts_with('[') && word.ends_with(']') {
+                screen = false;
+            } else if !screen {
+                words.push(word);
+            }
+        }
+    }
+    words
+}
+
+/// How many words at the start of a chunk repeat the end of the chunk
+/// before it (the chunker's overlap, whose first word may be cut short).
+fn overlap_words(previous: &str, words: &[&str]) -> usize {
+    let previous = previous.split_whitespace().collect::<Vec<_>>().join(" ");
+    (1..=words.len())
+        .rev()
+        .find(|&k| previous.ends_with(&words[..k].join(" ")))
+        .unwrap_or(0)
+}
+
+/// The speech a clip covers, on the clip's own timeline.
+///
+/// Each chunk's words are spread evenly over its time span, after dropping
+/// the overlap it repeats from the previous chunk; only words inside the
+/// clip are kept.
+fn clip_segments(chunks: &[Chunk], clip: &ClipSuggestion) -> Vec<TranscriptSegment> {
+    let mut segments = Vec::new();
+    let mut previous: Option<&str> = None;
+    for chunk in chunks {
+        let (Some(start), Some(end)) = (chunk.start_time, chunk.end_time) else {
+            continue;
+        };
+        let raw: Vec<&str> = chunk.content.split_whitespace().collect();
+        let skip = previous.map_or(0, |p| overlap_words(p, &raw));
+        previous = Some(&chunk.content);
+        if end <= clip.start_time || start >= clip.end_time {
+            continue;
+        }
+
+        // Drop the overlap from the text itself, keeping its lines
+        let mut seen = 0;
+        let text: String = chunk
+            .content
+            .lines()
+            .map(|line| {
+                let words: Vec<&str> = line
+                    .split_whitespace()
+                    .filter(|_| {
+                        seen += 1;
+                        seen > skip
+                    })
+                    .collect();
+                words.join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let words = spoken_words(&text);
+        if words.is_empty() || end <= start {
+            continue;
+        }
+
+        let per_word = (end - start) / words.len() as f64;
+        let inside: Vec<(usize, &str)> = words
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                let middle = start + (*i as f64 + 0.5) * per_word;
+                middle >= clip.start_time && middle < clip.end_time
+            })
+            .map(|(i, word)| (i, *word))
+            .collect();
+        let (Some(&(first, _)), Some(&(last, _))) = (inside.first(), inside.last()) else {
+            continue;
+        };
+        segments.push(TranscriptSegment {
+            text: inside.iter().map(|(_, word)| *word).collect::<Vec<_>>().join(" "),
+            start: (start + first as f64 * per_word - clip.start_time).max(0.0),
+            end: (start + (last + 1) as f64 * per_word).min(clip.end_time) - clip.start_time,
+            speaker: None,
+            words: Vec::new(),
+        });
+    }
+    segments
+}
+
+/// Add an exported clip as a child item of `item`.
+fn record_clip(
+    db: &Database,
+    item: &Item,
+    clip: &ClipSuggestion,
+    segments: &[TranscriptSegment],
+    path: &Path,
+    method: ClipMethod,
+    vertical: bool,
+) -> Result<()> {
+    let path_str = path.to_string_lossy().to_string();
+    // Exporting again over the same file replaces the earlier clip
+    if let Some(previous) = db.find_item_by_path(&path_str)? {
+        db.delete_item_cascade(&previous.id)?;
+    }
+
+    let mut clip_item = Item::new(item.item_type, &clip.title).with_source_path(&path_str);
+    clip_item.processed_at = Some(Utc::now());
+    clip_item.metadata = json!({
+        "clip_of": item.id,
+        "start": clip.start_time,
+        "end": clip.end_time,
+        "duration": clip.end_time - clip.start_time,
+        "reason": clip.reason,
+        "cut": method.as_str(),
+        "vertical": vertical,
+    });
+    db.create_item(&clip_item)?;
+
+    let clip_chunks: Vec<Chunk> = segments
+        .iter()
+        .enumerate()
+        .map(|(index, s)| {
+            Chunk::new(clip_item.id.clone(), index as i32, &s.text).with_timestamps(s.start, s.end)
+        })
+        .collect();
+    if !clip_chunks.is_empty() {
+        let content = clip_chunks
+            .iter()
+            .map(|c| c.content.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        db.set_item_content(&clip_item.id, &content)?;
+        db.create_chunks(&clip_chunks)?;
+    }
+
+    db.create_link(&Link::new(clip_item.id.clone(), item.id.clone(), LinkType::Child))?;
+    Ok(())
+}
+
+/// File name for the `number`th clip, from its title.
+fn clip_file_name(number: usize, title: &str, extension: &str) -> String {
+    let slug = title
+        .to_lowercase()
+        .replace(' ', "_")
+        .chars()
+        .filter(|c| c.is_alphanumeric() || *c == '_')
+        .take(30)
+        .collect::<String>();
+    format!("clip_{}_{}.{}", number, slug, extension)
+}
+
 /// Parse the AI response into clip suggestions.
 fn parse_clip_response(response: &str) -> Vec<ClipSuggestion> {
     let mut suggestions = Vec::new();
@@ -282,6 +546,49 @@ CLIP: bad format
         assert_eq!(clips[0].title, "The Shocking Truth");
     }
 
+    #[test]
+    fn test_clip_file_name() {
+        assert_eq!(
+            clip_file_name(2, "The Shocking Truth!", "mov"),
+            "clip_2_the_shocking_truth.mov"
+        );
+    }
+
+    #[test]
+    fn test_spoken_words() {
+        assert_eq!(spoken_words("[SPEAKER_00] So here's the trick"), vec!["So", "here's", "the", "trick"]);
+        assert_eq!(spoken_words("run it\n[Screen] cargo build\nand done"), vec!["run", "it", "and", "done"]);
+        assert_eq!(
+            spoken_words("[SPEAKER_00] look [Screen] cargo build [SPEAKER_01] nice"),
+            vec!["look", "nice"]
+        );
+    }
+
+    #[test]
+    fn test_clip_segments() {
+        let chunk = |index, text: &str, start, end| {
+            Chunk::new("item".to_string(), index, text).with_timestamps(start, end)
+        };
+        let chunks = vec![
+            chunk(0, "[Screen] fn main()\none two three four", 0.0, 4.0),
+            // Starts with the tail of the chunk before, cut mid-word
+            chunk(1, "ree four\nfive six seven eight", 4.0, 8.0),
+        ];
+        let clip = ClipSuggestion {
+            start_time: 2.0,
+            end_time: 6.0,
+            title: String::new(),
+            reason: String::new(),
+        };
+
+        let segments = clip_segments(&chunks, &clip);
+        assert_eq!(segments.len(), 2);
+        assert_eq!(segments[0].text, "three four");
+        assert_eq!((segments[0].start, segments[0].end), (0.0, 2.0));
+        assert_eq!(segments[1].text, "five six");
+        assert_eq!((segments[1].start, segments[1].end), (2.0, 4.0));
+    }
+
     #[test]
     fn test_format_time() {
         assert_eq!(format_time(0.0), "00:00");
@@ -0,0 +1,33 @@
+//! Compact command - compress existing chunk text and reclaim space.
+
+use super::{format_size, get_database, get_paths};
+use anyhow::Result;
+use colored::Colorize;
+use olal_db::Database;
+
+/// Compress plain-text chunks left over from before compression on write,
+/// then vacuum the database file.
+pub fn run() -> Result<()> {
+    let paths = get_paths()?;
+    let db = get_database()?;
+
+    println!("{}", "Compacting database...".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    let before = Database::file_size(&paths.database_file)?;
+    let saved = db.compress_all_chunks()?;
+    println!("  {} Compressed chunk text ({} saved)", "✓".green(), format_size(saved));
+
+    db.vacuum()?;
+    let after = Database::file_size(&paths.database_file)?;
+    println!("  {} Vacuumed", "✓".green());
+
+    println!();
+    println!(
+        "Database size: {} → {}",
+        format_size(before),
+        format_size(after).green()
+    );
+
+    Ok(())
+}
@@ -1,10 +1,16 @@
 //! Configuration commands.
 
-use super::get_paths;
+use super::{get_paths, open_database, open_in_editor, sync_tokenizer};
 use anyhow::{Context, Result};
-use olal_config::Config;
 use colored::Colorize;
-use std::process::Command;
+use olal_config::validate::{self, Problem};
+use olal_config::{Config, ConfigError, FtsTokenizer};
+use olal_ingest::scheduler;
+use olal_ollama::{ModelUse, OllamaClient};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::Path;
+use std::time::Duration;
+use tokio::runtime::Runtime;
 
 pub fn show() -> Result<()> {
     let paths = get_paths()?;
@@ -18,11 +24,68 @@ pub fn show() -> Result<()> {
 
     println!("{}", "Current Configuration".cyan().bold());
     println!("{}", "─".repeat(50));
-    println!("{}", contents);
+    println!("{}", mask_secrets(&contents));
+
+    // What the file doesn't show
+    let mut overrides: Vec<String> = std::env::vars()
+        .filter_map(|(var, value)| {
+            let key = olal_config::env_key(&var)?;
+            Some(format!("{}={}", var, if is_secret(&key) { MASK } else { &value }))
+        })
+        .collect();
+    overrides.sort();
+    if let Some(profile) = olal_config::active_profile() {
+        println!("{} [profile.{}]", "Profile:".cyan(), profile);
+    }
+    if !overrides.is_empty() {
+        println!("{}", "Overridden by the environment:".cyan());
+        for line in overrides {
+            println!("  {}", line);
+        }
+    }
 
     Ok(())
 }
 
+/// What `config show` prints in place of a secret.
+const MASK: &str = "********";
+
+/// Whether the value at a config key path is a credential: passwords,
+/// tokens, API keys and any request header. `context_tokens` isn't one.
+fn is_secret(path: &[String]) -> bool {
+    path.iter().any(|k| k == "headers")
+        || path.last().is_some_and(|k| {
+            k.split(['_', '-'])
+                .any(|word| matches!(word, "password" | "token" | "key" | "secret"))
+        })
+}
+
+/// The config file with the values of secret keys masked. Empty values and
+/// `keyring:` references give nothing away, so they're left as they are.
+fn mask_secrets(contents: &str) -> String {
+    let unquote = |s: &str| s.trim().trim_matches('"').to_lowercase();
+    let mut table: Vec<String> = Vec::new();
+    let mut lines = Vec::new();
+
+    for line in contents.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            let header = trimmed.split('#').next().unwrap_or("").trim().trim_matches(['[', ']']);
+            table = header.split('.').map(unquote).collect();
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| !trimmed.starts_with('#')) {
+            let mut path = table.clone();
+            path.push(unquote(key));
+            let value = value.trim();
+            if is_secret(&path) && value != "\"\"" && !value.starts_with("\"keyring:") {
+                lines.push(format!("{}= \"{}\"", key, MASK));
+                continue;
+            }
+        }
+        lines.push(line.to_string());
+    }
+    lines.join("\n")
+}
+
 pub fn edit() -> Result<()> {
     let paths = get_paths()?;
 
@@ -30,26 +93,7 @@ pub fn edit() -> Result<()> {
         anyhow::bail!("Config file not found. Run 'olal init' first.");
     }
 
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| {
-        if cfg!(target_os = "macos") {
-            "open -t".to_string()
-        } else {
-            "nano".to_string()
-        }
-    });
-
-    let parts: Vec<&str> = editor.split_whitespace().collect();
-    let (cmd, args) = parts.split_first().context("Invalid editor command")?;
-
-    let status = Command::new(cmd)
-        .args(args)
-        .arg(&paths.config_file)
-        .status()
-        .context("Failed to open editor")?;
-
-    if !status.success() {
-        anyhow::bail!("Editor exited with error");
-    }
+    open_in_editor(&paths.config_file)?;
 
     println!(
         "{} Configuration saved.",
@@ -79,7 +123,7 @@ pub fn add_watch(path: &str) -> Result<()> {
         .context("Failed to load config")?;
 
     // Check if already added
-    if config.watch.directories.contains(&path.to_string()) {
+    if config.watch_mut().directories.contains(&path.to_string()) {
         println!(
             "{} Directory already in watch list: {}",
             "Note:".yellow(),
@@ -101,6 +145,184 @@ pub fn add_watch(path: &str) -> Result<()> {
     Ok(())
 }
 
+/// Print one value of the effective configuration, for scripts: strings
+/// bare, anything else as TOML.
+pub fn get(key: &str) -> Result<()> {
+    let config = Config::load().context("Failed to load config")?;
+    let value = config
+        .get(key)
+        .ok_or_else(|| ConfigError::Invalid(format!("Unknown config key: {}", key)))?;
+
+    match value {
+        toml::Value::String(s) => println!("{}", s),
+        toml::Value::Table(table) => print!("{}", toml::to_string(&table)?),
+        value => println!("{}", value),
+    }
+    Ok(())
+}
+
+/// Timeout for reaching Ollama and the mail server during validation.
+const VALIDATE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Check the config file, reporting problems with their line.
+///
+/// Errors (bad syntax or types, unknown keys, missing watch directories,
+/// invalid settings) fail the command; unreachable hosts and missing models
+/// are warnings, since they may just not be running yet.
+pub fn validate() -> Result<()> {
+    let paths = get_paths()?;
+    if !paths.config_file.exists() {
+        anyhow::bail!("Config file not found. Run 'olal init' first.");
+    }
+    let text = std::fs::read_to_string(&paths.config_file).context("Failed to read config file")?;
+    let file = paths.config_file.display().to_string();
+
+    println!("{} {}", "Checking".cyan().bold(), file);
+    let (config, mut problems) = validate::check(&text);
+    if let Some(config) = &config {
+        problems.extend(check_settings(config, &text));
+    }
+    for problem in &problems {
+        let place = match problem.line {
+            Some(line) => format!("{}:{}:", file, line),
+            None => format!("{}:", file),
+        };
+        println!("  {} {} {}", "✗".red(), place.dimmed(), problem.message);
+    }
+
+    // Hosts are checked as they'll be used, with profile and env overrides
+    let mut warnings = 0;
+    if config.is_some() {
+        let effective = Config::load().context("Failed to load config")?;
+        for (ok, message) in check_hosts(&effective)? {
+            if ok {
+                println!("  {} {}", "✓".green(), message);
+            } else {
+                println!("  {} {}", "!".yellow(), message);
+                warnings += 1;
+            }
+        }
+    }
+
+    println!();
+    if !problems.is_empty() {
+        return Err(ConfigError::Invalid(format!(
+            "{} problem{} in {}",
+            problems.len(),
+            if problems.len() == 1 { "" } else { "s" },
+            file
+        ))
+        .into());
+    }
+    match warnings {
+        0 => println!("{} Configuration is valid", "✓".green()),
+        n => println!("{} Configuration is valid, with {} warning{}", "✓".green(), n, if n == 1 { "" } else { "s" }),
+    }
+    Ok(())
+}
+
+/// Settings that parse but can't work.
+fn check_settings(config: &Config, text: &str) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    let mut problem = |key: String, message: String| {
+        problems.push(Problem { line: validate::line_of(text, &key), message });
+    };
+
+    let mut watches = vec![("watch".to_string(), &config.watch)];
+    for (name, vault) in &config.vaults {
+        if let Some(watch) = &vault.watch {
+            watches.push((format!("vaults.{}.watch", name), watch));
+        }
+    }
+    for (prefix, watch) in watches {
+        for (i, dir) in watch.directories.iter().enumerate() {
+            if !Path::new(shellexpand::tilde(dir).as_ref()).is_dir() {
+                problem(
+                    format!("{}.directories[{}]", prefix, i),
+                    format!("Watch directory does not exist: {}", dir),
+                );
+            }
+        }
+    }
+
+    if olal_process::backend(&config.processing.whisper_backend).is_none() {
+        problem(
+            "processing.whisper_backend".to_string(),
+            format!(
+                "Unknown transcription backend '{}' (valid: {})",
+                config.processing.whisper_backend,
+                olal_process::BACKENDS.join(", ")
+            ),
+        );
+    }
+    for (i, job) in config.schedule.jobs.iter().enumerate() {
+        if let Err(e) = scheduler::validate(job) {
+            problem(format!("schedule.jobs[{}]", i), format!("Scheduled job '{}': {}", job.name(), e));
+        }
+    }
+    for (i, endpoint) in config.webhooks.endpoints.iter().enumerate() {
+        if !endpoint.url.starts_with("http://") && !endpoint.url.starts_with("https://") {
+            problem(
+                format!("webhooks.endpoints[{}].url", i),
+                format!("Webhook URL must start with http:// or https://: {}", endpoint.url),
+            );
+        }
+    }
+    problems
+}
+
+/// Whether Ollama (and its models) and the mail server can be reached.
+fn check_hosts(config: &Config) -> Result<Vec<(bool, String)>> {
+    let mut results = Vec::new();
+
+    let mut ollama = config.ollama.clone();
+    ollama.timeout_seconds = VALIDATE_TIMEOUT.as_secs();
+    let client = OllamaClient::from_config(&ollama)?;
+    let rt = Runtime::new().context("Failed to create async runtime")?;
+    match rt.block_on(client.list_models()) {
+        Ok(models) => {
+            results.push((true, format!("Ollama reachable at {}", ollama.host)));
+            let installed = |name: &str| {
+                models.iter().any(|m| m.name == name || m.name.starts_with(&format!("{}:", name)))
+            };
+            let models = [(&ollama.model, ModelUse::Generate), (&ollama.embedding_model, ModelUse::Embed)];
+            for (model, usage) in models {
+                if !installed(model) {
+                    results.push((false, format!("Model '{}' is not installed (ollama pull {})", model, model)));
+                    continue;
+                }
+                if let Ok(details) = rt.block_on(client.show_model(model)) {
+                    if !details.supports(usage) {
+                        let wanted = match usage {
+                            ModelUse::Generate => "a chat model but can't generate text",
+                            ModelUse::Embed => "an embedding model but can't generate embeddings",
+                        };
+                        results.push((false, format!("Model '{}' is set as {}", model, wanted)));
+                    }
+                }
+            }
+        }
+        Err(_) => results.push((false, format!("Ollama not reachable at {}", ollama.host))),
+    }
+
+    let smtp = &config.digest.smtp;
+    if smtp.is_configured() {
+        let address = format!("{}:{}", smtp.host, smtp.port);
+        let reachable = address
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .is_some_and(|addr| TcpStream::connect_timeout(&addr, VALIDATE_TIMEOUT).is_ok());
+        let message = if reachable {
+            format!("Mail server reachable at {}", address)
+        } else {
+            format!("Mail server not reachable at {}", address)
+        };
+        results.push((reachable, message));
+    }
+    Ok(results)
+}
+
 pub fn set(key: &str, value: &str) -> Result<()> {
     let paths = get_paths()?;
 
@@ -118,22 +340,88 @@ pub fn set(key: &str, value: &str) -> Result<()> {
             config.ollama.timeout_seconds = value.parse()
                 .context("Invalid timeout value")?;
         }
+        ["ollama", "rerank"] => {
+            config.ollama.rerank = value.parse()
+                .context("Invalid boolean value")?;
+        }
+        ["ollama", "rerank_model"] => config.ollama.rerank_model = value.to_string(),
+        ["ollama", "multi_query"] => {
+            config.ollama.multi_query = value.parse()
+                .context("Invalid boolean value")?;
+        }
+        ["ollama", "vision_model"] => config.ollama.vision_model = value.to_string(),
         ["processing", "whisper_model"] => config.processing.whisper_model = value.to_string(),
+        ["processing", "whisper_backend"] => {
+            if olal_process::backend(value).is_none() {
+                anyhow::bail!(
+                    "Unknown transcription backend '{}'. Valid values: {}",
+                    value,
+                    olal_process::BACKENDS.join(", ")
+                );
+            }
+            config.processing.whisper_backend = value.to_string();
+        }
+        ["processing", "transcription_language"] => {
+            config.processing.transcription_language = value.to_string()
+        }
+        ["processing", "diarize"] => {
+            config.processing.diarize = value.parse()
+                .context("Invalid boolean value")?;
+        }
         ["processing", "chunk_size"] => {
             config.processing.chunk_size = value.parse()
                 .context("Invalid chunk_size value")?;
         }
+        ["processing", "auto_embed"] => {
+            config.processing.auto_embed = value.parse()
+                .context("Invalid boolean value")?;
+        }
+        ["processing", "describe_images"] => {
+            config.processing.describe_images = value.parse()
+                .context("Invalid boolean value")?;
+        }
+        ["processing", "normalize_audio"] => {
+            config.processing.normalize_audio = value.parse()
+                .context("Invalid boolean value")?;
+        }
+        ["processing", "trim_silence"] => {
+            config.processing.trim_silence = value.parse()
+                .context("Invalid boolean value")?;
+        }
         ["processing", "max_concurrent_jobs"] => {
             config.processing.max_concurrent_jobs = value.parse()
                 .context("Invalid max_concurrent_jobs value")?;
         }
         ["youtube", "default_style"] => config.youtube.default_style = value.to_string(),
+        ["general", "record_usage"] => {
+            config.general.record_usage = value.parse()
+                .context("Invalid boolean value")?;
+        }
         ["ui", "color"] => {
             config.ui.color = value.parse()
                 .context("Invalid boolean value")?;
         }
         ["ui", "pager"] => config.ui.pager = value.to_string(),
         ["ui", "date_format"] => config.ui.date_format = value.to_string(),
+        ["ui", "notifications"] => {
+            config.ui.notifications = value.parse()
+                .context("Invalid boolean value")?;
+        }
+        ["search", "tokenizer"] => {
+            config.search.tokenizer = match value {
+                "unicode61" => FtsTokenizer::Unicode61,
+                "porter" => FtsTokenizer::Porter,
+                _ => anyhow::bail!("Unknown tokenizer '{}'. Valid values: unicode61, porter", value),
+            };
+        }
+        ["search", "typo_tolerance"] => {
+            config.search.typo_tolerance = value.parse()
+                .context("Invalid boolean value")?;
+        }
+        ["webhooks", "enabled"] => {
+            config.webhooks.enabled = value.parse()
+                .context("Invalid boolean value")?;
+        }
         _ => {
             anyhow::bail!("Unknown config key: {}", key);
         }
@@ -149,5 +437,51 @@ pub fn set(key: &str, value: &str) -> Result<()> {
         value
     );
 
+    // Reindex now rather than in the middle of some later command
+    if key == "search.tokenizer" && paths.is_initialized() {
+        sync_tokenizer(&open_database(&paths.database_file)?, config.search.tokenizer)?;
+    }
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mask_secrets() {
+        let contents = r#"[ollama]
+model = "llama3"
+api_key = ""
+# api_key = "example"
+
+[ollama.headers]
+"CF-Access-Client-Secret" = "abc123"
+"X-Team" = "keyring:team"
+
+[webhooks]
+token = "s3cret"  # rotate monthly"#;
+        assert_eq!(
+            mask_secrets(contents),
+            r#"[ollama]
+model = "llama3"
+api_key = ""
+# api_key = "example"
+
+[ollama.headers]
+"CF-Access-Client-Secret" = "********"
+"X-Team" = "keyring:team"
+
+[webhooks]
+token = "********""#
+        );
+
+        let path = |p: &[&str]| p.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(is_secret(&path(&["ollama", "api_key"])));
+        assert!(is_secret(&path(&["ollama", "headers", "authorization"])));
+        assert!(is_secret(&path(&["webhooks", "token"])));
+        assert!(!is_secret(&path(&["ollama", "model"])));
+        assert!(!is_secret(&path(&["ollama", "context_tokens"])));
+    }
+}
@@ -0,0 +1,382 @@
+//! Date arguments and date display.
+//!
+//! Every date argument (`--since`, `--until`) goes through [`parse_date`],
+//! which accepts ISO dates, locale-ordered numeric dates, month names and
+//! relative phrases like "yesterday", "3 days ago" or "last tuesday". Due
+//! dates go through [`parse_due`], which also takes a time of day ("friday
+//! 5pm") and bare weekdays. Timestamps are shown in local time with
+//! `ui.date_format`.
+
+use anyhow::Result;
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, Days, Local, Months, NaiveDate, NaiveTime, TimeZone, Timelike, Utc, Weekday};
+use olal_config::Config;
+use olal_core::Recurrence;
+use std::sync::OnceLock;
+
+const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// Parse a date argument, relative to today.
+pub fn parse_date(input: &str) -> Result<NaiveDate> {
+    parse_date_at(input, Local::now().date_naive(), month_first_locale()).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid date '{}'. Try YYYY-MM-DD, \"yesterday\", \"3 days ago\", \"last tuesday\" or \"March 5\".",
+            input
+        )
+    })
+}
+
+/// Parse a due date, optionally with a time ("friday 5pm", "tomorrow
+/// 09:30", "5pm"). Without a time, the task is due at the end of the day.
+pub fn parse_due(input: &str) -> Result<DateTime<Utc>> {
+    let (date, time) = parse_due_at(input, Local::now().date_naive(), month_first_locale())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid due date '{}'. Try YYYY-MM-DD, \"tomorrow\", \"friday 5pm\" or \"in 3 days 09:00\".",
+                input
+            )
+        })?;
+    Ok(match time {
+        Some(time) => local_to_utc(date, time),
+        None => end_of_day(date),
+    })
+}
+
+/// When a recurring task due at `due` is next due: the first occurrence
+/// after now, at the same local time of day.
+pub fn next_due(due: DateTime<Utc>, recurrence: Recurrence) -> DateTime<Utc> {
+    let local = due.with_timezone(&Local);
+    let time = local.time();
+    let mut date = local.date_naive();
+    let now = Utc::now();
+    loop {
+        date = recurrence.next_date(date);
+        let next = local_to_utc(date, time);
+        if next > now {
+            return next;
+        }
+    }
+}
+
+/// A due date for display: "today", "in 3 days", with the time unless
+/// it's the end of the day.
+pub fn format_due(dt: DateTime<Utc>) -> String {
+    let local = dt.with_timezone(&Local);
+    if local.hour() == 23 && local.minute() == 59 {
+        relative_day(dt)
+    } else {
+        format!("{} {}", relative_day(dt), local.format("%H:%M"))
+    }
+}
+
+/// Midnight at the start of `date`, local time.
+pub fn start_of_day(date: NaiveDate) -> DateTime<Utc> {
+    local_to_utc(date, NaiveTime::MIN)
+}
+
+/// The last second of `date`, local time.
+pub fn end_of_day(date: NaiveDate) -> DateTime<Utc> {
+    local_to_utc(date, NaiveTime::from_hms_opt(23, 59, 59).unwrap())
+}
+
+fn local_to_utc(date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+    let naive = date.and_time(time);
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|| naive.and_utc())
+}
+
+/// Format a timestamp in local time with `ui.date_format`.
+pub fn format_datetime(dt: DateTime<Utc>) -> String {
+    dt.with_timezone(&Local).format(date_format()).to_string()
+}
+
+/// "today", "tomorrow", "in 3 days", "2 days ago", relative to today.
+pub fn relative_day(dt: DateTime<Utc>) -> String {
+    let days = (dt.with_timezone(&Local).date_naive() - Local::now().date_naive()).num_days();
+    match days {
+        0 => "today".to_string(),
+        1 => "tomorrow".to_string(),
+        -1 => "yesterday".to_string(),
+        d if d > 1 => format!("in {} days", d),
+        d => format!("{} days ago", -d),
+    }
+}
+
+/// The configured display format, or the default if it's unset or invalid.
+fn date_format() -> &'static str {
+    static FORMAT: OnceLock<String> = OnceLock::new();
+    FORMAT.get_or_init(|| {
+        let format = Config::load()
+            .map(|c| c.ui.date_format)
+            .unwrap_or_else(|_| DEFAULT_FORMAT.to_string());
+        if StrftimeItems::new(&format).any(|item| matches!(item, Item::Error)) {
+            tracing::warn!("Invalid ui.date_format '{}', using {}", format, DEFAULT_FORMAT);
+            DEFAULT_FORMAT.to_string()
+        } else {
+            format
+        }
+    })
+}
+
+/// Whether numeric dates like 03/04/2024 are month-first in the user's
+/// locale (US English and a few others), judged from LC_ALL/LC_TIME/LANG.
+fn month_first_locale() -> bool {
+    let locale = ["LC_ALL", "LC_TIME", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    ["en_US", "en_PH", "en_CA", "es_US"]
+        .iter()
+        .any(|prefix| locale.starts_with(prefix))
+}
+
+fn parse_date_at(input: &str, today: NaiveDate, month_first: bool) -> Option<NaiveDate> {
+    let input = input.trim().to_lowercase().replace(',', "");
+    let words: Vec<&str> = input.split_whitespace().collect();
+
+    match words.as_slice() {
+        ["today"] | ["now"] => return Some(today),
+        ["yesterday"] => return today.checked_sub_days(Days::new(1)),
+        ["tomorrow"] => return today.checked_add_days(Days::new(1)),
+        ["last", "week"] => return shift(today, 1, "week", false),
+        ["last", "month"] => return shift(today, 1, "month", false),
+        ["last", "year"] => return shift(today, 1, "year", false),
+        ["next", "week"] => return shift(today, 1, "week", true),
+        ["next", "month"] => return shift(today, 1, "month", true),
+        ["last", day] => return weekday(day).map(|wd| previous_weekday(today, wd)),
+        ["next", day] => return weekday(day).map(|wd| next_weekday(today, wd)),
+        [n, unit, "ago"] => return shift(today, count(n)?, unit, false),
+        ["in", n, unit] => return shift(today, count(n)?, unit, true),
+        _ => {}
+    }
+
+    parse_absolute(&input, today, month_first)
+}
+
+fn parse_due_at(
+    input: &str,
+    today: NaiveDate,
+    month_first: bool,
+) -> Option<(NaiveDate, Option<NaiveTime>)> {
+    let input = input.trim().to_lowercase().replace(',', "");
+    let mut words: Vec<&str> = input.split_whitespace().collect();
+
+    // A trailing time: "5pm", "5 pm", "17:30", "noon", optionally after "at"
+    let mut time = None;
+    if let Some((last, rest)) = words.split_last() {
+        if matches!(*last, "am" | "pm") && !rest.is_empty() {
+            time = parse_time(&format!("{}{}", rest[rest.len() - 1], last));
+            if time.is_some() {
+                words.truncate(words.len() - 2);
+            }
+        } else {
+            time = parse_time(last);
+            if time.is_some() {
+                words.pop();
+            }
+        }
+    }
+    if time.is_some() && words.last() == Some(&"at") {
+        words.pop();
+    }
+
+    let date = match words.as_slice() {
+        [] if time.is_some() => today,
+        // A bare weekday is the next one, or today
+        [day] if weekday(day).is_some() => {
+            let wd = weekday(day)?;
+            if today.weekday() == wd {
+                today
+            } else {
+                next_weekday(today, wd)
+            }
+        }
+        _ => parse_date_at(&words.join(" "), today, month_first)?,
+    };
+    Some((date, time))
+}
+
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    if word == "noon" {
+        return NaiveTime::from_hms_opt(12, 0, 0);
+    }
+    let (clock, offset) = if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(0))
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(12))
+    } else {
+        (word, None)
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // A bare number is only a time with am/pm ("5" alone is ambiguous)
+        None if offset.is_some() => (clock.parse::<u32>().ok()?, 0),
+        None => return None,
+    };
+    let hour = match offset {
+        Some(offset) if (1..=12).contains(&hour) => hour % 12 + offset,
+        Some(_) => return None,
+        None => hour,
+    };
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+fn parse_absolute(input: &str, today: NaiveDate, month_first: bool) -> Option<NaiveDate> {
+    for format in ["%Y-%m-%d", "%Y/%m/%d", "%d %B %Y", "%d %b %Y", "%B %d %Y", "%b %d %Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(input, format) {
+            return Some(date);
+        }
+    }
+
+    // Month names without a year mean this year
+    let with_year = format!("{} {}", input, today.year());
+    for format in ["%d %B %Y", "%d %b %Y", "%B %d %Y", "%b %d %Y"] {
+        if let Ok(date) = NaiveDate::parse_from_str(&with_year, format) {
+            return Some(date);
+        }
+    }
+
+    // Numeric dates: dots are always day-first, slashes follow the locale
+    let (separator, day_first) = if input.contains('.') {
+        ('.', true)
+    } else if input.contains('/') {
+        ('/', !month_first)
+    } else {
+        return None;
+    };
+    let parts: Vec<u32> = input
+        .split(separator)
+        .map(|p| p.trim().parse().ok())
+        .collect::<Option<_>>()?;
+    let [a, b, year] = parts.as_slice() else {
+        return None;
+    };
+    let year = if *year < 100 { 2000 + *year } else { *year };
+    let (day, month) = if day_first { (*a, *b) } else { (*b, *a) };
+    NaiveDate::from_ymd_opt(year as i32, month, day)
+}
+
+fn count(word: &str) -> Option<u32> {
+    match word {
+        "a" | "an" | "one" => Some(1),
+        "two" => Some(2),
+        "three" => Some(3),
+        _ => word.parse().ok(),
+    }
+}
+
+fn shift(today: NaiveDate, n: u32, unit: &str, forward: bool) -> Option<NaiveDate> {
+    let unit = unit.trim_end_matches('s');
+    let (days, months) = match unit {
+        "day" => (n as u64, 0),
+        "week" => (n as u64 * 7, 0),
+        "month" => (0, n),
+        "year" => (0, n * 12),
+        _ => return None,
+    };
+    if forward {
+        today
+            .checked_add_days(Days::new(days))?
+            .checked_add_months(Months::new(months))
+    } else {
+        today
+            .checked_sub_days(Days::new(days))?
+            .checked_sub_months(Months::new(months))
+    }
+}
+
+fn weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// The most recent `weekday` strictly before `today`.
+fn previous_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let back = (today.weekday().num_days_from_monday() + 7 - weekday.num_days_from_monday()) % 7;
+    today - Days::new(if back == 0 { 7 } else { back as u64 })
+}
+
+/// The next `weekday` strictly after `today`.
+fn next_weekday(today: NaiveDate, weekday: Weekday) -> NaiveDate {
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today + Days::new(if ahead == 0 { 7 } else { ahead as u64 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_relative_dates() {
+        // A Thursday
+        let today = date(2024, 3, 14);
+        let parse = |s| parse_date_at(s, today, false);
+
+        assert_eq!(parse("today"), Some(today));
+        assert_eq!(parse("Yesterday"), Some(date(2024, 3, 13)));
+        assert_eq!(parse("3 days ago"), Some(date(2024, 3, 11)));
+        assert_eq!(parse("a week ago"), Some(date(2024, 3, 7)));
+        assert_eq!(parse("2 months ago"), Some(date(2024, 1, 14)));
+        assert_eq!(parse("in 2 weeks"), Some(date(2024, 3, 28)));
+        assert_eq!(parse("last tuesday"), Some(date(2024, 3, 12)));
+        assert_eq!(parse("last thursday"), Some(date(2024, 3, 7)));
+        assert_eq!(parse("next monday"), Some(date(2024, 3, 18)));
+        assert_eq!(parse("next week"), Some(date(2024, 3, 21)));
+        assert_eq!(parse("someday"), None);
+    }
+
+    #[test]
+    fn test_absolute_dates() {
+        let today = date(2024, 3, 14);
+
+        assert_eq!(parse_date_at("2024-03-01", today, false), Some(date(2024, 3, 1)));
+        assert_eq!(parse_date_at("March 5, 2023", today, false), Some(date(2023, 3, 5)));
+        assert_eq!(parse_date_at("5 mar", today, false), Some(date(2024, 3, 5)));
+        assert_eq!(parse_date_at("05.04.2024", today, true), Some(date(2024, 4, 5)));
+        // Slashes follow the locale
+        assert_eq!(parse_date_at("04/05/2024", today, true), Some(date(2024, 4, 5)));
+        assert_eq!(parse_date_at("04/05/2024", today, false), Some(date(2024, 5, 4)));
+        assert_eq!(parse_date_at("31/02/2024", today, false), None);
+    }
+
+    #[test]
+    fn test_due_dates() {
+        // A Thursday
+        let today = date(2024, 3, 14);
+        let parse = |s| parse_due_at(s, today, false);
+        let at = |h, m| NaiveTime::from_hms_opt(h, m, 0);
+
+        assert_eq!(parse("friday 5pm"), Some((date(2024, 3, 15), at(17, 0))));
+        assert_eq!(parse("Thursday"), Some((today, None)));
+        assert_eq!(parse("tomorrow at 9:30 am"), Some((date(2024, 3, 15), at(9, 30))));
+        assert_eq!(parse("12am"), Some((today, at(0, 0))));
+        assert_eq!(parse("2024-04-01 17:45"), Some((date(2024, 4, 1), at(17, 45))));
+        assert_eq!(parse("in 3 days noon"), Some((date(2024, 3, 17), at(12, 0))));
+        assert_eq!(parse("next week"), Some((date(2024, 3, 21), None)));
+        assert_eq!(parse("friday 13pm"), None);
+    }
+
+    #[test]
+    fn test_day_bounds() {
+        let day = date(2024, 3, 1);
+        assert!(end_of_day(day) > start_of_day(day));
+        assert!(end_of_day(day) < start_of_day(date(2024, 3, 2)));
+    }
+}
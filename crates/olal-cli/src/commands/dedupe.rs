@@ -0,0 +1,136 @@
+//! Dedupe command - find items with nearly the same text and merge or link
+//! them.
+
+use super::{dates, get_database, get_paths};
+use anyhow::Result;
+use colored::Colorize;
+use olal_core::{Item, Link, LinkType};
+use olal_db::Database;
+use olal_ingest::maintenance::is_report;
+use std::io::{self, Write};
+
+/// What to do with each cluster of near-duplicates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolution {
+    /// Ask for every cluster.
+    Ask,
+    Merge,
+    Link,
+}
+
+/// List near-duplicate clusters (fingerprints at most `distance` bits
+/// apart) and resolve them. Merging keeps each cluster's oldest item.
+pub fn run(distance: u32, resolution: Resolution) -> Result<()> {
+    let db = get_database()?;
+
+    println!("{}", "Near-duplicates".cyan().bold());
+    println!("{}", "─".repeat(70));
+
+    let clusters: Vec<Vec<Item>> = db
+        .find_near_duplicates(distance)?
+        .into_iter()
+        .map(|cluster| cluster.into_iter().filter(|item| !is_report(item)).collect::<Vec<_>>())
+        .filter(|cluster| cluster.len() > 1)
+        .collect();
+
+    if clusters.is_empty() {
+        println!("{}", "No near-duplicates found.".dimmed());
+        return Ok(());
+    }
+
+    let mut merged = 0;
+    let mut linked = 0;
+    for (n, cluster) in clusters.iter().enumerate() {
+        println!();
+        println!("{}", format!("Cluster {} of {}", n + 1, clusters.len()).bold());
+        for (i, item) in cluster.iter().enumerate() {
+            println!(
+                "  {}. {} {} {}{}",
+                i + 1,
+                item.title.white().bold(),
+                format!("[{}]", item.id.chars().take(8).collect::<String>()).dimmed(),
+                dates::format_datetime(item.created_at).dimmed(),
+                if i == 0 { " (kept on merge)".dimmed().to_string() } else { String::new() }
+            );
+            if let Some(ref path) = item.source_path {
+                println!("     {}", path.dimmed());
+            }
+        }
+
+        let action = match resolution {
+            Resolution::Merge => Resolution::Merge,
+            Resolution::Link => Resolution::Link,
+            Resolution::Ask => {
+                let Some(answer) = prompt("  [m]erge, [l]ink, [s]kip, [q]uit? ")? else {
+                    break;
+                };
+                match answer.as_str() {
+                    "m" | "merge" => Resolution::Merge,
+                    "l" | "link" => Resolution::Link,
+                    "q" | "quit" => break,
+                    _ => continue,
+                }
+            }
+        };
+
+        match action {
+            Resolution::Merge => {
+                merged += merge(&db, cluster)?;
+                println!("  {} Merged into {}", "✓".green(), cluster[0].title);
+            }
+            Resolution::Link => {
+                linked += link(&db, cluster)?;
+                println!("  {} Linked as duplicates", "✓".green());
+            }
+            Resolution::Ask => {}
+        }
+    }
+
+    println!();
+    println!(
+        "{} Merged {} item{}, made {} duplicate link{}",
+        "✓".green(),
+        merged,
+        if merged == 1 { "" } else { "s" },
+        linked,
+        if linked == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Merge every later item of a cluster into its first. Returns how many
+/// were merged.
+fn merge(db: &Database, cluster: &[Item]) -> Result<usize> {
+    let paths = get_paths()?;
+    let (keep, duplicates) = cluster.split_first().expect("clusters have two items");
+    for duplicate in duplicates {
+        db.merge_items(&keep.id, &duplicate.id)?;
+        paths.remove_thumbnail(&duplicate.id)?;
+    }
+    Ok(duplicates.len())
+}
+
+/// Link every pair of a cluster's items, newer to older, so the cluster
+/// isn't reported again. Returns how many links were made.
+fn link(db: &Database, cluster: &[Item]) -> Result<usize> {
+    let mut links = 0;
+    for (i, newer) in cluster.iter().enumerate() {
+        for older in &cluster[..i] {
+            db.create_link(&Link::new(newer.id.clone(), older.id.clone(), LinkType::Duplicates))?;
+            links += 1;
+        }
+    }
+    Ok(links)
+}
+
+/// Read one trimmed, lowercased line; `None` at end of input.
+fn prompt(message: &str) -> Result<Option<String>> {
+    print!("{}", message);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_lowercase()))
+}
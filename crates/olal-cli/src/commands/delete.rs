@@ -0,0 +1,85 @@
+//! Delete command - remove an item and everything derived from it.
+
+use super::{get_database, get_paths, resolve};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_db::DeletionSummary;
+
+/// Run the delete command.
+pub fn run(id: &str, dry_run: bool, artifacts: bool) -> Result<()> {
+    let db = get_database()?;
+    let paths = get_paths()?;
+
+    let item = resolve::item(&db, id)?;
+    let artifacts_dir = paths.item_artifacts_dir(&item.id);
+    let has_artifacts = artifacts && artifacts_dir.exists();
+    // Thumbnails are only a preview, so they always go with the item
+    let thumbnail = Some(paths.thumbnail_file(&item.id)).filter(|t| t.exists());
+
+    if dry_run {
+        let preview = db.item_deletion_preview(&item.id)?;
+
+        println!(
+            "{} {} [{}]",
+            "Would delete:".yellow().bold(),
+            item.title.white(),
+            item.id.chars().take(8).collect::<String>()
+        );
+        println!("{}", "─".repeat(50));
+        print_summary(&preview);
+        if has_artifacts {
+            println!("  {} {}", "Artifacts:".cyan(), artifacts_dir.display());
+        }
+        if let Some(ref thumbnail) = thumbnail {
+            println!("  {} {}", "Thumbnail:".cyan(), thumbnail.display());
+        }
+        println!();
+        println!("{}", "Dry run - nothing was deleted.".dimmed());
+        return Ok(());
+    }
+
+    let summary = db
+        .delete_item_cascade(&item.id)
+        .context("Failed to delete item")?;
+
+    if has_artifacts {
+        std::fs::remove_dir_all(&artifacts_dir).with_context(|| {
+            format!("Failed to remove artifacts at {}", artifacts_dir.display())
+        })?;
+    }
+    paths.remove_thumbnail(&item.id).with_context(|| {
+        format!("Failed to remove thumbnail at {}", paths.thumbnail_file(&item.id).display())
+    })?;
+
+    println!(
+        "{} Deleted '{}' [{}]",
+        "✓".green(),
+        item.title.white(),
+        item.id.chars().take(8).collect::<String>()
+    );
+    print_summary(&summary);
+    if has_artifacts {
+        println!("  {} {}", "Artifacts:".cyan(), artifacts_dir.display());
+    }
+    if let Some(ref thumbnail) = thumbnail {
+        println!("  {} {}", "Thumbnail:".cyan(), thumbnail.display());
+    }
+
+    if let Some(ref path) = item.source_path {
+        println!();
+        println!(
+            "{}",
+            format!("Source file left untouched: {}", path).dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+fn print_summary(summary: &DeletionSummary) {
+    println!("  {} {}", "Chunks:".cyan(), summary.chunks);
+    println!("  {} {}", "Embeddings:".cyan(), summary.embeddings);
+    println!("  {} {}", "Tags:".cyan(), summary.tags);
+    println!("  {} {}", "Links:".cyan(), summary.links);
+    println!("  {} {}", "Entities:".cyan(), summary.entities);
+}
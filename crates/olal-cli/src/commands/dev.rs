@@ -0,0 +1,336 @@
+//! Developer commands (built with `--features dev`).
+//!
+//! `olal dev seed` fills a knowledge base with a synthetic corpus - notes,
+//! timestamped video transcripts, tags, projects and tasks - for
+//! benchmarking search, the TUI and the server without private data. The
+//! same seed always produces the same content and IDs; dates are spread
+//! over the 90 days before the run.
+
+use super::get_database;
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use colored::Colorize;
+use olal_core::{Chunk, Item, ItemType, Project, Task, TaskStatus};
+use olal_db::Database;
+use olal_ingest::{ChunkConfig, Chunker};
+
+/// Items are written in batches of this many.
+const BATCH_SIZE: usize = 100;
+
+/// How far back seeded items are spread.
+const SPREAD_DAYS: i64 = 90;
+
+/// Topics: (name, tag, vocabulary).
+const TOPICS: &[(&str, &str, &[&str])] = &[
+    ("Rust", "rust", &["ownership", "borrowing", "lifetimes", "traits", "async", "cargo", "macros", "the borrow checker", "iterators", "error handling"]),
+    ("Cooking", "cooking", &["sourdough", "the starter", "braising", "knife skills", "stock", "fermentation", "cast iron", "seasoning", "the dough", "mise en place"]),
+    ("Fitness", "fitness", &["squats", "progressive overload", "recovery", "zone two cardio", "mobility", "protein intake", "deadlifts", "sleep", "the warm-up", "intervals"]),
+    ("Finance", "finance", &["index funds", "the budget", "compound interest", "tax brackets", "the emergency fund", "rebalancing", "expense ratios", "cash flow", "retirement accounts", "inflation"]),
+    ("Photography", "photography", &["aperture", "shutter speed", "golden hour", "composition", "the histogram", "prime lenses", "white balance", "long exposure", "color grading", "depth of field"]),
+    ("Gardening", "gardening", &["compost", "raised beds", "tomatoes", "companion planting", "mulch", "soil pH", "seedlings", "crop rotation", "drip irrigation", "pruning"]),
+    ("Machine Learning", "machine-learning", &["embeddings", "gradient descent", "overfitting", "the validation set", "transformers", "fine-tuning", "tokenization", "regularization", "attention", "the loss curve"]),
+    ("Travel", "travel", &["carry-on packing", "jet lag", "rail passes", "the itinerary", "travel insurance", "local markets", "layovers", "hostels", "phrasebooks", "day trips"]),
+];
+
+/// Tags added to some items on top of their topic tag.
+const EXTRA_TAGS: &[&str] = &["reference", "idea", "todo", "favorite", "to-review", "howto"];
+
+const NOTE_TITLES: &[&str] = &["Notes on {a} and {b}", "{A}: what I learned", "Thoughts about {a}", "{A} vs {b}", "Checklist for {a}"];
+const VIDEO_TITLES: &[&str] = &["{T} walkthrough: {a}", "Getting started with {a}", "{T} Q&A - {a} and {b}", "Deep dive into {a}"];
+const TASK_TITLES: &[&str] = &["Read more about {a}", "Try {a} this week", "Write up notes on {b}", "Compare {a} and {b}", "Review {a}"];
+
+const SENTENCES: &[&str] = &[
+    "{A} matters more than most people expect, especially alongside {b}.",
+    "The key idea is that {a} and {b} work together rather than against each other.",
+    "I keep coming back to {a} because it changes how I think about {b}.",
+    "A common mistake is to ignore {a} until {b} starts causing problems.",
+    "Start small with {a}, then add {b} once the basics feel natural.",
+    "{A} is easiest to understand with a concrete example involving {b}.",
+    "Next time, measure {a} before and after adjusting {b}.",
+    "Most advice about {a} skips over how it interacts with {b}.",
+];
+
+const SPOKEN: &[&str] = &[
+    "okay so today we're looking at {a}",
+    "the thing about {a} is that it connects to {b}",
+    "let me show you what happens with {a} here",
+    "if you remember from last time we talked about {b}",
+    "a lot of you asked about {a} in the comments",
+    "so that's basically {a}, and next up is {b}",
+    "you can see {a} makes a real difference",
+    "don't worry too much about {b} at this point",
+];
+
+/// Small deterministic PRNG (SplitMix64), so a seed gives the same corpus
+/// on every platform and version.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number in `range` (start inclusive, end exclusive).
+    fn range(&mut self, range: std::ops::Range<usize>) -> usize {
+        range.start + (self.next_u64() % (range.end - range.start) as u64) as usize
+    }
+
+    fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.range(0..items.len())]
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.range(0..100) < percent
+    }
+
+    /// A UUID-formatted ID.
+    fn id(&mut self) -> String {
+        let (a, b) = (self.next_u64(), self.next_u64());
+        format!(
+            "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+            a >> 32,
+            (a >> 16) & 0xffff,
+            a & 0xfff,
+            0x8000 | (b >> 48) & 0x3fff,
+            b & 0xffff_ffff_ffff
+        )
+    }
+}
+
+/// What a seed run created.
+#[derive(Debug, Default, PartialEq)]
+struct SeedSummary {
+    notes: usize,
+    videos: usize,
+    chunks: usize,
+    tags: usize,
+    projects: usize,
+    tasks: usize,
+}
+
+/// Generate a synthetic corpus of `items` items.
+pub fn seed(items: usize, seed: u64, force: bool) -> Result<()> {
+    let db = get_database()?;
+
+    let existing = db.get_stats()?.total_items;
+    if existing > 0 && !force {
+        anyhow::bail!(
+            "The knowledge base already has {} items. Seed a throwaway database with --db, or pass --force.",
+            existing
+        );
+    }
+
+    println!("{} {} items (seed {})", "Seeding".cyan().bold(), items, seed);
+    println!("{}", "─".repeat(50));
+
+    let summary = seed_corpus(&db, items, seed, Utc::now())?;
+
+    println!("  {} {} notes, {} video transcripts", "✓".green(), summary.notes, summary.videos);
+    println!("  {} {} chunks", "✓".green(), summary.chunks);
+    println!("  {} {} tag assignments", "✓".green(), summary.tags);
+    println!("  {} {} projects, {} tasks", "✓".green(), summary.projects, summary.tasks);
+    println!();
+    println!("Embeddings are not generated; run {} if needed.", "olal embed --all".cyan());
+
+    Ok(())
+}
+
+fn seed_corpus(db: &Database, count: usize, seed: u64, now: DateTime<Utc>) -> Result<SeedSummary> {
+    let mut rng = Rng(seed);
+    let chunker = Chunker::new(ChunkConfig::default());
+    let mut summary = SeedSummary::default();
+
+    let mut projects = Vec::with_capacity(TOPICS.len());
+    for (name, _, _) in TOPICS {
+        let mut project =
+            Project::new(*name).with_description(format!("Synthetic {} project", name.to_lowercase()));
+        project.id = rng.id();
+        project.created_at = now - Duration::days(SPREAD_DAYS);
+        match db.get_project_by_name(name)? {
+            // Seeding again with --force
+            Some(existing) => projects.push(existing),
+            None => {
+                db.create_project(&project)?;
+                summary.projects += 1;
+                projects.push(project);
+            }
+        }
+    }
+
+    let mut batch = Vec::with_capacity(BATCH_SIZE);
+    let mut tags = Vec::new();
+    for i in 0..count {
+        let topic = rng.range(0..TOPICS.len());
+        let (name, tag, words) = TOPICS[topic];
+        let created_at = now - Duration::minutes(rng.range(0..(SPREAD_DAYS * 24 * 60) as usize) as i64);
+
+        let (item, chunks) = if rng.chance(25) {
+            summary.videos += 1;
+            video(&mut rng, &chunker, name, words, i)
+        } else {
+            summary.notes += 1;
+            note(&mut rng, &chunker, words)
+        };
+        let mut item = item;
+        item.created_at = created_at;
+        item.processed_at = Some(created_at);
+        summary.chunks += chunks.len();
+
+        tags.push((item.id.clone(), tag));
+        if rng.chance(30) {
+            tags.push((item.id.clone(), *rng.pick(EXTRA_TAGS)));
+        }
+
+        if rng.chance(20) {
+            let mut task = Task::new(fill(rng.pick::<&str>(TASK_TITLES), &mut rng, name, words));
+            task.id = rng.id();
+            task.project_id = Some(projects[topic].id.clone());
+            task.priority = rng.range(0..4) as i32;
+            task.created_at = created_at;
+            task.status = *rng.pick(&[TaskStatus::Pending, TaskStatus::Pending, TaskStatus::InProgress, TaskStatus::Done]);
+            if rng.chance(50) {
+                task.due_date = Some(created_at + Duration::days(rng.range(1..30) as i64));
+            }
+            if task.status == TaskStatus::Done {
+                task.completed_at = Some(created_at + Duration::days(1));
+            }
+            db.create_task(&task)?;
+            summary.tasks += 1;
+        }
+
+        batch.push((item, chunks));
+        if batch.len() == BATCH_SIZE {
+            write_batch(db, &mut batch)?;
+        }
+    }
+    write_batch(db, &mut batch)?;
+
+    for (item_id, tag) in &tags {
+        db.tag_item(item_id, tag)?;
+    }
+    summary.tags = tags.len();
+
+    Ok(summary)
+}
+
+fn write_batch(db: &Database, batch: &mut Vec<(Item, Vec<Chunk>)>) -> Result<()> {
+    db.create_items_with_chunks(batch)?;
+    for (item, chunks) in batch.iter() {
+        if item.item_type == ItemType::Note {
+            let content: Vec<&str> = chunks.iter().map(|c| c.content.as_str()).collect();
+            db.set_item_content(&item.id, &content.join("\n\n"))?;
+        }
+    }
+    batch.clear();
+    Ok(())
+}
+
+fn note(rng: &mut Rng, chunker: &Chunker, words: &[&str]) -> (Item, Vec<Chunk>) {
+    let title = fill(rng.pick::<&str>(NOTE_TITLES), rng, "", words);
+    let paragraphs: Vec<String> = (0..rng.range(2..7))
+        .map(|_| {
+            (0..rng.range(3..7))
+                .map(|_| fill(rng.pick::<&str>(SENTENCES), rng, "", words))
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect();
+    let text = format!("# {}\n\n{}", title, paragraphs.join("\n\n"));
+
+    let mut item = Item::new(ItemType::Note, title);
+    item.id = rng.id();
+    item.metadata = serde_json::json!({ "source": "seed" });
+    let chunks = with_ids(rng, chunker.chunk_text(&item.id, &text));
+    (item, chunks)
+}
+
+fn video(rng: &mut Rng, chunker: &Chunker, topic: &str, words: &[&str], n: usize) -> (Item, Vec<Chunk>) {
+    let title = fill(rng.pick::<&str>(VIDEO_TITLES), rng, topic, words);
+    let mut item = Item::new(ItemType::Video, title);
+    item.id = rng.id();
+    item.source_path = Some(format!("/seed/videos/{:05}.mp4", n));
+
+    let mut segments = Vec::new();
+    let mut time = 0.0;
+    for _ in 0..rng.range(20..80) {
+        let length = rng.range(3..12) as f64;
+        segments.push((fill(rng.pick::<&str>(SPOKEN), rng, topic, words), time, time + length));
+        time += length;
+    }
+    item.metadata = serde_json::json!({ "source": "seed", "duration": time });
+
+    let chunks = with_ids(rng, chunker.chunk_transcript(&item.id, &segments));
+    (item, chunks)
+}
+
+/// Give chunks seeded IDs instead of random ones.
+fn with_ids(rng: &mut Rng, mut chunks: Vec<Chunk>) -> Vec<Chunk> {
+    for chunk in &mut chunks {
+        chunk.id = rng.id();
+    }
+    chunks
+}
+
+/// Fill a template: `{a}`/`{b}` are vocabulary words (`{A}` capitalized),
+/// `{T}` is the topic name.
+fn fill(template: &str, rng: &mut Rng, topic: &str, words: &[&str]) -> String {
+    let a = *rng.pick(words);
+    let mut b = *rng.pick(words);
+    if b == a {
+        b = words[(words.iter().position(|w| *w == a).unwrap_or(0) + 1) % words.len()];
+    }
+    template
+        .replace("{A}", &capitalize(a))
+        .replace("{a}", a)
+        .replace("{b}", b)
+        .replace("{T}", topic)
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seed_is_deterministic() {
+        let now = Utc::now();
+        let first = Database::open_in_memory().unwrap();
+        let second = Database::open_in_memory().unwrap();
+
+        let summary = seed_corpus(&first, 60, 7, now).unwrap();
+        assert_eq!(seed_corpus(&second, 60, 7, now).unwrap(), summary);
+        assert_eq!(summary.notes + summary.videos, 60);
+        assert!(summary.chunks >= 60);
+
+        let ids = |db: &Database| -> Vec<(String, String)> {
+            let mut items: Vec<_> = db
+                .list_items(None, None)
+                .unwrap()
+                .into_iter()
+                .map(|i| (i.id, i.title))
+                .collect();
+            items.sort();
+            items
+        };
+        assert_eq!(ids(&first), ids(&second));
+
+        // Seeded content is searchable, and transcripts keep timestamps
+        assert!(!first.search_items("ownership OR sourdough OR squats", None).unwrap().is_empty());
+        let video = first.list_items(Some(ItemType::Video), Some(1)).unwrap();
+        if let Some(video) = video.first() {
+            let chunks = first.get_chunks_by_item(&video.id).unwrap();
+            assert!(chunks.iter().all(|c| c.start_time.is_some()));
+        }
+    }
+}
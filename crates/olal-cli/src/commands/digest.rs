@@ -1,10 +1,20 @@
 //! Digest command - Generate AI summaries of content ingested over a time period.
+//!
+//! Every digest is stored with a few numbers about its period (items, items
+//! per topic, task activity), so `--compare` can contrast it with the one
+//! before.
 
-use super::get_database;
+use super::{dates, get_database, output};
 use anyhow::{Context, Result};
 use olal_config::Config;
-use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient};
-use chrono::{Duration, NaiveDate, Utc};
+use olal_core::{DigestPreference, DigestRecord, DigestStats, FeedbackTarget, Item};
+use olal_db::Database;
+use olal_ingest::Event;
+use olal_ollama::budget::truncate_to_tokens;
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{ContextBudget, GenerateRequest, OllamaClient, OllamaError};
+use serde_json::json;
+use chrono::{DateTime, Duration, Utc};
 use colored::Colorize;
 use std::collections::HashMap;
 use std::fs;
@@ -22,6 +32,7 @@ pub enum DigestPeriod {
 
 impl DigestPeriod {
     /// Parse from string.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "day" | "daily" => Some(Self::Day),
@@ -50,30 +61,46 @@ impl DigestPeriod {
     }
 }
 
+/// Maximum topics and sources offered for feedback after a digest.
+const FEEDBACK_SECTIONS: usize = 5;
+
+/// Length of the excerpt of each item's first chunk, in tokens.
+const EXCERPT_TOKENS: usize = 75;
+
 /// Run the digest command.
+///
+/// With `feedback`, asks for a thumbs-up or down on the topics and sources
+/// the digest covered after showing it. Stored feedback shapes every digest:
+/// items from ignored sources are left out, preferred topics come first, and
+/// the prompt tells the model what to emphasize.
+///
+/// With `compare`, a section contrasting the period with the previous one
+/// is added. With `email`, the digest is also sent to the `[digest.smtp]`
+/// recipients.
 pub fn run(
     period: &str,
     since: Option<String>,
     output: Option<PathBuf>,
     model: Option<String>,
+    feedback: bool,
+    compare: bool,
+    email: bool,
 ) -> Result<()> {
     let db = get_database()?;
     let config = Config::load().context("Failed to load configuration")?;
 
     // Determine start date
     let start_date = if let Some(ref date_str) = since {
-        // Parse custom date
-        let parsed = NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-            .context("Invalid date format. Use YYYY-MM-DD.")?;
-        parsed.and_hms_opt(0, 0, 0).unwrap().and_utc()
+        dates::start_of_day(dates::parse_date(date_str)?)
     } else {
         // Use period
         let digest_period = DigestPeriod::from_str(period).unwrap_or(DigestPeriod::Day);
         Utc::now() - digest_period.duration()
     };
+    let end_date = Utc::now();
 
-    let period_desc = if since.is_some() {
-        format!("since {}", since.as_ref().unwrap())
+    let period_desc = if let Some(ref date_str) = since {
+        format!("since {}", date_str)
     } else {
         DigestPeriod::from_str(period)
             .unwrap_or(DigestPeriod::Day)
@@ -87,9 +114,9 @@ pub fn run(
         format!("{} digest", period_desc).white()
     );
     println!(
-        "{} {}",
+        "{} {} to now",
         "Period:".cyan(),
-        format!("{} to now", start_date.format("%Y-%m-%d %H:%M UTC"))
+        dates::format_datetime(start_date)
     );
     println!("{}", "─".repeat(70));
     println!();
@@ -99,6 +126,19 @@ pub fn run(
         .items_since(start_date)
         .context("Failed to query items")?;
 
+    // Apply feedback from earlier digests
+    let preferences = db.digest_preferences()?;
+    let item_tags = tags_by_item(&db)?;
+    let total_items = items.len();
+    let items = apply_preferences(items, &item_tags, &preferences);
+    if items.len() < total_items {
+        println!(
+            "{} {} items from ignored sources",
+            "Skipped:".cyan(),
+            total_items - items.len()
+        );
+    }
+
     if items.is_empty() {
         println!(
             "{} No items found for this time period.",
@@ -151,11 +191,7 @@ pub fn run(
         // Get first chunk for excerpt
         if let Ok(chunks) = db.get_chunks_by_item(&item.id) {
             if let Some(first_chunk) = chunks.first() {
-                let excerpt = if first_chunk.content.len() > 300 {
-                    format!("{}...", &first_chunk.content[..300])
-                } else {
-                    first_chunk.content.clone()
-                };
+                let excerpt = truncate_to_tokens(&first_chunk.content, EXCERPT_TOKENS);
                 item_content.push_str(&format!("Excerpt: {}\n", excerpt));
             }
         }
@@ -165,17 +201,6 @@ pub fn run(
 
     let combined_content = content_parts.join("\n---\n\n");
 
-    // Truncate if too long
-    let combined_content = if combined_content.len() > 12000 {
-        format!(
-            "{}...\n[Content truncated - {} items total]",
-            &combined_content[..12000],
-            items.len()
-        )
-    } else {
-        combined_content
-    };
-
     // Create Ollama client
     let client = OllamaClient::from_config(&config.ollama)
         .context("Failed to create Ollama client")?;
@@ -186,27 +211,51 @@ pub fn run(
     // Check if Ollama is available
     let is_available = rt.block_on(client.is_available());
     if !is_available {
-        anyhow::bail!(
-            "Ollama is not running at {}. Start it with 'ollama serve'.",
-            config.ollama.host
-        );
+        return Err(OllamaError::ServerNotRunning {
+            host: config.ollama.host.clone(),
+        }
+        .into());
     }
 
     let model_name = model.as_deref().unwrap_or(&config.ollama.model);
+    let budget = rt.block_on(client.context_budget(model_name, config.ollama.context_tokens));
+
+    // Keep as much content as fits the model's context
+    let preference_text = preference_prompt(&preferences);
+    let empty = digest_prompt("", &period_desc, &preference_text)?;
+    let combined_content = match budget.truncate(&empty, &combined_content) {
+        Some(cut) => format!("{}\n[Content truncated - {} items total]", cut, items.len()),
+        None => combined_content,
+    };
 
     // Generate digest
     print!("{}", "Generating digest...".dimmed());
     io::stdout().flush()?;
 
-    let digest = generate_digest(&rt, &client, model_name, &combined_content, &period_desc)?;
+    let prompt = digest_prompt(&combined_content, &period_desc, &preference_text)?;
+    let digest = generate_digest(&rt, &client, model_name, &budget, &prompt)?;
 
     println!("\r{}", " ".repeat(30));
     println!();
 
+    let stats = period_stats(&db, &items, &item_tags, start_date, end_date)?;
+    let comparison = if compare {
+        let (previous, source) = previous_stats(&db, &period_desc, start_date, end_date, &item_tags)?;
+        Some(format_comparison(&stats, &previous, &source))
+    } else {
+        None
+    };
+    db.save_digest(&DigestRecord::new(&period_desc, start_date, end_date, &digest, stats))
+        .context("Failed to store digest")?;
+    let digest = match comparison {
+        Some(comparison) => format!("{}\n\n{}", digest.trim_end(), comparison),
+        None => digest,
+    };
+
     // Output
+    let markdown = format_digest_markdown(&digest, &period_desc, items.len());
     if let Some(ref output_path) = output {
         // Write to file
-        let markdown = format_digest_markdown(&digest, &period_desc, items.len());
         fs::write(output_path, &markdown).context("Failed to write output file")?;
         println!(
             "{} {}",
@@ -214,42 +263,333 @@ pub fn run(
             output_path.display()
         );
     } else {
-        // Display to stdout
-        println!("{}", "Digest:".green().bold());
-        println!();
-        println!("{}", digest);
+        // Display to stdout, through the pager
+        output::page(&format!("{}\n\n{}\n", "Digest:".green().bold(), digest))?;
+    }
+
+    if email {
+        let subject = format!("{} digest", capitalize(&period_desc));
+        super::email::send_markdown(&config.digest.smtp, &subject, &markdown)?;
+        println!(
+            "{} {}",
+            "Emailed to:".green().bold(),
+            config.digest.smtp.to.join(", ")
+        );
+    }
+
+    super::event_emitter(&config).emit(Event::DigestGenerated {
+        period: period_desc.clone(),
+        item_count: items.len(),
+        output: output.as_ref().map(|p| p.display().to_string()),
+        digest: digest.clone(),
+    });
+
+    if feedback {
+        collect_feedback(&db, &digest_sections(&items, &item_tags))?;
     }
 
     Ok(())
 }
 
-fn generate_digest(
-    rt: &Runtime,
-    client: &OllamaClient,
-    model: &str,
-    content: &str,
+/// Numbers about the items and tasks of one period.
+fn period_stats(
+    db: &Database,
+    items: &[Item],
+    item_tags: &HashMap<String, Vec<String>>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<DigestStats> {
+    let mut stats = DigestStats {
+        item_count: items.len(),
+        ..Default::default()
+    };
+    for tag in items.iter().filter_map(|item| item_tags.get(&item.id)).flatten() {
+        *stats.topics.entry(tag.clone()).or_default() += 1;
+    }
+    (stats.tasks_created, stats.tasks_completed) = db.task_activity(start, end)?;
+    Ok(stats)
+}
+
+/// Stats for the period before `start`, and where they came from: the
+/// stored digest for it, or else the items and tasks of a window as long
+/// as this one.
+fn previous_stats(
+    db: &Database,
     period_desc: &str,
-) -> Result<String> {
-    let prompt = format!(
-        r#"Generate a {} digest/summary of the following content that was ingested into a personal knowledge base.
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    item_tags: &HashMap<String, Vec<String>>,
+) -> Result<(DigestStats, String)> {
+    let length = end - start;
+    // Scheduled digests don't run at exactly the same time every period
+    if let Some(previous) = db.previous_digest(period_desc, start + length / 2)? {
+        let source = format!("the digest of {}", dates::format_datetime(previous.created_at));
+        return Ok((previous.stats, source));
+    }
 
-Structure your response as:
-1. **Overview** - A brief paragraph summarizing the key themes
-2. **Key Items** - The most notable pieces of content (3-5 bullet points)
-3. **Insights** - Connections or patterns you notice across the content
-4. **Action Items** - Suggested next steps or things to revisit (if applicable)
+    let previous_start = start - length;
+    let items = db.items_between(previous_start, start)?;
+    let stats = period_stats(db, &items, item_tags, previous_start, start)?;
+    let source = format!(
+        "{} to {}",
+        dates::format_datetime(previous_start),
+        dates::format_datetime(start)
+    );
+    Ok((stats, source))
+}
 
-Be concise but informative. Focus on what's most valuable to remember.
+/// Topics in `stats` and not in `other`, most frequent first.
+fn topics_missing_from(stats: &DigestStats, other: &DigestStats) -> Vec<String> {
+    let mut topics: Vec<(&String, &usize)> = stats
+        .topics
+        .iter()
+        .filter(|(topic, _)| !other.topics.contains_key(*topic))
+        .collect();
+    topics.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+    topics.into_iter().map(|(topic, _)| topic.clone()).collect()
+}
 
-Content:
-{}
+/// How many topics a comparison line lists.
+const COMPARISON_TOPICS: usize = 8;
+
+/// The "what changed" section of a compared digest.
+fn format_comparison(current: &DigestStats, previous: &DigestStats, source: &str) -> String {
+    let list = |topics: Vec<String>| {
+        if topics.is_empty() {
+            return "none".to_string();
+        }
+        let extra = topics.len().saturating_sub(COMPARISON_TOPICS);
+        let mut shown = topics.into_iter().take(COMPARISON_TOPICS).collect::<Vec<_>>().join(", ");
+        if extra > 0 {
+            shown.push_str(&format!(" (+{} more)", extra));
+        }
+        shown
+    };
+    let rate = |stats: &DigestStats| match stats.task_completion_rate() {
+        Some(rate) => format!(
+            "{:.0}% ({} of {} new tasks)",
+            rate * 100.0,
+            stats.tasks_completed,
+            stats.tasks_created
+        ),
+        None => format!("no new tasks ({} completed)", stats.tasks_completed),
+    };
+    let change = current.item_count as i64 - previous.item_count as i64;
+
+    format!(
+        "## Compared with the previous period\n\n\
+         *Previous period: {}*\n\n\
+         - **Items:** {} (was {}, {:+})\n\
+         - **New topics:** {}\n\
+         - **Dropped threads:** {}\n\
+         - **Task completion:** {}, was {}\n",
+        source,
+        current.item_count,
+        previous.item_count,
+        change,
+        list(topics_missing_from(current, previous)),
+        list(topics_missing_from(previous, current)),
+        rate(current),
+        rate(previous),
+    )
+}
+
+/// Tag names for every tagged item.
+fn tags_by_item(db: &Database) -> Result<HashMap<String, Vec<String>>> {
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (item_id, tag) in db.get_all_item_tags()? {
+        tags.entry(item_id).or_default().push(tag.name);
+    }
+    Ok(tags)
+}
 
-Generate the digest now:"#,
-        period_desc, content
+/// Where an item came from, for feedback purposes: the capture source
+/// recorded in its metadata, else the folder it was ingested from, else
+/// its type.
+fn source_label(item: &Item) -> String {
+    if let Some(source) = item.metadata.get("source").and_then(|s| s.as_str()) {
+        return source.to_string();
+    }
+    if let Some(parent) = item
+        .source_path
+        .as_deref()
+        .and_then(|p| std::path::Path::new(p).parent())
+    {
+        return parent.display().to_string();
+    }
+    item.item_type.as_str().to_string()
+}
+
+fn preference_score(prefs: &[DigestPreference], target: FeedbackTarget, name: &str) -> i64 {
+    prefs
+        .iter()
+        .find(|p| p.target == target && p.name.eq_ignore_ascii_case(name))
+        .map_or(0, |p| p.score)
+}
+
+/// Drop items from ignored sources and put items on preferred topics first.
+///
+/// A source is ignored once its feedback score is negative. Items are
+/// otherwise kept in their original order, so truncation of the prompt
+/// content cuts the least interesting items.
+fn apply_preferences(
+    items: Vec<Item>,
+    item_tags: &HashMap<String, Vec<String>>,
+    prefs: &[DigestPreference],
+) -> Vec<Item> {
+    let mut scored: Vec<(i64, Item)> = items
+        .into_iter()
+        .filter(|item| preference_score(prefs, FeedbackTarget::Source, &source_label(item)) >= 0)
+        .map(|item| {
+            let score = item_tags
+                .get(&item.id)
+                .map(|tags| {
+                    tags.iter()
+                        .map(|t| preference_score(prefs, FeedbackTarget::Topic, t))
+                        .sum()
+                })
+                .unwrap_or(0);
+            (score, item)
+        })
+        .collect();
+
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Lines describing the reader's preferences for the digest prompt, or an
+/// empty string.
+fn preference_prompt(prefs: &[DigestPreference]) -> String {
+    let names = |target: FeedbackTarget, positive: bool| -> Vec<&str> {
+        prefs
+            .iter()
+            .filter(|p| p.target == target && (p.score > 0) == positive && p.score != 0)
+            .map(|p| p.name.as_str())
+            .collect()
+    };
+
+    let liked_topics = names(FeedbackTarget::Topic, true);
+    let disliked_topics = names(FeedbackTarget::Topic, false);
+    let liked_sources = names(FeedbackTarget::Source, true);
+
+    let mut lines = Vec::new();
+    if !liked_topics.is_empty() {
+        lines.push(format!(
+            "The reader is most interested in these topics; give them more space: {}.",
+            liked_topics.join(", ")
+        ));
+    }
+    if !disliked_topics.is_empty() {
+        lines.push(format!(
+            "The reader finds these topics less useful; mention them briefly if at all: {}.",
+            disliked_topics.join(", ")
+        ));
+    }
+    if !liked_sources.is_empty() {
+        lines.push(format!(
+            "Content from these sources tends to matter most: {}.",
+            liked_sources.join(", ")
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// The topics (most common tags) and sources a digest covered, with item counts.
+fn digest_sections(
+    items: &[Item],
+    item_tags: &HashMap<String, Vec<String>>,
+) -> Vec<(FeedbackTarget, String, usize)> {
+    let mut topics: HashMap<&str, usize> = HashMap::new();
+    let mut sources: HashMap<String, usize> = HashMap::new();
+    for item in items {
+        for tag in item_tags.get(&item.id).into_iter().flatten() {
+            *topics.entry(tag.as_str()).or_default() += 1;
+        }
+        *sources.entry(source_label(item)).or_default() += 1;
+    }
+
+    let top = |counts: Vec<(String, usize)>| -> Vec<(String, usize)> {
+        let mut counts = counts;
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts.truncate(FEEDBACK_SECTIONS);
+        counts
+    };
+
+    let mut sections: Vec<(FeedbackTarget, String, usize)> = Vec::new();
+    for (name, count) in top(topics.into_iter().map(|(k, v)| (k.to_string(), v)).collect()) {
+        sections.push((FeedbackTarget::Topic, name, count));
+    }
+    for (name, count) in top(sources.into_iter().collect()) {
+        sections.push((FeedbackTarget::Source, name, count));
+    }
+    sections
+}
+
+/// Ask for a thumbs-up or down on each digest section and store the answers.
+fn collect_feedback(db: &Database, sections: &[(FeedbackTarget, String, usize)]) -> Result<()> {
+    if sections.is_empty() {
+        return Ok(());
+    }
+
+    println!();
+    println!("{}", "Feedback".cyan().bold());
+    println!(
+        "{}",
+        "+ more like this, - less (ignores a source), Enter to skip, q to stop".dimmed()
     );
 
-    let request = GenerateRequest::new(model, &prompt)
-        .with_options(GenerateOptions::new().with_temperature(0.7));
+    let mut recorded = 0;
+    for (target, name, count) in sections {
+        print!("  {} {} ({} items) [+/-]: ", format!("{}:", target).cyan(), name, count);
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        if io::stdin().read_line(&mut answer)? == 0 {
+            break;
+        }
+        let positive = match answer.trim() {
+            "+" | "y" | "up" => true,
+            "-" | "n" | "down" => false,
+            "q" => break,
+            _ => continue,
+        };
+
+        db.record_digest_feedback(*target, name, positive)?;
+        recorded += 1;
+    }
+
+    if recorded > 0 {
+        println!(
+            "{} Saved {} ratings; future digests will use them",
+            "✓".green(),
+            recorded
+        );
+    }
+
+    Ok(())
+}
+
+fn digest_prompt(content: &str, period_desc: &str, preferences: &str) -> Result<String> {
+    Ok(prompts::render(
+        Prompt::Digest,
+        &json!({
+            "period": period_desc,
+            "preferences": preferences,
+            "content": content,
+        }),
+    )?)
+}
+
+fn generate_digest(
+    rt: &Runtime,
+    client: &OllamaClient,
+    model: &str,
+    budget: &ContextBudget,
+    prompt: &str,
+) -> Result<String> {
+    let request = GenerateRequest::new(model, prompt)
+        .with_options(budget.options().with_temperature(0.7));
 
     let response = rt.block_on(client.generate(request)).map_err(|e| {
         anyhow::anyhow!("Failed to generate digest: {}", e)
@@ -258,6 +598,14 @@ Generate the digest now:"#,
     Ok(response.response.trim().to_string())
 }
 
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
 fn format_digest_markdown(digest: &str, period_desc: &str, item_count: usize) -> String {
     let now = Utc::now();
     format!(
@@ -274,9 +622,91 @@ fn format_digest_markdown(digest: &str, period_desc: &str, item_count: usize) ->
 
 *Generated by Olal*
 "#,
-        period_desc.chars().next().unwrap().to_uppercase().to_string() + &period_desc[1..],
-        now.format("%Y-%m-%d %H:%M UTC"),
+        capitalize(period_desc),
+        dates::format_datetime(now),
         item_count,
         digest
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::ItemType;
+
+    fn pref(target: FeedbackTarget, name: &str, score: i64) -> DigestPreference {
+        DigestPreference {
+            target,
+            name: name.to_string(),
+            score,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_source_label() {
+        let mut captured = Item::new(ItemType::Note, "Quick");
+        captured.metadata = serde_json::json!({ "source": "capture" });
+        assert_eq!(source_label(&captured), "capture");
+
+        let file = Item::new(ItemType::Note, "File").with_source_path("/notes/work/a.md");
+        assert_eq!(source_label(&file), "/notes/work");
+
+        assert_eq!(source_label(&Item::new(ItemType::Video, "V")), "video");
+    }
+
+    #[test]
+    fn test_apply_preferences() {
+        let cooking = Item::new(ItemType::Note, "Soup").with_source_path("/recipes/soup.md");
+        let rust = Item::new(ItemType::Note, "Borrowck").with_source_path("/notes/rust.md");
+        let other = Item::new(ItemType::Note, "Misc").with_source_path("/notes/misc.md");
+
+        let mut tags = HashMap::new();
+        tags.insert(rust.id.clone(), vec!["rust".to_string()]);
+
+        let prefs = vec![
+            pref(FeedbackTarget::Source, "/recipes", -1),
+            pref(FeedbackTarget::Topic, "Rust", 2),
+        ];
+
+        let items = apply_preferences(vec![other.clone(), cooking, rust.clone()], &tags, &prefs);
+        let ids: Vec<&str> = items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec![rust.id.as_str(), other.id.as_str()]);
+    }
+
+    #[test]
+    fn test_preference_prompt() {
+        assert_eq!(preference_prompt(&[]), "");
+
+        let prompt = preference_prompt(&[
+            pref(FeedbackTarget::Topic, "rust", 3),
+            pref(FeedbackTarget::Topic, "cooking", -1),
+            pref(FeedbackTarget::Source, "youtube", -2),
+        ]);
+        assert!(prompt.contains("more space: rust."));
+        assert!(prompt.contains("if at all: cooking."));
+        assert!(!prompt.contains("youtube"));
+    }
+
+    #[test]
+    fn test_format_comparison() {
+        let stats = |items, topics: &[&str], created, completed| DigestStats {
+            item_count: items,
+            topics: topics.iter().map(|t| (t.to_string(), 1)).collect(),
+            tasks_created: created,
+            tasks_completed: completed,
+        };
+        let current = stats(5, &["rust", "tokio"], 4, 3);
+        let previous = stats(7, &["rust", "python"], 0, 1);
+
+        let section = format_comparison(&current, &previous, "last week");
+        assert!(section.contains("**Items:** 5 (was 7, -2)"), "{}", section);
+        assert!(section.contains("**New topics:** tokio\n"), "{}", section);
+        assert!(section.contains("**Dropped threads:** python\n"), "{}", section);
+        assert!(
+            section.contains("75% (3 of 4 new tasks), was no new tasks (1 completed)"),
+            "{}",
+            section
+        );
+    }
+}
@@ -0,0 +1,96 @@
+//! Sending digests by email (`[digest.smtp]`).
+//!
+//! The markdown digest is sent as a multipart message: the markdown itself
+//! as the plain-text part, and rendered HTML for mail clients that show it.
+
+use anyhow::{Context, Result};
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::transport::smtp::client::{Tls, TlsParameters};
+use lettre::{Message, SmtpTransport, Transport};
+use olal_config::{SmtpConfig, SmtpSecurity};
+use pulldown_cmark::{html, Options, Parser};
+
+/// Render markdown to an HTML document.
+pub fn markdown_to_html(markdown: &str) -> String {
+    let parser = Parser::new_ext(markdown, Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH);
+    let mut body = String::new();
+    html::push_html(&mut body, parser);
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"></head>\n\
+         <body style=\"font-family: -apple-system, sans-serif; max-width: 42em; line-height: 1.5\">\n\
+         {}</body></html>\n",
+        body
+    )
+}
+
+/// Send `markdown` to the configured recipients.
+pub fn send_markdown(smtp: &SmtpConfig, subject: &str, markdown: &str) -> Result<()> {
+    if !smtp.is_configured() {
+        anyhow::bail!(
+            "Email is not configured; set host, from and to under [digest.smtp] in the config file"
+        );
+    }
+
+    let mut builder = Message::builder()
+        .from(parse_mailbox(&smtp.from)?)
+        .subject(subject);
+    for to in &smtp.to {
+        builder = builder.to(parse_mailbox(to)?);
+    }
+    let message = builder
+        .multipart(MultiPart::alternative_plain_html(
+            markdown.to_string(),
+            markdown_to_html(markdown),
+        ))
+        .context("Failed to build email")?;
+
+    transport(smtp)?
+        .send(&message)
+        .with_context(|| format!("Failed to send email via {}", smtp.host))?;
+    Ok(())
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox> {
+    address
+        .parse()
+        .with_context(|| format!("Invalid email address: {}", address))
+}
+
+fn transport(smtp: &SmtpConfig) -> Result<SmtpTransport> {
+    let tls = || {
+        TlsParameters::new(smtp.host.clone())
+            .with_context(|| format!("Failed to set up TLS for {}", smtp.host))
+    };
+    let mut builder = SmtpTransport::builder_dangerous(&smtp.host)
+        .port(smtp.port)
+        .tls(match smtp.security {
+            SmtpSecurity::Starttls => Tls::Required(tls()?),
+            SmtpSecurity::Tls => Tls::Wrapper(tls()?),
+            SmtpSecurity::None => Tls::None,
+        });
+
+    if !smtp.username.is_empty() {
+        let password = smtp.password().context("Failed to read the SMTP password")?;
+        builder = builder.credentials(Credentials::new(smtp.username.clone(), password));
+    }
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_to_html() {
+        let html = markdown_to_html("# Weekly Digest\n\n- **Rust** ownership\n");
+        assert!(html.contains("<h1>Weekly Digest</h1>"));
+        assert!(html.contains("<li><strong>Rust</strong> ownership</li>"));
+    }
+
+    #[test]
+    fn test_unconfigured_smtp_fails() {
+        let err = send_markdown(&SmtpConfig::default(), "Digest", "hi").unwrap_err();
+        assert!(err.to_string().contains("[digest.smtp]"));
+    }
+}
@@ -1,9 +1,9 @@
 //! Embed command - generate embeddings for chunks.
 
-use super::get_database;
+use super::{get_database, resolve, warn_model_use};
 use anyhow::{Context, Result};
 use olal_config::Config;
-use olal_ollama::OllamaClient;
+use olal_ollama::{ModelUse, OllamaClient, OllamaError};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use tokio::runtime::Runtime;
@@ -23,10 +23,10 @@ pub fn run(all: bool, item_id: Option<String>, batch_size: usize) -> Result<()>
     // Check if Ollama is available
     let is_available = rt.block_on(client.is_available());
     if !is_available {
-        anyhow::bail!(
-            "Ollama is not running at {}. Start it with 'ollama serve'.",
-            config.ollama.host
-        );
+        return Err(OllamaError::ServerNotRunning {
+            host: config.ollama.host.clone(),
+        }
+        .into());
     }
 
     // Check if embedding model is available
@@ -51,12 +51,16 @@ pub fn run(all: bool, item_id: Option<String>, batch_size: usize) -> Result<()>
         );
     }
 
+    warn_model_use(&rt, &client, &config.ollama.embedding_model, ModelUse::Embed);
+
     if let Some(ref id) = item_id {
         // Embed chunks for a specific item
         embed_item(&db, &client, &config.ollama.embedding_model, id, &rt)?;
+        link_similar(&db, &config)?;
     } else if all {
         // Embed all unembedded chunks
         embed_all(&db, &client, &config.ollama.embedding_model, batch_size, &rt)?;
+        link_similar(&db, &config)?;
     } else {
         // Show stats and usage
         let (embedded, total) = db.embedding_stats()?;
@@ -95,6 +99,69 @@ pub fn run(all: bool, item_id: Option<String>, batch_size: usize) -> Result<()>
     Ok(())
 }
 
+/// Embed an item's chunks without progress output, if Ollama is reachable.
+///
+/// Returns the number of chunks embedded, or `None` when Ollama or the
+/// embedding model isn't available.
+pub(crate) fn embed_item_quietly(
+    db: &olal_db::Database,
+    item_id: &olal_core::ItemId,
+) -> Result<Option<usize>> {
+    let config = Config::load().context("Failed to load configuration")?;
+    let client = OllamaClient::from_config(&config.ollama)
+        .context("Failed to create Ollama client")?;
+    let rt = Runtime::new().context("Failed to create async runtime")?;
+    let model = &config.ollama.embedding_model;
+
+    let has_model = rt.block_on(client.has_model(model)).unwrap_or(false);
+    if !rt.block_on(client.is_available()) || !has_model {
+        return Ok(None);
+    }
+
+    let mut embedded = 0;
+    for chunk in db.get_chunks_by_item(item_id)? {
+        if db.get_embedding(&chunk.id)?.is_some() || db.reuse_embedding(&chunk, model)? {
+            continue;
+        }
+        let embedding = rt.block_on(client.embed(model, &chunk.content))?;
+        db.store_embedding(&chunk.id, &embedding, model)?;
+        embedded += 1;
+    }
+    Ok(Some(embedded))
+}
+
+/// Link items whose embeddings are similar, if enabled in config.
+fn link_similar(db: &olal_db::Database, config: &Config) -> Result<()> {
+    if !config.processing.auto_link {
+        return Ok(());
+    }
+
+    let (created, queued) = db
+        .link_similar_items_reviewed(
+            config.processing.link_similarity_threshold,
+            config.review.link_review_threshold(),
+        )
+        .context("Failed to link similar items")?;
+
+    if created > 0 {
+        println!(
+            "{} Linked {} similar item pairs (threshold {:.2})",
+            "✓".green(),
+            created,
+            config.processing.link_similarity_threshold
+        );
+    }
+    if queued > 0 {
+        println!(
+            "{} {} weaker link suggestions queued for review ('olal review-ai')",
+            "•".yellow(),
+            queued
+        );
+    }
+
+    Ok(())
+}
+
 /// Embed chunks for a specific item.
 fn embed_item(
     db: &olal_db::Database,
@@ -104,9 +171,7 @@ fn embed_item(
     rt: &Runtime,
 ) -> Result<()> {
     // Try to find the item (support partial ID)
-    let item = db
-        .get_item_by_prefix(item_id)
-        .context("Item not found")?;
+    let item = resolve::item(db, item_id)?;
 
     println!(
         "{} {} [{}]",
@@ -131,6 +196,7 @@ fn embed_item(
     );
 
     let mut embedded = 0;
+    let mut reused = 0;
     let mut skipped = 0;
 
     for chunk in &chunks {
@@ -140,6 +206,11 @@ fn embed_item(
             pb.inc(1);
             continue;
         }
+        if db.reuse_embedding(chunk, model)? {
+            reused += 1;
+            pb.inc(1);
+            continue;
+        }
 
         // Generate embedding
         match rt.block_on(client.embed(model, &chunk.content)) {
@@ -163,9 +234,10 @@ fn embed_item(
     pb.finish_and_clear();
 
     println!(
-        "{} {} new embeddings, {} already embedded",
+        "{} {} new embeddings, {} reused, {} already embedded",
         "Done:".green().bold(),
         embedded.to_string().green(),
+        reused,
         skipped
     );
 
@@ -205,9 +277,11 @@ fn embed_all(
     );
 
     let mut total_embedded = 0;
+    let mut reused = 0;
     let mut errors = 0;
+    let mut stopped = None;
 
-    loop {
+    'batches: loop {
         let chunks = db.get_unembedded_chunks(batch_size)?;
 
         if chunks.is_empty() {
@@ -215,11 +289,20 @@ fn embed_all(
         }
 
         for chunk in &chunks {
+            if db.reuse_embedding(chunk, model)? {
+                reused += 1;
+                pb.inc(1);
+                continue;
+            }
             match rt.block_on(client.embed(model, &chunk.content)) {
                 Ok(embedding) => {
                     db.store_embedding(&chunk.id, &embedding, model)?;
                     total_embedded += 1;
                 }
+                Err(e @ OllamaError::CircuitOpen { .. }) => {
+                    stopped = Some(e);
+                    break 'batches;
+                }
                 Err(e) => {
                     errors += 1;
                     pb.println(format!(
@@ -244,6 +327,13 @@ fn embed_all(
         "✓".green(),
         total_embedded.to_string().green()
     );
+    if reused > 0 {
+        println!(
+            "{} Reused {} embeddings of identical text",
+            "✓".green(),
+            reused.to_string().green()
+        );
+    }
 
     if errors > 0 {
         println!(
@@ -252,6 +342,10 @@ fn embed_all(
             errors.to_string().yellow()
         );
     }
+    if let Some(e) = stopped {
+        println!("{} {}", "Stopped:".red().bold(), e);
+        println!("Run 'olal embed --all' again once Ollama is healthy to continue.");
+    }
 
     Ok(())
 }
@@ -0,0 +1,175 @@
+//! Encrypt command - migrate a plaintext knowledge base to SQLCipher.
+//!
+//! Requires a build with `--features encryption`. The key comes from the
+//! `OLAL_DB_KEY` environment variable, the OS keyring, or a prompt, in that
+//! order.
+
+use super::get_paths;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_db::{Database, ENCRYPTION_SUPPORTED};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable holding the database key.
+pub const KEY_ENV: &str = "OLAL_DB_KEY";
+
+/// Run the encrypt command.
+pub fn run(store_key: bool) -> Result<()> {
+    let paths = get_paths()?;
+
+    if !ENCRYPTION_SUPPORTED {
+        anyhow::bail!(
+            "This build of olal has no encryption support. Rebuild with: cargo install --path crates/olal-cli --features encryption"
+        );
+    }
+    if !paths.is_initialized() {
+        return Err(olal_core::Error::NotInitialized.into());
+    }
+    if Database::is_encrypted(&paths.database_file) {
+        anyhow::bail!("The database is already encrypted.");
+    }
+
+    let key = match env_key() {
+        Some(key) => key,
+        None => {
+            let key = secret::prompt("New database key: ")?;
+            if secret::prompt("Confirm key: ")? != key {
+                anyhow::bail!("Keys did not match");
+            }
+            key
+        }
+    };
+    if key.is_empty() {
+        anyhow::bail!("Key cannot be empty");
+    }
+
+    println!("{}", "Encrypting database...".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    let staging = sidecar(&paths.database_file, ".encrypting");
+    if staging.exists() {
+        fs::remove_file(&staging)?;
+    }
+
+    let db = Database::open(&paths.database_file).context("Failed to open database")?;
+    db.encrypt_to(&staging, &key).context("Failed to export encrypted copy")?;
+    drop(db);
+
+    // Verify before replacing the plaintext original
+    let encrypted = Database::open_with_key(&staging, Some(&key))
+        .context("Encrypted copy could not be opened")?;
+    if !encrypted.integrity_check()? {
+        let _ = fs::remove_file(&staging);
+        anyhow::bail!("Encrypted copy failed its integrity check; nothing was changed");
+    }
+    drop(encrypted);
+
+    // Plaintext WAL files would leak content and can't be replayed anyway
+    for suffix in ["-wal", "-shm"] {
+        let path = sidecar(&paths.database_file, suffix);
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+    }
+    fs::rename(&staging, &paths.database_file)?;
+    println!("  {} Encrypted {}", "✓".green(), paths.database_file.display());
+
+    if store_key {
+        secret::store(&key).context("Failed to save key to the keyring")?;
+        println!("  {} Saved key to the OS keyring", "✓".green());
+    }
+
+    println!();
+    println!(
+        "{} Keep your key safe - without it the knowledge base cannot be opened.",
+        "Note:".yellow().bold()
+    );
+    println!(
+        "Existing backups are still plaintext; run {} to replace them.",
+        "olal backup".cyan()
+    );
+
+    Ok(())
+}
+
+/// Resolve the key needed to open the database at `path`.
+///
+/// Returns `None` for plaintext databases.
+pub fn database_key(path: &Path) -> Result<Option<String>> {
+    if !Database::is_encrypted(path) {
+        return Ok(None);
+    }
+    if !ENCRYPTION_SUPPORTED {
+        anyhow::bail!(
+            "{} is encrypted but this build of olal has no encryption support. Rebuild with --features encryption.",
+            path.display()
+        );
+    }
+
+    if let Some(key) = env_key() {
+        return Ok(Some(key));
+    }
+    if let Some(key) = secret::stored() {
+        return Ok(Some(key));
+    }
+    Ok(Some(secret::prompt("Database key: ")?))
+}
+
+/// Like [`database_key`], but never prompts: `None` if the database is
+/// encrypted and no key is in the environment or keyring.
+pub fn unattended_key(path: &Path) -> Option<Option<String>> {
+    if !Database::is_encrypted(path) {
+        return Some(None);
+    }
+    if !ENCRYPTION_SUPPORTED {
+        return None;
+    }
+    env_key().or_else(secret::stored).map(Some)
+}
+
+fn env_key() -> Option<String> {
+    std::env::var(KEY_ENV).ok().filter(|key| !key.is_empty())
+}
+
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    PathBuf::from(format!("{}{}", path.display(), suffix))
+}
+
+#[cfg(feature = "encryption")]
+mod secret {
+    use anyhow::Result;
+    use olal_config::secrets;
+
+    const ACCOUNT: &str = "database-key";
+
+    pub fn stored() -> Option<String> {
+        secrets::get(ACCOUNT).ok()
+    }
+
+    pub fn store(key: &str) -> Result<()> {
+        secrets::set(ACCOUNT, key)?;
+        Ok(())
+    }
+
+    pub fn prompt(message: &str) -> Result<String> {
+        Ok(rpassword::prompt_password(message)?)
+    }
+}
+
+#[cfg(not(feature = "encryption"))]
+mod secret {
+    use anyhow::Result;
+
+    pub fn stored() -> Option<String> {
+        None
+    }
+
+    pub fn store(_key: &str) -> Result<()> {
+        anyhow::bail!("Built without encryption support")
+    }
+
+    pub fn prompt(_message: &str) -> Result<String> {
+        anyhow::bail!("Built without encryption support")
+    }
+}
@@ -0,0 +1,170 @@
+//! Entity commands - people, organizations and topics mentioned in items.
+
+use super::{get_database, resolve};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use indicatif::{ProgressBar, ProgressStyle};
+use olal_config::Config;
+use olal_core::EntityKind;
+use olal_ingest::ai_enrich::{self, AiEnricher};
+
+fn parse_kind(kind: &str) -> Result<EntityKind> {
+    EntityKind::from_str(kind).ok_or_else(|| {
+        anyhow::anyhow!("Invalid kind '{}'. Use: person, organization, topic", kind)
+    })
+}
+
+fn kind_icon(kind: EntityKind) -> &'static str {
+    match kind {
+        EntityKind::Person => "👤",
+        EntityKind::Organization => "🏢",
+        EntityKind::Topic => "💡",
+    }
+}
+
+/// List entities, optionally filtered by kind.
+pub fn list(kind: Option<String>, limit: usize) -> Result<()> {
+    let db = get_database()?;
+    let kind = kind.as_deref().map(parse_kind).transpose()?;
+
+    let entities = db.list_entities(kind)?;
+
+    if entities.is_empty() {
+        println!(
+            "{}",
+            "No entities found. Use 'olal entity extract --all' to extract them.".dimmed()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "Entities".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    for (entity, count) in entities.iter().take(limit) {
+        println!(
+            "  {} {} {} ({})",
+            kind_icon(entity.kind),
+            entity.name.white(),
+            entity.kind.as_str().dimmed(),
+            count
+        );
+    }
+
+    if entities.len() > limit {
+        println!(
+            "{}",
+            format!("... and {} more", entities.len() - limit).dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Show every item mentioning an entity.
+pub fn show(name: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let entities = db.find_entities_by_name(name)?;
+    if entities.is_empty() {
+        anyhow::bail!("No entity named '{}'. Use 'olal entity list' to browse.", name);
+    }
+
+    for entity in entities {
+        let items = db.get_items_by_entity(&entity.id)?;
+
+        println!(
+            "{} {} {}",
+            kind_icon(entity.kind),
+            entity.name.white().bold(),
+            format!("({})", entity.kind).dimmed()
+        );
+        println!("{}", "─".repeat(70));
+
+        for (item, mentions) in &items {
+            println!(
+                "  {} {} {} {}",
+                item.id.chars().take(8).collect::<String>().dimmed(),
+                item.title.white(),
+                format!("[{}]", item.item_type).dimmed(),
+                format!("×{}", mentions).green()
+            );
+        }
+
+        println!();
+        println!("{} {} items", "Mentioned in".dimmed(), items.len());
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Extract entities for one item or all items.
+pub fn extract(item_id: Option<String>, all: bool) -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+
+    let items = if let Some(ref id) = item_id {
+        vec![resolve::item(&db, id)?]
+    } else if all {
+        db.list_items(None, None)?
+    } else {
+        anyhow::bail!("Specify --item <id> or --all");
+    };
+
+    let enricher = AiEnricher::from_config(&config)?;
+
+    let pb = ProgressBar::new(items.len() as u64);
+    pb.set_style(
+        ProgressStyle::default_bar()
+            .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} {msg}")
+            .unwrap()
+            .progress_chars("█▓░"),
+    );
+
+    let mut total = 0;
+    let mut failed = 0;
+
+    for item in &items {
+        pb.set_message(item.title.chars().take(30).collect::<String>());
+
+        let content = db
+            .get_chunks_by_item(&item.id)?
+            .into_iter()
+            .map(|c| c.content)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if content.trim().is_empty() {
+            pb.inc(1);
+            continue;
+        }
+
+        match enricher
+            .extract_entities(&content, &item.title)
+            .and_then(|entities| ai_enrich::store_entities(&db, item, &content, &entities))
+        {
+            Ok(count) => total += count,
+            Err(e) => {
+                failed += 1;
+                pb.println(format!("{} {}: {}", "✗".red(), item.title, e));
+            }
+        }
+
+        pb.inc(1);
+    }
+
+    pb.finish_and_clear();
+
+    println!(
+        "{} Extracted {} entities from {} items",
+        "✓".green(),
+        total,
+        items.len() - failed
+    );
+
+    if failed > 0 {
+        println!("{} {} items failed", "Warning:".yellow(), failed);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,264 @@
+//! Flashcards command - LLM-generated Q/A pairs with Anki export.
+
+use super::{get_database, resolve};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::Config;
+use olal_core::{Flashcard, Item};
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient, OllamaError};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use tokio::runtime::Runtime;
+
+/// Item content sent to the model is cut off after this many characters.
+const CONTENT_LIMIT: usize = 6000;
+
+/// Generate flashcards for an item, replacing any it already has.
+pub fn generate(item_id: &str, count: usize, model: Option<String>) -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+    let item = resolve::item(&db, item_id)?;
+
+    let chunks = db.get_chunks_by_item(&item.id)?;
+    if chunks.is_empty() {
+        anyhow::bail!("Item '{}' has no content to make flashcards from", item.title);
+    }
+    let content: String = chunks
+        .iter()
+        .map(|c| c.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+        .chars()
+        .take(CONTENT_LIMIT)
+        .collect();
+
+    let client = OllamaClient::from_config(&config.ollama)
+        .context("Failed to create Ollama client")?;
+    let rt = Runtime::new().context("Failed to create async runtime")?;
+    if !rt.block_on(client.is_available()) {
+        return Err(OllamaError::ServerNotRunning {
+            host: config.ollama.host.clone(),
+        }
+        .into());
+    }
+    let model_name = model.as_deref().unwrap_or(&config.ollama.model);
+
+    println!("{} {}", "Generating flashcards:".cyan().bold(), item.title);
+    println!("{}", "─".repeat(50));
+
+    let prompt = prompts::render(Prompt::Flashcards, &json!({ "count": count, "content": content }))?;
+    let request = GenerateRequest::new(model_name, prompt)
+        .with_options(GenerateOptions::new().with_temperature(0.3).with_num_predict(1500));
+    let response = rt
+        .block_on(client.generate(request))
+        .context("Failed to generate flashcards")?;
+
+    let cards: Vec<Flashcard> = parse_flashcards(&response.response)
+        .into_iter()
+        .take(count)
+        .map(|(question, answer)| Flashcard::new(item.id.clone(), question, answer))
+        .collect();
+    if cards.is_empty() {
+        anyhow::bail!("The model didn't return any flashcards; try again or use another --model");
+    }
+
+    let replaced = db.replace_flashcards(&item.id, &cards)?;
+    print_cards(&cards);
+
+    println!();
+    print!("{} Saved {} flashcards", "✓".green(), cards.len());
+    if replaced > 0 {
+        print!(" (replaced {})", replaced);
+    }
+    println!();
+    println!("Export for Anki with: olal flashcards export --output deck.txt");
+    Ok(())
+}
+
+/// List stored flashcards, for one item or all.
+pub fn list(item_id: Option<String>) -> Result<()> {
+    let db = get_database()?;
+    let item = item_id.map(|id| resolve::item(&db, &id)).transpose()?;
+    let cards = db.list_flashcards(item.as_ref().map(|i| &i.id))?;
+
+    println!("{}", "Flashcards".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if cards.is_empty() {
+        println!("{}", "No flashcards yet.".dimmed());
+        println!("Generate some with: olal flashcards <item>");
+        return Ok(());
+    }
+
+    let titles = item_titles(&db, &cards)?;
+    let mut current: Option<&str> = None;
+    for card in &cards {
+        if current != Some(card.item_id.as_str()) {
+            current = Some(&card.item_id);
+            println!();
+            println!("{}", titles[&card.item_id].white().bold());
+        }
+        println!("  {} {}", "Q:".cyan(), card.question);
+        println!("  {} {}", "A:".green(), card.answer);
+    }
+
+    Ok(())
+}
+
+/// Export flashcards as Anki-importable tab-separated text.
+pub fn export(output: Option<PathBuf>, item_id: Option<String>) -> Result<()> {
+    let db = get_database()?;
+    let item = item_id.map(|id| resolve::item(&db, &id)).transpose()?;
+    let cards = db.list_flashcards(item.as_ref().map(|i| &i.id))?;
+
+    if cards.is_empty() {
+        anyhow::bail!("No flashcards to export. Generate some with: olal flashcards <item>");
+    }
+
+    let titles = item_titles(&db, &cards)?;
+    let tsv = to_anki_tsv(&cards, &titles);
+
+    match output {
+        Some(path) => {
+            fs::write(&path, tsv).context("Failed to write export file")?;
+            println!(
+                "{} Exported {} flashcards to {}",
+                "✓".green(),
+                cards.len(),
+                path.display()
+            );
+            println!("In Anki: File > Import, then pick the file.");
+        }
+        None => print!("{}", tsv),
+    }
+    Ok(())
+}
+
+fn print_cards(cards: &[Flashcard]) {
+    for (i, card) in cards.iter().enumerate() {
+        println!();
+        println!("{} {}", format!("{}.", i + 1).cyan(), card.question.white().bold());
+        println!("   {}", card.answer);
+    }
+}
+
+fn item_titles(db: &olal_db::Database, cards: &[Flashcard]) -> Result<HashMap<String, String>> {
+    let mut titles = HashMap::new();
+    for card in cards {
+        if !titles.contains_key(&card.item_id) {
+            let item: Item = db.get_item(&card.item_id)?;
+            titles.insert(card.item_id.clone(), item.title);
+        }
+    }
+    Ok(titles)
+}
+
+/// Parse "Q: ... / A: ..." pairs. Multi-line answers are joined.
+fn parse_flashcards(response: &str) -> Vec<(String, String)> {
+    let mut cards = Vec::new();
+    let mut question: Option<String> = None;
+    let mut answer: Option<String> = None;
+
+    let mut finish = |question: &mut Option<String>, answer: &mut Option<String>| {
+        if let (Some(q), Some(a)) = (question.take(), answer.take()) {
+            if !q.is_empty() && !a.is_empty() {
+                cards.push((q, a));
+            }
+        }
+    };
+
+    for line in response.lines() {
+        let line = line.trim().trim_start_matches(['*', '-']).trim();
+        if let Some(q) = strip_label(line, "Q") {
+            finish(&mut question, &mut answer);
+            question = Some(q.to_string());
+        } else if let Some(a) = strip_label(line, "A") {
+            answer = Some(a.to_string());
+        } else if !line.is_empty() {
+            if let Some(a) = answer.as_mut() {
+                a.push(' ');
+                a.push_str(line);
+            }
+        }
+    }
+    finish(&mut question, &mut answer);
+
+    cards
+}
+
+/// `line` without a leading "Q:" (or "**Q:**", "Question:") label.
+fn strip_label<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    let long = if label == "Q" { "Question" } else { "Answer" };
+    [label, long].iter().find_map(|l| {
+        let rest = line.strip_prefix(l)?.trim_start_matches('*');
+        rest.strip_prefix(':').map(|r| r.trim_start_matches('*').trim())
+    })
+}
+
+/// Anki's plain-text import format: a header, then question, answer and tags
+/// per line. Items become tags so cards can be filtered by source.
+fn to_anki_tsv(cards: &[Flashcard], titles: &HashMap<String, String>) -> String {
+    let mut out = String::from("#separator:tab\n#html:false\n#tags column:3\n");
+    for card in cards {
+        let tag = anki_tag(titles.get(&card.item_id).map(String::as_str).unwrap_or("item"));
+        out.push_str(&format!(
+            "{}\t{}\tolal {}\n",
+            tsv_field(&card.question),
+            tsv_field(&card.answer),
+            tag
+        ));
+    }
+    out
+}
+
+fn tsv_field(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Anki tags can't contain spaces.
+fn anki_tag(title: &str) -> String {
+    title
+        .split_whitespace()
+        .map(|w| w.trim_matches(|c: char| !c.is_alphanumeric()).to_lowercase())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flashcards() {
+        let response = "Here are your cards:\n\n\
+            Q: What owns a value in Rust?\n\
+            A: Exactly one owner.\n\n\
+            **Q:** When is a value dropped?\n\
+            **A:** When its owner\n goes out of scope.\n\n\
+            Q: A question without an answer\n";
+        let cards = parse_flashcards(response);
+        assert_eq!(
+            cards,
+            vec![
+                ("What owns a value in Rust?".to_string(), "Exactly one owner.".to_string()),
+                (
+                    "When is a value dropped?".to_string(),
+                    "When its owner goes out of scope.".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_anki_tsv() {
+        let card = Flashcard::new("item".to_string(), "What\tis it?", "An\nanswer");
+        let titles = HashMap::from([("item".to_string(), "Rust: Ownership".to_string())]);
+        let tsv = to_anki_tsv(&[card], &titles);
+        assert!(tsv.starts_with("#separator:tab\n"));
+        assert!(tsv.ends_with("What is it?\tAn answer\tolal rust_ownership\n"));
+    }
+}
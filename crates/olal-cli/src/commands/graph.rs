@@ -0,0 +1,420 @@
+//! Graph command - export the item/link/tag knowledge graph.
+
+use super::{dates, get_database};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use olal_core::ItemType;
+use olal_db::Database;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Supported export formats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    GraphMl,
+    Json,
+}
+
+impl GraphFormat {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "dot" | "graphviz" => Some(GraphFormat::Dot),
+            "graphml" | "xml" => Some(GraphFormat::GraphMl),
+            "json" => Some(GraphFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// A node in the exported graph (an item or a tag).
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+    /// "item" or "tag".
+    pub kind: &'static str,
+    pub item_type: Option<String>,
+    pub created_at: Option<String>,
+}
+
+/// An edge in the exported graph (an explicit link or a tag assignment).
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    /// Link type, or "tagged" for item-to-tag edges.
+    pub kind: String,
+    pub weight: f64,
+}
+
+/// The exported graph.
+#[derive(Debug, Default)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// Filters applied to items before export.
+#[derive(Debug, Default)]
+pub struct GraphFilter {
+    pub tag: Option<String>,
+    pub item_type: Option<ItemType>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+/// Run the graph export command.
+pub fn export(
+    format: &str,
+    tag: Option<String>,
+    item_type: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let db = get_database()?;
+
+    let graph_format = GraphFormat::from_str(format).ok_or_else(|| {
+        anyhow::anyhow!("Invalid format '{}'. Use: dot, graphml, json", format)
+    })?;
+
+    let item_type = match item_type {
+        Some(t) => Some(ItemType::from_str(&t).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid type '{}'. Use: video, audio, document, note, code, image, bookmark",
+                t
+            )
+        })?),
+        None => None,
+    };
+
+    let filter = GraphFilter {
+        tag,
+        item_type,
+        since: since.as_deref().map(dates::parse_date).transpose()?.map(dates::start_of_day),
+        until: until.as_deref().map(dates::parse_date).transpose()?.map(dates::end_of_day),
+    };
+
+    let graph = build_graph(&db, &filter)?;
+
+    let rendered = match graph_format {
+        GraphFormat::Dot => render_dot(&graph),
+        GraphFormat::GraphMl => render_graphml(&graph),
+        GraphFormat::Json => render_json(&graph)?,
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!(
+                "{} Exported {} nodes and {} edges to {}",
+                "✓".green(),
+                graph.nodes.len(),
+                graph.edges.len(),
+                path.display()
+            );
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+/// Build the graph of items matching the filter, their tags, and links between them.
+pub fn build_graph(db: &Database, filter: &GraphFilter) -> Result<Graph> {
+    let item_tags = db.get_all_item_tags()?;
+
+    let tagged_with: Option<HashSet<&str>> = filter.tag.as_ref().map(|name| {
+        item_tags
+            .iter()
+            .filter(|(_, t)| t.is_within(name))
+            .map(|(item_id, _)| item_id.as_str())
+            .collect()
+    });
+
+    let items: Vec<_> = db
+        .list_items(filter.item_type, None)?
+        .into_iter()
+        .filter(|item| filter.since.is_none_or(|since| item.created_at >= since))
+        .filter(|item| filter.until.is_none_or(|until| item.created_at <= until))
+        .filter(|item| {
+            tagged_with
+                .as_ref()
+                .is_none_or(|ids| ids.contains(item.id.as_str()))
+        })
+        .collect();
+
+    let item_ids: HashSet<&str> = items.iter().map(|i| i.id.as_str()).collect();
+    let mut graph = Graph::default();
+
+    for item in &items {
+        graph.nodes.push(GraphNode {
+            id: item.id.clone(),
+            label: item.title.clone(),
+            kind: "item",
+            item_type: Some(item.item_type.as_str().to_string()),
+            created_at: Some(item.created_at.to_rfc3339()),
+        });
+    }
+
+    let mut tag_ids: HashSet<String> = HashSet::new();
+    for (item_id, tag) in &item_tags {
+        if !item_ids.contains(item_id.as_str()) {
+            continue;
+        }
+        let node_id = format!("tag:{}", tag.id);
+        if tag_ids.insert(node_id.clone()) {
+            graph.nodes.push(GraphNode {
+                id: node_id.clone(),
+                label: tag.name.clone(),
+                kind: "tag",
+                item_type: None,
+                created_at: None,
+            });
+        }
+        graph.edges.push(GraphEdge {
+            source: item_id.clone(),
+            target: node_id,
+            kind: "tagged".to_string(),
+            weight: 1.0,
+        });
+    }
+
+    for link in db.list_links()? {
+        if item_ids.contains(link.source_id.as_str()) && item_ids.contains(link.target_id.as_str())
+        {
+            graph.edges.push(GraphEdge {
+                source: link.source_id,
+                target: link.target_id,
+                kind: link.link_type.as_str().to_string(),
+                weight: link.strength,
+            });
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Render the graph in Graphviz DOT format.
+pub fn render_dot(graph: &Graph) -> String {
+    let mut out = String::from("digraph olal {\n");
+    out.push_str("  node [style=filled];\n");
+
+    for node in &graph.nodes {
+        let (shape, color) = if node.kind == "tag" {
+            ("ellipse", "#ffe08a")
+        } else {
+            ("box", "#a8d8ff")
+        };
+        out.push_str(&format!(
+            "  \"{}\" [label=\"{}\", shape={}, fillcolor=\"{}\"];\n",
+            escape_dot(&node.id),
+            escape_dot(&node.label),
+            shape,
+            color
+        ));
+    }
+
+    for edge in &graph.edges {
+        let style = if edge.kind == "tagged" { ", style=dashed" } else { "" };
+        // Graphviz only takes integer weights, so strength (0-1) is scaled
+        // to 0-100; line width shows it too
+        let strength = edge.weight.clamp(0.0, 1.0);
+        out.push_str(&format!(
+            "  \"{}\" -> \"{}\" [label=\"{}\", weight={}, penwidth={:.1}{}];\n",
+            escape_dot(&edge.source),
+            escape_dot(&edge.target),
+            escape_dot(&edge.kind),
+            (strength * 100.0).round() as i64,
+            1.0 + 2.0 * strength,
+            style
+        ));
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// Render the graph as GraphML (Gephi, yEd, Cytoscape).
+pub fn render_graphml(graph: &Graph) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str("<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n");
+    out.push_str("  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"kind\" for=\"node\" attr.name=\"kind\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"item_type\" for=\"node\" attr.name=\"item_type\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"created_at\" for=\"node\" attr.name=\"created_at\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"edge_kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n");
+    out.push_str("  <key id=\"weight\" for=\"edge\" attr.name=\"weight\" attr.type=\"double\"/>\n");
+    out.push_str("  <graph id=\"olal\" edgedefault=\"directed\">\n");
+
+    for node in &graph.nodes {
+        out.push_str(&format!("    <node id=\"{}\">\n", escape_xml(&node.id)));
+        out.push_str(&format!(
+            "      <data key=\"label\">{}</data>\n",
+            escape_xml(&node.label)
+        ));
+        out.push_str(&format!("      <data key=\"kind\">{}</data>\n", node.kind));
+        if let Some(ref item_type) = node.item_type {
+            out.push_str(&format!("      <data key=\"item_type\">{}</data>\n", item_type));
+        }
+        if let Some(ref created_at) = node.created_at {
+            out.push_str(&format!(
+                "      <data key=\"created_at\">{}</data>\n",
+                created_at
+            ));
+        }
+        out.push_str("    </node>\n");
+    }
+
+    for edge in &graph.edges {
+        out.push_str(&format!(
+            "    <edge source=\"{}\" target=\"{}\">\n",
+            escape_xml(&edge.source),
+            escape_xml(&edge.target)
+        ));
+        out.push_str(&format!(
+            "      <data key=\"edge_kind\">{}</data>\n",
+            escape_xml(&edge.kind)
+        ));
+        out.push_str(&format!("      <data key=\"weight\">{}</data>\n", edge.weight));
+        out.push_str("    </edge>\n");
+    }
+
+    out.push_str("  </graph>\n");
+    out.push_str("</graphml>\n");
+    out
+}
+
+/// Render the graph as JSON with `nodes` and `edges` arrays.
+pub fn render_json(graph: &Graph) -> Result<String> {
+    let nodes: Vec<serde_json::Value> = graph
+        .nodes
+        .iter()
+        .map(|n| {
+            serde_json::json!({
+                "id": n.id,
+                "label": n.label,
+                "kind": n.kind,
+                "item_type": n.item_type,
+                "created_at": n.created_at,
+            })
+        })
+        .collect();
+
+    let edges: Vec<serde_json::Value> = graph
+        .edges
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "source": e.source,
+                "target": e.target,
+                "kind": e.kind,
+                "weight": e.weight,
+            })
+        })
+        .collect();
+
+    let value = serde_json::json!({ "nodes": nodes, "edges": edges });
+    Ok(serde_json::to_string_pretty(&value)? + "\n")
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, Link, LinkType};
+
+    fn sample_db() -> (Database, Item, Item, Item) {
+        let db = Database::open_in_memory().unwrap();
+        let a = Item::new(ItemType::Note, "Rust \"ownership\"");
+        let b = Item::new(ItemType::Document, "Borrowing <guide>");
+        let c = Item::new(ItemType::Note, "Cooking");
+        db.create_item(&a).unwrap();
+        db.create_item(&b).unwrap();
+        db.create_item(&c).unwrap();
+        db.tag_item(&a.id, "rust").unwrap();
+        db.tag_item(&b.id, "rust").unwrap();
+        db.create_link(&Link::new(a.id.clone(), b.id.clone(), LinkType::References))
+            .unwrap();
+        db.create_link(&Link::new(a.id.clone(), c.id.clone(), LinkType::Related))
+            .unwrap();
+        (db, a, b, c)
+    }
+
+    #[test]
+    fn test_build_graph_filters() {
+        let (db, _, _, _) = sample_db();
+
+        let all = build_graph(&db, &GraphFilter::default()).unwrap();
+        assert_eq!(all.nodes.len(), 4); // 3 items + 1 tag
+        assert_eq!(all.edges.len(), 4); // 2 tagged + 2 links
+
+        let rust_only = build_graph(
+            &db,
+            &GraphFilter {
+                tag: Some("rust".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(rust_only.nodes.len(), 3);
+        // Link to the filtered-out item is dropped
+        assert_eq!(rust_only.edges.len(), 3);
+
+        let notes = build_graph(
+            &db,
+            &GraphFilter {
+                item_type: Some(ItemType::Note),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(notes.nodes.iter().filter(|n| n.kind == "item").count(), 2);
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let (db, a, _, _) = sample_db();
+        let graph = build_graph(&db, &GraphFilter::default()).unwrap();
+
+        let dot = render_dot(&graph);
+        assert!(dot.starts_with("digraph olal {"));
+        assert!(dot.contains("Rust \\\"ownership\\\""));
+        assert!(dot.contains(&format!("\"{}\"", a.id)));
+
+        let graphml = render_graphml(&graph);
+        assert!(graphml.contains("Borrowing &lt;guide&gt;"));
+        assert!(graphml.contains("edgedefault=\"directed\""));
+
+        let json: serde_json::Value = serde_json::from_str(&render_json(&graph).unwrap()).unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 4);
+        assert_eq!(json["edges"].as_array().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_dot_weights_are_integers() {
+        let (db, a, b, _) = sample_db();
+        db.create_link(&Link::new(a.id.clone(), b.id.clone(), LinkType::Related).with_strength(0.734))
+            .unwrap();
+        let dot = render_dot(&build_graph(&db, &GraphFilter::default()).unwrap());
+
+        assert!(dot.contains("[label=\"related\", weight=73, penwidth=2.5]"), "{}", dot);
+        assert!(dot.contains("[label=\"tagged\", weight=100, penwidth=3.0, style=dashed]"), "{}", dot);
+    }
+}
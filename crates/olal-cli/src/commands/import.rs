@@ -0,0 +1,88 @@
+//! Import commands - bring in items exported from other tools.
+
+use super::get_database;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::Config;
+use olal_ingest::bookmarks::{self, Bookmark};
+use olal_ingest::{kindle, read_later, ChunkConfig, ImportSummary, IngestResult, Ingestor};
+use std::path::Path;
+
+/// Import a browser bookmarks export (HTML, or Chrome/Firefox JSON).
+pub fn bookmarks(file: &Path, fetch: bool) -> Result<()> {
+    import_bookmarks(file, "bookmarks", bookmarks::parse_file, fetch)
+}
+
+/// Import a Pocket export (HTML or CSV).
+pub fn pocket(file: &Path, fetch: bool) -> Result<()> {
+    import_bookmarks(file, "articles", read_later::parse_pocket_file, fetch)
+}
+
+/// Import an Instapaper CSV export.
+pub fn instapaper(file: &Path, fetch: bool) -> Result<()> {
+    import_bookmarks(file, "articles", read_later::parse_instapaper_file, fetch)
+}
+
+/// Import Kindle highlights from `My Clippings.txt`.
+pub fn kindle(file: &Path) -> Result<()> {
+    let config = Config::load().unwrap_or_default();
+    let books = kindle::parse_file(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    if books.is_empty() {
+        println!("{}", "No highlights found.".yellow());
+        return Ok(());
+    }
+
+    let summary = ingestor(&config)?.import_kindle(&books)?;
+    print_summary("highlights", summary);
+    println!("  from {} books", books.len());
+    Ok(())
+}
+
+fn import_bookmarks(
+    file: &Path,
+    what: &str,
+    parse: fn(&Path) -> IngestResult<Vec<Bookmark>>,
+    fetch: bool,
+) -> Result<()> {
+    let config = Config::load().unwrap_or_default();
+    let parsed = parse(file).with_context(|| format!("Failed to read {}", file.display()))?;
+    if parsed.is_empty() {
+        println!("{}", format!("No {} found.", what).yellow());
+        return Ok(());
+    }
+
+    let ingestor = ingestor(&config)?;
+    let summary = ingestor.import_bookmarks(&parsed, fetch)?;
+    print_summary(what, summary);
+
+    if fetch && summary.added > 0 {
+        if config.processing.fetch_interval_seconds == 0 {
+            println!(
+                "  {} fetching is off; set processing.fetch_interval_seconds to fetch pages",
+                "Note:".yellow()
+            );
+        } else {
+            println!(
+                "  Pages are fetched by 'olal worker start', one every {}s",
+                config.processing.fetch_interval_seconds
+            );
+        }
+    }
+    Ok(())
+}
+
+fn ingestor(config: &Config) -> Result<Ingestor> {
+    let db = get_database()?;
+    let chunk_config = ChunkConfig::from_processing_config(&config.processing);
+    Ok(Ingestor::new(db, chunk_config).with_events(super::event_emitter(config)))
+}
+
+fn print_summary(what: &str, summary: ImportSummary) {
+    println!("{} {} {}", "Imported:".green().bold(), summary.added, what);
+    if summary.updated > 0 {
+        println!("{} {} (extended or annotated)", "Updated:".cyan().bold(), summary.updated);
+    }
+    if summary.skipped > 0 {
+        println!("{} {} (already imported)", "Skipped:".yellow().bold(), summary.skipped);
+    }
+}
@@ -3,22 +3,30 @@
 use anyhow::Result;
 use olal_config::Config;
 use olal_core::ItemType;
-use olal_db::Database;
-use olal_ingest::{ChunkConfig, Ingestor};
+use olal_ingest::{archive, ChunkConfig, EventEmitter, Ingestor};
 use colored::Colorize;
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::Path;
 
-/// Ingest a single file or directory.
+/// Ingest a single file, archive or directory, or `-` for content piped on
+/// stdin.
 pub fn run(
     path: &str,
     item_type_filter: Option<String>,
+    title: Option<String>,
     dry_run: bool,
     queue: bool,
+    archive_item: bool,
 ) -> Result<()> {
     let config = Config::load().unwrap_or_default();
     let paths = olal_config::AppPaths::new().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
-    let db = Database::open(&paths.database_file)?;
+    let db = super::open_database(&paths.database_file)?;
+
+    if path == "-" {
+        let chunk_config = ChunkConfig::from_processing_config(&config.processing);
+        let ingestor = Ingestor::new(db, chunk_config).with_events(super::event_emitter(&config));
+        return ingest_stdin(ingestor, item_type_filter, title, dry_run);
+    }
 
     let path = Path::new(path);
     if !path.exists() {
@@ -38,7 +46,18 @@ pub fn run(
 
     // Create ingestor with config-based chunking settings
     let chunk_config = ChunkConfig::from_processing_config(&config.processing);
-    let ingestor = Ingestor::new(db, chunk_config);
+    let events = super::event_emitter(&config);
+    let ingestor = Ingestor::new(db, chunk_config)
+        .with_threads(config.processing.parse_threads)
+        .with_media_jobs(config.processing.max_concurrent_jobs)
+        .with_events(events.clone());
+
+    if path.is_file() && archive::is_archive(path) {
+        if queue {
+            return Err(anyhow::anyhow!("Archives can't be queued; ingest them directly"));
+        }
+        return ingest_archive(&ingestor, &events, path, dry_run, archive_item);
+    }
 
     if path.is_file() {
         // Single file
@@ -65,6 +84,7 @@ pub fn run(
             pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}")?);
             pb.set_message(format!("Ingesting {}", path.display()));
             pb.enable_steady_tick(std::time::Duration::from_millis(100));
+            super::progress::attach(&events, &pb);
 
             let result = ingestor.ingest_file(path)?;
 
@@ -72,7 +92,7 @@ pub fn run(
                 "{} {} ({} chunks)",
                 if result.was_update { "Updated:" } else { "Ingested:" }.green().bold(),
                 result.item.title,
-                result.chunks.len()
+                result.chunk_count
             ));
 
             println!("  ID: {}", result.item.id);
@@ -142,27 +162,38 @@ pub fn run(
         let mut failed = 0;
         let mut skipped = 0;
 
-        for entry in &files {
-            let filename = entry.path()
-                .file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("file");
-            pb.set_message(format!("{}", filename));
+        if queue {
+            for entry in &files {
+                let filename = entry.path()
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("file");
+                pb.set_message(filename.to_string());
 
-            if queue {
                 match ingestor.queue_file(entry.path(), 0) {
                     Ok(_) => success += 1,
                     Err(olal_ingest::IngestError::AlreadyProcessed(_)) => skipped += 1,
                     Err(_) => failed += 1,
                 }
-            } else {
-                match ingestor.ingest_file(entry.path()) {
+
+                pb.inc(1);
+            }
+        } else {
+            // Files are parsed in parallel and finish out of order; the
+            // ingestor's progress events name the stage each one is at
+            super::progress::attach(&events, &pb);
+            let paths: Vec<_> = files.iter().map(|e| e.path().to_path_buf()).collect();
+            ingestor.ingest_files(&paths, |path, result| {
+                match result {
                     Ok(_) => success += 1,
-                    Err(_) => failed += 1,
+                    Err(e) => {
+                        pb.println(format!("{} {}: {}", "Failed:".red(), path.display(), e));
+                        failed += 1;
+                    }
                 }
-            }
 
-            pb.inc(1);
+                pb.inc(1);
+            })?;
         }
 
         pb.finish_and_clear();
@@ -184,15 +215,213 @@ pub fn run(
     Ok(())
 }
 
+/// Ingest the text files of a git repository, optionally only those
+/// changed since `since`.
+pub fn run_git(path: &str, since: Option<&str>, dry_run: bool) -> Result<()> {
+    let config = Config::load().unwrap_or_default();
+    let paths = olal_config::AppPaths::new().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
+    let repo = Path::new(path);
+    if !repo.is_dir() {
+        return Err(anyhow::anyhow!("Not a directory: {}", repo.display()));
+    }
+
+    if dry_run {
+        let snapshot = olal_ingest::git::snapshot(repo, since)?;
+        println!("{} {} at {}", "Repository:".cyan(), snapshot.root.display(), &snapshot.commit[..8]);
+        for file in &snapshot.files {
+            println!("  {}", file.relative);
+        }
+        println!("\n{} files", snapshot.files.len());
+        println!("{}", "Dry run - no files were ingested.".cyan());
+        return Ok(());
+    }
+
+    let db = super::open_database(&paths.database_file)?;
+    let chunk_config = ChunkConfig::from_processing_config(&config.processing);
+    let events = super::event_emitter(&config);
+    let ingestor = Ingestor::new(db, chunk_config)
+        .with_threads(config.processing.parse_threads)
+        .with_media_jobs(config.processing.max_concurrent_jobs)
+        .with_events(events.clone());
+
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}")?);
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+    super::progress::attach(&events, &pb);
+
+    let mut success = 0;
+    let mut failed = 0;
+    let snapshot = ingestor.ingest_git(repo, since, |path, result| match result {
+        Ok(_) => success += 1,
+        Err(e) => {
+            pb.println(format!("{} {}: {}", "Failed:".red(), path.display(), e));
+            failed += 1;
+        }
+    })?;
+    pb.finish_and_clear();
+
+    println!(
+        "{} {} files from {} at {}",
+        "Ingested:".green().bold(),
+        success,
+        snapshot.root.display(),
+        &snapshot.commit[..8]
+    );
+    if failed > 0 {
+        println!("{} {} files", "Failed:".red().bold(), failed);
+    }
+    println!("  Next time: olal ingest --git {} --since {}", path, &snapshot.commit[..12]);
+    Ok(())
+}
+
+/// Download and ingest an arXiv paper by ID or URL.
+pub fn run_arxiv(id: &str, dry_run: bool) -> Result<()> {
+    let config = Config::load().unwrap_or_default();
+
+    if dry_run {
+        let fetcher = olal_ingest::web::PageFetcher::new(std::time::Duration::from_secs(30))?;
+        let paper = olal_ingest::arxiv::fetch(&fetcher, id)?;
+        println!("{} {}", "Paper:".cyan(), paper.title);
+        println!("  {}", paper.authors.join(", "));
+        println!("  {} ({})", paper.abs_url(), paper.categories.join(", "));
+        println!("{}", "Dry run - nothing was downloaded.".cyan());
+        return Ok(());
+    }
+
+    let paths = olal_config::AppPaths::new().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
+    let db = super::open_database(&paths.database_file)?;
+    let chunk_config = ChunkConfig::from_processing_config(&config.processing);
+    let events = super::event_emitter(&config);
+    let ingestor = Ingestor::new(db, chunk_config).with_events(events.clone());
+
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}")?);
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+    pb.set_message(format!("Downloading {}", id));
+    super::progress::attach(&events, &pb);
+    let result = ingestor.ingest_arxiv(id);
+    pb.finish_and_clear();
+
+    let result = result?;
+    println!(
+        "{} {} ({} chunks)",
+        if result.was_update { "Updated:" } else { "Ingested:" }.green().bold(),
+        result.item.title,
+        result.chunk_count
+    );
+    println!("  ID: {}", result.item.id);
+    Ok(())
+}
+
+/// Ingest the supported files in an archive, optionally keeping an item for
+/// the archive itself.
+fn ingest_archive(
+    ingestor: &Ingestor,
+    events: &EventEmitter,
+    path: &Path,
+    dry_run: bool,
+    archive_item: bool,
+) -> Result<()> {
+    println!("{} {}", "Unpacking:".cyan(), path.display());
+
+    if dry_run {
+        let entries = archive::list(path)?;
+        if entries.is_empty() {
+            println!("{}", "No supported files found.".yellow());
+            return Ok(());
+        }
+        for entry in &entries {
+            let item_type = Path::new(entry)
+                .extension()
+                .and_then(|e| e.to_str())
+                .and_then(ItemType::from_extension)
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            println!("  {} [{}]", entry, item_type);
+        }
+        println!("\n{}", "Dry run - no files were ingested.".cyan());
+        return Ok(());
+    }
+
+    let pb = ProgressBar::new_spinner();
+    pb.set_style(ProgressStyle::default_spinner().template("{spinner:.green} {msg}")?);
+    pb.enable_steady_tick(std::time::Duration::from_millis(100));
+    super::progress::attach(events, &pb);
+
+    let mut success = 0;
+    let mut failed = 0;
+    let parent = ingestor.ingest_archive(path, archive_item, |member, result| match result {
+        Ok(_) => success += 1,
+        Err(e) => {
+            pb.println(format!("{} {}: {}", "Failed:".red(), member.entry, e));
+            failed += 1;
+        }
+    })?;
+    pb.finish_and_clear();
+
+    if success + failed == 0 {
+        println!("{}", "No supported files found.".yellow());
+    } else {
+        println!("\n{} {} files", "Ingested:".green().bold(), success);
+    }
+    if failed > 0 {
+        println!("{} {} files", "Failed:".red().bold(), failed);
+    }
+    if let Some(parent) = parent {
+        println!("  Archive item: {}", parent.id);
+    }
+    Ok(())
+}
+
+/// Ingest content piped on stdin; `item_type` is a hint (default: note).
+fn ingest_stdin(
+    ingestor: Ingestor,
+    item_type: Option<String>,
+    title: Option<String>,
+    dry_run: bool,
+) -> Result<()> {
+    let item_type = match item_type {
+        Some(t) => ItemType::from_str(&t).ok_or_else(|| anyhow::anyhow!("Unknown item type: {}", t))?,
+        None => ItemType::Note,
+    };
+    let content = super::read_stdin()?;
+
+    if dry_run {
+        println!(
+            "{} {} from stdin ({} bytes)",
+            "Would ingest:".cyan(),
+            item_type,
+            content.len()
+        );
+        return Ok(());
+    }
+
+    let metadata = serde_json::json!({
+        "source": "stdin",
+        "captured_at": chrono::Utc::now().to_rfc3339(),
+    });
+    let result = ingestor.ingest_text(&content, item_type, title.as_deref(), metadata)?;
+
+    println!(
+        "{} {} ({} chunks)",
+        "Ingested:".green().bold(),
+        result.item.title,
+        result.chunk_count
+    );
+    println!("  ID: {}", result.item.id);
+    println!("  Type: {}", result.item.item_type);
+    Ok(())
+}
+
 /// Process all pending items in the queue.
 #[allow(dead_code)]
 pub fn process_queue() -> Result<()> {
     let config = Config::load().unwrap_or_default();
     let paths = olal_config::AppPaths::new().ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?;
-    let db = Database::open(&paths.database_file)?;
+    let db = super::open_database(&paths.database_file)?;
 
     let chunk_config = ChunkConfig::from_processing_config(&config.processing);
-    let ingestor = Ingestor::new(db, chunk_config);
+    let ingestor = Ingestor::new(db, chunk_config).with_events(super::event_emitter(&config));
 
     println!("{}", "Processing queue...".cyan());
 
@@ -28,17 +28,29 @@ pub fn run() -> Result<()> {
         .context("Failed to create directories")?;
     println!("  {} Created directories", "✓".green());
 
-    // Create config file
-    Config::create_default_file(&paths.config_file)
-        .context("Failed to create config file")?;
-    println!(
-        "  {} Created config: {}",
-        "✓".green(),
-        paths.config_file.display()
-    );
+    // Create config file, keeping one that already exists
+    if paths.config_file.exists() {
+        println!(
+            "  {} Using existing config: {}",
+            "✓".green(),
+            paths.config_file.display()
+        );
+    } else {
+        Config::create_default_file(&paths.config_file)
+            .context("Failed to create config file")?;
+        println!(
+            "  {} Created config: {}",
+            "✓".green(),
+            paths.config_file.display()
+        );
+    }
 
     // Initialize database
-    let _db = Database::open(&paths.database_file).context("Failed to initialize database")?;
+    let db = Database::open(&paths.database_file).context("Failed to initialize database")?;
+    // A kept config may ask for another tokenizer than the default
+    if let Ok(config) = Config::load() {
+        super::sync_tokenizer(&db, config.search.tokenizer)?;
+    }
     println!(
         "  {} Created database: {}",
         "✓".green(),
@@ -61,6 +73,10 @@ pub fn run() -> Result<()> {
         "  3. Check status: {}",
         "olal status".cyan()
     );
+    println!(
+        "  4. Take the tour: {}",
+        "olal tutorial".cyan()
+    );
 
     Ok(())
 }
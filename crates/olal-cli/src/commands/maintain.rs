@@ -0,0 +1,37 @@
+//! Maintain command - knowledge-graph maintenance pass.
+
+use super::get_database;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use olal_config::Config;
+use olal_ingest::maintenance;
+
+/// Run maintenance now and save the report as a note.
+pub fn run(dry_run: bool) -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+
+    println!("{}", "Running maintenance...".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    let report = maintenance::run_maintenance(&db, &config, dry_run)
+        .context("Maintenance failed")?;
+
+    println!("{}", report.to_markdown(Utc::now()));
+
+    if dry_run {
+        println!("{}", "Dry run - nothing was changed or saved.".dimmed());
+        return Ok(());
+    }
+
+    let item = maintenance::save_report(&db, &report).context("Failed to save report")?;
+
+    println!(
+        "{} Report saved as note [{}]",
+        "✓".green(),
+        item.id.chars().take(8).collect::<String>()
+    );
+
+    Ok(())
+}
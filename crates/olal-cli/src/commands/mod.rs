@@ -1,28 +1,72 @@
 //! CLI command implementations.
 
+pub mod archive;
 pub mod ask;
+pub mod backup;
+pub mod board;
 pub mod capture;
 pub mod clips;
+pub mod compact;
 pub mod config;
+pub mod dates;
+pub mod dedupe;
+pub mod delete;
+#[cfg(feature = "dev")]
+pub mod dev;
 pub mod digest;
+pub mod email;
 pub mod embed;
+pub mod encrypt;
+pub mod entity;
+pub mod flashcards;
+pub mod graph;
+pub mod import;
 pub mod ingest;
 pub mod init;
+pub mod maintain;
+pub mod note;
+pub mod notifications;
+pub mod output;
+pub mod progress;
 pub mod project;
+pub mod prompts;
+pub mod queue;
+pub mod quicksearch;
+pub mod recall;
 pub mod recent;
+pub mod related;
+pub mod resolve;
+pub mod retention;
+pub mod review;
+pub mod schedule;
 pub mod search;
+pub mod secret;
+pub mod serve;
 pub mod shell;
 pub mod show;
 pub mod stats;
 pub mod status;
 pub mod tag;
 pub mod task;
+pub mod translate;
+pub mod tutorial;
+pub mod usage;
+pub mod vault;
 pub mod watch;
+pub mod worker;
 pub mod youtube;
 
-use olal_config::AppPaths;
-use olal_db::Database;
+use olal_config::{AppPaths, Config, ConfigError, FtsTokenizer, OllamaConfig};
+use olal_core::{Diagnostic, ErrorCode};
+use olal_db::fts::Tokenizer;
+use olal_db::{Database, DbError};
+use olal_ingest::{webhooks, EventEmitter, IngestError};
+use olal_ollama::{ModelUse, OllamaClient, OllamaError};
+use olal_process::ProcessError;
 use anyhow::{Context, Result};
+use colored::Colorize;
+use std::path::Path;
+use tokio::runtime::Runtime;
 
 /// Get the application paths.
 pub fn get_paths() -> Result<AppPaths> {
@@ -34,12 +78,137 @@ pub fn get_database() -> Result<Database> {
     let paths = get_paths()?;
 
     if !paths.is_initialized() {
-        anyhow::bail!(
-            "Olal is not initialized. Run 'olal init' first."
-        );
+        return Err(olal_core::Error::NotInitialized.into());
     }
 
-    Database::open(&paths.database_file).context("Failed to open database")
+    open_database(&paths.database_file)
+}
+
+/// Get a database connection for a command that searches. The full-text
+/// index is rebuilt first if `search.tokenizer` has changed since it was
+/// built; other commands leave it alone, so a fast path like
+/// `status --short` never turns into a reindex.
+pub fn get_search_database() -> Result<Database> {
+    let db = get_database()?;
+    if let Ok(config) = Config::load() {
+        sync_tokenizer(&db, config.search.tokenizer)?;
+    }
+    Ok(db)
+}
+
+/// Open a database file, unlocking it first if it is encrypted.
+pub fn open_database(path: &Path) -> Result<Database> {
+    let key = encrypt::database_key(path)?;
+    Database::open_with_key(path, key.as_deref()).context("Failed to open database")
+}
+
+/// Rebuild the full-text index unless it was built with `tokenizer`.
+fn sync_tokenizer(db: &Database, tokenizer: FtsTokenizer) -> Result<()> {
+    let tokenizer = match tokenizer {
+        FtsTokenizer::Unicode61 => Tokenizer::Unicode61,
+        FtsTokenizer::Porter => Tokenizer::Porter,
+    };
+    if db.fts_tokenizer()? == Some(tokenizer) {
+        return Ok(());
+    }
+    eprintln!(
+        "{} Rebuilding the search index with the {} tokenizer...",
+        "→".cyan(),
+        tokenizer.as_str()
+    );
+    let indexed = db.rebuild_fts(tokenizer).context("Failed to rebuild the search index")?;
+    eprintln!("{} Indexed {} chunks", "✓".green(), indexed);
+    Ok(())
+}
+
+/// Event emitter with the listeners configured in `config` (webhooks and
+/// desktop notifications). Pending webhooks are delivered when the last
+/// clone is dropped.
+pub fn event_emitter(config: &Config) -> EventEmitter {
+    let emitter = EventEmitter::new();
+    webhooks::attach(&emitter, &config.webhooks);
+    if config.ui.notifications {
+        notifications::attach(&emitter);
+    }
+    emitter
+}
+
+/// A connection to Ollama, with a runtime to call it from synchronous code.
+/// The shell keeps one for its whole session.
+pub struct Ollama {
+    pub rt: Runtime,
+    pub client: OllamaClient,
+}
+
+impl Ollama {
+    /// Connect to the configured Ollama server, failing if it isn't running.
+    pub fn connect(config: &OllamaConfig) -> Result<Self> {
+        let client = OllamaClient::from_config(config).context("Failed to create Ollama client")?;
+        let rt = Runtime::new().context("Failed to create async runtime")?;
+        if !rt.block_on(client.is_available()) {
+            return Err(OllamaError::ServerNotRunning {
+                host: config.host.clone(),
+            }
+            .into());
+        }
+        Ok(Self { rt, client })
+    }
+}
+
+/// Warn if `model` can't be used for `usage`, e.g. an embedding-only model
+/// picked for answers. Models Ollama can't describe aren't warned about.
+pub fn warn_model_use(rt: &Runtime, client: &OllamaClient, model: &str, usage: ModelUse) {
+    let Ok(details) = rt.block_on(client.show_model(model)) else {
+        return;
+    };
+    if details.supports(usage) {
+        return;
+    }
+    let problem = match usage {
+        ModelUse::Generate => "is an embedding model and can't generate text; pick a chat model",
+        ModelUse::Embed => "can't generate embeddings; pick an embedding model like nomic-embed-text",
+    };
+    println!("{} '{}' {}", "Warning:".yellow(), model, problem);
+}
+
+/// Read piped input, for commands that accept `-` in place of content.
+pub fn read_stdin() -> Result<String> {
+    use std::io::{IsTerminal, Read};
+
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        anyhow::bail!("Nothing piped to stdin (e.g. `pbpaste | olal capture -`)");
+    }
+    let mut content = String::new();
+    stdin
+        .read_to_string(&mut content)
+        .context("Failed to read stdin")?;
+    Ok(content)
+}
+
+/// Open a file in the user's editor ($EDITOR) and wait for it to close.
+pub fn open_in_editor(path: &Path) -> Result<()> {
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| {
+        if cfg!(target_os = "macos") {
+            "open -W -t".to_string()
+        } else {
+            "nano".to_string()
+        }
+    });
+
+    let parts: Vec<&str> = editor.split_whitespace().collect();
+    let (cmd, args) = parts.split_first().context("Invalid editor command")?;
+
+    let status = std::process::Command::new(cmd)
+        .args(args)
+        .arg(path)
+        .status()
+        .context("Failed to open editor")?;
+
+    if !status.success() {
+        anyhow::bail!("Editor exited with error");
+    }
+    Ok(())
 }
 
 /// Format a file size in human-readable form.
@@ -58,3 +227,55 @@ pub fn format_size(bytes: i64) -> String {
         format!("{} bytes", bytes)
     }
 }
+
+/// Classify a command's error: the code and hint of the first error in its
+/// chain that has one, so context added with `.context()` doesn't hide it.
+pub fn diagnose(error: &anyhow::Error) -> (ErrorCode, Option<String>) {
+    for cause in error.chain() {
+        let diagnostic: Option<&dyn Diagnostic> = if let Some(e) = cause.downcast_ref::<olal_core::Error>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<DbError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<ConfigError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<IngestError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<OllamaError>() {
+            Some(e)
+        } else if let Some(e) = cause.downcast_ref::<ProcessError>() {
+            Some(e)
+        } else {
+            None
+        };
+        if let Some(diagnostic) = diagnostic {
+            return (diagnostic.code(), diagnostic.hint());
+        }
+        if cause.is::<std::io::Error>() {
+            return (ErrorCode::Io, None);
+        }
+    }
+    (ErrorCode::Internal, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_diagnose() {
+        let error = Err::<(), _>(OllamaError::ModelNotFound {
+            model: "nomic-embed-text".to_string(),
+        })
+        .context("Failed to embed")
+        .unwrap_err();
+        let (code, hint) = diagnose(&error);
+        assert_eq!(code, ErrorCode::ModelMissing);
+        assert_eq!(hint.as_deref(), Some("Run 'ollama pull nomic-embed-text' to download it."));
+
+        let wrapped = anyhow::Error::new(IngestError::Database(DbError::NotFound("x".to_string())));
+        assert_eq!(diagnose(&wrapped).0, ErrorCode::NotFound);
+
+        assert_eq!(diagnose(&anyhow::anyhow!("Cancelled")), (ErrorCode::Internal, None));
+    }
+}
@@ -0,0 +1,89 @@
+//! Note commands - edit captured notes in $EDITOR.
+
+use super::embed::embed_item_quietly;
+use super::{get_database, open_in_editor, resolve};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::Config;
+use olal_core::ItemType;
+use olal_ingest::{ChunkConfig, Ingestor};
+use std::fs;
+
+/// Edit a note's text in $EDITOR, then re-chunk, re-enrich and re-embed it.
+pub fn edit(id: &str) -> Result<()> {
+    let db = get_database()?;
+    let item = resolve::item(&db, id)?;
+
+    if item.item_type != ItemType::Note {
+        anyhow::bail!(
+            "'{}' is a {}, not a note. Only notes can be edited.",
+            item.title,
+            item.item_type
+        );
+    }
+    if let Some(ref path) = item.source_path {
+        anyhow::bail!(
+            "'{}' comes from {}. Edit that file and run 'olal ingest' again.",
+            item.title,
+            path
+        );
+    }
+
+    // Notes captured before content was stored only have their chunks
+    let original = match db.get_item_content(&item.id)? {
+        Some(content) => content,
+        None => db
+            .get_chunks_by_item(&item.id)?
+            .into_iter()
+            .map(|c| c.content)
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+    };
+
+    let path = std::env::temp_dir().join(format!(
+        "olal-note-{}.md",
+        item.id.chars().take(8).collect::<String>()
+    ));
+    fs::write(&path, &original).context("Failed to write temporary file")?;
+    let edited = open_in_editor(&path).and_then(|_| {
+        fs::read_to_string(&path).context("Failed to read edited note")
+    });
+    let _ = fs::remove_file(&path);
+    let edited = edited?;
+
+    if edited.trim() == original.trim() {
+        println!("{} No changes.", "Note:".yellow());
+        return Ok(());
+    }
+    if edited.trim().is_empty() {
+        anyhow::bail!("Note is empty; use 'olal delete' to remove it");
+    }
+
+    let config = Config::load().context("Failed to load configuration")?;
+    let chunk_config = ChunkConfig::from_processing_config(&config.processing);
+    let ingestor = Ingestor::new(db.clone(), chunk_config).with_events(super::event_emitter(&config));
+    let result = ingestor
+        .replace_text(&item, edited.trim())
+        .context("Failed to update note")?;
+
+    println!("{} Updated note", "✓".green());
+    println!();
+    println!(
+        "  {} {}",
+        "ID:".cyan(),
+        item.id.chars().take(8).collect::<String>()
+    );
+    println!("  {}: {}", "Title".cyan(), result.item.title);
+    println!("  {}: {}", "Chunks".cyan(), result.chunk_count);
+
+    match embed_item_quietly(&db, &item.id) {
+        Ok(Some(count)) => println!("  {}: {}", "Embedded".cyan(), count),
+        Ok(None) => println!(
+            "  {}",
+            "Ollama unavailable; run 'olal embed --all' later.".dimmed()
+        ),
+        Err(e) => println!("  {} Embedding failed: {}", "Warning:".yellow(), e),
+    }
+
+    Ok(())
+}
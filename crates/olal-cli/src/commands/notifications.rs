@@ -0,0 +1,70 @@
+//! Desktop notifications for long-running jobs (`[ui] notifications`).
+
+use notify_rust::Notification;
+use olal_ingest::{Event, EventEmitter};
+use tracing::debug;
+
+/// Show a desktop notification when a video finishes processing or a
+/// digest is generated.
+pub fn attach(emitter: &EventEmitter) {
+    emitter.subscribe(|event| {
+        if let Some((summary, body)) = message(event) {
+            if let Err(e) = Notification::new().appname("olal").summary(&summary).body(&body).show() {
+                debug!("Desktop notification failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Notification summary and body for `event`, if it warrants one.
+fn message(event: &Event) -> Option<(String, String)> {
+    match event {
+        Event::ItemProcessed {
+            title,
+            item_type,
+            chunk_count,
+            ..
+        } if item_type == "video" => Some((
+            "Video processed".to_string(),
+            format!("{} ({} chunks)", title, chunk_count),
+        )),
+        Event::DigestGenerated {
+            period,
+            item_count,
+            output,
+            ..
+        } => {
+            let mut body = format!("{} digest of {} items", period, item_count);
+            if let Some(output) = output {
+                body.push_str(&format!("\nSaved to {}", output));
+            }
+            Some(("Digest ready".to_string(), body))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, ItemType};
+
+    #[test]
+    fn test_messages() {
+        let video = Item::new(ItemType::Video, "Debugging session");
+        let (summary, body) = message(&Event::item_processed(&video, 12)).unwrap();
+        assert_eq!(summary, "Video processed");
+        assert_eq!(body, "Debugging session (12 chunks)");
+
+        let note = Item::new(ItemType::Note, "Idea");
+        assert!(message(&Event::item_processed(&note, 1)).is_none());
+
+        let digest = Event::DigestGenerated {
+            period: "Weekly".to_string(),
+            item_count: 4,
+            output: None,
+            digest: String::new(),
+        };
+        assert_eq!(message(&digest).unwrap().1, "Weekly digest of 4 items");
+    }
+}
@@ -0,0 +1,74 @@
+//! Terminal output settings: color and paging.
+//!
+//! Colors are off when `ui.color = false`, `NO_COLOR` is set or stdout is
+//! not a terminal (`CLICOLOR_FORCE` turns them back on). Long output
+//! (digests, `show --chunks`) goes through `ui.pager` when stdout is a
+//! terminal; an empty pager or `--no-pager` prints it directly.
+
+use anyhow::{Context, Result};
+use olal_config::Config;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+
+static PAGER: OnceLock<Option<String>> = OnceLock::new();
+
+/// Apply the `[ui]` settings. Call once, before any output.
+pub fn init(no_pager: bool) {
+    let ui = Config::load().map(|c| c.ui).unwrap_or_default();
+
+    // colored already handles NO_COLOR, CLICOLOR_FORCE and non-terminals
+    let forced = std::env::var("CLICOLOR_FORCE").is_ok_and(|v| v != "0");
+    if !ui.color && !forced {
+        colored::control::set_override(false);
+    }
+
+    let pager = Some(ui.pager.trim().to_string()).filter(|p| !no_pager && !p.is_empty());
+    let _ = PAGER.set(pager);
+}
+
+/// Show `text` through the pager, or print it if paging is off or stdout
+/// isn't a terminal.
+pub fn page(text: &str) -> Result<()> {
+    let pager = PAGER.get().cloned().flatten();
+    match pager {
+        Some(pager) if io::stdout().is_terminal() => run_pager(&pager, text),
+        _ => {
+            print!("{}", text);
+            Ok(())
+        }
+    }
+}
+
+fn run_pager(pager: &str, text: &str) -> Result<()> {
+    let parts: Vec<&str> = pager.split_whitespace().collect();
+    let (cmd, args) = parts.split_first().context("Invalid pager command")?;
+
+    let mut command = Command::new(cmd);
+    command.args(args).stdin(Stdio::piped());
+    // Keep colors, and don't page output that fits on one screen
+    if std::env::var_os("LESS").is_none() {
+        command.env("LESS", "FRX");
+    }
+
+    let mut child = match command.spawn() {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!("Failed to start pager '{}': {}", pager, e);
+            print!("{}", text);
+            return Ok(());
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // The pager closing early (the user quit) is not an error
+        match stdin.write_all(text.as_bytes()) {
+            Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+                return Err(e).context("Failed to write to pager")
+            }
+            _ => {}
+        }
+    }
+    child.wait().context("Failed to wait for pager")?;
+    Ok(())
+}
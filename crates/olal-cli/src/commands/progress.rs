@@ -0,0 +1,62 @@
+//! Live ingestion progress from the ingestor's `ingest.*` events.
+
+use indicatif::ProgressBar;
+use olal_ingest::{Event, EventEmitter};
+use std::path::Path;
+
+/// Show each file's current stage as the message of `pb`.
+pub fn attach(emitter: &EventEmitter, pb: &ProgressBar) {
+    let pb = pb.clone();
+    emitter.subscribe(move |event| {
+        if let Some(msg) = message(event) {
+            pb.set_message(msg);
+        }
+    });
+}
+
+/// Progress message for `event`, if it's an `ingest.*` event.
+fn message(event: &Event) -> Option<String> {
+    match event {
+        Event::IngestStarted { path, index, total } if *total > 1 => {
+            Some(format!("Parsing {} ({}/{})", file_name(path), index, total))
+        }
+        Event::IngestStarted { path, .. } => Some(format!("Parsing {}", file_name(path))),
+        Event::IngestParsed { title, .. } => Some(format!("Chunking {}", title)),
+        Event::IngestChunked { chunk_count, kept, .. } if *kept > 0 => Some(format!(
+            "Enriching ({} chunks, {} unchanged)",
+            chunk_count, kept
+        )),
+        Event::IngestChunked { chunk_count, .. } => Some(format!("Enriching ({} chunks)", chunk_count)),
+        Event::IngestEnriched { tag_count, .. } => Some(format!("Enriched ({} tags)", tag_count)),
+        Event::IngestFailed { path, error } => Some(format!("Failed {}: {}", file_name(path), error)),
+        _ => None,
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    Path::new(path).file_name().and_then(|n| n.to_str()).unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, ItemType};
+
+    #[test]
+    fn test_messages() {
+        let started = Event::IngestStarted {
+            path: "/notes/idea.md".to_string(),
+            index: 2,
+            total: 5,
+        };
+        assert_eq!(message(&started).unwrap(), "Parsing idea.md (2/5)");
+
+        let item = Item::new(ItemType::Note, "Idea");
+        assert_eq!(message(&Event::ingest_chunked(&item, 4, 3)).unwrap(), "Enriching (4 chunks, 3 unchanged)");
+        assert_eq!(
+            message(&Event::ingest_failed(Path::new("/notes/bad.pdf"), "broken")).unwrap(),
+            "Failed bad.pdf: broken"
+        );
+        assert!(message(&Event::item_processed(&item, 4)).is_none());
+    }
+}
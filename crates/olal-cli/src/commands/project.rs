@@ -1,8 +1,8 @@
 //! Project management commands.
 
-use super::get_database;
+use super::{dates, get_database, resolve};
 use anyhow::Result;
-use olal_core::{Project, ProjectStatus, TaskStatus};
+use olal_core::{ItemType, Project, ProjectStatus, TaskStatus};
 use colored::Colorize;
 
 pub fn create(name: &str, description: Option<String>) -> Result<()> {
@@ -79,9 +79,7 @@ pub fn list() -> Result<()> {
 pub fn show(name: &str) -> Result<()> {
     let db = get_database()?;
 
-    let project = db
-        .get_project_by_name(name)?
-        .ok_or_else(|| anyhow::anyhow!("Project not found: {}", name))?;
+    let project = resolve::project(&db, name)?;
 
     let status_icon = match project.status {
         ProjectStatus::Active => "●".green(),
@@ -105,7 +103,7 @@ pub fn show(name: &str) -> Result<()> {
     println!(
         "  {}: {}",
         "Created".cyan(),
-        project.created_at.format("%Y-%m-%d %H:%M")
+        dates::format_datetime(project.created_at)
     );
 
     if let Some(ref desc) = project.description {
@@ -137,5 +135,75 @@ pub fn show(name: &str) -> Result<()> {
         }
     }
 
+    // List knowledge items
+    let items = db.list_project_items(&project.id)?;
+    if !items.is_empty() {
+        println!();
+        println!("{} ({})", "Items".white().bold(), items.len());
+        println!("{}", "─".repeat(70));
+
+        for item in items {
+            let type_icon = match item.item_type {
+                ItemType::Video => "🎬",
+                ItemType::Audio => "🎵",
+                ItemType::Document => "📄",
+                ItemType::Note => "📝",
+                ItemType::Code => "💻",
+                ItemType::Image => "🖼️",
+                ItemType::Bookmark => "🔖",
+            };
+
+            println!(
+                "  {} {} {}",
+                type_icon,
+                item.title,
+                format!("[{}]", item.id.chars().take(8).collect::<String>()).dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+pub fn add_item(name: &str, item_id: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let project = resolve::project(&db, name)?;
+    let item = resolve::item(&db, item_id)?;
+
+    if db.add_item_to_project(&project.id, &item.id)? {
+        println!(
+            "{} Added '{}' to project '{}'",
+            "✓".green(),
+            item.title.white(),
+            project.name.cyan()
+        );
+    } else {
+        println!(
+            "{}",
+            format!("'{}' is already in project '{}'", item.title, project.name).dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+pub fn remove_item(name: &str, item_id: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let project = resolve::project(&db, name)?;
+    let item = resolve::item(&db, item_id)?;
+
+    if !db.remove_item_from_project(&project.id, &item.id)? {
+        anyhow::bail!("'{}' is not in project '{}'", item.title, project.name);
+    }
+
+    println!(
+        "{} Removed '{}' from project '{}'",
+        "✓".green(),
+        item.title.white(),
+        project.name.cyan()
+    );
+
     Ok(())
 }
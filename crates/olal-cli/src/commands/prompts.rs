@@ -0,0 +1,103 @@
+//! Prompts command - inspect and override the prompt templates.
+
+use super::{get_paths, open_in_editor};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_ollama::{Prompt, PromptTemplates};
+use std::fs;
+
+/// List every prompt, marking the overridden ones.
+pub fn list() -> Result<()> {
+    let dir = get_paths()?.prompts_dir();
+    let templates = PromptTemplates::load(&dir);
+
+    println!("{}", "Prompt Templates".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    for prompt in Prompt::all() {
+        let status = if let Some(error) = templates.error(*prompt) {
+            format!("[broken: {}]", error).red().to_string()
+        } else if templates.is_overridden(*prompt) {
+            "[custom]".green().to_string()
+        } else {
+            String::new()
+        };
+        println!(
+            "  {:<20} {} {}",
+            prompt.name().white(),
+            prompt.description().dimmed(),
+            status
+        );
+    }
+
+    println!();
+    println!("Overrides: {}", dir.display());
+    println!("Customize one with: olal prompts edit <name>");
+    Ok(())
+}
+
+/// Print the template in use for a prompt.
+pub fn show(name: &str) -> Result<()> {
+    let prompt = parse(name)?;
+    let dir = get_paths()?.prompts_dir();
+    let templates = PromptTemplates::load(&dir);
+
+    let origin = if templates.is_overridden(prompt) {
+        prompt.override_path(&dir).display().to_string()
+    } else {
+        "built-in".to_string()
+    };
+    println!("{} {}", prompt.name().cyan().bold(), format!("({})", origin).dimmed());
+    if !prompt.variables().is_empty() {
+        println!("{} {}", "Variables:".dimmed(), prompt.variables().join(", ").dimmed());
+    }
+    println!("{}", "─".repeat(50));
+    print!("{}", templates.source(prompt));
+    Ok(())
+}
+
+/// Open a prompt's override in the editor, starting from the built-in.
+pub fn edit(name: &str) -> Result<()> {
+    let prompt = parse(name)?;
+    let dir = get_paths()?.prompts_dir();
+    let path = prompt.override_path(&dir);
+
+    if !path.exists() {
+        fs::create_dir_all(&dir).context("Failed to create prompts directory")?;
+        fs::write(&path, prompt.builtin()).context("Failed to write prompt template")?;
+    }
+    open_in_editor(&path)?;
+
+    let templates = PromptTemplates::load(&dir);
+    if let Some(error) = templates.error(prompt) {
+        println!("{} {}", "Warning:".yellow().bold(), error);
+        println!("olal will report this error until the template is fixed or reset.");
+    } else {
+        println!("{} Saved {}", "✓".green(), path.display());
+    }
+    Ok(())
+}
+
+/// Delete a prompt's override, going back to the built-in template.
+pub fn reset(name: &str) -> Result<()> {
+    let prompt = parse(name)?;
+    let path = prompt.override_path(&get_paths()?.prompts_dir());
+
+    if !path.exists() {
+        println!("{} already uses the built-in template", prompt.name());
+        return Ok(());
+    }
+    fs::remove_file(&path).context("Failed to remove prompt template")?;
+    println!("{} Reset {} to the built-in template", "✓".green(), prompt.name());
+    Ok(())
+}
+
+fn parse(name: &str) -> Result<Prompt> {
+    match Prompt::from_name(name) {
+        Some(prompt) => Ok(prompt),
+        None => {
+            let names: Vec<_> = Prompt::all().iter().map(|p| p.name()).collect();
+            anyhow::bail!("Unknown prompt '{}'. Available: {}", name, names.join(", "))
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Queue commands - inspect, retry, clear and process the ingestion queue.
+
+use super::get_database;
+use anyhow::{Context, Result};
+use colored::{ColoredString, Colorize};
+use olal_config::Config;
+use olal_core::{QueueItem, QueueStatus};
+use olal_ingest::{ChunkConfig, Ingestor};
+
+/// List queue entries, optionally filtered by status.
+pub fn list(status_filter: Option<String>) -> Result<()> {
+    let db = get_database()?;
+
+    let status = match status_filter {
+        Some(ref s) => Some(QueueStatus::from_str(s).ok_or_else(|| {
+            anyhow::anyhow!("Invalid status. Valid values: pending, processing, done, failed")
+        })?),
+        None => None,
+    };
+
+    let entries = db.list_queue(status)?;
+    if entries.is_empty() {
+        println!(
+            "{}",
+            "Queue is empty. Use 'olal ingest <path> --queue' to add files.".dimmed()
+        );
+        return Ok(());
+    }
+
+    println!("{}", "Processing Queue".cyan().bold());
+    println!("{}", "─".repeat(70));
+
+    for entry in &entries {
+        println!(
+            "{} {} {} {}",
+            status_icon(entry.status),
+            file_name(entry),
+            format!("[{}]", entry.id.chars().take(8).collect::<String>()).dimmed(),
+            format!("{} · {}", entry.item_type, entry.status).dimmed()
+        );
+        if entry.attempts > 1 {
+            println!("    {} attempts", entry.attempts.to_string().yellow());
+        }
+        if let Some(ref err) = entry.error {
+            println!("    {}", err.red());
+        }
+    }
+
+    println!();
+    println!("{} entries", entries.len());
+
+    Ok(())
+}
+
+/// Reset a failed entry (or all failed entries) to pending.
+pub fn retry(id: Option<String>, all_failed: bool) -> Result<()> {
+    let db = get_database()?;
+
+    if all_failed {
+        let count = db.retry_all_failed()?;
+        if count == 0 {
+            println!("{}", "No failed entries to retry.".dimmed());
+        } else {
+            println!("{} Reset {} failed entries to pending", "✓".green(), count);
+            println!("  Run {} to process them.", "olal queue process".cyan());
+        }
+        return Ok(());
+    }
+
+    let id = id.ok_or_else(|| anyhow::anyhow!("Specify a queue ID or --all-failed"))?;
+    let entry = db.get_queue_item_by_prefix(&id)?;
+    if entry.status != QueueStatus::Failed {
+        anyhow::bail!(
+            "Queue entry {} is {}, only failed entries can be retried",
+            entry.id.chars().take(8).collect::<String>(),
+            entry.status
+        );
+    }
+
+    db.retry(&entry.id)?;
+    println!("{} Queued again: {}", "✓".green(), file_name(&entry));
+
+    Ok(())
+}
+
+/// Remove finished entries from the queue.
+pub fn clear(done: bool, failed: bool) -> Result<()> {
+    if !done && !failed {
+        anyhow::bail!("Specify what to clear: --done, --failed, or both");
+    }
+
+    let db = get_database()?;
+
+    if done {
+        let count = db.clear_completed()?;
+        println!("{} Cleared {} completed entries", "✓".green(), count);
+    }
+    if failed {
+        let count = db.clear_failed()?;
+        println!("{} Cleared {} failed entries", "✓".green(), count);
+    }
+
+    Ok(())
+}
+
+/// Process pending queue entries in the foreground.
+pub fn process(limit: Option<usize>) -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+    let chunk_config = ChunkConfig::from_processing_config(&config.processing);
+    let ingestor = Ingestor::new(db.clone(), chunk_config).with_events(super::event_emitter(&config));
+
+    let (pending, _, _, _) = db.queue_counts()?;
+    if pending == 0 {
+        println!("{}", "No pending entries in the queue.".dimmed());
+        return Ok(());
+    }
+
+    let total = limit.map_or(pending as usize, |l| l.min(pending as usize));
+    println!(
+        "{} Processing {} of {} pending entries",
+        "→".cyan(),
+        total,
+        pending
+    );
+    println!("{}", "─".repeat(50));
+
+    let mut processed = 0;
+    let mut failed = 0;
+
+    for _ in 0..total {
+        // Peek so failures can be reported by name; process_next dequeues the same entry
+        let next = db.list_queue(Some(QueueStatus::Pending))?.into_iter().next();
+        let Some(entry) = next else { break };
+
+        match ingestor.process_next() {
+            Ok(Some(result)) => {
+                processed += 1;
+                println!(
+                    "  {} {} ({} chunks)",
+                    "✓".green(),
+                    result.item.title,
+                    result.chunk_count
+                );
+            }
+            Ok(None) => break,
+            Err(e) => {
+                failed += 1;
+                println!("  {} {}: {}", "✗".red(), file_name(&entry), e);
+            }
+        }
+    }
+
+    println!();
+    println!(
+        "{} {} processed, {} failed",
+        "Done:".green().bold(),
+        processed.to_string().green(),
+        if failed > 0 {
+            failed.to_string().red()
+        } else {
+            failed.to_string().normal()
+        }
+    );
+    if failed > 0 {
+        println!(
+            "  Run {} to try them again.",
+            "olal queue retry --all-failed".cyan()
+        );
+    }
+
+    Ok(())
+}
+
+fn status_icon(status: QueueStatus) -> ColoredString {
+    match status {
+        QueueStatus::Pending => "○".yellow(),
+        QueueStatus::Processing => "◐".blue(),
+        QueueStatus::Done => "●".green(),
+        QueueStatus::Failed => "✗".red(),
+    }
+}
+
+fn file_name(entry: &QueueItem) -> String {
+    std::path::Path::new(&entry.source_path)
+        .file_name()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| entry.source_path.clone())
+}
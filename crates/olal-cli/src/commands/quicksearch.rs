@@ -0,0 +1,178 @@
+//! Quicksearch command - launcher-friendly JSON output (Alfred, Raycast).
+
+use super::get_search_database;
+use anyhow::Result;
+use olal_core::Item;
+
+/// Launcher output format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LauncherFormat {
+    Alfred,
+    Raycast,
+}
+
+impl LauncherFormat {
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "alfred" => Some(LauncherFormat::Alfred),
+            "raycast" => Some(LauncherFormat::Raycast),
+            _ => None,
+        }
+    }
+}
+
+/// Run the quicksearch command. Always prints a single JSON document.
+pub fn run(query: &str, format: &str, limit: i64) -> Result<()> {
+    let launcher = LauncherFormat::from_str(format).ok_or_else(|| {
+        anyhow::anyhow!("Invalid format '{}'. Use: alfred, raycast", format)
+    })?;
+
+    let db = get_search_database()?;
+
+    let fts_query = prefix_query(query);
+    let items = if fts_query.is_empty() {
+        db.recent_items(Some(limit))?
+    } else {
+        db.search_items(&fts_query, Some(limit))?
+    };
+
+    let output = match launcher {
+        LauncherFormat::Alfred => render_alfred(&items),
+        LauncherFormat::Raycast => render_raycast(&items),
+    };
+
+    println!("{}", serde_json::to_string(&output)?);
+    Ok(())
+}
+
+/// Turn raw launcher input into a safe FTS5 prefix query.
+///
+/// Launchers send every keystroke, so the query is usually a partial word
+/// and may contain FTS syntax characters. Each word is quoted and matched
+/// as a prefix: `rust own` becomes `"rust"* "own"*`.
+fn prefix_query(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|word| word.replace('"', ""))
+        .filter(|word| !word.is_empty())
+        .map(|word| format!("\"{}\"*", word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The value a launcher action receives: the source file if there is one,
+/// otherwise the item ID (for `olal show`).
+fn item_arg(item: &Item) -> String {
+    item.source_path.clone().unwrap_or_else(|| item.id.clone())
+}
+
+fn item_subtitle(item: &Item) -> String {
+    let mut parts = vec![item.item_type.as_str().to_string()];
+    parts.push(item.created_at.format("%Y-%m-%d").to_string());
+    if let Some(ref summary) = item.summary {
+        let preview: String = summary.chars().take(80).collect();
+        parts.push(preview);
+    }
+    parts.join(" · ")
+}
+
+/// Render results as an Alfred Script Filter JSON document.
+fn render_alfred(items: &[Item]) -> serde_json::Value {
+    if items.is_empty() {
+        return serde_json::json!({
+            "items": [{
+                "title": "No results",
+                "subtitle": "Try different keywords",
+                "valid": false,
+            }]
+        });
+    }
+
+    let results: Vec<serde_json::Value> = items
+        .iter()
+        .map(|item| {
+            let arg = item_arg(item);
+            let mut value = serde_json::json!({
+                "uid": item.id,
+                "title": item.title,
+                "subtitle": item_subtitle(item),
+                "arg": arg,
+                "autocomplete": item.title,
+                "mods": {
+                    "cmd": {
+                        "arg": item.id,
+                        "subtitle": "Copy item ID",
+                    }
+                },
+            });
+            if item.source_path.is_some() {
+                value["type"] = serde_json::json!("file");
+                value["quicklookurl"] = serde_json::json!(arg);
+            }
+            value
+        })
+        .collect();
+
+    serde_json::json!({ "items": results })
+}
+
+/// Render results as a list for a Raycast script command or extension.
+fn render_raycast(items: &[Item]) -> serde_json::Value {
+    let results: Vec<serde_json::Value> = items
+        .iter()
+        .map(|item| {
+            serde_json::json!({
+                "id": item.id,
+                "title": item.title,
+                "subtitle": item_subtitle(item),
+                "arg": item_arg(item),
+                "path": item.source_path,
+                "accessories": [
+                    { "text": item.item_type.as_str() },
+                    { "date": item.created_at.to_rfc3339() },
+                ],
+            })
+        })
+        .collect();
+
+    serde_json::json!({ "items": results })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::ItemType;
+
+    #[test]
+    fn test_prefix_query() {
+        assert_eq!(prefix_query("rust own"), "\"rust\"* \"own\"*");
+        assert_eq!(prefix_query("  \"quoted\"  "), "\"quoted\"*");
+        assert_eq!(prefix_query("   "), "");
+    }
+
+    #[test]
+    fn test_render_alfred() {
+        let mut item = Item::new(ItemType::Document, "Rust Book");
+        item.source_path = Some("/docs/rust.pdf".to_string());
+        let note = Item::new(ItemType::Note, "Idea");
+
+        let output = render_alfred(&[item, note.clone()]);
+        let items = output["items"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["arg"], "/docs/rust.pdf");
+        assert_eq!(items[0]["type"], "file");
+        assert_eq!(items[1]["arg"], note.id);
+
+        let empty = render_alfred(&[]);
+        assert_eq!(empty["items"][0]["valid"], false);
+    }
+
+    #[test]
+    fn test_render_raycast() {
+        let item = Item::new(ItemType::Note, "Idea");
+        let output = render_raycast(std::slice::from_ref(&item));
+        assert_eq!(output["items"][0]["id"], item.id);
+        assert_eq!(output["items"][0]["accessories"][0]["text"], "note");
+    }
+}
@@ -0,0 +1,187 @@
+//! Review command - spaced repetition over items worth remembering.
+
+use super::{dates, get_database, resolve};
+use anyhow::Result;
+use chrono::Utc;
+use colored::Colorize;
+use olal_core::{Item, RecallRating, Review};
+use olal_db::Database;
+use std::io::{self, Write};
+
+/// Longest excerpt shown when an item has no summary.
+const EXCERPT_CHARS: usize = 600;
+
+/// Add an item to the review schedule, due now.
+pub fn add(id: &str) -> Result<()> {
+    let db = get_database()?;
+    let item = resolve::item(&db, id)?;
+
+    if db.get_review(&item.id)?.is_some() {
+        println!("{} is already scheduled for review", item.title.white().bold());
+        return Ok(());
+    }
+    db.save_review(&Review::new(item.id.clone()))?;
+
+    println!("{} Added to reviews: {}", "✓".green(), item.title);
+    Ok(())
+}
+
+/// Remove an item from the review schedule.
+pub fn remove(id: &str) -> Result<()> {
+    let db = get_database()?;
+    let item = resolve::item(&db, id)?;
+
+    if db.delete_review(&item.id)? {
+        println!("{} Removed from reviews: {}", "✓".green(), item.title);
+    } else {
+        println!("{} is not scheduled for review", item.title);
+    }
+    Ok(())
+}
+
+/// List scheduled reviews, soonest first.
+pub fn list(limit: usize) -> Result<()> {
+    let db = get_database()?;
+    let reviews = db.list_reviews(limit)?;
+
+    println!("{}", "Reviews".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if reviews.is_empty() {
+        println!("{}", "Nothing scheduled.".dimmed());
+        println!("Add items with: olal review add <id>");
+        return Ok(());
+    }
+
+    let now = Utc::now();
+    for (review, item) in &reviews {
+        let id_short: String = item.id.chars().take(8).collect();
+        let due = if review.is_due(now) {
+            "due now".yellow().to_string()
+        } else {
+            dates::format_datetime(review.due_at)
+        };
+        println!(
+            "  {} {} {} {}",
+            id_short.dimmed(),
+            item.title.white(),
+            format!("[{}]", due).dimmed(),
+            format!("{} reps", review.repetitions).dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Review the items that are due, recording a recall rating for each.
+pub fn run(limit: usize) -> Result<()> {
+    let db = get_database()?;
+    let due = db.due_reviews(Utc::now(), limit)?;
+
+    println!("{}", "Review".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if due.is_empty() {
+        println!("{}", "Nothing due. Nice work!".green());
+        if let Some((next, _)) = db.list_reviews(1)?.first() {
+            println!("Next review: {}", dates::format_datetime(next.due_at));
+        }
+        return Ok(());
+    }
+
+    println!("{} items due", due.len());
+    println!(
+        "{}",
+        "Recall what you can, press Enter to reveal, then rate: 1 again, 2 hard, 3 good, 4 easy (s skip, q quit)"
+            .dimmed()
+    );
+
+    let mut reviewed = 0;
+    for (i, (mut review, item)) in due.into_iter().enumerate() {
+        println!();
+        println!(
+            "{} {} {}",
+            format!("[{}]", i + 1).dimmed(),
+            item.title.white().bold(),
+            format!("({})", item.item_type.as_str()).dimmed()
+        );
+        match prompt("  Enter to reveal: ")?.as_deref() {
+            None | Some("q") => break,
+            _ => {}
+        }
+
+        println!();
+        println!("{}", indent(&recall_text(&db, &item)?));
+        println!();
+
+        let rating = loop {
+            let Some(answer) = prompt("  How well did you remember? [1-4]: ")? else {
+                break None;
+            };
+            match answer.as_str() {
+                "q" => break None,
+                "s" | "" => break Some(None),
+                other => match RecallRating::from_str(other) {
+                    Some(rating) => break Some(Some(rating)),
+                    None => println!("  {}", "Enter 1-4, s or q".dimmed()),
+                },
+            }
+        };
+        let Some(rating) = rating else {
+            break;
+        };
+        let Some(rating) = rating else {
+            continue;
+        };
+
+        review.record(rating, Utc::now());
+        db.save_review(&review)?;
+        reviewed += 1;
+        println!(
+            "  {} Next review in {} day{}",
+            "✓".green(),
+            review.interval_days,
+            if review.interval_days == 1 { "" } else { "s" }
+        );
+    }
+
+    println!();
+    println!("{} Reviewed {} items", "✓".green(), reviewed);
+    Ok(())
+}
+
+/// Read one trimmed line; `None` at end of input.
+fn prompt(message: &str) -> Result<Option<String>> {
+    print!("{}", message);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    if io::stdin().read_line(&mut answer)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(answer.trim().to_lowercase()))
+}
+
+/// The item's summary, or the start of its content.
+fn recall_text(db: &Database, item: &Item) -> Result<String> {
+    if let Some(summary) = &item.summary {
+        return Ok(summary.clone());
+    }
+
+    let chunks = db.get_chunks_by_item(&item.id)?;
+    let Some(first) = chunks.first() else {
+        return Ok("(no content)".to_string());
+    };
+    let mut excerpt: String = first.content.chars().take(EXCERPT_CHARS).collect();
+    if first.content.chars().count() > EXCERPT_CHARS || chunks.len() > 1 {
+        excerpt.push_str("...");
+    }
+    Ok(excerpt)
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("  {}", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
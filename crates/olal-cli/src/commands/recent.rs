@@ -1,17 +1,25 @@
 //! Recent command - list recent items.
 
-use super::get_database;
+use super::{dates, get_database};
 use anyhow::Result;
 use olal_core::ItemType;
 use colored::Colorize;
 
-pub fn run(limit: i64, item_type: Option<String>) -> Result<()> {
+pub fn run(limit: i64, item_type: Option<String>, archived: bool) -> Result<()> {
     let db = get_database()?;
-    run_with_db(&db, limit, item_type)
+    run_with_db(&db, limit, item_type, archived)
 }
 
 /// Run recent with an existing database connection.
-pub fn run_with_db(db: &olal_db::Database, limit: i64, item_type: Option<String>) -> Result<()> {
+///
+/// With `archived`, lists archived items (most recently archived first)
+/// instead of active ones.
+pub fn run_with_db(
+    db: &olal_db::Database,
+    limit: i64,
+    item_type: Option<String>,
+    archived: bool,
+) -> Result<()> {
 
     let item_type_filter = item_type
         .as_ref()
@@ -23,17 +31,27 @@ pub fn run_with_db(db: &olal_db::Database, limit: i64, item_type: Option<String>
         );
     }
 
-    let items = db.list_items(item_type_filter, Some(limit))?;
+    let items = if archived {
+        db.list_archived_items(item_type_filter, Some(limit))?
+    } else {
+        db.list_items(item_type_filter, Some(limit))?
+    };
 
     if items.is_empty() {
-        println!(
-            "{}",
-            "No items found. Use 'olal ingest <path>' to add content.".dimmed()
-        );
+        let hint = if archived {
+            "No archived items."
+        } else {
+            "No items found. Use 'olal ingest <path>' to add content."
+        };
+        println!("{}", hint.dimmed());
         return Ok(());
     }
 
-    println!("{}", "Recent Items".cyan().bold());
+    if archived {
+        println!("{}", "Archived Items".cyan().bold());
+    } else {
+        println!("{}", "Recent Items".cyan().bold());
+    }
     println!("{}", "─".repeat(70));
 
     for item in items {
@@ -47,7 +65,7 @@ pub fn run_with_db(db: &olal_db::Database, limit: i64, item_type: Option<String>
             ItemType::Bookmark => "🔖",
         };
 
-        let date = item.created_at.format("%Y-%m-%d %H:%M").to_string();
+        let date = dates::format_datetime(item.archived_at.unwrap_or(item.created_at));
 
         println!(
             "{} {} {} {}",
@@ -76,5 +94,13 @@ pub fn run_with_db(db: &olal_db::Database, limit: i64, item_type: Option<String>
         }
     }
 
+    if archived {
+        println!();
+        println!(
+            "{}",
+            "Restore an item with 'olal unarchive <id>'.".dimmed()
+        );
+    }
+
     Ok(())
 }
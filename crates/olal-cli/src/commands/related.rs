@@ -0,0 +1,74 @@
+//! Related command - show items related to a given item.
+
+use super::{get_database, resolve};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_core::ItemType;
+
+pub fn run(id: &str, limit: usize) -> Result<()> {
+    let db = get_database()?;
+
+    let item = resolve::item(&db, id)?;
+
+    let related = db
+        .related_items(&item.id, limit)
+        .context("Failed to find related items")?;
+
+    println!(
+        "{} {}",
+        "Related to".cyan().bold(),
+        item.title.white().bold()
+    );
+    println!("{}", "─".repeat(70));
+
+    if related.is_empty() {
+        println!("{}", "No related items found.".dimmed());
+        println!(
+            "{}",
+            "Tip: tag items or run 'olal embed --all' to discover connections.".dimmed()
+        );
+        return Ok(());
+    }
+
+    for (i, r) in related.iter().enumerate() {
+        let type_icon = match r.item.item_type {
+            ItemType::Video => "🎬",
+            ItemType::Audio => "🎵",
+            ItemType::Document => "📄",
+            ItemType::Note => "📝",
+            ItemType::Code => "💻",
+            ItemType::Image => "🖼️",
+            ItemType::Bookmark => "🔖",
+        };
+
+        println!(
+            "{}. {} {} {}",
+            (i + 1).to_string().cyan(),
+            type_icon,
+            r.item.title.white().bold(),
+            format!("[{:.0}%]", r.score * 100.0).green()
+        );
+
+        let mut reasons = Vec::new();
+        if let Some(link_type) = r.link_type {
+            reasons.push(format!("linked ({})", link_type.as_str()));
+        }
+        if !r.shared_tags.is_empty() {
+            reasons.push(format!("tags: {}", r.shared_tags.join(", ")));
+        }
+        if let Some(similarity) = r.similarity {
+            reasons.push(format!("similarity {:.0}%", similarity * 100.0));
+        }
+
+        println!(
+            "   {} {}",
+            r.item.id.chars().take(8).collect::<String>().dimmed(),
+            reasons.join(" · ").dimmed()
+        );
+    }
+
+    println!();
+    println!("{} {} related items", "Found".dimmed(), related.len());
+
+    Ok(())
+}
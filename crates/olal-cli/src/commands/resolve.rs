@@ -0,0 +1,216 @@
+//! Shared lookup of items, projects and tags from user input.
+//!
+//! Exact matches (and unique ID prefixes) resolve directly. Otherwise the
+//! closest known values by edit distance are suggested in the error, or
+//! offered as a numbered menu when `--pick` is given.
+
+use anyhow::Result;
+use colored::Colorize;
+use olal_core::{Item, Project, Tag};
+use olal_db::{Database, DbError};
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Maximum number of suggestions shown or offered.
+const MAX_SUGGESTIONS: usize = 5;
+
+static PICK: AtomicBool = AtomicBool::new(false);
+
+/// Enable interactive picking for this run (the global `--pick` flag).
+pub fn set_pick(enabled: bool) {
+    PICK.store(enabled, Ordering::Relaxed);
+}
+
+/// Resolve an item from a full ID, ID prefix, or (approximate) title.
+pub fn item(db: &Database, input: &str) -> Result<Item> {
+    let candidates: Vec<(String, String)> = match db.get_item_by_prefix(input) {
+        Ok(item) => return Ok(item),
+        // Ambiguous prefix: offer the items it matches
+        Err(DbError::Other(_)) => db
+            .find_items_by_prefix(input, MAX_SUGGESTIONS as i64)?
+            .into_iter()
+            .map(|item| (item.id.clone(), item.title))
+            .collect(),
+        Err(DbError::NotFound(_)) => {
+            let titles = db.item_titles()?;
+            closest(input, &titles, |(id, title)| {
+                let prefix: String = id.chars().take(input.chars().count()).collect();
+                distance(input, &prefix).min(distance(input, title))
+            })
+            .into_iter()
+            .cloned()
+            .collect()
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let labels: Vec<String> = candidates
+        .iter()
+        .map(|(id, title)| format!("{}  {}", id.chars().take(8).collect::<String>(), title))
+        .collect();
+    let index = choose("Item", input, &labels)?;
+    Ok(db.get_item(&candidates[index].0)?)
+}
+
+/// Resolve a project by name (case-insensitive).
+pub fn project(db: &Database, name: &str) -> Result<Project> {
+    if let Some(project) = db.get_project_by_name(name)? {
+        return Ok(project);
+    }
+
+    let projects = db.list_projects(None)?;
+    if let Some(project) = projects.iter().find(|p| p.name.eq_ignore_ascii_case(name)) {
+        return Ok(project.clone());
+    }
+    let candidates = closest(name, &projects, |p| distance(name, &p.name));
+    let labels: Vec<String> = candidates.iter().map(|p| p.name.clone()).collect();
+    let index = choose("Project", name, &labels)?;
+    Ok(candidates[index].clone())
+}
+
+/// Resolve an existing tag by name (case-insensitive).
+pub fn tag(db: &Database, name: &str) -> Result<Tag> {
+    if let Some(tag) = db.get_tag_by_name(name)? {
+        return Ok(tag);
+    }
+
+    let tags = db.list_tags()?;
+    if let Some(tag) = tags.iter().find(|t| t.name.eq_ignore_ascii_case(name)) {
+        return Ok(tag.clone());
+    }
+    let candidates = similar_tags(name, &tags);
+    let labels: Vec<String> = candidates.iter().map(|t| t.name.clone()).collect();
+    let index = choose("Tag", name, &labels)?;
+    Ok(candidates[index].clone())
+}
+
+/// Existing tags close to `name`, for catching typos before a new tag is created.
+pub fn similar_tags<'a>(name: &str, tags: &'a [Tag]) -> Vec<&'a Tag> {
+    closest(name, tags, |t| distance(name, &t.name))
+}
+
+/// Pick one of `labels`: interactively with `--pick`, otherwise fail with
+/// the labels as suggestions.
+fn choose(kind: &str, input: &str, labels: &[String]) -> Result<usize> {
+    let not_found = || anyhow::Error::new(DbError::NotFound(input.to_string()));
+    if labels.is_empty() {
+        return Err(not_found().context(format!("{} not found: {}", kind, input)));
+    }
+    if PICK.load(Ordering::Relaxed) && std::io::stdin().is_terminal() {
+        return prompt(kind, input, labels);
+    }
+
+    let suggestions: Vec<String> = labels.iter().map(|l| format!("  {}", l)).collect();
+    Err(not_found().context(format!(
+        "{} not found: {}\n\nDid you mean:\n{}\n\nRe-run with --pick to choose one.",
+        kind,
+        input,
+        suggestions.join("\n")
+    )))
+}
+
+fn prompt(kind: &str, input: &str, labels: &[String]) -> Result<usize> {
+    println!("{} '{}' - did you mean:", format!("{} not found:", kind).yellow(), input);
+    for (i, label) in labels.iter().enumerate() {
+        println!("  {} {}", format!("{})", i + 1).cyan(), label);
+    }
+
+    print!("Choose [1-{}], or Enter to cancel: ", labels.len());
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+
+    match answer.trim().parse::<usize>() {
+        Ok(n) if (1..=labels.len()).contains(&n) => Ok(n - 1),
+        _ => anyhow::bail!("Cancelled"),
+    }
+}
+
+/// The candidates within typo distance of `input`, closest first.
+fn closest<'a, T>(input: &str, candidates: &'a [T], score: impl Fn(&T) -> usize) -> Vec<&'a T> {
+    let limit = max_distance(input);
+    let mut scored: Vec<(usize, &T)> = candidates
+        .iter()
+        .map(|c| (score(c), c))
+        .filter(|(d, _)| *d <= limit)
+        .collect();
+    scored.sort_by_key(|(d, _)| *d);
+    scored.into_iter().take(MAX_SUGGESTIONS).map(|(_, c)| c).collect()
+}
+
+/// How many edits still count as a typo: one per three characters, at least one.
+fn max_distance(input: &str) -> usize {
+    (input.chars().count() / 3).max(1)
+}
+
+/// Case-insensitive Levenshtein distance, counting a swap of adjacent
+/// characters as one edit (the most common typo).
+fn distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+
+    // d[i][j] = distance between the first i chars of a and first j of b
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance("rust", "rust"), 0);
+        assert_eq!(distance("Rust", "rust"), 0);
+        assert_eq!(distance("rsut", "rust"), 1);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_closest() {
+        let names = ["rust", "rustic", "python", "trust"];
+        let found = closest("rsut", &names, |n| distance("rsut", n));
+        assert_eq!(found, vec![&"rust"]);
+
+        // Longer input tolerates more edits; closest first
+        let found = closest("rustik", &names, |n| distance("rustik", n));
+        assert_eq!(found, vec![&"rustic", &"rust"]);
+    }
+
+    #[test]
+    fn test_item_suggestions() {
+        let db = Database::open_in_memory().unwrap();
+        let mut item = Item::new(olal_core::ItemType::Note, "Ownership notes");
+        item.id = "3fa1df26-aaaa".to_string();
+        db.create_item(&item).unwrap();
+
+        // Exact prefix resolves
+        assert_eq!(super::item(&db, "3fa1").unwrap().id, item.id);
+
+        // A typo in the prefix or title is suggested
+        let err = super::item(&db, "3fb1df26").unwrap_err().to_string();
+        assert!(err.contains("Did you mean"));
+        assert!(err.contains("Ownership notes"));
+        let err = super::item(&db, "Ownershp notes").unwrap_err().to_string();
+        assert!(err.contains("3fa1df26"));
+
+        let err = super::item(&db, "zzzzzzzz").unwrap_err().to_string();
+        assert!(!err.contains("Did you mean"));
+    }
+}
@@ -0,0 +1,52 @@
+//! Retention command - apply archive/delete/compress policies from config.
+
+use super::get_database;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::Config;
+use olal_ingest::retention;
+
+/// Run the configured retention rules now.
+pub fn run(dry_run: bool) -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+
+    println!("{}", "Applying retention rules...".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if config.retention.rules.is_empty() {
+        println!("No retention rules configured. Add [[retention.rules]] to your config.");
+        return Ok(());
+    }
+
+    let report = retention::run_retention(&db, &config, dry_run)
+        .context("Retention failed")?;
+
+    for outcome in &report.outcomes {
+        println!(
+            "{} {} ({} matched)",
+            "•".cyan(),
+            outcome.rule,
+            outcome.matched.len()
+        );
+        for (title, id) in &outcome.matched {
+            println!("    {} {}", format!("[{}]", id).dimmed(), title);
+        }
+        if outcome.bytes_saved > 0 {
+            println!(
+                "    {} {:.1} MB",
+                if dry_run { "Uncompressed:" } else { "Saved:" }.dimmed(),
+                outcome.bytes_saved as f64 / 1_048_576.0
+            );
+        }
+    }
+
+    println!();
+    if dry_run {
+        println!("{}", "Dry run - nothing was changed.".dimmed());
+    } else {
+        println!("{} Applied to {} entries", "✓".green(), report.total_matched());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Review-ai command - accept or reject low-confidence AI enrichment.
+
+use super::get_database;
+use anyhow::Result;
+use colored::Colorize;
+use olal_core::{AiSuggestion, SuggestionKind, SuggestionStatus};
+use olal_db::Database;
+use std::collections::HashSet;
+
+/// Which pending suggestions a bulk action applies to.
+#[derive(Debug, Default)]
+struct ReviewFilter {
+    kind: Option<SuggestionKind>,
+    below: Option<f32>,
+    above: Option<f32>,
+}
+
+impl ReviewFilter {
+    fn matches(&self, suggestion: &AiSuggestion) -> bool {
+        self.kind.is_none_or(|k| suggestion.kind == k)
+            && self.below.is_none_or(|b| suggestion.confidence < b)
+            && self.above.is_none_or(|a| suggestion.confidence >= a)
+    }
+}
+
+/// Options for the review-ai command.
+#[derive(Debug, Default)]
+pub struct ReviewOptions {
+    pub accept: Vec<String>,
+    pub reject: Vec<String>,
+    pub accept_all: bool,
+    pub reject_all: bool,
+    pub kind: Option<String>,
+    pub below: Option<f32>,
+    pub above: Option<f32>,
+}
+
+/// Run the review-ai command.
+///
+/// Without any accept/reject option, lists the pending suggestions that
+/// match the filters.
+pub fn run(options: ReviewOptions) -> Result<()> {
+    let kind = match options.kind.as_deref() {
+        Some(k) => Some(SuggestionKind::from_str(k).ok_or_else(|| {
+            anyhow::anyhow!("Invalid kind '{}'. Use: tag, summary, link", k)
+        })?),
+        None => None,
+    };
+    let filter = ReviewFilter {
+        kind,
+        below: options.below,
+        above: options.above,
+    };
+
+    let db = get_database()?;
+    let pending: Vec<AiSuggestion> = db
+        .list_suggestions(Some(SuggestionStatus::PendingReview), None)?
+        .into_iter()
+        .filter(|s| filter.matches(s))
+        .collect();
+
+    let mut to_accept: Vec<&AiSuggestion> = Vec::new();
+    let mut to_reject: Vec<&AiSuggestion> = Vec::new();
+
+    if options.accept_all {
+        to_accept.extend(pending.iter());
+    } else if options.reject_all {
+        to_reject.extend(pending.iter());
+    }
+    for prefix in &options.accept {
+        to_accept.push(resolve(&pending, prefix)?);
+    }
+    for prefix in &options.reject {
+        to_reject.push(resolve(&pending, prefix)?);
+    }
+
+    // Catch contradictions before changing anything
+    if let Some(both) = to_accept.iter().find(|a| to_reject.iter().any(|r| r.id == a.id)) {
+        anyhow::bail!(
+            "Suggestion {} is both accepted and rejected",
+            both.id.chars().take(8).collect::<String>()
+        );
+    }
+    let mut seen = HashSet::new();
+    to_accept.retain(|s| seen.insert(s.id.clone()));
+    to_reject.retain(|s| seen.insert(s.id.clone()));
+
+    if to_accept.is_empty() && to_reject.is_empty() {
+        return list(&db, &pending);
+    }
+
+    for suggestion in &to_accept {
+        db.accept_suggestion(&suggestion.id)?;
+    }
+    for suggestion in &to_reject {
+        db.reject_suggestion(&suggestion.id)?;
+    }
+
+    if !to_accept.is_empty() {
+        println!("{} Accepted {} suggestions", "✓".green(), to_accept.len());
+    }
+    if !to_reject.is_empty() {
+        println!("{} Rejected {} suggestions", "✓".green(), to_reject.len());
+    }
+
+    let remaining = db.pending_suggestion_count()?;
+    if remaining > 0 {
+        println!("{}", format!("{} suggestions still pending.", remaining).dimmed());
+    }
+
+    Ok(())
+}
+
+/// Find the pending suggestion whose ID starts with `prefix`.
+fn resolve<'a>(pending: &'a [AiSuggestion], prefix: &str) -> Result<&'a AiSuggestion> {
+    let matches: Vec<&AiSuggestion> = pending
+        .iter()
+        .filter(|s| s.id.starts_with(prefix))
+        .collect();
+
+    match matches.as_slice() {
+        [one] => Ok(one),
+        [] => anyhow::bail!("No pending suggestion matches '{}'", prefix),
+        _ => anyhow::bail!("'{}' matches several suggestions; use a longer prefix", prefix),
+    }
+}
+
+fn list(db: &Database, pending: &[AiSuggestion]) -> Result<()> {
+    if pending.is_empty() {
+        println!("{}", "No AI suggestions awaiting review.".dimmed());
+        return Ok(());
+    }
+
+    println!("{}", "AI Suggestions Pending Review".cyan().bold());
+    println!("{}", "─".repeat(70));
+
+    let mut current_item = None;
+    for suggestion in pending {
+        if current_item != Some(&suggestion.item_id) {
+            current_item = Some(&suggestion.item_id);
+            let title = db
+                .get_item(&suggestion.item_id)
+                .map(|i| i.title)
+                .unwrap_or_else(|_| "(missing item)".to_string());
+            println!();
+            println!(
+                "{} {}",
+                title.white().bold(),
+                format!("[{}]", suggestion.item_id.chars().take(8).collect::<String>()).dimmed()
+            );
+        }
+
+        let value = match suggestion.kind {
+            SuggestionKind::Tag => format!("#{}", suggestion.value),
+            SuggestionKind::Summary => {
+                let preview: String = suggestion.value.chars().take(60).collect();
+                if preview.len() < suggestion.value.len() {
+                    format!("\"{}...\"", preview)
+                } else {
+                    format!("\"{}\"", preview)
+                }
+            }
+            SuggestionKind::Link => {
+                let target = db
+                    .get_item(&suggestion.value)
+                    .map(|i| i.title)
+                    .unwrap_or_else(|_| suggestion.value.clone());
+                format!("→ {}", target)
+            }
+        };
+
+        println!(
+            "  {} {:<8} {} {}",
+            suggestion.id.chars().take(8).collect::<String>().yellow(),
+            suggestion.kind.as_str().cyan(),
+            format!("{:.2}", suggestion.confidence).dimmed(),
+            value
+        );
+    }
+
+    println!();
+    println!(
+        "{}",
+        "Accept with --accept <id>..., reject with --reject <id>..., or use --accept-all / --reject-all with --kind, --below, --above."
+            .dimmed()
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suggestion(kind: SuggestionKind, confidence: f32) -> AiSuggestion {
+        AiSuggestion::new("item", kind, "value", confidence)
+    }
+
+    #[test]
+    fn test_filter_matches() {
+        let filter = ReviewFilter {
+            kind: Some(SuggestionKind::Tag),
+            below: Some(0.5),
+            above: None,
+        };
+        assert!(filter.matches(&suggestion(SuggestionKind::Tag, 0.3)));
+        assert!(!filter.matches(&suggestion(SuggestionKind::Tag, 0.5)));
+        assert!(!filter.matches(&suggestion(SuggestionKind::Link, 0.3)));
+        assert!(ReviewFilter::default().matches(&suggestion(SuggestionKind::Summary, 0.9)));
+    }
+
+    #[test]
+    fn test_resolve_prefix() {
+        let pending = vec![
+            suggestion(SuggestionKind::Tag, 0.3),
+            suggestion(SuggestionKind::Tag, 0.4),
+        ];
+        let id = &pending[1].id;
+        assert_eq!(resolve(&pending, id).unwrap().id, *id);
+        assert!(resolve(&pending, "zzzz-not-an-id").is_err());
+        assert!(resolve(&pending, "").is_err());
+    }
+}
@@ -0,0 +1,184 @@
+//! Schedule commands - recurring jobs from the `[schedule]` config.
+
+use super::{dates, get_database, get_paths};
+use anyhow::{Context, Result};
+use chrono::{Local, Utc};
+use colored::Colorize;
+use olal_config::{AppPaths, Config, ScheduledJob, ScheduledTask};
+use olal_db::Database;
+use olal_ingest::{maintenance, retention, scheduler, Embedder};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// How often a running daemon checks for due jobs.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Default maximum queue entries or chunks per scheduled run.
+const DEFAULT_LIMIT: usize = 500;
+
+/// List configured jobs with their last run.
+pub fn list() -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+
+    println!("{}", "Scheduled jobs".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if config.schedule.jobs.is_empty() {
+        println!("{}", "No jobs configured.".dimmed());
+        println!("Add [[schedule.jobs]] entries with: olal config edit");
+        return Ok(());
+    }
+    if !config.schedule.enabled {
+        println!("{}", "Scheduling is disabled (schedule.enabled = false).".yellow());
+    }
+
+    let now = Local::now();
+    for job in &config.schedule.jobs {
+        println!();
+        println!("{} {}", job.name().white().bold(), format!("[{}]", job.task.as_str()).dimmed());
+        if let Err(e) = scheduler::validate(job) {
+            println!("  {} {}", "Invalid:".red(), e);
+            continue;
+        }
+        println!("  {} {}", "When:".cyan(), job.describe());
+
+        let last_run = db.get_schedule_run(job.name())?;
+        match &last_run {
+            Some(run) => {
+                let when = dates::format_datetime(run.last_run_at);
+                match &run.error {
+                    Some(error) => println!("  {} {} ({} {})", "Last run:".cyan(), when, "failed:".red(), error),
+                    None => println!("  {} {} {}", "Last run:".cyan(), when, "✓".green()),
+                }
+            }
+            None => println!("  {} never", "Last run:".cyan()),
+        }
+        if scheduler::is_due(job, last_run.map(|r| r.last_run_at), &now) {
+            println!("  {} due now", "→".yellow());
+        }
+    }
+
+    Ok(())
+}
+
+/// Run a configured job now, whether or not it's due.
+pub fn run(name: &str) -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+    let paths = get_paths()?;
+
+    let Some(job) = config.schedule.jobs.iter().find(|j| j.name() == name) else {
+        let names: Vec<&str> = config.schedule.jobs.iter().map(|j| j.name()).collect();
+        if names.is_empty() {
+            anyhow::bail!("No job named '{}' (no jobs configured)", name);
+        }
+        anyhow::bail!("No job named '{}'. Configured jobs: {}", name, names.join(", "));
+    };
+
+    run_and_record(&db, &config, &paths, job)
+}
+
+/// Run every job that's due, recording each run. Failures are reported
+/// and recorded, not returned, so one broken job doesn't stop the others.
+fn run_due(db: &Database, config: &Config, paths: &AppPaths) {
+    let due = match scheduler::due_jobs(db, config, Utc::now()) {
+        Ok(due) => due,
+        Err(e) => {
+            warn!("Failed to check scheduled jobs: {}", e);
+            return;
+        }
+    };
+
+    for job in due {
+        if let Err(e) = run_and_record(db, config, paths, job) {
+            println!("  {} {}", "Error:".red(), e);
+        }
+    }
+}
+
+/// Check for due jobs every [`CHECK_INTERVAL`] on a background thread until
+/// `stop` is set.
+pub fn spawn(db: Database, config: Config, paths: AppPaths, stop: Arc<AtomicBool>) -> JoinHandle<()> {
+    std::thread::spawn(move || {
+        let mut last_check: Option<Instant> = None;
+        while !stop.load(Ordering::Acquire) {
+            if last_check.is_none_or(|at| at.elapsed() >= CHECK_INTERVAL) {
+                last_check = Some(Instant::now());
+                run_due(&db, &config, &paths);
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+    })
+}
+
+fn run_and_record(db: &Database, config: &Config, paths: &AppPaths, job: &ScheduledJob) -> Result<()> {
+    scheduler::validate(job).map_err(|e| anyhow::anyhow!("Job '{}' is invalid: {}", job.name(), e))?;
+
+    println!("{} {}", "Running scheduled job:".cyan(), job.name());
+    let started = Utc::now();
+    let result = run_job(db, config, paths, job);
+
+    let error = result.as_ref().err().map(|e| format!("{:#}", e));
+    db.record_schedule_run(job.name(), started, error.as_deref())
+        .context("Failed to record scheduled run")?;
+
+    let summary = result?;
+    println!("  {} {}", "✓".green(), summary);
+    Ok(())
+}
+
+/// Run one job, returning a one-line summary of what it did.
+fn run_job(db: &Database, config: &Config, paths: &AppPaths, job: &ScheduledJob) -> Result<String> {
+    let limit = job.limit.unwrap_or(DEFAULT_LIMIT);
+
+    match job.task {
+        ScheduledTask::Digest => {
+            let period = job.period.as_deref().unwrap_or("day");
+            let dir = paths.digests_dir();
+            std::fs::create_dir_all(&dir).context("Failed to create digests directory")?;
+            let output = dir.join(format!("{}-{}.md", Local::now().format("%Y-%m-%d"), period));
+            super::digest::run(period, None, Some(output.clone()), None, false, false, job.email)?;
+            if output.exists() {
+                Ok(format!("Digest written to {}", output.display()))
+            } else {
+                Ok("No new items, no digest written".to_string())
+            }
+        }
+        ScheduledTask::ProcessQueue => {
+            super::queue::process(Some(limit))?;
+            Ok("Queue processed".to_string())
+        }
+        ScheduledTask::Embed => {
+            let embedder = Embedder::from_config(config)?;
+            let (embedded, failed) = embedder.embed_pending(db, limit)?;
+            Ok(format!("Embedded {} chunks ({} failed)", embedded, failed))
+        }
+        ScheduledTask::Maintenance => {
+            let report = maintenance::run_maintenance(db, config, false)?;
+            let item = maintenance::save_report(db, &report)?;
+            Ok(format!(
+                "{} links, {} summaries, {} duplicate groups, {} tag merges (report {})",
+                report.links_created,
+                report.summaries_refreshed.len(),
+                report.duplicates.len(),
+                report.tag_merges.len(),
+                &item.id[..8]
+            ))
+        }
+        ScheduledTask::Retention => {
+            if !config.retention.enabled {
+                return Ok("Retention is disabled (retention.enabled = false)".to_string());
+            }
+            let report = retention::run_retention(db, config, false)?;
+            Ok(format!(
+                "Applied {} retention rules to {} entries",
+                report.outcomes.len(),
+                report.total_matched()
+            ))
+        }
+    }
+}
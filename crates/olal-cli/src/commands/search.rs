@@ -1,39 +1,174 @@
 //! Search command - full-text and semantic search.
 
-use super::get_database;
+use super::{dates, get_search_database, resolve, Ollama};
 use anyhow::{Context, Result};
 use olal_config::Config;
 use olal_core::ItemType;
-use olal_ollama::OllamaClient;
+use olal_db::fts::{self, Snippet};
+use olal_db::SearchFilter;
 use colored::Colorize;
-use tokio::runtime::Runtime;
 
-pub fn run(query: &str, limit: i64, semantic: bool) -> Result<()> {
-    let db = get_database()?;
-    run_with_db(&db, query, limit, semantic)
+/// Words of matching text shown with each result by default.
+pub const DEFAULT_CONTEXT_WORDS: usize = 20;
+
+/// How a query is matched.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    FullText,
+    Semantic,
+    /// Vector and full-text scores combined, `vector_weight` going to the
+    /// vector side.
+    Hybrid { vector_weight: f32 },
+}
+
+/// Search, showing up to `context` words of matching text per result.
+pub fn run(
+    query: &str,
+    limit: i64,
+    mode: SearchMode,
+    filter: &SearchFilter,
+    context: usize,
+) -> Result<()> {
+    let db = get_search_database()?;
+    let filter = resolve_project(&db, filter.clone())?;
+    run_with_db(&db, None, query, limit, mode, &filter, context)
+}
+
+/// Run search with an existing database connection, and an existing
+/// Ollama connection if there is one (semantic and hybrid searches connect
+/// otherwise).
+pub fn run_with_db(
+    db: &olal_db::Database,
+    ollama: Option<&Ollama>,
+    query: &str,
+    limit: i64,
+    mode: SearchMode,
+    filter: &SearchFilter,
+    context: usize,
+) -> Result<()> {
+    match mode {
+        SearchMode::FullText => run_fts_search(db, query, limit, filter, context),
+        SearchMode::Semantic => {
+            run_semantic_search(db, ollama, query, limit as usize, filter, context)
+        }
+        SearchMode::Hybrid { vector_weight } => {
+            run_hybrid_search(db, ollama, query, limit as usize, vector_weight, filter, context)
+        }
+    }
+}
+
+/// Build a filter from the `--type`, `--tag`, `--project`, `--since` and
+/// `--until` arguments.
+pub fn filter(
+    item_type: Option<&str>,
+    tag: Option<String>,
+    project: Option<String>,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Result<SearchFilter> {
+    let item_type = match item_type {
+        Some(t) => Some(ItemType::from_str(t).with_context(|| {
+            format!(
+                "Invalid item type '{}'. Valid types: video, audio, document, note, code, image, bookmark",
+                t
+            )
+        })?),
+        None => None,
+    };
+    Ok(SearchFilter {
+        item_type,
+        tag,
+        project,
+        since: since.map(dates::parse_date).transpose()?.map(dates::start_of_day),
+        until: until.map(dates::parse_date).transpose()?.map(dates::end_of_day),
+        item_id: None,
+    })
 }
 
-/// Run search with an existing database connection.
-pub fn run_with_db(db: &olal_db::Database, query: &str, limit: i64, semantic: bool) -> Result<()> {
-    if semantic {
-        run_semantic_search(db, query, limit as usize)
-    } else {
-        run_fts_search(db, query, limit)
+/// Replace the filter's project with the exact name of an existing
+/// project, so a typo fails loudly instead of matching nothing.
+pub fn resolve_project(db: &olal_db::Database, mut filter: SearchFilter) -> Result<SearchFilter> {
+    if let Some(ref name) = filter.project {
+        filter.project = Some(resolve::project(db, name)?.name);
     }
+    Ok(filter)
+}
+
+/// Replace the filter's item, given as an ID or ID prefix, with the full
+/// ID of that item.
+pub fn resolve_item(db: &olal_db::Database, mut filter: SearchFilter) -> Result<SearchFilter> {
+    if let Some(ref input) = filter.item_id {
+        filter.item_id = Some(resolve::item(db, input)?.id);
+    }
+    Ok(filter)
+}
+
+/// Print the active filters, if any.
+pub fn print_filter(filter: &SearchFilter) {
+    if filter.is_empty() {
+        return;
+    }
+    let mut parts = Vec::new();
+    if let Some(item_type) = filter.item_type {
+        parts.push(format!("type {}", item_type));
+    }
+    if let Some(ref tag) = filter.tag {
+        parts.push(format!("tag {}", tag));
+    }
+    if let Some(ref project) = filter.project {
+        parts.push(format!("project {}", project));
+    }
+    if let Some(since) = filter.since {
+        parts.push(format!("since {}", dates::format_datetime(since)));
+    }
+    if let Some(until) = filter.until {
+        parts.push(format!("until {}", dates::format_datetime(until)));
+    }
+    if let Some(ref item_id) = filter.item_id {
+        parts.push(format!("item {}", &item_id[..8.min(item_id.len())]));
+    }
+    println!("{}", format!("Filtered by {}", parts.join(", ")).dimmed());
 }
 
 /// Run full-text search (original behavior).
-fn run_fts_search(db: &olal_db::Database, query: &str, limit: i64) -> Result<()> {
+fn run_fts_search(
+    db: &olal_db::Database,
+    query: &str,
+    limit: i64,
+    filter: &SearchFilter,
+    context: usize,
+) -> Result<()> {
     println!(
         "{} \"{}\"",
         "Searching for:".cyan().bold(),
         query
     );
+    print_filter(filter);
     println!("{}", "─".repeat(70));
 
-    let items = db.search_items(query, Some(limit))?;
+    let mut query = query.to_string();
+    let mut matches = db.search_matches_filtered(&query, Some(limit), filter)?;
+
+    // Retry a search that found nothing with misspelled words corrected
+    let typo_tolerance = Config::load().map_or(true, |c| c.search.typo_tolerance);
+    if matches.is_empty() && typo_tolerance {
+        if let Some(suggestion) = db.spelling_suggestion(&query)? {
+            let suggested = db.search_matches_filtered(&suggestion, Some(limit), filter)?;
+            if !suggested.is_empty() {
+                println!();
+                println!(
+                    "{} \"{}\"? {}",
+                    "Did you mean".yellow(),
+                    suggestion.bold(),
+                    "Showing results for it.".dimmed()
+                );
+                query = suggestion;
+                matches = suggested;
+            }
+        }
+    }
 
-    if items.is_empty() {
+    if matches.is_empty() {
         println!();
         println!("{}", "No results found.".dimmed());
         println!();
@@ -48,20 +183,29 @@ fn run_fts_search(db: &olal_db::Database, query: &str, limit: i64) -> Result<()>
     println!();
     println!(
         "Found {} result{}",
-        items.len().to_string().green(),
-        if items.len() == 1 { "" } else { "s" }
+        matches.len().to_string().green(),
+        if matches.len() == 1 { "" } else { "s" }
     );
     println!();
 
-    for item in items {
-        print_item(&item.item_type, &item.title, &item.id, item.summary.as_deref(), None);
+    let tokenizer = db.fts_tokenizer()?.unwrap_or_default();
+    for m in matches {
+        // Items matched by title or tags alone show the start of their text
+        let text = match (&m.chunk, &m.item.summary) {
+            (Some(chunk), _) => chunk.content.as_str(),
+            (None, Some(summary)) => summary.as_str(),
+            (None, None) => "",
+        };
+        let snippet = fts::snippet(text, &query, context, tokenizer);
+        print_item(&m.item.item_type, &m.item.title, &m.item.id, &snippet);
     }
 
     Ok(())
 }
 
-/// Run semantic (vector) search.
-fn run_semantic_search(db: &olal_db::Database, query: &str, limit: usize) -> Result<()> {
+/// Embed the query with the configured model, checking first that there
+/// are embeddings to compare it against.
+fn embed_query(db: &olal_db::Database, ollama: Option<&Ollama>, query: &str) -> Result<Vec<f32>> {
     let config = Config::load().context("Failed to load configuration")?;
 
     // Check embedding stats
@@ -78,21 +222,31 @@ fn run_semantic_search(db: &olal_db::Database, query: &str, limit: usize) -> Res
         }
     }
 
-    // Create Ollama client
-    let client = OllamaClient::from_config(&config.ollama)
-        .context("Failed to create Ollama client")?;
+    let connected;
+    let ollama = match ollama {
+        Some(ollama) => ollama,
+        None => {
+            connected = Ollama::connect(&config.ollama)?;
+            &connected
+        }
+    };
 
-    // Create async runtime
-    let rt = Runtime::new().context("Failed to create async runtime")?;
+    ollama
+        .rt
+        .block_on(ollama.client.embed(&config.ollama.embedding_model, query))
+        .context("Failed to embed query")
+}
 
-    // Check if Ollama is available
-    let is_available = rt.block_on(client.is_available());
-    if !is_available {
-        anyhow::bail!(
-            "Ollama is not running at {}. Start it with 'ollama serve'.",
-            config.ollama.host
-        );
-    }
+/// Run semantic (vector) search.
+fn run_semantic_search(
+    db: &olal_db::Database,
+    ollama: Option<&Ollama>,
+    query: &str,
+    limit: usize,
+    filter: &SearchFilter,
+    context: usize,
+) -> Result<()> {
+    let query_embedding = embed_query(db, ollama, query)?;
 
     println!(
         "{} \"{}\" {}",
@@ -100,15 +254,11 @@ fn run_semantic_search(db: &olal_db::Database, query: &str, limit: usize) -> Res
         query,
         "(meaning-based)".dimmed()
     );
+    print_filter(filter);
     println!("{}", "─".repeat(70));
 
-    // Generate embedding for the query
-    let query_embedding = rt
-        .block_on(client.embed(&config.ollama.embedding_model, query))
-        .context("Failed to embed query")?;
-
     // Search for similar chunks
-    let results = db.vector_search(&query_embedding, limit, Some(0.2))?;
+    let results = db.vector_search_filtered(&query_embedding, limit, Some(0.2), filter)?;
 
     if results.is_empty() {
         println!();
@@ -142,7 +292,7 @@ fn run_semantic_search(db: &olal_db::Database, query: &str, limit: usize) -> Res
                 // Keep the highest similarity and best content snippet
                 if result.similarity > *sim {
                     *sim = result.similarity;
-                    *content = truncate(&result.chunk.content, 150);
+                    *content = fts::snippet(&result.chunk.content, "", context, fts::Tokenizer::default()).text;
                 }
             })
             .or_insert_with(|| {
@@ -150,7 +300,7 @@ fn run_semantic_search(db: &olal_db::Database, query: &str, limit: usize) -> Res
                     result.item_title.clone(),
                     item_id.clone(),
                     result.similarity,
-                    truncate(&result.chunk.content, 150),
+                    fts::snippet(&result.chunk.content, "", context, fts::Tokenizer::default()).text,
                 )
             });
     }
@@ -178,14 +328,90 @@ fn run_semantic_search(db: &olal_db::Database, query: &str, limit: usize) -> Res
     Ok(())
 }
 
-/// Print an item result.
-fn print_item(
-    item_type: &ItemType,
-    title: &str,
-    id: &str,
-    summary: Option<&str>,
-    similarity: Option<f32>,
-) {
+/// Run hybrid search: vector similarity and full-text relevance combined.
+fn run_hybrid_search(
+    db: &olal_db::Database,
+    ollama: Option<&Ollama>,
+    query: &str,
+    limit: usize,
+    vector_weight: f32,
+    filter: &SearchFilter,
+    context: usize,
+) -> Result<()> {
+    if !(0.0..=1.0).contains(&vector_weight) {
+        anyhow::bail!("--vector-weight must be between 0 and 1, got {}", vector_weight);
+    }
+    let query_embedding = embed_query(db, ollama, query)?;
+
+    println!(
+        "{} \"{}\" {}",
+        "Hybrid search for:".cyan().bold(),
+        query,
+        format!(
+            "(vector {:.0}% · text {:.0}%)",
+            vector_weight * 100.0,
+            (1.0 - vector_weight) * 100.0
+        )
+        .dimmed()
+    );
+    print_filter(filter);
+    println!("{}", "─".repeat(70));
+
+    let results = db.hybrid_search(query, &query_embedding, limit, vector_weight, filter)?;
+
+    if results.is_empty() {
+        println!();
+        println!("{}", "No results found.".dimmed());
+        println!();
+        println!("Tips:");
+        println!("  • Try different keywords");
+        println!("  • Run 'olal embed --all' to generate more embeddings");
+        return Ok(());
+    }
+
+    println!();
+    println!(
+        "Found {} matching chunk{}",
+        results.len().to_string().green(),
+        if results.len() == 1 { "" } else { "s" }
+    );
+    println!();
+
+    // Best chunk per item; results are already sorted by score
+    let tokenizer = db.fts_tokenizer()?.unwrap_or_default();
+    let mut seen = std::collections::HashSet::new();
+    for result in results.iter().filter(|r| seen.insert(r.item_id.clone())) {
+        println!(
+            "{} {} {}",
+            "•".cyan(),
+            result.item_title.white().bold(),
+            format!("[{}]", result.item_id.chars().take(8).collect::<String>()).dimmed()
+        );
+        println!(
+            "  {} {:.0}%  {}",
+            "Score:".dimmed(),
+            result.score * 100.0,
+            format!(
+                "(vector {} · text {})",
+                percent(result.vector_score),
+                percent(result.fts_score)
+            )
+            .dimmed()
+        );
+        println!("  {}", highlighted(&fts::snippet(&result.chunk.content, query, context, tokenizer)));
+        println!();
+    }
+
+    Ok(())
+}
+
+/// A score as a percentage, or "-" if that source didn't match.
+fn percent(score: Option<f32>) -> String {
+    score.map_or_else(|| "-".to_string(), |s| format!("{:.0}%", s * 100.0))
+}
+
+/// Print an item result with the passage that matched.
+fn print_item(item_type: &ItemType, title: &str, id: &str, snippet: &Snippet) {
     let type_icon = match item_type {
         ItemType::Video => "🎬",
         ItemType::Audio => "🎵",
@@ -203,24 +429,19 @@ fn print_item(
         format!("[{}]", id.chars().take(8).collect::<String>()).dimmed()
     );
 
-    if let Some(sim) = similarity {
-        println!("  {} {:.0}%", "Similarity:".dimmed(), sim * 100.0);
-    }
-
-    if let Some(summary) = summary {
-        let short_summary = truncate(summary, 100);
-        println!("  {}", short_summary.dimmed());
-    }
-
+    println!("  {}", highlighted(snippet));
     println!();
 }
 
-/// Truncate a string to a maximum length.
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
-    } else {
-        let truncated: String = s.chars().take(max_len - 3).collect();
-        format!("{}...", truncated)
+/// A snippet dimmed, with the query's terms emphasized.
+fn highlighted(snippet: &Snippet) -> String {
+    let mut out = String::new();
+    let mut at = 0;
+    for range in &snippet.highlights {
+        out.push_str(&snippet.text[at..range.start].dimmed().to_string());
+        out.push_str(&snippet.text[range.clone()].yellow().bold().to_string());
+        at = range.end;
     }
+    out.push_str(&snippet.text[at..].dimmed().to_string());
+    out
 }
@@ -0,0 +1,82 @@
+//! Secret commands - credentials in the OS keyring.
+//!
+//! A stored secret is used from the config as `"keyring:<name>"`, e.g.
+//! `password = "keyring:smtp"` under `[digest.smtp]`.
+
+use super::get_paths;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::{secrets, Config, ConfigError};
+use std::io::{BufRead, IsTerminal};
+
+/// Store a secret, read from a hidden prompt or a line of stdin.
+pub fn set(name: &str) -> Result<()> {
+    ensure_supported()?;
+    let value = if std::io::stdin().is_terminal() {
+        prompt(&format!("Value for '{}': ", name))?
+    } else {
+        let mut line = String::new();
+        std::io::stdin().lock().read_line(&mut line)?;
+        line.trim_end_matches(['\r', '\n']).to_string()
+    };
+    if value.is_empty() {
+        anyhow::bail!("Empty secret; nothing stored");
+    }
+
+    secrets::set(name, &value).context("Failed to save the secret")?;
+    println!("{} Stored '{}' in the OS keyring", "✓".green(), name);
+    println!("  Use it in the config as: \"{}{}\"", secrets::KEYRING_PREFIX, name);
+    Ok(())
+}
+
+/// Remove a secret from the keyring.
+pub fn delete(name: &str) -> Result<()> {
+    ensure_supported()?;
+    secrets::delete(name)?;
+    println!("{} Removed '{}' from the OS keyring", "✓".green(), name);
+    Ok(())
+}
+
+/// List the `keyring:` references in the config and whether each is stored.
+pub fn list() -> Result<()> {
+    let paths = get_paths()?;
+    let config = Config::load_from(&paths.config_file).context("Failed to load config")?;
+    let references = secrets::references(&config);
+
+    println!("{}", "Secrets".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if references.is_empty() {
+        println!("{}", "No keyring: references in the config.".dimmed());
+        println!("Store one with: olal secret set <name>");
+        return Ok(());
+    }
+    for (key, name) in references {
+        let state = match secrets::get(&name) {
+            Ok(_) => "stored".green(),
+            Err(ConfigError::MissingSecret(_)) => "missing".red(),
+            Err(e) => e.to_string().yellow(),
+        };
+        println!("  {} {} ({})", key.white().bold(), format!("keyring:{}", name).dimmed(), state);
+    }
+    Ok(())
+}
+
+fn ensure_supported() -> Result<()> {
+    if !secrets::KEYRING_SUPPORTED {
+        anyhow::bail!(
+            "This build of olal has no keyring support. Rebuild with: cargo install --path crates/olal-cli --features keyring"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(feature = "keyring")]
+fn prompt(message: &str) -> Result<String> {
+    Ok(rpassword::prompt_password(message)?)
+}
+
+#[cfg(not(feature = "keyring"))]
+fn prompt(_message: &str) -> Result<String> {
+    anyhow::bail!("Built without keyring support")
+}
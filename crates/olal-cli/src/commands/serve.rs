@@ -0,0 +1,320 @@
+//! Serve command - a local HTTP API, and with `--ui` a browser interface.
+//!
+//! The API is JSON under `/api`:
+//!
+//! - `GET /api/items?limit=N` - recent items
+//! - `GET /api/search?q=...&limit=N` - full-text search
+//! - `GET /api/items/{id}` - an item with its tags and chunks (with
+//!   timestamps, for transcripts)
+//! - `GET /api/items/{id}/media` - a video or audio item's source file,
+//!   with range requests so the player can seek
+//! - `POST /api/ask` with `{"question": "..."}` - the answer as
+//!   newline-delimited JSON: `{"sources": [...]}`, then `{"text": "..."}`
+//!   pieces as the model writes them, then `{"done": true}`
+//!
+//! With `--ui`, a small single-page app compiled into the binary is served
+//! at `/`, so nothing else needs installing.
+
+use super::get_database;
+use anyhow::{Context, Result};
+use axum::body::{Body, Bytes};
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{header, StatusCode, Uri};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use colored::Colorize;
+use olal_config::Config;
+use olal_core::ItemType;
+use olal_db::{Database, DbError, SearchFilter};
+use olal_ollama::rag::{fuse_rankings, ContextItem};
+use olal_ollama::{OllamaClient, RagConfig};
+use rust_embed::RustEmbed;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use tower_http::services::ServeFile;
+
+/// The browser interface.
+#[derive(RustEmbed)]
+#[folder = "ui/"]
+struct Assets;
+
+/// Chunks of context given to the model for `/api/ask`.
+const ASK_CONTEXT: usize = 5;
+const ASK_MIN_SIMILARITY: f32 = 0.3;
+
+struct AppState {
+    db: Database,
+    config: Config,
+}
+
+type Shared = Arc<AppState>;
+
+/// Serve the API (and the UI with `ui`) on `host:port` until Ctrl+C.
+pub fn run(host: &str, port: u16, ui: bool) -> Result<()> {
+    let state = Arc::new(AppState {
+        db: get_database()?,
+        config: Config::load().context("Failed to load configuration")?,
+    });
+    let rt = Runtime::new().context("Failed to create async runtime")?;
+
+    rt.block_on(async move {
+        let listener = tokio::net::TcpListener::bind((host, port))
+            .await
+            .with_context(|| format!("Failed to listen on {}:{}", host, port))?;
+        let addr = listener.local_addr()?;
+
+        println!("{} http://{}", "Serving on".green().bold(), addr);
+        if ui {
+            println!("  Open http://{}/ in your browser", addr);
+        } else {
+            println!("  {}", "API only; add --ui for the browser interface".dimmed());
+        }
+        println!("  {}", "Press Ctrl+C to stop".dimmed());
+
+        axum::serve(listener, router(state, ui))
+            .with_graceful_shutdown(async {
+                let _ = tokio::signal::ctrl_c().await;
+            })
+            .await
+            .context("Server failed")
+    })
+}
+
+fn router(state: Shared, ui: bool) -> Router {
+    let api = Router::new()
+        .route("/api/items", get(list_items))
+        .route("/api/items/{id}", get(show_item))
+        .route("/api/items/{id}/media", get(item_media))
+        .route("/api/search", get(search))
+        .route("/api/ask", post(ask))
+        .with_state(state);
+
+    if ui {
+        api.fallback(static_asset)
+    } else {
+        api
+    }
+}
+
+/// An error as a JSON response.
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self(StatusCode::BAD_REQUEST, message.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<DbError> for ApiError {
+    fn from(e: DbError) -> Self {
+        match e {
+            DbError::NotFound(message) => Self(StatusCode::NOT_FOUND, message),
+            e => Self(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        }
+    }
+}
+
+impl From<olal_ollama::OllamaError> for ApiError {
+    fn from(e: olal_ollama::OllamaError) -> Self {
+        Self(StatusCode::BAD_GATEWAY, e.to_string())
+    }
+}
+
+type ApiResult<T> = Result<T, ApiError>;
+
+#[derive(Deserialize)]
+struct ListParams {
+    limit: Option<i64>,
+}
+
+async fn list_items(
+    State(state): State<Shared>,
+    Query(params): Query<ListParams>,
+) -> ApiResult<Json<Value>> {
+    let items = state.db.list_items(None, Some(params.limit.unwrap_or(50)))?;
+    Ok(Json(json!({ "items": items })))
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+    limit: Option<i64>,
+}
+
+async fn search(
+    State(state): State<Shared>,
+    Query(params): Query<SearchParams>,
+) -> ApiResult<Json<Value>> {
+    let query = params.q.trim();
+    if query.is_empty() {
+        return Err(ApiError::bad_request("Empty search query"));
+    }
+    let items = state
+        .db
+        .search_items_filtered(query, Some(params.limit.unwrap_or(20)), &SearchFilter::default())
+        .map_err(|e| match e {
+            DbError::Sqlite(_) => ApiError::bad_request(format!("Invalid search query: {}", e)),
+            e => e.into(),
+        })?;
+    Ok(Json(json!({ "items": items })))
+}
+
+async fn show_item(State(state): State<Shared>, Path(id): Path<String>) -> ApiResult<Json<Value>> {
+    let item = state.db.get_item(&id)?;
+    let tags: Vec<String> = state.db.get_item_tags(&id)?.into_iter().map(|t| t.name).collect();
+    let chunks = state.db.get_chunks_by_item(&id)?;
+    let media = media_path(&item).is_some();
+    Ok(Json(json!({ "item": item, "tags": tags, "chunks": chunks, "media": media })))
+}
+
+/// The playable source file of a video or audio item.
+fn media_path(item: &olal_core::Item) -> Option<std::path::PathBuf> {
+    if !matches!(item.item_type, ItemType::Video | ItemType::Audio) {
+        return None;
+    }
+    let path = std::path::PathBuf::from(item.source_path.as_ref()?);
+    path.is_file().then_some(path)
+}
+
+async fn item_media(
+    State(state): State<Shared>,
+    Path(id): Path<String>,
+    request: Request,
+) -> ApiResult<Response> {
+    let item = state.db.get_item(&id)?;
+    let path = media_path(&item)
+        .ok_or_else(|| ApiError(StatusCode::NOT_FOUND, "Item has no playable media".to_string()))?;
+    let response = ServeFile::new(path)
+        .try_call(request)
+        .await
+        .map_err(|e| ApiError(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(response.map(Body::new))
+}
+
+#[derive(Deserialize)]
+struct AskRequest {
+    question: String,
+    #[serde(default)]
+    rerank: bool,
+    #[serde(default)]
+    expand: bool,
+}
+
+async fn ask(State(state): State<Shared>, Json(request): Json<AskRequest>) -> ApiResult<Response> {
+    let question = request.question.trim();
+    if question.is_empty() {
+        return Err(ApiError::bad_request("Empty question"));
+    }
+
+    let config = &state.config.ollama;
+    let client = OllamaClient::from_config(config)?;
+    let rag_config = RagConfig {
+        model: config.model.clone(),
+        embedding_model: config.embedding_model.clone(),
+        max_context_chunks: ASK_CONTEXT,
+        min_similarity: ASK_MIN_SIMILARITY,
+        temperature: 0.7,
+        rerank: request.rerank || config.rerank,
+        rerank_model: (!config.rerank_model.is_empty()).then(|| config.rerank_model.clone()),
+        multi_query: request.expand || config.multi_query,
+        context_tokens: config.context_tokens,
+    };
+
+    let mut queries = vec![question.to_string()];
+    if rag_config.multi_query {
+        queries.extend(client.expand_query(question, &rag_config).await?);
+    }
+    let mut rankings = Vec::new();
+    for query in &queries {
+        let embedding = client.embed(&config.embedding_model, query).await?;
+        let results = state.db.vector_search_filtered(
+            &embedding,
+            rag_config.candidate_count(),
+            Some(ASK_MIN_SIMILARITY),
+            &SearchFilter::default(),
+        )?;
+        rankings.push(
+            results
+                .into_iter()
+                .map(|r| ContextItem {
+                    pages: r.chunk.page_label(),
+                    content: r.chunk.content,
+                    similarity: r.similarity,
+                    item_id: r.item_id,
+                    item_title: r.item_title,
+                })
+                .collect(),
+        );
+    }
+    let mut context = fuse_rankings(rankings);
+    context.truncate(rag_config.candidate_count());
+    if context.is_empty() {
+        return Err(ApiError(
+            StatusCode::NOT_FOUND,
+            "No relevant content found in your knowledge base".to_string(),
+        ));
+    }
+
+    let context = if rag_config.rerank {
+        client.rerank(question, context, &rag_config).await?
+    } else {
+        context
+    };
+
+    let (rx, sources) = client.rag_query_stream(question, &context, &[], &rag_config).await?;
+    let first = ndjson(&json!({ "sources": sources }));
+    let pieces = futures_util::stream::unfold(Some(rx), |rx| async move {
+        let mut rx = rx?;
+        match rx.recv().await {
+            Some(text) => Some((ndjson(&json!({ "text": text })), Some(rx))),
+            None => Some((ndjson(&json!({ "done": true })), None)),
+        }
+    });
+    let lines = futures_util::StreamExt::map(
+        futures_util::StreamExt::chain(futures_util::stream::iter([first]), pieces),
+        Ok::<_, std::convert::Infallible>,
+    );
+
+    Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], Body::from_stream(lines)).into_response())
+}
+
+fn ndjson(value: &Value) -> Bytes {
+    Bytes::from(format!("{}\n", value))
+}
+
+/// A file of the UI, with `index.html` for `/` and unknown paths.
+async fn static_asset(uri: Uri) -> Response {
+    let path = uri.path().trim_start_matches('/');
+    let file = Assets::get(path)
+        .filter(|_| !path.is_empty())
+        .or_else(|| Assets::get("index.html"));
+    let Some(file) = file else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    (
+        [(header::CONTENT_TYPE, file.metadata.mimetype().to_string())],
+        Body::from(file.data.into_owned()),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ui_is_embedded() {
+        for file in ["index.html", "app.js", "style.css"] {
+            assert!(Assets::get(file).is_some(), "{} missing", file);
+        }
+    }
+}
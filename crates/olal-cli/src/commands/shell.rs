@@ -1,28 +1,56 @@
 //! Shell command - interactive REPL for Olal.
+//!
+//! Commands are written as slash commands (`/search rust`); the slash is
+//! optional. The database and the Ollama connection are opened once and
+//! shared by every command. History persists in the data directory, and
+//! Tab completes commands, item IDs and tag names.
 
-use super::get_database;
+use super::search::{SearchMode, DEFAULT_CONTEXT_WORDS};
+use super::{get_paths, get_search_database, Ollama};
 use anyhow::Result;
-use olal_config::Config;
 use colored::Colorize;
+use olal_config::Config;
+use olal_db::{Database, SearchFilter};
+use rustyline::completion::{Completer, Pair};
+use rustyline::config::Configurer;
 use rustyline::error::ReadlineError;
-use rustyline::DefaultEditor;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::{Context, Editor, Helper};
+use std::cell::OnceCell;
+
+/// Commands, for completion.
+const COMMANDS: &[&str] = &[
+    "/search", "/semantic", "/ask", "/task", "/recent", "/show", "/stats", "/tags", "/clear",
+    "/help", "/exit",
+];
+
+/// `/task` subcommands, for completion.
+const TASK_COMMANDS: &[&str] = &["add", "done", "list"];
+
+/// Lines of history kept.
+const HISTORY_SIZE: usize = 1000;
 
 /// Run the interactive shell.
 pub fn run() -> Result<()> {
-    let db = get_database()?;
-    let config = Config::load().unwrap_or_default();
+    let session = Session {
+        db: get_search_database()?,
+        config: Config::load().unwrap_or_default(),
+        ollama: OnceCell::new(),
+    };
 
-    let mut rl = DefaultEditor::new()?;
+    let mut rl: Editor<ShellHelper, DefaultHistory> = Editor::new()?;
+    rl.set_max_history_size(HISTORY_SIZE)?;
+    rl.set_helper(Some(ShellHelper::load(&session.db)));
 
-    // Try to load history
-    let history_path = dirs::data_dir()
-        .map(|p| p.join("olal").join("shell_history"))
-        .unwrap_or_default();
+    let history_path = get_paths()?.data_dir.join("shell_history");
     let _ = rl.load_history(&history_path);
 
     println!("{}", "Olal Interactive Shell".cyan().bold());
     println!("{}", "─".repeat(50));
-    println!("Type {} for available commands, {} to exit.", "help".cyan(), "exit".cyan());
+    println!("Type {} for available commands, {} to exit.", "/help".cyan(), "/exit".cyan());
     println!();
 
     loop {
@@ -36,9 +64,15 @@ pub fn run() -> Result<()> {
 
                 let _ = rl.add_history_entry(line);
 
-                if let Err(e) = execute_command(line, &db, &config) {
+                if matches!(line.trim_start_matches('/'), "exit" | "quit" | "q") {
+                    println!("Goodbye!");
+                    break;
+                }
+                if let Err(e) = execute_command(line, &session) {
                     eprintln!("{} {}", "Error:".red(), e);
                 }
+                // Commands may have added items or tags
+                rl.set_helper(Some(ShellHelper::load(&session.db)));
             }
             Err(ReadlineError::Interrupted) => {
                 println!("^C");
@@ -64,56 +98,101 @@ pub fn run() -> Result<()> {
     Ok(())
 }
 
+/// Connections shared by every command in the shell.
+struct Session {
+    db: Database,
+    config: Config,
+    /// Connected on first use, so commands that don't need Ollama work
+    /// without it.
+    ollama: OnceCell<Ollama>,
+}
+
+impl Session {
+    fn ollama(&self) -> Result<&Ollama> {
+        if self.ollama.get().is_none() {
+            let _ = self.ollama.set(Ollama::connect(&self.config.ollama)?);
+        }
+        Ok(self.ollama.get().expect("just connected"))
+    }
+}
+
 /// Execute a shell command.
-fn execute_command(input: &str, db: &olal_db::Database, config: &Config) -> Result<()> {
+fn execute_command(input: &str, session: &Session) -> Result<()> {
     let parts: Vec<&str> = input.split_whitespace().collect();
-    let cmd = parts.first().map(|s| *s).unwrap_or("");
+    let cmd = parts.first().copied().unwrap_or("").trim_start_matches('/');
     let args = &parts[1..];
+    let db = &session.db;
 
     match cmd {
         "help" | "?" => print_help(),
-        "exit" | "quit" | "q" => std::process::exit(0),
 
         "search" | "s" => {
             if args.is_empty() {
-                println!("Usage: search <query>");
+                println!("Usage: /search <query>");
                 return Ok(());
             }
             let query = args.join(" ");
-            super::search::run_with_db(db, &query, 10, false)
+            super::search::run_with_db(db, None, &query, 10, SearchMode::FullText, &SearchFilter::default(), DEFAULT_CONTEXT_WORDS)
         }
 
         "semantic" | "ss" => {
             if args.is_empty() {
-                println!("Usage: semantic <query>");
+                println!("Usage: /semantic <query>");
                 return Ok(());
             }
             let query = args.join(" ");
-            super::search::run_with_db(db, &query, 10, true)
+            let ollama = session.ollama()?;
+            super::search::run_with_db(db, Some(ollama), &query, 10, SearchMode::Semantic, &SearchFilter::default(), DEFAULT_CONTEXT_WORDS)
         }
 
         "ask" | "a" => {
             if args.is_empty() {
-                println!("Usage: ask <question>");
+                println!("Usage: /ask <question>");
                 return Ok(());
             }
             let question = args.join(" ");
-            super::ask::run_with_db(db, config, &question, None, true, 5, false)
+            let ollama = session.ollama()?;
+            super::ask::run_with_db(
+                db,
+                &session.config,
+                ollama,
+                &question,
+                None,
+                true,
+                None,
+                false,
+                false,
+                false,
+                false,
+                &SearchFilter::default(),
+            )
         }
 
+        "task" | "t" => match args {
+            [] | ["list"] => super::task::list_with_db(db, None),
+            ["add", description @ ..] if !description.is_empty() => {
+                super::task::add_with_db(db, &description.join(" "), 0, None, None, None, None)
+            }
+            ["done", id] => super::task::done_with_db(db, id),
+            _ => {
+                println!("Usage: /task [list] | /task add <description> | /task done <id>");
+                Ok(())
+            }
+        },
+
         "recent" | "r" => {
             let limit = args.first()
                 .and_then(|s| s.parse::<i64>().ok())
                 .unwrap_or(10);
-            super::recent::run_with_db(db, limit, None)
+            super::recent::run_with_db(db, limit, None, false)
         }
 
         "show" => {
             if args.is_empty() {
-                println!("Usage: show <item_id>");
+                println!("Usage: /show <item_id> [--chunks]");
                 return Ok(());
             }
-            super::show::run_with_db(db, args[0])
+            super::show::run_with_db(db, args[0], args.contains(&"--chunks"))
         }
 
         "stats" => {
@@ -145,7 +224,7 @@ fn execute_command(input: &str, db: &olal_db::Database, config: &Config) -> Resu
                 "{} Unknown command: '{}'. Type {} for help.",
                 "?".yellow(),
                 cmd,
-                "help".cyan()
+                "/help".cyan()
             );
             Ok(())
         }
@@ -156,18 +235,155 @@ fn execute_command(input: &str, db: &olal_db::Database, config: &Config) -> Resu
 fn print_help() -> Result<()> {
     println!("{}", "Available Commands:".cyan().bold());
     println!();
-    println!("  {}          Search the knowledge base", "search <query>".white());
-    println!("  {}         Semantic search", "semantic <query>".white());
-    println!("  {}              Ask a question (RAG)", "ask <question>".white());
-    println!("  {}               List recent items", "recent [limit]".white());
-    println!("  {}               Show item details", "show <id>".white());
-    println!("  {}                     Show database statistics", "stats".white());
-    println!("  {}                      List all tags", "tags".white());
-    println!("  {}                     Clear the screen", "clear".white());
-    println!("  {}                      Exit the shell", "exit".white());
+    println!("  {}         Search the knowledge base", "/search <query>".white());
+    println!("  {}       Semantic search", "/semantic <query>".white());
+    println!("  {}            Ask a question (RAG)", "/ask <question>".white());
+    println!("  {}             List tasks", "/task [list]".white());
+    println!("  {}     Add a task", "/task add <description>".white());
+    println!("  {}          Complete a task", "/task done <id>".white());
+    println!("  {}             List recent items", "/recent [limit]".white());
+    println!("  {}  Show item details (--chunks for all content)", "/show <id> [--chunks]".white());
+    println!("  {}                   Show database statistics", "/stats".white());
+    println!("  {}                    List all tags", "/tags".white());
+    println!("  {}                   Clear the screen", "/clear".white());
+    println!("  {}                    Exit the shell", "/exit".white());
     println!();
     println!("{}", "Shortcuts:".cyan().bold());
-    println!("  {} = search, {} = semantic, {} = ask, {} = recent", "s".yellow(), "ss".yellow(), "a".yellow(), "r".yellow());
+    println!(
+        "  {} = search, {} = semantic, {} = ask, {} = task, {} = recent",
+        "/s".yellow(),
+        "/ss".yellow(),
+        "/a".yellow(),
+        "/t".yellow(),
+        "/r".yellow()
+    );
+    println!("  The slash is optional; {} completes commands, item IDs and tags.", "Tab".yellow());
     println!();
     Ok(())
 }
+
+/// Tab completion of commands, item IDs and tag names.
+struct ShellHelper {
+    /// (id, title) of every item, newest first.
+    items: Vec<(String, String)>,
+    tags: Vec<String>,
+}
+
+impl ShellHelper {
+    /// Load the items and tags to complete. Completion is skipped if they
+    /// can't be read.
+    fn load(db: &Database) -> Self {
+        Self {
+            items: db.item_titles().unwrap_or_default(),
+            tags: db
+                .list_tags()
+                .map(|tags| tags.into_iter().map(|t| t.name).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Completions for the word ending at `pos` in `line`, and where it starts.
+fn complete(
+    line: &str,
+    pos: usize,
+    items: &[(String, String)],
+    tags: &[String],
+) -> (usize, Vec<Pair>) {
+    let before = &line[..pos];
+    let start = before.rfind(char::is_whitespace).map(|i| i + 1).unwrap_or(0);
+    let word = &before[start..];
+    let pair = |display: String, replacement: String| Pair { display, replacement };
+
+    // The command itself, with the slash if it was typed
+    if start == 0 {
+        let slash = word.starts_with('/');
+        let name = word.trim_start_matches('/');
+        let candidates = COMMANDS
+            .iter()
+            .map(|c| c.trim_start_matches('/'))
+            .filter(|c| c.starts_with(name))
+            .map(|c| {
+                let command = if slash { format!("/{}", c) } else { c.to_string() };
+                pair(command.clone(), command)
+            })
+            .collect();
+        return (start, candidates);
+    }
+
+    let mut words = before.split_whitespace();
+    let command = words.next().unwrap_or("").trim_start_matches('/');
+    if matches!(command, "task" | "t") && before[..start].split_whitespace().count() == 1 {
+        let candidates = TASK_COMMANDS
+            .iter()
+            .filter(|c| c.starts_with(word))
+            .map(|c| pair(c.to_string(), c.to_string()))
+            .collect();
+        return (start, candidates);
+    }
+
+    let lower = word.to_lowercase();
+    let mut candidates: Vec<Pair> = tags
+        .iter()
+        .filter(|t| t.to_lowercase().starts_with(&lower))
+        .map(|t| pair(t.clone(), t.clone()))
+        .collect();
+    if !word.is_empty() {
+        candidates.extend(items.iter().filter(|(id, _)| id.starts_with(word)).map(|(id, title)| {
+            let short: String = id.chars().take(8).collect();
+            pair(format!("{}  {}", short, title), short)
+        }));
+    }
+    (start, candidates)
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(complete(line, pos, &self.items, &self.tags))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacements(line: &str) -> Vec<String> {
+        let items = vec![("1a2b3c4d-0000".to_string(), "Rust ownership".to_string())];
+        let tags = vec!["rust".to_string(), "Recipes".to_string()];
+        complete(line, line.len(), &items, &tags)
+            .1
+            .into_iter()
+            .map(|p| p.replacement)
+            .collect()
+    }
+
+    #[test]
+    fn test_complete() {
+        assert_eq!(replacements("/se"), vec!["/search", "/semantic"]);
+        assert_eq!(replacements("as"), vec!["ask"]);
+        assert_eq!(replacements("/task d"), vec!["done"]);
+        assert_eq!(replacements("/search r"), vec!["rust", "Recipes"]);
+        assert_eq!(replacements("/show 1a"), vec!["1a2b3c4d"]);
+        assert!(replacements("/show zz").is_empty());
+
+        let (start, _) = complete("/show 1a", 8, &[], &[]);
+        assert_eq!(start, 6);
+    }
+}
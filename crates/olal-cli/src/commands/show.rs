@@ -1,20 +1,23 @@
 //! Show command - display item details.
 
-use super::get_database;
+use super::{dates, get_database, output, resolve};
 use anyhow::Result;
 use olal_core::ItemType;
 use colored::Colorize;
 use serde_json;
+use std::fmt::Write;
 
-pub fn run(id: &str) -> Result<()> {
+pub fn run(id: &str, all_chunks: bool) -> Result<()> {
     let db = get_database()?;
-    run_with_db(&db, id)
+    run_with_db(&db, id, all_chunks)
 }
 
-/// Run show with an existing database connection.
-pub fn run_with_db(db: &olal_db::Database, id: &str) -> Result<()> {
-
-    let item = db.get_item(id)?;
+/// Run show with an existing database connection. With `all_chunks`,
+/// every chunk is shown in full instead of a short preview. The output
+/// goes through the pager.
+pub fn run_with_db(db: &olal_db::Database, id: &str, all_chunks: bool) -> Result<()> {
+    let item = resolve::item(db, id)?;
+    db.mark_item_viewed(&item.id)?;
 
     let type_icon = match item.item_type {
         ItemType::Video => "🎬",
@@ -26,96 +29,141 @@ pub fn run_with_db(db: &olal_db::Database, id: &str) -> Result<()> {
         ItemType::Bookmark => "🔖",
     };
 
-    println!("{} {}", type_icon, item.title.white().bold());
-    println!("{}", "─".repeat(70));
+    let mut out = String::new();
+    writeln!(out, "{} {}", type_icon, item.title.white().bold())?;
+    writeln!(out, "{}", "─".repeat(70))?;
 
-    println!("  {}: {}", "ID".cyan(), item.id);
-    println!("  {}: {}", "Type".cyan(), item.item_type);
-    println!(
+    writeln!(out, "  {}: {}", "ID".cyan(), item.id)?;
+    writeln!(out, "  {}: {}", "Type".cyan(), item.item_type)?;
+    writeln!(
+        out,
         "  {}: {}",
         "Created".cyan(),
-        item.created_at.format("%Y-%m-%d %H:%M:%S")
-    );
+        dates::format_datetime(item.created_at)
+    )?;
 
     if let Some(processed) = item.processed_at {
-        println!(
+        writeln!(
+            out,
             "  {}: {}",
             "Processed".cyan(),
-            processed.format("%Y-%m-%d %H:%M:%S")
-        );
+            dates::format_datetime(processed)
+        )?;
     }
 
     if let Some(ref path) = item.source_path {
-        println!("  {}: {}", "Source".cyan(), path);
+        writeln!(out, "  {}: {}", "Source".cyan(), path)?;
+    }
+
+    if let Some(thumbnail) = item.metadata["thumbnail"].as_str() {
+        writeln!(out, "  {}: {}", "Thumbnail".cyan(), thumbnail)?;
     }
 
     if let Some(ref hash) = item.content_hash {
-        println!("  {}: {}", "Hash".cyan(), hash);
+        writeln!(out, "  {}: {}", "Hash".cyan(), hash)?;
+    }
+
+    // Earlier or later content at the same path (a reused filename)
+    if let Some(ref path) = item.source_path {
+        let versions = db.find_items_by_path(path)?;
+        if versions.len() > 1 {
+            writeln!(out, "  {}:", "Versions".cyan())?;
+            for version in &versions {
+                let marker = if version.id == item.id { " (this)" } else { "" };
+                writeln!(
+                    out,
+                    "    {} {}{}",
+                    version.id.chars().take(8).collect::<String>().dimmed(),
+                    dates::format_datetime(version.created_at),
+                    marker
+                )?;
+            }
+        }
     }
 
     // Tags
     let tags = db.get_item_tags(&item.id)?;
     if !tags.is_empty() {
         let tag_names: Vec<String> = tags.iter().map(|t| t.name.clone()).collect();
-        println!("  {}: {}", "Tags".cyan(), tag_names.join(", ").yellow());
+        writeln!(out, "  {}: {}", "Tags".cyan(), tag_names.join(", ").yellow())?;
+    }
+
+    // Projects
+    let projects = db.get_item_projects(&item.id)?;
+    if !projects.is_empty() {
+        let project_names: Vec<&str> = projects.iter().map(|p| p.name.as_str()).collect();
+        writeln!(out, "  {}: {}", "Projects".cyan(), project_names.join(", "))?;
+    }
+
+    // Entities
+    let entities = db.get_item_entities(&item.id)?;
+    if !entities.is_empty() {
+        let entity_names: Vec<String> = entities.iter().map(|e| e.name.clone()).collect();
+        writeln!(out, "  {}: {}", "Entities".cyan(), entity_names.join(", "))?;
     }
 
     // Summary
     if let Some(ref summary) = item.summary {
-        println!();
-        println!("{}", "Summary".white().bold());
-        println!("{}", "─".repeat(70));
-        println!("{}", summary);
+        writeln!(out)?;
+        writeln!(out, "{}", "Summary".white().bold())?;
+        writeln!(out, "{}", "─".repeat(70))?;
+        writeln!(out, "{}", summary)?;
     }
 
-    // Chunks preview
+    // Chunks: a preview of the first few, or all of them
     let chunks = db.get_chunks_by_item(&item.id)?;
     if !chunks.is_empty() {
-        println!();
-        println!(
-            "{} ({} chunks)",
-            "Content Preview".white().bold(),
-            chunks.len()
-        );
-        println!("{}", "─".repeat(70));
-
-        // Show first few chunks
-        for chunk in chunks.iter().take(3) {
-            let preview = if chunk.content.len() > 200 {
+        let heading = if all_chunks { "Content" } else { "Content Preview" };
+        writeln!(out)?;
+        writeln!(out, "{} ({} chunks)", heading.white().bold(), chunks.len())?;
+        writeln!(out, "{}", "─".repeat(70))?;
+
+        let shown = if all_chunks { chunks.len() } else { 3 };
+        for chunk in chunks.iter().take(shown) {
+            let text = if !all_chunks && chunk.content.len() > 200 {
                 format!("{}...", &chunk.content[..197])
             } else {
                 chunk.content.clone()
             };
 
             if let (Some(start), Some(end)) = (chunk.start_time, chunk.end_time) {
-                println!(
-                    "[{:.1}s - {:.1}s]",
-                    start,
-                    end
-                );
+                writeln!(out, "[{:.1}s - {:.1}s]", start, end)?;
+            }
+            if let Some(pages) = chunk.page_label() {
+                writeln!(out, "[{}]", pages)?;
+            }
+            if all_chunks {
+                writeln!(out, "{}", text)?;
+            } else {
+                writeln!(out, "{}", text.dimmed())?;
             }
-            println!("{}", preview.dimmed());
-            println!();
+            writeln!(out)?;
         }
 
-        if chunks.len() > 3 {
-            println!(
+        if chunks.len() > shown {
+            writeln!(
+                out,
                 "{}",
-                format!("... and {} more chunks", chunks.len() - 3).dimmed()
-            );
+                format!(
+                    "... and {} more chunks (see them all with --chunks)",
+                    chunks.len() - shown
+                )
+                .dimmed()
+            )?;
         }
     }
 
     // Metadata
     if !item.metadata.is_null() && item.metadata != serde_json::json!({}) {
-        println!();
-        println!("{}", "Metadata".white().bold());
-        println!("{}", "─".repeat(70));
-        println!(
+        writeln!(out)?;
+        writeln!(out, "{}", "Metadata".white().bold())?;
+        writeln!(out, "{}", "─".repeat(70))?;
+        writeln!(
+            out,
             "{}",
             serde_json::to_string_pretty(&item.metadata)?.dimmed()
-        );
+        )?;
     }
 
-    Ok(())
+    output::page(&out)
 }
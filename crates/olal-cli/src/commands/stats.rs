@@ -1,8 +1,14 @@
-//! Stats command - show database statistics.
+//! Stats command - show database statistics, or your own usage with `--me`.
+//!
+//! Besides the totals, stats charts ingestion per day and week, embedding
+//! coverage of each week's items, the most-used tags and the largest items.
 
 use super::{format_size, get_database};
 use anyhow::Result;
+use chrono::{Duration, Local, Timelike, Utc};
 use colored::Colorize;
+use olal_db::{ActivityBucket, CommandUsage, Database, TimeBucket};
+use std::collections::{HashMap, HashSet};
 
 pub fn run() -> Result<()> {
     let db = get_database()?;
@@ -10,7 +16,7 @@ pub fn run() -> Result<()> {
 }
 
 /// Run stats with an existing database connection.
-pub fn run_with_db(db: &olal_db::Database) -> Result<()> {
+pub fn run_with_db(db: &Database) -> Result<()> {
     let stats = db.get_stats()?;
 
     println!("{}", "Olal Statistics".cyan().bold());
@@ -39,6 +45,10 @@ pub fn run_with_db(db: &olal_db::Database) -> Result<()> {
 
     println!("  Total chunks: {}", stats.total_chunks);
 
+    if stats.total_items > 0 {
+        print_trends(db)?;
+    }
+
     // Organization
     println!();
     println!("{}", "Organization".white().bold());
@@ -67,3 +77,315 @@ pub fn run_with_db(db: &olal_db::Database) -> Result<()> {
 
     Ok(())
 }
+
+/// Days shown in the daily sparkline.
+const DAILY_BUCKETS: usize = 30;
+/// Weeks shown in the weekly chart.
+const WEEKLY_BUCKETS: usize = 12;
+/// Rows in the tag and largest-item lists.
+const TOP_ROWS: usize = 5;
+/// Width of the longest bar.
+const BAR_WIDTH: usize = 24;
+
+/// Ingestion and embedding trends, tags and the largest items.
+fn print_trends(db: &Database) -> Result<()> {
+    let days = db.ingestion_timeline(TimeBucket::Day, DAILY_BUCKETS)?;
+    let weeks = db.ingestion_timeline(TimeBucket::Week, WEEKLY_BUCKETS)?;
+
+    println!();
+    println!("{}", "Activity".white().bold());
+    let daily: Vec<f64> = days.iter().map(|d| d.items as f64).collect();
+    println!(
+        "  Last {} days  {}  {} items",
+        DAILY_BUCKETS,
+        sparkline(&daily).cyan(),
+        days.iter().map(|d| d.items).sum::<i64>()
+    );
+    let peak = weeks.iter().map(|w| w.items).max().unwrap_or(0);
+    for week in &weeks {
+        println!(
+            "  {}  {} {}",
+            week.start.format("%b %d"),
+            bar(week.items, peak, BAR_WIDTH).cyan(),
+            week.items
+        );
+    }
+
+    println!();
+    println!("{}", "Embedding Coverage".white().bold());
+    let (embedded, total) = db.embedding_stats()?;
+    if total > 0 {
+        println!(
+            "  Chunks: {}/{} ({:.0}%)",
+            embedded,
+            total,
+            embedded as f64 * 100.0 / total as f64
+        );
+    }
+    println!(
+        "  Items by week  {}  {}",
+        sparkline(&coverage(&weeks)).green(),
+        "(share of each week's items embedded)".dimmed()
+    );
+
+    let tags: Vec<_> = db
+        .get_tag_counts()?
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .take(TOP_ROWS)
+        .collect();
+    if !tags.is_empty() {
+        println!();
+        println!("{}", "Top Tags".white().bold());
+        let width = tags.iter().map(|(tag, _)| tag.name.chars().count()).max().unwrap_or(0);
+        let peak = tags[0].1;
+        for (tag, count) in &tags {
+            println!(
+                "  {:<width$}  {} {}",
+                tag.name,
+                bar(*count, peak, BAR_WIDTH / 2).cyan(),
+                count,
+                width = width
+            );
+        }
+    }
+
+    let largest = db.largest_items(TOP_ROWS)?;
+    if !largest.is_empty() {
+        println!();
+        println!("{}", "Largest Items".white().bold());
+        for (item, chunks) in &largest {
+            println!(
+                "  {}  {:>5} chunks  {}",
+                item.id.chars().take(8).collect::<String>().dimmed(),
+                chunks,
+                item.title
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Share of each bucket's items that are embedded (0 for empty buckets).
+fn coverage(buckets: &[ActivityBucket]) -> Vec<f64> {
+    buckets
+        .iter()
+        .map(|b| if b.items > 0 { b.embedded as f64 / b.items as f64 } else { 0.0 })
+        .collect()
+}
+
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One block character per value, scaled to the largest. Zero is the
+/// lowest block; anything above zero shows at least the second.
+fn sparkline(values: &[f64]) -> String {
+    let max = values.iter().copied().fold(0.0, f64::max);
+    values
+        .iter()
+        .map(|&v| {
+            if v <= 0.0 || max <= 0.0 {
+                SPARKS[0]
+            } else {
+                SPARKS[1 + ((v / max) * 6.0).floor() as usize]
+            }
+        })
+        .collect()
+}
+
+/// A bar of up to `width` blocks, scaled to `max`.
+fn bar(value: i64, max: i64, width: usize) -> String {
+    if value <= 0 || max <= 0 {
+        return String::new();
+    }
+    "█".repeat(((value as usize) * width).div_ceil(max as usize).max(1))
+}
+
+/// Show your own usage patterns from the local command history.
+pub fn me(days: i64) -> Result<()> {
+    let db = get_database()?;
+    let usage = db.command_usage_since(Utc::now() - Duration::days(days.max(1)))?;
+
+    println!("{}", format!("Your Usage (last {} days)", days).cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if usage.is_empty() {
+        println!("{}", "No usage recorded yet.".dimmed());
+        println!("Usage is recorded when general.record_usage is on (the default).");
+        return Ok(());
+    }
+
+    let summary = UsageSummary::from_usage(&usage);
+
+    println!();
+    print!("  Commands run: {}", summary.total.to_string().green());
+    if summary.failed > 0 {
+        print!(" ({} failed)", summary.failed.to_string().red());
+    }
+    println!();
+    println!("  Active days: {}", summary.active_days);
+    println!("  Time in olal: {}", format_duration(summary.total_ms));
+
+    println!();
+    println!("{}", "Most Used".white().bold());
+    for (command, count, avg_ms) in summary.by_command.iter().take(10) {
+        println!(
+            "  {:<20} {:>5}  {}",
+            command,
+            count,
+            format!("avg {}", format_duration(*avg_ms)).dimmed()
+        );
+    }
+
+    println!();
+    println!("{}", "Search vs Ask".white().bold());
+    println!("  Searches: {}  Questions: {}", summary.searches, summary.asks);
+    if summary.searches > 0 && summary.asks > 0 {
+        println!(
+            "  {}",
+            format!("{:.1} searches per question", summary.searches as f64 / summary.asks as f64).dimmed()
+        );
+    }
+
+    println!();
+    println!("{}", "Busiest Hours".white().bold());
+    let peak = summary.by_hour.iter().copied().max().unwrap_or(0).max(1);
+    let mut hours: Vec<(usize, usize)> = summary
+        .by_hour
+        .iter()
+        .copied()
+        .enumerate()
+        .filter(|(_, count)| *count > 0)
+        .collect();
+    hours.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    for (hour, count) in hours.into_iter().take(5) {
+        println!(
+            "  {:02}:00  {} {}",
+            hour,
+            "█".repeat((count * 20).div_ceil(peak)).cyan(),
+            count
+        );
+    }
+
+    println!();
+    println!(
+        "{}",
+        "Recorded locally, never uploaded. Turn off with: olal config set general.record_usage false".dimmed()
+    );
+
+    Ok(())
+}
+
+/// Aggregates over a run of command invocations.
+#[derive(Debug, Default, PartialEq)]
+struct UsageSummary {
+    total: usize,
+    failed: usize,
+    total_ms: i64,
+    active_days: usize,
+    /// (command, count, average duration in ms), most used first.
+    by_command: Vec<(String, usize, i64)>,
+    searches: usize,
+    asks: usize,
+    /// Invocations per local hour of day.
+    by_hour: [usize; 24],
+}
+
+impl UsageSummary {
+    fn from_usage(usage: &[CommandUsage]) -> Self {
+        let mut summary = UsageSummary::default();
+        let mut commands: HashMap<&str, (usize, i64)> = HashMap::new();
+        let mut days = HashSet::new();
+
+        for entry in usage {
+            let local = entry.started_at.with_timezone(&Local);
+            summary.total += 1;
+            summary.total_ms += entry.duration_ms;
+            if !entry.success {
+                summary.failed += 1;
+            }
+            summary.by_hour[local.hour() as usize] += 1;
+            days.insert(local.date_naive());
+
+            let stats = commands.entry(entry.command.as_str()).or_default();
+            stats.0 += 1;
+            stats.1 += entry.duration_ms;
+
+            match entry.command.as_str() {
+                "search" | "quicksearch" => summary.searches += 1,
+                "ask" => summary.asks += 1,
+                _ => {}
+            }
+        }
+
+        summary.active_days = days.len();
+        summary.by_command = commands
+            .into_iter()
+            .map(|(command, (count, ms))| (command.to_string(), count, ms / count as i64))
+            .collect();
+        summary
+            .by_command
+            .sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        summary
+    }
+}
+
+fn format_duration(ms: i64) -> String {
+    match ms {
+        ms if ms < 1000 => format!("{}ms", ms),
+        ms if ms < 60_000 => format!("{:.1}s", ms as f64 / 1000.0),
+        ms if ms < 3_600_000 => format!("{}m {}s", ms / 60_000, ms % 60_000 / 1000),
+        ms => format!("{}h {}m", ms / 3_600_000, ms % 3_600_000 / 60_000),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(command: &str, duration_ms: i64, success: bool) -> CommandUsage {
+        CommandUsage {
+            command: command.to_string(),
+            started_at: Utc::now(),
+            duration_ms,
+            success,
+        }
+    }
+
+    #[test]
+    fn test_usage_summary() {
+        let summary = UsageSummary::from_usage(&[
+            usage("search", 100, true),
+            usage("ask", 3000, true),
+            usage("search", 300, true),
+            usage("quicksearch", 50, false),
+        ]);
+
+        assert_eq!(summary.total, 4);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.active_days, 1);
+        assert_eq!(summary.by_command[0], ("search".to_string(), 2, 200));
+        assert_eq!((summary.searches, summary.asks), (3, 1));
+        assert_eq!(summary.by_hour.iter().sum::<usize>(), 4);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[0.0, 1.0, 5.0, 10.0]), "▁▂▅█");
+        assert_eq!(sparkline(&[0.0, 0.0]), "▁▁");
+    }
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(10, 10, 4), "████");
+        assert_eq!(bar(1, 10, 4), "█");
+        assert_eq!(bar(0, 10, 4), "");
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(250), "250ms");
+        assert_eq!(format_duration(1500), "1.5s");
+        assert_eq!(format_duration(125_000), "2m 5s");
+        assert_eq!(format_duration(7_500_000), "2h 5m");
+    }
+}
@@ -2,11 +2,16 @@
 
 use super::get_database;
 use anyhow::Result;
+use chrono::Utc;
 use colored::Colorize;
 
-pub fn run() -> Result<()> {
+pub fn run(short: bool) -> Result<()> {
     let db = get_database()?;
 
+    if short {
+        return run_short(&db);
+    }
+
     println!("{}", "Olal Status".cyan().bold());
     println!("{}", "─".repeat(50));
 
@@ -117,3 +122,49 @@ pub fn run() -> Result<()> {
 
     Ok(())
 }
+
+/// Print a compact single-line status for prompt segments (tmux, starship).
+///
+/// Uses a single query and never touches Ollama, usage history or the
+/// config file (beyond what an active profile or vault needs), so it stays
+/// fast enough to run on every prompt.
+fn run_short(db: &olal_db::Database) -> Result<()> {
+    let end_of_day = Utc::now()
+        .date_naive()
+        .and_hms_opt(23, 59, 59)
+        .unwrap()
+        .and_utc();
+
+    let (queue, due, unread) = db.status_summary(end_of_day)?;
+
+    println!("{}", format_short(queue, due, unread));
+    Ok(())
+}
+
+/// Format the short status line. Zero counts are omitted; an empty line
+/// means there's nothing to report.
+fn format_short(queue: i64, due: i64, unread: i64) -> String {
+    let mut parts = Vec::new();
+    if queue > 0 {
+        parts.push(format!("q:{}", queue));
+    }
+    if due > 0 {
+        parts.push(format!("due:{}", due));
+    }
+    if unread > 0 {
+        parts.push(format!("unread:{}", unread));
+    }
+    parts.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_short() {
+        assert_eq!(format_short(3, 2, 5), "q:3 due:2 unread:5");
+        assert_eq!(format_short(0, 1, 0), "due:1");
+        assert_eq!(format_short(0, 0, 0), "");
+    }
+}
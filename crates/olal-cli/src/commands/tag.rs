@@ -1,14 +1,32 @@
 //! Tag management commands.
 
-use super::get_database;
+use super::{dates, get_database, resolve};
 use anyhow::Result;
 use colored::Colorize;
+use olal_core::Tag;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 pub fn add(item_id: &str, tag_name: &str) -> Result<()> {
     let db = get_database()?;
 
-    // Verify item exists
-    let item = db.get_item(item_id)?;
+    let item = resolve::item(&db, item_id)?;
+
+    // Point out likely typos before creating a new tag
+    if db.get_tag_by_name(tag_name)?.is_none() {
+        let tags = db.list_tags()?;
+        let similar: Vec<&str> = resolve::similar_tags(tag_name, &tags)
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect();
+        if !similar.is_empty() {
+            println!(
+                "{} Creating new tag '{}' (similar: {})",
+                "Note:".yellow(),
+                tag_name,
+                similar.join(", ")
+            );
+        }
+    }
 
     // Add tag (creates if doesn't exist)
     let tag = db.tag_item(&item.id, tag_name)?;
@@ -23,12 +41,14 @@ pub fn add(item_id: &str, tag_name: &str) -> Result<()> {
     Ok(())
 }
 
+/// List tags as a tree, each with the number of items carrying it or
+/// any of its sub-tags.
 pub fn list() -> Result<()> {
     let db = get_database()?;
 
-    let tag_counts = db.get_tag_counts()?;
+    let tags = db.list_tags()?;
 
-    if tag_counts.is_empty() {
+    if tags.is_empty() {
         println!(
             "{}",
             "No tags found. Use 'olal tag <item-id> <tag>' to create one.".dimmed()
@@ -36,10 +56,46 @@ pub fn list() -> Result<()> {
         return Ok(());
     }
 
+    let by_id: HashMap<&str, &Tag> = tags.iter().map(|t| (t.id.as_str(), t)).collect();
+
+    // Count each item once per tag it falls under
+    let item_tags = db.get_all_item_tags()?;
+    let mut items_under: HashMap<&str, HashSet<&str>> = HashMap::new();
+    for (item_id, tag) in &item_tags {
+        let mut current = Some(tag.id.as_str());
+        while let Some(id) = current {
+            items_under.entry(id).or_default().insert(item_id);
+            current = by_id.get(id).and_then(|t| t.parent_id.as_deref());
+        }
+    }
+    let count = |tag: &Tag| items_under.get(tag.id.as_str()).map_or(0, HashSet::len);
+
+    let mut children: HashMap<Option<&str>, Vec<&Tag>> = HashMap::new();
+    for tag in &tags {
+        let parent = tag
+            .parent_id
+            .as_deref()
+            .filter(|id| by_id.contains_key(id));
+        children.entry(parent).or_default().push(tag);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by(|a, b| count(b).cmp(&count(a)).then_with(|| a.name.cmp(&b.name)));
+    }
+
     println!("{}", "Tags".cyan().bold());
     println!("{}", "─".repeat(50));
+    print_tree(&children, None, 1, &count);
 
-    for (tag, count) in tag_counts {
+    Ok(())
+}
+
+fn print_tree(
+    children: &HashMap<Option<&str>, Vec<&Tag>>,
+    parent: Option<&str>,
+    depth: usize,
+    count: &dyn Fn(&Tag) -> usize,
+) {
+    for tag in children.get(&parent).into_iter().flatten() {
         let color_indicator = if let Some(ref color) = tag.color {
             format!(" ({})", color)
         } else {
@@ -47,11 +103,144 @@ pub fn list() -> Result<()> {
         };
 
         println!(
-            "  {} {}{} ({})",
+            "{}{} {}{} ({})",
+            "  ".repeat(depth),
             "•".yellow(),
-            tag.name.white(),
+            tag.leaf_name().white(),
             color_indicator.dimmed(),
-            count
+            count(tag)
+        );
+        print_tree(children, Some(tag.id.as_str()), depth + 1, count);
+    }
+}
+
+pub fn rename(name: &str, new_name: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let tag = resolve::tag(&db, name)?;
+    if let Some(existing) = db.get_tag_by_name(new_name)? {
+        if existing.id != tag.id {
+            anyhow::bail!(
+                "Tag '{}' already exists. Use 'olal tag merge {} {}' to combine them.",
+                existing.name,
+                tag.name,
+                existing.name
+            );
+        }
+    }
+
+    db.rename_tag(&tag.id, new_name)?;
+
+    println!(
+        "{} Renamed tag '{}' to '{}'",
+        "✓".green(),
+        tag.name,
+        new_name.yellow()
+    );
+
+    Ok(())
+}
+
+pub fn merge(from: &str, into: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let from_tag = resolve::tag(&db, from)?;
+    let into_tag = resolve::tag(&db, into)?;
+    if from_tag.id == into_tag.id {
+        anyhow::bail!("Can't merge tag '{}' into itself", from_tag.name);
+    }
+
+    let moved = db.get_items_by_tag(&from_tag.id)?.len();
+    let added = db.merge_tags(&from_tag.id, &into_tag.id)?;
+
+    println!(
+        "{} Merged '{}' into '{}' ({} items, {} newly tagged)",
+        "✓".green(),
+        from_tag.name,
+        into_tag.name.yellow(),
+        moved,
+        added
+    );
+
+    Ok(())
+}
+
+pub fn delete(name: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let tag = resolve::tag(&db, name)?;
+    let sub_tags = db.get_tag_descendants(&tag.id)?.len();
+    let count = db.get_items_by_tag_tree(&tag.id)?.len();
+    db.delete_tag(&tag.id)?;
+
+    println!(
+        "{} Deleted tag '{}' (removed from {} items)",
+        "✓".green(),
+        tag.name,
+        count
+    );
+    if sub_tags > 0 {
+        println!("  {}", format!("Also deleted {} sub-tags", sub_tags).dimmed());
+    }
+
+    Ok(())
+}
+
+pub fn show(name: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let tag = resolve::tag(&db, name)?;
+    let sub_tags = db.get_tag_descendants(&tag.id)?;
+    let mut items = db
+        .get_items_by_tag_tree(&tag.id)?
+        .iter()
+        .map(|id| db.get_item(id))
+        .collect::<Result<Vec<_>, _>>()?;
+    items.sort_by_key(|item| std::cmp::Reverse(item.created_at));
+
+    println!("{} {}", "Tag".cyan().bold(), tag.name.yellow().bold());
+    println!("{}", "─".repeat(50));
+    println!("  {}: {}", "ID".cyan(), tag.id);
+    if let Some(ref color) = tag.color {
+        println!("  {}: {}", "Color".cyan(), color);
+    }
+    if !sub_tags.is_empty() {
+        let names: Vec<&str> = sub_tags.iter().map(|t| t.name.as_str()).collect();
+        println!("  {}: {}", "Sub-tags".cyan(), names.join(", ").yellow());
+    }
+    println!("  {}: {}", "Items".cyan(), items.len());
+
+    if items.is_empty() {
+        return Ok(());
+    }
+
+    let mut by_type: BTreeMap<String, usize> = BTreeMap::new();
+    for item in &items {
+        *by_type.entry(item.item_type.to_string()).or_default() += 1;
+    }
+    let breakdown: Vec<String> = by_type
+        .iter()
+        .map(|(item_type, count)| format!("{} {}", count, item_type))
+        .collect();
+    println!("  {}: {}", "Types".cyan(), breakdown.join(", "));
+
+    if let (Some(newest), Some(oldest)) = (items.first(), items.last()) {
+        println!(
+            "  {}: {} - {}",
+            "Span".cyan(),
+            dates::format_datetime(oldest.created_at),
+            dates::format_datetime(newest.created_at)
+        );
+    }
+
+    println!();
+    for item in &items {
+        println!(
+            "  {} {} {} {}",
+            "•".yellow(),
+            item.title.white(),
+            format!("[{}]", item.id.chars().take(8).collect::<String>()).dimmed(),
+            item.item_type.to_string().dimmed()
         );
     }
 
@@ -1,23 +1,58 @@
 //! Task management commands.
 
-use super::get_database;
+use super::{dates, get_database, resolve};
 use anyhow::Result;
-use olal_core::{Task, TaskStatus};
+use olal_core::{Recurrence, Task, TaskStatus};
+use olal_db::Database;
+use chrono::{Local, Utc};
 use colored::Colorize;
+use std::collections::HashMap;
 
-pub fn add(description: &str, priority: i32, project: Option<String>) -> Result<()> {
-    let db = get_database()?;
+pub fn add(
+    description: &str,
+    priority: i32,
+    project: Option<String>,
+    due: Option<String>,
+    repeat: Option<String>,
+    parent: Option<String>,
+) -> Result<()> {
+    add_with_db(&get_database()?, description, priority, project, due, repeat, parent)
+}
 
+/// Add a task with an existing database connection.
+pub fn add_with_db(
+    db: &Database,
+    description: &str,
+    priority: i32,
+    project: Option<String>,
+    due: Option<String>,
+    repeat: Option<String>,
+    parent: Option<String>,
+) -> Result<()> {
     let mut task = Task::new(description).with_priority(priority);
+    if let Some(ref parent) = parent {
+        let parent = find_task(db, parent)?;
+        // Subtasks belong to their parent's project unless told otherwise
+        if let Some(project_id) = parent.project_id {
+            task = task.with_project(project_id);
+        }
+        task = task.with_parent(parent.id);
+    }
+    if let Some(ref due) = due {
+        task = task.with_due_date(dates::parse_due(due)?);
+    }
+    if let Some(ref repeat) = repeat {
+        let recurrence = parse_recurrence(repeat)?;
+        // Recurring tasks need a first due date; default to today
+        if task.due_date.is_none() {
+            task = task.with_due_date(dates::end_of_day(Local::now().date_naive()));
+        }
+        task = task.with_recurrence(recurrence);
+    }
 
     // If project specified, find it
     if let Some(ref project_name) = project {
-        let proj = db.get_project_by_name(project_name)?;
-        if let Some(p) = proj {
-            task = task.with_project(p.id);
-        } else {
-            anyhow::bail!("Project not found: {}", project_name);
-        }
+        task = task.with_project(resolve::project(db, project_name)?.id);
     }
 
     db.create_task(&task)?;
@@ -31,13 +66,25 @@ pub fn add(description: &str, priority: i32, project: Option<String>) -> Result<
         "  ID: {}",
         task.id.chars().take(8).collect::<String>().dimmed()
     );
+    if let Some(due_date) = task.due_date {
+        println!("  Due: {}", dates::format_datetime(due_date));
+    }
+    if let Some(recurrence) = task.recurrence {
+        println!("  Repeats: {}", recurrence);
+    }
+    if let Some(ref parent_id) = task.parent_task_id {
+        println!("  Subtask of: {}", parent_id.chars().take(8).collect::<String>().dimmed());
+    }
 
     Ok(())
 }
 
 pub fn list(status_filter: Option<String>) -> Result<()> {
-    let db = get_database()?;
+    list_with_db(&get_database()?, status_filter)
+}
 
+/// List tasks with an existing database connection.
+pub fn list_with_db(db: &Database, status_filter: Option<String>) -> Result<()> {
     let status = status_filter
         .as_ref()
         .and_then(|s| TaskStatus::from_str(s));
@@ -59,58 +106,191 @@ pub fn list(status_filter: Option<String>) -> Result<()> {
     println!("{}", "Tasks".cyan().bold());
     println!("{}", "─".repeat(70));
 
-    for task in tasks {
-        let status_icon = match task.status {
-            TaskStatus::Pending => "○".yellow(),
-            TaskStatus::InProgress => "◐".blue(),
-            TaskStatus::Done => "●".green(),
-            TaskStatus::Cancelled => "✗".dimmed(),
-        };
+    let blocked = Blockers::load(db)?;
 
-        let priority_indicator = if task.priority > 0 {
-            format!(" [P{}]", task.priority).red().to_string()
-        } else {
-            String::new()
-        };
+    // Subtasks under their parent; tasks whose parent isn't listed are roots
+    let listed: HashMap<&str, &Task> = tasks.iter().map(|t| (t.id.as_str(), t)).collect();
+    let mut children: HashMap<Option<&str>, Vec<&Task>> = HashMap::new();
+    for task in &tasks {
+        let parent = task
+            .parent_task_id
+            .as_deref()
+            .filter(|id| listed.contains_key(id));
+        children.entry(parent).or_default().push(task);
+    }
+    print_tree(&children, None, 0, &blocked);
 
-        let id_short = task.id.chars().take(8).collect::<String>();
+    Ok(())
+}
 
-        let title = if task.status == TaskStatus::Done {
-            task.title.dimmed().strikethrough().to_string()
-        } else {
-            task.title.white().to_string()
-        };
+fn print_tree(
+    children: &HashMap<Option<&str>, Vec<&Task>>,
+    parent: Option<&str>,
+    depth: usize,
+    blocked: &Blockers,
+) {
+    for task in children.get(&parent).into_iter().flatten() {
+        print_task(task, depth, blocked);
+        print_tree(children, Some(task.id.as_str()), depth + 1, blocked);
+    }
+}
 
-        println!(
-            "{} {} {} {}",
-            status_icon,
-            title,
-            format!("[{}]", id_short).dimmed(),
-            priority_indicator
-        );
+/// Blocked-by relationships, for flagging tasks as blocked or ready.
+struct Blockers {
+    /// Task ID to its blockers' (title, done) pairs.
+    by_task: HashMap<String, Vec<(String, bool)>>,
+}
+
+impl Blockers {
+    fn load(db: &Database) -> Result<Self> {
+        let tasks: HashMap<String, Task> = db
+            .list_tasks(None)?
+            .into_iter()
+            .map(|t| (t.id.clone(), t))
+            .collect();
+        let mut by_task: HashMap<String, Vec<(String, bool)>> = HashMap::new();
+        for (task_id, blocker_id) in db.list_task_dependencies()? {
+            if let Some(blocker) = tasks.get(&blocker_id) {
+                let finished = matches!(blocker.status, TaskStatus::Done | TaskStatus::Cancelled);
+                by_task
+                    .entry(task_id)
+                    .or_default()
+                    .push((blocker.title.clone(), finished));
+            }
+        }
+        Ok(Self { by_task })
+    }
 
-        if let Some(ref desc) = task.description {
-            println!("  {}", desc.dimmed());
+    /// " blocked by ..." while any blocker is open, " ready" once all are done.
+    fn flag(&self, task: &Task) -> String {
+        if matches!(task.status, TaskStatus::Done | TaskStatus::Cancelled) {
+            return String::new();
+        }
+        let Some(blockers) = self.by_task.get(&task.id) else {
+            return String::new();
+        };
+        let open: Vec<&str> = blockers
+            .iter()
+            .filter(|(_, finished)| !finished)
+            .map(|(title, _)| title.as_str())
+            .collect();
+        if open.is_empty() {
+            format!(" {}", "✓ ready".green())
+        } else {
+            format!(" {}", format!("⧗ blocked by {}", open.join(", ")).yellow())
         }
     }
+}
+
+/// Open tasks due by the end of today, overdue ones included.
+pub fn today() -> Result<()> {
+    let db = get_database()?;
+
+    let tasks = db.list_tasks_due_before(dates::end_of_day(Local::now().date_naive()))?;
+    if tasks.is_empty() {
+        println!("{}", "Nothing due today.".dimmed());
+        return Ok(());
+    }
+
+    let blocked = Blockers::load(&db)?;
+    println!("{}", "Due Today".cyan().bold());
+    println!("{}", "─".repeat(70));
+    for task in &tasks {
+        print_task(task, 0, &blocked);
+    }
 
     Ok(())
 }
 
-pub fn done(id: &str) -> Result<()> {
+/// Open tasks past their due date.
+pub fn overdue() -> Result<()> {
     let db = get_database()?;
 
-    // Try to find task by ID or prefix
-    let task = db.get_task(id).or_else(|_| {
-        // Try to find by prefix
-        let tasks = db.list_tasks(None)?;
-        tasks
-            .into_iter()
-            .find(|t| t.id.starts_with(id))
-            .ok_or_else(|| olal_db::DbError::NotFound(format!("Task not found: {}", id)))
-    })?;
+    let tasks = db.list_tasks_due_before(Utc::now())?;
+    if tasks.is_empty() {
+        println!("{}", "No overdue tasks.".dimmed());
+        return Ok(());
+    }
 
-    db.complete_task(&task.id)?;
+    let blocked = Blockers::load(&db)?;
+    println!("{}", "Overdue".red().bold());
+    println!("{}", "─".repeat(70));
+    for task in &tasks {
+        print_task(task, 0, &blocked);
+    }
+
+    Ok(())
+}
+
+fn print_task(task: &Task, depth: usize, blocked: &Blockers) {
+    let indent = "  ".repeat(depth);
+    let status_icon = match task.status {
+        TaskStatus::Pending => "○".yellow(),
+        TaskStatus::InProgress => "◐".blue(),
+        TaskStatus::Done => "●".green(),
+        TaskStatus::Cancelled => "✗".dimmed(),
+    };
+
+    let priority_indicator = if task.priority > 0 {
+        format!(" [P{}]", task.priority).red().to_string()
+    } else {
+        String::new()
+    };
+
+    let id_short = task.id.chars().take(8).collect::<String>();
+
+    let title = if task.status == TaskStatus::Done {
+        task.title.dimmed().strikethrough().to_string()
+    } else {
+        task.title.white().to_string()
+    };
+
+    let due = match task.due_date {
+        Some(due_date) if task.status != TaskStatus::Done => {
+            let label = format!(" (due {})", dates::format_due(due_date));
+            if due_date < Utc::now() {
+                label.red().to_string()
+            } else {
+                label.dimmed().to_string()
+            }
+        }
+        _ => String::new(),
+    };
+
+    let repeat = match task.recurrence {
+        Some(recurrence) => format!(" ↻ {}", recurrence).dimmed().to_string(),
+        None => String::new(),
+    };
+
+    println!(
+        "{}{} {} {} {}{}{}{}",
+        indent,
+        status_icon,
+        title,
+        format!("[{}]", id_short).dimmed(),
+        priority_indicator,
+        due,
+        repeat,
+        blocked.flag(task)
+    );
+
+    if let Some(ref desc) = task.description {
+        println!("{}  {}", indent, desc.dimmed());
+    }
+}
+
+pub fn done(id: &str) -> Result<()> {
+    done_with_db(&get_database()?, id)
+}
+
+/// Complete a task with an existing database connection.
+pub fn done_with_db(db: &Database, id: &str) -> Result<()> {
+    let task = find_task(db, id)?;
+    if task.status == TaskStatus::Done {
+        println!("{}", format!("'{}' is already done", task.title).dimmed());
+        return Ok(());
+    }
+    let next = complete(db, &task)?;
 
     println!(
         "{} Task completed: {}",
@@ -118,20 +298,211 @@ pub fn done(id: &str) -> Result<()> {
         task.title.strikethrough()
     );
 
+    // Tasks that were only waiting on this one can start now
+    for (waiting_id, blocker_id) in db.list_task_dependencies()? {
+        if blocker_id != task.id {
+            continue;
+        }
+        let blockers = db.get_task_blockers(&waiting_id)?;
+        if blockers.iter().all(|b| matches!(b.status, TaskStatus::Done | TaskStatus::Cancelled)) {
+            let waiting = db.get_task(&waiting_id)?;
+            println!("  {} Ready to start: {}", "→".green(), waiting.title.white());
+        }
+    }
+
+    if let Some(next) = next {
+        println!(
+            "  {} Next due {} [{}]",
+            "↻".cyan(),
+            next.due_date.map(dates::format_datetime).unwrap_or_default(),
+            next.id.chars().take(8).collect::<String>().dimmed()
+        );
+    }
+
+    Ok(())
+}
+
+/// Mark a task done. A recurring task gets its next occurrence, which is
+/// returned, unless it was already done or its next occurrence exists
+/// (it was completed before, reopened and completed again).
+pub fn complete(db: &Database, task: &Task) -> Result<Option<Task>> {
+    if task.status == TaskStatus::Done {
+        return Ok(None);
+    }
+    db.complete_task(&task.id)?;
+
+    let Some(recurrence) = task.recurrence else {
+        return Ok(None);
+    };
+    let spawned = db.list_tasks(None)?.iter().any(|t| {
+        t.id != task.id
+            && t.title == task.title
+            && t.recurrence == task.recurrence
+            && t.project_id == task.project_id
+            && t.status != TaskStatus::Done
+            && t.due_date > task.due_date
+    });
+    if spawned {
+        return Ok(None);
+    }
+    let due = dates::next_due(task.due_date.unwrap_or_else(Utc::now), recurrence);
+    let next = task.next_occurrence(due);
+    db.create_task(&next)?;
+    Ok(Some(next))
+}
+
+pub fn start(id: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let mut task = find_task(&db, id)?;
+    if task.status == TaskStatus::InProgress {
+        println!("{}", format!("'{}' is already in progress", task.title).dimmed());
+        return Ok(());
+    }
+
+    let reopened = matches!(task.status, TaskStatus::Done | TaskStatus::Cancelled);
+    task.mark_in_progress();
+    db.update_task(&task)?;
+
+    println!(
+        "{} {} {}",
+        "◐".blue(),
+        if reopened { "Reopened:" } else { "Started:" },
+        task.title.white().bold()
+    );
+
+    let open: Vec<String> = db
+        .get_task_blockers(&task.id)?
+        .into_iter()
+        .filter(|b| !matches!(b.status, TaskStatus::Done | TaskStatus::Cancelled))
+        .map(|b| b.title)
+        .collect();
+    if !open.is_empty() {
+        println!(
+            "  {} still blocked by {}",
+            "Note:".yellow(),
+            open.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+pub fn cancel(id: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let mut task = find_task(&db, id)?;
+    task.mark_cancelled();
+    db.update_task(&task)?;
+
+    println!(
+        "{} Task cancelled: {}",
+        "✗".dimmed(),
+        task.title.strikethrough()
+    );
+
+    Ok(())
+}
+
+/// Changes for `olal task edit`; unset fields are left alone.
+#[derive(Debug, Default)]
+pub struct TaskEdit {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub priority: Option<i32>,
+    /// A project name, or "none" to remove it from its project.
+    pub project: Option<String>,
+    /// A due date, or "none" to clear it.
+    pub due: Option<String>,
+    /// A recurrence, or "none" to stop repeating.
+    pub repeat: Option<String>,
+}
+
+pub fn edit(id: &str, changes: TaskEdit) -> Result<()> {
+    let db = get_database()?;
+
+    let mut task = find_task(&db, id)?;
+    let mut changed = Vec::new();
+
+    if let Some(title) = changes.title {
+        task.title = title;
+        changed.push(format!("title: {}", task.title));
+    }
+    if let Some(description) = changes.description {
+        task.description = Some(description).filter(|d| !d.is_empty());
+        changed.push("description".to_string());
+    }
+    if let Some(priority) = changes.priority {
+        task.priority = priority;
+        changed.push(format!("priority: {}", priority));
+    }
+    if let Some(project) = changes.project {
+        if is_none(&project) {
+            task.project_id = None;
+            changed.push("project: none".to_string());
+        } else {
+            let project = resolve::project(&db, &project)?;
+            task.project_id = Some(project.id);
+            changed.push(format!("project: {}", project.name));
+        }
+    }
+    if let Some(due) = changes.due {
+        if is_none(&due) {
+            task.due_date = None;
+            changed.push("due: none".to_string());
+        } else {
+            let due_date = dates::parse_due(&due)?;
+            task.due_date = Some(due_date);
+            changed.push(format!("due: {}", dates::format_datetime(due_date)));
+        }
+    }
+    if let Some(repeat) = changes.repeat {
+        if is_none(&repeat) {
+            task.recurrence = None;
+            changed.push("repeats: never".to_string());
+        } else {
+            let recurrence = parse_recurrence(&repeat)?;
+            task.recurrence = Some(recurrence);
+            if task.due_date.is_none() {
+                task.due_date = Some(dates::end_of_day(Local::now().date_naive()));
+            }
+            changed.push(format!("repeats: {}", recurrence));
+        }
+    }
+
+    if changed.is_empty() {
+        anyhow::bail!(
+            "Nothing to change. Use --title, --description, --priority, --project, --due or --repeat."
+        );
+    }
+
+    db.update_task(&task)?;
+
+    println!("{} Task updated: {}", "✓".green(), task.title.white().bold());
+    for change in changed {
+        println!("  {}", change.dimmed());
+    }
+
     Ok(())
 }
 
+fn is_none(value: &str) -> bool {
+    value.eq_ignore_ascii_case("none")
+}
+
+fn parse_recurrence(value: &str) -> Result<Recurrence> {
+    Recurrence::from_str(value).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid repeat '{}'. Valid values: daily, weekdays, weekly, monthly, yearly",
+            value
+        )
+    })
+}
+
 pub fn delete(id: &str) -> Result<()> {
     let db = get_database()?;
 
-    // Try to find task by ID or prefix
-    let task = db.get_task(id).or_else(|_| {
-        let tasks = db.list_tasks(None)?;
-        tasks
-            .into_iter()
-            .find(|t| t.id.starts_with(id))
-            .ok_or_else(|| olal_db::DbError::NotFound(format!("Task not found: {}", id)))
-    })?;
+    let task = find_task(&db, id)?;
 
     db.delete_task(&task.id)?;
 
@@ -143,3 +514,51 @@ pub fn delete(id: &str) -> Result<()> {
 
     Ok(())
 }
+
+pub fn block(id: &str, blocked_by: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let task = find_task(&db, id)?;
+    let blocker = find_task(&db, blocked_by)?;
+    db.add_task_dependency(&task.id, &blocker.id)?;
+
+    println!(
+        "{} '{}' is blocked by '{}'",
+        "✓".green(),
+        task.title.white(),
+        blocker.title.white()
+    );
+
+    Ok(())
+}
+
+pub fn unblock(id: &str, blocked_by: &str) -> Result<()> {
+    let db = get_database()?;
+
+    let task = find_task(&db, id)?;
+    let blocker = find_task(&db, blocked_by)?;
+    if !db.remove_task_dependency(&task.id, &blocker.id)? {
+        anyhow::bail!("'{}' isn't blocked by '{}'", task.title, blocker.title);
+    }
+
+    println!(
+        "{} '{}' is no longer blocked by '{}'",
+        "✓".green(),
+        task.title.white(),
+        blocker.title.white()
+    );
+
+    Ok(())
+}
+
+/// Find a task by full ID or ID prefix.
+fn find_task(db: &Database, id: &str) -> Result<Task> {
+    let task = db.get_task(id).or_else(|_| {
+        let tasks = db.list_tasks(None)?;
+        tasks
+            .into_iter()
+            .find(|t| t.id.starts_with(id))
+            .ok_or_else(|| olal_db::DbError::NotFound(format!("Task not found: {}", id)))
+    })?;
+    Ok(task)
+}
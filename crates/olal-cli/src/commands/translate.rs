@@ -0,0 +1,197 @@
+//! Translate command - an item's transcript or text in another language.
+//!
+//! The item's chunks are translated one at a time, so long transcripts fit
+//! the model's context and keep their timestamps. The translation is stored
+//! as a sibling item linked to the original (`translates`), and can be
+//! embedded so searches in either language find the recording.
+
+use super::embed::embed_item_quietly;
+use super::{get_database, resolve};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::Colorize;
+use olal_config::Config;
+use olal_core::{Chunk, Item, ItemType, Link, LinkType};
+use olal_db::Database;
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient, OllamaError};
+use serde_json::json;
+use tokio::runtime::Runtime;
+
+/// Translate an item into `to`, replacing an earlier translation into the
+/// same language.
+pub fn run(item_id: &str, to: &str, model: Option<String>, embed: bool) -> Result<()> {
+    let db = get_database()?;
+    let config = Config::load().context("Failed to load configuration")?;
+    let item = resolve::item(&db, item_id)?;
+    let to = to.trim().to_lowercase();
+
+    if item.metadata.get("translation_of").is_some() {
+        anyhow::bail!("'{}' is already a translation; translate the original instead", item.title);
+    }
+    let source_language = item.metadata["language"].as_str().map(str::to_string);
+    if source_language.as_deref() == Some(to.as_str()) {
+        anyhow::bail!("'{}' is already in {}", item.title, language_name(&to));
+    }
+
+    let chunks = db.get_chunks_by_item(&item.id)?;
+    if chunks.is_empty() {
+        anyhow::bail!("Item '{}' has no content to translate", item.title);
+    }
+
+    let client = OllamaClient::from_config(&config.ollama)
+        .context("Failed to create Ollama client")?;
+    let rt = Runtime::new().context("Failed to create async runtime")?;
+    if !rt.block_on(client.is_available()) {
+        return Err(OllamaError::ServerNotRunning {
+            host: config.ollama.host.clone(),
+        }
+        .into());
+    }
+    let model_name = model.as_deref().unwrap_or(&config.ollama.model);
+
+    println!(
+        "{} {} → {}",
+        "Translating:".cyan().bold(),
+        item.title,
+        language_name(&to)
+    );
+    println!("{}", "─".repeat(50));
+
+    let mut translated = Vec::with_capacity(chunks.len());
+    for (i, chunk) in chunks.iter().enumerate() {
+        println!("  {} segment {}/{}", "→".dimmed(), i + 1, chunks.len());
+        let prompt = prompts::render(
+            Prompt::Translation,
+            &json!({ "language": language_name(&to), "content": chunk.content }),
+        )?;
+        let request = GenerateRequest::new(model_name, prompt)
+            .with_options(GenerateOptions::new().with_temperature(0.2).with_num_predict(2048));
+        let response = rt
+            .block_on(client.generate(request))
+            .with_context(|| format!("Failed to translate segment {}", i + 1))?;
+        translated.push(response.response.trim().to_string());
+    }
+
+    let replaced = remove_previous(&db, &item, &to)?;
+    let translation = store(&db, &item, &chunks, &translated, &to, source_language, model_name)?;
+
+    println!();
+    print!("{} Saved translation", "✓".green());
+    if replaced {
+        print!(" (replaced the previous one)");
+    }
+    println!();
+    println!(
+        "  {} {}",
+        "ID:".cyan(),
+        translation.id.chars().take(8).collect::<String>()
+    );
+    println!("  {}: {}", "Title".cyan(), translation.title);
+    println!("  {}: {}", "Chunks".cyan(), translated.len());
+
+    if embed {
+        match embed_item_quietly(&db, &translation.id) {
+            Ok(Some(count)) => println!("  {}: {}", "Embedded".cyan(), count),
+            Ok(None) => println!(
+                "  {}",
+                "Ollama unavailable; run 'olal embed --all' later.".dimmed()
+            ),
+            Err(e) => println!("  {} Embedding failed: {}", "Warning:".yellow(), e),
+        }
+    }
+    Ok(())
+}
+
+/// Delete an earlier translation of `item` into `language`.
+fn remove_previous(db: &Database, item: &Item, language: &str) -> Result<bool> {
+    let mut replaced = false;
+    for link in db.get_item_links(&item.id)? {
+        if link.link_type != LinkType::Translates || link.target_id != item.id {
+            continue;
+        }
+        let Ok(previous) = db.get_item(&link.source_id) else {
+            continue;
+        };
+        if previous.metadata["language"].as_str() == Some(language) {
+            db.delete_item_cascade(&previous.id)?;
+            replaced = true;
+        }
+    }
+    Ok(replaced)
+}
+
+/// Save the translated chunks as a new item next to `item`.
+fn store(
+    db: &Database,
+    item: &Item,
+    chunks: &[Chunk],
+    translated: &[String],
+    language: &str,
+    source_language: Option<String>,
+    model: &str,
+) -> Result<Item> {
+    // Recordings can't hold text; their translation is a document
+    let item_type = match item.item_type {
+        ItemType::Video | ItemType::Audio | ItemType::Image => ItemType::Document,
+        other => other,
+    };
+    let mut translation = Item::new(item_type, format!("{} ({})", item.title, language));
+    translation.processed_at = Some(Utc::now());
+    translation.metadata = json!({
+        "translation_of": item.id,
+        "language": language,
+        "source_language": source_language,
+        "translation_model": model,
+    });
+    db.create_item(&translation)?;
+    db.set_item_content(&translation.id, &translated.join("\n\n"))?;
+
+    let translated_chunks: Vec<Chunk> = chunks
+        .iter()
+        .zip(translated)
+        .map(|(original, text)| {
+            let chunk = Chunk::new(translation.id.clone(), original.chunk_index, text);
+            match (original.start_time, original.end_time) {
+                (Some(start), Some(end)) => chunk.with_timestamps(start, end),
+                _ => chunk,
+            }
+        })
+        .collect();
+    db.create_chunks(&translated_chunks)?;
+
+    for tag in db.get_item_tags(&item.id)? {
+        db.tag_item(&translation.id, &tag.name)?;
+    }
+    db.create_link(&Link::new(
+        translation.id.clone(),
+        item.id.clone(),
+        LinkType::Translates,
+    ))?;
+    Ok(translation)
+}
+
+/// English name of an ISO 639-1 code, for the prompt; unknown codes are
+/// passed through.
+fn language_name(code: &str) -> String {
+    let name = match code {
+        "en" => "English",
+        "es" => "Spanish",
+        "fr" => "French",
+        "de" => "German",
+        "it" => "Italian",
+        "pt" => "Portuguese",
+        "nl" => "Dutch",
+        "ru" => "Russian",
+        "uk" => "Ukrainian",
+        "pl" => "Polish",
+        "tr" => "Turkish",
+        "ar" => "Arabic",
+        "hi" => "Hindi",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "zh" => "Chinese",
+        other => return other.to_string(),
+    };
+    name.to_string()
+}
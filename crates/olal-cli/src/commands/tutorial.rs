@@ -0,0 +1,270 @@
+//! Tutorial command - a guided tour in a throwaway knowledge base.
+//!
+//! Each step runs the real `olal` binary against a sandbox (via `--db` and
+//! `--config`), then checks the sandbox database to confirm the step did
+//! what it says. Your own knowledge base is never touched.
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::{Config, GeneralConfig};
+use olal_db::Database;
+use olal_ollama::OllamaClient;
+use std::fs;
+use std::io::{self, IsTerminal, Write};
+use std::path::PathBuf;
+use std::process::Command;
+use tokio::runtime::Runtime;
+
+const THOUGHT: &str = "Borrowing lets code use a value without taking ownership of it";
+
+const SAMPLE_NOTE: &str = "# Rust Ownership
+
+Every value in Rust has exactly one owner. When the owner goes out of scope,
+the value is dropped and its memory is freed.
+
+Ownership can be moved to another variable, or borrowed through references.
+A value can have many shared references or one mutable reference, never both.
+";
+
+const QUESTION: &str = "Who owns a value in Rust?";
+
+/// Run the tutorial. With `keep`, the sandbox is left behind to explore.
+pub fn run(keep: bool) -> Result<()> {
+    let user_config = Config::load().unwrap_or_default();
+    let sandbox = Sandbox::create(&user_config, keep)?;
+
+    println!("{}", "Olal Tutorial".cyan().bold());
+    println!("{}", "─".repeat(50));
+    println!("This walks through capture → ingest → embed → search → ask,");
+    println!("running real commands against a sandbox knowledge base:");
+    println!("  {}", sandbox.dir.display().to_string().dimmed());
+    println!("Your own knowledge base is not touched.");
+
+    let ollama = ollama_available(&user_config);
+
+    sandbox.step(
+        1,
+        "Set up",
+        "'olal init' creates the config file and database. The tutorial points it at the sandbox.",
+        &["init"],
+    )?;
+    let db = sandbox.database()?;
+
+    sandbox.step(
+        2,
+        "Capture a thought",
+        "'olal capture' saves a quick note straight into your knowledge base.",
+        &["capture", THOUGHT, "--tag", "rust"],
+    )?;
+    let notes = db.search_items("Borrowing", Some(5))?;
+    verify(!notes.is_empty(), "the captured note is in the database")?;
+
+    let note_path = sandbox.dir.join("rust-ownership.md");
+    fs::write(&note_path, SAMPLE_NOTE).context("Failed to write sample note")?;
+    sandbox.step(
+        3,
+        "Ingest a file",
+        "'olal ingest' reads files (notes, documents, code, video, audio) and splits them into searchable chunks.",
+        &["ingest", &note_path.to_string_lossy()],
+    )?;
+    let items = db.list_items(None, None)?;
+    verify(
+        items
+            .iter()
+            .any(|i| i.source_path.as_deref().is_some_and(|p| p.ends_with("rust-ownership.md"))),
+        "the file was ingested",
+    )?;
+
+    if ollama {
+        sandbox.step(
+            4,
+            "Embed for semantic search",
+            "'olal embed --all' turns chunks into vectors so search and ask can match meaning, not just words.",
+            &["embed", "--all"],
+        )?;
+        let (embedded, _) = db.embedding_stats()?;
+        verify(embedded > 0, "chunks have embeddings")?;
+    } else {
+        skip(4, "Embed for semantic search", &user_config);
+    }
+
+    sandbox.step(
+        5,
+        "Search",
+        "'olal search' finds items by keyword; add --semantic to search by meaning once embedded.",
+        &["search", "ownership"],
+    )?;
+    verify(
+        !db.search_items("ownership", Some(5))?.is_empty(),
+        "search finds the ingested note",
+    )?;
+
+    if ollama {
+        sandbox.step(
+            6,
+            "Ask a question",
+            "'olal ask' answers from your own content, citing the items it used.",
+            &["ask", QUESTION],
+        )?;
+    } else {
+        skip(6, "Ask a question", &user_config);
+    }
+
+    println!();
+    println!("{} Tutorial complete!", "✓".green().bold());
+    println!();
+    println!("Next steps:");
+    println!("  {} Set up your own knowledge base", "olal init".cyan());
+    println!("  {} Ingest a folder of notes", "olal ingest ~/notes".cyan());
+    println!("  {} Ingest new files automatically", "olal watch".cyan());
+    println!("  {} See every command", "olal --help".cyan());
+    if keep {
+        println!();
+        println!("The sandbox was kept. Explore it with:");
+        println!("  {}", sandbox.command_line(&["recent"]).cyan());
+    }
+
+    Ok(())
+}
+
+/// A throwaway config and database, removed on drop unless kept.
+struct Sandbox {
+    dir: PathBuf,
+    db: PathBuf,
+    config: PathBuf,
+    keep: bool,
+}
+
+impl Sandbox {
+    fn create(user_config: &Config, keep: bool) -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!("olal-tutorial-{}", std::process::id()));
+        if dir.exists() {
+            fs::remove_dir_all(&dir)?;
+        }
+        fs::create_dir_all(&dir).context("Failed to create sandbox")?;
+
+        let sandbox = Self {
+            db: dir.join("olal.db"),
+            config: dir.join("config.toml"),
+            dir,
+            keep,
+        };
+
+        // Same Ollama and processing setup as the real config, but none of
+        // its watches, webhooks or schedules
+        let config = Config {
+            general: GeneralConfig {
+                record_usage: false,
+                ..GeneralConfig::default()
+            },
+            ollama: user_config.ollama.clone(),
+            processing: user_config.processing.clone(),
+            ..Config::default()
+        };
+        config
+            .save_to(&sandbox.config)
+            .context("Failed to write sandbox config")?;
+
+        Ok(sandbox)
+    }
+
+    fn database(&self) -> Result<Database> {
+        Database::open(&self.db).context("Failed to open sandbox database")
+    }
+
+    /// Explain a step, wait for Enter, then run it.
+    fn step(&self, number: usize, title: &str, explanation: &str, args: &[&str]) -> Result<()> {
+        println!();
+        println!("{}", format!("Step {}: {}", number, title).white().bold());
+        println!("{}", explanation);
+        println!();
+        println!("  {} {}", "$".dimmed(), display_command(args).cyan());
+        pause()?;
+
+        let status = Command::new(std::env::current_exe()?)
+            .arg("--db")
+            .arg(&self.db)
+            .arg("--config")
+            .arg(&self.config)
+            .args(args)
+            .status()
+            .context("Failed to run olal")?;
+        if !status.success() {
+            anyhow::bail!("Step {} failed: '{}' exited with {}", number, display_command(args), status);
+        }
+        Ok(())
+    }
+
+    /// The full command line to run `args` against the sandbox.
+    fn command_line(&self, args: &[&str]) -> String {
+        format!(
+            "olal --db {} --config {} {}",
+            self.db.display(),
+            self.config.display(),
+            args.join(" ")
+        )
+    }
+}
+
+impl Drop for Sandbox {
+    fn drop(&mut self) {
+        if !self.keep {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+fn verify(ok: bool, what: &str) -> Result<()> {
+    if !ok {
+        anyhow::bail!("Tutorial check failed: expected {}", what);
+    }
+    println!("  {} Checked: {}", "✓".green(), what);
+    Ok(())
+}
+
+fn skip(number: usize, title: &str, config: &Config) {
+    println!();
+    println!(
+        "{} {}",
+        format!("Step {}: {}", number, title).white().bold(),
+        "(skipped)".yellow()
+    );
+    println!(
+        "This step needs Ollama, which isn't running at {}. Start it with 'ollama serve' and run the tutorial again.",
+        config.ollama.host
+    );
+}
+
+fn ollama_available(config: &Config) -> bool {
+    let Ok(client) = OllamaClient::from_config(&config.ollama) else {
+        return false;
+    };
+    Runtime::new()
+        .map(|rt| rt.block_on(client.is_available()))
+        .unwrap_or(false)
+}
+
+/// Wait for Enter when run interactively.
+fn pause() -> Result<()> {
+    if !io::stdin().is_terminal() {
+        return Ok(());
+    }
+    print!("  {}", "Press Enter to run it...".dimmed());
+    io::stdout().flush()?;
+    io::stdin().read_line(&mut String::new())?;
+    Ok(())
+}
+
+/// `olal` plus `args`, quoting arguments with spaces.
+fn display_command(args: &[&str]) -> String {
+    let mut line = String::from("olal");
+    for arg in args {
+        if arg.contains(' ') {
+            line.push_str(&format!(" \"{}\"", arg));
+        } else {
+            line.push(' ');
+            line.push_str(arg);
+        }
+    }
+    line
+}
@@ -0,0 +1,61 @@
+//! Local usage history for `olal stats --me`.
+//!
+//! Each invocation's command name, start time, duration and outcome go into
+//! the knowledge base itself; nothing is ever sent anywhere. Turn it off with
+//! `general.record_usage = false`.
+
+use super::{encrypt, get_paths};
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use clap::ArgMatches;
+use olal_config::Config;
+use olal_db::{CommandUsage, Database};
+use std::time::Duration;
+
+/// The invoked command path, e.g. "search" or "queue list".
+pub fn command_name(matches: &ArgMatches) -> String {
+    let mut names = Vec::new();
+    let mut current = matches;
+    while let Some((name, sub)) = current.subcommand() {
+        names.push(name);
+        current = sub;
+    }
+    names.join(" ")
+}
+
+/// Whether an invocation is worth recording. `status --short` runs on
+/// every shell prompt, so it would swamp the history (and slow the prompt).
+pub fn is_recorded(matches: &ArgMatches) -> bool {
+    !matches.subcommand_matches("status").is_some_and(|m| m.get_flag("short"))
+}
+
+/// Record an invocation. Best effort: skipped when olal isn't initialized,
+/// recording is disabled, or an encrypted database would need a key prompt.
+pub fn record(command: &str, started_at: DateTime<Utc>, elapsed: Duration, success: bool) {
+    if let Err(e) = try_record(command, started_at, elapsed, success) {
+        tracing::debug!("Usage not recorded: {}", e);
+    }
+}
+
+fn try_record(command: &str, started_at: DateTime<Utc>, elapsed: Duration, success: bool) -> Result<()> {
+    let paths = get_paths()?;
+    if command.is_empty() || !paths.is_initialized() {
+        return Ok(());
+    }
+    // load() so profiles and OLAL_GENERAL__RECORD_USAGE apply too
+    if !Config::load()?.general.record_usage {
+        return Ok(());
+    }
+    let Some(key) = encrypt::unattended_key(&paths.database_file) else {
+        return Ok(());
+    };
+
+    let db = Database::open_with_key(&paths.database_file, key.as_deref())?;
+    db.record_command_usage(&CommandUsage {
+        command: command.to_string(),
+        started_at,
+        duration_ms: elapsed.as_millis().min(i64::MAX as u128) as i64,
+        success,
+    })?;
+    Ok(())
+}
@@ -0,0 +1,60 @@
+//! Vault commands - separate knowledge bases from the `[vaults]` config.
+
+use super::get_paths;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::{active_vault, AppPaths, Config, VaultConfig};
+
+/// List the default knowledge base and each configured vault.
+pub fn list() -> Result<()> {
+    let paths = get_paths()?;
+    let config = Config::load_from(&paths.config_file).context("Failed to load config")?;
+    let active = active_vault();
+
+    println!("{}", "Vaults".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    if let Some(default) = AppPaths::platform() {
+        print_vault("default", &default.data_dir, active.is_none());
+    }
+    for (name, vault) in &config.vaults {
+        print_vault(name, &vault.data_dir(), active.as_deref() == Some(name));
+    }
+
+    if config.vaults.is_empty() {
+        println!();
+        println!("{}", "No vaults configured.".dimmed());
+        println!("Add one with: olal vault add <name> <data-dir>");
+    }
+    Ok(())
+}
+
+fn print_vault(name: &str, data_dir: &std::path::Path, active: bool) {
+    let marker = if active { "*".green().bold() } else { " ".normal() };
+    let state = if data_dir.join("olal.db").exists() {
+        String::new()
+    } else {
+        format!(" {}", "(not initialized)".dimmed())
+    };
+    println!("{} {} {}{}", marker, name.white().bold(), data_dir.display(), state);
+}
+
+/// Add a vault to the config.
+pub fn add(name: &str, data_dir: &str) -> Result<()> {
+    let paths = get_paths()?;
+    let mut config = Config::load_from(&paths.config_file).context("Failed to load config")?;
+
+    if name.is_empty() || name == "default" {
+        anyhow::bail!("Invalid vault name: '{}'", name);
+    }
+    if config.vaults.contains_key(name) {
+        anyhow::bail!("Vault '{}' already exists", name);
+    }
+
+    config.vaults.insert(name.to_string(), VaultConfig::new(data_dir));
+    config.save_to(&paths.config_file).context("Failed to save config")?;
+
+    println!("{} Added vault '{}': {}", "✓".green(), name, data_dir);
+    println!("  Set it up with: olal --vault {} init", name);
+    Ok(())
+}
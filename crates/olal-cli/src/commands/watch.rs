@@ -2,9 +2,10 @@
 
 use anyhow::Result;
 use olal_config::Config;
-use olal_db::Database;
 use olal_ingest::{ChunkConfig, FileWatcher, Ingestor, WatchEvent, WatcherConfig};
 use colored::Colorize;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
 use std::time::Duration;
 use tracing::{error, info};
 
@@ -56,9 +57,12 @@ pub fn run(daemon: bool) -> Result<()> {
     watcher.start()?;
 
     // Set up the ingestor
-    let db = Database::open(&paths.database_file)?;
+    let db = super::open_database(&paths.database_file)?;
     let chunk_config = ChunkConfig::from_processing_config(&config.processing);
-    let ingestor = Ingestor::new(db, chunk_config);
+    let ingestor = Ingestor::new(db.clone(), chunk_config).with_events(super::event_emitter(&config));
+
+    // Scheduled jobs run until the process exits
+    super::schedule::spawn(db.clone(), config.clone(), paths, Arc::new(AtomicBool::new(false)));
 
     // Main watch loop
     loop {
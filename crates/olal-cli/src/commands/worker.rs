@@ -0,0 +1,245 @@
+//! Worker commands - drain the processing queue in the background.
+
+use super::{get_database, get_paths};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use olal_config::Config;
+use olal_ingest::{process_alive, ChunkConfig, Worker, WorkerEvent};
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long an idle worker waits before checking the queue again.
+const IDLE_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often an idle worker retries chunks that failed to embed.
+const EMBED_RETRY_INTERVAL: Duration = Duration::from_secs(300);
+
+/// How long `olal worker stop` waits for in-flight jobs to finish.
+const STOP_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Start the queue worker, in the foreground or as a background process.
+pub fn start(daemon: bool) -> Result<()> {
+    let db = get_database()?;
+    let paths = get_paths()?;
+    let config = Config::load().context("Failed to load configuration")?;
+
+    let pid_file = paths.worker_pid_file();
+    if let Some(pid) = running_pid(&pid_file) {
+        anyhow::bail!("Worker is already running (pid {})", pid);
+    }
+
+    if daemon {
+        return spawn_daemon(&paths);
+    }
+
+    fs::write(&pid_file, std::process::id().to_string()).context("Failed to write PID file")?;
+
+    let stop = Arc::new(AtomicBool::new(false));
+    watch_for_shutdown(Arc::clone(&stop))?;
+
+    let jobs = config.processing.max_concurrent_jobs.max(1);
+    println!("{}", "Queue worker".cyan().bold());
+    println!("{}", "─".repeat(50));
+    println!("  {} {}", "Jobs:".cyan(), jobs);
+    println!("  {} {}", "PID:".cyan(), std::process::id());
+    if config.processing.auto_embed {
+        println!("  {} on", "Auto-embed:".cyan());
+    }
+    if config.schedule.enabled && !config.schedule.jobs.is_empty() {
+        println!("  {} {}", "Scheduled jobs:".cyan(), config.schedule.jobs.len());
+    }
+    println!("\nPress Ctrl+C to stop.\n");
+
+    let worker = Worker {
+        jobs,
+        idle_interval: IDLE_INTERVAL,
+        embed_retry_interval: config
+            .processing
+            .auto_embed
+            .then_some(EMBED_RETRY_INTERVAL),
+        fetch_interval: (config.processing.fetch_interval_seconds > 0)
+            .then(|| Duration::from_secs(config.processing.fetch_interval_seconds)),
+        chunk_config: ChunkConfig::from_processing_config(&config.processing),
+        events: super::event_emitter(&config),
+    };
+    let scheduler = super::schedule::spawn(db.clone(), config.clone(), paths.clone(), Arc::clone(&stop));
+    let result = worker.run(&db, &stop, print_event);
+    let _ = scheduler.join();
+    let _ = fs::remove_file(&pid_file);
+    let summary = result?;
+
+    println!();
+    println!(
+        "{} Worker stopped: {} processed, {} failed",
+        "✓".green(),
+        summary.processed,
+        summary.failed
+    );
+
+    Ok(())
+}
+
+/// Ask a background worker to finish its current jobs and exit.
+pub fn stop() -> Result<()> {
+    let paths = get_paths()?;
+    let pid_file = paths.worker_pid_file();
+
+    let Some(pid) = running_pid(&pid_file) else {
+        let _ = fs::remove_file(&pid_file);
+        println!("{}", "Worker is not running.".dimmed());
+        return Ok(());
+    };
+
+    let status = Command::new("kill")
+        .args(["-TERM", &pid.to_string()])
+        .status()
+        .context("Failed to signal worker")?;
+    if !status.success() {
+        anyhow::bail!("Failed to signal worker (pid {})", pid);
+    }
+
+    println!("Waiting for in-flight jobs to finish...");
+    let started = Instant::now();
+    while process_alive(pid) {
+        if started.elapsed() > STOP_TIMEOUT {
+            anyhow::bail!("Worker (pid {}) did not stop within {}s", pid, STOP_TIMEOUT.as_secs());
+        }
+        std::thread::sleep(Duration::from_millis(200));
+    }
+
+    println!("{} Worker stopped", "✓".green());
+    Ok(())
+}
+
+/// Show whether a worker is running and what's left in the queue.
+pub fn status() -> Result<()> {
+    let db = get_database()?;
+    let paths = get_paths()?;
+
+    println!("{}", "Queue worker".cyan().bold());
+    println!("{}", "─".repeat(50));
+
+    match running_pid(&paths.worker_pid_file()) {
+        Some(pid) => println!("  {} running (pid {})", "●".green(), pid),
+        None => println!("  {} not running", "○".dimmed()),
+    }
+
+    let (pending, processing, _, failed) = db.queue_counts()?;
+    println!("  {} {}", "Pending:".cyan(), pending);
+    println!("  {} {}", "Processing:".cyan(), processing);
+    if failed > 0 {
+        println!("  {} {}", "Failed:".red(), failed);
+    }
+    if paths.worker_log_file().exists() {
+        println!("  {} {}", "Log:".cyan(), paths.worker_log_file().display());
+    }
+
+    Ok(())
+}
+
+fn print_event(event: WorkerEvent) {
+    match event {
+        WorkerEvent::Recovered(count) => println!(
+            "{} Requeued {} entries interrupted by a previous run",
+            "→".yellow(),
+            count
+        ),
+        WorkerEvent::Processed {
+            title, chunk_count, ..
+        } => println!("{} {} ({} chunks)", "✓".green(), title, chunk_count),
+        WorkerEvent::Failed { entry, error } => {
+            println!("{} {}: {}", "✗".red(), entry.source_path, error)
+        }
+        WorkerEvent::Embedded { embedded, failed } => println!(
+            "{} Embedded {} pending chunks ({} failed)",
+            "✓".green(),
+            embedded,
+            failed
+        ),
+        WorkerEvent::Fetched { title, error: None } => println!("{} Fetched {}", "✓".green(), title),
+        WorkerEvent::Fetched {
+            title,
+            error: Some(error),
+        } => println!("{} {}: {}", "✗".red(), title, error),
+    }
+}
+
+/// Re-run `olal worker start` detached, logging to the worker log file.
+fn spawn_daemon(paths: &olal_config::AppPaths) -> Result<()> {
+    fs::create_dir_all(&paths.log_dir)?;
+    let log_path = paths.worker_log_file();
+    let log = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .with_context(|| format!("Failed to open {}", log_path.display()))?;
+
+    let exe = std::env::current_exe().context("Failed to locate the olal executable")?;
+    let mut command = Command::new(exe);
+    command
+        .args(["worker", "start"])
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .stdout(log.try_clone()?)
+        .stderr(log);
+
+    // Its own process group, so the terminal's Ctrl+C and hangup don't reach it
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        command.process_group(0);
+    }
+
+    let child = command.spawn().context("Failed to start worker")?;
+
+    println!("{} Worker started (pid {})", "✓".green(), child.id());
+    println!("  {} {}", "Log:".cyan(), log_path.display());
+    println!("  Stop it with {}", "olal worker stop".cyan());
+    Ok(())
+}
+
+/// Set `stop` on Ctrl+C or SIGTERM so in-flight jobs can finish.
+fn watch_for_shutdown(stop: Arc<AtomicBool>) -> Result<()> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .context("Failed to create async runtime")?;
+
+    std::thread::spawn(move || {
+        rt.block_on(async {
+            #[cfg(unix)]
+            {
+                use tokio::signal::unix::{signal, SignalKind};
+                match signal(SignalKind::terminate()) {
+                    Ok(mut term) => {
+                        tokio::select! {
+                            _ = tokio::signal::ctrl_c() => {}
+                            _ = term.recv() => {}
+                        }
+                    }
+                    Err(_) => {
+                        let _ = tokio::signal::ctrl_c().await;
+                    }
+                }
+            }
+            #[cfg(not(unix))]
+            {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        });
+        println!("{}", "Stopping after current jobs...".yellow());
+        stop.store(true, Ordering::Release);
+    });
+
+    Ok(())
+}
+
+/// The PID in `pid_file`, if that process is still alive.
+fn running_pid(pid_file: &Path) -> Option<u32> {
+    let pid = fs::read_to_string(pid_file).ok()?.trim().parse().ok()?;
+    process_alive(pid).then_some(pid)
+}
@@ -1,12 +1,16 @@
 //! YouTube command - Generate YouTube metadata from video content.
 
-use super::get_database;
+use super::{get_database, resolve};
 use anyhow::{Context, Result};
 use olal_config::Config;
-use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient};
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{ContextBudget, GenerateRequest, OllamaClient, OllamaError};
+use serde_json::{json, Value};
 use colored::Colorize;
 use std::fmt;
+use std::fs;
 use std::io::{self, Write};
+use std::path::Path;
 use tokio::runtime::Runtime;
 
 /// Content style for YouTube metadata generation.
@@ -20,6 +24,7 @@ pub enum ContentStyle {
 
 impl ContentStyle {
     /// Parse from string.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "tutorial" => Some(Self::Tutorial),
@@ -65,6 +70,13 @@ impl fmt::Display for Chapter {
     }
 }
 
+/// A thumbnail idea: the text on it and what it shows.
+#[derive(Debug, Clone)]
+pub struct ThumbnailConcept {
+    pub text: String,
+    pub concept: String,
+}
+
 /// YouTube metadata container.
 #[derive(Debug, Clone, Default)]
 pub struct YoutubeMetadata {
@@ -72,6 +84,8 @@ pub struct YoutubeMetadata {
     pub description: Option<String>,
     pub tags: Option<Vec<String>>,
     pub chapters: Option<Vec<Chapter>>,
+    pub thumbnails: Option<Vec<ThumbnailConcept>>,
+    pub hooks: Option<Vec<String>>,
 }
 
 /// Output mode flags.
@@ -80,6 +94,8 @@ pub struct OutputMode {
     pub description_only: bool,
     pub chapters_only: bool,
     pub tags_only: bool,
+    /// Also suggest thumbnails and opening hooks.
+    pub thumbnail: bool,
 }
 
 impl OutputMode {
@@ -89,15 +105,14 @@ impl OutputMode {
     }
 }
 
-/// Run the youtube command.
+/// Run the youtube command, writing the results to `output` (markdown, or
+/// JSON for a `.json` file) if given.
 pub fn run(
     item_id: &str,
     style: Option<String>,
     model: Option<String>,
-    title_only: bool,
-    description_only: bool,
-    chapters_only: bool,
-    tags_only: bool,
+    output_mode: OutputMode,
+    output: Option<&Path>,
 ) -> Result<()> {
     let db = get_database()?;
     let config = Config::load().context("Failed to load configuration")?;
@@ -105,14 +120,11 @@ pub fn run(
     // Parse content style
     let content_style = style
         .as_deref()
-        .map(|s| ContentStyle::from_str(s))
-        .flatten()
+        .and_then(ContentStyle::from_str)
         .unwrap_or(ContentStyle::Educational);
 
     // Get item by ID (with prefix matching)
-    let item = db
-        .get_item_by_prefix(item_id)
-        .context("Failed to find item")?;
+    let item = resolve::item(&db, item_id)?;
 
     println!(
         "{} {} {}",
@@ -149,13 +161,6 @@ pub fn run(
         .collect::<Vec<_>>()
         .join("\n\n");
 
-    // Truncate if too long (keep first ~8000 chars for context window)
-    let content = if content.len() > 8000 {
-        format!("{}...\n[Content truncated]", &content[..8000])
-    } else {
-        content
-    };
-
     // Create Ollama client
     let client = OllamaClient::from_config(&config.ollama)
         .context("Failed to create Ollama client")?;
@@ -166,19 +171,14 @@ pub fn run(
     // Check if Ollama is available
     let is_available = rt.block_on(client.is_available());
     if !is_available {
-        anyhow::bail!(
-            "Ollama is not running at {}. Start it with 'ollama serve'.",
-            config.ollama.host
-        );
+        return Err(OllamaError::ServerNotRunning {
+            host: config.ollama.host.clone(),
+        }
+        .into());
     }
 
     let model_name = model.as_deref().unwrap_or(&config.ollama.model);
-    let output_mode = OutputMode {
-        title_only,
-        description_only,
-        chapters_only,
-        tags_only,
-    };
+    let budget = rt.block_on(client.context_budget(model_name, config.ollama.context_tokens));
 
     // Generate metadata
     let mut metadata = YoutubeMetadata::default();
@@ -187,7 +187,7 @@ pub fn run(
     if output_mode.generate_all() || output_mode.title_only {
         print!("{}", "Generating title...".dimmed());
         io::stdout().flush()?;
-        let title = generate_title(&rt, &client, model_name, &content, content_style)?;
+        let title = generate_title(&rt, &client, model_name, &budget, &content, content_style)?;
         println!("\r{}", " ".repeat(30));
         metadata.title = Some(title);
     }
@@ -196,7 +196,7 @@ pub fn run(
     if output_mode.generate_all() || output_mode.description_only {
         print!("{}", "Generating description...".dimmed());
         io::stdout().flush()?;
-        let description = generate_description(&rt, &client, model_name, &content, content_style)?;
+        let description = generate_description(&rt, &client, model_name, &budget, &content, content_style)?;
         println!("\r{}", " ".repeat(30));
         metadata.description = Some(description);
     }
@@ -205,7 +205,7 @@ pub fn run(
     if output_mode.generate_all() || output_mode.tags_only {
         print!("{}", "Generating tags...".dimmed());
         io::stdout().flush()?;
-        let tags = generate_tags(&rt, &client, model_name, &content, content_style)?;
+        let tags = generate_tags(&rt, &client, model_name, &budget, &content, content_style)?;
         println!("\r{}", " ".repeat(30));
         metadata.tags = Some(tags);
     }
@@ -215,45 +215,85 @@ pub fn run(
     if has_timestamps && (output_mode.generate_all() || output_mode.chapters_only) {
         print!("{}", "Generating chapters...".dimmed());
         io::stdout().flush()?;
-        let chapters = generate_chapters(&rt, &client, model_name, &content, content_style)?;
+        let chapters = generate_chapters(&rt, &client, model_name, &budget, &content, content_style)?;
         println!("\r{}", " ".repeat(30));
         metadata.chapters = Some(chapters);
     }
 
+    // Generate thumbnail ideas and hooks
+    if output_mode.thumbnail {
+        print!("{}", "Generating thumbnail ideas...".dimmed());
+        io::stdout().flush()?;
+        let thumbnails = generate_thumbnails(&rt, &client, model_name, &budget, &content, content_style)?;
+        println!("\r{}", " ".repeat(30));
+        metadata.thumbnails = Some(thumbnails);
+
+        print!("{}", "Generating hooks...".dimmed());
+        io::stdout().flush()?;
+        let hooks = generate_hooks(&rt, &client, model_name, &budget, &content, content_style)?;
+        println!("\r{}", " ".repeat(30));
+        metadata.hooks = Some(hooks);
+    }
+
     // Display output
     display_metadata(&metadata, &output_mode);
 
+    if let Some(path) = output {
+        let is_json = path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let contents = if is_json {
+            serde_json::to_string_pretty(&metadata_json(&metadata))? + "\n"
+        } else {
+            metadata_markdown(&metadata, &item.title)
+        };
+        fs::write(path, contents).context("Failed to write output file")?;
+        println!("{} {}", "Saved to:".green().bold(), path.display());
+    }
+
     Ok(())
 }
 
+/// Template data shared by the YouTube prompts.
+fn style_data(style: ContentStyle, content: &str) -> Value {
+    json!({
+        "style": style.to_string(),
+        "style_instructions": style.prompt_modifier(),
+        "content": content,
+    })
+}
+
+/// Render a YouTube prompt with as much of `content` as fits the model's
+/// context.
+fn render_prompt(
+    prompt: Prompt,
+    style: ContentStyle,
+    content: &str,
+    budget: &ContextBudget,
+) -> Result<String> {
+    let empty = prompts::render(prompt, &style_data(style, ""))?;
+    let rendered = match budget.truncate(&empty, content) {
+        Some(cut) => prompts::render(
+            prompt,
+            &style_data(style, &format!("{}\n[Content truncated]", cut)),
+        )?,
+        None => prompts::render(prompt, &style_data(style, content))?,
+    };
+    Ok(rendered)
+}
+
 fn generate_title(
     rt: &Runtime,
     client: &OllamaClient,
     model: &str,
+    budget: &ContextBudget,
     content: &str,
     style: ContentStyle,
 ) -> Result<String> {
-    let prompt = format!(
-        r#"Generate a compelling YouTube video title based on this content.
-
-{}
-
-Requirements:
-- 50-60 characters maximum
-- Attention-grabbing but not clickbait
-- Include relevant keywords
-- Match the content style
-
-Content:
-{}
-
-Respond with ONLY the title, no quotes or extra text."#,
-        style.prompt_modifier(),
-        content
-    );
+    let prompt = render_prompt(Prompt::YoutubeTitle, style, content, budget)?;
 
     let request = GenerateRequest::new(model, &prompt)
-        .with_options(GenerateOptions::new().with_temperature(0.7));
+        .with_options(budget.options().with_temperature(0.7));
 
     let response = rt.block_on(client.generate(request)).map_err(|e| {
         anyhow::anyhow!("Failed to generate title: {}", e)
@@ -266,35 +306,14 @@ fn generate_description(
     rt: &Runtime,
     client: &OllamaClient,
     model: &str,
+    budget: &ContextBudget,
     content: &str,
     style: ContentStyle,
 ) -> Result<String> {
-    let prompt = format!(
-        r#"Generate a YouTube video description based on this content.
-
-{}
-
-Structure:
-1. Opening hook (1-2 sentences that grab attention)
-2. Main content summary as bullet points (3-5 points)
-3. Call to action (subscribe, like, comment)
-
-Requirements:
-- Use emojis sparingly but effectively
-- Include relevant keywords naturally
-- Keep it scannable with line breaks
-- 150-300 words total
-
-Content:
-{}
-
-Respond with ONLY the description text, ready to paste into YouTube."#,
-        style.prompt_modifier(),
-        content
-    );
+    let prompt = render_prompt(Prompt::YoutubeDescription, style, content, budget)?;
 
     let request = GenerateRequest::new(model, &prompt)
-        .with_options(GenerateOptions::new().with_temperature(0.7));
+        .with_options(budget.options().with_temperature(0.7));
 
     let response = rt.block_on(client.generate(request)).map_err(|e| {
         anyhow::anyhow!("Failed to generate description: {}", e)
@@ -307,30 +326,14 @@ fn generate_tags(
     rt: &Runtime,
     client: &OllamaClient,
     model: &str,
+    budget: &ContextBudget,
     content: &str,
     style: ContentStyle,
 ) -> Result<Vec<String>> {
-    let prompt = format!(
-        r#"Generate YouTube tags for this video content.
-
-{}
-
-Requirements:
-- 10-15 tags
-- Mix of broad and specific terms
-- Include relevant keywords from the content
-- Consider search terms viewers might use
-
-Content:
-{}
-
-Respond with ONLY a comma-separated list of tags, no numbering or extra text."#,
-        style.prompt_modifier(),
-        content
-    );
+    let prompt = render_prompt(Prompt::YoutubeTags, style, content, budget)?;
 
     let request = GenerateRequest::new(model, &prompt)
-        .with_options(GenerateOptions::new().with_temperature(0.5));
+        .with_options(budget.options().with_temperature(0.5));
 
     let response = rt.block_on(client.generate(request)).map_err(|e| {
         anyhow::anyhow!("Failed to generate tags: {}", e)
@@ -350,35 +353,14 @@ fn generate_chapters(
     rt: &Runtime,
     client: &OllamaClient,
     model: &str,
+    budget: &ContextBudget,
     content: &str,
     style: ContentStyle,
 ) -> Result<Vec<Chapter>> {
-    let prompt = format!(
-        r#"Generate YouTube chapter markers based on this timestamped content.
-
-{}
-
-Requirements:
-- Use the timestamps from the content (format: MM:SS or H:MM:SS)
-- Create 5-10 logical chapter breaks
-- Each chapter title should be 2-5 words
-- First chapter should start at 0:00 (Intro)
-
-Content:
-{}
-
-Respond with chapters in this exact format, one per line:
-0:00 Intro
-1:23 Chapter Title
-etc.
-
-No extra text or formatting."#,
-        style.prompt_modifier(),
-        content
-    );
+    let prompt = render_prompt(Prompt::YoutubeChapters, style, content, budget)?;
 
     let request = GenerateRequest::new(model, &prompt)
-        .with_options(GenerateOptions::new().with_temperature(0.3));
+        .with_options(budget.options().with_temperature(0.3));
 
     let response = rt.block_on(client.generate(request)).map_err(|e| {
         anyhow::anyhow!("Failed to generate chapters: {}", e)
@@ -408,6 +390,143 @@ No extra text or formatting."#,
     Ok(chapters)
 }
 
+fn generate_thumbnails(
+    rt: &Runtime,
+    client: &OllamaClient,
+    model: &str,
+    budget: &ContextBudget,
+    content: &str,
+    style: ContentStyle,
+) -> Result<Vec<ThumbnailConcept>> {
+    let prompt = render_prompt(Prompt::YoutubeThumbnails, style, content, budget)?;
+
+    let request = GenerateRequest::new(model, &prompt)
+        .with_options(budget.options().with_temperature(0.8));
+
+    let response = rt.block_on(client.generate(request)).map_err(|e| {
+        anyhow::anyhow!("Failed to generate thumbnail ideas: {}", e)
+    })?;
+
+    Ok(parse_thumbnails(&response.response))
+}
+
+fn generate_hooks(
+    rt: &Runtime,
+    client: &OllamaClient,
+    model: &str,
+    budget: &ContextBudget,
+    content: &str,
+    style: ContentStyle,
+) -> Result<Vec<String>> {
+    let prompt = render_prompt(Prompt::YoutubeHooks, style, content, budget)?;
+
+    let request = GenerateRequest::new(model, &prompt)
+        .with_options(budget.options().with_temperature(0.8));
+
+    let response = rt.block_on(client.generate(request)).map_err(|e| {
+        anyhow::anyhow!("Failed to generate hooks: {}", e)
+    })?;
+
+    Ok(parse_hooks(&response.response))
+}
+
+/// Parse `THUMBNAIL: text | concept` lines.
+fn parse_thumbnails(response: &str) -> Vec<ThumbnailConcept> {
+    response
+        .lines()
+        .filter_map(|line| strip_label(line, "THUMBNAIL:"))
+        .filter_map(|rest| {
+            let (text, concept) = rest.split_once('|')?;
+            let text = text.trim().trim_matches('"').to_string();
+            (!text.is_empty()).then(|| ThumbnailConcept {
+                text,
+                concept: concept.trim().to_string(),
+            })
+        })
+        .take(3)
+        .collect()
+}
+
+/// Parse `HOOK: script` lines.
+fn parse_hooks(response: &str) -> Vec<String> {
+    response
+        .lines()
+        .filter_map(|line| strip_label(line, "HOOK:"))
+        .map(|hook| hook.trim_matches('"').to_string())
+        .filter(|hook| !hook.is_empty())
+        .take(3)
+        .collect()
+}
+
+/// The rest of `line` after `label`, ignoring list markers and bold.
+fn strip_label<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    let line = line.trim().trim_start_matches(['-', '*', ' ']);
+    Some(line.strip_prefix(label)?.trim_start_matches('*').trim())
+}
+
+/// The metadata as a JSON object, leaving out sections not generated.
+fn metadata_json(metadata: &YoutubeMetadata) -> Value {
+    let mut value = json!({});
+    if let Some(ref title) = metadata.title {
+        value["title"] = json!(title);
+    }
+    if let Some(ref description) = metadata.description {
+        value["description"] = json!(description);
+    }
+    if let Some(ref tags) = metadata.tags {
+        value["tags"] = json!(tags);
+    }
+    if let Some(ref chapters) = metadata.chapters {
+        value["chapters"] = chapters
+            .iter()
+            .map(|c| json!({ "timestamp": c.timestamp, "title": c.title }))
+            .collect();
+    }
+    if let Some(ref thumbnails) = metadata.thumbnails {
+        value["thumbnails"] = thumbnails
+            .iter()
+            .map(|t| json!({ "text": t.text, "concept": t.concept }))
+            .collect();
+    }
+    if let Some(ref hooks) = metadata.hooks {
+        value["hooks"] = json!(hooks);
+    }
+    value
+}
+
+/// The metadata as a markdown document, one section per part.
+fn metadata_markdown(metadata: &YoutubeMetadata, item_title: &str) -> String {
+    let mut md = format!("# YouTube: {}\n", item_title);
+    if let Some(ref title) = metadata.title {
+        md.push_str(&format!("\n## Title\n\n{}\n", title));
+    }
+    if let Some(ref description) = metadata.description {
+        md.push_str(&format!("\n## Description\n\n{}\n", description));
+    }
+    if let Some(ref tags) = metadata.tags {
+        md.push_str(&format!("\n## Tags\n\n{}\n", tags.join(", ")));
+    }
+    if let Some(ref chapters) = metadata.chapters {
+        md.push_str("\n## Chapters\n\n");
+        for chapter in chapters {
+            md.push_str(&format!("{}\n", chapter));
+        }
+    }
+    if let Some(ref thumbnails) = metadata.thumbnails {
+        md.push_str("\n## Thumbnail Ideas\n\n");
+        for (i, thumbnail) in thumbnails.iter().enumerate() {
+            md.push_str(&format!("{}. **{}** - {}\n", i + 1, thumbnail.text, thumbnail.concept));
+        }
+    }
+    if let Some(ref hooks) = metadata.hooks {
+        md.push_str("\n## Hooks\n\n");
+        for (i, hook) in hooks.iter().enumerate() {
+            md.push_str(&format!("{}. {}\n", i + 1, hook));
+        }
+    }
+    md
+}
+
 fn display_metadata(metadata: &YoutubeMetadata, mode: &OutputMode) {
     if let Some(ref title) = metadata.title {
         if mode.generate_all() || mode.title_only {
@@ -443,6 +562,22 @@ fn display_metadata(metadata: &YoutubeMetadata, mode: &OutputMode) {
         }
     }
 
+    if let Some(ref thumbnails) = metadata.thumbnails {
+        println!("{}", "Thumbnail Ideas:".green().bold());
+        for (i, thumbnail) in thumbnails.iter().enumerate() {
+            println!("{}. {} {}", i + 1, thumbnail.text.white().bold(), thumbnail.concept.dimmed());
+        }
+        println!();
+    }
+
+    if let Some(ref hooks) = metadata.hooks {
+        println!("{}", "Hooks:".green().bold());
+        for (i, hook) in hooks.iter().enumerate() {
+            println!("{}. {}", i + 1, hook);
+        }
+        println!();
+    }
+
     // Show copy hint
     if mode.generate_all() {
         println!("{}", "─".repeat(70));
@@ -453,3 +588,41 @@ fn display_metadata(metadata: &YoutubeMetadata, mode: &OutputMode) {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_thumbnails_and_hooks() {
+        let thumbnails = parse_thumbnails(
+            "THUMBNAIL: \"It Compiled?!\" | Shocked face next to a green build log\n\
+             - **THUMBNAIL:** 3 Lines | Tiny code snippet, huge arrow\n\
+             THUMBNAIL: no concept here\n",
+        );
+        assert_eq!(thumbnails.len(), 2);
+        assert_eq!(thumbnails[0].text, "It Compiled?!");
+        assert_eq!(thumbnails[0].concept, "Shocked face next to a green build log");
+        assert_eq!(thumbnails[1].text, "3 Lines");
+
+        let hooks = parse_hooks("Here you go:\nHOOK: What if the borrow checker is right?\nHOOK: \n");
+        assert_eq!(hooks, vec!["What if the borrow checker is right?"]);
+    }
+
+    #[test]
+    fn test_metadata_exports() {
+        let metadata = YoutubeMetadata {
+            title: Some("Rust in 10 minutes".to_string()),
+            hooks: Some(vec!["Ever fought the borrow checker?".to_string()]),
+            ..Default::default()
+        };
+        let value = metadata_json(&metadata);
+        assert_eq!(value["title"], "Rust in 10 minutes");
+        assert_eq!(value["hooks"][0], "Ever fought the borrow checker?");
+        assert!(value.get("tags").is_none());
+
+        let md = metadata_markdown(&metadata, "rust-intro");
+        assert!(md.starts_with("# YouTube: rust-intro\n\n## Title\n\nRust in 10 minutes\n"));
+        assert!(md.contains("## Hooks\n\n1. Ever fought the borrow checker?\n"));
+    }
+}
@@ -2,8 +2,9 @@
 
 mod commands;
 
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 use colored::Colorize;
+use commands::search::SearchMode;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 /// Olal - Your Personal Second Brain & Life Operating System
@@ -18,6 +19,30 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// When an ID or name doesn't match, choose from the closest matches
+    #[arg(long, global = true)]
+    pick: bool,
+
+    /// Use this database file instead of the default (also OLAL_DB)
+    #[arg(long, global = true, value_name = "PATH")]
+    db: Option<std::path::PathBuf>,
+
+    /// Use this config file instead of the default (also OLAL_CONFIG)
+    #[arg(long, global = true, value_name = "PATH")]
+    config: Option<std::path::PathBuf>,
+
+    /// Merge [profile.NAME] from the config over the rest (also OLAL_PROFILE)
+    #[arg(long, global = true, value_name = "NAME")]
+    profile: Option<String>,
+
+    /// Use the named vault from [vaults] in the config (also OLAL_VAULT)
+    #[arg(long, global = true, value_name = "NAME")]
+    vault: Option<String>,
+
+    /// Print long output directly instead of through ui.pager
+    #[arg(long, global = true)]
+    no_pager: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -27,15 +52,66 @@ enum Commands {
     /// Initialize Olal (create config and database)
     Init,
 
+    /// Guided tour of capture, ingest, embed, search and ask in a sandbox
+    Tutorial {
+        /// Keep the sandbox knowledge base afterwards instead of deleting it
+        #[arg(long)]
+        keep: bool,
+    },
+
     /// Manage configuration
     #[command(subcommand)]
     Config(ConfigCommands),
 
+    /// Inspect and customize the prompt templates sent to the model
+    #[command(subcommand)]
+    Prompts(PromptsCommands),
+
     /// Show processing queue status
-    Status,
+    Status {
+        /// Print a compact one-line summary for shell prompts (queue, due tasks, unread items)
+        #[arg(short, long)]
+        short: bool,
+    },
+
+    /// Manage the processing queue
+    #[command(subcommand)]
+    Queue(QueueCommands),
 
     /// Show database statistics
-    Stats,
+    Stats {
+        /// Show your own usage patterns instead (recorded locally, never uploaded)
+        #[arg(long)]
+        me: bool,
+
+        /// Days of usage history to include
+        #[arg(long, default_value = "30", requires = "me")]
+        days: i64,
+    },
+
+    /// Compress stored chunk text and reclaim database space
+    Compact,
+
+    /// Write a compressed backup of the database and config
+    Backup {
+        /// Output archive (default: ./olal-backup-<timestamp>.tar.gz)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+
+        /// Also include derived artifacts (thumbnails, clips, extracted audio)
+        #[arg(long)]
+        artifacts: bool,
+    },
+
+    /// Restore a backup created with 'olal backup'
+    Restore {
+        /// Backup archive (.tar.gz)
+        archive: std::path::PathBuf,
+
+        /// Replace the existing knowledge base
+        #[arg(long)]
+        force: bool,
+    },
 
     /// List recent items
     Recent {
@@ -46,6 +122,10 @@ enum Commands {
         /// Filter by type (video, document, note, code, image)
         #[arg(short = 't', long)]
         item_type: Option<String>,
+
+        /// Show archived items instead
+        #[arg(long)]
+        archived: bool,
     },
 
     /// Search the knowledge base
@@ -58,8 +138,54 @@ enum Commands {
         limit: i64,
 
         /// Use semantic (vector) search instead of full-text
-        #[arg(long)]
+        #[arg(long, conflicts_with = "hybrid")]
         semantic: bool,
+
+        /// Combine semantic and full-text scores
+        #[arg(long)]
+        hybrid: bool,
+
+        /// Share of the hybrid score given to semantic similarity (0 to 1)
+        #[arg(long, default_value = "0.6", requires = "hybrid")]
+        vector_weight: f32,
+
+        /// Only items of this type (video, audio, document, note, code, image, bookmark)
+        #[arg(short = 't', long = "type", value_name = "TYPE")]
+        item_type: Option<String>,
+
+        /// Only items with this tag or its sub-tags (e.g. project/olal)
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only items attached to this project
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Only items added on or after this date (e.g. 2024-01-31, "last month", "2 weeks ago")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only items added on or before this date
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Words of matching text to show with each result
+        #[arg(long, value_name = "WORDS", default_value_t = commands::search::DEFAULT_CONTEXT_WORDS)]
+        context: usize,
+    },
+
+    /// Search with JSON output for launchers (Alfred, Raycast)
+    Quicksearch {
+        /// Search query (partial words are matched as prefixes)
+        query: String,
+
+        /// Output format: alfred, raycast
+        #[arg(short, long, default_value = "alfred")]
+        format: String,
+
+        /// Maximum results
+        #[arg(short, long, default_value = "20")]
+        limit: i64,
     },
 
     /// Ask a question using RAG (retrieval-augmented generation)
@@ -75,13 +201,54 @@ enum Commands {
         #[arg(short, long, default_value = "true")]
         sources: bool,
 
-        /// Maximum number of context chunks to use
-        #[arg(short, long, default_value = "5")]
-        context: usize,
+        /// Maximum number of context chunks to use (default: as many as fit
+        /// the model's context)
+        #[arg(short, long)]
+        context: Option<usize>,
 
         /// Stream the response as it's generated
         #[arg(long)]
         stream: bool,
+
+        /// Have a model re-order the retrieved chunks first (also ollama.rerank)
+        #[arg(long)]
+        rerank: bool,
+
+        /// Also search with 2-4 sub-queries a model splits the question
+        /// into, fusing the results (also ollama.multi_query)
+        #[arg(long)]
+        expand: bool,
+
+        /// Keep asking follow-up questions, with the conversation so far
+        #[arg(short, long)]
+        interactive: bool,
+
+        /// Only use items with this tag or its sub-tags
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only use items attached to this project
+        #[arg(long)]
+        project: Option<String>,
+
+        /// Only use this item (ID or ID prefix)
+        #[arg(long)]
+        item: Option<String>,
+
+        /// Only use items added on or after this date (e.g. yesterday, "last week", 2024-01-31)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only use items added on or before this date
+        #[arg(long)]
+        until: Option<String>,
+    },
+
+    /// Encrypt the database with SQLCipher (requires --features encryption)
+    Encrypt {
+        /// Save the key to the OS keyring so olal can unlock without prompting
+        #[arg(long)]
+        store_key: bool,
     },
 
     /// Generate embeddings for semantic search
@@ -103,8 +270,60 @@ enum Commands {
     Show {
         /// Item ID
         id: String,
+
+        /// Show every chunk in full (the whole transcript for video and audio)
+        #[arg(long)]
+        chunks: bool,
+    },
+
+    /// Delete an item with its chunks, embeddings, tags and links
+    Delete {
+        /// Item ID (or prefix)
+        id: String,
+
+        /// Show what would be deleted without deleting
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Also remove derived artifacts (thumbnails, clips, extracted audio)
+        #[arg(long)]
+        artifacts: bool,
+    },
+
+    /// Archive an item (hide it from search, recent and ask without deleting)
+    Archive {
+        /// Item ID (or prefix)
+        id: String,
+    },
+
+    /// Restore an archived item
+    Unarchive {
+        /// Item ID (or prefix)
+        id: String,
+    },
+
+    /// Show items related to an item (links, shared tags, similarity)
+    Related {
+        /// Item ID (or prefix)
+        id: String,
+
+        /// Maximum number of related items to show
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
     },
 
+    /// People, organizations and topics mentioned in your items
+    #[command(subcommand)]
+    Entity(EntityCommands),
+
+    /// Knowledge graph operations
+    #[command(subcommand)]
+    Graph(GraphCommands),
+
+    /// Edit captured notes
+    #[command(subcommand)]
+    Note(NoteCommands),
+
     /// Manage tasks
     #[command(subcommand)]
     Task(TaskCommands),
@@ -113,13 +332,18 @@ enum Commands {
     #[command(subcommand)]
     Project(ProjectCommands),
 
-    /// Add a tag to an item
+    /// Add a tag to an item, or manage tags
+    #[command(args_conflicts_with_subcommands = true, arg_required_else_help = true)]
     Tag {
+        #[command(subcommand)]
+        command: Option<TagCommands>,
+
         /// Item ID
-        item_id: String,
+        #[arg(requires = "tag")]
+        item_id: Option<String>,
 
         /// Tag name
-        tag: String,
+        tag: Option<String>,
     },
 
     /// List all tags
@@ -127,13 +351,18 @@ enum Commands {
 
     /// Ingest files or directories
     Ingest {
-        /// Path to file or directory to ingest
+        /// Path to file or directory to ingest (`-` to read from stdin)
         path: String,
 
-        /// Filter by file type (video, document, note, code, image)
-        #[arg(short = 't', long)]
+        /// Filter by file type (video, document, note, code, image), or the
+        /// type of content read from stdin
+        #[arg(short = 't', long, visible_alias = "type")]
         item_type: Option<String>,
 
+        /// Title for content read from stdin (default: its first line)
+        #[arg(long)]
+        title: Option<String>,
+
         /// Show what would be ingested without actually ingesting
         #[arg(long)]
         dry_run: bool,
@@ -141,11 +370,34 @@ enum Commands {
         /// Add to processing queue instead of processing immediately
         #[arg(short, long)]
         queue: bool,
+
+        /// For a .zip or .tar.gz archive, also keep an item for the archive
+        /// itself, with its files linked to it
+        #[arg(long)]
+        archive_item: bool,
+
+        /// Treat the path as a git repository: skip ignored and binary files
+        /// and record each file's commit and relative path
+        #[arg(long, conflicts_with_all = ["queue", "archive_item"])]
+        git: bool,
+
+        /// With --git, only ingest files changed since this revision
+        #[arg(long, value_name = "REV", requires = "git")]
+        since: Option<String>,
+
+        /// Treat the path as an arXiv ID or URL: download the paper's PDF and
+        /// record its authors, abstract and categories
+        #[arg(long, conflicts_with_all = ["queue", "archive_item", "git"])]
+        arxiv: bool,
     },
 
+    /// Import items exported from other tools
+    #[command(subcommand)]
+    Import(ImportCommands),
+
     /// Capture a quick thought or note
     Capture {
-        /// The thought or note content
+        /// The thought or note content (`-` to read from stdin)
         thought: String,
 
         /// Optional title for the note
@@ -155,6 +407,16 @@ enum Commands {
         /// Tags to add (can be specified multiple times)
         #[arg(short = 'T', long = "tag")]
         tags: Vec<String>,
+
+        /// Then ask how the thought relates to your knowledge base, linking it to related items
+        #[arg(
+            long,
+            value_name = "QUESTION",
+            num_args = 0..=1,
+            require_equals = true,
+            default_missing_value = "How does this relate to my existing notes?"
+        )]
+        ask: Option<String>,
     },
 
     /// Detect engaging clips from video/audio content
@@ -177,12 +439,90 @@ enum Commands {
         /// Model to use for analysis
         #[arg(short, long)]
         model: Option<String>,
+
+        /// Cut the clips with ffmpeg and add them as items
+        #[arg(long)]
+        export: bool,
+
+        /// Directory for exported clips (default: current directory)
+        #[arg(long, requires = "export")]
+        dir: Option<std::path::PathBuf>,
+
+        /// Export 9:16 shorts with burned-in subtitles (Shorts, TikTok, Reels)
+        #[arg(long, requires = "export")]
+        vertical: bool,
+
+        /// Fit the whole frame into 9:16 with bars instead of cropping
+        #[arg(long, requires = "vertical")]
+        pad: bool,
+    },
+
+    /// Generate study flashcards from an item, and export them for Anki
+    #[command(
+        args_conflicts_with_subcommands = true,
+        subcommand_negates_reqs = true,
+        arg_required_else_help = true
+    )]
+    Flashcards {
+        #[command(subcommand)]
+        command: Option<FlashcardCommands>,
+
+        /// Item to generate flashcards from (ID, prefix or title)
+        #[arg(required = true)]
+        item_id: Option<String>,
+
+        /// Number of flashcards to generate
+        #[arg(short, long, default_value = "10")]
+        count: usize,
+
+        /// Model to use for generation
+        #[arg(short, long)]
+        model: Option<String>,
+    },
+
+    /// Translate an item's transcript or text into another language
+    Translate {
+        /// Item to translate (ID, prefix or title)
+        item_id: String,
+
+        /// Target language (ISO 639-1 code, e.g. en, es, de)
+        #[arg(long)]
+        to: String,
+
+        /// Model to use for translation
+        #[arg(short, long)]
+        model: Option<String>,
+
+        /// Embed the translation, so searches in that language find it
+        #[arg(long)]
+        embed: bool,
     },
 
     /// Watch directories for new files
     #[command(subcommand)]
     Watch(WatchCommands),
 
+    /// Process queued files in the background
+    #[command(subcommand)]
+    Worker(WorkerCommands),
+
+    /// Developer tools (built with --features dev)
+    #[cfg(feature = "dev")]
+    #[command(subcommand)]
+    Dev(DevCommands),
+
+    /// List and run recurring jobs from the [schedule] config
+    #[command(subcommand)]
+    Schedule(ScheduleCommands),
+
+    /// Store credentials in the OS keyring for "keyring:<name>" config values
+    #[command(subcommand)]
+    Secret(SecretCommands),
+
+    /// Manage separate knowledge bases (use one with --vault <name>)
+    #[command(subcommand)]
+    Vault(VaultCommands),
+
     /// Generate YouTube metadata from video content
     Youtube {
         /// Item ID (video with transcript)
@@ -211,18 +551,112 @@ enum Commands {
         /// Generate tags only
         #[arg(long)]
         tags_only: bool,
+
+        /// Also suggest 3 thumbnail concepts and 3 opening hooks
+        #[arg(long)]
+        thumbnail: bool,
+
+        /// Save everything generated to a file (.json for JSON, else markdown)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+
+    /// Find items with nearly the same text, and merge or link them
+    Dedupe {
+        /// Bits (of 64) two fingerprints may differ by to count as near duplicates
+        #[arg(long, default_value_t = olal_db::fingerprint::DEFAULT_MAX_DISTANCE)]
+        distance: u32,
+
+        /// Merge every cluster into its oldest item without asking
+        #[arg(long, conflicts_with = "link")]
+        merge: bool,
+
+        /// Link every cluster's items as duplicates without asking
+        #[arg(long)]
+        link: bool,
+    },
+
+    /// Run knowledge-graph maintenance (links, duplicates, summaries, tags)
+    Maintain {
+        /// Report recommendations without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Apply retention rules (archive, delete, compress, queue cleanup)
+    Retention {
+        /// Show what would change without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Spaced-repetition review of items worth remembering
+    #[command(args_conflicts_with_subcommands = true)]
+    Review {
+        #[command(subcommand)]
+        command: Option<ReviewCommands>,
+
+        /// Maximum items to review
+        #[arg(short, long, default_value = "20")]
+        limit: usize,
+    },
+
+    /// Review low-confidence AI tags, summaries and links
+    ReviewAi {
+        /// Accept suggestions by ID (or prefix)
+        #[arg(long, num_args = 1..)]
+        accept: Vec<String>,
+
+        /// Reject suggestions by ID (or prefix)
+        #[arg(long, num_args = 1..)]
+        reject: Vec<String>,
+
+        /// Accept all pending suggestions matching the filters
+        #[arg(long, conflicts_with_all = ["reject_all", "reject"])]
+        accept_all: bool,
+
+        /// Reject all pending suggestions matching the filters
+        #[arg(long, conflicts_with = "accept")]
+        reject_all: bool,
+
+        /// Only suggestions of this kind (tag, summary, link)
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Only suggestions with confidence below this value
+        #[arg(long)]
+        below: Option<f32>,
+
+        /// Only suggestions with confidence at or above this value
+        #[arg(long)]
+        above: Option<f32>,
     },
 
     /// Start an interactive shell
     Shell,
 
+    /// Serve the knowledge base over HTTP (JSON API, browser UI with --ui)
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1")]
+        host: String,
+
+        /// Port to listen on (0 picks a free one)
+        #[arg(short, long, default_value = "7878")]
+        port: u16,
+
+        /// Also serve the browser interface at /
+        #[arg(long)]
+        ui: bool,
+    },
+
     /// Generate a digest of recent content
     Digest {
         /// Time period: day, week, month
         #[arg(short, long, default_value = "day")]
         period: String,
 
-        /// Start from specific date (YYYY-MM-DD)
+        /// Start from a specific date (e.g. 2024-03-01, "last monday", "2 weeks ago")
         #[arg(long)]
         since: Option<String>,
 
@@ -233,6 +667,18 @@ enum Commands {
         /// Model to use
         #[arg(short, long)]
         model: Option<String>,
+
+        /// Rate the digest's topics and sources afterwards to personalize future digests
+        #[arg(long)]
+        feedback: bool,
+
+        /// Add a section on what changed since the previous period
+        #[arg(long)]
+        compare: bool,
+
+        /// Also email the digest (configure [digest.smtp] first)
+        #[arg(long)]
+        email: bool,
     },
 }
 
@@ -250,6 +696,15 @@ enum ConfigCommands {
         path: String,
     },
 
+    /// Print a configuration value (with profile and env overrides applied)
+    Get {
+        /// Configuration key (e.g., ollama.host or watch.directories[0])
+        key: String,
+    },
+
+    /// Check the config file for errors, unknown keys and unreachable hosts
+    Validate,
+
     /// Set a configuration value
     Set {
         /// Configuration key (e.g., ollama.model)
@@ -260,6 +715,182 @@ enum ConfigCommands {
     },
 }
 
+#[derive(Subcommand)]
+enum PromptsCommands {
+    /// List prompts, marking customized ones
+    List,
+
+    /// Print the template in use for a prompt
+    Show {
+        /// Prompt name (e.g. summary, rag, digest)
+        name: String,
+    },
+
+    /// Customize a prompt in your editor (starts from the built-in)
+    Edit {
+        /// Prompt name
+        name: String,
+    },
+
+    /// Go back to the built-in template for a prompt
+    Reset {
+        /// Prompt name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum EntityCommands {
+    /// List entities
+    List {
+        /// Filter by kind (person, organization, topic)
+        #[arg(short, long)]
+        kind: Option<String>,
+
+        /// Maximum number of entities to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+
+    /// Show everything mentioning an entity
+    Show {
+        /// Entity name
+        name: String,
+    },
+
+    /// Extract entities from existing items
+    Extract {
+        /// Extract for a specific item (ID or prefix)
+        #[arg(short, long)]
+        item: Option<String>,
+
+        /// Extract for all items
+        #[arg(long)]
+        all: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GraphCommands {
+    /// Export items, links and tags as a graph
+    Export {
+        /// Output format: dot, graphml, json
+        #[arg(short, long, default_value = "json")]
+        format: String,
+
+        /// Only include items with this tag or its sub-tags
+        #[arg(long)]
+        tag: Option<String>,
+
+        /// Only include items of this type (video, document, note, code, image)
+        #[arg(short = 't', long)]
+        item_type: Option<String>,
+
+        /// Only include items created on or after this date (e.g. 2024-03-01, "3 days ago")
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only include items created on or before this date
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Output to file (default: stdout)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum QueueCommands {
+    /// List queue entries
+    List {
+        /// Filter by status (pending, processing, done, failed)
+        #[arg(short, long)]
+        status: Option<String>,
+    },
+
+    /// Retry a failed entry
+    Retry {
+        /// Queue entry ID (or prefix)
+        #[arg(required_unless_present = "all_failed")]
+        id: Option<String>,
+
+        /// Retry every failed entry
+        #[arg(long, conflicts_with = "id")]
+        all_failed: bool,
+    },
+
+    /// Remove finished entries
+    Clear {
+        /// Remove completed entries
+        #[arg(long)]
+        done: bool,
+
+        /// Remove failed entries
+        #[arg(long)]
+        failed: bool,
+    },
+
+    /// Process pending entries now
+    Process {
+        /// Maximum number of entries to process
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+}
+
+#[derive(Subcommand)]
+enum FlashcardCommands {
+    /// List stored flashcards
+    List {
+        /// Only this item's flashcards (ID or prefix)
+        #[arg(long)]
+        item: Option<String>,
+    },
+
+    /// Export flashcards as Anki-importable tab-separated text
+    Export {
+        /// Output file (default: stdout)
+        #[arg(short, long)]
+        output: Option<std::path::PathBuf>,
+
+        /// Only this item's flashcards (ID or prefix)
+        #[arg(long)]
+        item: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReviewCommands {
+    /// Schedule an item for review (due now)
+    Add {
+        /// Item ID (or prefix)
+        id: String,
+    },
+
+    /// Stop reviewing an item
+    Remove {
+        /// Item ID (or prefix)
+        id: String,
+    },
+
+    /// List scheduled reviews, soonest first
+    List {
+        /// Maximum number to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+    },
+}
+
+#[derive(Subcommand)]
+enum NoteCommands {
+    /// Edit a note in $EDITOR, then re-chunk and re-embed it
+    Edit {
+        /// Item ID (or prefix)
+        id: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum TaskCommands {
     /// Add a new task
@@ -274,6 +905,18 @@ enum TaskCommands {
         /// Project name
         #[arg(short = 'P', long)]
         project: Option<String>,
+
+        /// Due date, optionally with a time (e.g. 2024-03-01, tomorrow, "friday 5pm", "in 3 days")
+        #[arg(short, long)]
+        due: Option<String>,
+
+        /// Repeat the task: daily, weekdays, weekly, monthly, yearly
+        #[arg(short, long, value_name = "EVERY")]
+        repeat: Option<String>,
+
+        /// Make this a subtask of another task (ID or prefix)
+        #[arg(long, value_name = "TASK")]
+        parent: Option<String>,
     },
 
     /// List tasks
@@ -283,17 +926,90 @@ enum TaskCommands {
         status: Option<String>,
     },
 
-    /// Mark a task as done
+    /// Open tasks due today, including overdue ones
+    Today,
+
+    /// Open tasks past their due date
+    Overdue,
+
+    /// Kanban board of pending, in-progress and done tasks
+    Board {
+        /// Only this project's tasks
+        #[arg(short = 'P', long)]
+        project: Option<String>,
+    },
+
+    /// Mark a task as in progress (reopens done or cancelled tasks)
+    Start {
+        /// Task ID
+        id: String,
+    },
+
+    /// Change a task's title, description, priority, project, due date or recurrence
+    Edit {
+        /// Task ID
+        id: String,
+
+        /// New title
+        #[arg(long)]
+        title: Option<String>,
+
+        /// New description (empty to clear)
+        #[arg(long)]
+        description: Option<String>,
+
+        /// New priority
+        #[arg(short, long)]
+        priority: Option<i32>,
+
+        /// Move to this project ("none" to remove from its project)
+        #[arg(short = 'P', long)]
+        project: Option<String>,
+
+        /// New due date ("none" to clear)
+        #[arg(short, long)]
+        due: Option<String>,
+
+        /// New recurrence ("none" to stop repeating)
+        #[arg(short, long, value_name = "EVERY")]
+        repeat: Option<String>,
+    },
+
+    /// Cancel a task
+    Cancel {
+        /// Task ID
+        id: String,
+    },
+
+    /// Mark a task as done (a recurring task gets its next occurrence)
     Done {
         /// Task ID
         id: String,
     },
 
-    /// Delete a task
+    /// Delete a task and its subtasks
     Delete {
         /// Task ID
         id: String,
     },
+
+    /// Mark a task as blocked until another is done
+    Block {
+        /// Task ID (or prefix)
+        id: String,
+
+        /// The task it waits on (ID or prefix)
+        blocked_by: String,
+    },
+
+    /// Remove a blocked-by relationship
+    Unblock {
+        /// Task ID (or prefix)
+        id: String,
+
+        /// The task it waited on (ID or prefix)
+        blocked_by: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -312,6 +1028,126 @@ enum WatchCommands {
     Status,
 }
 
+#[derive(Subcommand)]
+enum WorkerCommands {
+    /// Start draining the queue (foreground)
+    Start {
+        /// Run in the background, logging to the worker log file
+        #[arg(short, long)]
+        daemon: bool,
+    },
+
+    /// Stop a background worker after its current jobs
+    Stop,
+
+    /// Show worker and queue status
+    Status,
+}
+
+#[cfg(feature = "dev")]
+#[derive(Subcommand)]
+enum DevCommands {
+    /// Fill the knowledge base with a synthetic, reproducible corpus
+    Seed {
+        /// Number of items to generate
+        #[arg(long, default_value = "500")]
+        items: usize,
+
+        /// Random seed; the same seed gives the same corpus
+        #[arg(long, default_value = "42")]
+        seed: u64,
+
+        /// Add to a knowledge base that already has items (use a new --seed)
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum ScheduleCommands {
+    /// List scheduled jobs with their last run
+    List,
+
+    /// Run a scheduled job now
+    Run {
+        /// Job name (its task name unless named in the config)
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ImportCommands {
+    /// Import a browser bookmarks export (HTML, or Chrome/Firefox JSON),
+    /// tagging each bookmark with its folder
+    Bookmarks {
+        /// Exported bookmarks file
+        file: std::path::PathBuf,
+
+        /// Fetch each page's text in the background ('olal worker start')
+        #[arg(long)]
+        fetch: bool,
+    },
+
+    /// Import a Pocket export (HTML or CSV), tagging archived articles
+    Pocket {
+        /// Exported Pocket file
+        file: std::path::PathBuf,
+
+        /// Fetch each page's text in the background ('olal worker start')
+        #[arg(long)]
+        fetch: bool,
+    },
+
+    /// Import an Instapaper CSV export, tagging archived and starred articles
+    Instapaper {
+        /// Exported Instapaper CSV file
+        file: std::path::PathBuf,
+
+        /// Fetch each page's text in the background ('olal worker start')
+        #[arg(long)]
+        fetch: bool,
+    },
+
+    /// Import Kindle highlights from 'My Clippings.txt', one item per book
+    Kindle {
+        /// The Kindle's clippings file (documents/My Clippings.txt)
+        file: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum SecretCommands {
+    /// Store a secret (prompts, or reads a line from stdin)
+    Set {
+        /// Secret name, used in the config as "keyring:<name>"
+        name: String,
+    },
+
+    /// Remove a secret
+    Delete {
+        /// Secret name
+        name: String,
+    },
+
+    /// Show the keyring references in the config and whether each is stored
+    List,
+}
+
+#[derive(Subcommand)]
+enum VaultCommands {
+    /// List vaults, marking the active one
+    List,
+
+    /// Add a vault with its own data directory
+    Add {
+        /// Vault name, as passed to --vault
+        name: String,
+
+        /// Directory for the vault's database and files
+        data_dir: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum ProjectCommands {
     /// Create a new project
@@ -327,11 +1163,62 @@ enum ProjectCommands {
     /// List all projects
     List,
 
-    /// Show project details
+    /// Show project details, tasks and items
     Show {
         /// Project name or ID
         name: String,
     },
+
+    /// Attach a knowledge item to a project
+    AddItem {
+        /// Project name
+        project: String,
+
+        /// Item ID (or prefix)
+        item_id: String,
+    },
+
+    /// Detach an item from a project
+    RemoveItem {
+        /// Project name
+        project: String,
+
+        /// Item ID (or prefix)
+        item_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum TagCommands {
+    /// Rename a tag (its sub-tags move along)
+    Rename {
+        /// Current tag name
+        name: String,
+
+        /// New tag name
+        new_name: String,
+    },
+
+    /// Move every item from one tag to another, then delete the first
+    Merge {
+        /// Tag to merge away
+        from: String,
+
+        /// Tag to keep
+        into: String,
+    },
+
+    /// Delete a tag and its sub-tags, removing them from their items
+    Delete {
+        /// Tag name
+        name: String,
+    },
+
+    /// Show a tag's usage and the items carrying it or its sub-tags
+    Show {
+        /// Tag name
+        name: String,
+    },
 }
 
 fn init_logging(verbose: bool) {
@@ -341,50 +1228,236 @@ fn init_logging(verbose: bool) {
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("olal=info,warn"))
     };
 
+    // Logs go to stderr so they never mix into output meant for scripts
     tracing_subscriber::registry()
         .with(filter)
-        .with(tracing_subscriber::fmt::layer().with_target(false))
+        .with(tracing_subscriber::fmt::layer().with_target(false).with_writer(std::io::stderr))
         .init();
 }
 
+/// Route `--db`/`--config` through the environment, so every path lookup
+/// (and any child process, like a daemonized worker) sees them.
+fn apply_path_overrides(cli: &Cli) -> anyhow::Result<()> {
+    let overrides = [
+        (olal_config::DB_ENV, &cli.db),
+        (olal_config::CONFIG_ENV, &cli.config),
+    ];
+    for (name, path) in overrides {
+        if let Some(path) = path {
+            let path = std::path::absolute(path).unwrap_or_else(|_| path.clone());
+            std::env::set_var(name, path);
+        }
+    }
+
+    if let Some(profile) = &cli.profile {
+        std::env::set_var(olal_config::PROFILE_ENV, profile);
+    }
+
+    // A vault swaps in its own data directory; an explicit --db still wins.
+    if let Some(vault) = &cli.vault {
+        std::env::set_var(olal_config::VAULT_ENV, vault);
+    }
+    let (profile, vault) = (olal_config::active_profile(), olal_config::active_vault());
+    if profile.is_none() && vault.is_none() {
+        return Ok(());
+    }
+    let paths = olal_config::AppPaths::new().ok_or(olal_config::ConfigError::NoConfigDir)?;
+    let config = olal_config::Config::load_from(&paths.config_file)?;
+    if let Some(name) = profile.filter(|name| !config.profile.contains_key(name)) {
+        return Err(olal_config::ConfigError::UnknownProfile(name).into());
+    }
+    if let (Some(name), None) = (vault, &cli.db) {
+        std::env::set_var(olal_config::DB_ENV, config.vault(&name)?.database_file());
+    }
+    Ok(())
+}
+
+fn report_error(e: &anyhow::Error) -> ! {
+    let (code, hint) = commands::diagnose(e);
+    eprintln!("{} {}", format!("Error[{}]:", code).red().bold(), e);
+    if let Some(hint) = hint {
+        eprintln!("{} {}", "Hint:".yellow(), hint);
+    }
+    std::process::exit(code.exit_code());
+}
+
 fn main() {
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let command_name = commands::usage::command_name(&matches);
+    let record_usage = commands::usage::is_recorded(&matches);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    if let Err(e) = apply_path_overrides(&cli) {
+        report_error(&e);
+    }
     init_logging(cli.verbose);
+    // The prompt status neither pages nor colors, so skips loading the config
+    if !matches!(cli.command, Commands::Status { short: true }) {
+        commands::output::init(cli.no_pager);
+    }
+    commands::resolve::set_pick(cli.pick);
 
+    let started_at = chrono::Utc::now();
+    let timer = std::time::Instant::now();
     let result = match cli.command {
         Commands::Init => commands::init::run(),
+        Commands::Tutorial { keep } => commands::tutorial::run(keep),
         Commands::Config(cmd) => match cmd {
             ConfigCommands::Show => commands::config::show(),
             ConfigCommands::Edit => commands::config::edit(),
             ConfigCommands::AddWatch { path } => commands::config::add_watch(&path),
+            ConfigCommands::Get { key } => commands::config::get(&key),
+            ConfigCommands::Validate => commands::config::validate(),
             ConfigCommands::Set { key, value } => commands::config::set(&key, &value),
         },
-        Commands::Status => commands::status::run(),
-        Commands::Stats => commands::stats::run(),
-        Commands::Recent { limit, item_type } => commands::recent::run(limit, item_type),
-        Commands::Search { query, limit, semantic } => commands::search::run(&query, limit, semantic),
-        Commands::Show { id } => commands::show::run(&id),
+        Commands::Prompts(cmd) => match cmd {
+            PromptsCommands::List => commands::prompts::list(),
+            PromptsCommands::Show { name } => commands::prompts::show(&name),
+            PromptsCommands::Edit { name } => commands::prompts::edit(&name),
+            PromptsCommands::Reset { name } => commands::prompts::reset(&name),
+        },
+        Commands::Status { short } => commands::status::run(short),
+        Commands::Queue(cmd) => match cmd {
+            QueueCommands::List { status } => commands::queue::list(status),
+            QueueCommands::Retry { id, all_failed } => commands::queue::retry(id, all_failed),
+            QueueCommands::Clear { done, failed } => commands::queue::clear(done, failed),
+            QueueCommands::Process { limit } => commands::queue::process(limit),
+        },
+        Commands::Stats { me: false, .. } => commands::stats::run(),
+        Commands::Stats { me: true, days } => commands::stats::me(days),
+        Commands::Compact => commands::compact::run(),
+        Commands::Backup { output, artifacts } => commands::backup::run(output, artifacts),
+        Commands::Restore { archive, force } => commands::backup::restore(&archive, force),
+        Commands::Recent {
+            limit,
+            item_type,
+            archived,
+        } => commands::recent::run(limit, item_type, archived),
+        Commands::Search {
+            query,
+            limit,
+            semantic,
+            hybrid,
+            vector_weight,
+            item_type,
+            tag,
+            project,
+            since,
+            until,
+            context,
+        } => {
+            let mode = if hybrid {
+                SearchMode::Hybrid { vector_weight }
+            } else if semantic {
+                SearchMode::Semantic
+            } else {
+                SearchMode::FullText
+            };
+            commands::search::filter(
+                item_type.as_deref(),
+                tag,
+                project,
+                since.as_deref(),
+                until.as_deref(),
+            )
+            .and_then(|filter| commands::search::run(&query, limit, mode, &filter, context))
+        }
+        Commands::Quicksearch {
+            query,
+            format,
+            limit,
+        } => commands::quicksearch::run(&query, &format, limit),
+        Commands::Show { id, chunks } => commands::show::run(&id, chunks),
+        Commands::Delete {
+            id,
+            dry_run,
+            artifacts,
+        } => commands::delete::run(&id, dry_run, artifacts),
+        Commands::Archive { id } => commands::archive::archive(&id),
+        Commands::Unarchive { id } => commands::archive::unarchive(&id),
+        Commands::Related { id, limit } => commands::related::run(&id, limit),
         Commands::Ask {
             question,
             model,
             sources,
             context,
             stream,
-        } => commands::ask::run(&question, model, sources, context, stream),
+            rerank,
+            expand,
+            interactive,
+            tag,
+            project,
+            item,
+            since,
+            until,
+        } => commands::search::filter(None, tag, project, since.as_deref(), until.as_deref())
+            .and_then(|filter| {
+                let filter = olal_db::SearchFilter { item_id: item, ..filter };
+                commands::ask::run(
+                    &question, model, sources, context, stream, rerank, expand, interactive, filter,
+                )
+            }),
+        Commands::Encrypt { store_key } => commands::encrypt::run(store_key),
         Commands::Embed {
             all,
             item,
             batch_size,
         } => commands::embed::run(all, item, batch_size),
+        Commands::Entity(cmd) => match cmd {
+            EntityCommands::List { kind, limit } => commands::entity::list(kind, limit),
+            EntityCommands::Show { name } => commands::entity::show(&name),
+            EntityCommands::Extract { item, all } => commands::entity::extract(item, all),
+        },
+        Commands::Graph(cmd) => match cmd {
+            GraphCommands::Export {
+                format,
+                tag,
+                item_type,
+                since,
+                until,
+                output,
+            } => commands::graph::export(&format, tag, item_type, since, until, output),
+        },
+        Commands::Note(cmd) => match cmd {
+            NoteCommands::Edit { id } => commands::note::edit(&id),
+        },
         Commands::Task(cmd) => match cmd {
             TaskCommands::Add {
                 description,
                 priority,
                 project,
-            } => commands::task::add(&description, priority, project),
+                due,
+                repeat,
+                parent,
+            } => commands::task::add(&description, priority, project, due, repeat, parent),
             TaskCommands::List { status } => commands::task::list(status),
+            TaskCommands::Today => commands::task::today(),
+            TaskCommands::Overdue => commands::task::overdue(),
+            TaskCommands::Board { project } => commands::board::run(project),
+            TaskCommands::Start { id } => commands::task::start(&id),
+            TaskCommands::Edit {
+                id,
+                title,
+                description,
+                priority,
+                project,
+                due,
+                repeat,
+            } => commands::task::edit(
+                &id,
+                commands::task::TaskEdit {
+                    title,
+                    description,
+                    priority,
+                    project,
+                    due,
+                    repeat,
+                },
+            ),
+            TaskCommands::Cancel { id } => commands::task::cancel(&id),
             TaskCommands::Done { id } => commands::task::done(&id),
             TaskCommands::Delete { id } => commands::task::delete(&id),
+            TaskCommands::Block { id, blocked_by } => commands::task::block(&id, &blocked_by),
+            TaskCommands::Unblock { id, blocked_by } => commands::task::unblock(&id, &blocked_by),
         },
         Commands::Project(cmd) => match cmd {
             ProjectCommands::Create { name, description } => {
@@ -392,33 +1465,163 @@ fn main() {
             }
             ProjectCommands::List => commands::project::list(),
             ProjectCommands::Show { name } => commands::project::show(&name),
+            ProjectCommands::AddItem { project, item_id } => {
+                commands::project::add_item(&project, &item_id)
+            }
+            ProjectCommands::RemoveItem { project, item_id } => {
+                commands::project::remove_item(&project, &item_id)
+            }
+        },
+        Commands::Tag {
+            command,
+            item_id,
+            tag,
+        } => match (command, item_id, tag) {
+            (Some(TagCommands::Rename { name, new_name }), _, _) => {
+                commands::tag::rename(&name, &new_name)
+            }
+            (Some(TagCommands::Merge { from, into }), _, _) => commands::tag::merge(&from, &into),
+            (Some(TagCommands::Delete { name }), _, _) => commands::tag::delete(&name),
+            (Some(TagCommands::Show { name }), _, _) => commands::tag::show(&name),
+            (None, Some(item_id), Some(tag)) => commands::tag::add(&item_id, &tag),
+            (None, _, _) => unreachable!("clap requires an item and tag or a subcommand"),
         },
-        Commands::Tag { item_id, tag } => commands::tag::add(&item_id, &tag),
         Commands::Tags => commands::tag::list(),
         Commands::Ingest {
             path,
             item_type,
+            title,
             dry_run,
             queue,
-        } => commands::ingest::run(&path, item_type, dry_run, queue),
+            archive_item,
+            git,
+            since,
+            arxiv,
+        } => {
+            if git {
+                commands::ingest::run_git(&path, since.as_deref(), dry_run)
+            } else if arxiv {
+                commands::ingest::run_arxiv(&path, dry_run)
+            } else {
+                commands::ingest::run(&path, item_type, title, dry_run, queue, archive_item)
+            }
+        }
+        Commands::Import(cmd) => match cmd {
+            ImportCommands::Bookmarks { file, fetch } => commands::import::bookmarks(&file, fetch),
+            ImportCommands::Pocket { file, fetch } => commands::import::pocket(&file, fetch),
+            ImportCommands::Instapaper { file, fetch } => commands::import::instapaper(&file, fetch),
+            ImportCommands::Kindle { file } => commands::import::kindle(&file),
+        },
         Commands::Capture {
             thought,
             title,
             tags,
-        } => commands::capture::run(&thought, title, tags),
+            ask,
+        } => commands::capture::run(&thought, title, tags, ask),
         Commands::Clips {
             item_id,
             count,
             min_duration,
             max_duration,
             model,
-        } => commands::clips::run(&item_id, count, min_duration, max_duration, model),
+            export,
+            dir,
+            vertical,
+            pad,
+        } => commands::clips::run(
+            &item_id,
+            count,
+            min_duration,
+            max_duration,
+            model,
+            export.then(|| commands::clips::ClipExport {
+                dir: dir.unwrap_or_else(|| ".".into()),
+                vertical: vertical.then_some(if pad {
+                    olal_process::VerticalFit::Pad
+                } else {
+                    olal_process::VerticalFit::Crop
+                }),
+            }),
+        ),
+        Commands::Flashcards {
+            command,
+            item_id,
+            count,
+            model,
+        } => match (command, item_id) {
+            (Some(FlashcardCommands::List { item }), _) => commands::flashcards::list(item),
+            (Some(FlashcardCommands::Export { output, item }), _) => commands::flashcards::export(output, item),
+            (None, Some(item_id)) => commands::flashcards::generate(&item_id, count, model),
+            (None, None) => unreachable!("clap requires an item or a subcommand"),
+        },
+        Commands::Translate {
+            item_id,
+            to,
+            model,
+            embed,
+        } => commands::translate::run(&item_id, &to, model, embed),
+        Commands::Dedupe { distance, merge, link } => {
+            let resolution = match (merge, link) {
+                (true, _) => commands::dedupe::Resolution::Merge,
+                (_, true) => commands::dedupe::Resolution::Link,
+                _ => commands::dedupe::Resolution::Ask,
+            };
+            commands::dedupe::run(distance, resolution)
+        }
+        Commands::Maintain { dry_run } => commands::maintain::run(dry_run),
+        Commands::Retention { dry_run } => commands::retention::run(dry_run),
+        Commands::Review { command, limit } => match command {
+            None => commands::recall::run(limit),
+            Some(ReviewCommands::Add { id }) => commands::recall::add(&id),
+            Some(ReviewCommands::Remove { id }) => commands::recall::remove(&id),
+            Some(ReviewCommands::List { limit }) => commands::recall::list(limit),
+        },
+        Commands::ReviewAi {
+            accept,
+            reject,
+            accept_all,
+            reject_all,
+            kind,
+            below,
+            above,
+        } => commands::review::run(commands::review::ReviewOptions {
+            accept,
+            reject,
+            accept_all,
+            reject_all,
+            kind,
+            below,
+            above,
+        }),
         Commands::Shell => commands::shell::run(),
+        Commands::Serve { host, port, ui } => commands::serve::run(&host, port, ui),
         Commands::Watch(cmd) => match cmd {
             WatchCommands::Start { daemon } => commands::watch::run(daemon),
             WatchCommands::Stop => commands::watch::stop(),
             WatchCommands::Status => commands::watch::status(),
         },
+        Commands::Worker(cmd) => match cmd {
+            WorkerCommands::Start { daemon } => commands::worker::start(daemon),
+            WorkerCommands::Stop => commands::worker::stop(),
+            WorkerCommands::Status => commands::worker::status(),
+        },
+        #[cfg(feature = "dev")]
+        Commands::Dev(cmd) => match cmd {
+            DevCommands::Seed { items, seed, force } => commands::dev::seed(items, seed, force),
+        },
+        Commands::Schedule(cmd) => match cmd {
+            ScheduleCommands::List => commands::schedule::list(),
+            ScheduleCommands::Run { name } => commands::schedule::run(&name),
+        },
+        Commands::Secret(cmd) => match cmd {
+            SecretCommands::Set { name } => commands::secret::set(&name),
+            SecretCommands::Delete { name } => commands::secret::delete(&name),
+            SecretCommands::List => commands::secret::list(),
+        },
+        Commands::Vault(cmd) => match cmd {
+            VaultCommands::List => commands::vault::list(),
+            VaultCommands::Add { name, data_dir } => commands::vault::add(&name, &data_dir),
+        },
         Commands::Youtube {
             item_id,
             style,
@@ -427,25 +1630,36 @@ fn main() {
             description_only,
             chapters_only,
             tags_only,
+            thumbnail,
+            output,
         } => commands::youtube::run(
             &item_id,
             style,
             model,
-            title_only,
-            description_only,
-            chapters_only,
-            tags_only,
+            commands::youtube::OutputMode {
+                title_only,
+                description_only,
+                chapters_only,
+                tags_only,
+                thumbnail,
+            },
+            output.as_deref(),
         ),
         Commands::Digest {
             period,
             since,
             output,
             model,
-        } => commands::digest::run(&period, since, output, model),
+            feedback,
+            compare,
+            email,
+        } => commands::digest::run(&period, since, output, model, feedback, compare, email),
     };
+    if record_usage {
+        commands::usage::record(&command_name, started_at, timer.elapsed(), result.is_ok());
+    }
 
     if let Err(e) = result {
-        eprintln!("{} {}", "Error:".red().bold(), e);
-        std::process::exit(1);
+        report_error(&e);
     }
 }
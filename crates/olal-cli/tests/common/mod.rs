@@ -0,0 +1,583 @@
+//! Shared harness for the end-to-end tests.
+//!
+//! [`FakeOllama`] is a minimal HTTP server implementing the Ollama endpoints
+//! olal uses (`/api/tags`, `/api/show`, `/api/embeddings`, `/api/generate` with
+//! and without streaming), with canned, deterministic responses. [`Olal`] runs the real
+//! `olal` binary against a throwaway database and config pointing at it.
+//! [`FakeSmtp`] accepts mail for the email tests.
+
+#![allow(dead_code)]
+
+use olal_config::Config;
+use olal_db::Database;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::TempDir;
+
+pub const CHAT_MODEL: &str = "llama3";
+pub const EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+/// Canned generation responses, chosen by the prompt.
+pub const SUMMARY: &str = "A short note about Rust ownership and borrowing.";
+pub const TAGS: &str = "rust | 0.95\nmemory safety | 0.9";
+pub const ENTITIES: &str = "PERSON: Graydon Hoare\nTOPIC: Ownership";
+pub const DIGEST: &str = "**Overview** - This period was all about Rust ownership.";
+/// Cites the first source only.
+pub const ANSWER: &str = "Each value in Rust has a single owner [1].";
+/// Ranks the second candidate above the first.
+pub const RERANK: &str = "[1] 2\n[2] 9";
+pub const SUB_QUERIES: &str = "1. Rust value ownership\n2. Sourdough starter flour";
+pub const IMAGE_DESCRIPTION: &str = "Caption: A whiteboard sketch of the ingestion pipeline.\n\
+Objects: whiteboard, marker, diagram";
+pub const FLASHCARDS: &str = "Q: Who owns a value in Rust?\nA: Exactly one owner.\n\n\
+Q: When is a value dropped?\nA: When its owner goes out of scope.";
+
+pub const TRANSLATION: &str = "Cada valor en Rust tiene un único dueño.";
+
+/// Dimensions of the fake embeddings.
+const EMBEDDING_DIMS: usize = 32;
+
+/// Context length every fake model reports.
+pub const CONTEXT_LENGTH: usize = 4096;
+
+/// A request received by the fake server.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    /// Headers, with lowercase names.
+    pub headers: Vec<(String, String)>,
+    pub body: Value,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(n, _)| n == name).map(|(_, v)| v.as_str())
+    }
+}
+
+/// A fake Ollama server on a random local port, stopped on drop.
+pub struct FakeOllama {
+    pub host: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+    failures: Arc<Mutex<HashMap<String, usize>>>,
+    stop: Arc<AtomicBool>,
+    addr: std::net::SocketAddr,
+}
+
+impl FakeOllama {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let failures = Arc::new(Mutex::new(HashMap::new()));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let log = Arc::clone(&requests);
+        let failing = Arc::clone(&failures);
+        let stopped = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                if stopped.load(Ordering::Acquire) {
+                    break;
+                }
+                let Ok(stream) = stream else { continue };
+                let log = Arc::clone(&log);
+                let failing = Arc::clone(&failing);
+                std::thread::spawn(move || {
+                    let _ = serve(stream, &log, &failing);
+                });
+            }
+        });
+
+        Self {
+            host: format!("http://{}", addr),
+            requests,
+            failures,
+            stop,
+            addr,
+        }
+    }
+
+    /// Answer the next `n` requests to `path` with 503, like Ollama while a
+    /// model loads.
+    pub fn fail_next(&self, path: &str, n: usize) {
+        self.failures.lock().unwrap().insert(path.to_string(), n);
+    }
+
+    /// Requests received so far.
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// Requests received for `path`.
+    pub fn requests_to(&self, path: &str) -> Vec<Request> {
+        self.requests().into_iter().filter(|r| r.path == path).collect()
+    }
+}
+
+impl Drop for FakeOllama {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        // Wake the accept loop so it sees the stop flag
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/// Handle one connection: a single request, then close.
+fn serve(stream: TcpStream, log: &Mutex<Vec<Request>>, failures: &Mutex<HashMap<String, usize>>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let mut path = parts.next().unwrap_or_default().to_string();
+    // Requests through a proxy carry the full URL
+    if let Some(rest) = path.strip_prefix("http://") {
+        path = rest.find('/').map_or("/".to_string(), |i| rest[i..].to_string());
+    }
+
+    let mut content_length = 0;
+    let mut headers = Vec::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let (name, value) = (name.trim().to_lowercase(), value.trim().to_string());
+            if name == "content-length" {
+                content_length = value.parse().unwrap_or(0);
+            }
+            headers.push((name, value));
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+
+    log.lock().unwrap().push(Request {
+        method: method.clone(),
+        path: path.clone(),
+        headers,
+        body: body.clone(),
+    });
+
+    let mut stream = stream;
+    if let Some(n) = failures.lock().unwrap().get_mut(&path).filter(|n| **n > 0) {
+        *n -= 1;
+        return respond(&mut stream, 503, &json!({ "error": "model is loading" }));
+    }
+    match (method.as_str(), path.as_str()) {
+        ("GET", "/api/tags") => respond(
+            &mut stream,
+            200,
+            &json!({
+                "models": [
+                    { "name": format!("{}:latest", CHAT_MODEL) },
+                    { "name": format!("{}:latest", EMBEDDING_MODEL) },
+                ]
+            }),
+        ),
+        ("POST", "/api/show") => {
+            let model = body["model"].as_str().unwrap_or_default();
+            respond(&mut stream, 200, &show(model))
+        }
+        ("POST", "/api/embeddings") => {
+            let prompt = body["prompt"].as_str().unwrap_or_default();
+            respond(&mut stream, 200, &json!({ "embedding": embedding(prompt) }))
+        }
+        ("POST", "/api/generate") => {
+            let prompt = body["prompt"].as_str().unwrap_or_default();
+            let model = body["model"].as_str().unwrap_or(CHAT_MODEL);
+            let text = completion(prompt);
+            if body["stream"].as_bool().unwrap_or(false) {
+                stream_words(&mut stream, model, text)
+            } else {
+                respond(
+                    &mut stream,
+                    200,
+                    &json!({ "model": model, "response": text, "done": true }),
+                )
+            }
+        }
+        // Webhook receiver, for `[webhooks]` tests
+        ("POST", "/hooks") => respond(&mut stream, 200, &json!({})),
+        _ => respond(&mut stream, 404, &json!({ "error": "not found" })),
+    }
+}
+
+fn respond(stream: &mut TcpStream, status: u16, body: &Value) -> std::io::Result<()> {
+    let body = body.to_string();
+    write!(
+        stream,
+        "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+/// Stream `text` word by word as newline-delimited JSON, like Ollama does.
+fn stream_words(stream: &mut TcpStream, model: &str, text: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: application/x-ndjson\r\nConnection: close\r\n\r\n"
+    )?;
+    for word in text.split_inclusive(' ') {
+        writeln!(stream, "{}", json!({ "model": model, "response": word, "done": false }))?;
+        stream.flush()?;
+    }
+    writeln!(stream, "{}", json!({ "model": model, "response": "", "done": true }))?;
+    stream.flush()
+}
+
+/// Model details: the embedding model only embeds, everything else chats.
+fn show(model: &str) -> Value {
+    if model.starts_with(EMBEDDING_MODEL) {
+        json!({
+            "details": { "family": "nomic-bert", "quantization_level": "F16" },
+            "model_info": { "nomic-bert.context_length": 2048 },
+            "capabilities": ["embedding"],
+        })
+    } else {
+        json!({
+            "details": { "family": "llama", "parameter_size": "8.0B", "quantization_level": "Q4_K_M" },
+            "model_info": { "llama.context_length": CONTEXT_LENGTH },
+            "capabilities": ["completion"],
+        })
+    }
+}
+
+/// The canned response for a generation prompt.
+fn completion(prompt: &str) -> &'static str {
+    if prompt.starts_with("Summarize") {
+        SUMMARY
+    } else if prompt.starts_with("Rate how relevant") {
+        RERANK
+    } else if prompt.starts_with("Rewrite the question") {
+        SUB_QUERIES
+    } else if prompt.contains("suggest 3-5 relevant tags") {
+        TAGS
+    } else if prompt.starts_with("Extract the named") {
+        ENTITIES
+    } else if prompt.starts_with("Create") && prompt.contains("flashcards") {
+        FLASHCARDS
+    } else if prompt.starts_with("Describe this image") {
+        IMAGE_DESCRIPTION
+    } else if prompt.starts_with("Translate") {
+        TRANSLATION
+    } else if prompt.contains("digest") {
+        DIGEST
+    } else {
+        ANSWER
+    }
+}
+
+/// A fake SMTP server on a random local port that keeps the messages it
+/// receives. Plain text only (`security = "none"`).
+pub struct FakeSmtp {
+    pub port: u16,
+    messages: Arc<Mutex<Vec<String>>>,
+}
+
+impl FakeSmtp {
+    pub fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+
+        let log = Arc::clone(&messages);
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let log = Arc::clone(&log);
+                std::thread::spawn(move || {
+                    let _ = serve_smtp(stream, &log);
+                });
+            }
+        });
+
+        Self { port, messages }
+    }
+
+    /// Raw messages (headers and body) received so far.
+    pub fn messages(&self) -> Vec<String> {
+        self.messages.lock().unwrap().clone()
+    }
+}
+
+/// One SMTP session: accept every command, collect DATA until the lone dot.
+fn serve_smtp(stream: TcpStream, log: &Mutex<Vec<String>>) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+    write!(stream, "220 localhost ESMTP fake\r\n")?;
+
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let command = line.trim_end().to_ascii_uppercase();
+        if command.starts_with("EHLO") || command.starts_with("HELO") {
+            write!(stream, "250-localhost\r\n250 8BITMIME\r\n")?;
+        } else if command == "DATA" {
+            write!(stream, "354 go ahead\r\n")?;
+            let mut message = String::new();
+            loop {
+                line.clear();
+                if reader.read_line(&mut line)? == 0 || line == ".\r\n" {
+                    break;
+                }
+                message.push_str(&line);
+            }
+            log.lock().unwrap().push(message);
+            write!(stream, "250 queued\r\n")?;
+        } else if command == "QUIT" {
+            write!(stream, "221 bye\r\n")?;
+            return Ok(());
+        } else {
+            write!(stream, "250 ok\r\n")?;
+        }
+        stream.flush()?;
+    }
+}
+
+/// A deterministic bag-of-words embedding, so texts sharing words are similar.
+pub fn embedding(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0f32; EMBEDDING_DIMS];
+    for word in text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+    {
+        // FNV-1a
+        let hash = word
+            .to_lowercase()
+            .bytes()
+            .fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+        vector[(hash % EMBEDDING_DIMS as u64) as usize] += 1.0;
+    }
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+    vector
+}
+
+/// A throwaway olal installation talking to a [`FakeOllama`].
+pub struct Olal {
+    pub dir: TempDir,
+    pub ollama: FakeOllama,
+}
+
+impl Olal {
+    /// Initialize a fresh knowledge base. `configure` adjusts the config
+    /// (all AI processing is off by default).
+    pub fn new(configure: impl FnOnce(&mut Config)) -> Self {
+        let olal = Self {
+            dir: TempDir::new().unwrap(),
+            ollama: FakeOllama::start(),
+        };
+
+        let mut config = Config::default();
+        config.ollama.host = olal.ollama.host.clone();
+        config.ollama.model = CHAT_MODEL.to_string();
+        config.ollama.embedding_model = EMBEDDING_MODEL.to_string();
+        config.ollama.timeout_seconds = 10;
+        config.processing.generate_summary = false;
+        config.processing.auto_tag = false;
+        config.processing.extract_entities = false;
+        config.processing.auto_link = false;
+        config.processing.auto_embed = false;
+        config.processing.describe_images = false;
+        configure(&mut config);
+        config.save_to(&olal.config_path()).unwrap();
+
+        olal.success(&["init"]);
+        olal
+    }
+
+    pub fn db_path(&self) -> PathBuf {
+        self.dir.path().join("data").join("olal.db")
+    }
+
+    pub fn config_path(&self) -> PathBuf {
+        self.dir.path().join("config").join("config.toml")
+    }
+
+    /// Open the test database directly, for assertions.
+    pub fn db(&self) -> Database {
+        Database::open(self.db_path()).unwrap()
+    }
+
+    /// Write a file under the test directory.
+    pub fn write_file(&self, name: &str, content: &str) -> PathBuf {
+        let path = self.dir.path().join("files").join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    /// Run `olal` with `args`.
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.run_with_env(&[], args)
+    }
+
+    /// Run `olal` with `args` and extra environment variables.
+    pub fn run_with_env(&self, env: &[(&str, &str)], args: &[&str]) -> Output {
+        self.command(args).envs(env.iter().copied()).output().unwrap()
+    }
+
+    /// Run `olal` with `args`, writing `input` to its stdin, asserting
+    /// success. Returns stdout.
+    pub fn success_with_input(&self, args: &[&str], input: &str) -> String {
+        let mut child = self
+            .command(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        assert!(
+            output.status.success(),
+            "olal {} failed\nstdout:\n{}\nstderr:\n{}",
+            args.join(" "),
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        stdout
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_olal"));
+        command
+            .arg("--db")
+            .arg(self.db_path())
+            .arg("--config")
+            .arg(self.config_path())
+            .args(args)
+            .env("NO_COLOR", "1")
+            .env_remove(olal_config::DB_ENV)
+            .env_remove(olal_config::CONFIG_ENV)
+            .env_remove(olal_config::VAULT_ENV)
+            .current_dir(self.dir.path());
+        command
+    }
+
+    /// Run `olal` with `args` in the vault `name` (`OLAL_VAULT`), so the
+    /// database comes from the vault instead of `--db`.
+    pub fn run_in_vault(&self, name: &str, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_olal"))
+            .arg("--config")
+            .arg(self.config_path())
+            .args(args)
+            .env("NO_COLOR", "1")
+            .env(olal_config::VAULT_ENV, name)
+            .env_remove(olal_config::DB_ENV)
+            .env_remove(olal_config::CONFIG_ENV)
+            .current_dir(self.dir.path())
+            .output()
+            .unwrap()
+    }
+
+    /// Run `olal` with `args`, asserting success. Returns stdout.
+    pub fn success(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        assert!(
+            output.status.success(),
+            "olal {} failed\nstdout:\n{}\nstderr:\n{}",
+            args.join(" "),
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        stdout
+    }
+
+    /// Start `olal serve` with `args` on a free port.
+    pub fn serve(&self, args: &[&str]) -> Server {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_olal"))
+            .arg("--db")
+            .arg(self.db_path())
+            .arg("--config")
+            .arg(self.config_path())
+            .args(["serve", "--port", "0"])
+            .args(args)
+            .env("NO_COLOR", "1")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap();
+
+        // "Serving on http://127.0.0.1:PORT", after any log lines
+        let mut lines = BufReader::new(child.stdout.take().unwrap()).lines();
+        let line = lines
+            .by_ref()
+            .map(Result::unwrap)
+            .find(|line| line.starts_with("Serving on"))
+            .expect("olal serve exited before listening");
+        let addr = line.rsplit("http://").next().unwrap().to_string();
+        // Keep reading, so the server never writes to a closed pipe
+        std::thread::spawn(move || lines.for_each(drop));
+        Server { addr, child }
+    }
+
+    /// Ingest a file, asserting success.
+    pub fn ingest(&self, path: &Path) -> String {
+        self.success(&["ingest", path.to_str().unwrap()])
+    }
+}
+
+/// A running `olal serve`, killed on drop.
+pub struct Server {
+    pub addr: String,
+    child: Child,
+}
+
+impl Server {
+    /// Make a request, returning the status code and body.
+    pub fn request(&self, method: &str, path: &str, body: Option<&Value>) -> (u16, String) {
+        let body = body.map(|b| b.to_string()).unwrap_or_default();
+        let mut stream = TcpStream::connect(&self.addr).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            method,
+            path,
+            self.addr,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response.split_whitespace().nth(1).unwrap().parse().unwrap();
+        let body = response.split_once("\r\n\r\n").map(|(_, b)| b.to_string()).unwrap_or_default();
+        (status, body)
+    }
+
+    pub fn get(&self, path: &str) -> (u16, String) {
+        self.request("GET", path, None)
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
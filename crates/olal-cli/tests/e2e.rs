@@ -0,0 +1,1311 @@
+//! End-to-end tests: the `olal` binary against a fake Ollama server.
+
+mod common;
+
+use common::{FakeSmtp, Olal, ANSWER, CONTEXT_LENGTH, DIGEST, EMBEDDING_MODEL, SUMMARY, TRANSLATION};
+
+const OWNERSHIP_NOTE: &str = "# Rust ownership\n\n\
+Graydon Hoare started Rust. Ownership means each value has a single owner, \
+and the value is dropped when its owner goes out of scope. Borrowing lets \
+code use a value without taking ownership of it.\n";
+
+const COOKING_NOTE: &str = "# Sourdough\n\n\
+A sourdough starter needs flour and water fed daily. Bake the loaf in a hot \
+dutch oven for a crisp crust and an open crumb.\n";
+
+#[test]
+fn test_ingest_enriches_item() {
+    let olal = Olal::new(|config| {
+        config.processing.generate_summary = true;
+        config.processing.auto_tag = true;
+        config.processing.extract_entities = true;
+    });
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    let db = olal.db();
+    let items = db.list_items(None, None).unwrap();
+    assert_eq!(items.len(), 1);
+    let item = &items[0];
+    assert_eq!(item.summary.as_deref(), Some(SUMMARY));
+
+    let tags: Vec<String> = db
+        .get_item_tags(&item.id)
+        .unwrap()
+        .into_iter()
+        .map(|t| t.name)
+        .collect();
+    assert!(tags.contains(&"rust".to_string()), "tags: {:?}", tags);
+    assert!(tags.contains(&"memory safety".to_string()), "tags: {:?}", tags);
+
+    let entities: Vec<String> = db
+        .get_item_entities(&item.id)
+        .unwrap()
+        .into_iter()
+        .map(|e| e.name)
+        .collect();
+    assert!(entities.contains(&"Graydon Hoare".to_string()), "entities: {:?}", entities);
+
+    assert_eq!(olal.ollama.requests_to("/api/generate").len(), 3);
+}
+
+#[test]
+fn test_image_described_by_vision_model() {
+    let olal = Olal::new(|config| {
+        config.processing.ocr_enabled = false;
+        config.processing.describe_images = true;
+        config.ollama.vision_model = common::CHAT_MODEL.to_string();
+    });
+    olal.ingest(&olal.write_file("sketch.png", "PNG fake pixels"));
+
+    let generate = olal.ollama.requests_to("/api/generate");
+    assert_eq!(generate.len(), 1);
+    assert!(generate[0].body["images"][0].as_str().is_some_and(|i| !i.is_empty()));
+
+    let db = olal.db();
+    let item = &db.list_items(None, None).unwrap()[0];
+    assert_eq!(item.metadata["caption"], "A whiteboard sketch of the ingestion pipeline.");
+    assert_eq!(item.metadata["objects"][2], "diagram");
+    let chunks = db.get_chunks_by_item(&item.id).unwrap();
+    assert!(chunks[0].content.contains("Objects: whiteboard, marker, diagram"), "{:?}", chunks);
+
+    let out = olal.success(&["search", "whiteboard"]);
+    assert!(out.contains("sketch"), "{}", out);
+}
+
+#[test]
+fn test_thumbnail_shown_and_deleted_with_item() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let db = olal.db();
+    let mut item = db.list_items(None, None).unwrap().remove(0);
+
+    let thumbnail = olal.db_path().parent().unwrap().join("thumbnails").join(format!("{}.jpg", item.id));
+    std::fs::create_dir_all(thumbnail.parent().unwrap()).unwrap();
+    std::fs::write(&thumbnail, "jpeg").unwrap();
+    item.metadata["thumbnail"] = thumbnail.to_string_lossy().into();
+    db.update_item(&item).unwrap();
+
+    let out = olal.success(&["show", &item.id[..8]]);
+    assert!(out.contains(&format!("Thumbnail: {}", thumbnail.display())), "{}", out);
+
+    olal.success(&["delete", &item.id[..8]]);
+    assert!(!thumbnail.exists());
+}
+
+#[test]
+fn test_embed_then_ask() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+
+    let db = olal.db();
+    assert_eq!(db.embedding_stats().unwrap().0, 0);
+
+    olal.success(&["embed", "--all"]);
+    let (embedded, total) = db.embedding_stats().unwrap();
+    assert!(total > 0);
+    assert_eq!(embedded, total);
+
+    let out = olal.success(&["ask", "What does ownership mean for a value in Rust?"]);
+    assert!(out.contains(ANSWER), "{}", out);
+    assert!(out.contains("Sources:"), "sources missing: {}", out);
+
+    // The retrieved context went into the prompt
+    let generate = olal.ollama.requests_to("/api/generate");
+    let prompt = generate.last().unwrap().body["prompt"].as_str().unwrap().to_string();
+    assert!(prompt.contains("single owner"));
+    assert!(!generate.last().unwrap().body["stream"].as_bool().unwrap());
+    let system = generate.last().unwrap().body["system"].as_str().unwrap().to_string();
+    assert!(system.contains("square brackets"), "{}", system);
+
+    // Both notes were context, but only the cited one is listed
+    let source = |n: usize| {
+        let prefix = format!("[{}] From: ", n);
+        prompt.lines().find_map(|line| line.strip_prefix(&prefix)).unwrap().to_string()
+    };
+    let (cited, uncited) = (source(1), source(2));
+    assert!(out.contains(&format!("[1] {}", cited)), "{}", out);
+    assert!(!out.contains(&uncited), "{}", out);
+
+    let out = olal.success(&["ask", "--stream", "What does ownership mean for a value in Rust?"]);
+    assert!(out.contains(&format!("[1] {}", cited)) && !out.contains(&uncited), "{}", out);
+}
+
+#[test]
+fn test_ask_streams_answer() {
+    let olal = Olal::new(|config| config.processing.auto_embed = true);
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    // Auto-embed covered every chunk during ingest
+    let (embedded, total) = olal.db().embedding_stats().unwrap();
+    assert!(total > 0);
+    assert_eq!(embedded, total);
+
+    let out = olal.success(&["ask", "--stream", "Who owns a value in Rust?"]);
+    assert!(out.contains(ANSWER), "{}", out);
+
+    let generate = olal.ollama.requests_to("/api/generate");
+    assert!(generate.last().unwrap().body["stream"].as_bool().unwrap());
+}
+
+#[test]
+fn test_ask_fits_context_window() {
+    let olal = Olal::new(|config| {
+        config.processing.chunk_size = 2000;
+        config.ollama.context_tokens = 1400;
+    });
+    let sentences: Vec<String> = (1..=100)
+        .map(|i| format!("Fact number {} about Rust ownership.", i))
+        .collect();
+    olal.ingest(&olal.write_file("facts.md", &sentences.join(" ")));
+    olal.success(&["embed", "--all"]);
+
+    olal.success(&["ask", "Who owns a value in Rust?"]);
+    let request = olal.ollama.requests_to("/api/generate").pop().unwrap();
+    assert_eq!(request.body["options"]["num_ctx"], 1400);
+
+    // The chunk was cut at a sentence to fit
+    let prompt = request.body["prompt"].as_str().unwrap();
+    assert!(prompt.contains("Fact number 1 about"), "{}", prompt);
+    assert!(!prompt.contains("Fact number 100 about"), "{}", prompt);
+    assert!(prompt.contains("ownership.\n\n─"), "{}", prompt);
+
+    // A model's own context length caps the window
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.success(&["embed", "--all"]);
+    olal.success(&["ask", "Who owns a value in Rust?"]);
+    let request = olal.ollama.requests_to("/api/generate").pop().unwrap();
+    assert_eq!(request.body["options"]["num_ctx"], CONTEXT_LENGTH);
+}
+
+#[test]
+fn test_model_capabilities() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.success(&["embed", "--all"]);
+    let shown = olal.ollama.requests_to("/api/show").len();
+
+    let out = olal.success(&["ask", "Who owns a value in Rust?"]);
+    assert!(!out.contains("Warning:"), "{}", out);
+    // Looked up once per model, then cached
+    assert_eq!(olal.ollama.requests_to("/api/show").len(), shown + 2);
+    // The answer length is capped at a quarter of the window
+    let request = olal.ollama.requests_to("/api/generate").pop().unwrap();
+    assert_eq!(request.body["options"]["num_predict"], CONTEXT_LENGTH / 4);
+
+    let out = olal.success(&["ask", "--model", EMBEDDING_MODEL, "Who owns a value in Rust?"]);
+    assert!(
+        out.contains(&format!("Warning: '{}' is an embedding model", EMBEDDING_MODEL)),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn test_digest() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    let out = olal.success(&["digest", "--period", "week"]);
+    assert!(out.contains(DIGEST), "{}", out);
+
+    let output = olal.dir.path().join("digest.md");
+    olal.success(&["digest", "--output", output.to_str().unwrap()]);
+    assert!(std::fs::read_to_string(output).unwrap().contains(DIGEST));
+}
+
+#[test]
+fn test_digest_compare() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let item = olal.db().list_items(None, None).unwrap().remove(0);
+    olal.success(&["tag", &item.id[..8], "rust"]);
+
+    // Nothing stored yet: compared against the week before, which was empty
+    let out = olal.success(&["digest", "--period", "week", "--compare", "--no-pager"]);
+    assert!(out.contains("Compared with the previous period"), "{}", out);
+    assert!(out.contains("Items:** 1 (was 0, +1)"), "{}", out);
+    assert!(out.contains("New topics:** rust"), "{}", out);
+
+    // Last week's digest, as a scheduled run would have stored it
+    let now = chrono::Utc::now();
+    let mut stats = olal_core::DigestStats { item_count: 4, ..Default::default() };
+    stats.topics.insert("python".to_string(), 4);
+    let week = chrono::Duration::weeks(1);
+    let last_week = olal_core::DigestRecord::new("weekly", now - week * 2, now - week, "Python", stats);
+    olal.db().save_digest(&last_week).unwrap();
+
+    let out = olal.success(&["digest", "--period", "week", "--compare", "--no-pager"]);
+    assert!(out.contains("Previous period: the digest of"), "{}", out);
+    assert!(out.contains("Items:** 1 (was 4, -3)"), "{}", out);
+    assert!(out.contains("Dropped threads:** python"), "{}", out);
+    assert_eq!(olal.db().list_digests(10).unwrap().len(), 3);
+}
+
+#[test]
+fn test_ollama_unavailable() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    // Point the config at a port nothing listens on
+    let addr = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap()
+    };
+    olal.success(&["config", "set", "ollama.host", &format!("http://{}", addr)]);
+
+    let output = olal.run(&["ask", "What is ownership?"]);
+    assert_eq!(output.status.code(), Some(10));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Error[ollama_unavailable]: Ollama is not running"), "{}", stderr);
+    assert!(stderr.contains("Hint: Start it with 'ollama serve'"), "{}", stderr);
+
+    let output = olal.run(&["show", "no-such-item"]);
+    assert_eq!(output.status.code(), Some(4));
+}
+
+#[test]
+fn test_digest_email() {
+    let smtp = FakeSmtp::start();
+    let olal = Olal::new(|config| {
+        let mail = &mut config.digest.smtp;
+        mail.host = "127.0.0.1".to_string();
+        mail.port = smtp.port;
+        mail.security = olal_config::SmtpSecurity::None;
+        mail.from = "Olal <olal@example.com>".to_string();
+        mail.to = vec!["me@example.com".to_string()];
+    });
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    let out = olal.success(&["digest", "--period", "week", "--email", "--no-pager"]);
+    assert!(out.contains("Emailed to: me@example.com"), "{}", out);
+
+    let messages = smtp.messages();
+    assert_eq!(messages.len(), 1);
+    let message = &messages[0];
+    assert!(message.contains("Subject: Weekly digest"), "{}", message);
+    assert!(message.contains("text/html"), "{}", message);
+    assert!(message.contains("<strong>Overview</strong>"), "{}", message);
+}
+
+#[test]
+fn test_scheduled_digest() {
+    let olal = Olal::new(|config| {
+        config.schedule.jobs.push(olal_config::ScheduledJob {
+            task: olal_config::ScheduledTask::Digest,
+            name: Some("morning".to_string()),
+            at: Some("00:00".to_string()),
+            every_minutes: None,
+            days: Vec::new(),
+            period: Some("week".to_string()),
+            email: false,
+            limit: None,
+        });
+    });
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    let out = olal.success(&["schedule", "list"]);
+    assert!(out.contains("morning"), "{}", out);
+    assert!(out.contains("due now"), "{}", out);
+
+    olal.success(&["schedule", "run", "morning"]);
+    let run = olal.db().get_schedule_run("morning").unwrap().unwrap();
+    assert!(run.error.is_none());
+
+    let digests = olal.dir.path().join("data").join("digests");
+    let digest = std::fs::read_dir(&digests).unwrap().next().unwrap().unwrap().path();
+    assert!(std::fs::read_to_string(digest).unwrap().contains(DIGEST));
+
+    let out = olal.success(&["schedule", "list"]);
+    assert!(!out.contains("due now"), "{}", out);
+}
+
+#[test]
+fn test_webhooks() {
+    let olal = Olal::new(|config| {
+        let url = format!("{}/hooks", config.ollama.host);
+        config.webhooks.endpoints.push(olal_config::WebhookEndpoint {
+            url,
+            events: vec!["item.processed".to_string(), "digest.generated".to_string()],
+        });
+    });
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.success(&["digest", "--period", "week"]);
+
+    let hooks = olal.ollama.requests_to("/hooks");
+    let events: Vec<&str> = hooks.iter().map(|r| r.body["event"].as_str().unwrap()).collect();
+    assert_eq!(events, vec!["item.processed", "digest.generated"]);
+    assert_eq!(hooks[0].body["data"]["title"], "Rust ownership");
+    assert_eq!(hooks[1].body["data"]["digest"], DIGEST);
+}
+
+#[test]
+fn test_flashcards_export() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let item = olal.db().list_items(None, None).unwrap().remove(0);
+
+    let out = olal.success(&["flashcards", &item.id, "--count", "5"]);
+    assert!(out.contains("Saved 2 flashcards"), "{}", out);
+    assert_eq!(olal.db().list_flashcards(Some(&item.id)).unwrap().len(), 2);
+
+    let deck = olal.dir.path().join("deck.txt");
+    olal.success(&["flashcards", "export", "--output", deck.to_str().unwrap()]);
+    let tsv = std::fs::read_to_string(deck).unwrap();
+    assert!(tsv.starts_with("#separator:tab"), "{}", tsv);
+    assert!(
+        tsv.contains("Who owns a value in Rust?\tExactly one owner.\tolal rust_ownership"),
+        "{}",
+        tsv
+    );
+}
+
+#[test]
+fn test_translate_creates_linked_item() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let original = olal.db().list_items(None, None).unwrap().remove(0);
+
+    let out = olal.success(&["translate", &original.id, "--to", "ES", "--embed"]);
+    assert!(out.contains("Spanish"), "{}", out);
+    assert!(out.contains("Embedded"), "{}", out);
+
+    let db = olal.db();
+    let links = db.get_item_links(&original.id).unwrap();
+    assert_eq!(links.len(), 1);
+    assert_eq!(links[0].link_type, olal_core::LinkType::Translates);
+    let translation = db.get_item(&links[0].source_id).unwrap();
+    assert_eq!(translation.title, "Rust ownership (es)");
+    assert_eq!(translation.metadata["language"], "es");
+    let chunks = db.get_chunks_by_item(&translation.id).unwrap();
+    assert!(chunks.iter().all(|c| c.content == TRANSLATION));
+    assert!(db.get_embedding(&chunks[0].id).unwrap().is_some());
+
+    // Translating again replaces the earlier translation
+    let out = olal.success(&["translate", &original.id, "--to", "es"]);
+    assert!(out.contains("replaced"), "{}", out);
+    assert_eq!(db.get_item_links(&original.id).unwrap().len(), 1);
+    assert!(db.get_item(&translation.id).is_err());
+
+    let out = olal.run(&["translate", &links[0].source_id, "--to", "en"]);
+    assert!(!out.status.success());
+}
+
+#[test]
+fn test_stats_me() {
+    let olal = Olal::new(|_| {});
+    olal.success(&["search", "ownership"]);
+    olal.success(&["search", "borrowing"]);
+    olal.success(&["queue", "list"]);
+    // Prompt segments aren't recorded
+    olal.success(&["status", "--short"]);
+
+    let commands: Vec<String> = olal
+        .db()
+        .command_usage_since(chrono::Utc::now() - chrono::Duration::hours(1))
+        .unwrap()
+        .into_iter()
+        .map(|u| u.command)
+        .collect();
+    assert!(commands.ends_with(&["search".into(), "search".into(), "queue list".into()]), "{:?}", commands);
+
+    let out = olal.success(&["stats", "--me"]);
+    assert!(out.contains("Most Used"), "{}", out);
+    assert!(out.contains("Searches: 2  Questions: 0"), "{}", out);
+}
+
+#[test]
+fn test_serve_ui() {
+    let olal = Olal::new(|config| config.processing.auto_embed = true);
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let item = olal.db().list_items(None, None).unwrap().remove(0);
+    let server = olal.serve(&["--ui"]);
+
+    let (status, body) = server.get("/");
+    assert_eq!(status, 200);
+    assert!(body.contains("<title>olal</title>"), "{}", body);
+    assert_eq!(server.get("/app.js").0, 200);
+
+    let (status, body) = server.get("/api/items");
+    assert_eq!(status, 200);
+    assert!(body.contains("Rust ownership"), "{}", body);
+
+    let (status, body) = server.get("/api/search?q=ownership");
+    assert_eq!(status, 200);
+    assert!(body.contains(&item.id), "{}", body);
+
+    let (status, body) = server.get(&format!("/api/items/{}", item.id));
+    assert_eq!(status, 200);
+    assert!(body.contains("\"chunks\""), "{}", body);
+    assert!(body.contains("\"media\":false"), "{}", body);
+    assert_eq!(server.get("/api/items/nope").0, 404);
+
+    let question = serde_json::json!({ "question": "What is ownership in Rust?" });
+    let (status, body) = server.request("POST", "/api/ask", Some(&question));
+    assert_eq!(status, 200);
+    assert!(body.contains("\"sources\""), "{}", body);
+    assert!(body.contains("\"done\":true"), "{}", body);
+    assert!(body.contains("owner"), "{}", body);
+}
+
+#[test]
+fn test_stats_trends() {
+    let olal = Olal::new(|config| config.processing.auto_embed = true);
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let item = olal.db().list_items(None, None).unwrap().remove(0);
+    olal.success(&["tag", &item.id[..8], "rust"]);
+
+    let out = olal.success(&["stats"]);
+    assert!(out.contains("Last 30 days"), "{}", out);
+    assert!(out.contains("1 items"), "{}", out);
+    assert!(out.contains("(100%)"), "{}", out);
+    assert!(out.contains("Top Tags"), "{}", out);
+    assert!(out.contains("Rust ownership"), "{}", out);
+}
+
+#[test]
+fn test_vaults() {
+    let olal = Olal::new(|_| {});
+    let work_dir = olal.dir.path().join("work");
+    olal.success(&["vault", "add", "work", work_dir.to_str().unwrap()]);
+
+    let status = |output: std::process::Output| {
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    status(olal.run_in_vault("work", &["init"]));
+    let note = olal.write_file("ownership.md", OWNERSHIP_NOTE);
+    status(olal.run_in_vault("work", &["ingest", note.to_str().unwrap()]));
+
+    // The item lands in the vault's database, not the default one
+    let work = olal_db::Database::open(work_dir.join("olal.db")).unwrap();
+    assert_eq!(work.list_items(None, None).unwrap().len(), 1);
+    assert!(olal.db().list_items(None, None).unwrap().is_empty());
+
+    let out = status(olal.run_in_vault("work", &["vault", "list"]));
+    assert!(out.lines().any(|l| l.starts_with("* work")), "{}", out);
+
+    let output = olal.run_in_vault("personal", &["status"]);
+    assert_eq!(output.status.code(), Some(6));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Unknown vault: personal"));
+}
+
+#[test]
+fn test_profiles_and_env_overrides() {
+    let olal = Olal::new(|_| {});
+    let mut config = std::fs::read_to_string(olal.config_path()).unwrap();
+    config.push_str("\n[profile.summaries.processing]\ngenerate_summary = true\n");
+    std::fs::write(olal.config_path(), config).unwrap();
+    let summaries = || olal.ollama.requests_to("/api/generate").len();
+
+    olal.ingest(&olal.write_file("plain.md", COOKING_NOTE));
+    assert_eq!(summaries(), 0);
+
+    let note = olal.write_file("profile.md", OWNERSHIP_NOTE);
+    olal.success(&["--profile", "summaries", "ingest", note.to_str().unwrap()]);
+    assert_eq!(summaries(), 1);
+
+    let note = olal.write_file("env.md", &OWNERSHIP_NOTE.replace("Rust ownership", "Borrowing"));
+    let output = olal.run_with_env(
+        &[("OLAL_PROCESSING__GENERATE_SUMMARY", "true")],
+        &["ingest", note.to_str().unwrap()],
+    );
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(summaries(), 2);
+
+    let output = olal.run_with_env(
+        &[("OLAL_OLLAMA__MODEL", "other"), ("OLAL_OLLAMA__API_KEY", "sk-live")],
+        &["config", "show"],
+    );
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains("OLAL_OLLAMA__MODEL=other"), "{}", out);
+    assert!(out.contains("OLAL_OLLAMA__API_KEY=********") && !out.contains("sk-live"), "{}", out);
+
+    let output = olal.run(&["--profile", "missing", "status"]);
+    assert_eq!(output.status.code(), Some(6));
+}
+
+#[test]
+fn test_secret_list() {
+    let olal = Olal::new(|config| config.digest.smtp.password = "keyring:smtp".to_string());
+    let out = olal.success(&["secret", "list"]);
+    assert!(out.contains("digest.smtp.password keyring:smtp"), "{}", out);
+}
+
+#[test]
+fn test_config_get_and_validate() {
+    let olal = Olal::new(|_| {});
+    let host = olal.success(&["config", "get", "ollama.host"]);
+    assert_eq!(host.trim(), olal.ollama.host);
+    let output = olal.run_with_env(&[("OLAL_OLLAMA__TIMEOUT_SECONDS", "42")], &["config", "get", "ollama.timeout_seconds"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "42");
+    assert!(!olal.run(&["config", "get", "ollama.nope"]).status.success());
+
+    let out = olal.success(&["config", "validate"]);
+    assert!(out.contains("Ollama reachable"), "{}", out);
+    assert!(out.contains("Configuration is valid"), "{}", out);
+
+    let mut config = std::fs::read_to_string(olal.config_path()).unwrap();
+    config = config.replacen("[ollama]\n", "[ollama]\nhots = \"typo\"\n", 1);
+    config = config.replacen("directories = []", "directories = [\"/no/such/dir\"]", 1);
+    std::fs::write(olal.config_path(), &config).unwrap();
+    let line = |needle: &str| config.lines().position(|l| l.contains(needle)).unwrap() + 1;
+
+    let output = olal.run(&["config", "validate"]);
+    assert_eq!(output.status.code(), Some(6));
+    let out = String::from_utf8_lossy(&output.stdout);
+    assert!(out.contains(&format!("config.toml:{}: Unknown key 'ollama.hots'", line("hots"))), "{}", out);
+    assert!(
+        out.contains(&format!("config.toml:{}: Watch directory does not exist: /no/such/dir", line("/no/such/dir"))),
+        "{}",
+        out
+    );
+}
+
+#[test]
+fn test_remote_ollama_auth_and_proxy() {
+    let olal = Olal::new(|config| {
+        config.processing.auto_embed = true;
+        config.ollama.api_key = "token-123".to_string();
+        config.ollama.headers.insert("X-Team".to_string(), "ml".to_string());
+    });
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    let requests = olal.ollama.requests_to("/api/embeddings");
+    assert!(!requests.is_empty());
+    for request in &requests {
+        assert_eq!(request.header("authorization"), Some("Bearer token-123"));
+        assert_eq!(request.header("x-team"), Some("ml"));
+    }
+
+    // An unresolvable host is fine when the proxy forwards to Ollama
+    let proxied = [
+        ("OLAL_OLLAMA__HOST", "http://gpu-box.invalid:11434"),
+        ("OLAL_OLLAMA__PROXY", olal.ollama.host.as_str()),
+    ];
+    let output = olal.run_with_env(&proxied, &["ask", "What is ownership in Rust?"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert!(String::from_utf8_lossy(&output.stdout).contains(ANSWER));
+}
+
+#[test]
+fn test_retries_transient_ollama_errors() {
+    let olal = Olal::new(|config| {
+        config.ollama.retry.initial_backoff_ms = 1;
+        config.ollama.retry.breaker_cooldown_seconds = 0;
+    });
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    // Two 503s, then the embedding goes through
+    olal.ollama.fail_next("/api/embeddings", 2);
+    let out = olal.success(&["embed", "--all"]);
+    assert!(out.contains("Generated 1 embeddings"), "{}", out);
+    assert_eq!(olal.ollama.requests_to("/api/embeddings").len(), 3);
+
+    // Failing for good: retries run out, the breaker pauses, then gives up
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+    olal.ollama.fail_next("/api/embeddings", usize::MAX);
+    let out = olal.success(&["embed", "--all"]);
+    assert!(out.contains("Stopped:"), "{}", out);
+    assert!(out.contains("gave up after pausing 3 times"), "{}", out);
+}
+
+#[test]
+fn test_embedding_cache_after_edit() {
+    let olal = Olal::new(|config| {
+        config.processing.chunk_size = 100;
+        config.processing.chunk_overlap = 0;
+    });
+    let paragraph = |topic: &str| format!("{} ", topic).repeat(30).trim_end().to_string();
+    let path = olal.write_file(
+        "journal.md",
+        &format!("{}\n\n{}", paragraph("zeppelins"), paragraph("airships")),
+    );
+    olal.ingest(&path);
+    olal.success(&["embed", "--all"]);
+    let generated = olal.ollama.requests_to("/api/embeddings").len();
+    assert_eq!(generated, 2);
+
+    // Only the edited paragraph needs a new embedding
+    std::fs::write(&path, format!("{}\n\n{}", paragraph("zeppelins"), paragraph("balloons"))).unwrap();
+    olal.ingest(&path);
+    let out = olal.success(&["embed", "--all"]);
+    assert!(out.contains("Generated 1 embeddings"), "{}", out);
+    assert_eq!(olal.ollama.requests_to("/api/embeddings").len(), generated + 1);
+
+    // A paragraph shared with another file is copied, not generated
+    let other = olal.write_file(
+        "log.md",
+        &format!("{}\n\n{}", paragraph("zeppelins"), paragraph("dirigibles")),
+    );
+    olal.ingest(&other);
+    let out = olal.success(&["embed", "--all"]);
+    assert!(out.contains("Generated 1 embeddings"), "{}", out);
+    assert!(out.contains("Reused 1 embeddings"), "{}", out);
+    assert_eq!(olal.ollama.requests_to("/api/embeddings").len(), generated + 2);
+}
+
+#[test]
+fn test_tutorial() {
+    let olal = Olal::new(|_| {});
+    let out = olal.success(&["tutorial"]);
+    assert!(out.contains("Checked: the file was ingested"), "{}", out);
+    assert!(out.contains("Checked: chunks have embeddings"), "{}", out);
+    assert!(out.contains("Tutorial complete!"), "{}", out);
+    // The real knowledge base is untouched
+    assert!(olal.db().list_items(None, None).unwrap().is_empty());
+}
+
+#[test]
+fn test_prompt_override() {
+    let olal = Olal::new(|config| config.processing.generate_summary = true);
+    let prompts = olal.config_path().parent().unwrap().join("prompts");
+    std::fs::create_dir_all(&prompts).unwrap();
+    std::fs::write(prompts.join("summary.hbs"), "Summarize in French: {{content}}\n").unwrap();
+
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let prompt = olal.ollama.requests_to("/api/generate")[0].body["prompt"].clone();
+    assert!(prompt.as_str().unwrap().starts_with("Summarize in French: Rust ownership"), "{}", prompt);
+
+    let out = olal.success(&["prompts", "list"]);
+    assert!(out.lines().any(|l| l.contains("summary") && l.contains("[custom]")), "{}", out);
+    olal.success(&["prompts", "reset", "summary"]);
+    assert!(!prompts.join("summary.hbs").exists());
+}
+
+#[test]
+fn test_natural_dates() {
+    let olal = Olal::new(|config| config.ui.date_format = "%d.%m.%Y".to_string());
+    olal.success(&["task", "add", "Write the report", "--due", "in 3 days"]);
+    let out = olal.success(&["task", "list"]);
+    assert!(out.contains("Write the report") && out.contains("(due in 3 days)"), "{}", out);
+
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let out = olal.success(&["recent"]);
+    let today = chrono::Local::now().format("%d.%m.%Y").to_string();
+    assert!(out.contains(&today), "{}", out);
+
+    let out = olal.success(&["digest", "--since", "last monday"]);
+    assert!(out.contains("since last monday"), "{}", out);
+    assert!(!olal.run(&["digest", "--since", "someday"]).status.success());
+}
+
+#[test]
+fn test_show_chunks_without_terminal() {
+    // Not a terminal, so the pager is skipped and the text printed as-is
+    let olal = Olal::new(|config| config.ui.pager = "olal-test-missing-pager".to_string());
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    let id = olal.db().list_items(None, None).unwrap()[0].id.clone();
+
+    let out = olal.success(&["show", &id, "--chunks"]);
+    assert!(out.contains("without taking ownership of it."), "{}", out);
+    assert!(!out.contains('\x1b'), "{}", out);
+
+    let out = olal.success(&["show", &id, "--no-pager"]);
+    assert!(out.contains("Content Preview"), "{}", out);
+}
+
+#[test]
+fn test_ask_rerank() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file(
+        "borrowing.md",
+        "# Rust borrowing\n\nA reference borrows a value in Rust without taking ownership. \
+         The owner keeps the value while references to it are alive.\n",
+    ));
+    olal.success(&["embed", "--all"]);
+
+    let out = olal.success(&["ask", "--rerank", "--context", "1", "Who owns a value in Rust?"]);
+    assert!(out.contains(ANSWER), "{}", out);
+
+    // Both notes were candidates; the fake reranker prefers the second one
+    let generate = olal.ollama.requests_to("/api/generate");
+    let prompts: Vec<String> = generate
+        .iter()
+        .map(|r| r.body["prompt"].as_str().unwrap().to_string())
+        .collect();
+    let rerank = prompts.iter().find(|p| p.starts_with("Rate how relevant")).unwrap();
+    let second = rerank
+        .lines()
+        .find_map(|line| line.strip_prefix("[2] From: "))
+        .expect("two candidates");
+    let answer = prompts.last().unwrap();
+    assert!(answer.contains(&format!("[1] From: {}", second)), "{}", answer);
+    assert!(!answer.contains("[2] From:"), "{}", answer);
+}
+
+#[test]
+fn test_ask_expand() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+    olal.success(&["embed", "--all"]);
+
+    let question = "What have I been learning lately?";
+    olal.success(&["ask", question]);
+    let generate = olal.ollama.requests_to("/api/generate");
+    assert_eq!(generate.len(), 1);
+
+    let out = olal.success(&["ask", "--expand", question]);
+    assert!(out.contains("Also searching: Rust value ownership"), "{}", out);
+    assert!(out.contains("Also searching: Sourdough starter flour"), "{}", out);
+
+    // The question and both sub-queries were searched for
+    let embedded: Vec<String> = olal
+        .ollama
+        .requests_to("/api/embeddings")
+        .iter()
+        .map(|r| r.body["prompt"].as_str().unwrap().to_string())
+        .collect();
+    for query in [question, "Rust value ownership", "Sourdough starter flour"] {
+        assert!(embedded.iter().any(|p| p == query), "{:?}", embedded);
+    }
+
+    // Each sub-query found its note, and both made it into the answer
+    let generate = olal.ollama.requests_to("/api/generate");
+    let prompts: Vec<&str> = generate[1..]
+        .iter()
+        .map(|r| r.body["prompt"].as_str().unwrap())
+        .collect();
+    assert!(prompts[0].starts_with("Rewrite the question"), "{}", prompts[0]);
+    assert!(prompts[0].contains(question));
+    let answer = prompts.last().unwrap();
+    assert!(answer.contains("From: Rust ownership"), "{}", answer);
+    assert!(answer.contains("From: Sourdough"), "{}", answer);
+}
+
+#[test]
+fn test_ask_date_range() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+    olal.success(&["embed", "--all"]);
+    let created = (chrono::Utc::now() - chrono::Duration::days(3)).to_rfc3339();
+    olal.db()
+        .conn()
+        .unwrap()
+        .execute(
+            "UPDATE items SET created_at = ?1 WHERE title = 'Sourdough'",
+            [created],
+        )
+        .unwrap();
+
+    let question = "What did I learn about owners and sourdough starters?";
+    let answer_prompt = || {
+        olal.ollama.requests_to("/api/generate").last().unwrap().body["prompt"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    let out = olal.success(&["ask", "--since", "yesterday", question]);
+    assert!(out.contains("Filtered by since"), "{}", out);
+    let prompt = answer_prompt();
+    assert!(prompt.contains("From: Rust ownership"), "{}", prompt);
+    assert!(!prompt.contains("From: Sourdough"), "{}", prompt);
+
+    olal.success(&["ask", "--until", "2 days ago", question]);
+    let prompt = answer_prompt();
+    assert!(prompt.contains("From: Sourdough"), "{}", prompt);
+    assert!(!prompt.contains("From: Rust ownership"), "{}", prompt);
+
+    assert!(!olal.run(&["ask", "--since", "someday", question]).status.success());
+}
+
+#[test]
+fn test_ask_scoped() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+    olal.success(&["embed", "--all"]);
+    let items = olal.db().list_items(None, None).unwrap();
+    let sourdough = items.iter().find(|i| i.title == "Sourdough").unwrap();
+    let ownership = items.iter().find(|i| i.title == "Rust ownership").unwrap();
+    olal.success(&["tag", &sourdough.id, "baking"]);
+
+    let question = "What did I learn about owners and sourdough starters?";
+    let answer_prompt = || {
+        olal.ollama.requests_to("/api/generate").last().unwrap().body["prompt"]
+            .as_str()
+            .unwrap()
+            .to_string()
+    };
+
+    let out = olal.success(&["ask", "--tag", "baking", question]);
+    assert!(out.contains("Filtered by tag baking"), "{}", out);
+    let prompt = answer_prompt();
+    assert!(prompt.contains("From: Sourdough"), "{}", prompt);
+    assert!(!prompt.contains("From: Rust ownership"), "{}", prompt);
+
+    let out = olal.success(&["ask", "--item", &ownership.id, question]);
+    assert!(out.contains("Filtered by item"), "{}", out);
+    let prompt = answer_prompt();
+    assert!(prompt.contains("From: Rust ownership"), "{}", prompt);
+    assert!(!prompt.contains("From: Sourdough"), "{}", prompt);
+
+    // An ID prefix is enough
+    let out = olal.success(&["ask", "--tag", "baking", "--item", &sourdough.id[..8], question]);
+    assert!(out.contains("Filtered by tag baking, item"), "{}", out);
+
+    // Nothing matches both
+    let out = olal.success(&["ask", "--tag", "baking", "--item", &ownership.id, question]);
+    assert!(out.contains("No relevant content found"), "{}", out);
+    assert!(!olal.run(&["ask", "--item", "zzzzzzzz", question]).status.success());
+}
+
+#[test]
+fn test_ask_follow_ups() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+    olal.success(&["embed", "--all"]);
+
+    let out = olal.success_with_input(
+        &["ask", "-i", "--context", "1", "Who owns a value in Rust?"],
+        "And when is the value dropped?\nHow do I feed a sourdough starter with flour?\n\n",
+    );
+    assert_eq!(out.matches(ANSWER).count(), 3, "{}", out);
+    assert_eq!(out.matches("New topic").count(), 1, "{}", out);
+
+    let prompts: Vec<String> = olal
+        .ollama
+        .requests_to("/api/generate")
+        .iter()
+        .map(|r| r.body["prompt"].as_str().unwrap().to_string())
+        .collect();
+    assert_eq!(prompts.len(), 3);
+    assert!(!prompts[0].contains("Conversation so far"), "{}", prompts[0]);
+
+    // The follow-up kept the first answer's context and saw the exchange
+    assert!(prompts[1].contains("From: Rust ownership"), "{}", prompts[1]);
+    assert!(prompts[1].contains(&format!(
+        "Question: Who owns a value in Rust?\nAnswer: {}\n\nQuestion: And when",
+        ANSWER
+    )), "{}", prompts[1]);
+
+    // The change of topic searched again, keeping the history
+    assert!(prompts[2].contains("From: Sourdough"), "{}", prompts[2]);
+    assert!(!prompts[2].contains("From: Rust ownership"), "{}", prompts[2]);
+    assert!(prompts[2].contains("Question: And when is the value dropped?"), "{}", prompts[2]);
+}
+
+#[test]
+fn test_shell_slash_commands() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.success(&["embed", "--all"]);
+    let checks = olal.ollama.requests_to("/api/tags").len();
+
+    let out = olal.success_with_input(
+        &["shell"],
+        "/task add Review borrowing\n/task\n/search ownership\n/ask Who owns a value in Rust?\nsemantic owner\n/exit\n",
+    );
+    assert!(out.contains("Task added: Review borrowing"), "{}", out);
+    assert!(out.contains("Found 1 result"), "{}", out);
+    assert!(out.contains(ANSWER), "{}", out);
+    assert!(out.contains("Similarity:"), "{}", out);
+    assert!(out.contains("Goodbye!"), "{}", out);
+    assert_eq!(olal.db().list_tasks(None).unwrap().len(), 1);
+
+    // Ollama was checked once for the whole session
+    assert_eq!(olal.ollama.requests_to("/api/tags").len(), checks + 1);
+
+    let history = std::fs::read_to_string(olal.dir.path().join("data").join("shell_history")).unwrap();
+    assert!(history.contains("/task add Review borrowing"), "{}", history);
+}
+
+#[test]
+fn test_hybrid_search() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+    olal.success(&["embed", "--all"]);
+
+    let out = olal.success(&["search", "--hybrid", "--vector-weight", "0.5", "ownership"]);
+    assert!(out.contains("Hybrid search for"), "{}", out);
+    assert!(out.contains("Rust ownership"), "{}", out);
+    assert!(out.contains("text 100%"), "{}", out);
+    // The keyword match ranks first
+    let sourdough = out.find("Sourdough").unwrap_or(usize::MAX);
+    assert!(out.find("Rust ownership").unwrap() < sourdough, "{}", out);
+
+    assert!(!olal.run(&["search", "--hybrid", "--vector-weight", "2", "ownership"]).status.success());
+}
+
+#[test]
+fn test_capture_and_ask() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+    olal.success(&["embed", "--all"]);
+
+    // --ask before the thought doesn't take it as the question
+    let out = olal.success(&[
+        "capture",
+        "--ask",
+        "Rust ownership means each value has a single owner",
+    ]);
+    assert!(out.contains("Captured thought"), "{}", out);
+    assert!(out.contains(ANSWER), "{}", out);
+    assert!(out.contains("Linked to:") && out.contains("Rust ownership"), "{}", out);
+
+    let db = olal.db();
+    let thought = db
+        .search_items("single", Some(5))
+        .unwrap()
+        .into_iter()
+        .find(|i| i.title.starts_with("Rust ownership means"))
+        .unwrap();
+    let links = db.get_item_links(&thought.id).unwrap();
+    assert!(links.iter().any(|l| l.source_id == thought.id), "{:?}", links);
+    assert_eq!(db.embedding_stats().unwrap().0, db.embedding_stats().unwrap().1);
+
+    let prompt = olal.ollama.requests_to("/api/generate").last().unwrap().body["prompt"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(prompt.contains("Question: How does this relate to my existing notes?"), "{}", prompt);
+}
+
+#[test]
+fn test_search_filters() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.success(&["capture", "Ownership quiz tomorrow", "-T", "study"]);
+
+    let out = olal.success(&["search", "ownership", "--tag", "study"]);
+    assert!(out.contains("Ownership quiz") && !out.contains("Rust ownership"), "{}", out);
+    assert!(out.contains("Filtered by tag study"), "{}", out);
+
+    let out = olal.success(&["search", "ownership", "--type", "code"]);
+    assert!(out.contains("No results found"), "{}", out);
+
+    let out = olal.success(&["search", "ownership", "--since", "today"]);
+    assert!(out.contains("Found 2 results"), "{}", out);
+    let out = olal.success(&["search", "ownership", "--until", "yesterday"]);
+    assert!(out.contains("No results found"), "{}", out);
+
+    assert!(!olal.run(&["search", "ownership", "--type", "movie"]).status.success());
+}
+
+#[test]
+fn test_search_snippets() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    let out = olal.success(&["search", "borrowing", "--context", "5"]);
+    assert!(out.contains("...of scope. Borrowing lets code..."), "{}", out);
+
+    let out = olal.success(&["search", "dropped"]);
+    assert!(out.contains("the value is dropped when its owner"), "{}", out);
+}
+
+#[test]
+fn test_dedupe_near_duplicates() {
+    let article = "# What is ownership?\n\n\
+        Ownership is a set of rules that govern how a Rust program manages memory. All programs \
+        have to manage the way they use a computer's memory while running. Some languages have \
+        garbage collection that regularly looks for no-longer-used memory as the program runs; \
+        in other languages, the programmer must explicitly allocate and free the memory. Rust \
+        uses a third approach: memory is managed through a system of ownership with a set of \
+        rules that the compiler checks. If any of the rules are violated, the program won't \
+        compile.\n";
+    let copy = format!("{}\nSaved from the Rust book.\n", article.replace("third", "different"));
+
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", article));
+    olal.ingest(&olal.write_file("clippings/ownership.md", &copy));
+    olal.ingest(&olal.write_file("sourdough.md", COOKING_NOTE));
+
+    // Listed, then linked so they aren't listed again
+    let out = olal.success_with_input(&["dedupe"], "l\n");
+    assert!(out.contains("Cluster 1 of 1") && out.contains("clippings/ownership.md"), "{}", out);
+    assert!(!out.contains("sourdough.md"), "{}", out);
+    assert!(out.contains("Merged 0 items, made 1 duplicate link"), "{}", out);
+    let out = olal.success(&["dedupe"]);
+    assert!(out.contains("No near-duplicates found"), "{}", out);
+
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", article));
+    olal.ingest(&olal.write_file("clippings/ownership.md", &copy));
+    let db = olal.db();
+    let copy_id = db.list_items(None, None).unwrap()[0].id.clone();
+    db.tag_item(&copy_id, "clippings").unwrap();
+
+    let out = olal.success(&["dedupe", "--merge"]);
+    assert!(out.contains("Merged 1 item"), "{}", out);
+    let items = db.list_items(None, None).unwrap();
+    assert_eq!(items.len(), 1);
+    assert!(items[0].source_path.as_deref().is_some_and(|p| !p.contains("clippings")));
+    let tags: Vec<String> = db.get_item_tags(&items[0].id).unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(tags, vec!["clippings"]);
+}
+
+#[test]
+fn test_search_suggests_spelling() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    let out = olal.success(&["search", "onwership scpoe"]);
+    assert!(out.contains("Did you mean \"ownership scope\"?"), "{}", out);
+    assert!(out.contains("Found 1 result"), "{}", out);
+
+    olal.success(&["config", "set", "search.typo_tolerance", "false"]);
+    let out = olal.success(&["search", "onwership"]);
+    assert!(out.contains("No results found") && !out.contains("Did you mean"), "{}", out);
+}
+
+#[test]
+fn test_search_tokenizer_and_query_syntax() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+
+    // Punctuation is matched, not parsed
+    let out = olal.success(&["search", "value, (scope) AND"]);
+    assert!(out.contains("Found 1 result"), "{}", out);
+    let out = olal.success(&["search", "\"single owner\" borrow*"]);
+    assert!(out.contains("Found 1 result"), "{}", out);
+    // Tag names are searched along with the text
+    olal.success(&["capture", "Quiz tomorrow", "-T", "exams"]);
+    let out = olal.success(&["search", "exams"]);
+    assert!(out.contains("Found 1 result") && out.contains("Quiz tomorrow"), "{}", out);
+
+    let out = olal.success(&["search", "\"owner single\""]);
+    assert!(out.contains("No results found"), "{}", out);
+
+    let out = olal.success(&["search", "drops"]);
+    assert!(out.contains("No results found"), "{}", out);
+
+    // Switching to stemming rebuilds the index once, right away
+    let output = olal.run(&["config", "set", "search.tokenizer", "porter"]);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Rebuilding the search index with the porter tokenizer"), "{}", stderr);
+    assert_eq!(olal.db().fts_tokenizer().unwrap(), Some(olal_db::fts::Tokenizer::Porter));
+
+    let output = olal.run(&["search", "drops"]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Rebuilding"));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Found 1 result"));
+
+    // Only searching commands rebuild for a tokenizer set some other way
+    let env = [("OLAL_SEARCH__TOKENIZER", "unicode61")];
+    let output = olal.run_with_env(&env, &["status", "--short"]);
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("Rebuilding"));
+    assert_eq!(olal.db().fts_tokenizer().unwrap(), Some(olal_db::fts::Tokenizer::Porter));
+    let output = olal.run_with_env(&env, &["search", "drops"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Rebuilding"));
+}
+
+#[test]
+fn test_tag_management() {
+    let olal = Olal::new(|_| {});
+    olal.success(&["capture", "Borrow checker notes", "-T", "ml"]);
+    olal.success(&["capture", "Lifetimes cheat sheet", "-T", "ml", "-T", "ai"]);
+
+    let out = olal.success(&["tag", "rename", "ml", "rust"]);
+    assert!(out.contains("Renamed tag 'ml' to 'rust'"), "{}", out);
+    assert!(!olal.run(&["tag", "rename", "ai", "rust"]).status.success());
+
+    let out = olal.success(&["tag", "merge", "ai", "rust"]);
+    assert!(out.contains("(1 items, 0 newly tagged)"), "{}", out);
+
+    let out = olal.success(&["tag", "show", "Rust"]);
+    assert!(out.contains("Items: 2"), "{}", out);
+    assert!(out.contains("Borrow checker") && out.contains("Lifetimes"), "{}", out);
+
+    let out = olal.success(&["tag", "delete", "rust"]);
+    assert!(out.contains("removed from 2 items"), "{}", out);
+    let out = olal.success(&["tags"]);
+    assert!(out.contains("No tags found"), "{}", out);
+}
+
+#[test]
+fn test_nested_tags() {
+    let olal = Olal::new(|_| {});
+    olal.success(&["capture", "Ownership in the backend", "-T", "project/olal/backend"]);
+    olal.success(&["capture", "Ownership on the roadmap", "-T", "project/olal"]);
+    olal.success(&["capture", "Ownership of the garden", "-T", "home"]);
+
+    let out = olal.success(&["tags"]);
+    assert!(out.contains("  • project (2)"), "{}", out);
+    assert!(out.contains("    • olal (2)"), "{}", out);
+    assert!(out.contains("      • backend (1)"), "{}", out);
+
+    let out = olal.success(&["search", "ownership", "--tag", "project/olal"]);
+    assert!(out.contains("Found 2 results"), "{}", out);
+    let out = olal.success(&["search", "ownership", "--tag", "project/olal/backend"]);
+    assert!(out.contains("Found 1 result"), "{}", out);
+
+    olal.success(&["tag", "rename", "project", "work"]);
+    let out = olal.success(&["tag", "show", "work/olal"]);
+    assert!(out.contains("Sub-tags: work/olal/backend"), "{}", out);
+    assert!(out.contains("Items: 2"), "{}", out);
+}
+
+#[test]
+fn test_project_items() {
+    let olal = Olal::new(|_| {});
+    olal.ingest(&olal.write_file("ownership.md", OWNERSHIP_NOTE));
+    olal.success(&["capture", "Ownership quiz tomorrow"]);
+    olal.success(&["embed", "--all"]);
+    olal.success(&["project", "create", "Rust Course"]);
+
+    let items = olal.db().list_items(None, None).unwrap();
+    let note = items.iter().find(|i| i.title.contains("quiz")).unwrap();
+    let out = olal.success(&["project", "add-item", "rust course", &note.id[..8]]);
+    assert!(out.contains("Added 'Ownership quiz tomorrow' to project 'Rust Course'"), "{}", out);
+
+    let out = olal.success(&["project", "show", "Rust Course"]);
+    assert!(out.contains("Items (1)") && out.contains("Ownership quiz tomorrow"), "{}", out);
+
+    let out = olal.success(&["search", "ownership", "--project", "Rust Course"]);
+    assert!(out.contains("Found 1 result") && out.contains("Ownership quiz"), "{}", out);
+    assert!(!olal.run(&["search", "ownership", "--project", "Nope"]).status.success());
+
+    // Only the project's item is offered as context
+    olal.success(&["ask", "--project", "Rust Course", "When is the ownership quiz?"]);
+    let prompts = olal.ollama.requests_to("/api/generate");
+    let prompt = prompts.last().unwrap().body["prompt"].as_str().unwrap().to_string();
+    assert!(prompt.contains("Ownership quiz tomorrow"), "{}", prompt);
+    assert!(!prompt.contains("[2] From:"), "{}", prompt);
+}
+
+#[test]
+fn test_recurring_task() {
+    let olal = Olal::new(|_| {});
+    let out = olal.success(&["task", "add", "Water plants", "--due", "yesterday 9am", "--repeat", "daily"]);
+    assert!(out.contains("Repeats: daily"), "{}", out);
+    olal.success(&["task", "add", "File taxes", "--due", "in 3 days"]);
+
+    let out = olal.success(&["task", "overdue"]);
+    assert!(out.contains("Water plants") && !out.contains("File taxes"), "{}", out);
+    assert!(out.contains("(due yesterday 09:00)") && out.contains("↻ daily"), "{}", out);
+
+    let tasks = olal.db().list_tasks(None).unwrap();
+    let plants = tasks.iter().find(|t| t.title == "Water plants").unwrap();
+    let out = olal.success(&["task", "done", &plants.id[..8]]);
+    assert!(out.contains("Next due"), "{}", out);
+
+    // The next occurrence is pending and not yet overdue
+    let out = olal.success(&["task", "overdue"]);
+    assert!(out.contains("No overdue tasks"), "{}", out);
+    let pending = olal.db().list_tasks(Some(olal_core::TaskStatus::Pending)).unwrap();
+    assert_eq!(pending.iter().filter(|t| t.title == "Water plants").count(), 1);
+
+    // Completing it again, or after reopening it, doesn't repeat it twice
+    let out = olal.success(&["task", "done", &plants.id[..8]]);
+    assert!(out.contains("already done"), "{}", out);
+    olal.success(&["task", "start", &plants.id[..8]]);
+    let out = olal.success(&["task", "done", &plants.id[..8]]);
+    assert!(!out.contains("Next due"), "{}", out);
+    let pending = olal.db().list_tasks(Some(olal_core::TaskStatus::Pending)).unwrap();
+    assert_eq!(pending.iter().filter(|t| t.title == "Water plants").count(), 1);
+
+    assert!(!olal.run(&["task", "add", "Nap", "--repeat", "hourly"]).status.success());
+}
+
+#[test]
+fn test_subtasks_and_blockers() {
+    let olal = Olal::new(|_| {});
+    olal.success(&["task", "add", "Ship release"]);
+    let release = olal.db().list_tasks(None).unwrap()[0].id.clone();
+    olal.success(&["task", "add", "Write changelog", "--parent", &release[..8]]);
+    olal.success(&["task", "add", "Fix flaky test"]);
+
+    let tasks = olal.db().list_tasks(None).unwrap();
+    let id = |title: &str| tasks.iter().find(|t| t.title == title).unwrap().id[..8].to_string();
+    olal.success(&["task", "block", &id("Ship release"), &id("Fix flaky test")]);
+    assert!(!olal.run(&["task", "block", &id("Fix flaky test"), &id("Ship release")]).status.success());
+
+    let out = olal.success(&["task", "list"]);
+    assert!(out.contains("⧗ blocked by Fix flaky test"), "{}", out);
+    let release_line = out.lines().position(|l| l.contains("Ship release")).unwrap();
+    let subtask = out.lines().nth(release_line + 1).unwrap();
+    assert!(subtask.starts_with("  ○ Write changelog"), "{}", out);
+
+    let out = olal.success(&["task", "done", &id("Fix flaky test")]);
+    assert!(out.contains("Ready to start: Ship release"), "{}", out);
+    let out = olal.success(&["task", "list"]);
+    assert!(out.contains("✓ ready"), "{}", out);
+}
+
+#[test]
+fn test_task_start_edit_cancel() {
+    let olal = Olal::new(|_| {});
+    olal.success(&["project", "create", "Garden"]);
+    olal.success(&["task", "add", "Plant tomatos"]);
+    let id = olal.db().list_tasks(None).unwrap()[0].id.clone();
+
+    let out = olal.success(&["task", "start", &id[..8]]);
+    assert!(out.contains("Started: Plant tomatos"), "{}", out);
+
+    let out = olal.success(&[
+        "task", "edit", &id[..8], "--title", "Plant tomatoes", "--priority", "2", "--project", "garden",
+        "--due", "tomorrow",
+    ]);
+    assert!(out.contains("Task updated: Plant tomatoes") && out.contains("project: Garden"), "{}", out);
+    let task = olal.db().get_task(&id).unwrap();
+    assert_eq!(task.status, olal_core::TaskStatus::InProgress);
+    assert_eq!(task.priority, 2);
+    assert!(task.project_id.is_some() && task.due_date.is_some());
+
+    olal.success(&["task", "edit", &id[..8], "--due", "none"]);
+    assert!(olal.db().get_task(&id).unwrap().due_date.is_none());
+    assert!(!olal.run(&["task", "edit", &id[..8]]).status.success());
+
+    olal.success(&["task", "cancel", &id[..8]]);
+    assert_eq!(olal.db().get_task(&id).unwrap().status, olal_core::TaskStatus::Cancelled);
+    let out = olal.success(&["task", "start", &id[..8]]);
+    assert!(out.contains("Reopened: Plant tomatoes"), "{}", out);
+}
+
+#[test]
+fn test_task_board() {
+    let olal = Olal::new(|_| {});
+    olal.success(&["project", "create", "Garden"]);
+    olal.success(&["task", "add", "Water plants", "-P", "Garden"]);
+    olal.success(&["task", "add", "Prune roses", "-P", "Garden", "-p", "2"]);
+    olal.success(&["task", "add", "File taxes"]);
+    let tasks = olal.db().list_tasks(None).unwrap();
+    let id = |title: &str| tasks.iter().find(|t| t.title == title).unwrap().id[..8].to_string();
+    olal.success(&["task", "start", &id("Prune roses")]);
+    olal.success(&["task", "done", &id("File taxes")]);
+
+    let out = olal.success(&["task", "board"]);
+    assert!(out.contains("Pending (1)") && out.contains("In Progress (1)") && out.contains("Done (1)"), "{}", out);
+    // Cards sit under their column: one row holds all three
+    let row = out.lines().find(|l| l.contains("Water plants")).unwrap();
+    let (water, prune, taxes) = (row.find("Water").unwrap(), row.find("Prune").unwrap(), row.find("File").unwrap());
+    assert!(water < prune && prune < taxes, "{}", out);
+    assert!(out.contains("P2"), "{}", out);
+
+    let out = olal.success(&["task", "board", "-P", "garden"]);
+    assert!(out.contains("Task Board · Garden") && !out.contains("File taxes"), "{}", out);
+}
+
+#[test]
+fn test_review_ai_conflicting_decisions() {
+    let olal = Olal::new(|_| {});
+    let db = olal.db();
+    let item = olal_core::Item::new(olal_core::ItemType::Note, "Rust");
+    db.create_item(&item).unwrap();
+    let suggestion = |tag: &str| {
+        let suggestion = olal_core::AiSuggestion::new(item.id.clone(), olal_core::SuggestionKind::Tag, tag, 0.5);
+        db.create_suggestion(&suggestion).unwrap();
+        suggestion.id[..8].to_string()
+    };
+    let (rust, cargo) = (suggestion("rust"), suggestion("cargo"));
+
+    assert!(!olal.run(&["review-ai", "--accept-all", "--reject", &rust]).status.success());
+    assert!(!olal.run(&["review-ai", "--reject-all", "--accept", &rust]).status.success());
+    let output = olal.run(&["review-ai", "--accept", &rust, &cargo, "--reject", &rust]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("both accepted and rejected"));
+    assert_eq!(db.pending_suggestion_count().unwrap(), 2);
+
+    // The same suggestion named twice is accepted once
+    let out = olal.success(&["review-ai", "--accept", &rust, &rust[..6]]);
+    assert!(out.contains("Accepted 1 suggestions"), "{}", out);
+    assert_eq!(db.pending_suggestion_count().unwrap(), 1);
+}
@@ -0,0 +1,225 @@
+// olal browser interface. Talks to the JSON API served by `olal serve`.
+
+const $ = (id) => document.getElementById(id);
+
+function el(tag, props = {}, ...children) {
+  const node = Object.assign(document.createElement(tag), props);
+  node.append(...children);
+  return node;
+}
+
+async function api(path, options) {
+  const response = await fetch(path, options);
+  if (!response.ok) {
+    const body = await response.json().catch(() => ({}));
+    throw new Error(body.error || response.statusText);
+  }
+  return response;
+}
+
+function timestamp(seconds) {
+  const s = Math.floor(seconds);
+  const h = Math.floor(s / 3600);
+  const mm = String(Math.floor((s % 3600) / 60)).padStart(2, "0");
+  const ss = String(s % 60).padStart(2, "0");
+  return h ? `${h}:${mm}:${ss}` : `${mm}:${ss}`;
+}
+
+// Item list
+
+function showItems(title, items) {
+  $("list-title").textContent = title;
+  const list = $("items");
+  list.replaceChildren();
+  if (!items.length) {
+    list.append(el("li", { className: "muted", textContent: "Nothing found." }));
+  }
+  for (const item of items) {
+    const li = el(
+      "li",
+      { onclick: () => openItem(item.id) },
+      el("div", { className: "title", textContent: item.title }),
+      el("div", {
+        className: "meta",
+        textContent: `${item.item_type} · ${new Date(item.created_at).toLocaleDateString()}`,
+      })
+    );
+    li.dataset.id = item.id;
+    list.append(li);
+  }
+}
+
+async function loadRecent() {
+  const { items } = await (await api("/api/items?limit=50")).json();
+  showItems("Recent", items);
+}
+
+async function search(query) {
+  if (!query) return loadRecent();
+  try {
+    const params = new URLSearchParams({ q: query, limit: 50 });
+    const { items } = await (await api(`/api/search?${params}`)).json();
+    showItems(`Results for “${query}”`, items);
+  } catch (e) {
+    showItems(e.message, []);
+  }
+}
+
+// Item detail
+
+async function openItem(id) {
+  for (const li of $("items").children) {
+    li.classList.toggle("active", li.dataset.id === id);
+  }
+  const view = $("item");
+  try {
+    const { item, tags, chunks, media } = await (await api(`/api/items/${id}`)).json();
+    view.replaceChildren(
+      el("h2", { textContent: item.title }),
+      el("div", {
+        className: "meta",
+        textContent: [item.item_type, item.source_path].filter(Boolean).join(" · "),
+      }),
+      el("div", {}, ...tags.map((t) => el("span", { className: "tag", textContent: `#${t}` })))
+    );
+    if (item.summary) {
+      view.append(el("p", { textContent: item.summary }));
+    }
+
+    let player = null;
+    if (media) {
+      player = el(item.item_type === "video" ? "video" : "audio", {
+        controls: true,
+        preload: "metadata",
+        src: `/api/items/${id}/media`,
+      });
+      view.append(player);
+    }
+
+    for (const chunk of chunks) {
+      const p = el("p", { className: "chunk" });
+      if (chunk.start_time != null) {
+        p.append(
+          el("span", {
+            className: "time",
+            textContent: timestamp(chunk.start_time),
+            title: player ? "Play from here" : "",
+            onclick: () => {
+              if (!player) return;
+              player.currentTime = chunk.start_time;
+              player.play();
+            },
+          })
+        );
+      }
+      p.append(chunk.content);
+      view.append(p);
+    }
+  } catch (e) {
+    view.replaceChildren(el("p", { className: "error", textContent: e.message }));
+  }
+}
+
+// Ask, streamed as newline-delimited JSON
+
+function showSources(sources) {
+  $("sources").replaceChildren(
+    ...sources.map((source) =>
+      el("li", {
+        textContent: `[${source.number}] ${source.item_title}${source.pages ? `, ${source.pages}` : ""} (${Math.round(source.similarity * 100)}%)`,
+        onclick: () => openItem(source.item_id),
+      })
+    )
+  );
+}
+
+// Once the answer is complete, turn its [n] citations into links to the
+// sources and drop the sources it doesn't cite (unless it cites none).
+function linkCitations(text, sources) {
+  const byNumber = new Map(sources.map((source) => [source.number, source]));
+  const cited = new Set(
+    [...text.matchAll(/\[(\d+(?:\s*,\s*\d+)*)\]/g)].flatMap((m) => m[1].split(",").map(Number))
+  );
+
+  const answer = $("answer");
+  answer.replaceChildren();
+  for (const part of text.split(/(\[\d+\])/)) {
+    const source = byNumber.get(Number(part.slice(1, -1)));
+    if (/^\[\d+\]$/.test(part) && source) {
+      answer.append(
+        el("a", {
+          className: "citation",
+          href: "#",
+          textContent: part,
+          title: source.pages ? `${source.item_title}, ${source.pages}` : source.item_title,
+          onclick: (event) => {
+            event.preventDefault();
+            openItem(source.item_id);
+          },
+        })
+      );
+    } else {
+      answer.append(part);
+    }
+  }
+
+  if (sources.some((source) => cited.has(source.number))) {
+    showSources(sources.filter((source) => cited.has(source.number)));
+  }
+}
+
+async function ask(question) {
+  const answer = $("answer");
+  answer.textContent = "";
+  answer.classList.remove("error");
+  $("sources").replaceChildren();
+
+  try {
+    const response = await api("/api/ask", {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ question }),
+    });
+    const reader = response.body.getReader();
+    const decoder = new TextDecoder();
+    let buffered = "";
+    let text = "";
+    let sources = [];
+
+    for (;;) {
+      const { value, done } = await reader.read();
+      if (done) break;
+      buffered += decoder.decode(value, { stream: true });
+      const lines = buffered.split("\n");
+      buffered = lines.pop();
+      for (const line of lines.filter(Boolean)) {
+        const message = JSON.parse(line);
+        if (message.text) {
+          text += message.text;
+          answer.textContent = text;
+        }
+        if (message.sources) {
+          sources = message.sources;
+          showSources(sources);
+        }
+        if (message.done) linkCitations(text, sources);
+      }
+    }
+  } catch (e) {
+    answer.classList.add("error");
+    answer.textContent = e.message;
+  }
+}
+
+$("search-form").addEventListener("submit", (event) => {
+  event.preventDefault();
+  search($("search").value.trim());
+});
+
+$("ask-form").addEventListener("submit", (event) => {
+  event.preventDefault();
+  const question = $("question").value.trim();
+  if (question) ask(question);
+});
+
+loadRecent();
@@ -1,12 +1,14 @@
 //! Configuration structures and loading.
 
 use crate::error::{ConfigError, ConfigResult};
-use crate::paths::AppPaths;
+use crate::overrides;
+use crate::paths::{active_profile, active_vault, AppPaths};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
 /// Main configuration structure.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default)]
     pub general: GeneralConfig,
@@ -25,26 +27,85 @@ pub struct Config {
 
     #[serde(default)]
     pub ui: UiConfig,
-}
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            general: GeneralConfig::default(),
-            ollama: OllamaConfig::default(),
-            watch: WatchConfig::default(),
-            processing: ProcessingConfig::default(),
-            youtube: YoutubeConfig::default(),
-            ui: UiConfig::default(),
-        }
-    }
+    #[serde(default)]
+    pub search: SearchConfig,
+
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+
+    #[serde(default)]
+    pub review: ReviewConfig,
+
+    #[serde(default)]
+    pub retention: RetentionConfig,
+
+    #[serde(default)]
+    pub schedule: ScheduleConfig,
+
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+
+    #[serde(default)]
+    pub digest: DigestConfig,
+
+    /// Separate knowledge bases, selected with `--vault` or `OLAL_VAULT`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub vaults: BTreeMap<String, VaultConfig>,
+
+    /// Partial configs merged over the rest, selected with `--profile` or
+    /// `OLAL_PROFILE`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profile: BTreeMap<String, toml::Table>,
 }
 
 impl Config {
-    /// Load configuration from the default location.
+    /// Load the effective configuration: the default config file, with the
+    /// active profile (`OLAL_PROFILE`) merged over it and `OLAL_<SECTION>__<KEY>`
+    /// environment overrides on top. With a vault active (`OLAL_VAULT`), its
+    /// `[vaults.<name>.watch]` replaces `[watch]`.
+    ///
+    /// Use [`Config::load_from`] to edit the file itself.
     pub fn load() -> ConfigResult<Self> {
         let paths = AppPaths::new().ok_or(ConfigError::NoConfigDir)?;
-        Self::load_from(&paths.config_file)
+        let mut table = if paths.config_file.exists() {
+            toml::from_str(&std::fs::read_to_string(&paths.config_file)?)?
+        } else {
+            toml::Table::new()
+        };
+        migrate_nightly_maintenance(&mut table);
+        if let Some(name) = active_profile() {
+            overrides::apply_profile(&mut table, &name)?;
+        }
+        overrides::apply_env(&mut table, std::env::vars());
+
+        let mut config: Config = toml::Value::Table(table).try_into()?;
+        if let Some(name) = active_vault() {
+            config.watch = config.vault(&name)?.watch.clone().unwrap_or_default();
+        }
+        Ok(config)
+    }
+
+    /// The value of `key`, e.g. `ollama.host` or `watch.directories[0]`.
+    pub fn get(&self, key: &str) -> Option<toml::Value> {
+        use crate::validate::{parse_key, Segment};
+
+        let mut value = toml::Value::try_from(self).ok()?;
+        for segment in parse_key(key)? {
+            value = match (value, segment) {
+                (toml::Value::Table(mut t), Segment::Key(k)) => t.remove(&k)?,
+                (toml::Value::Array(mut a), Segment::Index(i)) if i < a.len() => a.swap_remove(i),
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// The vault called `name`.
+    pub fn vault(&self, name: &str) -> ConfigResult<&VaultConfig> {
+        self.vaults
+            .get(name)
+            .ok_or_else(|| ConfigError::UnknownVault(name.to_string()))
     }
 
     /// Load configuration from a specific path.
@@ -53,9 +114,9 @@ impl Config {
             return Ok(Self::default());
         }
 
-        let contents = std::fs::read_to_string(path)?;
-        let config: Config = toml::from_str(&contents)?;
-        Ok(config)
+        let mut table: toml::Table = toml::from_str(&std::fs::read_to_string(path)?)?;
+        migrate_nightly_maintenance(&mut table);
+        Ok(toml::Value::Table(table).try_into()?)
     }
 
     /// Save configuration to the default location.
@@ -92,6 +153,9 @@ impl Config {
 [general]
 # Data directory for database and cache
 # data_dir = "~/.local/share/olal"
+# Record which commands you run, and when, for 'olal stats --me'.
+# Stays in the local database and is never uploaded.
+record_usage = true
 
 [ollama]
 # Ollama server address
@@ -106,6 +170,46 @@ embedding_model = "nomic-embed-text"
 # Request timeout in seconds
 timeout_seconds = 120
 
+# Rerank: 'olal ask' fetches 4x the chunks it needs and has a model pick the
+# most relevant ones. Slower, but better on ambiguous questions.
+rerank = false
+
+# Model for reranking (empty: use the default model)
+rerank_model = ""
+
+# Multi-query: 'olal ask' has the model split the question into 2-4 narrower
+# searches and merges their results. Helps broad questions; one extra call.
+multi_query = false
+
+# Multimodal model that captions images with little or no text (llava, moondream)
+vision_model = "llava"
+
+# Prompts are packed with as much content as fits the model's context
+# window, up to this many tokens. Bigger windows use more memory.
+context_tokens = 8192
+
+# For a remote Ollama behind a reverse proxy. The API key is sent as
+# "Authorization: Bearer <key>"; it and header values can be "keyring:<name>"
+# references (see 'olal secret set').
+api_key = ""
+proxy = ""                     # e.g. "http://proxy.local:3128"
+ca_cert = ""                   # PEM file, for a self-signed certificate
+insecure_tls = false           # Skip certificate checks (testing only)
+# [ollama.headers]
+# "CF-Access-Client-Id" = "..."
+
+[ollama.retry]
+# Transient failures (connection refused, timeouts, 429/5xx while a model
+# loads) are retried with jittered exponential backoff.
+max_retries = 3
+initial_backoff_ms = 500
+max_backoff_ms = 10000
+# After this many failures in a row every request pauses for the cooldown,
+# then tries again; batches give up after breaker_max_pauses pauses in a row.
+breaker_threshold = 5
+breaker_cooldown_seconds = 30
+breaker_max_pauses = 3
+
 [watch]
 # Directories to watch for new files
 # Add your screen recordings folder, notes folder, etc.
@@ -126,14 +230,40 @@ ignore_patterns = [
 # How often to check for changes (seconds)
 poll_interval_seconds = 5
 
+# Language spoken in recordings from a directory (ISO 639-1 code), for
+# folders where detection guesses wrong. Elsewhere
+# processing.transcription_language applies.
+[watch.languages]
+# "~/Recordings/Spanish" = "es"
+
+# Settings for one machine, merged over the rest of this file when picked
+# with 'olal --profile <name>' or OLAL_PROFILE. Any key can also be set from
+# the environment as OLAL_<SECTION>__<KEY>, e.g. OLAL_OLLAMA__HOST.
+# [profile.laptop.ollama]
+# host = "http://desktop.local:11434"
+
+# Separate knowledge bases, picked with 'olal --vault <name>' or OLAL_VAULT.
+# Each keeps its own database, artifacts and digests in data_dir, and can
+# watch its own directories instead of [watch].
+# [vaults.work]
+# data_dir = "~/Work/olal"
+# [vaults.work.watch]
+# directories = ["~/Work/Recordings"]
+
 [processing]
 # Video processing options
 extract_audio = true
 transcribe = true
 ocr_enabled = true
 ocr_interval_seconds = 10      # Extract frame every N seconds for OCR
+describe_images = true         # Caption images with little text using ollama.vision_model
+describe_pdf_figures = false   # Caption figures embedded in PDFs too (slow: one call per figure)
 generate_summary = true        # AI-generated summaries for ingested content
 auto_tag = true                # AI-suggested tags for ingested content
+extract_entities = true        # AI-extracted people, organizations, topics
+auto_link = true               # Link similar items after 'olal embed'
+auto_embed = false             # Embed new chunks right after ingest (retried by 'olal worker')
+link_similarity_threshold = 0.8
 detect_chapters = true
 
 # Text chunking for RAG
@@ -142,10 +272,26 @@ chunk_overlap = 50             # Overlap between chunks
 
 # Performance
 max_concurrent_jobs = 2
+parse_threads = 0              # Directory ingest parse threads (0 = one per core)
+fetch_interval_seconds = 10    # Between bookmark page fetches in 'olal worker' (0 = off)
 
 # Whisper model size: tiny, base, small, medium, large
 whisper_model = "base"
 
+# Transcription backend: whisper (openai-whisper), faster-whisper
+# (whisper-ctranslate2, much faster on CPU) or whisperx (word-aligned,
+# can label speakers)
+whisper_backend = "whisper"
+diarize = false                # Label speakers (whisperx; needs HF_TOKEN)
+transcription_language = "auto"  # "auto" detects it, or a code like "en", "es"
+
+# Clean up audio before transcription (needs ffmpeg). Helps with quiet
+# screen recordings; cut silences don't shift transcript timestamps.
+normalize_audio = false        # Loudness normalization (loudnorm)
+trim_silence = false           # Cut long silences
+silence_threshold_db = -40.0   # Quieter than this counts as silence
+min_silence_seconds = 1.0      # Only cut silences at least this long
+
 [youtube]
 # Default style for YouTube metadata generation
 # Options: tutorial, review, vlog, educational
@@ -158,35 +304,190 @@ include_timestamps = true
 include_chapters = true
 
 [ui]
-# Enable colored output
+# Enable colored output (also off when NO_COLOR is set or output isn't a terminal)
 color = true
 
-# Pager for long output
+# Pager for long output like digests and 'olal show --chunks' ("" to disable)
 pager = "less"
 
-# Date format (strftime)
+# Date format (strftime) for dates shown by recent, show, task, digest, ...
 date_format = "%Y-%m-%d %H:%M"
+
+# Desktop notification when a video finishes processing or a digest is ready
+notifications = false
+
+[search]
+# How full-text search splits text into words: unicode61 (exact words) or
+# porter (English stemming, so "running" also finds "runs"). Both ignore
+# case and accents. 'olal config set search.tokenizer' rebuilds the index;
+# after editing this file, the next search does.
+tokenizer = "unicode61"
+
+# When a search finds nothing, suggest a spelling from indexed words
+# ("kubernets" -> "kubernetes") and show its results instead
+typo_tolerance = true
+
+[maintenance]
+# Link discovery, dedupe detection, summary refresh and tag consolidation.
+# Runs as the "maintenance" job in [schedule], or now with 'olal maintain'.
+
+# Maximum summaries to generate per run
+summary_refresh_limit = 20
+
+[review]
+# Hold low-confidence AI suggestions for 'olal review-ai' instead of
+# applying them automatically
+enabled = true
+
+# Minimum confidence (0-1) to apply without review
+tag_threshold = 0.7
+summary_threshold = 0.6
+link_threshold = 0.9           # Cosine similarity; links between
+                               # link_similarity_threshold and this are queued
+
+[retention]
+# Apply retention rules when the "retention" job in [schedule] runs.
+# Preview with 'olal retention --dry-run'.
+enabled = true
+
+# Rules are applied in order. Actions:
+#   archive     - hide matching items (restore with 'olal unarchive')
+#   delete      - delete matching items and everything derived from them
+#   compress    - store matching items' chunk text compressed
+#   purge_queue - remove queue entries with the given status (default failed)
+#
+# [[retention.rules]]
+# action = "archive"
+# item_type = "bookmark"
+# older_than_days = 90
+# unread = true                # Only items never opened with 'olal show'
+#
+# [[retention.rules]]
+# action = "purge_queue"
+# status = "failed"
+# older_than_days = 30
+#
+# [[retention.rules]]
+# action = "compress"
+# item_type = "video"
+# older_than_days = 365
+
+[schedule]
+# Run recurring jobs while 'olal watch' or 'olal worker' is running.
+# List them with 'olal schedule list'; run one now with 'olal schedule run'.
+enabled = true
+
+# Tasks: digest, process_queue, embed, maintenance, retention. Give each
+# job either a daily local time (at) or an interval (every_minutes).
+
+[[schedule.jobs]]
+task = "maintenance"
+at = "03:00"
+
+[[schedule.jobs]]
+task = "retention"
+at = "03:00"
+
+# [[schedule.jobs]]
+# task = "digest"
+# at = "07:30"
+# days = ["mon", "tue", "wed", "thu", "fri"]   # Default: every day
+# period = "day"               # day, week, month
+# email = true                 # Also send it with [digest.smtp]
+#
+# [[schedule.jobs]]
+# task = "embed"
+# every_minutes = 60
+# limit = 500                  # Max chunks (or queue entries) per run
+
+[webhooks]
+# POST a JSON payload to each endpoint when something happens:
+#   {"event": "item.processed", "timestamp": "...", "data": {...}}
+# Events: item.processed, queue.failed, digest.generated, and progress
+# events ingest.started, ingest.parsed, ingest.chunked, ingest.enriched,
+# ingest.failed
+enabled = true
+timeout_seconds = 10
+
+# [[webhooks.endpoints]]
+# url = "https://example.com/hooks/olal"
+# events = ["queue.failed"]    # Default: all but ingest.* progress events
+
+[digest.smtp]
+# Mail server for 'olal digest --email' (and scheduled digests with
+# email = true). The password can also come from OLAL_SMTP_PASSWORD.
+host = ""
+port = 587
+security = "starttls"          # starttls, tls or none
+username = ""
+password = ""                  # Or "keyring:smtp" after 'olal secret set smtp'
+from = ""                      # e.g. "Olal <olal@example.com>"
+to = []                        # e.g. ["me@example.com"]
 "#
         .to_string()
     }
 
     /// Add a directory to the watch list.
     pub fn add_watch_directory(&mut self, path: String) {
-        if !self.watch.directories.contains(&path) {
-            self.watch.directories.push(path);
+        let watch = self.watch_mut();
+        if !watch.directories.contains(&path) {
+            watch.directories.push(path);
+        }
+    }
+
+    /// The watch settings to edit: the active vault's, or `[watch]`.
+    pub fn watch_mut(&mut self) -> &mut WatchConfig {
+        match active_vault().and_then(|name| self.vaults.get_mut(&name)) {
+            Some(vault) => vault.watch.get_or_insert_with(WatchConfig::default),
+            None => &mut self.watch,
         }
     }
 }
 
+/// A separate knowledge base.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VaultConfig {
+    /// Where the vault's database, artifacts and logs live.
+    pub data_dir: String,
+    /// Replaces `[watch]` while the vault is active; without it the vault
+    /// watches nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watch: Option<WatchConfig>,
+}
+
+impl VaultConfig {
+    pub fn new(data_dir: impl Into<String>) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+            watch: None,
+        }
+    }
+
+    /// The data directory, with `~` expanded.
+    pub fn data_dir(&self) -> PathBuf {
+        PathBuf::from(shellexpand::tilde(&self.data_dir).as_ref())
+    }
+
+    pub fn database_file(&self) -> PathBuf {
+        self.data_dir().join("olal.db")
+    }
+}
+
 /// General application settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GeneralConfig {
     pub data_dir: Option<String>,
+    /// Keep a local record of command invocations for `olal stats --me`.
+    pub record_usage: bool,
 }
 
 impl Default for GeneralConfig {
     fn default() -> Self {
-        Self { data_dir: None }
+        Self {
+            data_dir: None,
+            record_usage: true,
+        }
     }
 }
 
@@ -198,6 +499,61 @@ pub struct OllamaConfig {
     pub model: String,
     pub embedding_model: String,
     pub timeout_seconds: u64,
+    /// Rerank retrieved chunks with an LLM before 'olal ask' answers.
+    pub rerank: bool,
+    /// Model for reranking; empty means `model`.
+    pub rerank_model: String,
+    /// Search with model-written sub-queries too for 'olal ask'.
+    pub multi_query: bool,
+    /// Multimodal model for describing images.
+    pub vision_model: String,
+    /// Largest context window to request, in tokens; a model with a
+    /// smaller one uses that.
+    pub context_tokens: usize,
+    /// Sent as `Authorization: Bearer <key>`; may be a `keyring:` reference.
+    pub api_key: String,
+    /// Extra request headers; values may be `keyring:` references.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Proxy for requests to Ollama; empty uses `HTTPS_PROXY`/`HTTP_PROXY`.
+    pub proxy: String,
+    /// PEM file with an extra CA certificate to trust.
+    pub ca_cert: String,
+    /// Skip TLS certificate verification.
+    pub insecure_tls: bool,
+    /// Retrying transient failures.
+    pub retry: RetryConfig,
+}
+
+/// Retries and circuit breaking for requests to Ollama.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Retries of a request that failed transiently (connection refused,
+    /// timeouts, 429 and 5xx responses such as a model still loading).
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled each time, with jitter.
+    pub initial_backoff_ms: u64,
+    pub max_backoff_ms: u64,
+    /// Consecutive failures that open the circuit, pausing all requests.
+    pub breaker_threshold: u32,
+    /// How long an open circuit pauses before trying again.
+    pub breaker_cooldown_seconds: u64,
+    /// Pauses in a row, with no success between them, before giving up.
+    pub breaker_max_pauses: u32,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 10_000,
+            breaker_threshold: 5,
+            breaker_cooldown_seconds: 30,
+            breaker_max_pauses: 3,
+        }
+    }
 }
 
 impl Default for OllamaConfig {
@@ -207,6 +563,17 @@ impl Default for OllamaConfig {
             model: "gpt-oss:20b".to_string(),
             embedding_model: "nomic-embed-text".to_string(),
             timeout_seconds: 120,
+            rerank: false,
+            rerank_model: String::new(),
+            multi_query: false,
+            vision_model: "llava".to_string(),
+            context_tokens: 8192,
+            api_key: String::new(),
+            headers: BTreeMap::new(),
+            proxy: String::new(),
+            ca_cert: String::new(),
+            insecure_tls: false,
+            retry: RetryConfig::default(),
         }
     }
 }
@@ -218,6 +585,8 @@ pub struct WatchConfig {
     pub directories: Vec<String>,
     pub ignore_patterns: Vec<String>,
     pub poll_interval_seconds: u64,
+    /// Transcription language by watched directory.
+    pub languages: BTreeMap<String, String>,
 }
 
 impl Default for WatchConfig {
@@ -232,6 +601,7 @@ impl Default for WatchConfig {
                 "*.part".to_string(),
             ],
             poll_interval_seconds: 5,
+            languages: BTreeMap::new(),
         }
     }
 }
@@ -244,13 +614,39 @@ pub struct ProcessingConfig {
     pub transcribe: bool,
     pub ocr_enabled: bool,
     pub ocr_interval_seconds: u64,
+    /// Caption images with little or no text using a vision model.
+    pub describe_images: bool,
+    /// Caption the figures embedded in PDFs using the vision model.
+    pub describe_pdf_figures: bool,
     pub generate_summary: bool,
     pub auto_tag: bool,
+    pub extract_entities: bool,
+    pub auto_link: bool,
+    /// Generate embeddings for new chunks as part of ingestion.
+    pub auto_embed: bool,
+    pub link_similarity_threshold: f32,
     pub detect_chapters: bool,
     pub chunk_size: usize,
     pub chunk_overlap: usize,
     pub max_concurrent_jobs: usize,
+    /// Threads for parsing files during directory ingest (0 = one per core).
+    pub parse_threads: usize,
+    /// Seconds between page fetches for imported bookmarks in `olal worker`
+    /// (0 = don't fetch).
+    pub fetch_interval_seconds: u64,
     pub whisper_model: String,
+    /// Transcription backend: whisper, faster-whisper or whisperx.
+    pub whisper_backend: String,
+    /// Label speakers in transcripts, with backends that can.
+    pub diarize: bool,
+    /// Language spoken in recordings, or "auto" to detect it.
+    pub transcription_language: String,
+    /// Normalize loudness before transcription.
+    pub normalize_audio: bool,
+    /// Cut long silences before transcription.
+    pub trim_silence: bool,
+    pub silence_threshold_db: f64,
+    pub min_silence_seconds: f64,
 }
 
 impl Default for ProcessingConfig {
@@ -260,13 +656,28 @@ impl Default for ProcessingConfig {
             transcribe: true,
             ocr_enabled: true,
             ocr_interval_seconds: 10,
+            describe_images: true,
+            describe_pdf_figures: false,
             generate_summary: true,
             auto_tag: true,
+            extract_entities: true,
+            auto_link: true,
+            auto_embed: false,
+            link_similarity_threshold: 0.8,
             detect_chapters: true,
             chunk_size: 512,
             chunk_overlap: 50,
             max_concurrent_jobs: 2,
+            parse_threads: 0,
+            fetch_interval_seconds: 10,
             whisper_model: "base".to_string(),
+            whisper_backend: "whisper".to_string(),
+            diarize: false,
+            transcription_language: "auto".to_string(),
+            normalize_audio: false,
+            trim_silence: false,
+            silence_threshold_db: -40.0,
+            min_silence_seconds: 1.0,
         }
     }
 }
@@ -297,6 +708,8 @@ pub struct UiConfig {
     pub color: bool,
     pub pager: String,
     pub date_format: String,
+    /// Desktop notifications when a video finishes processing or a digest is ready.
+    pub notifications: bool,
 }
 
 impl Default for UiConfig {
@@ -305,10 +718,426 @@ impl Default for UiConfig {
             color: true,
             pager: "less".to_string(),
             date_format: "%Y-%m-%d %H:%M".to_string(),
+            notifications: false,
+        }
+    }
+}
+
+/// How the full-text index splits text into terms.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FtsTokenizer {
+    /// Unicode words, matched exactly.
+    #[default]
+    Unicode61,
+    /// Unicode words reduced to their English stems.
+    Porter,
+}
+
+impl FtsTokenizer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FtsTokenizer::Unicode61 => "unicode61",
+            FtsTokenizer::Porter => "porter",
+        }
+    }
+}
+
+/// Full-text search settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchConfig {
+    pub tokenizer: FtsTokenizer,
+    /// Retry a search that finds nothing with misspelled words corrected.
+    pub typo_tolerance: bool,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            tokenizer: FtsTokenizer::default(),
+            typo_tolerance: true,
+        }
+    }
+}
+
+/// Turn the old `[maintenance]` `nightly` and `hour` settings into
+/// maintenance and retention jobs, unless `[schedule]` already lists jobs.
+fn migrate_nightly_maintenance(table: &mut toml::Table) {
+    let Some(maintenance) = table.get_mut("maintenance").and_then(toml::Value::as_table_mut) else {
+        return;
+    };
+    let nightly = maintenance.remove("nightly").and_then(|v| v.as_bool());
+    let hour = maintenance.remove("hour").and_then(|v| v.as_integer());
+    if nightly.is_none() && hour.is_none() {
+        return;
+    }
+
+    let Some(schedule) = table
+        .entry("schedule")
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        .as_table_mut()
+    else {
+        return;
+    };
+    if schedule.contains_key("jobs") {
+        return;
+    }
+    let jobs = match nightly {
+        Some(false) => Vec::new(),
+        _ => ScheduleConfig::nightly_jobs(&format!("{:02}:00", hour.unwrap_or(3).clamp(0, 23))),
+    };
+    if let Ok(jobs) = toml::Value::try_from(jobs) {
+        schedule.insert("jobs".to_string(), jobs);
+    }
+}
+
+/// Knowledge-graph maintenance settings. When it runs is up to `[schedule]`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MaintenanceConfig {
+    pub summary_refresh_limit: usize,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            summary_refresh_limit: 20,
+        }
+    }
+}
+
+/// Confidence thresholds for applying AI enrichment without human review.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReviewConfig {
+    pub enabled: bool,
+    pub tag_threshold: f32,
+    pub summary_threshold: f32,
+    pub link_threshold: f32,
+}
+
+impl ReviewConfig {
+    /// Whether a suggestion with `confidence` needs review given `threshold`.
+    pub fn needs_review(&self, confidence: f32, threshold: f32) -> bool {
+        self.enabled && confidence < threshold
+    }
+
+    /// Similarity below which new links are queued for review, if enabled.
+    pub fn link_review_threshold(&self) -> Option<f32> {
+        self.enabled.then_some(self.link_threshold)
+    }
+}
+
+impl Default for ReviewConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tag_threshold: 0.7,
+            summary_threshold: 0.6,
+            link_threshold: 0.9,
         }
     }
 }
 
+/// What a retention rule does to matching items or queue entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    Archive,
+    Delete,
+    Compress,
+    PurgeQueue,
+}
+
+impl RetentionAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetentionAction::Archive => "archive",
+            RetentionAction::Delete => "delete",
+            RetentionAction::Compress => "compress",
+            RetentionAction::PurgeQueue => "purge_queue",
+        }
+    }
+}
+
+/// A single retention rule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionRule {
+    pub action: RetentionAction,
+    /// Only items of this type (video, bookmark, ...). Ignored by `purge_queue`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item_type: Option<String>,
+    /// Minimum age in days (by creation time).
+    pub older_than_days: i64,
+    /// Only items never opened with `olal show`.
+    #[serde(default)]
+    pub unread: bool,
+    /// Queue status to purge (`purge_queue` only; default "failed").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+}
+
+impl RetentionRule {
+    /// Short human-readable description, e.g. "archive bookmark items older than 90 days (unread)".
+    pub fn describe(&self) -> String {
+        let subject = match self.action {
+            RetentionAction::PurgeQueue => format!(
+                "{} queue entries",
+                self.status.as_deref().unwrap_or("failed")
+            ),
+            _ => match self.item_type {
+                Some(ref t) => format!("{} items", t),
+                None => "items".to_string(),
+            },
+        };
+        let mut out = format!(
+            "{} {} older than {} days",
+            self.action.as_str(),
+            subject,
+            self.older_than_days
+        );
+        if self.unread && self.action != RetentionAction::PurgeQueue {
+            out.push_str(" (unread)");
+        }
+        out
+    }
+}
+
+/// Retention policy settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    pub enabled: bool,
+    pub rules: Vec<RetentionRule>,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            rules: Vec::new(),
+        }
+    }
+}
+
+/// Something the scheduler can run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledTask {
+    /// Write a digest of recent items to the data directory.
+    Digest,
+    /// Process pending queue entries.
+    ProcessQueue,
+    /// Embed chunks that don't have embeddings yet.
+    Embed,
+    /// Run knowledge-graph maintenance and save its report.
+    Maintenance,
+    /// Apply the `[[retention.rules]]`.
+    Retention,
+}
+
+impl ScheduledTask {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScheduledTask::Digest => "digest",
+            ScheduledTask::ProcessQueue => "process_queue",
+            ScheduledTask::Embed => "embed",
+            ScheduledTask::Maintenance => "maintenance",
+            ScheduledTask::Retention => "retention",
+        }
+    }
+}
+
+/// A recurring job.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledJob {
+    pub task: ScheduledTask,
+    /// Name used for last-run bookkeeping (default: the task name).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Daily local time, "HH:MM".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub at: Option<String>,
+    /// Run every N minutes instead of at a fixed time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub every_minutes: Option<u64>,
+    /// Only on these weekdays ("mon".."sun"); empty means every day.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub days: Vec<String>,
+    /// Digest period (`digest` only; default "day").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub period: Option<String>,
+    /// Email the digest too (`digest` only).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub email: bool,
+    /// Maximum entries or chunks per run (`process_queue` and `embed`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+impl ScheduledJob {
+    /// A job running `task` every day at `at` ("HH:MM").
+    pub fn daily(task: ScheduledTask, at: &str) -> Self {
+        Self {
+            task,
+            name: None,
+            at: Some(at.to_string()),
+            every_minutes: None,
+            days: Vec::new(),
+            period: None,
+            email: false,
+            limit: None,
+        }
+    }
+
+    /// The job's bookkeeping name.
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.task.as_str())
+    }
+
+    /// Short human-readable description, e.g. "daily at 07:30 (mon, fri)".
+    pub fn describe(&self) -> String {
+        let mut out = match (&self.at, self.every_minutes) {
+            (Some(at), _) => format!("daily at {}", at),
+            (None, Some(minutes)) => format!("every {} minutes", minutes),
+            (None, None) => "never (no 'at' or 'every_minutes')".to_string(),
+        };
+        if !self.days.is_empty() {
+            out.push_str(&format!(" ({})", self.days.join(", ")));
+        }
+        out
+    }
+}
+
+/// Recurring job settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    pub enabled: bool,
+    pub jobs: Vec<ScheduledJob>,
+}
+
+impl ScheduleConfig {
+    /// Maintenance and retention every night at `at`.
+    fn nightly_jobs(at: &str) -> Vec<ScheduledJob> {
+        vec![
+            ScheduledJob::daily(ScheduledTask::Maintenance, at),
+            ScheduledJob::daily(ScheduledTask::Retention, at),
+        ]
+    }
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            jobs: Self::nightly_jobs("03:00"),
+        }
+    }
+}
+
+/// A webhook receiver.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// Event names to send ("item.processed", ...); empty means all but
+    /// the "ingest.*" progress events.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub events: Vec<String>,
+}
+
+impl WebhookEndpoint {
+    /// Whether this endpoint wants the event `name`.
+    pub fn wants(&self, name: &str) -> bool {
+        if self.events.is_empty() {
+            return !name.starts_with("ingest.");
+        }
+        self.events.iter().any(|e| e == name)
+    }
+}
+
+/// Webhook notification settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WebhooksConfig {
+    pub enabled: bool,
+    pub timeout_seconds: u64,
+    pub endpoints: Vec<WebhookEndpoint>,
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_seconds: 10,
+            endpoints: Vec::new(),
+        }
+    }
+}
+
+/// How to connect to the mail server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Plain connection upgraded with STARTTLS (usually port 587).
+    Starttls,
+    /// TLS from the start (usually port 465).
+    Tls,
+    /// Unencrypted, for local relays.
+    None,
+}
+
+/// Mail server settings for sending digests.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    pub username: String,
+    pub password: String,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: String::new(),
+            port: 587,
+            security: SmtpSecurity::Starttls,
+            username: String::new(),
+            password: String::new(),
+            from: String::new(),
+            to: Vec::new(),
+        }
+    }
+}
+
+impl SmtpConfig {
+    /// Whether enough is set to send mail.
+    pub fn is_configured(&self) -> bool {
+        !self.host.is_empty() && !self.from.is_empty() && !self.to.is_empty()
+    }
+
+    /// The password, from OLAL_SMTP_PASSWORD if set, looked up in the
+    /// keyring if it is a `keyring:` reference.
+    pub fn password(&self) -> ConfigResult<String> {
+        match std::env::var("OLAL_SMTP_PASSWORD").ok().filter(|p| !p.is_empty()) {
+            Some(password) => Ok(password),
+            None => crate::secrets::resolve(&self.password),
+        }
+    }
+}
+
+/// Digest settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub smtp: SmtpConfig,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -362,4 +1191,186 @@ mod tests {
         assert_eq!(config.watch.directories.len(), 1);
         assert_eq!(config.watch.directories[0], "/path/to/watch");
     }
+
+    #[test]
+    fn test_watch_languages_parse() {
+        let config: Config = toml::from_str(&Config::default_config_string()).unwrap();
+        assert!(config.watch.languages.is_empty());
+        assert_eq!(config.processing.transcription_language, "auto");
+
+        let config: Config = toml::from_str(
+            r#"
+            [watch.languages]
+            "~/Recordings/Spanish" = "es"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.watch.languages["~/Recordings/Spanish"], "es");
+    }
+
+    #[test]
+    fn test_retention_rules_parse() {
+        let config: Config = toml::from_str(
+            r#"
+            [retention]
+            [[retention.rules]]
+            action = "archive"
+            item_type = "bookmark"
+            older_than_days = 90
+            unread = true
+
+            [[retention.rules]]
+            action = "purge_queue"
+            older_than_days = 30
+            "#,
+        )
+        .unwrap();
+
+        let rules = &config.retention.rules;
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].action, RetentionAction::Archive);
+        assert_eq!(
+            rules[0].describe(),
+            "archive bookmark items older than 90 days (unread)"
+        );
+        assert_eq!(rules[1].describe(), "purge_queue failed queue entries older than 30 days");
+        assert!(config.retention.enabled);
+    }
+
+    #[test]
+    fn test_schedule_jobs_parse() {
+        let config: Config = toml::from_str(
+            r#"
+            [[schedule.jobs]]
+            task = "digest"
+            at = "07:30"
+            days = ["mon", "fri"]
+
+            [[schedule.jobs]]
+            task = "process_queue"
+            name = "queue"
+            every_minutes = 15
+            "#,
+        )
+        .unwrap();
+
+        let jobs = &config.schedule.jobs;
+        assert!(config.schedule.enabled);
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].task, ScheduledTask::Digest);
+        assert_eq!(jobs[0].name(), "digest");
+        assert_eq!(jobs[0].describe(), "daily at 07:30 (mon, fri)");
+        assert_eq!(jobs[1].name(), "queue");
+        assert_eq!(jobs[1].describe(), "every 15 minutes");
+    }
+
+    #[test]
+    fn test_nightly_maintenance_migrates_to_schedule() {
+        let load = |text: &str| {
+            let mut file = NamedTempFile::new().unwrap();
+            file.write_all(text.as_bytes()).unwrap();
+            Config::load_from(&file.path().to_path_buf()).unwrap()
+        };
+
+        let jobs = Config::default().schedule.jobs;
+        assert_eq!(jobs.len(), 2);
+        assert_eq!(jobs[0].task, ScheduledTask::Maintenance);
+        assert_eq!(jobs[1].describe(), "daily at 03:00");
+
+        let config = load("[maintenance]\nnightly = true\nhour = 5\n");
+        assert_eq!(config.schedule.jobs.len(), 2);
+        assert!(config.schedule.jobs.iter().all(|j| j.at.as_deref() == Some("05:00")));
+
+        let config = load("[maintenance]\nnightly = false\n");
+        assert!(config.schedule.jobs.is_empty());
+
+        // Jobs already listed are left alone
+        let config = load("[maintenance]\nhour = 5\n\n[[schedule.jobs]]\ntask = \"embed\"\nevery_minutes = 60\n");
+        assert_eq!(config.schedule.jobs.len(), 1);
+        assert_eq!(config.schedule.jobs[0].task, ScheduledTask::Embed);
+    }
+
+    #[test]
+    fn test_get() {
+        let mut config = Config::default();
+        config.watch.directories = vec!["/a".to_string(), "/b".to_string()];
+        assert_eq!(config.get("ollama.model").unwrap().as_str(), Some(config.ollama.model.as_str()));
+        assert_eq!(config.get("watch.directories[1]").unwrap().as_str(), Some("/b"));
+        assert_eq!(config.get("ollama.timeout_seconds").unwrap().as_integer(), Some(120));
+        assert!(config.get("ollama").unwrap().is_table());
+        assert!(config.get("ollama.nope").is_none());
+        assert!(config.get("watch.directories[2]").is_none());
+    }
+
+    #[test]
+    fn test_vaults_parse() {
+        let config: Config = toml::from_str(&Config::default_config_string()).unwrap();
+        assert!(config.vaults.is_empty());
+        assert!(matches!(config.vault("work"), Err(ConfigError::UnknownVault(_))));
+
+        let config: Config = toml::from_str(
+            r#"
+            [vaults.personal]
+            data_dir = "/data/personal"
+
+            [vaults.work]
+            data_dir = "/data/work"
+            [vaults.work.watch]
+            directories = ["/work/recordings"]
+            "#,
+        )
+        .unwrap();
+        let work = config.vault("work").unwrap();
+        assert_eq!(work.database_file(), PathBuf::from("/data/work/olal.db"));
+        let watch = work.watch.as_ref().unwrap();
+        assert_eq!(watch.directories, vec!["/work/recordings"]);
+        // Unset watch keys keep their defaults
+        assert_eq!(watch.poll_interval_seconds, 5);
+        assert!(config.vault("personal").unwrap().watch.is_none());
+    }
+
+    #[test]
+    fn test_digest_smtp_parse() {
+        let config: Config = toml::from_str(&Config::default_config_string()).unwrap();
+        assert!(!config.digest.smtp.is_configured());
+
+        let config: Config = toml::from_str(
+            r#"
+            [digest.smtp]
+            host = "smtp.example.com"
+            port = 465
+            security = "tls"
+            from = "olal@example.com"
+            to = ["me@example.com"]
+            "#,
+        )
+        .unwrap();
+        let smtp = &config.digest.smtp;
+        assert!(smtp.is_configured());
+        assert_eq!(smtp.security, SmtpSecurity::Tls);
+        assert_eq!(smtp.port, 465);
+    }
+
+    #[test]
+    fn test_webhook_endpoints_parse() {
+        let config: Config = toml::from_str(
+            r#"
+            [[webhooks.endpoints]]
+            url = "http://localhost:9000/all"
+
+            [[webhooks.endpoints]]
+            url = "http://localhost:9000/failures"
+            events = ["queue.failed"]
+            "#,
+        )
+        .unwrap();
+
+        let endpoints = &config.webhooks.endpoints;
+        assert!(config.webhooks.enabled);
+        assert_eq!(config.webhooks.timeout_seconds, 10);
+        assert!(endpoints[0].wants("item.processed"));
+        assert!(endpoints[1].wants("queue.failed"));
+        assert!(!endpoints[1].wants("item.processed"));
+        assert!(!endpoints[0].wants("ingest.started"));
+    }
 }
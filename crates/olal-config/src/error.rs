@@ -1,5 +1,6 @@
 //! Configuration error types.
 
+use olal_core::{Diagnostic, ErrorCode};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -18,6 +19,49 @@ pub enum ConfigError {
 
     #[error("Invalid configuration: {0}")]
     Invalid(String),
+
+    #[error("Unknown vault: {0}")]
+    UnknownVault(String),
+
+    #[error("Unknown profile: {0}")]
+    UnknownProfile(String),
+
+    #[error("Secret not found in the keyring: {0}")]
+    MissingSecret(String),
+
+    #[error("Keyring unavailable: {0}")]
+    Keyring(String),
 }
 
 pub type ConfigResult<T> = Result<T, ConfigError>;
+
+impl Diagnostic for ConfigError {
+    fn code(&self) -> ErrorCode {
+        ErrorCode::Config
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            ConfigError::ParseError(_) | ConfigError::Invalid(_) => {
+                Some("Fix the file with 'olal config edit'.".to_string())
+            }
+            ConfigError::NoConfigDir => Some("Point olal at a config file with --config <path>.".to_string()),
+            ConfigError::UnknownVault(name) => Some(format!(
+                "Create it with 'olal vault add {} <data-dir>', or see 'olal vault list'.",
+                name
+            )),
+            ConfigError::UnknownProfile(name) => Some(format!(
+                "Add a [profile.{}] section with 'olal config edit'.",
+                name
+            )),
+            ConfigError::MissingSecret(name) => {
+                Some(format!("Store it with 'olal secret set {}'.", name))
+            }
+            ConfigError::Keyring(_) => Some(
+                "Put the value in the config file or an OLAL_<SECTION>__<KEY> variable instead."
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    }
+}
@@ -2,8 +2,12 @@
 
 mod config;
 mod error;
+mod overrides;
 mod paths;
+pub mod secrets;
+pub mod validate;
 
 pub use config::*;
 pub use error::{ConfigError, ConfigResult};
-pub use paths::AppPaths;
+pub use overrides::env_key;
+pub use paths::{active_profile, active_vault, AppPaths, CONFIG_ENV, DB_ENV, PROFILE_ENV, VAULT_ENV};
@@ -0,0 +1,175 @@
+//! Layering over the config file: named profiles and `OLAL_*` environment
+//! variables.
+//!
+//! A profile is a partial config under `[profile.<name>]`, merged key by key
+//! over the rest of the file. Any key can then be overridden from the
+//! environment as `OLAL_<SECTION>__<KEY>`, e.g. `OLAL_OLLAMA__HOST` for
+//! `ollama.host`; nested tables add more `__` separated parts.
+
+use crate::config::Config;
+use crate::error::{ConfigError, ConfigResult};
+use toml::{Table, Value};
+
+/// Prefix of environment variables overriding config keys.
+const ENV_PREFIX: &str = "OLAL_";
+
+/// Separator between key path parts in an override variable.
+const ENV_SEPARATOR: &str = "__";
+
+/// Merge `overlay` into `base`: tables merge recursively, anything else
+/// replaces what was there.
+pub(crate) fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(value)) => merge(existing, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Merge the profile `name` over the config.
+pub(crate) fn apply_profile(table: &mut Table, name: &str) -> ConfigResult<()> {
+    let profile = table
+        .get("profile")
+        .and_then(|profiles| profiles.get(name))
+        .and_then(Value::as_table)
+        .cloned()
+        .ok_or_else(|| ConfigError::UnknownProfile(name.to_string()))?;
+    merge(table, profile);
+    Ok(())
+}
+
+/// The config key path an environment variable overrides, if it is one.
+pub fn env_key(var: &str) -> Option<Vec<String>> {
+    let rest = var.strip_prefix(ENV_PREFIX)?;
+    if !rest.contains(ENV_SEPARATOR) {
+        return None;
+    }
+    let path: Vec<String> = rest.split(ENV_SEPARATOR).map(str::to_lowercase).collect();
+    path.iter().all(|part| !part.is_empty()).then_some(path)
+}
+
+/// Apply `OLAL_<SECTION>__<KEY>` overrides from `vars`.
+///
+/// Values are read as TOML (`true`, `30`, `["a", "b"]`) unless the key
+/// holds a string, so a model called `3` stays a string.
+pub(crate) fn apply_env(table: &mut Table, vars: impl IntoIterator<Item = (String, String)>) {
+    let defaults = Value::try_from(Config::default()).ok();
+
+    for (var, raw) in vars {
+        let Some(path) = env_key(&var) else {
+            continue;
+        };
+        let is_string = lookup(table, &path)
+            .or_else(|| defaults.as_ref().and_then(|d| d.as_table()).and_then(|d| lookup(d, &path)))
+            .is_some_and(Value::is_str);
+        let value = if is_string {
+            Value::String(raw)
+        } else {
+            parse_value(&raw).unwrap_or(Value::String(raw))
+        };
+        set(table, &path, value);
+    }
+}
+
+fn lookup<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    let (last, parents) = path.split_last()?;
+    let mut table = table;
+    for part in parents {
+        table = table.get(part)?.as_table()?;
+    }
+    table.get(last)
+}
+
+fn set(table: &mut Table, path: &[String], value: Value) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+    let mut table = table;
+    for part in parents {
+        let entry = table
+            .entry(part.clone())
+            .or_insert_with(|| Value::Table(Table::new()));
+        if !entry.is_table() {
+            *entry = Value::Table(Table::new());
+        }
+        table = entry.as_table_mut().expect("just made a table");
+    }
+    table.insert(last.clone(), value);
+}
+
+fn parse_value(raw: &str) -> Option<Value> {
+    let mut table: Table = toml::from_str(&format!("value = {}", raw)).ok()?;
+    table.remove("value")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_env_key() {
+        assert_eq!(env_key("OLAL_OLLAMA__HOST").unwrap(), vec!["ollama", "host"]);
+        assert_eq!(
+            env_key("OLAL_OLLAMA__EMBEDDING_MODEL").unwrap(),
+            vec!["ollama", "embedding_model"]
+        );
+        assert_eq!(
+            env_key("OLAL_DIGEST__SMTP__HOST").unwrap(),
+            vec!["digest", "smtp", "host"]
+        );
+        // Plain settings like OLAL_DB are not config keys
+        assert!(env_key("OLAL_DB").is_none());
+        assert!(env_key("OLAL_SMTP_PASSWORD").is_none());
+        assert!(env_key("OLAL_OLLAMA__").is_none());
+        assert!(env_key("HOME").is_none());
+    }
+
+    #[test]
+    fn test_profile_and_env() {
+        let mut table: Table = toml::from_str(
+            r#"
+            [ollama]
+            host = "http://localhost:11434"
+            model = "llama3"
+
+            [profile.laptop.ollama]
+            host = "http://desktop.local:11434"
+
+            [profile.laptop.processing]
+            generate_summary = false
+            "#,
+        )
+        .unwrap();
+        apply_profile(&mut table, "laptop").unwrap();
+        apply_env(
+            &mut table,
+            vars(&[
+                ("OLAL_OLLAMA__MODEL", "3"),
+                ("OLAL_OLLAMA__TIMEOUT_SECONDS", "30"),
+                ("OLAL_WATCH__DIRECTORIES", r#"["/a", "/b"]"#),
+                ("OLAL_DB", "/tmp/olal.db"),
+            ]),
+        );
+        let config: Config = Value::Table(table).try_into().unwrap();
+
+        assert_eq!(config.ollama.host, "http://desktop.local:11434");
+        assert_eq!(config.ollama.model, "3");
+        assert_eq!(config.ollama.timeout_seconds, 30);
+        assert!(!config.processing.generate_summary);
+        assert_eq!(config.watch.directories, vec!["/a", "/b"]);
+        assert!(config.profile.contains_key("laptop"));
+
+        let mut table = Table::new();
+        assert!(matches!(
+            apply_profile(&mut table, "desktop"),
+            Err(ConfigError::UnknownProfile(_))
+        ));
+    }
+}
@@ -1,7 +1,29 @@
 //! Application paths management.
 
 use directories::ProjectDirs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Environment variable overriding the database file (`olal --db`).
+pub const DB_ENV: &str = "OLAL_DB";
+
+/// Environment variable overriding the config file (`olal --config`).
+pub const CONFIG_ENV: &str = "OLAL_CONFIG";
+
+/// Environment variable selecting a vault (`olal --vault`).
+pub const VAULT_ENV: &str = "OLAL_VAULT";
+
+/// Environment variable selecting a config profile (`olal --profile`).
+pub const PROFILE_ENV: &str = "OLAL_PROFILE";
+
+/// The profile selected with `OLAL_PROFILE`, if any.
+pub fn active_profile() -> Option<String> {
+    std::env::var(PROFILE_ENV).ok().filter(|v| !v.is_empty())
+}
+
+/// The vault selected with `OLAL_VAULT`, if any.
+pub fn active_vault() -> Option<String> {
+    std::env::var(VAULT_ENV).ok().filter(|v| !v.is_empty())
+}
 
 /// Manages all application paths following platform conventions.
 #[derive(Debug, Clone)]
@@ -11,11 +33,20 @@ pub struct AppPaths {
     pub config_file: PathBuf,
     pub database_file: PathBuf,
     pub log_dir: PathBuf,
+    pub artifacts_dir: PathBuf,
 }
 
 impl AppPaths {
-    /// Create paths using platform-specific directories.
+    /// Create paths using platform-specific directories, honoring the
+    /// `OLAL_DB` and `OLAL_CONFIG` overrides.
     pub fn new() -> Option<Self> {
+        let paths = Self::platform()?;
+        let env_path = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+        Some(paths.with_overrides(env_path(DB_ENV), env_path(CONFIG_ENV)))
+    }
+
+    /// The platform default paths, ignoring overrides and vaults.
+    pub fn platform() -> Option<Self> {
         let proj_dirs = ProjectDirs::from("com", "olal", "olal")?;
 
         let config_dir = proj_dirs.config_dir().to_path_buf();
@@ -24,12 +55,32 @@ impl AppPaths {
         Some(Self {
             config_file: config_dir.join("config.toml"),
             log_dir: data_dir.join("logs"),
+            artifacts_dir: data_dir.join("artifacts"),
             database_file: data_dir.join("olal.db"),
             config_dir,
             data_dir,
         })
     }
 
+    /// Point at an explicit database and/or config file.
+    ///
+    /// Everything else stored next to the database (logs, artifacts, the
+    /// worker PID file) moves with it, so a throwaway database never touches
+    /// the real data directory.
+    pub fn with_overrides(mut self, database_file: Option<PathBuf>, config_file: Option<PathBuf>) -> Self {
+        if let Some(database_file) = database_file {
+            self.data_dir = parent_dir(&database_file);
+            self.log_dir = self.data_dir.join("logs");
+            self.artifacts_dir = self.data_dir.join("artifacts");
+            self.database_file = database_file;
+        }
+        if let Some(config_file) = config_file {
+            self.config_dir = parent_dir(&config_file);
+            self.config_file = config_file;
+        }
+        self
+    }
+
     /// Create all necessary directories.
     pub fn ensure_dirs(&self) -> std::io::Result<()> {
         std::fs::create_dir_all(&self.config_dir)?;
@@ -38,12 +89,64 @@ impl AppPaths {
         Ok(())
     }
 
+    /// Directory for files derived from an item (thumbnails, clips, audio).
+    pub fn item_artifacts_dir(&self, item_id: &str) -> PathBuf {
+        self.artifacts_dir.join(item_id)
+    }
+
+    /// Directory of video thumbnails, one `<item-id>.jpg` per item.
+    pub fn thumbnails_dir(&self) -> PathBuf {
+        self.data_dir.join("thumbnails")
+    }
+
+    /// Thumbnail of an item.
+    pub fn thumbnail_file(&self, item_id: &str) -> PathBuf {
+        self.thumbnails_dir().join(format!("{}.jpg", item_id))
+    }
+
+    /// Remove an item's thumbnail, if it has one. Thumbnails are only a
+    /// preview, so whatever removes an item should remove its thumbnail too.
+    pub fn remove_thumbnail(&self, item_id: &str) -> std::io::Result<()> {
+        match std::fs::remove_file(self.thumbnail_file(item_id)) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    /// Directory for digests written by scheduled jobs.
+    pub fn digests_dir(&self) -> PathBuf {
+        self.data_dir.join("digests")
+    }
+
+    /// Directory of prompt template overrides (`<prompt>.hbs`).
+    pub fn prompts_dir(&self) -> PathBuf {
+        self.config_dir.join("prompts")
+    }
+
+    /// PID file of the running queue worker.
+    pub fn worker_pid_file(&self) -> PathBuf {
+        self.data_dir.join("worker.pid")
+    }
+
+    /// Log file of the queue worker when started with `--daemon`.
+    pub fn worker_log_file(&self) -> PathBuf {
+        self.log_dir.join("worker.log")
+    }
+
     /// Check if olal has been initialized.
     pub fn is_initialized(&self) -> bool {
         self.config_file.exists() && self.database_file.exists()
     }
 }
 
+/// The directory containing `file`, or `.` for a bare file name.
+fn parent_dir(file: &Path) -> PathBuf {
+    match file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
 impl Default for AppPaths {
     fn default() -> Self {
         Self::new().expect("Could not determine application directories")
@@ -63,4 +166,32 @@ mod tests {
         assert!(paths.config_file.to_string_lossy().contains("config.toml"));
         assert!(paths.database_file.to_string_lossy().contains("olal.db"));
     }
+
+    #[test]
+    fn test_path_overrides() {
+        let paths = AppPaths::new().unwrap().with_overrides(
+            Some(PathBuf::from("/tmp/run/test.db")),
+            Some(PathBuf::from("settings.toml")),
+        );
+
+        assert_eq!(paths.database_file, PathBuf::from("/tmp/run/test.db"));
+        assert_eq!(paths.data_dir, PathBuf::from("/tmp/run"));
+        assert_eq!(paths.worker_pid_file(), PathBuf::from("/tmp/run/worker.pid"));
+        assert_eq!(paths.artifacts_dir, PathBuf::from("/tmp/run/artifacts"));
+        assert_eq!(paths.config_file, PathBuf::from("settings.toml"));
+        assert_eq!(paths.config_dir, PathBuf::from("."));
+    }
+
+    #[test]
+    fn test_remove_thumbnail() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = AppPaths::new().unwrap().with_overrides(Some(dir.path().join("olal.db")), None);
+        std::fs::create_dir_all(paths.thumbnails_dir()).unwrap();
+        std::fs::write(paths.thumbnail_file("abc"), b"jpeg").unwrap();
+
+        paths.remove_thumbnail("abc").unwrap();
+        assert!(!paths.thumbnail_file("abc").exists());
+        // An item without a thumbnail is fine
+        paths.remove_thumbnail("abc").unwrap();
+    }
 }
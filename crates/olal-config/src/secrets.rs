@@ -0,0 +1,155 @@
+//! Credentials kept in the OS keyring instead of the config file.
+//!
+//! A config value of `keyring:<name>` refers to the secret `<name>`, stored
+//! with `olal secret set <name>` under the `olal` service. Values are looked
+//! up only when they are used, so an unreachable keyring only affects the
+//! feature that needs it.
+
+use crate::config::Config;
+use crate::error::{ConfigError, ConfigResult};
+
+/// Prefix marking a config value as a keyring reference.
+pub const KEYRING_PREFIX: &str = "keyring:";
+
+/// The secret a config value refers to, if it is a `keyring:` reference.
+pub fn reference(value: &str) -> Option<&str> {
+    value
+        .strip_prefix(KEYRING_PREFIX)
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+}
+
+/// A config value with a `keyring:` reference replaced by the secret.
+pub fn resolve(value: &str) -> ConfigResult<String> {
+    match reference(value) {
+        Some(name) => get(name),
+        None => Ok(value.to_string()),
+    }
+}
+
+/// The `keyring:` references in a config, as (key, secret name) pairs.
+pub fn references(config: &Config) -> Vec<(String, String)> {
+    let mut found = Vec::new();
+    if let Ok(value) = toml::Value::try_from(config) {
+        collect(&value, String::new(), &mut found);
+    }
+    found
+}
+
+fn collect(value: &toml::Value, key: String, found: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::String(s) => {
+            if let Some(name) = reference(s) {
+                found.push((key, name.to_string()));
+            }
+        }
+        toml::Value::Table(table) => {
+            for (k, v) in table {
+                let key = if key.is_empty() { k.clone() } else { format!("{}.{}", key, k) };
+                collect(v, key, found);
+            }
+        }
+        toml::Value::Array(values) => {
+            for (i, v) in values.iter().enumerate() {
+                collect(v, format!("{}[{}]", key, i), found);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(feature = "keyring")]
+mod backend {
+    use super::*;
+
+    const SERVICE: &str = "olal";
+
+    fn entry(name: &str) -> ConfigResult<keyring::Entry> {
+        keyring::Entry::new(SERVICE, name).map_err(|e| error(name, e))
+    }
+
+    fn error(name: &str, e: keyring::Error) -> ConfigError {
+        match e {
+            keyring::Error::NoEntry => ConfigError::MissingSecret(name.to_string()),
+            e => ConfigError::Keyring(e.to_string()),
+        }
+    }
+
+    pub fn get(name: &str) -> ConfigResult<String> {
+        entry(name)?.get_password().map_err(|e| error(name, e))
+    }
+
+    pub fn set(name: &str, value: &str) -> ConfigResult<()> {
+        entry(name)?.set_password(value).map_err(|e| error(name, e))
+    }
+
+    pub fn delete(name: &str) -> ConfigResult<()> {
+        entry(name)?.delete_password().map_err(|e| error(name, e))
+    }
+}
+
+#[cfg(not(feature = "keyring"))]
+mod backend {
+    use super::*;
+
+    fn unsupported() -> ConfigError {
+        ConfigError::Keyring("this build of olal has no keyring support".to_string())
+    }
+
+    pub fn get(_name: &str) -> ConfigResult<String> {
+        Err(unsupported())
+    }
+
+    pub fn set(_name: &str, _value: &str) -> ConfigResult<()> {
+        Err(unsupported())
+    }
+
+    pub fn delete(_name: &str) -> ConfigResult<()> {
+        Err(unsupported())
+    }
+}
+
+/// Whether this build can reach the OS keyring.
+pub const KEYRING_SUPPORTED: bool = cfg!(feature = "keyring");
+
+/// Read the secret `name` from the keyring.
+pub fn get(name: &str) -> ConfigResult<String> {
+    backend::get(name)
+}
+
+/// Store `value` as the secret `name`, replacing any previous value.
+pub fn set(name: &str, value: &str) -> ConfigResult<()> {
+    backend::set(name, value)
+}
+
+/// Remove the secret `name`.
+pub fn delete(name: &str) -> ConfigResult<()> {
+    backend::delete(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reference() {
+        assert_eq!(reference("keyring:smtp"), Some("smtp"));
+        assert_eq!(reference("keyring: smtp "), Some("smtp"));
+        assert_eq!(reference("keyring:"), None);
+        assert_eq!(reference("hunter2"), None);
+        // Plain values pass through without touching the keyring
+        assert_eq!(resolve("hunter2").unwrap(), "hunter2");
+        assert_eq!(resolve("").unwrap(), "");
+    }
+
+    #[test]
+    fn test_references() {
+        let mut config = Config::default();
+        config.digest.smtp.password = "keyring:smtp".to_string();
+        config.ollama.host = "http://localhost:11434".to_string();
+        assert_eq!(
+            references(&config),
+            vec![("digest.smtp.password".to_string(), "smtp".to_string())]
+        );
+    }
+}
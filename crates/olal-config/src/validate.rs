@@ -0,0 +1,203 @@
+//! Checking a config file: syntax, types and unknown keys, reported with
+//! the line they are on.
+//!
+//! Keys are written as dotted paths with array indexes, e.g.
+//! `watch.directories[1]` or `schedule.jobs[0].at`.
+
+use crate::config::Config;
+use std::ops::Range;
+use toml_edit::{ImDocument, Item, Table, Value};
+
+/// A problem found in a config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    /// 1-based line, when the problem can be placed.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Parse `text` as a config, collecting problems.
+///
+/// Returns the config unless the file fails to parse (bad syntax or a value
+/// of the wrong type); unknown keys are reported but don't stop it.
+pub fn check(text: &str) -> (Option<Config>, Vec<Problem>) {
+    let mut ignored = Vec::new();
+    let result: Result<Config, toml::de::Error> =
+        serde_ignored::deserialize(toml::Deserializer::new(text), |path| {
+            ignored.push(path_to_key(&path))
+        });
+
+    match result {
+        Ok(config) => {
+            let problems = ignored
+                .into_iter()
+                .map(|key| Problem {
+                    line: line_of(text, &key),
+                    message: format!("Unknown key '{}'", key),
+                })
+                .collect();
+            (Some(config), problems)
+        }
+        Err(e) => {
+            let problem = Problem {
+                line: e.span().map(|span| line_at(text, span.start)),
+                message: e.message().trim().to_string(),
+            };
+            (None, vec![problem])
+        }
+    }
+}
+
+/// The line `key` is set on in `text`, if it is there.
+pub fn line_of(text: &str, key: &str) -> Option<usize> {
+    let doc = ImDocument::parse(text).ok()?;
+    let span = span_of(doc.as_table(), &parse_key(key)?)?;
+    Some(line_at(text, span.start))
+}
+
+/// One step of a key path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// Split `a.b[0].c` into segments.
+pub(crate) fn parse_key(key: &str) -> Option<Vec<Segment>> {
+    let mut segments = Vec::new();
+    for part in key.split('.') {
+        let (name, mut rest) = part.split_once('[').map_or((part, ""), |(n, r)| (n, r));
+        if name.is_empty() {
+            return None;
+        }
+        segments.push(Segment::Key(name.to_string()));
+        while !rest.is_empty() {
+            let (index, after) = rest.split_once(']')?;
+            segments.push(Segment::Index(index.parse().ok()?));
+            rest = after.strip_prefix('[').unwrap_or(after);
+        }
+    }
+    Some(segments)
+}
+
+fn path_to_key(path: &serde_ignored::Path) -> String {
+    use serde_ignored::Path;
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{}]", path_to_key(parent), index),
+        Path::Map { parent, key } => {
+            let parent = path_to_key(parent);
+            if parent.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", parent, key)
+            }
+        }
+        Path::Some { parent } | Path::NewtypeStruct { parent } | Path::NewtypeVariant { parent } => {
+            path_to_key(parent)
+        }
+    }
+}
+
+/// A node of a parsed document: tables and arrays of tables are items,
+/// inline values are values.
+#[derive(Clone, Copy)]
+enum Node<'a> {
+    Table(&'a Table),
+    Item(&'a Item),
+    Value(&'a Value),
+}
+
+/// The span of the deepest part of `path` present in `table`.
+fn span_of(table: &Table, path: &[Segment]) -> Option<Range<usize>> {
+    let mut node = Node::Table(table);
+    let mut span = None;
+
+    for segment in path {
+        let (next, key_span) = match (node, segment) {
+            (Node::Table(t), Segment::Key(k)) | (Node::Item(Item::Table(t)), Segment::Key(k)) => {
+                (t.get(k).map(Node::Item), t.key(k).and_then(|k| k.span()))
+            }
+            (Node::Item(Item::ArrayOfTables(a)), Segment::Index(i)) => (a.get(*i).map(Node::Table), None),
+            (Node::Item(Item::Value(v)) | Node::Value(v), segment) => match (v, segment) {
+                (Value::InlineTable(t), Segment::Key(k)) => {
+                    (t.get(k).map(Node::Value), t.key(k).and_then(|k| k.span()))
+                }
+                (Value::Array(a), Segment::Index(i)) => (a.get(*i).map(Node::Value), None),
+                _ => (None, None),
+            },
+            _ => (None, None),
+        };
+        let Some(next) = next else { break };
+        span = key_span.or_else(|| node_span(next)).or(span);
+        node = next;
+    }
+    span
+}
+
+fn node_span(node: Node) -> Option<Range<usize>> {
+    match node {
+        Node::Table(t) => t.span(),
+        Node::Item(i) => i.span(),
+        Node::Value(v) => v.span(),
+    }
+}
+
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEXT: &str = r#"[ollama]
+host = "http://localhost:11434"
+hots = "typo"
+
+[watch]
+directories = [
+    "/a",
+    "/b",
+]
+
+[[schedule.jobs]]
+task = "embed"
+every_minutes = 30
+colour = "red"
+"#;
+
+    #[test]
+    fn test_unknown_keys() {
+        let (config, problems) = check(TEXT);
+        assert!(config.is_some());
+        assert_eq!(
+            problems,
+            vec![
+                Problem { line: Some(3), message: "Unknown key 'ollama.hots'".to_string() },
+                Problem { line: Some(14), message: "Unknown key 'schedule.jobs[0].colour'".to_string() },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_error_line() {
+        let (config, problems) = check("[ollama]\nmodel = \"llama3\"\ntimeout_seconds = \"soon\"\n");
+        assert!(config.is_none());
+        assert_eq!(problems[0].line, Some(3));
+        assert!(problems[0].message.contains("invalid type"), "{}", problems[0].message);
+
+        let (_, problems) = check("[ollama\nmodel = 1\n");
+        assert_eq!(problems[0].line, Some(1));
+    }
+
+    #[test]
+    fn test_line_of() {
+        assert_eq!(line_of(TEXT, "ollama.host"), Some(2));
+        assert_eq!(line_of(TEXT, "watch.directories[1]"), Some(8));
+        assert_eq!(line_of(TEXT, "schedule.jobs[0].every_minutes"), Some(13));
+        // Falls back to the deepest part that exists
+        assert_eq!(line_of(TEXT, "watch.languages"), Some(5));
+        assert_eq!(line_of(TEXT, "digest.smtp.host"), None);
+    }
+}
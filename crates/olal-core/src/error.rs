@@ -1,4 +1,8 @@
 //! Error types for Olal.
+//!
+//! Each crate keeps its own error enum; all of them implement [`Diagnostic`],
+//! which maps every error onto a shared [`ErrorCode`] taxonomy with a stable
+//! name, a process exit code and (where there's an obvious fix) a hint.
 
 use thiserror::Error;
 
@@ -29,6 +33,9 @@ pub enum Error {
     #[error("Ollama error: {0}")]
     Ollama(String),
 
+    #[error("Olal is not initialized")]
+    NotInitialized,
+
     #[error("{0}")]
     Other(String),
 }
@@ -41,3 +48,142 @@ impl From<serde_json::Error> for Error {
         Error::Serialization(err.to_string())
     }
 }
+
+/// Kinds of failure, stable for scripts: the name is printed with the error
+/// and each kind exits with its own status code.
+///
+/// Exit code 2 is left to argument parsing errors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    /// Anything not covered below.
+    Internal,
+    InvalidInput,
+    NotFound,
+    NotInitialized,
+    Config,
+    Database,
+    Io,
+    /// The Ollama server couldn't be reached.
+    OllamaUnavailable,
+    /// A model isn't pulled.
+    ModelMissing,
+    /// Ollama answered with an error, timed out or returned something unusable.
+    Ai,
+    /// An external tool (ffmpeg, whisper, ...) isn't installed.
+    ToolMissing,
+    /// Parsing, transcription or another processing step failed.
+    Processing,
+    Unsupported,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::Internal => "internal",
+            ErrorCode::InvalidInput => "invalid_input",
+            ErrorCode::NotFound => "not_found",
+            ErrorCode::NotInitialized => "not_initialized",
+            ErrorCode::Config => "config",
+            ErrorCode::Database => "database",
+            ErrorCode::Io => "io",
+            ErrorCode::OllamaUnavailable => "ollama_unavailable",
+            ErrorCode::ModelMissing => "model_missing",
+            ErrorCode::Ai => "ai",
+            ErrorCode::ToolMissing => "tool_missing",
+            ErrorCode::Processing => "processing",
+            ErrorCode::Unsupported => "unsupported",
+        }
+    }
+
+    /// Process exit code for this kind of failure.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            ErrorCode::Internal => 1,
+            ErrorCode::InvalidInput => 3,
+            ErrorCode::NotFound => 4,
+            ErrorCode::NotInitialized => 5,
+            ErrorCode::Config => 6,
+            ErrorCode::Database => 7,
+            ErrorCode::Io => 8,
+            ErrorCode::OllamaUnavailable => 10,
+            ErrorCode::ModelMissing => 11,
+            ErrorCode::Ai => 12,
+            ErrorCode::ToolMissing => 13,
+            ErrorCode::Processing => 14,
+            ErrorCode::Unsupported => 15,
+        }
+    }
+
+    /// Every code, for documentation and tests.
+    pub fn all() -> &'static [ErrorCode] {
+        &[
+            ErrorCode::Internal,
+            ErrorCode::InvalidInput,
+            ErrorCode::NotFound,
+            ErrorCode::NotInitialized,
+            ErrorCode::Config,
+            ErrorCode::Database,
+            ErrorCode::Io,
+            ErrorCode::OllamaUnavailable,
+            ErrorCode::ModelMissing,
+            ErrorCode::Ai,
+            ErrorCode::ToolMissing,
+            ErrorCode::Processing,
+            ErrorCode::Unsupported,
+        ]
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An error that knows its [`ErrorCode`] and how to fix it.
+pub trait Diagnostic: std::error::Error {
+    fn code(&self) -> ErrorCode;
+
+    /// What the user can do about it, if there's an obvious fix.
+    fn hint(&self) -> Option<String> {
+        None
+    }
+}
+
+impl Diagnostic for Error {
+    fn code(&self) -> ErrorCode {
+        match self {
+            Error::Database(_) => ErrorCode::Database,
+            Error::Config(_) => ErrorCode::Config,
+            Error::Io(_) => ErrorCode::Io,
+            Error::Serialization(_) | Error::Other(_) => ErrorCode::Internal,
+            Error::NotFound(_) => ErrorCode::NotFound,
+            Error::InvalidInput(_) => ErrorCode::InvalidInput,
+            Error::Processing(_) => ErrorCode::Processing,
+            Error::Ollama(_) => ErrorCode::Ai,
+            Error::NotInitialized => ErrorCode::NotInitialized,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            Error::NotInitialized => Some("Run 'olal init' first.".to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_error_codes_are_distinct() {
+        let names: HashSet<_> = ErrorCode::all().iter().map(|c| c.as_str()).collect();
+        let exits: HashSet<_> = ErrorCode::all().iter().map(|c| c.exit_code()).collect();
+        assert_eq!(names.len(), ErrorCode::all().len());
+        assert_eq!(exits.len(), ErrorCode::all().len());
+        assert!(!exits.contains(&0) && !exits.contains(&2));
+    }
+}
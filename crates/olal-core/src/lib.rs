@@ -3,5 +3,5 @@
 mod error;
 mod types;
 
-pub use error::{Error, Result};
+pub use error::{Diagnostic, Error, ErrorCode, Result};
 pub use types::*;
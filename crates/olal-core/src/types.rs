@@ -1,8 +1,9 @@
 //! Core domain types for Olal.
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
+use std::collections::BTreeMap;
 
 /// Unique identifier for items.
 pub type ItemId = String;
@@ -19,6 +20,9 @@ pub type ProjectId = String;
 /// Unique identifier for tags.
 pub type TagId = String;
 
+/// Unique identifier for entities.
+pub type EntityId = String;
+
 /// Generate a new unique ID.
 pub fn new_id() -> String {
     Uuid::new_v4().to_string()
@@ -50,6 +54,7 @@ impl ItemType {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "video" => Some(ItemType::Video),
@@ -104,6 +109,9 @@ pub struct Item {
     pub created_at: DateTime<Utc>,
     pub processed_at: Option<DateTime<Utc>>,
     pub metadata: serde_json::Value,
+    /// When the item was archived (hidden from search, recent and RAG).
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
 }
 
 impl Item {
@@ -118,6 +126,7 @@ impl Item {
             created_at: Utc::now(),
             processed_at: None,
             metadata: serde_json::json!({}),
+            archived_at: None,
         }
     }
 
@@ -130,6 +139,10 @@ impl Item {
         self.content_hash = Some(hash.into());
         self
     }
+
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
 }
 
 /// A chunk of text content for RAG.
@@ -141,6 +154,11 @@ pub struct Chunk {
     pub content: String,
     pub start_time: Option<f64>,
     pub end_time: Option<f64>,
+    /// First and last page (1-based) of the source the chunk came from.
+    #[serde(default)]
+    pub start_page: Option<u32>,
+    #[serde(default)]
+    pub end_page: Option<u32>,
 }
 
 impl Chunk {
@@ -152,6 +170,8 @@ impl Chunk {
             content: content.into(),
             start_time: None,
             end_time: None,
+            start_page: None,
+            end_page: None,
         }
     }
 
@@ -160,6 +180,21 @@ impl Chunk {
         self.end_time = Some(end);
         self
     }
+
+    pub fn with_pages(mut self, start: u32, end: u32) -> Self {
+        self.start_page = Some(start);
+        self.end_page = Some(end);
+        self
+    }
+
+    /// The chunk's pages for citing it: "p. 12" or "p. 12–13".
+    pub fn page_label(&self) -> Option<String> {
+        let start = self.start_page?;
+        Some(match self.end_page {
+            Some(end) if end > start => format!("p. {}\u{2013}{}", start, end),
+            _ => format!("p. {}", start),
+        })
+    }
 }
 
 /// Status of a task.
@@ -183,6 +218,7 @@ impl TaskStatus {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "pending" => Some(TaskStatus::Pending),
@@ -200,6 +236,68 @@ impl std::fmt::Display for TaskStatus {
     }
 }
 
+/// How often a recurring task comes back.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Recurrence {
+    Daily,
+    /// Monday to Friday.
+    Weekdays,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Recurrence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Recurrence::Daily => "daily",
+            Recurrence::Weekdays => "weekdays",
+            Recurrence::Weekly => "weekly",
+            Recurrence::Monthly => "monthly",
+            Recurrence::Yearly => "yearly",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "daily" | "day" => Some(Recurrence::Daily),
+            "weekdays" | "weekday" => Some(Recurrence::Weekdays),
+            "weekly" | "week" => Some(Recurrence::Weekly),
+            "monthly" | "month" => Some(Recurrence::Monthly),
+            "yearly" | "year" | "annually" => Some(Recurrence::Yearly),
+            _ => None,
+        }
+    }
+
+    /// The next occurrence after `date`. Monthly and yearly dates past the
+    /// end of a shorter month land on its last day.
+    pub fn next_date(&self, date: NaiveDate) -> NaiveDate {
+        let next = match self {
+            Recurrence::Daily => date.checked_add_days(Days::new(1)),
+            Recurrence::Weekdays => {
+                let skip = match date.weekday() {
+                    Weekday::Fri => 3,
+                    Weekday::Sat => 2,
+                    _ => 1,
+                };
+                date.checked_add_days(Days::new(skip))
+            }
+            Recurrence::Weekly => date.checked_add_days(Days::new(7)),
+            Recurrence::Monthly => date.checked_add_months(Months::new(1)),
+            Recurrence::Yearly => date.checked_add_months(Months::new(12)),
+        };
+        next.unwrap_or(date)
+    }
+}
+
+impl std::fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// A task in the task management system.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -210,6 +308,12 @@ pub struct Task {
     pub priority: i32,
     pub project_id: Option<ProjectId>,
     pub due_date: Option<DateTime<Utc>>,
+    /// Completing a recurring task creates its next occurrence.
+    #[serde(default)]
+    pub recurrence: Option<Recurrence>,
+    /// The task this one is a subtask of.
+    #[serde(default)]
+    pub parent_task_id: Option<TaskId>,
     pub created_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
 }
@@ -224,6 +328,8 @@ impl Task {
             priority: 0,
             project_id: None,
             due_date: None,
+            recurrence: None,
+            parent_task_id: None,
             created_at: Utc::now(),
             completed_at: None,
         }
@@ -244,10 +350,48 @@ impl Task {
         self
     }
 
+    pub fn with_due_date(mut self, due_date: DateTime<Utc>) -> Self {
+        self.due_date = Some(due_date);
+        self
+    }
+
+    pub fn with_parent(mut self, parent_task_id: TaskId) -> Self {
+        self.parent_task_id = Some(parent_task_id);
+        self
+    }
+
+    pub fn with_recurrence(mut self, recurrence: Recurrence) -> Self {
+        self.recurrence = Some(recurrence);
+        self
+    }
+
     pub fn mark_done(&mut self) {
         self.status = TaskStatus::Done;
         self.completed_at = Some(Utc::now());
     }
+
+    /// Start work on the task, reopening it if it was done or cancelled.
+    pub fn mark_in_progress(&mut self) {
+        self.status = TaskStatus::InProgress;
+        self.completed_at = None;
+    }
+
+    pub fn mark_cancelled(&mut self) {
+        self.status = TaskStatus::Cancelled;
+        self.completed_at = None;
+    }
+
+    /// A fresh pending copy of this task, due at `due_date`.
+    pub fn next_occurrence(&self, due_date: DateTime<Utc>) -> Self {
+        Self {
+            id: new_id(),
+            status: TaskStatus::Pending,
+            due_date: Some(due_date),
+            created_at: Utc::now(),
+            completed_at: None,
+            ..self.clone()
+        }
+    }
 }
 
 /// Status of a project.
@@ -269,6 +413,7 @@ impl ProjectStatus {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "active" => Some(ProjectStatus::Active),
@@ -312,12 +457,20 @@ impl Project {
     }
 }
 
+/// Separates the levels of a nested tag, as in `project/olal/backend`.
+pub const TAG_SEPARATOR: char = '/';
+
 /// A tag for categorizing items.
+///
+/// Tags nest: the name is the full path (`project/olal/backend`) and
+/// `parent_id` points at the tag one level up (`project/olal`).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tag {
     pub id: TagId,
     pub name: String,
     pub color: Option<String>,
+    #[serde(default)]
+    pub parent_id: Option<TagId>,
 }
 
 impl Tag {
@@ -326,6 +479,7 @@ impl Tag {
             id: new_id(),
             name: name.into(),
             color: None,
+            parent_id: None,
         }
     }
 
@@ -333,6 +487,36 @@ impl Tag {
         self.color = Some(color.into());
         self
     }
+
+    /// Tidy a tag path: trims each level and drops empty ones, so
+    /// `" project / olal/ "` becomes `project/olal`.
+    pub fn normalize_name(name: &str) -> String {
+        name.split(TAG_SEPARATOR)
+            .map(str::trim)
+            .filter(|level| !level.is_empty())
+            .collect::<Vec<_>>()
+            .join(&TAG_SEPARATOR.to_string())
+    }
+
+    /// The path of the parent tag, if this tag is nested.
+    pub fn parent_name(name: &str) -> Option<&str> {
+        name.rsplit_once(TAG_SEPARATOR).map(|(parent, _)| parent)
+    }
+
+    /// The last level of the name (`backend` for `project/olal/backend`).
+    pub fn leaf_name(&self) -> &str {
+        self.name.rsplit(TAG_SEPARATOR).next().unwrap_or(&self.name)
+    }
+
+    /// Whether this tag is `ancestor` or nested under it (case-insensitive).
+    pub fn is_within(&self, ancestor: &str) -> bool {
+        let name = self.name.to_lowercase();
+        let ancestor = ancestor.to_lowercase();
+        name == ancestor
+            || name
+                .strip_prefix(&ancestor)
+                .is_some_and(|rest| rest.starts_with(TAG_SEPARATOR))
+    }
 }
 
 /// Status of a queue item.
@@ -356,6 +540,7 @@ impl QueueStatus {
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "pending" => Some(QueueStatus::Pending),
@@ -419,6 +604,12 @@ pub enum LinkType {
     Continues,
     Parent,
     Child,
+    /// The source has new content at the target's path (a reused filename).
+    Supersedes,
+    /// The source is a translation of the target.
+    Translates,
+    /// The source is a near-duplicate of the target, kept on purpose.
+    Duplicates,
 }
 
 impl LinkType {
@@ -429,9 +620,13 @@ impl LinkType {
             LinkType::Continues => "continues",
             LinkType::Parent => "parent",
             LinkType::Child => "child",
+            LinkType::Supersedes => "supersedes",
+            LinkType::Translates => "translates",
+            LinkType::Duplicates => "duplicates",
         }
     }
 
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s.to_lowercase().as_str() {
             "related" => Some(LinkType::Related),
@@ -439,6 +634,9 @@ impl LinkType {
             "continues" => Some(LinkType::Continues),
             "parent" => Some(LinkType::Parent),
             "child" => Some(LinkType::Child),
+            "supersedes" => Some(LinkType::Supersedes),
+            "translates" => Some(LinkType::Translates),
+            "duplicates" => Some(LinkType::Duplicates),
             _ => None,
         }
     }
@@ -469,8 +667,394 @@ impl Link {
     }
 }
 
-/// Statistics about the database.
+/// Kind of named entity extracted from content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+    Person,
+    Organization,
+    Topic,
+}
+
+impl EntityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityKind::Person => "person",
+            EntityKind::Organization => "organization",
+            EntityKind::Topic => "topic",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "person" | "people" => Some(EntityKind::Person),
+            "organization" | "organisation" | "org" => Some(EntityKind::Organization),
+            "topic" => Some(EntityKind::Topic),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// A named entity (person, organization, topic) mentioned in items.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entity {
+    pub id: EntityId,
+    pub name: String,
+    pub kind: EntityKind,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Entity {
+    pub fn new(name: impl Into<String>, kind: EntityKind) -> Self {
+        Self {
+            id: new_id(),
+            name: name.into(),
+            kind,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Kind of AI-generated enrichment awaiting review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SuggestionKind {
+    Tag,
+    Summary,
+    Link,
+}
+
+impl SuggestionKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuggestionKind::Tag => "tag",
+            SuggestionKind::Summary => "summary",
+            SuggestionKind::Link => "link",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "tag" | "tags" => Some(SuggestionKind::Tag),
+            "summary" | "summaries" => Some(SuggestionKind::Summary),
+            "link" | "links" => Some(SuggestionKind::Link),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SuggestionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Review state of an AI suggestion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuggestionStatus {
+    PendingReview,
+    Accepted,
+    Rejected,
+}
+
+impl SuggestionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SuggestionStatus::PendingReview => "pending_review",
+            SuggestionStatus::Accepted => "accepted",
+            SuggestionStatus::Rejected => "rejected",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "pending_review" | "pending" => Some(SuggestionStatus::PendingReview),
+            "accepted" => Some(SuggestionStatus::Accepted),
+            "rejected" => Some(SuggestionStatus::Rejected),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for SuggestionStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// An AI-generated tag, summary or link held back for human review.
+///
+/// `value` is the tag name, the summary text, or the target item ID.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiSuggestion {
+    pub id: String,
+    pub item_id: ItemId,
+    pub kind: SuggestionKind,
+    pub value: String,
+    pub confidence: f32,
+    pub status: SuggestionStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl AiSuggestion {
+    pub fn new(
+        item_id: impl Into<ItemId>,
+        kind: SuggestionKind,
+        value: impl Into<String>,
+        confidence: f32,
+    ) -> Self {
+        Self {
+            id: new_id(),
+            item_id: item_id.into(),
+            kind,
+            value: value.into(),
+            confidence,
+            status: SuggestionStatus::PendingReview,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// What a piece of digest feedback is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeedbackTarget {
+    /// A tag/topic covered by the digest.
+    Topic,
+    /// Where items came from (capture, youtube, a watched folder, ...).
+    Source,
+}
+
+impl FeedbackTarget {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FeedbackTarget::Topic => "topic",
+            FeedbackTarget::Source => "source",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "topic" | "tag" => Some(FeedbackTarget::Topic),
+            "source" => Some(FeedbackTarget::Source),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FeedbackTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Accumulated digest feedback for a topic or source.
+///
+/// Each thumbs-up adds one to `score`, each thumbs-down subtracts one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestPreference {
+    pub target: FeedbackTarget,
+    pub name: String,
+    pub score: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Numbers describing one digest period, kept so later digests can
+/// compare against it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DigestStats {
+    pub item_count: usize,
+    /// Items per tag.
+    #[serde(default)]
+    pub topics: BTreeMap<String, usize>,
+    #[serde(default)]
+    pub tasks_created: usize,
+    #[serde(default)]
+    pub tasks_completed: usize,
+}
+
+impl DigestStats {
+    /// Share of the period's new tasks that were completed in it, if any
+    /// were created.
+    pub fn task_completion_rate(&self) -> Option<f64> {
+        (self.tasks_created > 0)
+            .then(|| (self.tasks_completed as f64 / self.tasks_created as f64).min(1.0))
+    }
+}
+
+/// A generated digest, kept as history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DigestRecord {
+    pub id: String,
+    /// "daily", "weekly", "monthly" or "since <date>".
+    pub period: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// The digest text as the model wrote it.
+    pub content: String,
+    pub stats: DigestStats,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DigestRecord {
+    pub fn new(
+        period: impl Into<String>,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        content: impl Into<String>,
+        stats: DigestStats,
+    ) -> Self {
+        Self {
+            id: new_id(),
+            period: period.into(),
+            period_start,
+            period_end,
+            content: content.into(),
+            stats,
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// How well an item was remembered in a spaced-repetition review.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecallRating {
+    /// Forgotten; start over.
+    Again,
+    /// Remembered with difficulty.
+    Hard,
+    Good,
+    /// Remembered effortlessly.
+    Easy,
+}
+
+impl RecallRating {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecallRating::Again => "again",
+            RecallRating::Hard => "hard",
+            RecallRating::Good => "good",
+            RecallRating::Easy => "easy",
+        }
+    }
+
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "1" | "again" => Some(RecallRating::Again),
+            "2" | "hard" => Some(RecallRating::Hard),
+            "3" | "good" => Some(RecallRating::Good),
+            "4" | "easy" => Some(RecallRating::Easy),
+            _ => None,
+        }
+    }
+
+    /// SM-2 response quality (0-5).
+    fn quality(&self) -> f64 {
+        match self {
+            RecallRating::Again => 1.0,
+            RecallRating::Hard => 3.0,
+            RecallRating::Good => 4.0,
+            RecallRating::Easy => 5.0,
+        }
+    }
+}
+
+impl std::fmt::Display for RecallRating {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Spaced-repetition schedule for an item (SM-2).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Review {
+    pub item_id: ItemId,
+    /// Ease factor; grows with easy recalls, never below 1.3.
+    pub ease: f64,
+    pub interval_days: i64,
+    /// Successful recalls in a row.
+    pub repetitions: i32,
+    pub due_at: DateTime<Utc>,
+    pub last_reviewed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Review {
+    /// A new schedule, due now.
+    pub fn new(item_id: ItemId) -> Self {
+        let now = Utc::now();
+        Self {
+            item_id,
+            ease: 2.5,
+            interval_days: 0,
+            repetitions: 0,
+            due_at: now,
+            last_reviewed_at: None,
+            created_at: now,
+        }
+    }
+
+    /// Reschedule after a review at `now`.
+    pub fn record(&mut self, rating: RecallRating, now: DateTime<Utc>) {
+        let q = rating.quality();
+        if rating == RecallRating::Again {
+            self.repetitions = 0;
+            self.interval_days = 1;
+        } else {
+            self.interval_days = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval_days as f64 * self.ease).round() as i64,
+            };
+            self.repetitions += 1;
+        }
+        self.ease = (self.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(1.3);
+        self.due_at = now + chrono::Duration::days(self.interval_days);
+        self.last_reviewed_at = Some(now);
+    }
+
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.due_at <= now
+    }
+}
+
+/// A question/answer pair generated from an item, for studying.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Flashcard {
+    pub id: String,
+    pub item_id: ItemId,
+    pub question: String,
+    pub answer: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Flashcard {
+    pub fn new(item_id: ItemId, question: impl Into<String>, answer: impl Into<String>) -> Self {
+        Self {
+            id: new_id(),
+            item_id,
+            question: question.into(),
+            answer: answer.into(),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// Statistics about the database.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DatabaseStats {
     pub total_items: i64,
     pub items_by_type: std::collections::HashMap<String, i64>,
@@ -485,24 +1069,6 @@ pub struct DatabaseStats {
     pub database_size_bytes: i64,
 }
 
-impl Default for DatabaseStats {
-    fn default() -> Self {
-        Self {
-            total_items: 0,
-            items_by_type: std::collections::HashMap::new(),
-            total_chunks: 0,
-            total_tasks: 0,
-            pending_tasks: 0,
-            total_projects: 0,
-            total_tags: 0,
-            queue_pending: 0,
-            queue_processing: 0,
-            queue_failed: 0,
-            database_size_bytes: 0,
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -529,6 +1095,14 @@ mod tests {
         assert!(!item.id.is_empty());
     }
 
+    #[test]
+    fn test_chunk_page_label() {
+        let chunk = Chunk::new("item".to_string(), 0, "text");
+        assert_eq!(chunk.page_label(), None);
+        assert_eq!(chunk.clone().with_pages(12, 12).page_label().as_deref(), Some("p. 12"));
+        assert_eq!(chunk.with_pages(12, 13).page_label().as_deref(), Some("p. 12\u{2013}13"));
+    }
+
     #[test]
     fn test_task_workflow() {
         let mut task = Task::new("Complete Phase 1").with_priority(1);
@@ -540,5 +1114,68 @@ mod tests {
 
         assert_eq!(task.status, TaskStatus::Done);
         assert!(task.completed_at.is_some());
+
+        task.mark_in_progress();
+        assert_eq!(task.status, TaskStatus::InProgress);
+        assert!(task.completed_at.is_none());
+
+        task.mark_cancelled();
+        assert_eq!(task.status, TaskStatus::Cancelled);
+    }
+
+    #[test]
+    fn test_nested_tag_names() {
+        assert_eq!(Tag::normalize_name(" project / olal/ "), "project/olal");
+        assert_eq!(Tag::parent_name("project/olal/backend"), Some("project/olal"));
+        assert_eq!(Tag::parent_name("rust"), None);
+
+        let tag = Tag::new("project/olal/backend");
+        assert_eq!(tag.leaf_name(), "backend");
+        assert!(tag.is_within("Project/olal"));
+        assert!(tag.is_within("project/olal/backend"));
+        assert!(!tag.is_within("project/ol"));
+        assert!(!tag.is_within("project/olal/backend/api"));
+    }
+
+    #[test]
+    fn test_recurrence() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+
+        assert_eq!(Recurrence::from_str("Weekly"), Some(Recurrence::Weekly));
+        assert_eq!(Recurrence::from_str("fortnightly"), None);
+
+        // 2024-03-15 is a Friday
+        assert_eq!(Recurrence::Daily.next_date(date(2024, 3, 15)), date(2024, 3, 16));
+        assert_eq!(Recurrence::Weekdays.next_date(date(2024, 3, 15)), date(2024, 3, 18));
+        assert_eq!(Recurrence::Weekly.next_date(date(2024, 3, 15)), date(2024, 3, 22));
+        assert_eq!(Recurrence::Monthly.next_date(date(2024, 1, 31)), date(2024, 2, 29));
+        assert_eq!(Recurrence::Yearly.next_date(date(2024, 2, 29)), date(2025, 2, 28));
+
+        let task = Task::new("Water plants").with_recurrence(Recurrence::Weekly);
+        let next = task.next_occurrence(Utc::now());
+        assert_ne!(next.id, task.id);
+        assert_eq!(next.recurrence, Some(Recurrence::Weekly));
+        assert_eq!(next.status, TaskStatus::Pending);
+    }
+
+    #[test]
+    fn test_review_schedule() {
+        let mut review = Review::new("item".to_string());
+        let now = Utc::now();
+        assert!(review.is_due(now));
+
+        review.record(RecallRating::Good, now);
+        assert_eq!(review.interval_days, 1);
+        review.record(RecallRating::Good, now);
+        assert_eq!(review.interval_days, 6);
+        review.record(RecallRating::Easy, now);
+        assert_eq!(review.interval_days, 15);
+        assert!(review.ease > 2.5);
+        assert!(!review.is_due(now));
+
+        review.record(RecallRating::Again, now);
+        assert_eq!(review.repetitions, 0);
+        assert_eq!(review.interval_days, 1);
+        assert!(review.ease >= 1.3);
     }
 }
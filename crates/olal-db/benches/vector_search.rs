@@ -0,0 +1,75 @@
+//! Vector search latency over 10K and 100K stored embeddings.
+//!
+//! Run with `cargo bench -p olal-db --bench vector_search`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use olal_core::{Chunk, Item, ItemType};
+use olal_db::Database;
+
+/// Dimensions of nomic-embed-text, the default embedding model.
+const DIMENSIONS: usize = 768;
+
+/// Chunks per item, roughly a long note.
+const CHUNKS_PER_ITEM: usize = 20;
+
+/// Deterministic pseudo-random vectors (xorshift), so runs are comparable.
+struct Vectors(u64);
+
+impl Vectors {
+    fn next(&mut self) -> Vec<f32> {
+        (0..DIMENSIONS)
+            .map(|_| {
+                self.0 ^= self.0 << 13;
+                self.0 ^= self.0 >> 7;
+                self.0 ^= self.0 << 17;
+                (self.0 >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+            })
+            .collect()
+    }
+}
+
+fn database_with_embeddings(count: usize, vectors: &mut Vectors) -> Database {
+    let db = Database::open_in_memory().unwrap();
+
+    let batch: Vec<(Item, Vec<Chunk>)> = (0..count / CHUNKS_PER_ITEM)
+        .map(|i| {
+            let item = Item::new(ItemType::Note, format!("Note {}", i));
+            let chunks = (0..CHUNKS_PER_ITEM)
+                .map(|j| Chunk::new(item.id.clone(), j as i32, format!("Chunk {} of note {}", j, i)))
+                .collect();
+            (item, chunks)
+        })
+        .collect();
+    db.create_items_with_chunks(&batch).unwrap();
+
+    for (_, chunks) in &batch {
+        for chunk in chunks {
+            db.store_embedding(&chunk.id, &vectors.next(), "bench").unwrap();
+        }
+    }
+    db
+}
+
+fn vector_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("vector_search");
+    group.sample_size(10);
+
+    for count in [10_000, 100_000] {
+        let mut vectors = Vectors(0x9E37_79B9_7F4A_7C15);
+        let db = database_with_embeddings(count, &mut vectors);
+
+        // A fresh query each iteration, so the query cache never answers
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter_batched(
+                || vectors.next(),
+                |query| db.vector_search(&query, 10, None).unwrap(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, vector_search);
+criterion_main!(benches);
@@ -0,0 +1,169 @@
+//! In-process cache of recent search results.
+//!
+//! Type-ahead search in the TUI and repeated shell queries run the same
+//! FTS and vector searches over and over. Results are kept here, keyed by
+//! the query and its parameters, and dropped whenever this process writes
+//! to the database: every pooled connection registers an update hook that
+//! bumps a shared write generation. Writes from other processes (e.g.
+//! `olal watch`) aren't seen by the hook, so entries also expire after
+//! [`MAX_AGE`].
+
+use crate::filter::SearchFilter;
+use crate::operations::items::SearchMatch;
+use crate::operations::vectors::SimilarityResult;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Maximum number of cached queries.
+const CAPACITY: usize = 128;
+
+/// How long a result stays valid without a local write.
+const MAX_AGE: Duration = Duration::from_secs(30);
+
+/// A cached search result.
+#[derive(Clone)]
+pub(crate) enum Cached {
+    Matches(Vec<SearchMatch>),
+    Similar(Vec<SimilarityResult>),
+}
+
+struct Entry {
+    generation: u64,
+    stored_at: Instant,
+    value: Cached,
+}
+
+#[derive(Default)]
+struct Entries {
+    map: HashMap<String, Entry>,
+    /// Insertion order, oldest first, for eviction.
+    order: VecDeque<String>,
+}
+
+/// Shared between clones of a [`Database`](crate::Database).
+#[derive(Clone, Default)]
+pub(crate) struct QueryCache {
+    generation: Arc<AtomicU64>,
+    entries: Arc<Mutex<Entries>>,
+}
+
+impl QueryCache {
+    /// A handle for connection update hooks to mark the cache stale.
+    pub(crate) fn write_counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.generation)
+    }
+
+    /// The current write generation. Read it *before* running a query and
+    /// pass it to [`put`](Self::put), so a write during the query isn't missed.
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Acquire)
+    }
+
+    /// Look up a fresh result.
+    pub(crate) fn get(&self, key: &str) -> Option<Cached> {
+        let generation = self.generation();
+        let mut entries = self.entries.lock().ok()?;
+        match entries.map.get(key) {
+            Some(entry) if entry.generation == generation && entry.stored_at.elapsed() < MAX_AGE => {
+                Some(entry.value.clone())
+            }
+            Some(_) => {
+                entries.map.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store a result computed at `generation`.
+    pub(crate) fn put(&self, key: String, generation: u64, value: Cached) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if entries.map.len() >= CAPACITY && !entries.map.contains_key(&key) {
+            while let Some(oldest) = entries.order.pop_front() {
+                if entries.map.remove(&oldest).is_some() {
+                    break;
+                }
+            }
+        }
+        if !entries.map.contains_key(&key) {
+            entries.order.push_back(key.clone());
+        }
+        entries.map.insert(
+            key,
+            Entry {
+                generation,
+                stored_at: Instant::now(),
+                value,
+            },
+        );
+    }
+}
+
+/// Cache key for a full-text item search.
+pub(crate) fn fts_key(query: &str, limit: i64, filter: &SearchFilter) -> String {
+    format!("fts\0{}\0{}\0{}", query, limit, filter.cache_key())
+}
+
+/// Cache key for a vector search.
+pub(crate) fn vector_key(
+    query_vector: &[f32],
+    limit: usize,
+    min_similarity: f32,
+    filter: &SearchFilter,
+) -> String {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    for value in query_vector {
+        value.to_bits().hash(&mut hasher);
+    }
+    format!(
+        "vec\0{:x}\0{}\0{}\0{}\0{}",
+        hasher.finish(),
+        query_vector.len(),
+        limit,
+        min_similarity,
+        filter.cache_key()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Database;
+    use olal_core::{Chunk, Item, ItemType};
+
+    #[test]
+    fn test_cache_eviction() {
+        let cache = QueryCache::default();
+        for i in 0..CAPACITY + 5 {
+            cache.put(format!("q{}", i), 0, Cached::Matches(Vec::new()));
+        }
+        assert!(cache.get("q0").is_none());
+        assert!(cache.get(&format!("q{}", CAPACITY + 4)).is_some());
+        assert_eq!(cache.entries.lock().unwrap().map.len(), CAPACITY);
+    }
+
+    #[test]
+    fn test_search_invalidated_by_writes() {
+        let db = Database::open_in_memory().unwrap();
+        let first = Item::new(ItemType::Note, "First");
+        db.create_item(&first).unwrap();
+        db.create_chunk(&Chunk::new(first.id.clone(), 0, "ownership and borrowing"))
+            .unwrap();
+
+        assert_eq!(db.search_items("ownership", None).unwrap().len(), 1);
+        assert!(db.cache.get(&fts_key("ownership", 20, &SearchFilter::default())).is_some());
+
+        // A write through any connection makes the cached result stale
+        let second = Item::new(ItemType::Note, "Second");
+        db.create_item(&second).unwrap();
+        db.create_chunk(&Chunk::new(second.id.clone(), 0, "ownership rules"))
+            .unwrap();
+        assert!(db.cache.get(&fts_key("ownership", 20, &SearchFilter::default())).is_none());
+        assert_eq!(db.search_items("ownership", None).unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,67 @@
+//! Chunk text compression.
+//!
+//! Compressed chunk text is stored as a zstd BLOB in the same `content`
+//! column as plain TEXT, so the storage class tells readers whether to
+//! decompress. The full-text index is contentless and only ever fed plain
+//! text, so compression doesn't affect search.
+//!
+//! New chunks of at least [`MIN_COMPRESS_BYTES`] are compressed on write;
+//! shorter ones gain too little to be worth the CPU.
+
+use crate::error::{DbError, DbResult};
+use rusqlite::types::{Type, ValueRef};
+
+/// zstd level: fast, with most of the size win for prose.
+const LEVEL: i32 = 3;
+
+/// Chunks shorter than this are stored as plain text.
+pub(crate) const MIN_COMPRESS_BYTES: usize = 1024;
+
+/// Compress chunk text if it is long enough and actually shrinks.
+pub(crate) fn maybe_compress(text: &str) -> DbResult<Option<Vec<u8>>> {
+    if text.len() < MIN_COMPRESS_BYTES {
+        return Ok(None);
+    }
+    let compressed = compress_text(text)?;
+    Ok((compressed.len() < text.len()).then_some(compressed))
+}
+
+/// Compress chunk text.
+pub(crate) fn compress_text(text: &str) -> DbResult<Vec<u8>> {
+    zstd::encode_all(text.as_bytes(), LEVEL)
+        .map_err(|e| DbError::Other(format!("Compression failed: {}", e)))
+}
+
+/// Decompress chunk text stored by [`compress_text`].
+pub(crate) fn decompress_text(bytes: &[u8]) -> std::io::Result<String> {
+    let raw = zstd::decode_all(bytes)?;
+    String::from_utf8(raw).map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
+/// Read chunk content at `idx`, decompressing it if it was stored compressed.
+pub(crate) fn chunk_content(row: &rusqlite::Row, idx: usize) -> rusqlite::Result<String> {
+    match row.get_ref(idx)? {
+        ValueRef::Blob(bytes) => decompress_text(bytes)
+            .map_err(|e| rusqlite::Error::FromSqlConversionFailure(idx, Type::Blob, Box::new(e))),
+        _ => row.get(idx),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let text = "The quick brown fox. ".repeat(100);
+        let compressed = compress_text(&text).unwrap();
+        assert!(compressed.len() < text.len() / 4);
+        assert_eq!(decompress_text(&compressed).unwrap(), text);
+    }
+
+    #[test]
+    fn test_maybe_compress() {
+        assert!(maybe_compress("short chunk").unwrap().is_none());
+        assert!(maybe_compress(&"words ".repeat(500)).unwrap().is_some());
+    }
+}
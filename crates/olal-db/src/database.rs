@@ -1,27 +1,53 @@
 //! Database connection and pool management.
 
+use crate::cache::QueryCache;
 use crate::error::{DbError, DbResult};
 use crate::migrations;
 use r2d2::{Pool, PooledConnection};
 use r2d2_sqlite::SqliteConnectionManager;
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tracing::info;
 
 /// Type alias for connection pool.
 pub type ConnectionPool = Pool<SqliteConnectionManager>;
 pub type PooledConn = PooledConnection<SqliteConnectionManager>;
 
+/// Whether this build can open SQLCipher-encrypted databases.
+pub const ENCRYPTION_SUPPORTED: bool = cfg!(feature = "encryption");
+
+/// The first bytes of every plaintext SQLite database file.
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
 /// Main database handle.
 #[derive(Clone)]
 pub struct Database {
     pool: ConnectionPool,
+    key: Option<String>,
+    pub(crate) cache: QueryCache,
 }
 
 impl Database {
     /// Open a database at the specified path.
     pub fn open<P: AsRef<Path>>(path: P) -> DbResult<Self> {
+        Self::open_with_key(path, None)
+    }
+
+    /// Open a database, unlocking it with `key` if it is encrypted.
+    ///
+    /// Requires the `encryption` feature when a key is given. A new file
+    /// opened with a key is created encrypted.
+    pub fn open_with_key<P: AsRef<Path>>(path: P, key: Option<&str>) -> DbResult<Self> {
         let path = path.as_ref();
 
+        if key.is_some() && !ENCRYPTION_SUPPORTED {
+            return Err(DbError::Other(
+                "olal was built without encryption support (rebuild with --features encryption)"
+                    .to_string(),
+            ));
+        }
+
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             std::fs::create_dir_all(parent).map_err(|e| DbError::Other(e.to_string()))?;
@@ -29,8 +55,16 @@ impl Database {
 
         info!("Opening database at: {}", path.display());
 
+        let cache = QueryCache::default();
+        let writes = cache.write_counter();
+        let init_key = key.map(str::to_string);
         let manager = SqliteConnectionManager::file(path)
-            .with_init(|conn| {
+            .with_init(move |conn| {
+                // The key must be set before anything else touches the file
+                if let Some(ref key) = init_key {
+                    conn.pragma_update(None, "key", key)?;
+                }
+                track_writes(conn, &writes);
                 conn.execute_batch(
                     "PRAGMA journal_mode = WAL;
                      PRAGMA synchronous = NORMAL;
@@ -50,13 +84,20 @@ impl Database {
             migrations::initialize_schema(&conn)?;
         }
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            key: key.map(str::to_string),
+            cache,
+        })
     }
 
     /// Open an in-memory database (for testing).
     pub fn open_in_memory() -> DbResult<Self> {
+        let cache = QueryCache::default();
+        let writes = cache.write_counter();
         let manager = SqliteConnectionManager::memory()
-            .with_init(|conn| {
+            .with_init(move |conn| {
+                track_writes(conn, &writes);
                 conn.execute_batch("PRAGMA foreign_keys = ON;")?;
                 Ok(())
             });
@@ -71,7 +112,11 @@ impl Database {
             migrations::initialize_schema(&conn)?;
         }
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            key: None,
+            cache,
+        })
     }
 
     /// Get a connection from the pool.
@@ -92,6 +137,86 @@ impl Database {
         Ok(())
     }
 
+    /// Copy the database to `dest` using SQLite's online backup API.
+    ///
+    /// Safe to run while other connections (e.g. `olal watch`) are writing.
+    /// Encrypted databases are exported with the same key, so the copy stays
+    /// encrypted.
+    pub fn backup_to<P: AsRef<Path>>(&self, dest: P) -> DbResult<()> {
+        match self.key {
+            Some(ref key) => self.export_encrypted(dest.as_ref(), key),
+            None => {
+                let conn = self.conn()?;
+                conn.backup(rusqlite::DatabaseName::Main, dest, None)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Write an encrypted copy of this database to `dest` using `key`.
+    ///
+    /// This is the plaintext-to-encrypted migration path: export, verify
+    /// the copy opens with the key, then swap it in place of the original.
+    pub fn encrypt_to<P: AsRef<Path>>(&self, dest: P, key: &str) -> DbResult<()> {
+        if !ENCRYPTION_SUPPORTED {
+            return Err(DbError::Other(
+                "olal was built without encryption support (rebuild with --features encryption)"
+                    .to_string(),
+            ));
+        }
+        if key.is_empty() {
+            return Err(DbError::Other("Encryption key cannot be empty".to_string()));
+        }
+        self.export_encrypted(dest.as_ref(), key)
+    }
+
+    fn export_encrypted(&self, dest: &Path, key: &str) -> DbResult<()> {
+        let conn = self.conn()?;
+        let version: i32 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        conn.execute(
+            "ATTACH DATABASE ?1 AS encrypted KEY ?2",
+            rusqlite::params![dest.to_string_lossy(), key],
+        )?;
+        let result = conn
+            .query_row("SELECT sqlcipher_export('encrypted')", [], |_| Ok(()))
+            .and_then(|_| {
+                conn.execute_batch(&format!("PRAGMA encrypted.user_version = {}", version))
+            });
+        conn.execute("DETACH DATABASE encrypted", [])?;
+        result?;
+        Ok(())
+    }
+
+    /// Whether the file at `path` is encrypted (not a plaintext SQLite file).
+    ///
+    /// Missing or empty files count as not encrypted.
+    pub fn is_encrypted<P: AsRef<Path>>(path: P) -> bool {
+        use std::io::Read;
+
+        let mut header = [0u8; 16];
+        match std::fs::File::open(path) {
+            Ok(mut file) => match file.read_exact(&mut header) {
+                Ok(()) => header != SQLITE_HEADER,
+                Err(_) => false,
+            },
+            Err(_) => false,
+        }
+    }
+
+    /// Read the schema version of a database file without migrating it.
+    pub fn schema_version_of<P: AsRef<Path>>(path: P, key: Option<&str>) -> DbResult<i32> {
+        let conn = rusqlite::Connection::open_with_flags(
+            path,
+            rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY,
+        )?;
+        if let Some(key) = key {
+            conn.pragma_update(None, "key", key)?;
+        }
+        let version = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        Ok(version)
+    }
+
     /// Run integrity check on the database.
     pub fn integrity_check(&self) -> DbResult<bool> {
         let conn = self.conn()?;
@@ -100,6 +225,15 @@ impl Database {
     }
 }
 
+/// Bump the query cache's write generation on every row change made
+/// through `conn`.
+fn track_writes(conn: &rusqlite::Connection, writes: &Arc<AtomicU64>) {
+    let writes = Arc::clone(writes);
+    conn.update_hook(Some(move |_: rusqlite::hooks::Action, _: &str, _: &str, _: i64| {
+        writes.fetch_add(1, Ordering::AcqRel);
+    }));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,7 +241,54 @@ mod tests {
     #[test]
     fn test_open_in_memory() {
         let db = Database::open_in_memory();
-        assert!(db.is_ok());
+        assert!(db.is_ok(), "{:?}", db.err());
+    }
+
+    #[test]
+    fn test_backup_to() {
+        let dir = std::env::temp_dir().join(format!("olal-db-backup-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let dest = dir.join("backup.db");
+
+        let db = Database::open_in_memory().unwrap();
+        db.create_item(&olal_core::Item::new(olal_core::ItemType::Note, "Keep me"))
+            .unwrap();
+        db.backup_to(&dest).unwrap();
+
+        assert_eq!(
+            Database::schema_version_of(&dest, None).unwrap(),
+            migrations::SCHEMA_VERSION
+        );
+        assert!(!Database::is_encrypted(&dest));
+        let restored = Database::open(&dest).unwrap();
+        assert_eq!(restored.recent_items(None).unwrap().len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_encryption_requires_feature() {
+        let dir = std::env::temp_dir().join(format!("olal-db-key-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("plain.db");
+        let encrypted = dir.join("encrypted.db");
+
+        let db = Database::open(&plain).unwrap();
+        db.create_item(&olal_core::Item::new(olal_core::ItemType::Note, "Secret"))
+            .unwrap();
+
+        if ENCRYPTION_SUPPORTED {
+            db.encrypt_to(&encrypted, "hunter2").unwrap();
+            assert!(Database::is_encrypted(&encrypted));
+            assert!(Database::open(&encrypted).is_err());
+            let unlocked = Database::open_with_key(&encrypted, Some("hunter2")).unwrap();
+            assert_eq!(unlocked.recent_items(None).unwrap()[0].title, "Secret");
+        } else {
+            assert!(db.encrypt_to(&encrypted, "hunter2").is_err());
+            assert!(Database::open_with_key(&encrypted, Some("hunter2")).is_err());
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 
     #[test]
@@ -1,5 +1,6 @@
 //! Database error types.
 
+use olal_core::{Diagnostic, ErrorCode};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -24,3 +25,31 @@ pub enum DbError {
 }
 
 pub type DbResult<T> = Result<T, DbError>;
+
+impl Diagnostic for DbError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            DbError::NotFound(_) => ErrorCode::NotFound,
+            _ => ErrorCode::Database,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            DbError::Sqlite(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == rusqlite::ErrorCode::DatabaseBusy =>
+            {
+                Some("Another olal process is writing; try again in a moment.".to_string())
+            }
+            DbError::Sqlite(rusqlite::Error::SqliteFailure(e, _))
+                if e.code == rusqlite::ErrorCode::NotADatabase =>
+            {
+                Some("If the database is encrypted, set OLAL_DB_KEY to its key.".to_string())
+            }
+            DbError::Migration(_) => {
+                Some("Restore a backup with 'olal restore <archive>' or re-run 'olal init'.".to_string())
+            }
+            _ => None,
+        }
+    }
+}
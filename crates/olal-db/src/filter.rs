@@ -0,0 +1,79 @@
+//! Filters that scope a search to some of the items.
+
+use chrono::{DateTime, Utc};
+use olal_core::ItemType;
+use rusqlite::types::Value;
+
+/// Restricts full-text, vector and hybrid searches. Every condition is
+/// applied in SQL; unset fields don't filter.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilter {
+    pub item_type: Option<ItemType>,
+    /// Items with this tag or one of its sub-tags (case-insensitive).
+    pub tag: Option<String>,
+    /// Items attached to the project with this name (case-insensitive).
+    pub project: Option<String>,
+    /// Items created at or after this time.
+    pub since: Option<DateTime<Utc>>,
+    /// Items created at or before this time.
+    pub until: Option<DateTime<Utc>>,
+    /// Only the item with this ID.
+    pub item_id: Option<String>,
+}
+
+impl SearchFilter {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Conditions on the items table (aliased `i`), each starting with
+    /// " AND ", numbered from `?{first}`, and their parameters.
+    pub(crate) fn to_sql(&self, first: usize) -> (String, Vec<Value>) {
+        let mut sql = String::new();
+        let mut params: Vec<Value> = Vec::new();
+
+        if let Some(item_type) = self.item_type {
+            sql.push_str(&format!(" AND i.item_type = ?{}", first + params.len()));
+            params.push(Value::Text(item_type.as_str().to_string()));
+        }
+        if let Some(ref tag) = self.tag {
+            sql.push_str(&format!(
+                " AND EXISTS (
+                   WITH RECURSIVE tree(id) AS (
+                       SELECT id FROM tags WHERE name = ?{} COLLATE NOCASE
+                       UNION SELECT t.id FROM tags t INNER JOIN tree ON t.parent_id = tree.id
+                   )
+                   SELECT 1 FROM item_tags it WHERE it.item_id = i.id AND it.tag_id IN tree)",
+                first + params.len()
+            ));
+            params.push(Value::Text(tag.clone()));
+        }
+        if let Some(ref project) = self.project {
+            sql.push_str(&format!(
+                " AND EXISTS (SELECT 1 FROM item_projects ip JOIN projects p ON p.id = ip.project_id
+                   WHERE ip.item_id = i.id AND p.name = ?{} COLLATE NOCASE)",
+                first + params.len()
+            ));
+            params.push(Value::Text(project.clone()));
+        }
+        if let Some(since) = self.since {
+            sql.push_str(&format!(" AND i.created_at >= ?{}", first + params.len()));
+            params.push(Value::Text(since.to_rfc3339()));
+        }
+        if let Some(until) = self.until {
+            sql.push_str(&format!(" AND i.created_at <= ?{}", first + params.len()));
+            params.push(Value::Text(until.to_rfc3339()));
+        }
+        if let Some(ref item_id) = self.item_id {
+            sql.push_str(&format!(" AND i.id = ?{}", first + params.len()));
+            params.push(Value::Text(item_id.clone()));
+        }
+
+        (sql, params)
+    }
+
+    /// Distinguishes filtered results in the query cache.
+    pub(crate) fn cache_key(&self) -> String {
+        format!("{:?}", self)
+    }
+}
@@ -0,0 +1,121 @@
+//! Near-duplicate fingerprints.
+//!
+//! Content hashes only catch byte-identical files. A simhash of an item's
+//! text changes by a few bits when the text changes by a few words, so the
+//! same article saved twice with a different header, or a note with a typo
+//! fixed, ends up with fingerprints a small [`distance`] apart. Chunks are
+//! fingerprinted the same way, so boilerplate is only embedded once.
+
+use crate::fts::words;
+use sha2::{Digest, Sha256};
+
+/// Words per shingle. Hashing runs of words rather than single words
+/// keeps texts that share a vocabulary but not sentences apart.
+const SHINGLE_WORDS: usize = 3;
+
+/// Fewest words fingerprinted; shorter texts are too alike by chance.
+pub const MIN_WORDS: usize = 12;
+
+/// Largest [`distance`] at which two fingerprints count as near
+/// duplicates by default. Unrelated texts differ in about half of the 64
+/// bits; a page of text with a sentence changed, in two to four; a short
+/// note with one, in up to eight.
+pub const DEFAULT_MAX_DISTANCE: u32 = 8;
+
+/// Largest [`distance`] at which two chunks share an embedding. Stricter
+/// than for items, since the embedding of one stands in for the other; a
+/// footer whose page number changes moves three to five bits.
+pub const NEAR_IDENTICAL_DISTANCE: u32 = 5;
+
+/// The 64-bit simhash of `text`'s word shingles, ignoring case and
+/// punctuation, or `None` if it has fewer than [`MIN_WORDS`] words.
+pub fn simhash(text: &str) -> Option<u64> {
+    let words: Vec<String> = words(text).map(|(_, word)| word.to_lowercase()).collect();
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+
+    // Each bit is set if most shingle hashes have it set
+    let mut weights = [0i64; 64];
+    for shingle in words.windows(SHINGLE_WORDS) {
+        let hash = shingle_hash(&shingle.join(" "));
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash & (1 << bit) != 0 {
+                *weight += 1;
+            } else {
+                *weight -= 1;
+            }
+        }
+    }
+
+    Some(
+        weights
+            .iter()
+            .enumerate()
+            .filter(|(_, weight)| **weight > 0)
+            .fold(0, |hash, (bit, _)| hash | (1 << bit)),
+    )
+}
+
+/// A hash of `text`'s words, ignoring case, whitespace and punctuation, so
+/// text that differs only in those hashes the same.
+pub fn text_hash(text: &str) -> String {
+    let mut hasher = Sha256::new();
+    for (_, word) in words(text) {
+        hasher.update(word.to_lowercase().as_bytes());
+        hasher.update([0]);
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Bits that differ between two fingerprints.
+pub fn distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// A stable 64-bit hash (the standard library's may change between
+/// releases, and fingerprints are stored).
+fn shingle_hash(shingle: &str) -> u64 {
+    let digest = Sha256::digest(shingle.as_bytes());
+    u64::from_le_bytes(digest[..8].try_into().expect("digest has 32 bytes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ARTICLE: &str = "Ownership is a set of rules that govern how a Rust program manages \
+        memory. All programs have to manage the way they use a computer's memory while running. \
+        Some languages have garbage collection that regularly looks for no-longer-used memory as \
+        the program runs; in other languages, the programmer must explicitly allocate and free \
+        the memory. Rust uses a third approach: memory is managed through a system of ownership \
+        with a set of rules that the compiler checks.";
+
+    #[test]
+    fn test_simhash_near_duplicates() {
+        let original = simhash(ARTICLE).unwrap();
+        assert_eq!(simhash(&ARTICLE.to_uppercase()), Some(original));
+
+        let edited = ARTICLE.replace("third approach", "different approach");
+        let saved_again = format!("Saved from the Rust book\n\n{}\n\nShare this page", edited);
+        assert!(distance(original, simhash(&saved_again).unwrap()) <= DEFAULT_MAX_DISTANCE);
+
+        let other = "A sourdough starter needs flour and water fed daily. Bake the loaf in a \
+            hot dutch oven for a crisp crust and an open crumb, then let it cool for an hour \
+            before slicing so the inside sets.";
+        assert!(distance(original, simhash(other).unwrap()) > 2 * DEFAULT_MAX_DISTANCE);
+    }
+
+    #[test]
+    fn test_text_hash() {
+        assert_eq!(text_hash("Page footer -- (c) 2024"), text_hash("page footer\n\n(C) 2024."));
+        assert_ne!(text_hash("Page footer 2024"), text_hash("Page footer 2025"));
+        assert_ne!(text_hash("ab c"), text_hash("a bc"));
+    }
+
+    #[test]
+    fn test_simhash_too_short() {
+        assert_eq!(simhash("Buy milk and eggs"), None);
+        assert_eq!(simhash(""), None);
+    }
+}
@@ -0,0 +1,456 @@
+//! Full-text index tokenizers and query helpers.
+//!
+//! User queries are rewritten by [`match_query`] before they reach FTS5, so
+//! punctuation can't cause syntax errors. The FTS index is contentless
+//! (chunk text may be stored compressed), so FTS5's `snippet()` and
+//! `highlight()` have no text to work with. Snippets are cut from the
+//! decompressed chunk here instead, matching the query's terms the way the
+//! index does: ignoring case and diacritics, by stem with the porter
+//! tokenizer, and by prefix for `term*`.
+
+mod porter;
+
+use std::collections::HashMap;
+use std::ops::Range;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// Query words that are FTS5 operators rather than terms.
+const OPERATORS: &[&str] = &["AND", "OR", "NOT", "NEAR"];
+
+/// Operators kept by [`match_query`].
+const BOOLEAN_OPERATORS: &[&str] = &["AND", "OR", "NOT"];
+
+/// Shortest word [`spelling_suggestion`] corrects; shorter ones are too
+/// often abbreviations.
+const MIN_CORRECTED_LEN: usize = 4;
+
+/// How the full-text index splits text into terms. Both ignore case and
+/// diacritics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Tokenizer {
+    /// Unicode words, matched exactly.
+    #[default]
+    Unicode61,
+    /// Unicode words reduced to their English stems, so "running" matches
+    /// "runs".
+    Porter,
+}
+
+impl Tokenizer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tokenizer::Unicode61 => "unicode61",
+            Tokenizer::Porter => "porter",
+        }
+    }
+
+    /// The FTS5 `tokenize` option.
+    pub(crate) fn option(&self) -> &'static str {
+        match self {
+            Tokenizer::Unicode61 => "unicode61 remove_diacritics 2",
+            Tokenizer::Porter => "porter unicode61 remove_diacritics 2",
+        }
+    }
+
+    /// `word` as this tokenizer indexes it: lowercased, without diacritics
+    /// and, for [`Tokenizer::Porter`], stemmed.
+    pub(crate) fn normalize(&self, word: &str) -> String {
+        let folded: String = word
+            .nfd()
+            .filter(|c| !is_combining_mark(*c))
+            .flat_map(char::to_lowercase)
+            .collect();
+        match self {
+            Tokenizer::Unicode61 => folded,
+            Tokenizer::Porter => porter::stem(&folded),
+        }
+    }
+
+    /// The tokenizer a `CREATE VIRTUAL TABLE` statement uses, if it is one
+    /// of these.
+    pub(crate) fn from_table_sql(sql: &str) -> Option<Self> {
+        [Tokenizer::Unicode61, Tokenizer::Porter]
+            .into_iter()
+            .find(|t| sql.contains(&format!("tokenize='{}'", t.option())))
+    }
+}
+
+/// Rewrite a user's search into a safe FTS5 query.
+///
+/// Words and `"quoted phrases"` are quoted, so punctuation in them is
+/// matched rather than parsed; a trailing `*` keeps them prefix matches.
+/// `AND`, `OR` and `NOT` between terms stay operators; everything else,
+/// like parentheses and column filters, is taken literally.
+pub fn match_query(query: &str) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut after_term = false;
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+
+        let mut text = String::new();
+        let quoted = c == '"';
+        if quoted {
+            chars.next();
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                text.push(c);
+            }
+        } else {
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                text.push(c);
+                chars.next();
+            }
+        }
+
+        let mut prefix = false;
+        while chars.peek() == Some(&'*') {
+            prefix = true;
+            chars.next();
+        }
+        if !quoted && text.ends_with('*') {
+            prefix = true;
+        }
+        let text = if quoted { text.trim() } else { text.trim_end_matches('*') };
+
+        if !quoted && BOOLEAN_OPERATORS.contains(&text) {
+            // Only between two terms
+            if after_term {
+                parts.push(text.to_string());
+                after_term = false;
+            }
+        } else if !text.is_empty() {
+            let star = if prefix { "*" } else { "" };
+            parts.push(format!("\"{}\"{}", text, star));
+            after_term = true;
+        }
+    }
+    if !after_term {
+        parts.pop();
+    }
+    parts.join(" ")
+}
+
+/// Respell `query` with words from `vocabulary` (indexed term to the
+/// number of documents containing it), or `None` if no word changed.
+///
+/// Each word missing from the vocabulary is replaced by the term fewest
+/// edits away (one edit per four letters, at most two), the most common
+/// on ties. Quoted phrases, prefixes and operators are left alone.
+pub fn spelling_suggestion(query: &str, vocabulary: &HashMap<String, i64>) -> Option<String> {
+    let mut changed = false;
+    let words: Vec<String> = query
+        .split(' ')
+        .map(|token| {
+            let plain = token.chars().count() >= MIN_CORRECTED_LEN
+                && token.chars().all(char::is_alphanumeric)
+                && !OPERATORS.contains(&token);
+            let lower = token.to_lowercase();
+            if !plain || vocabulary.contains_key(&lower) {
+                return token.to_string();
+            }
+            match closest_term(&lower, vocabulary) {
+                Some(term) => {
+                    changed = true;
+                    term.to_string()
+                }
+                None => token.to_string(),
+            }
+        })
+        .collect();
+    changed.then(|| words.join(" "))
+}
+
+/// The term of `vocabulary` closest to `word`, if any is close enough.
+fn closest_term<'a>(word: &str, vocabulary: &'a HashMap<String, i64>) -> Option<&'a str> {
+    let len = word.chars().count();
+    let max_edits = (len / 4).clamp(1, 2);
+    vocabulary
+        .iter()
+        .filter(|(term, _)| term.chars().count().abs_diff(len) <= max_edits)
+        .filter_map(|(term, docs)| {
+            let edits = edit_distance(word, term);
+            (edits <= max_edits).then_some((edits, -docs, term.as_str()))
+        })
+        .min()
+        .map(|(_, _, term)| term)
+}
+
+/// Edits (insertions, deletions, substitutions and swaps of neighbouring
+/// letters) to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows[i][j]: edits between a[..i] and b[..j]
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, edits) in rows[0].iter_mut().enumerate() {
+        *edits = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut edits = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                edits = edits.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = edits;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// A passage of a chunk around the query's matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// The passage, with "..." where text was cut.
+    pub text: String,
+    /// Byte ranges of `text` that match a query term, in order.
+    pub highlights: Vec<Range<usize>>,
+}
+
+/// A term of a full-text query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Term {
+    /// Normalized by the tokenizer.
+    text: String,
+    prefix: bool,
+}
+
+impl Term {
+    /// Whether the term matches a word normalized by the same tokenizer.
+    fn matches(&self, word: &str) -> bool {
+        if self.prefix {
+            word.starts_with(&self.text)
+        } else {
+            word == self.text
+        }
+    }
+}
+
+/// The terms of an FTS5 query, without operators, quotes, grouping or
+/// column filters.
+fn query_terms(query: &str, tokenizer: Tokenizer) -> Vec<Term> {
+    let mut terms = Vec::new();
+    for token in query.split_whitespace() {
+        if OPERATORS.contains(&token) {
+            continue;
+        }
+        let token = token.rsplit(':').next().unwrap_or(token);
+        let prefix = token.trim_end_matches(['"', ')']).ends_with('*');
+        let mut words: Vec<&str> = words(token).map(|(_, word)| word).collect();
+        let last = words.pop();
+        for word in words {
+            terms.push(Term { text: tokenizer.normalize(word), prefix: false });
+        }
+        if let Some(word) = last {
+            terms.push(Term { text: tokenizer.normalize(word), prefix });
+        }
+    }
+    terms
+}
+
+/// The words of `text` (runs of alphanumeric characters) with their byte
+/// offsets.
+pub(crate) fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+/// Cut the `max_words` words of `text` with the most matches of `query`'s
+/// terms, marking the matches. Words match as `tokenizer` indexes them.
+/// Without matches, the passage is the start of the text.
+pub fn snippet(text: &str, query: &str, max_words: usize, tokenizer: Tokenizer) -> Snippet {
+    let terms = query_terms(query, tokenizer);
+    let is_match = |word: &str| {
+        let word = tokenizer.normalize(word);
+        terms.iter().any(|t| t.matches(&word))
+    };
+    let words: Vec<(usize, &str)> = words(text).collect();
+    let max_words = max_words.max(1);
+    let matched: Vec<bool> = words.iter().map(|(_, word)| is_match(word)).collect();
+
+    // The window with the most matches; ties go to the earliest
+    let mut best = 0;
+    let mut best_count = 0;
+    let mut count = 0;
+    for i in 0..words.len() {
+        count += matched[i] as usize;
+        if i >= max_words {
+            count -= matched[i - max_words] as usize;
+        }
+        let start = (i + 1).saturating_sub(max_words);
+        if count > best_count {
+            best = start;
+            best_count = count;
+        }
+    }
+    // Center a lone match rather than ending the passage on it
+    if best_count == 1 && words.len() > max_words {
+        let hit = (best..words.len()).find(|&i| matched[i]).unwrap_or(best);
+        best = hit.saturating_sub(max_words / 2).min(words.len() - max_words);
+    }
+
+    let end = (best + max_words).min(words.len());
+    let Some(&(from, _)) = words.get(best) else {
+        return Snippet { text: String::new(), highlights: Vec::new() };
+    };
+    let (last_offset, last_word) = words[end - 1];
+    let to = if end == words.len() {
+        text.trim_end().len()
+    } else {
+        last_offset + last_word.len()
+    };
+
+    let mut snippet = String::new();
+    if best > 0 {
+        snippet.push_str("...");
+    }
+    let base = snippet.len();
+    snippet.push_str(&text[from..to].split_whitespace().collect::<Vec<_>>().join(" "));
+    if end < words.len() {
+        snippet.push_str("...");
+    }
+
+    // Offsets changed with the collapsed whitespace, so find the words again
+    let highlights = self::words(&snippet[base..])
+        .filter(|(_, word)| is_match(word))
+        .map(|(offset, word)| base + offset..base + offset + word.len())
+        .collect();
+    Snippet { text: snippet, highlights }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marked(snippet: &Snippet) -> String {
+        let mut out = String::new();
+        let mut at = 0;
+        for range in &snippet.highlights {
+            out.push_str(&snippet.text[at..range.start]);
+            out.push_str(&format!("[{}]", &snippet.text[range.clone()]));
+            at = range.end;
+        }
+        out.push_str(&snippet.text[at..]);
+        out
+    }
+
+    #[test]
+    fn test_match_query() {
+        assert_eq!(match_query("rust ownership"), r#""rust" "ownership""#);
+        assert_eq!(match_query("quer* \"single owner\""), r#""quer"* "single owner""#);
+        assert_eq!(match_query("\"own\"* OR borrow"), r#""own"* OR "borrow""#);
+        // Punctuation and stray operators don't reach the parser
+        assert_eq!(match_query("C++ (async) NOT"), r#""C++" "(async)""#);
+        assert_eq!(match_query("OR rust's AND AND io:read"), r#""rust's" AND "io:read""#);
+        assert_eq!(match_query("\"unclosed phrase"), r#""unclosed phrase""#);
+        assert_eq!(match_query("* \"\""), "");
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kubernets", "kubernetes"), 1);
+        assert_eq!(edit_distance("ownership", "onwership"), 1);
+        assert_eq!(edit_distance("flour", "floor"), 1);
+        assert_eq!(edit_distance("", "rust"), 4);
+        assert_eq!(edit_distance("café", "cafe"), 1);
+    }
+
+    #[test]
+    fn test_spelling_suggestion() {
+        let vocabulary: HashMap<String, i64> = [("kubernetes", 3), ("deploy", 2), ("deploys", 5), ("rust", 9)]
+            .into_iter()
+            .map(|(term, docs)| (term.to_string(), docs))
+            .collect();
+        let suggest = |query: &str| spelling_suggestion(query, &vocabulary);
+
+        assert_eq!(suggest("Kubernets deploy").as_deref(), Some("kubernetes deploy"));
+        // The more common of two equally close terms
+        assert_eq!(suggest("deplos").as_deref(), Some("deploys"));
+        assert_eq!(suggest("rust deploy"), None);
+        assert_eq!(suggest("rsut").as_deref(), Some("rust"));
+        // Too short, too far, or not a plain word
+        assert_eq!(suggest("rst"), None);
+        assert_eq!(suggest("kbrnts"), None);
+        assert_eq!(suggest("\"kubernets\" kuber* OR"), None);
+    }
+
+    #[test]
+    fn test_tokenizer_from_table_sql() {
+        for tokenizer in [Tokenizer::Unicode61, Tokenizer::Porter] {
+            let sql = format!("CREATE VIRTUAL TABLE t USING fts5(content, tokenize='{}')", tokenizer.option());
+            assert_eq!(Tokenizer::from_table_sql(&sql), Some(tokenizer));
+        }
+        assert_eq!(Tokenizer::from_table_sql("CREATE VIRTUAL TABLE t USING fts5(content)"), None);
+    }
+
+    #[test]
+    fn test_query_terms() {
+        let terms = query_terms("\"single owner\" OR borrow* NOT content:drop", Tokenizer::Unicode61);
+        let texts: Vec<(&str, bool)> = terms.iter().map(|t| (t.text.as_str(), t.prefix)).collect();
+        assert_eq!(
+            texts,
+            vec![("single", false), ("owner", false), ("borrow", true), ("drop", false)]
+        );
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = "Graydon Hoare started Rust. Ownership means each value has a single owner, \
+                    and the value is dropped when its owner goes out of scope.";
+
+        let snippet = snippet(text, "owner", 8, Tokenizer::Unicode61);
+        assert_eq!(
+            marked(&snippet),
+            "...value has a single [owner], and the value..."
+        );
+
+        // The passage with the most matches wins
+        let snippet = super::snippet(text, "owner* OR scope", 6, Tokenizer::Unicode61);
+        assert_eq!(marked(&snippet), "...its [owner] goes out of [scope].");
+        assert!(super::snippet(text, "owner*", 100, Tokenizer::Unicode61).text.starts_with("Graydon"));
+    }
+
+    #[test]
+    fn test_snippet_without_match() {
+        let snippet = snippet("Bake the loaf\n\nin a   hot oven.", "ownership", 4, Tokenizer::Unicode61);
+        assert_eq!(snippet.text, "Bake the loaf in...");
+        assert!(snippet.highlights.is_empty());
+        assert_eq!(super::snippet("", "rust", 5, Tokenizer::Unicode61).text, "");
+    }
+
+    #[test]
+    fn test_snippet_unicode61() {
+        let text = "Met at the Café Rouge; the cafes nearby were running late.";
+        let snippet = snippet(text, "cafe", 20, Tokenizer::Unicode61);
+        assert_eq!(marked(&snippet), "Met at the [Café] Rouge; the cafes nearby were running late.");
+
+        let snippet = super::snippet(text, "runs", 20, Tokenizer::Unicode61);
+        assert!(snippet.highlights.is_empty());
+    }
+
+    #[test]
+    fn test_snippet_porter() {
+        let text = "Met at the Café Rouge; the cafes nearby were running late.";
+        let snippet = snippet(text, "cafe", 20, Tokenizer::Porter);
+        assert_eq!(marked(&snippet), "Met at the [Café] Rouge; the [cafes] nearby were running late.");
+
+        let snippet = super::snippet(text, "runs", 20, Tokenizer::Porter);
+        assert_eq!(marked(&snippet), "Met at the Café Rouge; the cafes nearby were [running] late.");
+    }
+}
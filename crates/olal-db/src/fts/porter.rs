@@ -0,0 +1,236 @@
+//! The Porter stemmer, as applied by FTS5's `porter` tokenizer.
+//!
+//! Follows Martin Porter's reference implementation, so snippets
+//! highlight the words the index matched.
+
+/// Words outside this length are left alone, as FTS5 does.
+const MIN_LEN: usize = 3;
+const MAX_LEN: usize = 64;
+
+const STEP2: &[(&str, &str)] = &[
+    ("ational", "ate"),
+    ("tional", "tion"),
+    ("enci", "ence"),
+    ("anci", "ance"),
+    ("izer", "ize"),
+    ("bli", "ble"),
+    ("alli", "al"),
+    ("entli", "ent"),
+    ("eli", "e"),
+    ("ousli", "ous"),
+    ("ization", "ize"),
+    ("ation", "ate"),
+    ("ator", "ate"),
+    ("alism", "al"),
+    ("iveness", "ive"),
+    ("fulness", "ful"),
+    ("ousness", "ous"),
+    ("aliti", "al"),
+    ("iviti", "ive"),
+    ("biliti", "ble"),
+    ("logi", "log"),
+];
+
+const STEP3: &[(&str, &str)] = &[
+    ("icate", "ic"),
+    ("ative", ""),
+    ("alize", "al"),
+    ("iciti", "ic"),
+    ("ical", "ic"),
+    ("ful", ""),
+    ("ness", ""),
+];
+
+const STEP4: &[(&str, &str)] = &[
+    ("al", ""),
+    ("ance", ""),
+    ("ence", ""),
+    ("er", ""),
+    ("ic", ""),
+    ("able", ""),
+    ("ible", ""),
+    ("ant", ""),
+    ("ement", ""),
+    ("ment", ""),
+    ("ent", ""),
+    ("ou", ""),
+    ("ism", ""),
+    ("ate", ""),
+    ("iti", ""),
+    ("ous", ""),
+    ("ive", ""),
+    ("ize", ""),
+];
+
+/// The stem of a lowercased word. Words that aren't plain ASCII letters
+/// and digits are returned unchanged.
+pub(crate) fn stem(word: &str) -> String {
+    if !(MIN_LEN..=MAX_LEN).contains(&word.len())
+        || !word.bytes().all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+    {
+        return word.to_string();
+    }
+
+    let mut w = word.as_bytes().to_vec();
+    apply(&mut w, &[("sses", "ss"), ("ies", "i"), ("ss", "ss"), ("s", "")], |_| true);
+    step1b(&mut w);
+    if w.ends_with(b"y") && has_vowel(&w[..w.len() - 1]) {
+        *w.last_mut().expect("ends with y") = b'i';
+    }
+    apply(&mut w, STEP2, |stem| measure(stem) > 0);
+    apply(&mut w, STEP3, |stem| measure(stem) > 0);
+    step4(&mut w);
+    step5(&mut w);
+    String::from_utf8(w).expect("stemming keeps ASCII")
+}
+
+/// Drop -ed and -ing, tidying up what is left.
+fn step1b(w: &mut Vec<u8>) {
+    if w.ends_with(b"eed") {
+        if measure(&w[..w.len() - 3]) > 0 {
+            w.pop();
+        }
+        return;
+    }
+    if !apply(w, &[("ed", ""), ("ing", "")], has_vowel) {
+        return;
+    }
+    if apply(w, &[("at", "ate"), ("bl", "ble"), ("iz", "ize")], |_| true) {
+        return;
+    }
+    if ends_double_consonant(w) && !matches!(w[w.len() - 1], b'l' | b's' | b'z') {
+        w.pop();
+    } else if measure(w) == 1 && ends_cvc(w) {
+        w.push(b'e');
+    }
+}
+
+/// Drop the remaining suffixes from stems long enough to keep without them.
+fn step4(w: &mut Vec<u8>) {
+    if w.ends_with(b"ion") {
+        let stem = &w[..w.len() - 3];
+        if measure(stem) > 1 && matches!(stem.last(), Some(b's' | b't')) {
+            w.truncate(stem.len());
+        }
+        return;
+    }
+    apply(w, STEP4, |stem| measure(stem) > 1);
+}
+
+/// Drop a final -e and undouble a final -ll.
+fn step5(w: &mut Vec<u8>) {
+    if w.ends_with(b"e") {
+        let stem = &w[..w.len() - 1];
+        let m = measure(stem);
+        if m > 1 || (m == 1 && !ends_cvc(stem)) {
+            w.pop();
+        }
+    }
+    if w.ends_with(b"ll") && measure(w) > 1 {
+        w.pop();
+    }
+}
+
+/// Replace the first suffix of `rules` that `w` ends with, if `condition`
+/// holds for the rest of the word. Returns whether it was replaced.
+fn apply(w: &mut Vec<u8>, rules: &[(&str, &str)], condition: impl Fn(&[u8]) -> bool) -> bool {
+    let Some((suffix, replacement)) = rules.iter().find(|(suffix, _)| w.ends_with(suffix.as_bytes()))
+    else {
+        return false;
+    };
+    let stem_len = w.len() - suffix.len();
+    if !condition(&w[..stem_len]) {
+        return false;
+    }
+    w.truncate(stem_len);
+    w.extend_from_slice(replacement.as_bytes());
+    true
+}
+
+fn is_consonant(w: &[u8], i: usize) -> bool {
+    match w[i] {
+        b'a' | b'e' | b'i' | b'o' | b'u' => false,
+        b'y' => i == 0 || !is_consonant(w, i - 1),
+        _ => true,
+    }
+}
+
+/// Porter's m: the number of vowel-consonant sequences in `w`.
+fn measure(w: &[u8]) -> usize {
+    let mut i = 0;
+    while i < w.len() && is_consonant(w, i) {
+        i += 1;
+    }
+    let mut m = 0;
+    loop {
+        while i < w.len() && !is_consonant(w, i) {
+            i += 1;
+        }
+        if i == w.len() {
+            return m;
+        }
+        while i < w.len() && is_consonant(w, i) {
+            i += 1;
+        }
+        m += 1;
+    }
+}
+
+fn has_vowel(w: &[u8]) -> bool {
+    (0..w.len()).any(|i| !is_consonant(w, i))
+}
+
+fn ends_double_consonant(w: &[u8]) -> bool {
+    let n = w.len();
+    n >= 2 && w[n - 1] == w[n - 2] && is_consonant(w, n - 1)
+}
+
+/// Whether `w` ends consonant-vowel-consonant, the last not w, x or y.
+fn ends_cvc(w: &[u8]) -> bool {
+    let n = w.len();
+    n >= 3
+        && is_consonant(w, n - 3)
+        && !is_consonant(w, n - 2)
+        && is_consonant(w, n - 1)
+        && !matches!(w[n - 1], b'w' | b'x' | b'y')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stem() {
+        let cases = [
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+            ("feed", "feed"),
+            ("agreed", "agre"),
+            ("plastered", "plaster"),
+            ("bled", "bled"),
+            ("motoring", "motor"),
+            ("sing", "sing"),
+            ("conflated", "conflat"),
+            ("hopping", "hop"),
+            ("falling", "fall"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("sky", "sky"),
+            ("relational", "relat"),
+            ("generalizations", "gener"),
+            ("oscillators", "oscil"),
+            ("adoption", "adopt"),
+            ("controlling", "control"),
+            ("running", "run"),
+            ("runs", "run"),
+        ];
+        for (word, expected) in cases {
+            assert_eq!(stem(word), expected, "stem of {}", word);
+        }
+
+        // Too short, or not plain ASCII
+        assert_eq!(stem("is"), "is");
+        assert_eq!(stem("naïve"), "naïve");
+    }
+}
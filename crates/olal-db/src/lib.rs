@@ -1,10 +1,25 @@
 //! Olal DB - Database layer for Olal using SQLite.
 
+mod cache;
+mod compression;
 mod database;
 mod error;
+mod filter;
+pub mod fingerprint;
+pub mod fts;
 mod migrations;
 mod operations;
 
-pub use database::Database;
+pub use database::{Database, ENCRYPTION_SUPPORTED};
 pub use error::{DbError, DbResult};
-pub use operations::vectors::{cosine_similarity, SimilarityResult};
+pub use filter::SearchFilter;
+pub use migrations::SCHEMA_VERSION;
+pub use operations::chunks::{ChunkChanges, SavedEmbeddings};
+pub use operations::items::{DeletionSummary, SearchMatch};
+pub use operations::related::RelatedItem;
+pub use operations::schedule::ScheduleRun;
+pub use operations::stats::{ActivityBucket, TimeBucket};
+pub use operations::usage::CommandUsage;
+pub use operations::vectors::{
+    centroid, cosine_similarity, embedding_hash, HybridResult, SimilarityResult,
+};
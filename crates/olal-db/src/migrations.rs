@@ -1,11 +1,17 @@
 //! Database migrations and schema management.
 
+use crate::compression::chunk_content;
 use crate::error::DbResult;
-use rusqlite::Connection;
+use crate::fts::Tokenizer;
+use crate::operations::chunks::insert_fingerprint;
+use crate::operations::vectors::embedding_hash;
+use olal_core::Tag;
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
 use tracing::info;
 
 /// Current schema version.
-pub const SCHEMA_VERSION: i32 = 1;
+pub const SCHEMA_VERSION: i32 = 25;
 
 /// Initialize the database schema.
 pub fn initialize_schema(conn: &Connection) -> DbResult<()> {
@@ -14,7 +20,7 @@ pub fn initialize_schema(conn: &Connection) -> DbResult<()> {
     if current_version == 0 {
         info!("Creating initial database schema...");
         create_initial_schema(conn)?;
-        set_schema_version(conn, SCHEMA_VERSION)?;
+        run_migrations(conn, 1)?;
     } else if current_version < SCHEMA_VERSION {
         info!(
             "Migrating database from version {} to {}",
@@ -180,30 +186,754 @@ fn create_initial_schema(conn: &Connection) -> DbResult<()> {
 }
 
 fn run_migrations(conn: &Connection, from_version: i32) -> DbResult<()> {
-    // Future migrations go here
-    // Example:
-    // if from_version < 2 {
-    //     migrate_v1_to_v2(conn)?;
-    // }
-    // if from_version < 3 {
-    //     migrate_v2_to_v3(conn)?;
-    // }
-
-    let _ = (conn, from_version); // Silence unused warnings
+    if from_version < 2 {
+        migrate_v1_to_v2(conn)?;
+    }
+    if from_version < 3 {
+        migrate_v2_to_v3(conn)?;
+    }
+    if from_version < 4 {
+        migrate_v3_to_v4(conn)?;
+    }
+    if from_version < 5 {
+        migrate_v4_to_v5(conn)?;
+    }
+    if from_version < 6 {
+        migrate_v5_to_v6(conn)?;
+    }
+    if from_version < 7 {
+        migrate_v6_to_v7(conn)?;
+    }
+    if from_version < 8 {
+        migrate_v7_to_v8(conn)?;
+    }
+    if from_version < 9 {
+        migrate_v8_to_v9(conn)?;
+    }
+    if from_version < 10 {
+        migrate_v9_to_v10(conn)?;
+    }
+    if from_version < 11 {
+        migrate_v10_to_v11(conn)?;
+    }
+    if from_version < 12 {
+        migrate_v11_to_v12(conn)?;
+    }
+    if from_version < 13 {
+        migrate_v12_to_v13(conn)?;
+    }
+    if from_version < 14 {
+        migrate_v13_to_v14(conn)?;
+    }
+    if from_version < 15 {
+        migrate_v14_to_v15(conn)?;
+    }
+    if from_version < 16 {
+        migrate_v15_to_v16(conn)?;
+    }
+    if from_version < 17 {
+        migrate_v16_to_v17(conn)?;
+    }
+    if from_version < 18 {
+        migrate_v17_to_v18(conn)?;
+    }
+    if from_version < 19 {
+        migrate_v18_to_v19(conn)?;
+    }
+    if from_version < 20 {
+        migrate_v19_to_v20(conn)?;
+    }
+    if from_version < 21 {
+        migrate_v20_to_v21(conn)?;
+    }
+    if from_version < 22 {
+        migrate_v21_to_v22(conn)?;
+    }
+    if from_version < 23 {
+        migrate_v22_to_v23(conn)?;
+    }
+    if from_version < 24 {
+        migrate_v23_to_v24(conn)?;
+    }
+    if from_version < 25 {
+        migrate_v24_to_v25(conn)?;
+    }
 
     set_schema_version(conn, SCHEMA_VERSION)?;
     Ok(())
 }
 
+/// v2: named entities extracted from content.
+fn migrate_v1_to_v2(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS entities (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            kind TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            UNIQUE (name, kind)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_entities_name ON entities(name COLLATE NOCASE);
+
+        CREATE TABLE IF NOT EXISTS item_entities (
+            item_id TEXT REFERENCES items(id) ON DELETE CASCADE,
+            entity_id TEXT REFERENCES entities(id) ON DELETE CASCADE,
+            mentions INTEGER DEFAULT 1,
+            PRIMARY KEY (item_id, entity_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_item_entities_entity ON item_entities(entity_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v3: soft delete (archive) for items.
+fn migrate_v2_to_v3(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE items ADD COLUMN archived_at TEXT;
+
+        CREATE INDEX IF NOT EXISTS idx_items_archived ON items(archived_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v4: review queue for low-confidence AI enrichment.
+fn migrate_v3_to_v4(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS ai_suggestions (
+            id TEXT PRIMARY KEY,
+            item_id TEXT NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL,
+            value TEXT NOT NULL,
+            confidence REAL NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending_review',
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_ai_suggestions_status ON ai_suggestions(status);
+        CREATE INDEX IF NOT EXISTS idx_ai_suggestions_item ON ai_suggestions(item_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v5: digest feedback (preferred topics, ignored sources).
+fn migrate_v4_to_v5(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS digest_feedback (
+            target TEXT NOT NULL,
+            name TEXT NOT NULL COLLATE NOCASE,
+            score INTEGER NOT NULL DEFAULT 0,
+            updated_at TEXT NOT NULL,
+            PRIMARY KEY (target, name)
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v6: retention support.
+///
+/// Tracks when items were last viewed (for "unread" rules) and rebuilds the
+/// full-text index as a contentless table so chunk text can be stored
+/// compressed: the triggers only index TEXT values, and deletes go by rowid.
+fn migrate_v5_to_v6(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE items ADD COLUMN last_viewed_at TEXT;
+
+        DROP TRIGGER IF EXISTS chunks_ai;
+        DROP TRIGGER IF EXISTS chunks_ad;
+        DROP TRIGGER IF EXISTS chunks_au;
+        DROP TABLE IF EXISTS chunks_fts;
+
+        CREATE VIRTUAL TABLE chunks_fts USING fts5(
+            content,
+            content='',
+            contentless_delete=1
+        );
+
+        INSERT INTO chunks_fts(rowid, content)
+            SELECT rowid, content FROM chunks WHERE typeof(content) = 'text';
+
+        CREATE TRIGGER chunks_ai AFTER INSERT ON chunks
+        WHEN typeof(NEW.content) = 'text' BEGIN
+            INSERT INTO chunks_fts(rowid, content) VALUES (NEW.rowid, NEW.content);
+        END;
+
+        CREATE TRIGGER chunks_ad AFTER DELETE ON chunks BEGIN
+            DELETE FROM chunks_fts WHERE rowid = OLD.rowid;
+        END;
+
+        CREATE TRIGGER chunks_au AFTER UPDATE OF content ON chunks
+        WHEN typeof(NEW.content) = 'text' BEGIN
+            DELETE FROM chunks_fts WHERE rowid = OLD.rowid;
+            INSERT INTO chunks_fts(rowid, content) VALUES (NEW.rowid, NEW.content);
+        END;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v7: canonical text for items without a source file (captured notes).
+///
+/// Chunks are derived from this and can be regenerated after an edit.
+fn migrate_v6_to_v7(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS item_content (
+            item_id TEXT PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+            content TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v8: last run of each scheduled job.
+fn migrate_v7_to_v8(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS schedule_runs (
+            name TEXT PRIMARY KEY,
+            last_run_at TEXT NOT NULL,
+            error TEXT
+        );
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v9: spaced-repetition schedules.
+fn migrate_v8_to_v9(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS reviews (
+            item_id TEXT PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+            ease REAL NOT NULL,
+            interval_days INTEGER NOT NULL,
+            repetitions INTEGER NOT NULL,
+            due_at TEXT NOT NULL,
+            last_reviewed_at TEXT,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_reviews_due ON reviews(due_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v10: flashcards generated from items.
+fn migrate_v9_to_v10(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS flashcards (
+            id TEXT PRIMARY KEY,
+            item_id TEXT NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+            question TEXT NOT NULL,
+            answer TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_flashcards_item ON flashcards(item_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v11: local record of command invocations, for `olal stats --me`.
+fn migrate_v10_to_v11(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS command_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            command TEXT NOT NULL,
+            started_at TEXT NOT NULL,
+            duration_ms INTEGER NOT NULL,
+            success INTEGER NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_command_usage_started ON command_usage(started_at);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v12: nested tags. Each tag points at its parent (`a/b` at `a`);
+/// existing slash-separated tags get their missing ancestors created.
+fn migrate_v11_to_v12(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE tags ADD COLUMN parent_id TEXT REFERENCES tags(id) ON DELETE SET NULL;
+
+        CREATE INDEX IF NOT EXISTS idx_tags_parent ON tags(parent_id);
+        "#,
+    )?;
+
+    let mut ids: HashMap<String, String> = {
+        let mut stmt = conn.prepare("SELECT name, id FROM tags")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let nested: Vec<String> = ids.keys().filter(|n| n.contains('/')).cloned().collect();
+    for name in nested {
+        if let Some(parent) = Tag::parent_name(&name) {
+            let parent_id = ensure_tag(conn, &mut ids, parent)?;
+            conn.execute(
+                "UPDATE tags SET parent_id = ?1 WHERE id = ?2",
+                params![parent_id, ids[&name]],
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// The ID of the tag called `name`, creating it and its ancestors if needed.
+fn ensure_tag(conn: &Connection, ids: &mut HashMap<String, String>, name: &str) -> DbResult<String> {
+    if let Some(id) = ids.get(name) {
+        return Ok(id.clone());
+    }
+    let parent_id = match Tag::parent_name(name) {
+        Some(parent) => Some(ensure_tag(conn, ids, parent)?),
+        None => None,
+    };
+    let tag = Tag::new(name);
+    conn.execute(
+        "INSERT INTO tags (id, name, parent_id) VALUES (?1, ?2, ?3)",
+        params![tag.id, tag.name, parent_id],
+    )?;
+    ids.insert(tag.name, tag.id.clone());
+    Ok(tag.id)
+}
+
+/// v13: knowledge items attached to projects.
+fn migrate_v12_to_v13(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS item_projects (
+            item_id TEXT NOT NULL REFERENCES items(id) ON DELETE CASCADE,
+            project_id TEXT NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+            added_at TEXT NOT NULL,
+            PRIMARY KEY (item_id, project_id)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_item_projects_project ON item_projects(project_id);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v14: recurring tasks, and an index for due-date views.
+fn migrate_v13_to_v14(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE tasks ADD COLUMN recurrence TEXT;
+
+        CREATE INDEX IF NOT EXISTS idx_tasks_due ON tasks(due_date);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v15: subtasks and blocked-by relationships between tasks.
+fn migrate_v14_to_v15(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE tasks ADD COLUMN parent_task_id TEXT REFERENCES tasks(id) ON DELETE CASCADE;
+
+        CREATE INDEX IF NOT EXISTS idx_tasks_parent ON tasks(parent_task_id);
+
+        CREATE TABLE IF NOT EXISTS task_deps (
+            task_id TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            blocked_by TEXT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+            PRIMARY KEY (task_id, blocked_by)
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_task_deps_blocked_by ON task_deps(blocked_by);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v16: digest history, for comparing periods.
+fn migrate_v15_to_v16(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS digests (
+            id TEXT PRIMARY KEY,
+            period TEXT NOT NULL,
+            period_start TEXT NOT NULL,
+            period_end TEXT NOT NULL,
+            content TEXT NOT NULL,
+            stats TEXT NOT NULL DEFAULT '{}',
+            created_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_digests_period ON digests(period, period_end);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v17: content hashes on embeddings, so identical chunk text reuses an
+/// existing vector instead of calling the model again.
+fn migrate_v16_to_v17(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE embeddings ADD COLUMN content_hash TEXT;
+
+        CREATE INDEX IF NOT EXISTS idx_embeddings_hash ON embeddings(content_hash);
+        "#,
+    )?;
+
+    let hashes: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT e.chunk_id, c.content, e.model FROM embeddings e JOIN chunks c ON c.id = e.chunk_id",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            let model: String = row.get(2)?;
+            Ok((row.get(0)?, embedding_hash(&chunk_content(row, 1)?, &model)))
+        })?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut update = conn.prepare("UPDATE embeddings SET content_hash = ?1 WHERE chunk_id = ?2")?;
+    for (chunk_id, hash) in hashes {
+        update.execute(params![hash, chunk_id])?;
+    }
+
+    Ok(())
+}
+
+/// v18: the chunk FTS index names its tokenizer, so a change of
+/// tokenizer in the config can be detected and the index rebuilt.
+fn migrate_v17_to_v18(conn: &Connection) -> DbResult<()> {
+    create_chunks_fts(conn, Tokenizer::default())?;
+    Ok(())
+}
+
+/// v19: full-text index of item titles, summaries and tag names, with the
+/// tokenizer the chunk index uses.
+fn migrate_v18_to_v19(conn: &Connection) -> DbResult<()> {
+    let sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'chunks_fts'",
+        [],
+        |row| row.get(0),
+    )?;
+    create_items_fts(conn, Tokenizer::from_table_sql(&sql).unwrap_or_default())
+}
+
+/// v20: the terms in the full-text indexes, for suggesting spellings.
+/// They follow the indexes by name, so survive rebuilds.
+fn migrate_v19_to_v20(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE VIRTUAL TABLE IF NOT EXISTS chunks_fts_vocab USING fts5vocab(chunks_fts, row);
+        CREATE VIRTUAL TABLE IF NOT EXISTS items_fts_vocab USING fts5vocab(items_fts, row);
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v21: near-duplicate fingerprints of item text. They're computed when
+/// duplicates are looked for, and dropped by triggers when an item's
+/// chunks change.
+fn migrate_v20_to_v21(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        CREATE TABLE IF NOT EXISTS fingerprints (
+            item_id TEXT PRIMARY KEY REFERENCES items(id) ON DELETE CASCADE,
+            simhash INTEGER  -- NULL: too little text to compare
+        );
+
+        CREATE TRIGGER IF NOT EXISTS fingerprints_chunks_ai AFTER INSERT ON chunks BEGIN
+            DELETE FROM fingerprints WHERE item_id = NEW.item_id;
+        END;
+
+        CREATE TRIGGER IF NOT EXISTS fingerprints_chunks_ad AFTER DELETE ON chunks BEGIN
+            DELETE FROM fingerprints WHERE item_id = OLD.item_id;
+        END;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v22: chunks with the same or nearly the same text as an embedded chunk
+/// point at it (`duplicate_of`) and share its embedding instead of having
+/// their own. Existing copies of an embedding are collapsed this way, and
+/// embedded chunks are fingerprinted to be found as duplicates.
+fn migrate_v21_to_v22(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE chunks ADD COLUMN duplicate_of TEXT;
+
+        CREATE INDEX IF NOT EXISTS idx_chunks_duplicate ON chunks(duplicate_of);
+
+        CREATE TABLE IF NOT EXISTS chunk_fingerprints (
+            chunk_id TEXT PRIMARY KEY REFERENCES chunks(id) ON DELETE CASCADE,
+            text_hash TEXT NOT NULL,
+            simhash INTEGER
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_chunk_fingerprints_hash ON chunk_fingerprints(text_hash);
+
+        -- Duplicates of a deleted chunk need an embedding of their own again
+        CREATE TRIGGER IF NOT EXISTS chunks_duplicates_ad AFTER DELETE ON chunks BEGIN
+            UPDATE chunks SET duplicate_of = NULL WHERE duplicate_of = OLD.id;
+            DELETE FROM chunk_fingerprints WHERE chunk_id = OLD.id;
+        END;
+        "#,
+    )?;
+
+    let embedded: Vec<(String, Option<String>, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT e.chunk_id, e.content_hash, c.content
+             FROM embeddings e JOIN chunks c ON c.id = e.chunk_id
+             ORDER BY c.rowid",
+        )?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, chunk_content(row, 2)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+
+    let mut canonical: HashMap<String, String> = HashMap::new();
+    for (chunk_id, hash, content) in embedded {
+        if let Some(original) = hash.as_ref().and_then(|h| canonical.get(h)) {
+            conn.execute(
+                "UPDATE chunks SET duplicate_of = ?1 WHERE id = ?2",
+                params![original, chunk_id],
+            )?;
+            conn.execute("DELETE FROM embeddings WHERE chunk_id = ?1", params![chunk_id])?;
+            continue;
+        }
+        if let Some(hash) = hash {
+            canonical.insert(hash, chunk_id.clone());
+        }
+        insert_fingerprint(conn, &chunk_id, &content)?;
+    }
+
+    Ok(())
+}
+
+/// v23: the page range a chunk came from, for citing PDFs by page.
+fn migrate_v22_to_v23(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch(
+        r#"
+        ALTER TABLE chunks ADD COLUMN start_page INTEGER;
+        ALTER TABLE chunks ADD COLUMN end_page INTEGER;
+        "#,
+    )?;
+
+    Ok(())
+}
+
+/// v24: item FTS rows share their item's rowid, so the triggers find
+/// them without scanning the index.
+fn migrate_v23_to_v24(conn: &Connection) -> DbResult<()> {
+    let sql: String = conn.query_row(
+        "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'chunks_fts'",
+        [],
+        |row| row.get(0),
+    )?;
+    create_items_fts(conn, Tokenizer::from_table_sql(&sql).unwrap_or_default())
+}
+
+/// v25: the process that claimed a queue entry, so only entries whose
+/// process died are requeued.
+fn migrate_v24_to_v25(conn: &Connection) -> DbResult<()> {
+    conn.execute_batch("ALTER TABLE queue ADD COLUMN claimed_by INTEGER;")?;
+
+    Ok(())
+}
+
+/// The chunk FTS insert trigger as created by [`create_chunks_fts`].
+///
+/// Bulk chunk inserts drop this trigger inside their transaction and
+/// recreate it from here.
+pub(crate) const CHUNKS_INSERT_TRIGGER: &str = r#"
+        CREATE TRIGGER chunks_ai AFTER INSERT ON chunks
+        WHEN typeof(NEW.content) = 'text' BEGIN
+            INSERT INTO chunks_fts(rowid, content) VALUES (NEW.rowid, NEW.content);
+        END;
+"#;
+
+/// Recreate the contentless chunk FTS index with `tokenizer` and index
+/// every chunk, returning how many were indexed.
+///
+/// Like [`migrate_v5_to_v6`], the triggers only index TEXT values;
+/// compressed chunks are decompressed and indexed here, as chunk inserts do.
+pub(crate) fn create_chunks_fts(conn: &Connection, tokenizer: Tokenizer) -> DbResult<usize> {
+    conn.execute_batch(&format!(
+        r#"
+        DROP TRIGGER IF EXISTS chunks_ai;
+        DROP TRIGGER IF EXISTS chunks_ad;
+        DROP TRIGGER IF EXISTS chunks_au;
+        DROP TABLE IF EXISTS chunks_fts;
+
+        CREATE VIRTUAL TABLE chunks_fts USING fts5(
+            content,
+            content='',
+            contentless_delete=1,
+            tokenize='{}'
+        );
+
+        INSERT INTO chunks_fts(rowid, content)
+            SELECT rowid, content FROM chunks WHERE typeof(content) = 'text';
+        {}
+        CREATE TRIGGER chunks_ad AFTER DELETE ON chunks BEGIN
+            DELETE FROM chunks_fts WHERE rowid = OLD.rowid;
+        END;
+
+        CREATE TRIGGER chunks_au AFTER UPDATE OF content ON chunks
+        WHEN typeof(NEW.content) = 'text' BEGIN
+            DELETE FROM chunks_fts WHERE rowid = OLD.rowid;
+            INSERT INTO chunks_fts(rowid, content) VALUES (NEW.rowid, NEW.content);
+        END;
+        "#,
+        tokenizer.option(),
+        CHUNKS_INSERT_TRIGGER
+    ))?;
+
+    let compressed: Vec<(i64, String)> = {
+        let mut stmt = conn.prepare("SELECT rowid, content FROM chunks WHERE typeof(content) = 'blob'")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, chunk_content(row, 1)?)))?;
+        rows.collect::<Result<_, _>>()?
+    };
+    let mut insert = conn.prepare("INSERT INTO chunks_fts(rowid, content) VALUES (?1, ?2)")?;
+    for (rowid, content) in &compressed {
+        insert.execute(params![rowid, content])?;
+    }
+
+    let total: i64 = conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
+    Ok(total as usize)
+}
+
+/// An item's tag names, space-separated, for `items_fts`. `{}` is the
+/// item's ID.
+const ITEM_TAG_NAMES: &str = "(SELECT COALESCE(group_concat(t.name, ' '), '')
+    FROM item_tags it JOIN tags t ON t.id = it.tag_id WHERE it.item_id = {})";
+
+/// Recreate the item FTS index (title, summary and tag names) with
+/// `tokenizer`, and index every item.
+///
+/// Unlike the chunk index it keeps its own copy of the text, which is
+/// small, so triggers can update single columns. Like the chunk index,
+/// each row has the rowid of its item, so triggers look rows up by rowid
+/// rather than scanning the unindexed `item_id` column.
+pub(crate) fn create_items_fts(conn: &Connection, tokenizer: Tokenizer) -> DbResult<()> {
+    let tags = |item_id: &str| ITEM_TAG_NAMES.replace("{}", item_id);
+    conn.execute_batch(&format!(
+        r#"
+        DROP TRIGGER IF EXISTS items_fts_ai;
+        DROP TRIGGER IF EXISTS items_fts_ad;
+        DROP TRIGGER IF EXISTS items_fts_au;
+        DROP TRIGGER IF EXISTS item_tags_fts_ai;
+        DROP TRIGGER IF EXISTS item_tags_fts_ad;
+        DROP TRIGGER IF EXISTS tags_fts_au;
+        DROP TABLE IF EXISTS items_fts;
+
+        CREATE VIRTUAL TABLE items_fts USING fts5(
+            item_id UNINDEXED,
+            title,
+            summary,
+            tags,
+            tokenize='{tokenize}'
+        );
+
+        INSERT INTO items_fts(rowid, item_id, title, summary, tags)
+            SELECT rowid, id, title, COALESCE(summary, ''), {all_tags} FROM items;
+
+        CREATE TRIGGER items_fts_ai AFTER INSERT ON items BEGIN
+            INSERT INTO items_fts(rowid, item_id, title, summary, tags)
+            VALUES (NEW.rowid, NEW.id, NEW.title, COALESCE(NEW.summary, ''), '');
+        END;
+
+        CREATE TRIGGER items_fts_ad AFTER DELETE ON items BEGIN
+            DELETE FROM items_fts WHERE rowid = OLD.rowid;
+        END;
+
+        CREATE TRIGGER items_fts_au AFTER UPDATE OF title, summary ON items BEGIN
+            UPDATE items_fts SET title = NEW.title, summary = COALESCE(NEW.summary, '')
+            WHERE rowid = NEW.rowid;
+        END;
+
+        CREATE TRIGGER item_tags_fts_ai AFTER INSERT ON item_tags BEGIN
+            UPDATE items_fts SET tags = {new_tags}
+            WHERE rowid = (SELECT rowid FROM items WHERE id = NEW.item_id);
+        END;
+
+        CREATE TRIGGER item_tags_fts_ad AFTER DELETE ON item_tags BEGIN
+            UPDATE items_fts SET tags = {old_tags}
+            WHERE rowid = (SELECT rowid FROM items WHERE id = OLD.item_id);
+        END;
+
+        CREATE TRIGGER tags_fts_au AFTER UPDATE OF name ON tags BEGIN
+            UPDATE items_fts SET tags = {renamed_tags}
+            WHERE rowid IN (
+                SELECT i.rowid FROM items i JOIN item_tags it ON it.item_id = i.id
+                WHERE it.tag_id = NEW.id
+            );
+        END;
+        "#,
+        tokenize = tokenizer.option(),
+        all_tags = tags("items.id"),
+        new_tags = tags("NEW.item_id"),
+        old_tags = tags("OLD.item_id"),
+        renamed_tags = tags("items_fts.item_id"),
+    ))?;
+    Ok(())
+}
+
 /// Drop all tables (for testing).
 #[cfg(test)]
 pub fn drop_all_tables(conn: &Connection) -> DbResult<()> {
     conn.execute_batch(
         r#"
+        DROP TABLE IF EXISTS chunk_fingerprints;
+        DROP TABLE IF EXISTS fingerprints;
+        DROP TABLE IF EXISTS digests;
+        DROP TABLE IF EXISTS task_deps;
+        DROP TABLE IF EXISTS item_projects;
+        DROP TABLE IF EXISTS command_usage;
+        DROP TABLE IF EXISTS flashcards;
+        DROP TABLE IF EXISTS reviews;
+        DROP TABLE IF EXISTS schedule_runs;
+        DROP TABLE IF EXISTS item_content;
+        DROP TABLE IF EXISTS digest_feedback;
+        DROP TABLE IF EXISTS ai_suggestions;
+        DROP TABLE IF EXISTS item_entities;
+        DROP TABLE IF EXISTS entities;
         DROP TABLE IF EXISTS item_tags;
         DROP TABLE IF EXISTS links;
         DROP TABLE IF EXISTS embeddings;
+        DROP TABLE IF EXISTS chunks_fts_vocab;
+        DROP TABLE IF EXISTS items_fts_vocab;
         DROP TABLE IF EXISTS chunks_fts;
+        DROP TABLE IF EXISTS items_fts;
         DROP TABLE IF EXISTS chunks;
         DROP TABLE IF EXISTS queue;
         DROP TABLE IF EXISTS tasks;
@@ -215,3 +945,168 @@ pub fn drop_all_tables(conn: &Connection) -> DbResult<()> {
     set_schema_version(conn, 0)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_initialize_after_drop() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+
+        drop_all_tables(&conn).unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), 0);
+
+        initialize_schema(&conn).unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn test_migrate_from_v1() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_initial_schema(&conn).unwrap();
+        set_schema_version(&conn, 1).unwrap();
+
+        initialize_schema(&conn).unwrap();
+        assert_eq!(get_schema_version(&conn).unwrap(), SCHEMA_VERSION);
+
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM entities", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_nested_tags_backfilled() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_initial_schema(&conn).unwrap();
+        run_migrations(&conn, 1).unwrap();
+        conn.execute_batch(
+            "DROP INDEX idx_tags_parent;
+             ALTER TABLE tags DROP COLUMN parent_id;
+             INSERT INTO tags (id, name) VALUES ('t1', 'project/olal/backend'), ('t2', 'project');",
+        )
+        .unwrap();
+
+        migrate_v11_to_v12(&conn).unwrap();
+
+        let parent_of = |name: &str| -> Option<String> {
+            conn.query_row(
+                "SELECT p.name FROM tags t LEFT JOIN tags p ON p.id = t.parent_id WHERE t.name = ?1",
+                params![name],
+                |row| row.get(0),
+            )
+            .unwrap()
+        };
+        assert_eq!(parent_of("project/olal/backend").as_deref(), Some("project/olal"));
+        assert_eq!(parent_of("project/olal").as_deref(), Some("project"));
+        assert_eq!(parent_of("project"), None);
+    }
+
+    #[test]
+    fn test_fts_survives_contentless_rebuild() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_initial_schema(&conn).unwrap();
+        run_migrations(&conn, 1).unwrap();
+        conn.execute_batch(
+            "INSERT INTO items (id, item_type, title, created_at) VALUES ('i1', 'note', 'T', '2024-01-01');
+             INSERT INTO chunks (id, item_id, chunk_index, content) VALUES ('c1', 'i1', 0, 'old zeppelin notes');",
+        )
+        .unwrap();
+
+        // Re-run the v6 migration against existing data
+        conn.execute_batch("ALTER TABLE items DROP COLUMN last_viewed_at").unwrap();
+        migrate_v5_to_v6(&conn).unwrap();
+
+        let hits: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM chunks_fts WHERE chunks_fts MATCH 'zeppelin'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hits, 1);
+    }
+
+    #[test]
+    fn test_fts_rebuilt_with_tokenizer() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        conn.execute_batch("INSERT INTO items (id, item_type, title, created_at) VALUES ('i1', 'note', 'T', '2024-01-01')")
+            .unwrap();
+        conn.execute(
+            "INSERT INTO chunks (id, item_id, chunk_index, content) VALUES ('c1', 'i1', 0, ?1)",
+            params![crate::compression::compress_text("Running café notes").unwrap()],
+        )
+        .unwrap();
+        let hits = |query: &str| -> i64 {
+            conn.query_row("SELECT COUNT(*) FROM chunks_fts WHERE chunks_fts MATCH ?1", [query], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+
+        assert_eq!(create_chunks_fts(&conn, Tokenizer::Unicode61).unwrap(), 1);
+        assert_eq!(hits("cafe"), 1);
+        assert_eq!(hits("runs"), 0);
+
+        create_chunks_fts(&conn, Tokenizer::Porter).unwrap();
+        assert_eq!(hits("runs"), 1);
+        let sql: String = conn
+            .query_row("SELECT sql FROM sqlite_master WHERE name = 'chunks_fts'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(Tokenizer::from_table_sql(&sql), Some(Tokenizer::Porter));
+    }
+
+    #[test]
+    fn test_items_fts_keyed_by_item_rowid() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize_schema(&conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO items (id, item_type, title, created_at) VALUES
+                ('i1', 'note', 'Zeppelin', '2024-01-01'), ('i2', 'note', 'Blimp', '2024-01-01');
+             INSERT INTO tags (id, name) VALUES ('t1', 'airship');
+             INSERT INTO item_tags (item_id, tag_id) VALUES ('i2', 't1');
+             DELETE FROM items WHERE id = 'i1';
+             UPDATE items SET title = 'Dirigible' WHERE id = 'i2';",
+        )
+        .unwrap();
+
+        let row: (String, String, String) = conn
+            .query_row(
+                "SELECT f.item_id, f.title, f.tags FROM items_fts f JOIN items i ON i.rowid = f.rowid",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(row, ("i2".to_string(), "Dirigible".to_string(), "airship".to_string()));
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM items_fts", [], |row| row.get(0)).unwrap();
+        assert_eq!(rows, 1);
+    }
+
+    #[test]
+    fn test_embedding_hashes_backfilled() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_initial_schema(&conn).unwrap();
+        run_migrations(&conn, 1).unwrap();
+        conn.execute_batch(
+            "DROP INDEX idx_embeddings_hash;
+             ALTER TABLE embeddings DROP COLUMN content_hash;
+             INSERT INTO items (id, item_type, title, created_at) VALUES ('i1', 'note', 'T', '2024-01-01');
+             INSERT INTO chunks (id, item_id, chunk_index, content) VALUES ('c1', 'i1', 0, 'zeppelins');
+             INSERT INTO embeddings (chunk_id, vector, model, dimensions) VALUES ('c1', x'00000000', 'm', 1);",
+        )
+        .unwrap();
+
+        migrate_v16_to_v17(&conn).unwrap();
+
+        let hash: String = conn
+            .query_row("SELECT content_hash FROM embeddings WHERE chunk_id = 'c1'", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(hash, embedding_hash("zeppelins", "m"));
+    }
+}
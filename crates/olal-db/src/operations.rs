@@ -2,9 +2,21 @@
 
 pub mod items;
 pub mod chunks;
+pub mod content;
 pub mod tasks;
 pub mod projects;
 pub mod tags;
 pub mod queue;
+pub mod reviews;
+pub mod schedule;
 pub mod stats;
 pub mod vectors;
+pub mod links;
+pub mod related;
+pub mod entities;
+pub mod suggestions;
+pub mod feedback;
+pub mod digests;
+pub mod duplicates;
+pub mod flashcards;
+pub mod usage;
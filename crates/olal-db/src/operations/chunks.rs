@@ -1,53 +1,61 @@
 //! Chunk CRUD operations.
 
+use crate::compression::{chunk_content, compress_text, maybe_compress, MIN_COMPRESS_BYTES};
 use crate::database::Database;
 use crate::error::{DbError, DbResult};
+use crate::fingerprint;
+use crate::fts::Tokenizer;
+use crate::migrations::{create_chunks_fts, create_items_fts, CHUNKS_INSERT_TRIGGER};
+use crate::operations::vectors::embedding_hash;
 use olal_core::{Chunk, ChunkId, ItemId};
-use rusqlite::params;
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use std::collections::{HashMap, VecDeque};
+
+/// Batches at least this large skip the per-row FTS trigger.
+pub(crate) const BULK_INSERT_MIN: usize = 256;
+
+/// An item's embeddings, saved by [`Database::save_embeddings`] while its
+/// chunks are replaced.
+#[derive(Debug, Default)]
+pub struct SavedEmbeddings {
+    /// Content hash to (vector, model, dimensions).
+    by_hash: HashMap<String, (Vec<u8>, String, i32)>,
+}
+
+/// How [`Database::replace_chunks`] changed an item's chunks.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkChanges {
+    /// Stored chunks with unchanged text, kept with their embeddings.
+    pub kept: usize,
+    /// New chunks stored.
+    pub added: usize,
+    /// Stored chunks deleted.
+    pub removed: usize,
+}
 
 impl Database {
     /// Create a new chunk.
+    ///
+    /// Long chunk text is stored compressed; reads decompress transparently.
     pub fn create_chunk(&self, chunk: &Chunk) -> DbResult<()> {
         let conn = self.conn()?;
-        conn.execute(
-            r#"
-            INSERT INTO chunks (id, item_id, chunk_index, content, start_time, end_time)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-            "#,
-            params![
-                chunk.id,
-                chunk.item_id,
-                chunk.chunk_index,
-                chunk.content,
-                chunk.start_time,
-                chunk.end_time,
-            ],
-        )?;
-        Ok(())
+        insert_chunk(&conn, chunk)
     }
 
     /// Create multiple chunks in a transaction.
     pub fn create_chunks(&self, chunks: &[Chunk]) -> DbResult<()> {
         let mut conn = self.conn()?;
-        let tx = conn.transaction()?;
-
-        {
-            let mut stmt = tx.prepare(
-                r#"
-                INSERT INTO chunks (id, item_id, chunk_index, content, start_time, end_time)
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-                "#,
-            )?;
-
+        // Take the write lock up front: a deferred transaction that reads
+        // before writing fails at once, instead of waiting, if another
+        // connection wrote in between
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+
+        if chunks.len() >= BULK_INSERT_MIN {
+            bulk_insert_chunks(&tx, chunks)?;
+        } else {
             for chunk in chunks {
-                stmt.execute(params![
-                    chunk.id,
-                    chunk.item_id,
-                    chunk.chunk_index,
-                    chunk.content,
-                    chunk.start_time,
-                    chunk.end_time,
-                ])?;
+                insert_chunk(&tx, chunk)?;
             }
         }
 
@@ -59,16 +67,19 @@ impl Database {
     pub fn get_chunk(&self, id: &ChunkId) -> DbResult<Chunk> {
         let conn = self.conn()?;
         let chunk = conn.query_row(
-            "SELECT id, item_id, chunk_index, content, start_time, end_time FROM chunks WHERE id = ?1",
+            "SELECT id, item_id, chunk_index, content, start_time, end_time, start_page, end_page
+             FROM chunks WHERE id = ?1",
             params![id],
             |row| {
                 Ok(Chunk {
                     id: row.get(0)?,
                     item_id: row.get(1)?,
                     chunk_index: row.get(2)?,
-                    content: row.get(3)?,
+                    content: chunk_content(row, 3)?,
                     start_time: row.get(4)?,
                     end_time: row.get(5)?,
+                    start_page: row.get(6)?,
+                    end_page: row.get(7)?,
                 })
             },
         ).map_err(|e| match e {
@@ -83,7 +94,7 @@ impl Database {
     pub fn get_chunks_by_item(&self, item_id: &ItemId) -> DbResult<Vec<Chunk>> {
         let conn = self.conn()?;
         let mut stmt = conn.prepare(
-            "SELECT id, item_id, chunk_index, content, start_time, end_time
+            "SELECT id, item_id, chunk_index, content, start_time, end_time, start_page, end_page
              FROM chunks WHERE item_id = ?1 ORDER BY chunk_index",
         )?;
 
@@ -92,15 +103,105 @@ impl Database {
                 id: row.get(0)?,
                 item_id: row.get(1)?,
                 chunk_index: row.get(2)?,
-                content: row.get(3)?,
+                content: chunk_content(row, 3)?,
                 start_time: row.get(4)?,
                 end_time: row.get(5)?,
+                start_page: row.get(6)?,
+                end_page: row.get(7)?,
             })
         })?;
 
         chunks.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 
+    /// Compress an item's chunk text in place. Returns the bytes saved.
+    ///
+    /// Chunks that are already compressed, or that wouldn't shrink, are left
+    /// alone. Search is unaffected: the full-text index keeps the tokens of
+    /// the original text.
+    pub fn compress_item_chunks(&self, item_id: &ItemId) -> DbResult<i64> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+
+        let plain: Vec<(String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT id, content FROM chunks WHERE item_id = ?1 AND typeof(content) = 'text'",
+            )?;
+            let rows = stmt.query_map(params![item_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut saved = 0i64;
+        for (id, content) in plain {
+            let compressed = compress_text(&content)?;
+            if compressed.len() < content.len() {
+                tx.execute(
+                    "UPDATE chunks SET content = ?2 WHERE id = ?1",
+                    params![id, compressed],
+                )?;
+                saved += (content.len() - compressed.len()) as i64;
+            }
+        }
+
+        tx.commit()?;
+        Ok(saved)
+    }
+
+    /// Compress all long plain-text chunks, e.g. ones written before
+    /// compression on write existed. Returns the bytes saved.
+    pub fn compress_all_chunks(&self) -> DbResult<i64> {
+        let item_ids: Vec<ItemId> = {
+            let conn = self.conn()?;
+            let mut stmt = conn.prepare(
+                "SELECT DISTINCT item_id FROM chunks
+                 WHERE typeof(content) = 'text' AND LENGTH(CAST(content AS BLOB)) >= ?1",
+            )?;
+            let rows = stmt.query_map(params![MIN_COMPRESS_BYTES as i64], |row| row.get(0))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut saved = 0;
+        for item_id in item_ids {
+            saved += self.compress_item_chunks(&item_id)?;
+        }
+        Ok(saved)
+    }
+
+    /// The tokenizer the full-text index was built with, or `None` for an
+    /// index built some other way.
+    pub fn fts_tokenizer(&self) -> DbResult<Option<Tokenizer>> {
+        let conn = self.conn()?;
+        let sql: String = conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'chunks_fts'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(Tokenizer::from_table_sql(&sql))
+    }
+
+    /// Rebuild the chunk and item full-text indexes with `tokenizer`, in
+    /// one transaction. Returns the number of chunks indexed.
+    pub fn rebuild_fts(&self, tokenizer: Tokenizer) -> DbResult<usize> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        let indexed = create_chunks_fts(&tx, tokenizer)?;
+        create_items_fts(&tx, tokenizer)?;
+        tx.commit()?;
+        Ok(indexed)
+    }
+
+    /// Total size in bytes of an item's uncompressed chunk text.
+    pub fn uncompressed_chunk_bytes(&self, item_id: &ItemId) -> DbResult<i64> {
+        let conn = self.conn()?;
+        let bytes = conn.query_row(
+            "SELECT COALESCE(SUM(LENGTH(CAST(content AS BLOB))), 0) FROM chunks
+             WHERE item_id = ?1 AND typeof(content) = 'text'",
+            params![item_id],
+            |row| row.get(0),
+        )?;
+        Ok(bytes)
+    }
+
     /// Delete all chunks for an item.
     pub fn delete_chunks_by_item(&self, item_id: &ItemId) -> DbResult<i64> {
         let conn = self.conn()?;
@@ -108,7 +209,71 @@ impl Database {
         Ok(count as i64)
     }
 
+    /// Replace an item's chunks with `chunks`, only writing what changed.
+    ///
+    /// A new chunk with the same text as a stored one takes that chunk's
+    /// ID, so the stored chunk is kept, renumbered, with its embedding and
+    /// index entries; the rest are inserted and stored chunks left over
+    /// are deleted.
+    pub fn replace_chunks(&self, item_id: &ItemId, chunks: &mut [Chunk]) -> DbResult<ChunkChanges> {
+        let mut stored: HashMap<String, VecDeque<ChunkId>> = HashMap::new();
+        for chunk in self.get_chunks_by_item(item_id)? {
+            stored.entry(chunk.content).or_default().push_back(chunk.id);
+        }
+
+        let mut kept = Vec::new();
+        let mut added = Vec::new();
+        for chunk in chunks.iter_mut() {
+            match stored.get_mut(&chunk.content).and_then(VecDeque::pop_front) {
+                Some(id) => {
+                    chunk.id = id;
+                    kept.push(&*chunk);
+                }
+                None => added.push(&*chunk),
+            }
+        }
+        let removed: Vec<ChunkId> = stored.into_values().flatten().collect();
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        for id in &removed {
+            tx.execute("DELETE FROM chunks WHERE id = ?1", params![id])?;
+        }
+        for chunk in &kept {
+            tx.execute(
+                "UPDATE chunks SET chunk_index = ?2, start_time = ?3, end_time = ?4, start_page = ?5, end_page = ?6
+                 WHERE id = ?1",
+                params![
+                    chunk.id,
+                    chunk.chunk_index,
+                    chunk.start_time,
+                    chunk.end_time,
+                    chunk.start_page,
+                    chunk.end_page
+                ],
+            )?;
+        }
+        if added.len() >= BULK_INSERT_MIN {
+            bulk_insert_chunks(&tx, added.iter().copied())?;
+        } else {
+            for chunk in &added {
+                insert_chunk(&tx, chunk)?;
+            }
+        }
+        tx.commit()?;
+
+        Ok(ChunkChanges {
+            kept: kept.len(),
+            added: added.len(),
+            removed: removed.len(),
+        })
+    }
+
     /// Store embedding for a chunk.
+    ///
+    /// The chunk's content hash is stored alongside, and the chunk is
+    /// fingerprinted, so [`reuse_embedding`](Self::reuse_embedding) can
+    /// find the vector for the same text later.
     pub fn store_embedding(&self, chunk_id: &ChunkId, vector: &[f32], model: &str) -> DbResult<()> {
         let conn = self.conn()?;
 
@@ -118,23 +283,152 @@ impl Database {
             .flat_map(|f| f.to_le_bytes())
             .collect();
 
+        let content = conn
+            .query_row(
+                "SELECT content FROM chunks WHERE id = ?1",
+                params![chunk_id],
+                |row| chunk_content(row, 0),
+            )
+            .optional()?;
+        let hash = content.as_ref().map(|content| embedding_hash(content, model));
+
         conn.execute(
             r#"
-            INSERT OR REPLACE INTO embeddings (chunk_id, vector, model, dimensions)
-            VALUES (?1, ?2, ?3, ?4)
+            INSERT OR REPLACE INTO embeddings (chunk_id, vector, model, dimensions, content_hash)
+            VALUES (?1, ?2, ?3, ?4, ?5)
             "#,
-            params![chunk_id, vector_bytes, model, vector.len() as i32],
+            params![chunk_id, vector_bytes, model, vector.len() as i32, hash],
         )?;
+        conn.execute("UPDATE chunks SET duplicate_of = NULL WHERE id = ?1", params![chunk_id])?;
+        if let Some(content) = content {
+            insert_fingerprint(&conn, chunk_id, &content)?;
+        }
 
         Ok(())
     }
 
-    /// Get embedding for a chunk.
+    /// Point `chunk` at an embedded chunk with the same or nearly the same
+    /// text (boilerplate, a repeated OCR frame, an unchanged paragraph of a
+    /// re-ingested file), so the two share that chunk's embedding by
+    /// `model` instead of `chunk` getting its own.
+    ///
+    /// Returns false when there is none and the embedding must be generated.
+    pub fn reuse_embedding(&self, chunk: &Chunk, model: &str) -> DbResult<bool> {
+        let conn = self.conn()?;
+        let same = conn
+            .query_row(
+                r#"
+                SELECT f.chunk_id FROM chunk_fingerprints f
+                JOIN embeddings e ON e.chunk_id = f.chunk_id
+                WHERE f.text_hash = ?1 AND e.model = ?2 AND f.chunk_id != ?3
+                LIMIT 1
+                "#,
+                params![fingerprint::text_hash(&chunk.content), model, chunk.id],
+                |row| row.get::<_, ChunkId>(0),
+            )
+            .optional()?;
+
+        let canonical = match (same, fingerprint::simhash(&chunk.content)) {
+            (Some(id), _) => Some(id),
+            (None, Some(hash)) => {
+                let mut stmt = conn.prepare(
+                    r#"
+                    SELECT f.chunk_id, f.simhash FROM chunk_fingerprints f
+                    JOIN embeddings e ON e.chunk_id = f.chunk_id
+                    WHERE f.simhash IS NOT NULL AND e.model = ?1 AND f.chunk_id != ?2
+                    "#,
+                )?;
+                let candidates = stmt.query_map(params![model, chunk.id], |row| {
+                    Ok((row.get::<_, ChunkId>(0)?, row.get::<_, i64>(1)? as u64))
+                })?;
+                let mut nearest: Option<(u32, ChunkId)> = None;
+                for candidate in candidates {
+                    let (id, other) = candidate?;
+                    let distance = fingerprint::distance(hash, other);
+                    if distance <= fingerprint::NEAR_IDENTICAL_DISTANCE
+                        && nearest.as_ref().is_none_or(|(best, _)| distance < *best)
+                    {
+                        nearest = Some((distance, id));
+                    }
+                }
+                nearest.map(|(_, id)| id)
+            }
+            (None, None) => None,
+        };
+
+        let Some(canonical) = canonical else {
+            return Ok(false);
+        };
+        conn.execute("DELETE FROM embeddings WHERE chunk_id = ?1", params![chunk.id])?;
+        conn.execute(
+            "UPDATE chunks SET duplicate_of = ?2 WHERE id = ?1",
+            params![chunk.id, canonical],
+        )?;
+        Ok(true)
+    }
+
+    /// Save the embeddings of an item's chunks before they are deleted, so
+    /// re-ingesting an edited file can [`restore_embeddings`](Self::restore_embeddings)
+    /// for the text that didn't change.
+    pub fn save_embeddings(&self, item_id: &ItemId) -> DbResult<SavedEmbeddings> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT e.content_hash, e.vector, e.model, e.dimensions
+            FROM embeddings e
+            JOIN chunks c ON c.id = e.chunk_id
+            WHERE c.item_id = ?1 AND e.content_hash IS NOT NULL
+            "#,
+        )?;
+        let by_hash = stmt
+            .query_map(params![item_id], |row| {
+                Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?)))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(SavedEmbeddings { by_hash })
+    }
+
+    /// Give the item's current chunks the saved embeddings of identical
+    /// text. Returns how many chunks got one.
+    pub fn restore_embeddings(&self, item_id: &ItemId, saved: &SavedEmbeddings) -> DbResult<usize> {
+        if saved.by_hash.is_empty() {
+            return Ok(0);
+        }
+        let mut models: Vec<&str> = saved.by_hash.values().map(|(_, model, _)| model.as_str()).collect();
+        models.sort_unstable();
+        models.dedup();
+
+        let chunks = self.get_chunks_by_item(item_id)?;
+        let conn = self.conn()?;
+        let mut insert = conn.prepare(
+            r#"
+            INSERT OR REPLACE INTO embeddings (chunk_id, vector, model, dimensions, content_hash)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+        )?;
+        let mut restored = 0;
+        for chunk in &chunks {
+            for model in &models {
+                let hash = embedding_hash(&chunk.content, model);
+                if let Some((vector, model, dimensions)) = saved.by_hash.get(&hash) {
+                    insert.execute(params![chunk.id, vector, model, dimensions, hash])?;
+                    insert_fingerprint(&conn, &chunk.id, &chunk.content)?;
+                    restored += 1;
+                    break;
+                }
+            }
+        }
+        Ok(restored)
+    }
+
+    /// Get embedding for a chunk: its own, or that of the chunk it
+    /// duplicates.
     pub fn get_embedding(&self, chunk_id: &ChunkId) -> DbResult<Option<Vec<f32>>> {
         let conn = self.conn()?;
 
         let result = conn.query_row(
-            "SELECT vector, dimensions FROM embeddings WHERE chunk_id = ?1",
+            "SELECT vector, dimensions FROM embeddings
+             WHERE chunk_id = COALESCE((SELECT duplicate_of FROM chunks WHERE id = ?1), ?1)",
             params![chunk_id],
             |row| {
                 let bytes: Vec<u8> = row.get(0)?;
@@ -171,6 +465,118 @@ impl Database {
     }
 }
 
+/// Record an embedded chunk's fingerprints, for
+/// [`Database::reuse_embedding`] to find.
+pub(crate) fn insert_fingerprint(conn: &Connection, chunk_id: &str, content: &str) -> DbResult<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO chunk_fingerprints (chunk_id, text_hash, simhash) VALUES (?1, ?2, ?3)",
+        params![
+            chunk_id,
+            fingerprint::text_hash(content),
+            fingerprint::simhash(content).map(|hash| hash as i64)
+        ],
+    )?;
+    Ok(())
+}
+
+/// Insert a chunk, compressing long text.
+///
+/// The FTS triggers only index TEXT content, so compressed chunks are
+/// indexed here from the original text.
+pub(crate) fn insert_chunk(conn: &Connection, chunk: &Chunk) -> DbResult<()> {
+    let compressed = maybe_compress(&chunk.content)?;
+    let is_compressed = compressed.is_some();
+    let content = match compressed {
+        Some(bytes) => Value::Blob(bytes),
+        None => Value::Text(chunk.content.clone()),
+    };
+
+    conn.execute(
+        r#"
+        INSERT INTO chunks (id, item_id, chunk_index, content, start_time, end_time, start_page, end_page)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+        "#,
+        params![
+            chunk.id,
+            chunk.item_id,
+            chunk.chunk_index,
+            content,
+            chunk.start_time,
+            chunk.end_time,
+            chunk.start_page,
+            chunk.end_page,
+        ],
+    )?;
+
+    if is_compressed {
+        conn.execute(
+            "INSERT INTO chunks_fts(rowid, content) VALUES (last_insert_rowid(), ?1)",
+            params![chunk.content],
+        )?;
+    }
+    Ok(())
+}
+
+/// Insert many chunks without the per-row FTS trigger, then index them in
+/// one pass.
+///
+/// Must run inside a transaction: the trigger is dropped and recreated, and
+/// other connections must never see it missing.
+pub(crate) fn bulk_insert_chunks<'a>(
+    conn: &Connection,
+    chunks: impl IntoIterator<Item = &'a Chunk>,
+) -> DbResult<()> {
+    let last_rowid: i64 =
+        conn.query_row("SELECT COALESCE(MAX(rowid), 0) FROM chunks", [], |row| row.get(0))?;
+    conn.execute_batch("DROP TRIGGER IF EXISTS chunks_ai")?;
+
+    // Compressed chunks can't be indexed from the table, so remember their text
+    let mut compressed_rows = Vec::new();
+    {
+        let mut stmt = conn.prepare(
+            r#"
+            INSERT INTO chunks (id, item_id, chunk_index, content, start_time, end_time, start_page, end_page)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            "#,
+        )?;
+        for chunk in chunks {
+            let content = match maybe_compress(&chunk.content)? {
+                Some(bytes) => Value::Blob(bytes),
+                None => Value::Text(chunk.content.clone()),
+            };
+            let is_compressed = matches!(content, Value::Blob(_));
+            stmt.execute(params![
+                chunk.id,
+                chunk.item_id,
+                chunk.chunk_index,
+                content,
+                chunk.start_time,
+                chunk.end_time,
+                chunk.start_page,
+                chunk.end_page,
+            ])?;
+            if is_compressed {
+                compressed_rows.push((conn.last_insert_rowid(), chunk.content.as_str()));
+            }
+        }
+    }
+
+    conn.execute(
+        "INSERT INTO chunks_fts(rowid, content)
+         SELECT rowid, content FROM chunks WHERE rowid > ?1 AND typeof(content) = 'text'",
+        params![last_rowid],
+    )?;
+    {
+        let mut stmt = conn.prepare("INSERT INTO chunks_fts(rowid, content) VALUES (?1, ?2)")?;
+        for (rowid, content) in compressed_rows {
+            stmt.execute(params![rowid, content])?;
+        }
+    }
+
+    conn.execute_batch(CHUNKS_INSERT_TRIGGER)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -226,4 +632,235 @@ mod tests {
         assert_eq!(retrieved.len(), 4);
         assert!((retrieved[0] - 0.1).abs() < 0.0001);
     }
+
+    #[test]
+    fn test_reuse_embedding() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Test Note");
+        db.create_item(&item).unwrap();
+
+        let original = Chunk::new(item.id.clone(), 0, "Same paragraph");
+        let copy = Chunk::new(item.id.clone(), 1, "Same paragraph");
+        let edited = Chunk::new(item.id.clone(), 2, "Edited paragraph");
+        for chunk in [&original, &copy, &edited] {
+            db.create_chunk(chunk).unwrap();
+        }
+        db.store_embedding(&original.id, &[0.5, 0.25], "test-model").unwrap();
+
+        assert!(db.reuse_embedding(&copy, "test-model").unwrap());
+        assert_eq!(db.get_embedding(&copy.id).unwrap(), Some(vec![0.5, 0.25]));
+        assert_eq!(db.embedding_stats().unwrap(), (2, 3));
+
+        // Different text or a different model needs a fresh embedding
+        assert!(!db.reuse_embedding(&edited, "test-model").unwrap());
+        assert!(!db.reuse_embedding(&original, "other-model").unwrap());
+        assert_eq!(db.get_embedding(&edited.id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_replace_chunks() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Journal");
+        db.create_item(&item).unwrap();
+        let old: Vec<Chunk> = ["Intro", "Monday draft", "Tuesday"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Chunk::new(item.id.clone(), i as i32, *text))
+            .collect();
+        db.create_chunks(&old).unwrap();
+        for chunk in &old {
+            db.store_embedding(&chunk.id, &[0.5, 0.25], "test-model").unwrap();
+        }
+
+        // Monday was edited and Wednesday written
+        let mut new: Vec<Chunk> = ["Intro", "Monday final", "Tuesday", "Wednesday"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| Chunk::new(item.id.clone(), i as i32, *text))
+            .collect();
+        let changes = db.replace_chunks(&item.id, &mut new).unwrap();
+        assert_eq!(changes, ChunkChanges { kept: 2, added: 2, removed: 1 });
+        assert_eq!(new[0].id, old[0].id);
+        assert_eq!(new[2].id, old[2].id);
+
+        let stored = db.get_chunks_by_item(&item.id).unwrap();
+        let ids: Vec<&str> = stored.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(ids, new.iter().map(|c| c.id.as_str()).collect::<Vec<_>>());
+        assert!(db.get_chunk(&old[1].id).is_err());
+        assert_eq!(db.embedding_stats().unwrap(), (2, 4));
+        assert_eq!(db.search_items("wednesday", None).unwrap().len(), 1);
+        assert!(db.search_items("draft", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_chunk_pages() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Document, "Paper");
+        db.create_item(&item).unwrap();
+        let chunk = Chunk::new(item.id.clone(), 0, "Results").with_pages(12, 13);
+        db.create_chunk(&chunk).unwrap();
+        assert_eq!(db.get_chunk(&chunk.id).unwrap().page_label().as_deref(), Some("p. 12\u{2013}13"));
+
+        // A page inserted before it moves the kept chunk along
+        let mut moved = vec![Chunk::new(item.id.clone(), 0, "Results").with_pages(13, 14)];
+        db.replace_chunks(&item.id, &mut moved).unwrap();
+        let stored = db.get_chunks_by_item(&item.id).unwrap();
+        assert_eq!(stored[0].id, chunk.id);
+        assert_eq!((stored[0].start_page, stored[0].end_page), (Some(13), Some(14)));
+    }
+
+    #[test]
+    fn test_duplicate_chunks_share_embedding() {
+        let db = Database::open_in_memory().unwrap();
+        let footer = "Copyright 2024 Example Corp. All rights reserved. No part of this \
+            publication may be reproduced, distributed, or transmitted in any form or by any \
+            means without the prior written permission of the publisher, except for brief \
+            quotations in reviews. Subscribe to our newsletter for weekly updates on new \
+            articles and releases, and follow us for announcements about upcoming events.";
+        let mut chunks = Vec::new();
+        for (title, text) in [
+            ("First", format!("{} Page 1", footer)),
+            ("Second", format!("{} PAGE 1", footer.to_uppercase())),
+            ("Third", format!("{} Page 4", footer)),
+        ] {
+            let item = Item::new(ItemType::Note, title);
+            db.create_item(&item).unwrap();
+            let chunk = Chunk::new(item.id.clone(), 0, text);
+            db.create_chunk(&chunk).unwrap();
+            chunks.push(chunk);
+        }
+        db.store_embedding(&chunks[0].id, &[1.0, 0.0], "test-model").unwrap();
+
+        // Same words, then nearly the same words, share the first embedding
+        assert!(db.reuse_embedding(&chunks[1], "test-model").unwrap());
+        assert!(db.reuse_embedding(&chunks[2], "test-model").unwrap());
+        assert_eq!(db.embedding_stats().unwrap(), (3, 3));
+        assert!(db.get_unembedded_chunks(10).unwrap().is_empty());
+
+        // Only the original is retrieved
+        let results = db.vector_search(&[1.0, 0.0], 10, None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].chunk.id, chunks[0].id);
+
+        // Deleting the original leaves its duplicates to be embedded again
+        db.delete_chunks_by_item(&chunks[0].item_id).unwrap();
+        assert_eq!(db.embedding_stats().unwrap(), (0, 2));
+    }
+
+    #[test]
+    fn test_restore_embeddings() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Test Note");
+        db.create_item(&item).unwrap();
+        let kept = Chunk::new(item.id.clone(), 0, "Kept paragraph");
+        let dropped = Chunk::new(item.id.clone(), 1, "Old paragraph");
+        db.create_chunks(&[kept.clone(), dropped.clone()]).unwrap();
+        db.store_embedding(&kept.id, &[0.5, 0.25], "test-model").unwrap();
+        db.store_embedding(&dropped.id, &[0.1, 0.2], "test-model").unwrap();
+
+        let saved = db.save_embeddings(&item.id).unwrap();
+        db.delete_chunks_by_item(&item.id).unwrap();
+        let rechunked = Chunk::new(item.id.clone(), 0, "Kept paragraph");
+        let edited = Chunk::new(item.id.clone(), 1, "New paragraph");
+        db.create_chunks(&[rechunked.clone(), edited.clone()]).unwrap();
+
+        assert_eq!(db.restore_embeddings(&item.id, &saved).unwrap(), 1);
+        assert_eq!(db.get_embedding(&rechunked.id).unwrap(), Some(vec![0.5, 0.25]));
+        assert_eq!(db.get_embedding(&edited.id).unwrap(), None);
+    }
+
+    #[test]
+    fn test_compress_item_chunks() {
+        use olal_core::{Item, ItemType};
+
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Video, "Long talk");
+        db.create_item(&item).unwrap();
+
+        // Below the compress-on-write threshold, so stored as plain text
+        let text = "we talked about zeppelins and airships for a long time. ".repeat(16);
+        let chunk = Chunk::new(item.id.clone(), 0, text.clone());
+        db.create_chunk(&chunk).unwrap();
+
+        assert_eq!(db.uncompressed_chunk_bytes(&item.id).unwrap(), text.len() as i64);
+        let saved = db.compress_item_chunks(&item.id).unwrap();
+        assert!(saved > 0);
+        assert_eq!(db.uncompressed_chunk_bytes(&item.id).unwrap(), 0);
+
+        // Reads are transparent and search still works
+        assert_eq!(db.get_chunk(&chunk.id).unwrap().content, text);
+        assert_eq!(db.get_chunks_by_item(&item.id).unwrap()[0].content, text);
+        assert_eq!(db.search_items("zeppelins", None).unwrap().len(), 1);
+
+        // Second pass is a no-op; deleting still cleans the index
+        assert_eq!(db.compress_item_chunks(&item.id).unwrap(), 0);
+        db.delete_chunks_by_item(&item.id).unwrap();
+        assert!(db.search_items("zeppelins", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compress_on_write() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Video, "Hour-long transcript");
+        db.create_item(&item).unwrap();
+
+        let long = "the airship drifted slowly over the harbour at dawn. ".repeat(100);
+        let chunks = vec![
+            Chunk::new(item.id.clone(), 0, long.clone()),
+            Chunk::new(item.id.clone(), 1, "short zeppelin note"),
+        ];
+        db.create_chunks(&chunks).unwrap();
+
+        // Only the short chunk is left as plain text
+        let short_len = "short zeppelin note".len() as i64;
+        assert_eq!(db.uncompressed_chunk_bytes(&item.id).unwrap(), short_len);
+        assert_eq!(db.get_chunk(&chunks[0].id).unwrap().content, long);
+        assert_eq!(db.search_items("harbour", None).unwrap().len(), 1);
+        assert_eq!(db.search_items("zeppelin", None).unwrap().len(), 1);
+        assert_eq!(db.compress_all_chunks().unwrap(), 0);
+
+        db.delete_chunks_by_item(&item.id).unwrap();
+        assert!(db.search_items("harbour", None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_bulk_insert_indexes_chunks() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Document, "Big book");
+        db.create_item(&item).unwrap();
+
+        let trigger_sql = |db: &Database| -> String {
+            db.conn()
+                .unwrap()
+                .query_row(
+                    "SELECT sql FROM sqlite_master WHERE type = 'trigger' AND name = 'chunks_ai'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        let before = trigger_sql(&db);
+
+        let mut chunks: Vec<Chunk> = (0..BULK_INSERT_MIN as i32)
+            .map(|i| Chunk::new(item.id.clone(), i, format!("page {} about lighthouses", i)))
+            .collect();
+        // One long chunk that is stored compressed
+        chunks.push(Chunk::new(
+            item.id.clone(),
+            BULK_INSERT_MIN as i32,
+            "the keeper's logbook mentions a shipwreck. ".repeat(50),
+        ));
+        db.create_chunks(&chunks).unwrap();
+
+        assert_eq!(db.get_chunks_by_item(&item.id).unwrap().len(), chunks.len());
+        assert_eq!(db.search_items("lighthouses", None).unwrap().len(), 1);
+        assert_eq!(db.search_items("shipwreck", None).unwrap().len(), 1);
+
+        // The trigger is back, unchanged, and still indexes single inserts
+        assert_eq!(trigger_sql(&db), before);
+        let note = Item::new(ItemType::Note, "Small note");
+        db.create_item(&note).unwrap();
+        db.create_chunk(&Chunk::new(note.id.clone(), 0, "foghorn")).unwrap();
+        assert_eq!(db.search_items("foghorn", None).unwrap().len(), 1);
+    }
 }
@@ -0,0 +1,62 @@
+//! Canonical item content operations.
+//!
+//! Items without a source file (captured notes, piped text) keep their full
+//! text here so it can be edited and re-chunked.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use chrono::Utc;
+use olal_core::ItemId;
+use rusqlite::params;
+
+impl Database {
+    /// Store (or replace) the canonical text of an item.
+    pub fn set_item_content(&self, item_id: &ItemId, content: &str) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO item_content (item_id, content, updated_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(item_id) DO UPDATE
+             SET content = excluded.content, updated_at = excluded.updated_at",
+            params![item_id, content, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Get the canonical text of an item, if stored.
+    pub fn get_item_content(&self, item_id: &ItemId) -> DbResult<Option<String>> {
+        let conn = self.conn()?;
+        let result = conn.query_row(
+            "SELECT content FROM item_content WHERE item_id = ?1",
+            params![item_id],
+            |row| row.get(0),
+        );
+
+        match result {
+            Ok(content) => Ok(Some(content)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, ItemType};
+
+    #[test]
+    fn test_item_content() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Idea");
+        db.create_item(&item).unwrap();
+
+        assert!(db.get_item_content(&item.id).unwrap().is_none());
+        db.set_item_content(&item.id, "first draft").unwrap();
+        db.set_item_content(&item.id, "second draft").unwrap();
+        assert_eq!(db.get_item_content(&item.id).unwrap().unwrap(), "second draft");
+
+        // Removed with the item
+        db.delete_item_cascade(&item.id).unwrap();
+        assert!(db.get_item_content(&item.id).unwrap().is_none());
+    }
+}
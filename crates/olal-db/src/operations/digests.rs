@@ -0,0 +1,114 @@
+//! Digest history operations.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use chrono::{DateTime, Utc};
+use olal_core::DigestRecord;
+use rusqlite::params;
+
+impl Database {
+    /// Store a generated digest.
+    pub fn save_digest(&self, digest: &DigestRecord) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO digests (id, period, period_start, period_end, content, stats, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                digest.id,
+                digest.period,
+                digest.period_start.to_rfc3339(),
+                digest.period_end.to_rfc3339(),
+                digest.content,
+                serde_json::to_string(&digest.stats)?,
+                digest.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// The latest `period` digest ending at or before `before`.
+    pub fn previous_digest(&self, period: &str, before: DateTime<Utc>) -> DbResult<Option<DigestRecord>> {
+        let conn = self.conn()?;
+        let result = conn.query_row(
+            "SELECT id, period, period_start, period_end, content, stats, created_at
+             FROM digests WHERE period = ?1 AND period_end <= ?2
+             ORDER BY period_end DESC LIMIT 1",
+            params![period, before.to_rfc3339()],
+            row_to_digest,
+        );
+
+        match result {
+            Ok(digest) => Ok(Some(digest)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    /// Stored digests, newest first.
+    pub fn list_digests(&self, limit: usize) -> DbResult<Vec<DigestRecord>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, period, period_start, period_end, content, stats, created_at
+             FROM digests ORDER BY created_at DESC LIMIT ?1",
+        )?;
+
+        let digests = stmt.query_map(params![limit as i64], row_to_digest)?;
+        digests.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+}
+
+fn row_to_digest(row: &rusqlite::Row) -> rusqlite::Result<DigestRecord> {
+    let time = |idx: usize| -> rusqlite::Result<DateTime<Utc>> {
+        let s: String = row.get(idx)?;
+        Ok(DateTime::parse_from_rfc3339(&s)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()))
+    };
+    let stats_str: String = row.get(5)?;
+
+    Ok(DigestRecord {
+        id: row.get(0)?,
+        period: row.get(1)?,
+        period_start: time(2)?,
+        period_end: time(3)?,
+        content: row.get(4)?,
+        stats: serde_json::from_str(&stats_str).unwrap_or_default(),
+        created_at: time(6)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use olal_core::DigestStats;
+
+    #[test]
+    fn test_previous_digest() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        let week = Duration::weeks(1);
+
+        let mut stats = DigestStats {
+            item_count: 3,
+            tasks_created: 2,
+            tasks_completed: 1,
+            ..Default::default()
+        };
+        stats.topics.insert("rust".to_string(), 2);
+        let older = DigestRecord::new("weekly", now - week * 3, now - week * 2, "Older", stats.clone());
+        let last = DigestRecord::new("weekly", now - week * 2, now - week, "Last week", stats);
+        let daily = DigestRecord::new("daily", now - Duration::days(8), now - Duration::days(7), "Day", Default::default());
+        for digest in [&older, &last, &daily] {
+            db.save_digest(digest).unwrap();
+        }
+
+        let previous = db.previous_digest("weekly", now - week).unwrap().unwrap();
+        assert_eq!(previous.content, "Last week");
+        assert_eq!(previous.stats.topics["rust"], 2);
+        assert_eq!(previous.stats.task_completion_rate(), Some(0.5));
+
+        assert!(db.previous_digest("monthly", now).unwrap().is_none());
+        assert_eq!(db.list_digests(10).unwrap().len(), 3);
+    }
+}
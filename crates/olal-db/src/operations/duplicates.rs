@@ -0,0 +1,215 @@
+//! Near-duplicate detection and merging.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use crate::fingerprint::{distance, simhash};
+use crate::operations::items::{delete_item_rows, row_to_item};
+use olal_core::{Item, ItemId, LinkType};
+use rusqlite::params;
+use std::collections::{HashMap, HashSet};
+
+impl Database {
+    /// Fingerprint every item that has none yet, new items and those whose
+    /// chunks changed. Returns how many were fingerprinted.
+    pub fn update_fingerprints(&self) -> DbResult<usize> {
+        let ids: Vec<ItemId> = {
+            let conn = self.conn()?;
+            let mut stmt = conn.prepare(
+                "SELECT id FROM items WHERE id NOT IN (SELECT item_id FROM fingerprints)",
+            )?;
+            let ids = stmt.query_map([], |row| row.get(0))?;
+            ids.collect::<Result<_, _>>()?
+        };
+
+        for id in &ids {
+            let text = self
+                .get_chunks_by_item(id)?
+                .into_iter()
+                .map(|c| c.content)
+                .collect::<Vec<_>>()
+                .join(" ");
+            let conn = self.conn()?;
+            conn.execute(
+                "INSERT OR REPLACE INTO fingerprints (item_id, simhash) VALUES (?1, ?2)",
+                params![id, simhash(&text).map(|hash| hash as i64)],
+            )?;
+        }
+
+        Ok(ids.len())
+    }
+
+    /// Find clusters of items whose text is nearly the same: fingerprints
+    /// at most `max_distance` bits apart, chained (if A is near B and B
+    /// near C, all three are one cluster).
+    ///
+    /// Pairs linked as [`LinkType::Duplicates`] were kept on purpose and
+    /// don't count. Each cluster has at least two items, oldest first;
+    /// clusters are ordered by their oldest item.
+    pub fn find_near_duplicates(&self, max_distance: u32) -> DbResult<Vec<Vec<Item>>> {
+        self.update_fingerprints()?;
+
+        let mut kept: HashSet<(ItemId, ItemId)> = HashSet::new();
+        for link in self.list_links()? {
+            if link.link_type == LinkType::Duplicates {
+                kept.insert((link.target_id.clone(), link.source_id.clone()));
+                kept.insert((link.source_id, link.target_id));
+            }
+        }
+
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT i.id, i.item_type, i.title, i.source_path, i.content_hash, i.summary,
+                    i.created_at, i.processed_at, i.metadata, i.archived_at, f.simhash
+             FROM fingerprints f JOIN items i ON i.id = f.item_id
+             WHERE f.simhash IS NOT NULL
+             ORDER BY i.created_at, i.rowid",
+        )?;
+        let items = stmt
+            .query_map([], |row| Ok((row_to_item(row)?, row.get::<_, i64>(10)? as u64)))?
+            .collect::<Result<Vec<_>, _>>()?;
+        drop(stmt);
+        drop(conn);
+
+        // Union-find over item indexes; roots stay the oldest of their set
+        let mut parent: Vec<usize> = (0..items.len()).collect();
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        for i in 0..items.len() {
+            for j in i + 1..items.len() {
+                let ((a, a_hash), (b, b_hash)) = (&items[i], &items[j]);
+                if distance(*a_hash, *b_hash) > max_distance
+                    || kept.contains(&(a.id.clone(), b.id.clone()))
+                {
+                    continue;
+                }
+                let (ri, rj) = (root(&mut parent, i), root(&mut parent, j));
+                parent[ri.max(rj)] = ri.min(rj);
+            }
+        }
+
+        let mut clusters: Vec<Vec<Item>> = Vec::new();
+        let mut by_root: HashMap<usize, usize> = HashMap::new();
+        for (i, (item, _)) in items.into_iter().enumerate() {
+            let r = root(&mut parent, i);
+            match by_root.get(&r) {
+                Some(&cluster) => clusters[cluster].push(item),
+                None => {
+                    by_root.insert(r, clusters.len());
+                    clusters.push(vec![item]);
+                }
+            }
+        }
+
+        clusters.retain(|c| c.len() > 1);
+        Ok(clusters)
+    }
+
+    /// Merge `duplicate` into `keep`: its tags, projects, entities, links,
+    /// review schedule and flashcards move to `keep` (where `keep` doesn't
+    /// already have them), then `duplicate` is deleted with everything
+    /// else derived from it.
+    pub fn merge_items(&self, keep: &ItemId, duplicate: &ItemId) -> DbResult<()> {
+        if keep == duplicate {
+            return Err(DbError::Other(format!("Can't merge item {} into itself", keep)));
+        }
+        self.get_item(keep)?;
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        for sql in [
+            "UPDATE OR IGNORE item_tags SET item_id = ?1 WHERE item_id = ?2",
+            "UPDATE OR IGNORE item_projects SET item_id = ?1 WHERE item_id = ?2",
+            "UPDATE OR IGNORE item_entities SET item_id = ?1 WHERE item_id = ?2",
+            "UPDATE OR IGNORE reviews SET item_id = ?1 WHERE item_id = ?2",
+            "UPDATE flashcards SET item_id = ?1 WHERE item_id = ?2",
+            "UPDATE OR IGNORE links SET source_id = ?1 WHERE source_id = ?2 AND target_id != ?1",
+            "UPDATE OR IGNORE links SET target_id = ?1 WHERE target_id = ?2 AND source_id != ?1",
+        ] {
+            tx.execute(sql, params![keep, duplicate])?;
+        }
+        delete_item_rows(&tx, duplicate)?;
+        tx.commit()?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fingerprint::DEFAULT_MAX_DISTANCE;
+    use crate::Database;
+    use olal_core::{Chunk, Item, ItemType, Link, LinkType};
+
+    const ARTICLE: &str = "Ownership is a set of rules that govern how a Rust program manages \
+        memory. Some languages have garbage collection that regularly looks for no-longer-used \
+        memory as the program runs; in other languages, the programmer must explicitly allocate \
+        and free the memory. Rust uses a third approach.";
+
+    fn note(db: &Database, title: &str, text: &str) -> Item {
+        let item = Item::new(ItemType::Note, title);
+        db.create_item(&item).unwrap();
+        db.create_chunk(&Chunk::new(item.id.clone(), 0, text)).unwrap();
+        item
+    }
+
+    #[test]
+    fn test_find_near_duplicates() {
+        let db = Database::open_in_memory().unwrap();
+        let original = note(&db, "Ownership", ARTICLE);
+        let copy = note(&db, "Ownership (saved again)", &format!("{} Share this page.", ARTICLE));
+        note(&db, "Sourdough", "A sourdough starter needs flour and water fed daily. Bake the \
+            loaf in a hot dutch oven for a crisp crust and an open crumb.");
+        note(&db, "Short", "Rust ownership");
+
+        let clusters = db.find_near_duplicates(DEFAULT_MAX_DISTANCE).unwrap();
+        assert_eq!(clusters.len(), 1);
+        let ids: Vec<&str> = clusters[0].iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(ids, vec![original.id.as_str(), copy.id.as_str()]);
+
+        // New text means a new fingerprint
+        db.delete_chunks_by_item(&copy.id).unwrap();
+        db.create_chunk(&Chunk::new(copy.id.clone(), 0, "Entirely different words about \
+            cooking pasta in salted water until it is tender but still firm to the bite."))
+            .unwrap();
+        assert!(db.find_near_duplicates(DEFAULT_MAX_DISTANCE).unwrap().is_empty());
+
+        // Linked duplicates were kept on purpose
+        db.delete_chunks_by_item(&copy.id).unwrap();
+        db.create_chunk(&Chunk::new(copy.id.clone(), 0, ARTICLE)).unwrap();
+        db.create_link(&Link::new(copy.id.clone(), original.id.clone(), LinkType::Duplicates))
+            .unwrap();
+        assert!(db.find_near_duplicates(DEFAULT_MAX_DISTANCE).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_merge_items() {
+        let db = Database::open_in_memory().unwrap();
+        let keep = note(&db, "Ownership", ARTICLE);
+        let duplicate = note(&db, "Ownership copy", ARTICLE);
+        let other = note(&db, "Borrowing", "Borrowing lets code use a value");
+        db.tag_item(&keep.id, "rust").unwrap();
+        db.tag_item(&duplicate.id, "rust").unwrap();
+        db.tag_item(&duplicate.id, "memory").unwrap();
+        db.create_link(&Link::new(duplicate.id.clone(), other.id.clone(), LinkType::Related))
+            .unwrap();
+        db.create_link(&Link::new(duplicate.id.clone(), keep.id.clone(), LinkType::Related))
+            .unwrap();
+
+        db.merge_items(&keep.id, &duplicate.id).unwrap();
+
+        assert!(db.get_item(&duplicate.id).is_err());
+        let mut tags: Vec<String> = db.get_item_tags(&keep.id).unwrap().into_iter().map(|t| t.name).collect();
+        tags.sort();
+        assert_eq!(tags, vec!["memory", "rust"]);
+        let links = db.get_item_links(&keep.id).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!((links[0].source_id.as_str(), links[0].target_id.as_str()), (keep.id.as_str(), other.id.as_str()));
+
+        assert!(db.merge_items(&keep.id, &keep.id).is_err());
+    }
+}
@@ -0,0 +1,203 @@
+//! Entity operations (people, organizations, topics).
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use crate::operations::items::row_to_item;
+use olal_core::{Entity, EntityId, EntityKind, Item, ItemId};
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+impl Database {
+    /// Get an entity by name and kind, creating it if needed.
+    ///
+    /// Names are matched case-insensitively so "Ada Lovelace" and
+    /// "ada lovelace" resolve to the same entity.
+    pub fn get_or_create_entity(&self, name: &str, kind: EntityKind) -> DbResult<Entity> {
+        let conn = self.conn()?;
+        let existing = conn.query_row(
+            "SELECT id, name, kind, created_at FROM entities
+             WHERE name = ?1 COLLATE NOCASE AND kind = ?2",
+            params![name, kind.as_str()],
+            row_to_entity,
+        );
+
+        match existing {
+            Ok(entity) => Ok(entity),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                let entity = Entity::new(name, kind);
+                conn.execute(
+                    "INSERT INTO entities (id, name, kind, created_at) VALUES (?1, ?2, ?3, ?4)",
+                    params![
+                        entity.id,
+                        entity.name,
+                        entity.kind.as_str(),
+                        entity.created_at.to_rfc3339(),
+                    ],
+                )?;
+                Ok(entity)
+            }
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    /// Record that an item mentions an entity `mentions` times.
+    pub fn add_entity_to_item(
+        &self,
+        item_id: &ItemId,
+        entity_id: &EntityId,
+        mentions: i64,
+    ) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO item_entities (item_id, entity_id, mentions) VALUES (?1, ?2, ?3)
+             ON CONFLICT(item_id, entity_id) DO UPDATE SET mentions = excluded.mentions",
+            params![item_id, entity_id, mentions],
+        )?;
+        Ok(())
+    }
+
+    /// Remove all entity associations for an item (before re-extraction).
+    pub fn clear_item_entities(&self, item_id: &ItemId) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "DELETE FROM item_entities WHERE item_id = ?1",
+            params![item_id],
+        )?;
+        Ok(())
+    }
+
+    /// Get all entities mentioned by an item.
+    pub fn get_item_entities(&self, item_id: &ItemId) -> DbResult<Vec<Entity>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT e.id, e.name, e.kind, e.created_at FROM entities e
+             INNER JOIN item_entities ie ON ie.entity_id = e.id
+             WHERE ie.item_id = ?1
+             ORDER BY e.kind, e.name",
+        )?;
+
+        let entities = stmt.query_map(params![item_id], row_to_entity)?;
+        entities.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// List entities with the number of items mentioning each, most mentioned first.
+    pub fn list_entities(&self, kind: Option<EntityKind>) -> DbResult<Vec<(Entity, i64)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT e.id, e.name, e.kind, e.created_at, COUNT(ie.item_id) as count
+             FROM entities e
+             LEFT JOIN item_entities ie ON ie.entity_id = e.id
+             WHERE ?1 IS NULL OR e.kind = ?1
+             GROUP BY e.id
+             ORDER BY count DESC, e.name",
+        )?;
+
+        let results = stmt.query_map(params![kind.map(|k| k.as_str())], |row| {
+            let entity = row_to_entity(row)?;
+            let count: i64 = row.get(4)?;
+            Ok((entity, count))
+        })?;
+
+        results.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Find entities by name (case-insensitive).
+    pub fn find_entities_by_name(&self, name: &str) -> DbResult<Vec<Entity>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, kind, created_at FROM entities
+             WHERE name = ?1 COLLATE NOCASE
+             ORDER BY kind",
+        )?;
+
+        let entities = stmt.query_map(params![name], row_to_entity)?;
+        entities.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Get all items mentioning an entity, with mention counts, most mentions first.
+    pub fn get_items_by_entity(&self, entity_id: &EntityId) -> DbResult<Vec<(Item, i64)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT i.id, i.item_type, i.title, i.source_path, i.content_hash,
+                    i.summary, i.created_at, i.processed_at, i.metadata, i.archived_at, ie.mentions
+             FROM item_entities ie
+             INNER JOIN items i ON i.id = ie.item_id
+             WHERE ie.entity_id = ?1
+             ORDER BY ie.mentions DESC, i.created_at DESC",
+        )?;
+
+        let results = stmt.query_map(params![entity_id], |row| {
+            let item = row_to_item(row)?;
+            let mentions: i64 = row.get(10)?;
+            Ok((item, mentions))
+        })?;
+
+        results.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+}
+
+fn row_to_entity(row: &rusqlite::Row) -> rusqlite::Result<Entity> {
+    let kind_str: String = row.get(2)?;
+    let created_at_str: String = row.get(3)?;
+
+    Ok(Entity {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        kind: EntityKind::from_str(&kind_str).unwrap_or(EntityKind::Topic),
+        created_at: DateTime::parse_from_rfc3339(&created_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::ItemType;
+
+    #[test]
+    fn test_entity_get_or_create() {
+        let db = Database::open_in_memory().unwrap();
+
+        let a = db.get_or_create_entity("Ada Lovelace", EntityKind::Person).unwrap();
+        let b = db.get_or_create_entity("ada lovelace", EntityKind::Person).unwrap();
+        assert_eq!(a.id, b.id);
+
+        // Same name, different kind is a different entity
+        let topic = db.get_or_create_entity("Ada Lovelace", EntityKind::Topic).unwrap();
+        assert_ne!(a.id, topic.id);
+
+        assert_eq!(db.find_entities_by_name("ADA LOVELACE").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_item_entity_association() {
+        let db = Database::open_in_memory().unwrap();
+
+        let note = Item::new(ItemType::Note, "Meeting notes");
+        let doc = Item::new(ItemType::Document, "Biography");
+        db.create_item(&note).unwrap();
+        db.create_item(&doc).unwrap();
+
+        let ada = db.get_or_create_entity("Ada Lovelace", EntityKind::Person).unwrap();
+        let acme = db.get_or_create_entity("Acme", EntityKind::Organization).unwrap();
+
+        db.add_entity_to_item(&note.id, &ada.id, 1).unwrap();
+        db.add_entity_to_item(&doc.id, &ada.id, 5).unwrap();
+        db.add_entity_to_item(&note.id, &acme.id, 2).unwrap();
+
+        let items = db.get_items_by_entity(&ada.id).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].0.id, doc.id);
+        assert_eq!(items[0].1, 5);
+
+        let people = db.list_entities(Some(EntityKind::Person)).unwrap();
+        assert_eq!(people.len(), 1);
+        assert_eq!(people[0].1, 2);
+
+        assert_eq!(db.get_item_entities(&note.id).unwrap().len(), 2);
+
+        db.clear_item_entities(&note.id).unwrap();
+        assert!(db.get_item_entities(&note.id).unwrap().is_empty());
+    }
+}
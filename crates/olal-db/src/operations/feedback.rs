@@ -0,0 +1,97 @@
+//! Digest feedback operations.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use chrono::{DateTime, Utc};
+use olal_core::{DigestPreference, FeedbackTarget};
+use rusqlite::params;
+
+impl Database {
+    /// Record a thumbs-up (`positive`) or thumbs-down for a digest topic or source.
+    ///
+    /// Returns the updated preference.
+    pub fn record_digest_feedback(
+        &self,
+        target: FeedbackTarget,
+        name: &str,
+        positive: bool,
+    ) -> DbResult<DigestPreference> {
+        let delta: i64 = if positive { 1 } else { -1 };
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO digest_feedback (target, name, score, updated_at) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(target, name) DO UPDATE
+             SET score = score + excluded.score, updated_at = excluded.updated_at",
+            params![target.as_str(), name, delta, Utc::now().to_rfc3339()],
+        )?;
+
+        conn.query_row(
+            "SELECT target, name, score, updated_at FROM digest_feedback
+             WHERE target = ?1 AND name = ?2",
+            params![target.as_str(), name],
+            row_to_preference,
+        )
+        .map_err(DbError::from)
+    }
+
+    /// All digest preferences with a non-zero score, strongest first.
+    pub fn digest_preferences(&self) -> DbResult<Vec<DigestPreference>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT target, name, score, updated_at FROM digest_feedback
+             WHERE score != 0
+             ORDER BY ABS(score) DESC, name",
+        )?;
+
+        let prefs = stmt.query_map([], row_to_preference)?;
+        prefs.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Forget all digest feedback. Returns the number of entries removed.
+    pub fn clear_digest_feedback(&self) -> DbResult<usize> {
+        let conn = self.conn()?;
+        Ok(conn.execute("DELETE FROM digest_feedback", [])?)
+    }
+}
+
+fn row_to_preference(row: &rusqlite::Row) -> rusqlite::Result<DigestPreference> {
+    let target_str: String = row.get(0)?;
+    let updated_at_str: String = row.get(3)?;
+
+    Ok(DigestPreference {
+        target: FeedbackTarget::from_str(&target_str).unwrap_or(FeedbackTarget::Topic),
+        name: row.get(1)?,
+        score: row.get(2)?,
+        updated_at: DateTime::parse_from_rfc3339(&updated_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digest_feedback_accumulates() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.record_digest_feedback(FeedbackTarget::Topic, "rust", true).unwrap();
+        let pref = db.record_digest_feedback(FeedbackTarget::Topic, "Rust", true).unwrap();
+        assert_eq!(pref.score, 2);
+
+        db.record_digest_feedback(FeedbackTarget::Source, "youtube", false).unwrap();
+        db.record_digest_feedback(FeedbackTarget::Topic, "cooking", true).unwrap();
+        db.record_digest_feedback(FeedbackTarget::Topic, "cooking", false).unwrap();
+
+        // Neutral entries are hidden
+        let prefs = db.digest_preferences().unwrap();
+        assert_eq!(prefs.len(), 2);
+        assert_eq!(prefs[0].name, "rust");
+        assert_eq!(prefs[1].target, FeedbackTarget::Source);
+        assert_eq!(prefs[1].score, -1);
+
+        assert_eq!(db.clear_digest_feedback().unwrap(), 3);
+        assert!(db.digest_preferences().unwrap().is_empty());
+    }
+}
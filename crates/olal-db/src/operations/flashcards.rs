@@ -0,0 +1,90 @@
+//! Flashcard operations.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use chrono::{DateTime, Utc};
+use olal_core::{Flashcard, ItemId};
+use rusqlite::params;
+
+impl Database {
+    /// Replace an item's flashcards with `cards`. Returns how many were removed.
+    pub fn replace_flashcards(&self, item_id: &ItemId, cards: &[Flashcard]) -> DbResult<usize> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+
+        let removed = tx.execute("DELETE FROM flashcards WHERE item_id = ?1", params![item_id])?;
+        for card in cards {
+            tx.execute(
+                "INSERT INTO flashcards (id, item_id, question, answer, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    card.id,
+                    card.item_id,
+                    card.question,
+                    card.answer,
+                    card.created_at.to_rfc3339()
+                ],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Flashcards for one item, or all of them, grouped by item in creation order.
+    pub fn list_flashcards(&self, item_id: Option<&ItemId>) -> DbResult<Vec<Flashcard>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_id, question, answer, created_at FROM flashcards
+             WHERE ?1 IS NULL OR item_id = ?1
+             ORDER BY item_id, created_at, rowid",
+        )?;
+
+        let cards = stmt.query_map(params![item_id], row_to_flashcard)?;
+        cards.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+}
+
+fn row_to_flashcard(row: &rusqlite::Row) -> rusqlite::Result<Flashcard> {
+    let created_at_str: String = row.get(4)?;
+
+    Ok(Flashcard {
+        id: row.get(0)?,
+        item_id: row.get(1)?,
+        question: row.get(2)?,
+        answer: row.get(3)?,
+        created_at: DateTime::parse_from_rfc3339(&created_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, ItemType};
+
+    #[test]
+    fn test_replace_flashcards() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Ownership");
+        db.create_item(&item).unwrap();
+
+        let first = vec![Flashcard::new(item.id.clone(), "Old?", "Old.")];
+        assert_eq!(db.replace_flashcards(&item.id, &first).unwrap(), 0);
+
+        let second = vec![
+            Flashcard::new(item.id.clone(), "What owns a value?", "Exactly one owner."),
+            Flashcard::new(item.id.clone(), "When is it dropped?", "When the owner goes out of scope."),
+        ];
+        assert_eq!(db.replace_flashcards(&item.id, &second).unwrap(), 1);
+
+        let cards = db.list_flashcards(Some(&item.id)).unwrap();
+        assert_eq!(cards.len(), 2);
+        assert_eq!(cards[0].question, "What owns a value?");
+        assert_eq!(db.list_flashcards(None).unwrap().len(), 2);
+
+        db.delete_item_cascade(&item.id).unwrap();
+        assert!(db.list_flashcards(None).unwrap().is_empty());
+    }
+}
@@ -1,32 +1,70 @@
 //! Item CRUD operations.
 
+use crate::cache::{self, Cached};
+use crate::compression::chunk_content;
 use crate::database::Database;
 use crate::error::{DbError, DbResult};
-use olal_core::{Item, ItemType};
+use crate::filter::SearchFilter;
+use crate::fts;
+use crate::operations::chunks::{bulk_insert_chunks, insert_chunk, BULK_INSERT_MIN};
+use olal_core::{Chunk, Item, ItemId, ItemType};
 use chrono::{DateTime, Utc};
-use rusqlite::params;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::collections::HashMap;
+
+/// BM25 weights of the item index's title, summary and tag names.
+const TITLE_WEIGHT: f64 = 10.0;
+const SUMMARY_WEIGHT: f64 = 2.0;
+const TAGS_WEIGHT: f64 = 5.0;
+
+/// An item found by full-text search, with its best matching chunk.
+#[derive(Debug, Clone)]
+pub struct SearchMatch {
+    pub item: Item,
+    /// The best matching chunk, or the item's first chunk if only its
+    /// title, summary or tags matched; `None` for an item without chunks.
+    pub chunk: Option<Chunk>,
+}
+
+/// Rows removed (or that would be removed) when deleting an item.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DeletionSummary {
+    pub chunks: i64,
+    pub embeddings: i64,
+    pub tags: i64,
+    pub links: i64,
+    pub entities: i64,
+}
 
 impl Database {
     /// Create a new item.
     pub fn create_item(&self, item: &Item) -> DbResult<()> {
         let conn = self.conn()?;
-        conn.execute(
-            r#"
-            INSERT INTO items (id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-            "#,
-            params![
-                item.id,
-                item.item_type.as_str(),
-                item.title,
-                item.source_path,
-                item.content_hash,
-                item.summary,
-                item.created_at.to_rfc3339(),
-                item.processed_at.map(|dt| dt.to_rfc3339()),
-                item.metadata.to_string(),
-            ],
-        )?;
+        insert_item(&conn, item)
+    }
+
+    /// Create several items and their chunks in one transaction.
+    ///
+    /// Used by batch ingestion so thousands of small files don't each pay
+    /// for their own commit.
+    pub fn create_items_with_chunks(&self, batch: &[(Item, Vec<Chunk>)]) -> DbResult<()> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+
+        for (item, _) in batch {
+            insert_item(&tx, item)?;
+        }
+        let chunks: Vec<&Chunk> = batch.iter().flat_map(|(_, chunks)| chunks).collect();
+        if chunks.len() >= BULK_INSERT_MIN {
+            bulk_insert_chunks(&tx, chunks)?;
+        } else {
+            for chunk in chunks {
+                insert_chunk(&tx, chunk)?;
+            }
+        }
+
+        tx.commit()?;
         Ok(())
     }
 
@@ -34,32 +72,9 @@ impl Database {
     pub fn get_item(&self, id: &str) -> DbResult<Item> {
         let conn = self.conn()?;
         let item = conn.query_row(
-            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata FROM items WHERE id = ?1",
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at FROM items WHERE id = ?1",
             params![id],
-            |row| {
-                let item_type_str: String = row.get(1)?;
-                let created_at_str: String = row.get(6)?;
-                let processed_at_str: Option<String> = row.get(7)?;
-                let metadata_str: String = row.get(8)?;
-
-                Ok(Item {
-                    id: row.get(0)?,
-                    item_type: ItemType::from_str(&item_type_str).unwrap_or(ItemType::Document),
-                    title: row.get(2)?,
-                    source_path: row.get(3)?,
-                    content_hash: row.get(4)?,
-                    summary: row.get(5)?,
-                    created_at: DateTime::parse_from_rfc3339(&created_at_str)
-                        .map(|dt| dt.with_timezone(&Utc))
-                        .unwrap_or_else(|_| Utc::now()),
-                    processed_at: processed_at_str.and_then(|s| {
-                        DateTime::parse_from_rfc3339(&s)
-                            .map(|dt| dt.with_timezone(&Utc))
-                            .ok()
-                    }),
-                    metadata: serde_json::from_str(&metadata_str).unwrap_or_default(),
-                })
-            },
+            row_to_item,
         ).map_err(|e| match e {
             rusqlite::Error::QueryReturnedNoRows => DbError::NotFound(format!("Item not found: {}", id)),
             _ => DbError::from(e),
@@ -108,6 +123,109 @@ impl Database {
         Ok(())
     }
 
+    /// Count the rows that [`Database::delete_item_cascade`] would remove.
+    pub fn item_deletion_preview(&self, id: &str) -> DbResult<DeletionSummary> {
+        let conn = self.conn()?;
+        let count = |sql: &str| -> DbResult<i64> {
+            Ok(conn.query_row(sql, params![id], |row| row.get(0))?)
+        };
+
+        Ok(DeletionSummary {
+            chunks: count("SELECT COUNT(*) FROM chunks WHERE item_id = ?1")?,
+            embeddings: count(
+                "SELECT COUNT(*) FROM embeddings WHERE chunk_id IN (SELECT id FROM chunks WHERE item_id = ?1)",
+            )?,
+            tags: count("SELECT COUNT(*) FROM item_tags WHERE item_id = ?1")?,
+            links: count("SELECT COUNT(*) FROM links WHERE source_id = ?1 OR target_id = ?1")?,
+            entities: count("SELECT COUNT(*) FROM item_entities WHERE item_id = ?1")?,
+        })
+    }
+
+    /// Delete an item and everything derived from it in one transaction.
+    ///
+    /// Removes embeddings, chunks (and their FTS rows, via trigger), tag and
+    /// entity associations, and links in either direction. This doesn't rely
+    /// on `ON DELETE CASCADE`, so it also cleans up databases opened without
+    /// foreign keys enabled.
+    pub fn delete_item_cascade(&self, id: &str) -> DbResult<DeletionSummary> {
+        let summary = self.item_deletion_preview(id)?;
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        delete_item_rows(&tx, id)?;
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    /// Find groups of likely-duplicate items.
+    ///
+    /// Items are grouped when they share a content hash, or when they have
+    /// the same type and (case-insensitive) title. Each group has at least
+    /// two items, oldest first.
+    pub fn find_duplicate_items(&self) -> DbResult<Vec<Vec<Item>>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+             FROM items ORDER BY created_at",
+        )?;
+        let items = stmt
+            .query_map([], row_to_item)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut groups: Vec<Vec<Item>> = Vec::new();
+        let mut by_key: std::collections::HashMap<String, usize> = std::collections::HashMap::new();
+
+        for item in items {
+            let hash_key = item.content_hash.as_ref().map(|h| format!("hash:{}", h));
+            let title_key = format!("title:{}:{}", item.item_type.as_str(), item.title.to_lowercase());
+
+            let existing = hash_key
+                .as_ref()
+                .and_then(|k| by_key.get(k))
+                .or_else(|| by_key.get(&title_key))
+                .copied();
+
+            let index = match existing {
+                Some(index) => {
+                    groups[index].push(item);
+                    index
+                }
+                None => {
+                    groups.push(vec![item]);
+                    groups.len() - 1
+                }
+            };
+
+            if let Some(k) = hash_key {
+                by_key.entry(k).or_insert(index);
+            }
+            by_key.entry(title_key).or_insert(index);
+        }
+
+        groups.retain(|g| g.len() > 1);
+        Ok(groups)
+    }
+
+    /// Get items that have chunks but no summary (and none awaiting review), oldest first.
+    pub fn items_without_summary(&self, limit: i64) -> DbResult<Vec<Item>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+             FROM items
+             WHERE (summary IS NULL OR summary = '') AND archived_at IS NULL
+               AND EXISTS (SELECT 1 FROM chunks c WHERE c.item_id = items.id)
+               AND NOT EXISTS (
+                   SELECT 1 FROM ai_suggestions s
+                   WHERE s.item_id = items.id AND s.kind = 'summary' AND s.status = 'pending_review'
+               )
+             ORDER BY created_at
+             LIMIT ?1",
+        )?;
+
+        let items = stmt.query_map(params![limit], row_to_item)?;
+        items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
     /// List items with optional filtering.
     pub fn list_items(&self, item_type: Option<ItemType>, limit: Option<i64>) -> DbResult<Vec<Item>> {
         let conn = self.conn()?;
@@ -115,12 +233,12 @@ impl Database {
 
         let sql = match item_type {
             Some(_) => {
-                "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata
-                 FROM items WHERE item_type = ?1 ORDER BY created_at DESC LIMIT ?2"
+                "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+                 FROM items WHERE item_type = ?1 AND archived_at IS NULL ORDER BY created_at DESC LIMIT ?2"
             }
             None => {
-                "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata
-                 FROM items ORDER BY created_at DESC LIMIT ?1"
+                "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+                 FROM items WHERE archived_at IS NULL ORDER BY created_at DESC LIMIT ?1"
             }
         };
 
@@ -135,28 +253,30 @@ impl Database {
         items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 
-    /// Find item by source path.
+    /// Find the newest item ingested from a source path.
     pub fn find_item_by_path(&self, path: &str) -> DbResult<Option<Item>> {
+        Ok(self.find_items_by_path(path)?.into_iter().next())
+    }
+
+    /// Every item ingested from a source path, newest first. A path holds
+    /// several items when a reused filename (e.g. `recording.mov`) brought
+    /// new content.
+    pub fn find_items_by_path(&self, path: &str) -> DbResult<Vec<Item>> {
         let conn = self.conn()?;
-        let result = conn.query_row(
-            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata
-             FROM items WHERE source_path = ?1",
-            params![path],
-            row_to_item,
-        );
+        let mut stmt = conn.prepare(
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+             FROM items WHERE source_path = ?1 ORDER BY created_at DESC, rowid DESC",
+        )?;
 
-        match result {
-            Ok(item) => Ok(Some(item)),
-            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
-            Err(e) => Err(DbError::from(e)),
-        }
+        let items = stmt.query_map(params![path], row_to_item)?;
+        items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 
     /// Find item by content hash.
     pub fn find_item_by_hash(&self, hash: &str) -> DbResult<Option<Item>> {
         let conn = self.conn()?;
         let result = conn.query_row(
-            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
              FROM items WHERE content_hash = ?1",
             params![hash],
             row_to_item,
@@ -170,24 +290,267 @@ impl Database {
     }
 
     /// Full-text search on items via chunks.
+    ///
+    /// The query is made safe with [`fts::match_query`]: words and quoted
+    /// phrases, `word*` prefixes and `AND`/`OR`/`NOT`. Results are served
+    /// from the query cache until the next write.
     pub fn search_items(&self, query: &str, limit: Option<i64>) -> DbResult<Vec<Item>> {
-        let conn = self.conn()?;
+        self.search_items_filtered(query, limit, &SearchFilter::default())
+    }
+
+    /// Full-text search on items matching `filter`.
+    pub fn search_items_filtered(
+        &self,
+        query: &str,
+        limit: Option<i64>,
+        filter: &SearchFilter,
+    ) -> DbResult<Vec<Item>> {
+        Ok(self
+            .search_matches_filtered(query, limit, filter)?
+            .into_iter()
+            .map(|m| m.item)
+            .collect())
+    }
+
+    /// Full-text search on items matching `filter`, with each item's best
+    /// matching chunk, for showing where the query matched.
+    ///
+    /// Items are found by their chunks and by their title, summary and tag
+    /// names. An item's score adds its best chunk's BM25 to its item BM25,
+    /// in which a title match weighs most, so an item matching both ranks
+    /// first. Results are served from the query cache until the next write.
+    pub fn search_matches_filtered(
+        &self,
+        query: &str,
+        limit: Option<i64>,
+        filter: &SearchFilter,
+    ) -> DbResult<Vec<SearchMatch>> {
         let limit = limit.unwrap_or(20);
+        let key = cache::fts_key(query, limit, filter);
+        if let Some(Cached::Matches(matches)) = self.cache.get(&key) {
+            return Ok(matches);
+        }
+        let generation = self.cache.generation();
 
-        let mut stmt = conn.prepare(
+        let conn = self.conn()?;
+
+        let (conditions, mut filter_params) = filter.to_sql(3);
+        let mut stmt = conn.prepare(&format!(
             r#"
-            SELECT DISTINCT i.id, i.item_type, i.title, i.source_path, i.content_hash,
-                   i.summary, i.created_at, i.processed_at, i.metadata
-            FROM items i
-            INNER JOIN chunks c ON c.item_id = i.id
-            INNER JOIN chunks_fts fts ON fts.rowid = c.rowid
-            WHERE chunks_fts MATCH ?1
-            ORDER BY rank
+            WITH hits AS (
+                SELECT c.rowid AS chunk_rowid, c.item_id, bm25(chunks_fts) AS score
+                FROM chunks_fts
+                INNER JOIN chunks c ON c.rowid = chunks_fts.rowid
+                INNER JOIN items i ON i.id = c.item_id
+                WHERE chunks_fts MATCH ?1 AND i.archived_at IS NULL{conditions}
+            ),
+            best AS (
+                SELECT chunk_rowid, item_id, score,
+                       ROW_NUMBER() OVER (PARTITION BY item_id ORDER BY score) AS n
+                FROM hits
+            ),
+            item_hits AS (
+                SELECT i.id AS item_id, bm25(items_fts, 0.0, {title}, {summary}, {tags}) AS score
+                FROM items_fts
+                INNER JOIN items i ON i.id = items_fts.item_id
+                WHERE items_fts MATCH ?1 AND i.archived_at IS NULL{conditions}
+            ),
+            scores AS (
+                SELECT item_id, SUM(score) AS score FROM (
+                    SELECT item_id, score FROM best WHERE n = 1
+                    UNION ALL
+                    SELECT item_id, score FROM item_hits
+                )
+                GROUP BY item_id
+            )
+            SELECT i.id, i.item_type, i.title, i.source_path, i.content_hash,
+                   i.summary, i.created_at, i.processed_at, i.metadata, i.archived_at,
+                   c.id, c.item_id, c.chunk_index, c.content, c.start_time, c.end_time,
+                   c.start_page, c.end_page
+            FROM scores
+            INNER JOIN items i ON i.id = scores.item_id
+            LEFT JOIN best ON best.item_id = scores.item_id AND best.n = 1
+            LEFT JOIN chunks c ON c.rowid = COALESCE(
+                best.chunk_rowid,
+                (SELECT rowid FROM chunks WHERE item_id = scores.item_id ORDER BY chunk_index LIMIT 1)
+            )
+            ORDER BY scores.score
             LIMIT ?2
             "#,
+            conditions = conditions,
+            title = TITLE_WEIGHT,
+            summary = SUMMARY_WEIGHT,
+            tags = TAGS_WEIGHT,
+        ))?;
+
+        let mut params = vec![Value::Text(fts::match_query(query)), Value::Integer(limit)];
+        params.append(&mut filter_params);
+        let matches = stmt
+            .query_map(params_from_iter(params), |row| {
+                let chunk = match row.get::<_, Option<String>>(10)? {
+                    Some(id) => Some(Chunk {
+                        id,
+                        item_id: row.get(11)?,
+                        chunk_index: row.get(12)?,
+                        content: chunk_content(row, 13)?,
+                        start_time: row.get(14)?,
+                        end_time: row.get(15)?,
+                        start_page: row.get(16)?,
+                        end_page: row.get(17)?,
+                    }),
+                    None => None,
+                };
+                Ok(SearchMatch { item: row_to_item(row)?, chunk })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.cache.put(key, generation, Cached::Matches(matches.clone()));
+        Ok(matches)
+    }
+
+    /// A respelling of `query` with words from the full-text indexes, for
+    /// a "did you mean" when a search finds nothing. See
+    /// [`fts::spelling_suggestion`].
+    pub fn spelling_suggestion(&self, query: &str) -> DbResult<Option<String>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT term, doc FROM chunks_fts_vocab UNION ALL SELECT term, doc FROM items_fts_vocab",
+        )?;
+        let mut vocabulary: HashMap<String, i64> = HashMap::new();
+        for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))? {
+            let (term, docs) = row?;
+            *vocabulary.entry(term).or_default() += docs;
+        }
+        let Some(suggestion) = fts::spelling_suggestion(query, &vocabulary) else {
+            return Ok(None);
+        };
+
+        let sql: String = conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'chunks_fts'",
+            [],
+            |row| row.get(0),
+        )?;
+        if fts::Tokenizer::from_table_sql(&sql) != Some(fts::Tokenizer::Porter) {
+            return Ok(Some(suggestion));
+        }
+        // A porter index holds stems ("kubernet"), so each correction is
+        // swapped for a word that was stemmed to it
+        let mut changed = false;
+        let mut words = Vec::new();
+        for (word, term) in query.split(' ').zip(suggestion.split(' ')) {
+            let surface = if word == term {
+                None
+            } else {
+                surface_form(&conn, term)?.filter(|surface| *surface != word.to_lowercase())
+            };
+            changed |= surface.is_some();
+            words.push(surface.unwrap_or_else(|| word.to_string()));
+        }
+        Ok(changed.then(|| words.join(" ")))
+    }
+
+    /// Archive an item, hiding it from search, recent items and RAG context.
+    pub fn archive_item(&self, id: &str) -> DbResult<()> {
+        let conn = self.conn()?;
+        let rows = conn.execute(
+            "UPDATE items SET archived_at = ?2 WHERE id = ?1",
+            params![id, Utc::now().to_rfc3339()],
+        )?;
+
+        if rows == 0 {
+            return Err(DbError::NotFound(format!("Item not found: {}", id)));
+        }
+
+        Ok(())
+    }
+
+    /// Restore an archived item.
+    pub fn unarchive_item(&self, id: &str) -> DbResult<()> {
+        let conn = self.conn()?;
+        let rows = conn.execute(
+            "UPDATE items SET archived_at = NULL WHERE id = ?1",
+            params![id],
+        )?;
+
+        if rows == 0 {
+            return Err(DbError::NotFound(format!("Item not found: {}", id)));
+        }
+
+        Ok(())
+    }
+
+    /// List archived items, optionally of one type, most recently archived first.
+    pub fn list_archived_items(&self, item_type: Option<ItemType>, limit: Option<i64>) -> DbResult<Vec<Item>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+             FROM items WHERE archived_at IS NOT NULL AND (?1 IS NULL OR item_type = ?1)
+             ORDER BY archived_at DESC LIMIT ?2",
+        )?;
+
+        let item_type = item_type.map(|t| t.as_str());
+        let items = stmt.query_map(params![item_type, limit.unwrap_or(100)], row_to_item)?;
+        items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Record that an item was opened (e.g. with `olal show`).
+    pub fn mark_item_viewed(&self, id: &str) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE items SET last_viewed_at = ?2 WHERE id = ?1",
+            params![id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// Items created before `before`, for retention rules.
+    ///
+    /// `unread_only` keeps items that were never viewed; archived items are
+    /// only included with `include_archived`.
+    pub fn retention_candidates(
+        &self,
+        item_type: Option<ItemType>,
+        before: DateTime<Utc>,
+        unread_only: bool,
+        include_archived: bool,
+    ) -> DbResult<Vec<Item>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+             FROM items
+             WHERE created_at < ?1
+               AND (?2 IS NULL OR item_type = ?2)
+               AND (?3 = 0 OR last_viewed_at IS NULL)
+               AND (?4 = 1 OR archived_at IS NULL)
+             ORDER BY created_at",
         )?;
 
-        let items = stmt.query_map(params![query, limit], row_to_item)?;
+        let items = stmt.query_map(
+            params![
+                before.to_rfc3339(),
+                item_type.map(|t| t.as_str()),
+                unread_only,
+                include_archived,
+            ],
+            row_to_item,
+        )?;
+        items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Bookmarks waiting to have their page fetched (metadata `"fetch":
+    /// "pending"`), oldest first.
+    pub fn bookmarks_to_fetch(&self, limit: i64) -> DbResult<Vec<Item>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+             FROM items
+             WHERE item_type = 'bookmark' AND archived_at IS NULL
+               AND json_extract(metadata, '$.fetch') = 'pending'
+             ORDER BY created_at
+             LIMIT ?1",
+        )?;
+
+        let items = stmt.query_map(params![limit], row_to_item)?;
         items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 
@@ -202,8 +565,8 @@ impl Database {
         let since_str = since.to_rfc3339();
         let mut stmt = conn.prepare(
             "SELECT id, item_type, title, source_path, content_hash, summary,
-                    created_at, processed_at, metadata
-             FROM items WHERE created_at >= ?1 ORDER BY created_at DESC",
+                    created_at, processed_at, metadata, archived_at
+             FROM items WHERE created_at >= ?1 AND archived_at IS NULL ORDER BY created_at DESC",
         )?;
         let items = stmt.query_map(params![since_str], row_to_item)?;
         items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
@@ -216,8 +579,9 @@ impl Database {
         let end_str = end.to_rfc3339();
         let mut stmt = conn.prepare(
             "SELECT id, item_type, title, source_path, content_hash, summary,
-                    created_at, processed_at, metadata
-             FROM items WHERE created_at >= ?1 AND created_at <= ?2 ORDER BY created_at DESC",
+                    created_at, processed_at, metadata, archived_at
+             FROM items WHERE created_at >= ?1 AND created_at <= ?2 AND archived_at IS NULL
+             ORDER BY created_at DESC",
         )?;
         let items = stmt.query_map(params![start_str, end_str], row_to_item)?;
         items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
@@ -227,23 +591,13 @@ impl Database {
     ///
     /// Useful for CLI where users can type partial IDs.
     pub fn get_item_by_prefix(&self, prefix: &str) -> DbResult<Item> {
-        let conn = self.conn()?;
-
         // First try exact match
         if let Ok(item) = self.get_item(prefix) {
             return Ok(item);
         }
 
         // Then try prefix match
-        let pattern = format!("{}%", prefix);
-        let mut stmt = conn.prepare(
-            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata
-             FROM items WHERE id LIKE ?1 LIMIT 2",
-        )?;
-
-        let items: Vec<Item> = stmt
-            .query_map(params![pattern], row_to_item)?
-            .collect::<Result<Vec<_>, _>>()?;
+        let items = self.find_items_by_prefix(prefix, 2)?;
 
         match items.len() {
             0 => Err(DbError::NotFound(format!("Item not found: {}", prefix))),
@@ -254,13 +608,88 @@ impl Database {
             ))),
         }
     }
+
+    /// Items whose ID starts with `prefix`, newest first.
+    pub fn find_items_by_prefix(&self, prefix: &str, limit: i64) -> DbResult<Vec<Item>> {
+        let conn = self.conn()?;
+        let pattern = format!("{}%", prefix.replace(['%', '_'], ""));
+        let mut stmt = conn.prepare(
+            "SELECT id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at
+             FROM items WHERE id LIKE ?1 ORDER BY created_at DESC LIMIT ?2",
+        )?;
+
+        let items = stmt.query_map(params![pattern, limit], row_to_item)?;
+        items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// IDs and titles of all items (including archived), for fuzzy lookups.
+    pub fn item_titles(&self) -> DbResult<Vec<(ItemId, String)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT id, title FROM items ORDER BY created_at DESC")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
 }
 
-fn row_to_item(row: &rusqlite::Row) -> rusqlite::Result<Item> {
+fn insert_item(conn: &Connection, item: &Item) -> DbResult<()> {
+    conn.execute(
+        r#"
+        INSERT INTO items (id, item_type, title, source_path, content_hash, summary, created_at, processed_at, metadata, archived_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+        "#,
+        params![
+            item.id,
+            item.item_type.as_str(),
+            item.title,
+            item.source_path,
+            item.content_hash,
+            item.summary,
+            item.created_at.to_rfc3339(),
+            item.processed_at.map(|dt| dt.to_rfc3339()),
+            item.metadata.to_string(),
+            item.archived_at.map(|dt| dt.to_rfc3339()),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Delete an item's row and every row derived from it, for
+/// [`Database::delete_item_cascade`] and merges, inside their transaction.
+pub(crate) fn delete_item_rows(conn: &Connection, id: &str) -> DbResult<()> {
+    conn.execute(
+        "DELETE FROM embeddings WHERE chunk_id IN (SELECT id FROM chunks WHERE item_id = ?1)",
+        params![id],
+    )?;
+    conn.execute("DELETE FROM chunks WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM item_tags WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM item_projects WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM item_entities WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM item_content WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM reviews WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM flashcards WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM fingerprints WHERE item_id = ?1", params![id])?;
+    conn.execute(
+        "DELETE FROM ai_suggestions WHERE item_id = ?1 OR (kind = 'link' AND value = ?1)",
+        params![id],
+    )?;
+    conn.execute(
+        "DELETE FROM links WHERE source_id = ?1 OR target_id = ?1",
+        params![id],
+    )?;
+    let rows = conn.execute("DELETE FROM items WHERE id = ?1", params![id])?;
+
+    if rows == 0 {
+        return Err(DbError::NotFound(format!("Item not found: {}", id)));
+    }
+    Ok(())
+}
+
+pub(crate) fn row_to_item(row: &rusqlite::Row) -> rusqlite::Result<Item> {
     let item_type_str: String = row.get(1)?;
     let created_at_str: String = row.get(6)?;
     let processed_at_str: Option<String> = row.get(7)?;
     let metadata_str: String = row.get(8)?;
+    let archived_at_str: Option<String> = row.get(9)?;
 
     Ok(Item {
         id: row.get(0)?,
@@ -278,9 +707,63 @@ fn row_to_item(row: &rusqlite::Row) -> rusqlite::Result<Item> {
                 .ok()
         }),
         metadata: serde_json::from_str(&metadata_str).unwrap_or_default(),
+        archived_at: archived_at_str.and_then(|s| {
+            DateTime::parse_from_rfc3339(&s)
+                .map(|dt| dt.with_timezone(&Utc))
+                .ok()
+        }),
     })
 }
 
+/// A word as written that a porter index stemmed to `term`, read from the
+/// text at one of the term's positions in the chunk or item index.
+fn surface_form(conn: &Connection, term: &str) -> DbResult<Option<String>> {
+    conn.execute_batch(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS temp.chunks_fts_instance USING fts5vocab(main, chunks_fts, instance);
+         CREATE VIRTUAL TABLE IF NOT EXISTS temp.items_fts_instance USING fts5vocab(main, items_fts, instance);",
+    )?;
+
+    let in_chunk: Option<(i64, i64)> = conn
+        .query_row(
+            "SELECT doc, offset FROM temp.chunks_fts_instance WHERE term = ?1 LIMIT 1",
+            [term],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    let text = match in_chunk {
+        Some((rowid, offset)) => conn
+            .query_row("SELECT content FROM chunks WHERE rowid = ?1", [rowid], |row| chunk_content(row, 0))
+            .optional()?
+            .map(|text| (text, offset)),
+        None => {
+            let in_item: Option<(i64, String, i64)> = conn
+                .query_row(
+                    "SELECT doc, col, offset FROM temp.items_fts_instance WHERE term = ?1 LIMIT 1",
+                    [term],
+                    |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+                )
+                .optional()?;
+            match in_item {
+                Some((rowid, column, offset)) if ["title", "summary", "tags"].contains(&column.as_str()) => conn
+                    .query_row(&format!("SELECT {} FROM items_fts WHERE rowid = ?1", column), [rowid], |row| {
+                        row.get::<_, String>(0)
+                    })
+                    .optional()?
+                    .map(|text| (text, offset)),
+                _ => None,
+            }
+        }
+    };
+
+    let Some((text, offset)) = text else {
+        return Ok(None);
+    };
+    let word = fts::words(&text).nth(offset as usize).map(|(_, word)| word.to_lowercase());
+    // Porter only rewrites endings, so the word starts like its stem
+    let prefix: String = term.chars().take(3).collect();
+    Ok(word.filter(|word| word.starts_with(&prefix)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,6 +809,142 @@ mod tests {
 
         let not_found = db.find_item_by_path("/nonexistent").unwrap();
         assert!(not_found.is_none());
+
+        // A reused path keeps both items, newest first
+        let mut newer = Item::new(ItemType::Note, "Newer Note")
+            .with_source_path("/path/to/note.md");
+        newer.created_at = item.created_at + chrono::Duration::minutes(5);
+        db.create_item(&newer).unwrap();
+
+        let history = db.find_items_by_path("/path/to/note.md").unwrap();
+        let titles: Vec<_> = history.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(titles, vec!["Newer Note", "Test Note"]);
+        assert_eq!(db.find_item_by_path("/path/to/note.md").unwrap().unwrap().id, newer.id);
+    }
+
+    #[test]
+    fn test_search_titles_summaries_tags() {
+        let db = Database::open_in_memory().unwrap();
+        let add = |title: &str, content: &str| {
+            let item = Item::new(ItemType::Note, title);
+            db.create_item(&item).unwrap();
+            db.create_chunk(&Chunk::new(item.id.clone(), 0, content)).unwrap();
+            item
+        };
+        let both = add("Zeppelin history", "The first zeppelin flew in 1900.");
+        add("Travel notes", "We saw a zeppelin over the bay.");
+        let titled = add("Zeppelin sketches", "Pencil drawings.");
+        let mut summarized = add("Untitled", "Nothing to see.");
+        summarized.summary = Some("An overview of zeppelin design.".to_string());
+        db.update_item(&summarized).unwrap();
+        let tagged = add("Photos", "Pictures from the museum.");
+        db.tag_item(&tagged.id, "zeppelin").unwrap();
+
+        let matches = db.search_matches_filtered("zeppelin", None, &SearchFilter::default()).unwrap();
+        assert_eq!(matches.len(), 5);
+        assert_eq!(matches[0].item.id, both.id);
+        assert_eq!(matches[0].chunk.as_ref().unwrap().content, "The first zeppelin flew in 1900.");
+        // A title match without a body match still shows the body
+        let found = matches.iter().find(|m| m.item.id == titled.id).unwrap();
+        assert_eq!(found.chunk.as_ref().unwrap().content, "Pencil drawings.");
+
+        // Tag changes are indexed
+        let tag = db.list_tags().unwrap().into_iter().find(|t| t.name == "zeppelin").unwrap();
+        db.rename_tag(&tag.id, "airship").unwrap();
+        assert_eq!(db.search_items("airship", None).unwrap()[0].id, tagged.id);
+        db.remove_tag_from_item(&tagged.id, &tag.id).unwrap();
+        assert!(db.search_items("airship", None).unwrap().is_empty());
+
+        db.delete_item(&titled.id).unwrap();
+        assert_eq!(db.search_items("sketches", None).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_spelling_suggestion() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Kubernetes cluster");
+        db.create_item(&item).unwrap();
+        db.create_chunk(&Chunk::new(item.id.clone(), 0, "Deploying pods with helm charts.")).unwrap();
+
+        assert_eq!(db.spelling_suggestion("kubernets").unwrap().as_deref(), Some("kubernetes"));
+        assert_eq!(db.spelling_suggestion("helm chrats").unwrap().as_deref(), Some("helm charts"));
+        assert_eq!(db.spelling_suggestion("helm charts").unwrap(), None);
+
+        // The vocabulary follows a rebuilt index, and stems are suggested as
+        // the words they came from
+        db.rebuild_fts(fts::Tokenizer::Porter).unwrap();
+        assert_eq!(db.spelling_suggestion("clustr").unwrap().as_deref(), Some("cluster"));
+        assert_eq!(db.spelling_suggestion("kubernets").unwrap().as_deref(), Some("kubernetes"));
+        assert_eq!(db.spelling_suggestion("helm chartz").unwrap().as_deref(), Some("helm charts"));
+        assert_eq!(db.spelling_suggestion("helm charts").unwrap(), None);
+    }
+
+    #[test]
+    fn test_search_items_filtered() {
+        use chrono::Duration;
+
+        let db = Database::open_in_memory().unwrap();
+        let mut old_note = Item::new(ItemType::Note, "Old rust note");
+        old_note.created_at = Utc::now() - Duration::days(60);
+        let new_note = Item::new(ItemType::Note, "New rust note");
+        let code = Item::new(ItemType::Code, "Rust code");
+        for item in [&old_note, &new_note, &code] {
+            db.create_item(item).unwrap();
+            db.create_chunk(&Chunk::new(item.id.clone(), 0, "all about rust")).unwrap();
+        }
+        db.tag_item(&new_note.id, "Learning").unwrap();
+        let project = olal_core::Project::new("Compiler");
+        db.create_project(&project).unwrap();
+        db.add_item_to_project(&project.id, &code.id).unwrap();
+
+        let search = |filter: SearchFilter| -> Vec<String> {
+            let mut titles: Vec<_> = db
+                .search_items_filtered("rust", None, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|i| i.title)
+                .collect();
+            titles.sort();
+            titles
+        };
+
+        assert_eq!(search(SearchFilter::default()).len(), 3);
+        assert_eq!(
+            search(SearchFilter {
+                item_type: Some(ItemType::Note),
+                ..Default::default()
+            }),
+            vec!["New rust note", "Old rust note"]
+        );
+        assert_eq!(
+            search(SearchFilter {
+                tag: Some("learning".to_string()),
+                ..Default::default()
+            }),
+            vec!["New rust note"]
+        );
+        assert_eq!(
+            search(SearchFilter {
+                project: Some("compiler".to_string()),
+                ..Default::default()
+            }),
+            vec!["Rust code"]
+        );
+        assert_eq!(
+            search(SearchFilter {
+                item_type: Some(ItemType::Note),
+                since: Some(Utc::now() - Duration::days(30)),
+                ..Default::default()
+            }),
+            vec!["New rust note"]
+        );
+        assert_eq!(
+            search(SearchFilter {
+                until: Some(Utc::now() - Duration::days(30)),
+                ..Default::default()
+            }),
+            vec!["Old rust note"]
+        );
     }
 
     #[test]
@@ -375,4 +994,187 @@ mod tests {
         let items = db.items_between(start, end).unwrap();
         assert!(items.is_empty());
     }
+
+    #[test]
+    fn test_delete_item_cascade() {
+        use olal_core::{Chunk, EntityKind, Link, LinkType};
+
+        let db = Database::open_in_memory().unwrap();
+
+        let item = Item::new(ItemType::Note, "Doomed");
+        let other = Item::new(ItemType::Note, "Survivor");
+        db.create_item(&item).unwrap();
+        db.create_item(&other).unwrap();
+
+        let chunk = Chunk::new(item.id.clone(), 0, "searchable unicorn content");
+        db.create_chunks(std::slice::from_ref(&chunk)).unwrap();
+        db.store_embedding(&chunk.id, &[1.0, 0.0], "test-model").unwrap();
+        db.tag_item(&item.id, "temp").unwrap();
+        db.create_link(&Link::new(other.id.clone(), item.id.clone(), LinkType::References))
+            .unwrap();
+        let entity = db.get_or_create_entity("Unicorn", EntityKind::Topic).unwrap();
+        db.add_entity_to_item(&item.id, &entity.id, 1).unwrap();
+
+        let preview = db.item_deletion_preview(&item.id).unwrap();
+        assert_eq!(
+            preview,
+            DeletionSummary {
+                chunks: 1,
+                embeddings: 1,
+                tags: 1,
+                links: 1,
+                entities: 1,
+            }
+        );
+
+        let summary = db.delete_item_cascade(&item.id).unwrap();
+        assert_eq!(summary, preview);
+
+        assert!(db.get_item(&item.id).is_err());
+        assert!(db.get_embedding(&chunk.id).unwrap().is_none());
+        assert!(db.search_items("unicorn", None).unwrap().is_empty());
+        assert!(db.get_item_links(&other.id).unwrap().is_empty());
+        assert!(db.get_item(&other.id).is_ok());
+
+        assert!(db.delete_item_cascade(&item.id).is_err());
+    }
+
+    #[test]
+    fn test_find_duplicate_items() {
+        let db = Database::open_in_memory().unwrap();
+
+        let a = Item::new(ItemType::Note, "Meeting Notes");
+        let b = Item::new(ItemType::Note, "meeting notes");
+        let c = Item::new(ItemType::Document, "Meeting Notes");
+        let d = Item::new(ItemType::Note, "Other").with_content_hash("abc");
+        let e = Item::new(ItemType::Note, "Other copy").with_content_hash("abc");
+        for item in [&a, &b, &c, &d, &e] {
+            db.create_item(item).unwrap();
+        }
+
+        let groups = db.find_duplicate_items().unwrap();
+        assert_eq!(groups.len(), 2);
+        assert!(groups.iter().all(|g| g.len() == 2));
+    }
+
+    #[test]
+    fn test_items_without_summary() {
+        use olal_core::Chunk;
+
+        let db = Database::open_in_memory().unwrap();
+
+        let bare = Item::new(ItemType::Note, "No summary");
+        let mut summarized = Item::new(ItemType::Note, "Has summary");
+        summarized.summary = Some("Done".to_string());
+        let empty = Item::new(ItemType::Note, "No content");
+        for item in [&bare, &summarized, &empty] {
+            db.create_item(item).unwrap();
+        }
+        db.create_chunk(&Chunk::new(bare.id.clone(), 0, "text")).unwrap();
+        db.create_chunk(&Chunk::new(summarized.id.clone(), 0, "text")).unwrap();
+
+        let items = db.items_without_summary(10).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, bare.id);
+    }
+
+    #[test]
+    fn test_archive_item() {
+        use olal_core::Chunk;
+
+        let db = Database::open_in_memory().unwrap();
+
+        let item = Item::new(ItemType::Note, "Old idea");
+        db.create_item(&item).unwrap();
+        db.create_chunk(&Chunk::new(item.id.clone(), 0, "zeppelin")).unwrap();
+
+        db.archive_item(&item.id).unwrap();
+
+        assert!(db.get_item(&item.id).unwrap().is_archived());
+        assert!(db.recent_items(None).unwrap().is_empty());
+        assert!(db.search_items("zeppelin", None).unwrap().is_empty());
+        assert_eq!(db.list_archived_items(None, None).unwrap().len(), 1);
+        assert_eq!(db.list_archived_items(Some(ItemType::Note), None).unwrap().len(), 1);
+        assert!(db.list_archived_items(Some(ItemType::Video), None).unwrap().is_empty());
+
+        db.unarchive_item(&item.id).unwrap();
+        assert_eq!(db.recent_items(None).unwrap().len(), 1);
+        assert_eq!(db.search_items("zeppelin", None).unwrap().len(), 1);
+        assert!(db.list_archived_items(None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_retention_candidates() {
+        use chrono::Duration;
+
+        let db = Database::open_in_memory().unwrap();
+
+        let mut old = Item::new(ItemType::Bookmark, "Old link");
+        old.created_at = Utc::now() - Duration::days(120);
+        let mut read = Item::new(ItemType::Bookmark, "Read link");
+        read.created_at = Utc::now() - Duration::days(120);
+        let fresh = Item::new(ItemType::Bookmark, "New link");
+        let mut note = Item::new(ItemType::Note, "Old note");
+        note.created_at = Utc::now() - Duration::days(120);
+        for item in [&old, &read, &fresh, &note] {
+            db.create_item(item).unwrap();
+        }
+        db.mark_item_viewed(&read.id).unwrap();
+
+        let cutoff = Utc::now() - Duration::days(90);
+        let unread = db
+            .retention_candidates(Some(ItemType::Bookmark), cutoff, true, false)
+            .unwrap();
+        assert_eq!(unread.len(), 1);
+        assert_eq!(unread[0].id, old.id);
+
+        assert_eq!(db.retention_candidates(None, cutoff, false, false).unwrap().len(), 3);
+
+        db.archive_item(&old.id).unwrap();
+        assert!(db
+            .retention_candidates(Some(ItemType::Bookmark), cutoff, true, false)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.retention_candidates(Some(ItemType::Bookmark), cutoff, true, true)
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_bookmarks_to_fetch() {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut pending = Item::new(ItemType::Bookmark, "To read");
+        pending.metadata = serde_json::json!({ "url": "https://example.com", "fetch": "pending" });
+        let mut fetched = Item::new(ItemType::Bookmark, "Read");
+        fetched.metadata = serde_json::json!({ "url": "https://example.org", "fetch": "done" });
+        let plain = Item::new(ItemType::Bookmark, "Plain");
+        for item in [&pending, &fetched, &plain] {
+            db.create_item(item).unwrap();
+        }
+
+        let items = db.bookmarks_to_fetch(10).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, pending.id);
+    }
+
+    #[test]
+    fn test_find_items_by_prefix() {
+        let db = Database::open_in_memory().unwrap();
+        let mut a = Item::new(ItemType::Note, "Alpha");
+        a.id = "abc11111-0000".to_string();
+        let mut b = Item::new(ItemType::Note, "Beta");
+        b.id = "abc22222-0000".to_string();
+        db.create_item(&a).unwrap();
+        db.create_item(&b).unwrap();
+
+        assert_eq!(db.find_items_by_prefix("abc", 10).unwrap().len(), 2);
+        assert_eq!(db.find_items_by_prefix("abc2", 10).unwrap()[0].title, "Beta");
+        assert!(db.get_item_by_prefix("abc").is_err());
+        assert_eq!(db.get_item_by_prefix("abc1").unwrap().id, a.id);
+        assert_eq!(db.item_titles().unwrap().len(), 2);
+    }
 }
@@ -0,0 +1,253 @@
+//! Link operations for the knowledge graph.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use crate::operations::vectors::cosine_similarity;
+use olal_core::{AiSuggestion, ItemId, Link, LinkType, SuggestionKind};
+use rusqlite::params;
+use std::collections::HashSet;
+
+impl Database {
+    /// Create or update a link between two items.
+    pub fn create_link(&self, link: &Link) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT OR REPLACE INTO links (source_id, target_id, link_type, strength) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                link.source_id,
+                link.target_id,
+                link.link_type.as_str(),
+                link.strength,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Delete the link from `source_id` to `target_id`.
+    pub fn delete_link(&self, source_id: &ItemId, target_id: &ItemId) -> DbResult<()> {
+        let conn = self.conn()?;
+        let rows = conn.execute(
+            "DELETE FROM links WHERE source_id = ?1 AND target_id = ?2",
+            params![source_id, target_id],
+        )?;
+
+        if rows == 0 {
+            return Err(DbError::NotFound(format!(
+                "Link not found: {} -> {}",
+                source_id, target_id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Get all links touching an item, in either direction.
+    pub fn get_item_links(&self, item_id: &ItemId) -> DbResult<Vec<Link>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT source_id, target_id, link_type, strength FROM links
+             WHERE source_id = ?1 OR target_id = ?1",
+        )?;
+
+        let links = stmt.query_map(params![item_id], row_to_link)?;
+        links.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// List all links.
+    pub fn list_links(&self) -> DbResult<Vec<Link>> {
+        let conn = self.conn()?;
+        let mut stmt =
+            conn.prepare("SELECT source_id, target_id, link_type, strength FROM links")?;
+
+        let links = stmt.query_map([], row_to_link)?;
+        links.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Create `Related` links between unarchived items whose chunk-embedding
+    /// centroids have a cosine similarity of at least `threshold`.
+    ///
+    /// Pairs that are already linked (in either direction, any type) are left
+    /// untouched. The link strength is the similarity. Returns the number of
+    /// links created.
+    pub fn link_similar_items(&self, threshold: f32) -> DbResult<usize> {
+        let (created, _) = self.link_similar_items_reviewed(threshold, None)?;
+        Ok(created)
+    }
+
+    /// Like [`Database::link_similar_items`], but pairs whose similarity is
+    /// below `review_threshold` are queued as link suggestions for review
+    /// instead of being linked.
+    ///
+    /// Pairs with an existing link suggestion (pending or decided) are
+    /// skipped. Returns `(links created, suggestions queued)`.
+    pub fn link_similar_items_reviewed(
+        &self,
+        threshold: f32,
+        review_threshold: Option<f32>,
+    ) -> DbResult<(usize, usize)> {
+        let mut linked: HashSet<(ItemId, ItemId)> = HashSet::new();
+        for link in self.list_links()? {
+            linked.insert((link.target_id.clone(), link.source_id.clone()));
+            linked.insert((link.source_id, link.target_id));
+        }
+        for suggestion in self.list_suggestions(None, Some(SuggestionKind::Link))? {
+            linked.insert((suggestion.value.clone(), suggestion.item_id.clone()));
+            linked.insert((suggestion.item_id, suggestion.value));
+        }
+
+        let mut centroids: Vec<(ItemId, Vec<f32>)> = self.item_centroids()?.into_iter().collect();
+        centroids.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut created = 0;
+        let mut queued = 0;
+        for (i, (source_id, source_vec)) in centroids.iter().enumerate() {
+            for (target_id, target_vec) in &centroids[i + 1..] {
+                if linked.contains(&(source_id.clone(), target_id.clone())) {
+                    continue;
+                }
+
+                let similarity = cosine_similarity(source_vec, target_vec);
+                if similarity < threshold {
+                    continue;
+                }
+
+                if review_threshold.is_some_and(|r| similarity < r) {
+                    let suggestion = AiSuggestion::new(
+                        source_id.clone(),
+                        SuggestionKind::Link,
+                        target_id.clone(),
+                        similarity,
+                    );
+                    if self.create_suggestion(&suggestion)? {
+                        queued += 1;
+                    }
+                } else {
+                    let link = Link::new(source_id.clone(), target_id.clone(), LinkType::Related)
+                        .with_strength(similarity as f64);
+                    self.create_link(&link)?;
+                    created += 1;
+                }
+            }
+        }
+
+        Ok((created, queued))
+    }
+}
+
+fn row_to_link(row: &rusqlite::Row) -> rusqlite::Result<Link> {
+    let link_type_str: String = row.get(2)?;
+    let strength: Option<f64> = row.get(3)?;
+
+    Ok(Link {
+        source_id: row.get(0)?,
+        target_id: row.get(1)?,
+        link_type: LinkType::from_str(&link_type_str).unwrap_or(LinkType::Related),
+        strength: strength.unwrap_or(1.0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Chunk, Item, ItemType};
+
+    #[test]
+    fn test_link_crud() {
+        let db = Database::open_in_memory().unwrap();
+
+        let a = Item::new(ItemType::Note, "A");
+        let b = Item::new(ItemType::Note, "B");
+        db.create_item(&a).unwrap();
+        db.create_item(&b).unwrap();
+
+        let link = Link::new(a.id.clone(), b.id.clone(), LinkType::References).with_strength(0.5);
+        db.create_link(&link).unwrap();
+
+        // Visible from both ends
+        let from_a = db.get_item_links(&a.id).unwrap();
+        let from_b = db.get_item_links(&b.id).unwrap();
+        assert_eq!(from_a.len(), 1);
+        assert_eq!(from_b.len(), 1);
+        assert_eq!(from_a[0].link_type, LinkType::References);
+        assert!((from_a[0].strength - 0.5).abs() < 1e-9);
+
+        // Upsert replaces
+        db.create_link(&Link::new(a.id.clone(), b.id.clone(), LinkType::Related))
+            .unwrap();
+        assert_eq!(db.list_links().unwrap().len(), 1);
+
+        db.delete_link(&a.id, &b.id).unwrap();
+        assert!(db.get_item_links(&a.id).unwrap().is_empty());
+        assert!(db.delete_link(&a.id, &b.id).is_err());
+    }
+
+    #[test]
+    fn test_link_similar_items() {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut items = Vec::new();
+        for (title, vector) in [
+            ("Rust", [1.0, 0.0]),
+            ("Cargo", [0.95, 0.05]),
+            ("Baking", [0.0, 1.0]),
+        ] {
+            let item = Item::new(ItemType::Note, title);
+            db.create_item(&item).unwrap();
+            let chunk = Chunk::new(item.id.clone(), 0, title);
+            db.create_chunk(&chunk).unwrap();
+            db.store_embedding(&chunk.id, &vector, "test-model").unwrap();
+            items.push(item);
+        }
+
+        assert_eq!(db.link_similar_items(0.9).unwrap(), 1);
+        let links = db.get_item_links(&items[0].id).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].link_type, LinkType::Related);
+        assert!(db.get_item_links(&items[2].id).unwrap().is_empty());
+
+        // Second pass doesn't duplicate
+        assert_eq!(db.link_similar_items(0.9).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_link_similar_items_reviewed() {
+        let db = Database::open_in_memory().unwrap();
+
+        for (title, vector) in [("Rust", [1.0, 0.0]), ("Cargo", [0.8, 0.6])] {
+            let item = Item::new(ItemType::Note, title);
+            db.create_item(&item).unwrap();
+            let chunk = Chunk::new(item.id.clone(), 0, title);
+            db.create_chunk(&chunk).unwrap();
+            db.store_embedding(&chunk.id, &vector, "test-model").unwrap();
+        }
+
+        // Similarity 0.8: above the link threshold, below the review threshold
+        assert_eq!(db.link_similar_items_reviewed(0.7, Some(0.9)).unwrap(), (0, 1));
+        assert!(db.list_links().unwrap().is_empty());
+
+        // Queued pairs aren't re-suggested or auto-linked later
+        assert_eq!(db.link_similar_items_reviewed(0.7, Some(0.9)).unwrap(), (0, 0));
+        assert_eq!(db.link_similar_items(0.7).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_link_similar_items_skips_archived() {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut items = Vec::new();
+        for (title, vector) in [("Rust", [1.0, 0.0]), ("Cargo", [0.95, 0.05])] {
+            let item = Item::new(ItemType::Note, title);
+            db.create_item(&item).unwrap();
+            let chunk = Chunk::new(item.id.clone(), 0, title);
+            db.create_chunk(&chunk).unwrap();
+            db.store_embedding(&chunk.id, &vector, "test-model").unwrap();
+            items.push(item);
+        }
+
+        db.archive_item(&items[1].id).unwrap();
+        assert_eq!(db.link_similar_items(0.9).unwrap(), 0);
+
+        db.unarchive_item(&items[1].id).unwrap();
+        assert_eq!(db.link_similar_items(0.9).unwrap(), 1);
+    }
+}
@@ -2,7 +2,8 @@
 
 use crate::database::Database;
 use crate::error::{DbError, DbResult};
-use olal_core::{Project, ProjectId, ProjectStatus};
+use crate::operations::items::row_to_item;
+use olal_core::{Item, ItemId, Project, ProjectId, ProjectStatus};
 use chrono::{DateTime, Utc};
 use rusqlite::params;
 
@@ -117,6 +118,58 @@ impl Database {
 
         Ok(projects)
     }
+
+    /// Attach a knowledge item to a project. Returns false if it was
+    /// already attached.
+    pub fn add_item_to_project(&self, project_id: &ProjectId, item_id: &ItemId) -> DbResult<bool> {
+        let conn = self.conn()?;
+        let rows = conn.execute(
+            "INSERT OR IGNORE INTO item_projects (item_id, project_id, added_at) VALUES (?1, ?2, ?3)",
+            params![item_id, project_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Detach an item from a project. Returns false if it wasn't attached.
+    pub fn remove_item_from_project(&self, project_id: &ProjectId, item_id: &ItemId) -> DbResult<bool> {
+        let conn = self.conn()?;
+        let rows = conn.execute(
+            "DELETE FROM item_projects WHERE item_id = ?1 AND project_id = ?2",
+            params![item_id, project_id],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// Items attached to a project, most recently added first.
+    pub fn list_project_items(&self, project_id: &ProjectId) -> DbResult<Vec<Item>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT i.id, i.item_type, i.title, i.source_path, i.content_hash, i.summary,
+                    i.created_at, i.processed_at, i.metadata, i.archived_at
+             FROM items i
+             INNER JOIN item_projects ip ON ip.item_id = i.id
+             WHERE ip.project_id = ?1
+             ORDER BY ip.added_at DESC, i.created_at DESC",
+        )?;
+
+        let items = stmt.query_map(params![project_id], row_to_item)?;
+        items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Projects an item is attached to.
+    pub fn get_item_projects(&self, item_id: &ItemId) -> DbResult<Vec<Project>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT p.id, p.name, p.description, p.status, p.created_at
+             FROM projects p
+             INNER JOIN item_projects ip ON ip.project_id = p.id
+             WHERE ip.item_id = ?1
+             ORDER BY p.name",
+        )?;
+
+        let projects = stmt.query_map(params![item_id], row_to_project)?;
+        projects.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
 }
 
 fn row_to_project(row: &rusqlite::Row) -> rusqlite::Result<Project> {
@@ -171,4 +224,30 @@ mod tests {
         db.delete_project(&project.id).unwrap();
         assert!(db.get_project(&project.id).is_err());
     }
+
+    #[test]
+    fn test_project_items() {
+        let db = Database::open_in_memory().unwrap();
+
+        let project = Project::new("Olal");
+        db.create_project(&project).unwrap();
+        let item = Item::new(olal_core::ItemType::Note, "Design notes");
+        db.create_item(&item).unwrap();
+
+        assert!(db.add_item_to_project(&project.id, &item.id).unwrap());
+        assert!(!db.add_item_to_project(&project.id, &item.id).unwrap());
+
+        let items = db.list_project_items(&project.id).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].title, "Design notes");
+        assert_eq!(db.get_item_projects(&item.id).unwrap()[0].name, "Olal");
+
+        assert!(db.remove_item_from_project(&project.id, &item.id).unwrap());
+        assert!(db.list_project_items(&project.id).unwrap().is_empty());
+
+        // Deleting either side drops the association
+        db.add_item_to_project(&project.id, &item.id).unwrap();
+        db.delete_item_cascade(&item.id).unwrap();
+        assert!(db.list_project_items(&project.id).unwrap().is_empty());
+    }
 }
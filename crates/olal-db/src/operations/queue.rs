@@ -47,43 +47,83 @@ impl Database {
         Ok(item)
     }
 
+    /// Get a queue item by ID or unique ID prefix.
+    pub fn get_queue_item_by_prefix(&self, prefix: &str) -> DbResult<QueueItem> {
+        let conn = self.conn()?;
+        let pattern = format!("{}%", prefix);
+        let mut stmt = conn.prepare(
+            "SELECT id, source_path, item_type, status, priority, attempts, error, created_at, started_at, completed_at
+             FROM queue WHERE id LIKE ?1 LIMIT 2",
+        )?;
+        let items: Vec<QueueItem> = stmt
+            .query_map(params![pattern], row_to_queue_item)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match items.len() {
+            0 => Err(DbError::NotFound(format!("Queue item not found: {}", prefix))),
+            1 => Ok(items.into_iter().next().unwrap()),
+            _ => Err(DbError::Other(format!(
+                "Ambiguous ID prefix '{}': multiple queue items match",
+                prefix
+            ))),
+        }
+    }
+
     /// Dequeue the next item for processing (marks it as processing).
+    ///
+    /// Claims the entry in a single statement, so concurrent workers never
+    /// pick up the same entry. The claim records this process's ID.
     pub fn dequeue(&self) -> DbResult<Option<QueueItem>> {
         let conn = self.conn()?;
         let now = Utc::now().to_rfc3339();
 
-        // Get the highest priority pending item
+        // Claim the highest priority pending item
         let result = conn.query_row(
-            "SELECT id, source_path, item_type, status, priority, attempts, error, created_at, started_at, completed_at
-             FROM queue
-             WHERE status = 'pending'
-             ORDER BY priority DESC, created_at ASC
-             LIMIT 1",
-            [],
+            "UPDATE queue
+             SET status = 'processing', started_at = ?1, attempts = attempts + 1, claimed_by = ?2
+             WHERE id = (
+                 SELECT id FROM queue
+                 WHERE status = 'pending'
+                 ORDER BY priority DESC, created_at ASC
+                 LIMIT 1
+             )
+             RETURNING id, source_path, item_type, status, priority, attempts, error, created_at, started_at, completed_at",
+            params![now, std::process::id()],
             row_to_queue_item,
         );
 
-        let item = match result {
-            Ok(item) => item,
-            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
-            Err(e) => return Err(DbError::from(e)),
-        };
-
-        // Mark as processing
-        conn.execute(
-            "UPDATE queue SET status = 'processing', started_at = ?2, attempts = attempts + 1 WHERE id = ?1",
-            params![item.id, now],
-        )?;
-
-        // Re-fetch the updated item using the same connection
-        let updated = conn.query_row(
-            "SELECT id, source_path, item_type, status, priority, attempts, error, created_at, started_at, completed_at
-             FROM queue WHERE id = ?1",
-            params![item.id],
-            row_to_queue_item,
-        )?;
+        match result {
+            Ok(item) => Ok(Some(item)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
 
-        Ok(Some(updated))
+    /// Return entries stuck in `processing` to `pending`.
+    ///
+    /// Entries stay in `processing` if the process handling them died, so
+    /// only those whose claiming process `is_running` says is gone (or that
+    /// were claimed before claims were recorded) are requeued; entries
+    /// another worker is still processing are left alone.
+    pub fn requeue_processing(&self, is_running: impl Fn(u32) -> bool) -> DbResult<i64> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT id, claimed_by FROM queue WHERE status = 'processing'")?;
+        let claims = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, Option<u32>>(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut count = 0;
+        for (id, owner) in claims {
+            if owner.is_some_and(&is_running) {
+                continue;
+            }
+            count += conn.execute(
+                "UPDATE queue SET status = 'pending', started_at = NULL, claimed_by = NULL
+                 WHERE id = ?1 AND status = 'processing'",
+                params![id],
+            )?;
+        }
+        Ok(count as i64)
     }
 
     /// Mark a queue item as completed.
@@ -136,6 +176,17 @@ impl Database {
         Ok(())
     }
 
+    /// Reset every failed queue item to pending. Returns how many were reset.
+    pub fn retry_all_failed(&self) -> DbResult<i64> {
+        let conn = self.conn()?;
+        let count = conn.execute(
+            "UPDATE queue SET status = 'pending', error = NULL, started_at = NULL, completed_at = NULL
+             WHERE status = 'failed'",
+            [],
+        )?;
+        Ok(count as i64)
+    }
+
     /// List queue items by status.
     pub fn list_queue(&self, status: Option<QueueStatus>) -> DbResult<Vec<QueueItem>> {
         let conn = self.conn()?;
@@ -187,6 +238,35 @@ impl Database {
         Ok(count as i64)
     }
 
+    /// Queue entries with `status` created before `before`.
+    pub fn queue_entries_before(
+        &self,
+        status: QueueStatus,
+        before: DateTime<Utc>,
+    ) -> DbResult<Vec<QueueItem>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, source_path, item_type, status, priority, attempts, error, created_at, started_at, completed_at
+             FROM queue WHERE status = ?1 AND created_at < ?2 ORDER BY created_at",
+        )?;
+        let rows = stmt.query_map(params![status.as_str(), before.to_rfc3339()], row_to_queue_item)?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Delete queue entries with `status` created before `before`.
+    pub fn delete_queue_entries_before(
+        &self,
+        status: QueueStatus,
+        before: DateTime<Utc>,
+    ) -> DbResult<i64> {
+        let conn = self.conn()?;
+        let count = conn.execute(
+            "DELETE FROM queue WHERE status = ?1 AND created_at < ?2",
+            params![status.as_str(), before.to_rfc3339()],
+        )?;
+        Ok(count as i64)
+    }
+
     /// Get queue counts by status.
     pub fn queue_counts(&self) -> DbResult<(i64, i64, i64, i64)> {
         let conn = self.conn()?;
@@ -301,6 +381,45 @@ mod tests {
         assert!(retried.error.is_none());
     }
 
+    #[test]
+    fn test_retry_all_failed_and_prefix() {
+        let db = Database::open_in_memory().unwrap();
+
+        for path in ["/a.mp4", "/b.mp4"] {
+            db.enqueue(&QueueItem::new(path, ItemType::Video)).unwrap();
+            let dequeued = db.dequeue().unwrap().unwrap();
+            db.mark_failed(&dequeued.id, "boom").unwrap();
+        }
+        let item = QueueItem::new("/c.mp4", ItemType::Video);
+        db.enqueue(&item).unwrap();
+
+        let found = db.get_queue_item_by_prefix(&item.id[..8]).unwrap();
+        assert_eq!(found.source_path, "/c.mp4");
+        assert!(db.get_queue_item_by_prefix("zzzz").is_err());
+
+        assert_eq!(db.retry_all_failed().unwrap(), 2);
+        assert_eq!(db.list_queue(Some(QueueStatus::Pending)).unwrap().len(), 3);
+        assert_eq!(db.retry_all_failed().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_requeue_processing() {
+        let db = Database::open_in_memory().unwrap();
+
+        db.enqueue(&QueueItem::new("/a.mp4", ItemType::Video)).unwrap();
+        let stuck = db.dequeue().unwrap().unwrap();
+        assert!(db.dequeue().unwrap().is_none());
+
+        // Still being processed
+        assert_eq!(db.requeue_processing(|pid| pid == std::process::id()).unwrap(), 0);
+        assert!(db.dequeue().unwrap().is_none());
+
+        assert_eq!(db.requeue_processing(|_| false).unwrap(), 1);
+        let again = db.dequeue().unwrap().unwrap();
+        assert_eq!(again.id, stuck.id);
+        assert_eq!(again.attempts, 2);
+    }
+
     #[test]
     fn test_queue_counts() {
         let db = Database::open_in_memory().unwrap();
@@ -0,0 +1,227 @@
+//! Related item discovery combining links, tags and embeddings.
+
+use crate::database::Database;
+use crate::error::DbResult;
+use crate::operations::vectors::cosine_similarity;
+use olal_core::{Item, ItemId, LinkType};
+use rusqlite::params;
+use std::collections::{HashMap, HashSet};
+
+/// Weight of an explicit link in the combined score.
+const LINK_WEIGHT: f32 = 0.4;
+/// Weight of shared tags in the combined score.
+const TAG_WEIGHT: f32 = 0.2;
+/// Weight of centroid similarity in the combined score.
+const SIMILARITY_WEIGHT: f32 = 0.4;
+/// Embedding-only matches below this similarity are ignored.
+const MIN_SIMILARITY: f32 = 0.3;
+
+/// An item related to another item, with the signals that connect them.
+#[derive(Debug, Clone)]
+pub struct RelatedItem {
+    /// The related item.
+    pub item: Item,
+    /// Combined relatedness score (0.0 to 1.0).
+    pub score: f32,
+    /// Explicit link type, if the items are linked.
+    pub link_type: Option<LinkType>,
+    /// Strength of the explicit link (0.0 if not linked).
+    pub link_strength: f32,
+    /// Names of tags both items share.
+    pub shared_tags: Vec<String>,
+    /// Cosine similarity between the items' chunk centroids.
+    pub similarity: Option<f32>,
+}
+
+#[derive(Default)]
+struct Signals {
+    link_type: Option<LinkType>,
+    link_strength: f32,
+    shared_tags: Vec<String>,
+    similarity: Option<f32>,
+}
+
+impl Database {
+    /// Find items related to `item_id`.
+    ///
+    /// Combines explicit links, the fraction of the item's tags that are
+    /// shared, and cosine similarity between chunk-embedding centroids into
+    /// a single score. Archived items are left out. Results are sorted by
+    /// score, highest first.
+    pub fn related_items(&self, item_id: &ItemId, limit: usize) -> DbResult<Vec<RelatedItem>> {
+        let mut signals: HashMap<ItemId, Signals> = HashMap::new();
+
+        let archived: HashSet<ItemId> = {
+            let conn = self.conn()?;
+            let mut stmt = conn.prepare("SELECT id FROM items WHERE archived_at IS NOT NULL")?;
+            let ids = stmt.query_map([], |row| row.get(0))?;
+            ids.collect::<Result<_, _>>()?
+        };
+
+        // Explicit links (either direction)
+        for link in self.get_item_links(item_id)? {
+            let other = if &link.source_id == item_id {
+                link.target_id
+            } else {
+                link.source_id
+            };
+            if archived.contains(&other) {
+                continue;
+            }
+            let entry = signals.entry(other).or_default();
+            let strength = (link.strength as f32).clamp(0.0, 1.0);
+            if entry.link_type.is_none() || strength > entry.link_strength {
+                entry.link_type = Some(link.link_type);
+                entry.link_strength = strength;
+            }
+        }
+
+        // Shared tags
+        let tag_count = self.get_item_tags(item_id)?.len();
+        {
+            let conn = self.conn()?;
+            let mut stmt = conn.prepare(
+                "SELECT other.item_id, t.name FROM item_tags mine
+                 INNER JOIN item_tags other ON other.tag_id = mine.tag_id AND other.item_id != mine.item_id
+                 INNER JOIN tags t ON t.id = mine.tag_id
+                 INNER JOIN items i ON i.id = other.item_id
+                 WHERE mine.item_id = ?1 AND i.archived_at IS NULL
+                 ORDER BY t.name",
+            )?;
+            let rows = stmt.query_map(params![item_id], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?;
+            for row in rows {
+                let (other, tag_name) = row?;
+                signals.entry(other).or_default().shared_tags.push(tag_name);
+            }
+        }
+
+        // Embedding centroid similarity
+        let centroids = self.item_centroids()?;
+        if let Some(own) = centroids.get(item_id) {
+            for (other, vector) in &centroids {
+                if other == item_id {
+                    continue;
+                }
+                let similarity = cosine_similarity(own, vector);
+                if similarity >= MIN_SIMILARITY || signals.contains_key(other) {
+                    signals.entry(other.clone()).or_default().similarity = Some(similarity);
+                }
+            }
+        }
+
+        signals.remove(item_id);
+
+        let mut scored: Vec<(ItemId, f32, Signals)> = signals
+            .into_iter()
+            .map(|(id, s)| {
+                let tag_score = if tag_count > 0 {
+                    s.shared_tags.len() as f32 / tag_count as f32
+                } else {
+                    0.0
+                };
+                let similarity_score = s.similarity.unwrap_or(0.0).max(0.0);
+                let score = LINK_WEIGHT * s.link_strength
+                    + TAG_WEIGHT * tag_score
+                    + SIMILARITY_WEIGHT * similarity_score;
+                (id, score, s)
+            })
+            .collect();
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(limit);
+
+        let mut results = Vec::with_capacity(scored.len());
+        for (id, score, s) in scored {
+            let item = self.get_item(&id)?;
+            results.push(RelatedItem {
+                item,
+                score,
+                link_type: s.link_type,
+                link_strength: s.link_strength,
+                shared_tags: s.shared_tags,
+                similarity: s.similarity,
+            });
+        }
+
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Chunk, ItemType, Link};
+
+    fn add_item(db: &Database, title: &str, vector: &[f32]) -> Item {
+        let item = Item::new(ItemType::Note, title);
+        db.create_item(&item).unwrap();
+        let chunk = Chunk::new(item.id.clone(), 0, title);
+        db.create_chunk(&chunk).unwrap();
+        db.store_embedding(&chunk.id, vector, "test-model").unwrap();
+        item
+    }
+
+    #[test]
+    fn test_related_items_combines_signals() {
+        let db = Database::open_in_memory().unwrap();
+
+        let source = add_item(&db, "Rust ownership", &[1.0, 0.0, 0.0]);
+        let similar = add_item(&db, "Rust borrowing", &[0.9, 0.1, 0.0]);
+        let linked = add_item(&db, "Linked note", &[0.0, 1.0, 0.0]);
+        let tagged = add_item(&db, "Tagged note", &[0.0, 0.0, 1.0]);
+        let _unrelated = add_item(&db, "Cooking", &[0.0, -1.0, 0.0]);
+
+        db.create_link(&Link::new(source.id.clone(), linked.id.clone(), LinkType::References))
+            .unwrap();
+        db.tag_item(&source.id, "rust").unwrap();
+        db.tag_item(&tagged.id, "rust").unwrap();
+        db.tag_item(&similar.id, "rust").unwrap();
+
+        let related = db.related_items(&source.id, 10).unwrap();
+        let ids: Vec<&str> = related.iter().map(|r| r.item.id.as_str()).collect();
+
+        assert_eq!(related.len(), 3);
+        // Similar embedding + shared tag ranks first
+        assert_eq!(ids[0], similar.id);
+        assert!(ids.contains(&linked.id.as_str()));
+        assert!(ids.contains(&tagged.id.as_str()));
+
+        let linked_result = related.iter().find(|r| r.item.id == linked.id).unwrap();
+        assert_eq!(linked_result.link_type, Some(LinkType::References));
+
+        let tagged_result = related.iter().find(|r| r.item.id == tagged.id).unwrap();
+        assert_eq!(tagged_result.shared_tags, vec!["rust".to_string()]);
+    }
+
+    #[test]
+    fn test_related_items_skips_archived() {
+        let db = Database::open_in_memory().unwrap();
+
+        let source = add_item(&db, "Rust ownership", &[1.0, 0.0]);
+        let similar = add_item(&db, "Rust borrowing", &[0.9, 0.1]);
+        let linked = add_item(&db, "Linked note", &[0.0, 1.0]);
+        let tagged = add_item(&db, "Tagged note", &[0.0, 1.0]);
+        db.create_link(&Link::new(source.id.clone(), linked.id.clone(), LinkType::References))
+            .unwrap();
+        db.tag_item(&source.id, "rust").unwrap();
+        db.tag_item(&tagged.id, "rust").unwrap();
+        assert_eq!(db.related_items(&source.id, 10).unwrap().len(), 3);
+
+        for item in [&similar, &linked, &tagged] {
+            db.archive_item(&item.id).unwrap();
+        }
+        assert!(db.related_items(&source.id, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_related_items_empty() {
+        let db = Database::open_in_memory().unwrap();
+
+        let item = Item::new(ItemType::Note, "Lonely");
+        db.create_item(&item).unwrap();
+
+        assert!(db.related_items(&item.id, 10).unwrap().is_empty());
+    }
+}
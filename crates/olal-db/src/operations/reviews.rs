@@ -0,0 +1,154 @@
+//! Spaced-repetition review operations.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use chrono::{DateTime, Utc};
+use olal_core::{Item, ItemId, Review};
+use rusqlite::params;
+
+const REVIEW_COLUMNS: &str =
+    "item_id, ease, interval_days, repetitions, due_at, last_reviewed_at, created_at";
+
+impl Database {
+    /// Insert or update an item's review schedule.
+    pub fn save_review(&self, review: &Review) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            &format!(
+                "INSERT INTO reviews ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                 ON CONFLICT(item_id) DO UPDATE SET
+                    ease = excluded.ease,
+                    interval_days = excluded.interval_days,
+                    repetitions = excluded.repetitions,
+                    due_at = excluded.due_at,
+                    last_reviewed_at = excluded.last_reviewed_at",
+                REVIEW_COLUMNS
+            ),
+            params![
+                review.item_id,
+                review.ease,
+                review.interval_days,
+                review.repetitions,
+                review.due_at.to_rfc3339(),
+                review.last_reviewed_at.map(|dt| dt.to_rfc3339()),
+                review.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// An item's review schedule, if it's being reviewed.
+    pub fn get_review(&self, item_id: &ItemId) -> DbResult<Option<Review>> {
+        let conn = self.conn()?;
+        let result = conn.query_row(
+            &format!("SELECT {} FROM reviews WHERE item_id = ?1", REVIEW_COLUMNS),
+            params![item_id],
+            row_to_review,
+        );
+
+        match result {
+            Ok(review) => Ok(Some(review)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+
+    /// Stop reviewing an item. Returns whether it had a schedule.
+    pub fn delete_review(&self, item_id: &ItemId) -> DbResult<bool> {
+        let conn = self.conn()?;
+        let count = conn.execute("DELETE FROM reviews WHERE item_id = ?1", params![item_id])?;
+        Ok(count > 0)
+    }
+
+    /// Reviews due at `now` with their items, most overdue first.
+    /// Archived items are skipped.
+    pub fn due_reviews(&self, now: DateTime<Utc>, limit: usize) -> DbResult<Vec<(Review, Item)>> {
+        self.reviews_where("r.due_at <= ?1", now, limit)
+    }
+
+    /// All scheduled reviews with their items, soonest first.
+    pub fn list_reviews(&self, limit: usize) -> DbResult<Vec<(Review, Item)>> {
+        self.reviews_where("?1 IS NOT NULL", Utc::now(), limit)
+    }
+
+    fn reviews_where(&self, condition: &str, now: DateTime<Utc>, limit: usize) -> DbResult<Vec<(Review, Item)>> {
+        let ids: Vec<ItemId> = {
+            let conn = self.conn()?;
+            let mut stmt = conn.prepare(&format!(
+                "SELECT r.item_id FROM reviews r
+                 JOIN items i ON i.id = r.item_id
+                 WHERE i.archived_at IS NULL AND {}
+                 ORDER BY r.due_at
+                 LIMIT ?2",
+                condition
+            ))?;
+            let rows = stmt.query_map(params![now.to_rfc3339(), limit as i64], |row| row.get(0))?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut reviews = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(review) = self.get_review(&id)? {
+                reviews.push((review, self.get_item(&id)?));
+            }
+        }
+        Ok(reviews)
+    }
+}
+
+fn row_to_review(row: &rusqlite::Row) -> rusqlite::Result<Review> {
+    let due_at_str: String = row.get(4)?;
+    let last_reviewed_str: Option<String> = row.get(5)?;
+    let created_at_str: String = row.get(6)?;
+
+    Ok(Review {
+        item_id: row.get(0)?,
+        ease: row.get(1)?,
+        interval_days: row.get(2)?,
+        repetitions: row.get(3)?,
+        due_at: parse_time(&due_at_str),
+        last_reviewed_at: last_reviewed_str.as_deref().map(parse_time),
+        created_at: parse_time(&created_at_str),
+    })
+}
+
+fn parse_time(s: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(s)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use olal_core::{ItemType, RecallRating};
+
+    #[test]
+    fn test_due_reviews() {
+        let db = Database::open_in_memory().unwrap();
+        let first = Item::new(ItemType::Note, "Ownership");
+        let second = Item::new(ItemType::Note, "Lifetimes");
+        db.create_item(&first).unwrap();
+        db.create_item(&second).unwrap();
+
+        db.save_review(&Review::new(first.id.clone())).unwrap();
+        let mut later = Review::new(second.id.clone());
+        later.record(RecallRating::Good, Utc::now());
+        db.save_review(&later).unwrap();
+
+        let now = Utc::now();
+        let due = db.due_reviews(now, 10).unwrap();
+        assert_eq!(due.len(), 1);
+        assert_eq!(due[0].1.title, "Ownership");
+        assert_eq!(db.due_reviews(now + Duration::days(2), 10).unwrap().len(), 2);
+        assert_eq!(db.list_reviews(10).unwrap().len(), 2);
+
+        let stored = db.get_review(&second.id).unwrap().unwrap();
+        assert_eq!(stored.interval_days, 1);
+        assert!(stored.last_reviewed_at.is_some());
+
+        assert!(db.delete_review(&first.id).unwrap());
+        assert!(db.get_review(&first.id).unwrap().is_none());
+    }
+}
@@ -0,0 +1,78 @@
+//! Scheduled job bookkeeping.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+/// The last run of a scheduled job.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScheduleRun {
+    pub name: String,
+    pub last_run_at: DateTime<Utc>,
+    /// Error message if the last run failed.
+    pub error: Option<String>,
+}
+
+impl Database {
+    /// Record that the job `name` ran at `at`, with its error if it failed.
+    pub fn record_schedule_run(&self, name: &str, at: DateTime<Utc>, error: Option<&str>) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO schedule_runs (name, last_run_at, error) VALUES (?1, ?2, ?3)
+             ON CONFLICT(name) DO UPDATE
+             SET last_run_at = excluded.last_run_at, error = excluded.error",
+            params![name, at.to_rfc3339(), error],
+        )?;
+        Ok(())
+    }
+
+    /// The last run of the job `name`, if it has ever run.
+    pub fn get_schedule_run(&self, name: &str) -> DbResult<Option<ScheduleRun>> {
+        let conn = self.conn()?;
+        let result = conn.query_row(
+            "SELECT name, last_run_at, error FROM schedule_runs WHERE name = ?1",
+            params![name],
+            row_to_run,
+        );
+
+        match result {
+            Ok(run) => Ok(Some(run)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(DbError::from(e)),
+        }
+    }
+}
+
+fn row_to_run(row: &rusqlite::Row) -> rusqlite::Result<ScheduleRun> {
+    let last_run_at_str: String = row.get(1)?;
+
+    Ok(ScheduleRun {
+        name: row.get(0)?,
+        last_run_at: DateTime::parse_from_rfc3339(&last_run_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        error: row.get(2)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_schedule_runs() {
+        let db = Database::open_in_memory().unwrap();
+        assert!(db.get_schedule_run("digest").unwrap().is_none());
+
+        let first = Utc::now() - Duration::hours(1);
+        db.record_schedule_run("digest", first, Some("Ollama is not running")).unwrap();
+        let now = Utc::now();
+        db.record_schedule_run("digest", now, None).unwrap();
+
+        let run = db.get_schedule_run("digest").unwrap().unwrap();
+        assert_eq!(run.last_run_at.timestamp(), now.timestamp());
+        assert!(run.error.is_none());
+    }
+}
@@ -1,10 +1,47 @@
 //! Database statistics operations.
 
+use super::items::row_to_item;
 use crate::database::Database;
-use crate::error::DbResult;
-use olal_core::DatabaseStats;
+use crate::error::{DbError, DbResult};
+use chrono::{DateTime, Datelike, Days, NaiveDate, Utc};
+use olal_core::{DatabaseStats, Item};
+use rusqlite::params;
 use std::collections::HashMap;
 
+/// Granularity of [`Database::ingestion_timeline`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBucket {
+    Day,
+    /// Weeks starting on Monday.
+    Week,
+}
+
+impl TimeBucket {
+    /// The first day of the bucket `date` falls in.
+    pub fn start_of(&self, date: NaiveDate) -> NaiveDate {
+        match self {
+            Self::Day => date,
+            Self::Week => date - Days::new(date.weekday().num_days_from_monday() as u64),
+        }
+    }
+
+    fn days(&self) -> u64 {
+        match self {
+            Self::Day => 1,
+            Self::Week => 7,
+        }
+    }
+}
+
+/// Items ingested in one day or week (UTC).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityBucket {
+    pub start: NaiveDate,
+    pub items: i64,
+    /// How many of those items have at least one embedded chunk.
+    pub embedded: i64,
+}
+
 impl Database {
     /// Get comprehensive database statistics.
     pub fn get_stats(&self) -> DbResult<DatabaseStats> {
@@ -107,6 +144,85 @@ impl Database {
             database_size_bytes,
         })
     }
+
+    /// Items ingested per day or week over the last `count` buckets, oldest
+    /// first, including the current one. Empty buckets are included.
+    pub fn ingestion_timeline(&self, bucket: TimeBucket, count: usize) -> DbResult<Vec<ActivityBucket>> {
+        let count = count.max(1) as u64;
+        let last = bucket.start_of(Utc::now().date_naive());
+        let first = last - Days::new(bucket.days() * (count - 1));
+
+        let mut timeline: Vec<ActivityBucket> = (0..count)
+            .map(|i| ActivityBucket {
+                start: first + Days::new(bucket.days() * i),
+                items: 0,
+                embedded: 0,
+            })
+            .collect();
+
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT date(created_at) AS day, COUNT(*),
+                    SUM(EXISTS (SELECT 1 FROM chunks c JOIN embeddings e ON e.chunk_id = COALESCE(c.duplicate_of, c.id)
+                                WHERE c.item_id = items.id))
+             FROM items WHERE date(created_at) >= ?1
+             GROUP BY day",
+        )?;
+        let rows = stmt.query_map(params![first.to_string()], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+        })?;
+
+        for row in rows {
+            let (day, items, embedded) = row?;
+            let Ok(day) = day.parse::<NaiveDate>() else {
+                continue;
+            };
+            let index = (day - first).num_days() as u64 / bucket.days();
+            if let Some(entry) = timeline.get_mut(index as usize) {
+                entry.items += items;
+                entry.embedded += embedded;
+            }
+        }
+        Ok(timeline)
+    }
+
+    /// Items with the most chunks, largest first.
+    pub fn largest_items(&self, limit: usize) -> DbResult<Vec<(Item, i64)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT i.id, i.item_type, i.title, i.source_path, i.content_hash, i.summary,
+                    i.created_at, i.processed_at, i.metadata, i.archived_at, COUNT(c.id) AS chunks
+             FROM items i JOIN chunks c ON c.item_id = i.id
+             GROUP BY i.id
+             ORDER BY chunks DESC, i.created_at DESC
+             LIMIT ?1",
+        )?;
+
+        let results = stmt.query_map(params![limit as i64], |row| {
+            Ok((row_to_item(row)?, row.get(10)?))
+        })?;
+        results.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Get the compact status counts in a single query:
+    /// (queue depth, open tasks due by `due_before`, unread items).
+    ///
+    /// Queue depth counts pending and processing entries. Unread items are
+    /// the unarchived ones never opened.
+    pub fn status_summary(&self, due_before: DateTime<Utc>) -> DbResult<(i64, i64, i64)> {
+        let conn = self.conn()?;
+        let counts = conn.query_row(
+            "SELECT
+                (SELECT COUNT(*) FROM queue WHERE status IN ('pending', 'processing')),
+                (SELECT COUNT(*) FROM tasks
+                 WHERE status IN ('pending', 'in_progress')
+                   AND due_date IS NOT NULL AND due_date <= ?1),
+                (SELECT COUNT(*) FROM items WHERE last_viewed_at IS NULL AND archived_at IS NULL)",
+            params![due_before.to_rfc3339()],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+        Ok(counts)
+    }
 }
 
 #[cfg(test)]
@@ -139,4 +255,63 @@ mod tests {
         assert_eq!(stats.pending_tasks, 1);
         assert!(stats.database_size_bytes > 0);
     }
+
+    #[test]
+    fn test_ingestion_timeline() {
+        use olal_core::Chunk;
+
+        let db = Database::open_in_memory().unwrap();
+        let today = Utc::now();
+
+        let mut old = Item::new(ItemType::Note, "Old");
+        old.created_at = today - chrono::Duration::days(60);
+        let embedded = Item::new(ItemType::Note, "Embedded");
+        let plain = Item::new(ItemType::Note, "Plain");
+        for item in [&old, &embedded, &plain] {
+            db.create_item(item).unwrap();
+        }
+        let chunks: Vec<Chunk> = (0..3)
+            .map(|i| Chunk::new(embedded.id.clone(), i, format!("chunk {}", i)))
+            .collect();
+        db.create_chunks(&chunks).unwrap();
+        db.store_embedding(&chunks[0].id, &[0.1, 0.2], "test").unwrap();
+
+        let days = db.ingestion_timeline(TimeBucket::Day, 7).unwrap();
+        assert_eq!(days.len(), 7);
+        assert_eq!(days[6].start, today.date_naive());
+        assert_eq!((days[6].items, days[6].embedded), (2, 1));
+        assert_eq!(days.iter().map(|d| d.items).sum::<i64>(), 2);
+
+        let weeks = db.ingestion_timeline(TimeBucket::Week, 12).unwrap();
+        assert_eq!(weeks[11].start.weekday(), chrono::Weekday::Mon);
+        assert_eq!(weeks.iter().map(|w| w.items).sum::<i64>(), 3);
+
+        let largest = db.largest_items(5).unwrap();
+        assert_eq!(largest.len(), 1);
+        assert_eq!((largest[0].0.title.as_str(), largest[0].1), ("Embedded", 3));
+    }
+
+    #[test]
+    fn test_status_summary() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+
+        db.create_item(&Item::new(ItemType::Note, "Fresh")).unwrap();
+        let read = Item::new(ItemType::Note, "Read");
+        db.create_item(&read).unwrap();
+        db.mark_item_viewed(&read.id).unwrap();
+
+        let mut overdue = Task::new("Overdue");
+        overdue.due_date = Some(now - chrono::Duration::days(1));
+        db.create_task(&overdue).unwrap();
+
+        let mut later = Task::new("Later");
+        later.due_date = Some(now + chrono::Duration::days(7));
+        db.create_task(&later).unwrap();
+
+        let (queue, due, unread) = db.status_summary(now).unwrap();
+        assert_eq!(queue, 0);
+        assert_eq!(due, 1);
+        assert_eq!(unread, 1);
+    }
 }
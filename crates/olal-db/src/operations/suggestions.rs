@@ -0,0 +1,223 @@
+//! Review queue for low-confidence AI enrichment.
+
+use crate::database::Database;
+use crate::error::{DbError, DbResult};
+use chrono::{DateTime, Utc};
+use olal_core::{AiSuggestion, ItemId, Link, LinkType, SuggestionKind, SuggestionStatus};
+use rusqlite::params;
+
+impl Database {
+    /// Queue a suggestion for review.
+    ///
+    /// Suggestions identical to an existing one (same item, kind and value)
+    /// are ignored, so re-running enrichment doesn't resurface decisions
+    /// that were already made. Returns whether the suggestion was queued.
+    pub fn create_suggestion(&self, suggestion: &AiSuggestion) -> DbResult<bool> {
+        if self.has_suggestion(&suggestion.item_id, suggestion.kind, &suggestion.value)? {
+            return Ok(false);
+        }
+
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO ai_suggestions (id, item_id, kind, value, confidence, status, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                suggestion.id,
+                suggestion.item_id,
+                suggestion.kind.as_str(),
+                suggestion.value,
+                suggestion.confidence as f64,
+                suggestion.status.as_str(),
+                suggestion.created_at.to_rfc3339(),
+            ],
+        )?;
+        Ok(true)
+    }
+
+    /// Whether a suggestion with this item, kind and value exists in any state.
+    pub fn has_suggestion(&self, item_id: &ItemId, kind: SuggestionKind, value: &str) -> DbResult<bool> {
+        let conn = self.conn()?;
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM ai_suggestions
+             WHERE item_id = ?1 AND kind = ?2 AND value = ?3 COLLATE NOCASE",
+            params![item_id, kind.as_str(), value],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    /// Get a suggestion by ID.
+    pub fn get_suggestion(&self, id: &str) -> DbResult<AiSuggestion> {
+        let conn = self.conn()?;
+        conn.query_row(
+            "SELECT id, item_id, kind, value, confidence, status, created_at
+             FROM ai_suggestions WHERE id = ?1",
+            params![id],
+            row_to_suggestion,
+        )
+        .map_err(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => {
+                DbError::NotFound(format!("Suggestion not found: {}", id))
+            }
+            _ => DbError::from(e),
+        })
+    }
+
+    /// List suggestions, optionally filtered by status and kind, lowest confidence first.
+    pub fn list_suggestions(
+        &self,
+        status: Option<SuggestionStatus>,
+        kind: Option<SuggestionKind>,
+    ) -> DbResult<Vec<AiSuggestion>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, item_id, kind, value, confidence, status, created_at
+             FROM ai_suggestions
+             WHERE (?1 IS NULL OR status = ?1) AND (?2 IS NULL OR kind = ?2)
+             ORDER BY item_id, confidence, created_at",
+        )?;
+
+        let suggestions = stmt.query_map(
+            params![status.map(|s| s.as_str()), kind.map(|k| k.as_str())],
+            row_to_suggestion,
+        )?;
+        suggestions.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Count suggestions awaiting review.
+    pub fn pending_suggestion_count(&self) -> DbResult<i64> {
+        let conn = self.conn()?;
+        let count = conn.query_row(
+            "SELECT COUNT(*) FROM ai_suggestions WHERE status = 'pending_review'",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Accept a pending suggestion: apply the tag, summary or link and mark it accepted.
+    pub fn accept_suggestion(&self, id: &str) -> DbResult<AiSuggestion> {
+        let mut suggestion = self.get_suggestion(id)?;
+        if suggestion.status != SuggestionStatus::PendingReview {
+            return Err(DbError::Other(format!(
+                "Suggestion {} is already {}",
+                id, suggestion.status
+            )));
+        }
+
+        match suggestion.kind {
+            SuggestionKind::Tag => {
+                self.tag_item(&suggestion.item_id, &suggestion.value)?;
+            }
+            SuggestionKind::Summary => {
+                let mut item = self.get_item(&suggestion.item_id)?;
+                item.summary = Some(suggestion.value.clone());
+                self.update_item(&item)?;
+            }
+            SuggestionKind::Link => {
+                let link = Link::new(
+                    suggestion.item_id.clone(),
+                    suggestion.value.clone(),
+                    LinkType::Related,
+                )
+                .with_strength(suggestion.confidence as f64);
+                self.create_link(&link)?;
+            }
+        }
+
+        self.set_suggestion_status(id, SuggestionStatus::Accepted)?;
+        suggestion.status = SuggestionStatus::Accepted;
+        Ok(suggestion)
+    }
+
+    /// Reject a pending suggestion. Nothing is applied.
+    pub fn reject_suggestion(&self, id: &str) -> DbResult<AiSuggestion> {
+        let mut suggestion = self.get_suggestion(id)?;
+        if suggestion.status != SuggestionStatus::PendingReview {
+            return Err(DbError::Other(format!(
+                "Suggestion {} is already {}",
+                id, suggestion.status
+            )));
+        }
+
+        self.set_suggestion_status(id, SuggestionStatus::Rejected)?;
+        suggestion.status = SuggestionStatus::Rejected;
+        Ok(suggestion)
+    }
+
+    fn set_suggestion_status(&self, id: &str, status: SuggestionStatus) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "UPDATE ai_suggestions SET status = ?2 WHERE id = ?1",
+            params![id, status.as_str()],
+        )?;
+        Ok(())
+    }
+}
+
+fn row_to_suggestion(row: &rusqlite::Row) -> rusqlite::Result<AiSuggestion> {
+    let kind_str: String = row.get(2)?;
+    let confidence: f64 = row.get(4)?;
+    let status_str: String = row.get(5)?;
+    let created_at_str: String = row.get(6)?;
+
+    Ok(AiSuggestion {
+        id: row.get(0)?,
+        item_id: row.get(1)?,
+        kind: SuggestionKind::from_str(&kind_str).unwrap_or(SuggestionKind::Tag),
+        value: row.get(3)?,
+        confidence: confidence as f32,
+        status: SuggestionStatus::from_str(&status_str).unwrap_or(SuggestionStatus::PendingReview),
+        created_at: DateTime::parse_from_rfc3339(&created_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, ItemType};
+
+    #[test]
+    fn test_suggestion_review() {
+        let db = Database::open_in_memory().unwrap();
+
+        let item = Item::new(ItemType::Note, "Draft");
+        let other = Item::new(ItemType::Note, "Other");
+        db.create_item(&item).unwrap();
+        db.create_item(&other).unwrap();
+
+        let tag = AiSuggestion::new(item.id.clone(), SuggestionKind::Tag, "maybe-tag", 0.4);
+        let summary = AiSuggestion::new(item.id.clone(), SuggestionKind::Summary, "A draft.", 0.5);
+        let link = AiSuggestion::new(item.id.clone(), SuggestionKind::Link, other.id.clone(), 0.85);
+        assert!(db.create_suggestion(&tag).unwrap());
+        assert!(db.create_suggestion(&summary).unwrap());
+        assert!(db.create_suggestion(&link).unwrap());
+
+        // Duplicates are ignored
+        let again = AiSuggestion::new(item.id.clone(), SuggestionKind::Tag, "Maybe-Tag", 0.3);
+        assert!(!db.create_suggestion(&again).unwrap());
+        assert_eq!(db.pending_suggestion_count().unwrap(), 3);
+
+        // Nothing applied yet
+        assert!(db.get_item_tags(&item.id).unwrap().is_empty());
+        assert!(db.get_item(&item.id).unwrap().summary.is_none());
+
+        db.accept_suggestion(&summary.id).unwrap();
+        db.accept_suggestion(&link.id).unwrap();
+        db.reject_suggestion(&tag.id).unwrap();
+
+        assert_eq!(db.get_item(&item.id).unwrap().summary.as_deref(), Some("A draft."));
+        assert_eq!(db.get_item_links(&other.id).unwrap().len(), 1);
+        assert!(db.get_item_tags(&item.id).unwrap().is_empty());
+        assert_eq!(db.pending_suggestion_count().unwrap(), 0);
+
+        // Decisions are final
+        assert!(db.accept_suggestion(&tag.id).is_err());
+        let rejected = db
+            .list_suggestions(Some(SuggestionStatus::Rejected), Some(SuggestionKind::Tag))
+            .unwrap();
+        assert_eq!(rejected.len(), 1);
+    }
+}
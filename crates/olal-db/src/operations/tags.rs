@@ -1,17 +1,27 @@
 //! Tag CRUD operations.
+//!
+//! Tags nest through their names: `project/olal/backend` is a child of
+//! `project/olal`, which is created along with it. Each tag stores its
+//! parent, and renames, merges and deletes carry the sub-tags along.
 
 use crate::database::Database;
 use crate::error::{DbError, DbResult};
 use olal_core::{ItemId, Tag, TagId};
 use rusqlite::params;
 
+/// The IDs of every tag below `?1`.
+const DESCENDANTS: &str = "WITH RECURSIVE tree(id) AS (
+         SELECT id FROM tags WHERE parent_id = ?1
+         UNION SELECT t.id FROM tags t INNER JOIN tree ON t.parent_id = tree.id
+     )";
+
 impl Database {
     /// Create a new tag.
     pub fn create_tag(&self, tag: &Tag) -> DbResult<()> {
         let conn = self.conn()?;
         conn.execute(
-            "INSERT INTO tags (id, name, color) VALUES (?1, ?2, ?3)",
-            params![tag.id, tag.name, tag.color],
+            "INSERT INTO tags (id, name, color, parent_id) VALUES (?1, ?2, ?3, ?4)",
+            params![tag.id, tag.name, tag.color, tag.parent_id],
         )?;
         Ok(())
     }
@@ -20,15 +30,9 @@ impl Database {
     pub fn get_tag(&self, id: &TagId) -> DbResult<Tag> {
         let conn = self.conn()?;
         let tag = conn.query_row(
-            "SELECT id, name, color FROM tags WHERE id = ?1",
+            "SELECT id, name, color, parent_id FROM tags WHERE id = ?1",
             params![id],
-            |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                })
-            },
+            row_to_tag,
         ).map_err(|e| match e {
             rusqlite::Error::QueryReturnedNoRows => DbError::NotFound(format!("Tag not found: {}", id)),
             _ => DbError::from(e),
@@ -41,15 +45,9 @@ impl Database {
     pub fn get_tag_by_name(&self, name: &str) -> DbResult<Option<Tag>> {
         let conn = self.conn()?;
         let result = conn.query_row(
-            "SELECT id, name, color FROM tags WHERE name = ?1",
+            "SELECT id, name, color, parent_id FROM tags WHERE name = ?1",
             params![name],
-            |row| {
-                Ok(Tag {
-                    id: row.get(0)?,
-                    name: row.get(1)?,
-                    color: row.get(2)?,
-                })
-            },
+            row_to_tag,
         );
 
         match result {
@@ -59,21 +57,31 @@ impl Database {
         }
     }
 
-    /// Get or create a tag by name.
+    /// Get or create a tag by name, creating any missing parent tags.
     pub fn get_or_create_tag(&self, name: &str) -> DbResult<Tag> {
-        if let Some(tag) = self.get_tag_by_name(name)? {
+        let name = Tag::normalize_name(name);
+        if name.is_empty() {
+            return Err(DbError::Other("Tag name is empty".to_string()));
+        }
+        if let Some(tag) = self.get_tag_by_name(&name)? {
             return Ok(tag);
         }
 
-        let tag = Tag::new(name);
+        let mut tag = Tag::new(name);
+        if let Some(parent) = Tag::parent_name(&tag.name) {
+            tag.parent_id = Some(self.get_or_create_tag(parent)?.id);
+        }
         self.create_tag(&tag)?;
         Ok(tag)
     }
 
-    /// Delete a tag by ID.
+    /// Delete a tag and its sub-tags by ID. Items keep their other tags.
     pub fn delete_tag(&self, id: &TagId) -> DbResult<()> {
         let conn = self.conn()?;
-        let rows = conn.execute("DELETE FROM tags WHERE id = ?1", params![id])?;
+        let rows = conn.execute(
+            &format!("{} DELETE FROM tags WHERE id = ?1 OR id IN tree", DESCENDANTS),
+            params![id],
+        )?;
 
         if rows == 0 {
             return Err(DbError::NotFound(format!("Tag not found: {}", id)));
@@ -82,19 +90,121 @@ impl Database {
         Ok(())
     }
 
+    /// Rename a tag, moving its sub-tags along (renaming `ml` to `ai/ml`
+    /// turns `ml/vision` into `ai/ml/vision`). Fails if any of the new
+    /// names is already taken (merge them instead).
+    pub fn rename_tag(&self, id: &TagId, new_name: &str) -> DbResult<()> {
+        let new_name = Tag::normalize_name(new_name);
+        if new_name.is_empty() {
+            return Err(DbError::Other("Tag name is empty".to_string()));
+        }
+        let tag = self.get_tag(id)?;
+        if Tag::new(&new_name).is_within(&tag.name) && !new_name.eq_ignore_ascii_case(&tag.name) {
+            return Err(DbError::Other(format!("Can't move tag '{}' under itself", tag.name)));
+        }
+
+        let mut renames = vec![(tag.id.clone(), new_name.clone())];
+        for descendant in self.get_tag_descendants(id)? {
+            let rest = descendant.name.strip_prefix(&tag.name).unwrap_or_default();
+            renames.push((descendant.id, format!("{}{}", new_name, rest)));
+        }
+        for (id, name) in &renames {
+            if let Some(existing) = self.get_tag_by_name(name)? {
+                if &existing.id != id {
+                    return Err(DbError::Other(format!("Tag '{}' already exists", name)));
+                }
+            }
+        }
+
+        let parent_id = match Tag::parent_name(&new_name) {
+            Some(parent) => Some(self.get_or_create_tag(parent)?.id),
+            None => None,
+        };
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "UPDATE tags SET parent_id = ?1 WHERE id = ?2",
+            params![parent_id, tag.id],
+        )?;
+        for (id, name) in &renames {
+            tx.execute("UPDATE tags SET name = ?1 WHERE id = ?2", params![name, id])?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Move every item tagged `from` to `into`, then delete `from`.
+    /// Sub-tags of `from` move under `into`, merging with any sub-tag of
+    /// the same name. Returns how many items gained the `into` tag.
+    pub fn merge_tags(&self, from: &TagId, into: &TagId) -> DbResult<usize> {
+        if from == into {
+            return Ok(0);
+        }
+        let from_tag = self.get_tag(from)?;
+        let into_tag = self.get_tag(into)?;
+        if into_tag.is_within(&from_tag.name) {
+            return Err(DbError::Other(format!(
+                "Can't merge tag '{}' into its own sub-tag '{}'",
+                from_tag.name, into_tag.name
+            )));
+        }
+
+        for child in self.get_tag_children(from)? {
+            let target = format!("{}/{}", into_tag.name, child.leaf_name());
+            match self.get_tag_by_name(&target)? {
+                Some(existing) => {
+                    self.merge_tags(&child.id, &existing.id)?;
+                }
+                None => self.rename_tag(&child.id, &target)?,
+            }
+        }
+
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        let added = tx.execute(
+            "INSERT OR IGNORE INTO item_tags (item_id, tag_id)
+             SELECT item_id, ?2 FROM item_tags WHERE tag_id = ?1",
+            params![from, into],
+        )?;
+        tx.execute("DELETE FROM item_tags WHERE tag_id = ?1", params![from])?;
+        tx.execute("DELETE FROM tags WHERE id = ?1", params![from])?;
+        tx.commit()?;
+
+        Ok(added)
+    }
+
     /// List all tags.
     pub fn list_tags(&self) -> DbResult<Vec<Tag>> {
         let conn = self.conn()?;
-        let mut stmt = conn.prepare("SELECT id, name, color FROM tags ORDER BY name")?;
-
-        let tags = stmt.query_map([], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-            })
-        })?;
+        let mut stmt = conn.prepare("SELECT id, name, color, parent_id FROM tags ORDER BY name")?;
+
+        let tags = stmt.query_map([], row_to_tag)?;
+
+        tags.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// The tags directly below a tag.
+    pub fn get_tag_children(&self, id: &TagId) -> DbResult<Vec<Tag>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, name, color, parent_id FROM tags WHERE parent_id = ?1 ORDER BY name",
+        )?;
+
+        let tags = stmt.query_map(params![id], row_to_tag)?;
+        tags.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
 
+    /// Every tag below a tag, at any depth.
+    pub fn get_tag_descendants(&self, id: &TagId) -> DbResult<Vec<Tag>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "{} SELECT id, name, color, parent_id FROM tags WHERE id IN tree ORDER BY name",
+            DESCENDANTS
+        ))?;
+
+        let tags = stmt.query_map(params![id], row_to_tag)?;
         tags.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 
@@ -129,18 +239,12 @@ impl Database {
     pub fn get_item_tags(&self, item_id: &ItemId) -> DbResult<Vec<Tag>> {
         let conn = self.conn()?;
         let mut stmt = conn.prepare(
-            "SELECT t.id, t.name, t.color FROM tags t
+            "SELECT t.id, t.name, t.color, t.parent_id FROM tags t
              INNER JOIN item_tags it ON it.tag_id = t.id
              WHERE it.item_id = ?1 ORDER BY t.name",
         )?;
 
-        let tags = stmt.query_map(params![item_id], |row| {
-            Ok(Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-            })
-        })?;
+        let tags = stmt.query_map(params![item_id], row_to_tag)?;
 
         tags.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
@@ -156,11 +260,37 @@ impl Database {
         items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 
+    /// Get all items with a tag or any of its sub-tags.
+    pub fn get_items_by_tag_tree(&self, tag_id: &TagId) -> DbResult<Vec<ItemId>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "{} SELECT DISTINCT item_id FROM item_tags WHERE tag_id = ?1 OR tag_id IN tree",
+            DESCENDANTS
+        ))?;
+
+        let items = stmt.query_map(params![tag_id], |row| row.get(0))?;
+        items.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Get every item/tag association.
+    pub fn get_all_item_tags(&self) -> DbResult<Vec<(ItemId, Tag)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.color, t.parent_id, it.item_id FROM item_tags it
+             INNER JOIN tags t ON t.id = it.tag_id
+             ORDER BY t.name",
+        )?;
+
+        let results = stmt.query_map([], |row| Ok((row.get(4)?, row_to_tag(row)?)))?;
+
+        results.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
     /// Get tag usage counts.
     pub fn get_tag_counts(&self) -> DbResult<Vec<(Tag, i64)>> {
         let conn = self.conn()?;
         let mut stmt = conn.prepare(
-            "SELECT t.id, t.name, t.color, COUNT(it.item_id) as count
+            "SELECT t.id, t.name, t.color, t.parent_id, COUNT(it.item_id) as count
              FROM tags t
              LEFT JOIN item_tags it ON it.tag_id = t.id
              GROUP BY t.id
@@ -168,19 +298,23 @@ impl Database {
         )?;
 
         let results = stmt.query_map([], |row| {
-            let tag = Tag {
-                id: row.get(0)?,
-                name: row.get(1)?,
-                color: row.get(2)?,
-            };
-            let count: i64 = row.get(3)?;
-            Ok((tag, count))
+            let count: i64 = row.get(4)?;
+            Ok((row_to_tag(row)?, count))
         })?;
 
         results.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 }
 
+fn row_to_tag(row: &rusqlite::Row) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        parent_id: row.get(3)?,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,6 +376,30 @@ mod tests {
         assert!(tags.is_empty());
     }
 
+    #[test]
+    fn test_get_all_item_tags() {
+        let db = Database::open_in_memory().unwrap();
+
+        let a = Item::new(ItemType::Note, "A");
+        let b = Item::new(ItemType::Note, "B");
+        db.create_item(&a).unwrap();
+        db.create_item(&b).unwrap();
+        db.tag_item(&a.id, "rust").unwrap();
+        db.tag_item(&b.id, "rust").unwrap();
+        db.tag_item(&a.id, "cargo").unwrap();
+
+        let all: Vec<(ItemId, String)> = db
+            .get_all_item_tags()
+            .unwrap()
+            .into_iter()
+            .map(|(item_id, tag)| (item_id, tag.name))
+            .collect();
+        assert_eq!(all.len(), 3);
+        // Ordered by tag name
+        assert_eq!(all[0], (a.id.clone(), "cargo".to_string()));
+        assert!(all[1..].iter().all(|(_, name)| name == "rust"));
+    }
+
     #[test]
     fn test_tag_item_helper() {
         let db = Database::open_in_memory().unwrap();
@@ -257,4 +415,81 @@ mod tests {
         let existing = db.get_tag_by_name("auto-created").unwrap();
         assert!(existing.is_some());
     }
+
+    #[test]
+    fn test_rename_and_merge_tags() {
+        let db = Database::open_in_memory().unwrap();
+
+        let first = Item::new(ItemType::Note, "First");
+        let second = Item::new(ItemType::Note, "Second");
+        db.create_item(&first).unwrap();
+        db.create_item(&second).unwrap();
+
+        let ml = db.tag_item(&first.id, "ml").unwrap();
+        db.tag_item(&second.id, "ml").unwrap();
+        let ai = db.tag_item(&first.id, "ai").unwrap();
+
+        // Rename
+        db.rename_tag(&ml.id, "machine-learning").unwrap();
+        assert_eq!(db.get_tag(&ml.id).unwrap().name, "machine-learning");
+        assert!(db.get_tag_by_name("ml").unwrap().is_none());
+
+        // Renaming onto an existing tag is refused
+        assert!(db.rename_tag(&ai.id, "machine-learning").is_err());
+
+        // Merge: only the second item is new to the target
+        let added = db.merge_tags(&ml.id, &ai.id).unwrap();
+        assert_eq!(added, 1);
+        assert!(db.get_tag(&ml.id).is_err());
+        assert_eq!(db.get_items_by_tag(&ai.id).unwrap().len(), 2);
+        assert_eq!(db.get_item_tags(&first.id).unwrap().len(), 1);
+
+        // Deleting a tag untags its items
+        db.delete_tag(&ai.id).unwrap();
+        assert!(db.get_item_tags(&first.id).unwrap().is_empty());
+        assert!(db.get_all_item_tags().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_nested_tags() {
+        let db = Database::open_in_memory().unwrap();
+
+        let item = Item::new(ItemType::Note, "Backend notes");
+        let other = Item::new(ItemType::Note, "Roadmap");
+        db.create_item(&item).unwrap();
+        db.create_item(&other).unwrap();
+
+        // Parents are created along with the tag
+        let backend = db.tag_item(&item.id, " project/olal /backend").unwrap();
+        assert_eq!(backend.name, "project/olal/backend");
+        let olal = db.get_tag_by_name("project/olal").unwrap().unwrap();
+        let project = db.get_tag_by_name("project").unwrap().unwrap();
+        assert_eq!(backend.parent_id, Some(olal.id.clone()));
+        assert_eq!(olal.parent_id, Some(project.id.clone()));
+        db.add_tag_to_item(&other.id, &olal.id).unwrap();
+
+        assert_eq!(db.get_tag_descendants(&project.id).unwrap().len(), 2);
+        assert_eq!(db.get_items_by_tag_tree(&project.id).unwrap().len(), 2);
+        assert_eq!(db.get_items_by_tag_tree(&backend.id).unwrap(), vec![item.id.clone()]);
+
+        // Renames carry sub-tags along, under a new parent
+        db.rename_tag(&olal.id, "work/olal").unwrap();
+        assert_eq!(db.get_tag(&backend.id).unwrap().name, "work/olal/backend");
+        let work = db.get_tag_by_name("work").unwrap().unwrap();
+        assert_eq!(db.get_tag(&olal.id).unwrap().parent_id, Some(work.id.clone()));
+        assert!(db.rename_tag(&work.id, "work/olal/inner").is_err());
+
+        // Merging moves sub-tags, combining same-named ones
+        let api = db.get_or_create_tag("project/backend").unwrap();
+        db.add_tag_to_item(&other.id, &api.id).unwrap();
+        db.merge_tags(&olal.id, &project.id).unwrap();
+        assert!(db.get_tag(&backend.id).is_err());
+        assert_eq!(db.get_items_by_tag(&api.id).unwrap().len(), 2);
+        assert!(db.merge_tags(&project.id, &api.id).is_err());
+
+        // Deleting a tag deletes its sub-tags
+        db.delete_tag(&project.id).unwrap();
+        assert!(db.get_tag(&api.id).is_err());
+        assert_eq!(db.list_tags().unwrap().len(), 1);
+    }
 }
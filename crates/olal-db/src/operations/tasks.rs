@@ -2,7 +2,7 @@
 
 use crate::database::Database;
 use crate::error::{DbError, DbResult};
-use olal_core::{Task, TaskStatus};
+use olal_core::{Recurrence, Task, TaskStatus};
 use chrono::{DateTime, Utc};
 use rusqlite::params;
 
@@ -12,8 +12,8 @@ impl Database {
         let conn = self.conn()?;
         conn.execute(
             r#"
-            INSERT INTO tasks (id, title, description, status, priority, project_id, due_date, created_at, completed_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+            INSERT INTO tasks (id, title, description, status, priority, project_id, due_date, created_at, completed_at, recurrence, parent_task_id)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
             "#,
             params![
                 task.id,
@@ -25,6 +25,8 @@ impl Database {
                 task.due_date.map(|dt| dt.to_rfc3339()),
                 task.created_at.to_rfc3339(),
                 task.completed_at.map(|dt| dt.to_rfc3339()),
+                task.recurrence.map(|r| r.as_str()),
+                task.parent_task_id,
             ],
         )?;
         Ok(())
@@ -34,7 +36,7 @@ impl Database {
     pub fn get_task(&self, id: &str) -> DbResult<Task> {
         let conn = self.conn()?;
         let task = conn.query_row(
-            "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at
+            "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at, recurrence, parent_task_id
              FROM tasks WHERE id = ?1",
             params![id],
             row_to_task,
@@ -53,7 +55,8 @@ impl Database {
             r#"
             UPDATE tasks
             SET title = ?2, description = ?3, status = ?4, priority = ?5,
-                project_id = ?6, due_date = ?7, completed_at = ?8
+                project_id = ?6, due_date = ?7, completed_at = ?8, recurrence = ?9,
+                parent_task_id = ?10
             WHERE id = ?1
             "#,
             params![
@@ -65,6 +68,8 @@ impl Database {
                 task.project_id,
                 task.due_date.map(|dt| dt.to_rfc3339()),
                 task.completed_at.map(|dt| dt.to_rfc3339()),
+                task.recurrence.map(|r| r.as_str()),
+                task.parent_task_id,
             ],
         )?;
 
@@ -94,7 +99,7 @@ impl Database {
         let tasks = match status {
             Some(s) => {
                 let mut stmt = conn.prepare(
-                    "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at
+                    "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at, recurrence, parent_task_id
                      FROM tasks WHERE status = ?1 ORDER BY priority DESC, created_at",
                 )?;
                 let rows = stmt.query_map(params![s.as_str()], row_to_task)?;
@@ -102,7 +107,7 @@ impl Database {
             }
             None => {
                 let mut stmt = conn.prepare(
-                    "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at
+                    "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at, recurrence, parent_task_id
                      FROM tasks ORDER BY priority DESC, created_at",
                 )?;
                 let rows = stmt.query_map([], row_to_task)?;
@@ -117,7 +122,7 @@ impl Database {
     pub fn list_tasks_by_project(&self, project_id: &str) -> DbResult<Vec<Task>> {
         let conn = self.conn()?;
         let mut stmt = conn.prepare(
-            "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at
+            "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at, recurrence, parent_task_id
              FROM tasks WHERE project_id = ?1 ORDER BY priority DESC, created_at",
         )?;
 
@@ -125,6 +130,87 @@ impl Database {
         tasks.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
     }
 
+    /// Open (pending or in-progress) tasks due at or before `until`,
+    /// soonest first.
+    pub fn list_tasks_due_before(&self, until: DateTime<Utc>) -> DbResult<Vec<Task>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, title, description, status, priority, project_id, due_date, created_at, completed_at, recurrence, parent_task_id
+             FROM tasks
+             WHERE status IN ('pending', 'in_progress') AND due_date IS NOT NULL AND due_date <= ?1
+             ORDER BY due_date, priority DESC",
+        )?;
+
+        let tasks = stmt.query_map(params![until.to_rfc3339()], row_to_task)?;
+        tasks.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Record that `task_id` can't start until `blocked_by` is done.
+    /// Fails if that would make the two tasks wait on each other.
+    pub fn add_task_dependency(&self, task_id: &str, blocked_by: &str) -> DbResult<()> {
+        if task_id == blocked_by {
+            return Err(DbError::Other("A task can't block itself".to_string()));
+        }
+        self.get_task(task_id)?;
+        self.get_task(blocked_by)?;
+
+        let conn = self.conn()?;
+        // Does the blocker already wait on the task, directly or not?
+        let cycle: bool = conn.query_row(
+            "WITH RECURSIVE waits(id) AS (
+                 SELECT blocked_by FROM task_deps WHERE task_id = ?1
+                 UNION SELECT d.blocked_by FROM task_deps d INNER JOIN waits ON d.task_id = waits.id
+             )
+             SELECT EXISTS (SELECT 1 FROM waits WHERE id = ?2)",
+            params![blocked_by, task_id],
+            |row| row.get(0),
+        )?;
+        if cycle {
+            return Err(DbError::Other("That would make the tasks block each other".to_string()));
+        }
+
+        conn.execute(
+            "INSERT OR IGNORE INTO task_deps (task_id, blocked_by) VALUES (?1, ?2)",
+            params![task_id, blocked_by],
+        )?;
+        Ok(())
+    }
+
+    /// Remove a blocked-by relationship. Returns false if there wasn't one.
+    pub fn remove_task_dependency(&self, task_id: &str, blocked_by: &str) -> DbResult<bool> {
+        let conn = self.conn()?;
+        let rows = conn.execute(
+            "DELETE FROM task_deps WHERE task_id = ?1 AND blocked_by = ?2",
+            params![task_id, blocked_by],
+        )?;
+        Ok(rows > 0)
+    }
+
+    /// The tasks a task is blocked by.
+    pub fn get_task_blockers(&self, task_id: &str) -> DbResult<Vec<Task>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.title, t.description, t.status, t.priority, t.project_id, t.due_date,
+                    t.created_at, t.completed_at, t.recurrence, t.parent_task_id
+             FROM tasks t
+             INNER JOIN task_deps d ON d.blocked_by = t.id
+             WHERE d.task_id = ?1
+             ORDER BY t.created_at",
+        )?;
+
+        let tasks = stmt.query_map(params![task_id], row_to_task)?;
+        tasks.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Every blocked-by relationship, as (task, blocker) pairs.
+    pub fn list_task_dependencies(&self) -> DbResult<Vec<(String, String)>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT task_id, blocked_by FROM task_deps")?;
+
+        let deps = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        deps.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
     /// Mark a task as done.
     pub fn complete_task(&self, id: &str) -> DbResult<()> {
         let conn = self.conn()?;
@@ -141,6 +227,23 @@ impl Database {
 
         Ok(())
     }
+
+    /// Tasks created and tasks completed between `start` and `end`.
+    pub fn task_activity(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> DbResult<(usize, usize)> {
+        let conn = self.conn()?;
+        let (start, end) = (start.to_rfc3339(), end.to_rfc3339());
+        let created: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE created_at >= ?1 AND created_at <= ?2",
+            params![start, end],
+            |row| row.get(0),
+        )?;
+        let completed: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM tasks WHERE completed_at >= ?1 AND completed_at <= ?2",
+            params![start, end],
+            |row| row.get(0),
+        )?;
+        Ok((created as usize, completed as usize))
+    }
 }
 
 fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
@@ -148,6 +251,7 @@ fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
     let due_date_str: Option<String> = row.get(6)?;
     let created_at_str: String = row.get(7)?;
     let completed_at_str: Option<String> = row.get(8)?;
+    let recurrence_str: Option<String> = row.get(9)?;
 
     Ok(Task {
         id: row.get(0)?,
@@ -169,6 +273,8 @@ fn row_to_task(row: &rusqlite::Row) -> rusqlite::Result<Task> {
                 .map(|dt| dt.with_timezone(&Utc))
                 .ok()
         }),
+        recurrence: recurrence_str.and_then(|s| Recurrence::from_str(&s)),
+        parent_task_id: row.get(10)?,
     })
 }
 
@@ -230,4 +336,63 @@ mod tests {
         let all = db.list_tasks(None).unwrap();
         assert_eq!(all.len(), 2);
     }
+
+    #[test]
+    fn test_tasks_due_before() {
+        use chrono::Duration;
+
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+
+        let overdue = Task::new("Overdue")
+            .with_due_date(now - Duration::days(1))
+            .with_recurrence(Recurrence::Weekly);
+        let later = Task::new("Later").with_due_date(now + Duration::days(3));
+        let mut done = Task::new("Done").with_due_date(now - Duration::days(2));
+        done.mark_done();
+        for task in [&overdue, &later, &done] {
+            db.create_task(task).unwrap();
+        }
+        db.create_task(&Task::new("Someday")).unwrap();
+
+        let due: Vec<_> = db
+            .list_tasks_due_before(now)
+            .unwrap()
+            .into_iter()
+            .map(|t| t.title)
+            .collect();
+        assert_eq!(due, vec!["Overdue"]);
+        assert_eq!(db.list_tasks_due_before(now + Duration::days(7)).unwrap().len(), 2);
+        assert_eq!(db.get_task(&overdue.id).unwrap().recurrence, Some(Recurrence::Weekly));
+    }
+
+    #[test]
+    fn test_subtasks_and_dependencies() {
+        let db = Database::open_in_memory().unwrap();
+
+        let release = Task::new("Release");
+        let changelog = Task::new("Write changelog").with_parent(release.id.clone());
+        let tests = Task::new("Fix tests");
+        for task in [&release, &changelog, &tests] {
+            db.create_task(task).unwrap();
+        }
+        assert_eq!(db.get_task(&changelog.id).unwrap().parent_task_id, Some(release.id.clone()));
+
+        db.add_task_dependency(&release.id, &tests.id).unwrap();
+        db.add_task_dependency(&tests.id, &changelog.id).unwrap();
+        assert_eq!(db.get_task_blockers(&release.id).unwrap()[0].title, "Fix tests");
+        assert_eq!(db.list_task_dependencies().unwrap().len(), 2);
+
+        // No cycles, direct or indirect
+        assert!(db.add_task_dependency(&tests.id, &tests.id).is_err());
+        assert!(db.add_task_dependency(&changelog.id, &release.id).is_err());
+
+        assert!(db.remove_task_dependency(&tests.id, &changelog.id).unwrap());
+        assert!(!db.remove_task_dependency(&tests.id, &changelog.id).unwrap());
+
+        // Deleting a task removes its subtasks and relationships
+        db.delete_task(&release.id).unwrap();
+        assert!(db.get_task(&changelog.id).is_err());
+        assert!(db.list_task_dependencies().unwrap().is_empty());
+    }
 }
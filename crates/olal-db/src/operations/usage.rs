@@ -0,0 +1,86 @@
+//! Local command usage history. Never leaves the machine.
+
+use crate::database::Database;
+use crate::error::DbResult;
+use chrono::{DateTime, Utc};
+use rusqlite::params;
+
+/// One invocation of an olal command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandUsage {
+    /// Command path, e.g. "search" or "queue list".
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: i64,
+    pub success: bool,
+}
+
+impl Database {
+    /// Record a command invocation.
+    pub fn record_command_usage(&self, usage: &CommandUsage) -> DbResult<()> {
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO command_usage (command, started_at, duration_ms, success)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                usage.command,
+                usage.started_at.to_rfc3339(),
+                usage.duration_ms,
+                usage.success,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Command invocations started at or after `since`, oldest first.
+    pub fn command_usage_since(&self, since: DateTime<Utc>) -> DbResult<Vec<CommandUsage>> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT command, started_at, duration_ms, success FROM command_usage
+             WHERE started_at >= ?1 ORDER BY started_at",
+        )?;
+        let usage = stmt
+            .query_map(params![since.to_rfc3339()], row_to_usage)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(usage)
+    }
+}
+
+fn row_to_usage(row: &rusqlite::Row) -> rusqlite::Result<CommandUsage> {
+    let started_at_str: String = row.get(1)?;
+
+    Ok(CommandUsage {
+        command: row.get(0)?,
+        started_at: DateTime::parse_from_rfc3339(&started_at_str)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        duration_ms: row.get(2)?,
+        success: row.get(3)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_command_usage() {
+        let db = Database::open_in_memory().unwrap();
+        let now = Utc::now();
+        for (command, age, success) in [("search", 40, true), ("ask", 2, true), ("queue list", 1, false)] {
+            db.record_command_usage(&CommandUsage {
+                command: command.to_string(),
+                started_at: now - Duration::days(age),
+                duration_ms: 120,
+                success,
+            })
+            .unwrap();
+        }
+
+        let recent = db.command_usage_since(now - Duration::days(30)).unwrap();
+        let commands: Vec<_> = recent.iter().map(|u| u.command.as_str()).collect();
+        assert_eq!(commands, vec!["ask", "queue list"]);
+        assert!(!recent[1].success);
+    }
+}
@@ -1,9 +1,16 @@
 //! Vector search operations for semantic search.
 
+use crate::cache::{self, Cached};
+use crate::compression::chunk_content;
 use crate::database::Database;
-use crate::error::DbResult;
-use olal_core::Chunk;
-use rusqlite::params;
+use crate::error::{DbError, DbResult};
+use crate::filter::SearchFilter;
+use crate::fts;
+use olal_core::{Chunk, ItemId};
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
 
 /// Result of a similarity search.
 #[derive(Debug, Clone)]
@@ -18,6 +25,24 @@ pub struct SimilarityResult {
     pub item_title: String,
 }
 
+/// Result of a hybrid search, with the score from each source.
+#[derive(Debug, Clone)]
+pub struct HybridResult {
+    /// The matching chunk.
+    pub chunk: Chunk,
+    /// Weighted combination of the two scores (0.0 to 1.0).
+    pub score: f32,
+    /// Cosine similarity, if vector search found the chunk.
+    pub vector_score: Option<f32>,
+    /// BM25 relevance relative to the best match, if full-text search
+    /// found the chunk.
+    pub fts_score: Option<f32>,
+    /// ID of the parent item.
+    pub item_id: String,
+    /// Title of the parent item.
+    pub item_title: String,
+}
+
 /// Calculate cosine similarity between two vectors.
 pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     if a.len() != b.len() || a.is_empty() {
@@ -42,54 +67,128 @@ pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
     dot_product / denominator
 }
 
+/// Compute the centroid (element-wise mean) of a set of vectors.
+///
+/// Vectors whose length differs from the first one are skipped, so mixing
+/// embedding models never produces a garbage centroid.
+pub fn centroid(vectors: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let dimensions = vectors.first()?.len();
+    if dimensions == 0 {
+        return None;
+    }
+
+    let mut sum = vec![0.0f32; dimensions];
+    let mut count = 0usize;
+
+    for vector in vectors.iter().filter(|v| v.len() == dimensions) {
+        for (acc, value) in sum.iter_mut().zip(vector) {
+            *acc += value;
+        }
+        count += 1;
+    }
+
+    let count = count as f32;
+    Some(sum.into_iter().map(|v| v / count).collect())
+}
+
+/// Key of the embedding cache: the same text embedded by the same model
+/// always gives the same vector, so it only needs generating once.
+pub fn embedding_hash(content: &str, model: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(model.as_bytes());
+    hasher.update([0]);
+    hasher.update(content.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Decode a little-endian f32 vector stored as a BLOB.
+pub(crate) fn bytes_to_vector(bytes: &[u8], dimensions: i32) -> Vec<f32> {
+    bytes
+        .chunks(4)
+        .take(dimensions as usize)
+        .map(|b| {
+            if b.len() == 4 {
+                f32::from_le_bytes([b[0], b[1], b[2], b[3]])
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
 impl Database {
     /// Find similar chunks using cosine similarity.
     ///
     /// This performs a brute-force search over all embeddings, which is
-    /// efficient for personal knowledge bases (<100K chunks).
+    /// efficient for personal knowledge bases (<100K chunks). Results are
+    /// served from the query cache until the next write.
     pub fn vector_search(
         &self,
         query_vector: &[f32],
         limit: usize,
         min_similarity: Option<f32>,
     ) -> DbResult<Vec<SimilarityResult>> {
-        let conn = self.conn()?;
+        self.vector_search_filtered(query_vector, limit, min_similarity, &SearchFilter::default())
+    }
+
+    /// Find similar chunks in items matching `filter`.
+    pub fn vector_search_filtered(
+        &self,
+        query_vector: &[f32],
+        limit: usize,
+        min_similarity: Option<f32>,
+        filter: &SearchFilter,
+    ) -> DbResult<Vec<SimilarityResult>> {
         let min_sim = min_similarity.unwrap_or(0.0);
+        let key = cache::vector_key(query_vector, limit, min_sim, filter);
+        if let Some(Cached::Similar(results)) = self.cache.get(&key) {
+            return Ok(results);
+        }
+        let generation = self.cache.generation();
+
+        let conn = self.conn()?;
 
         // Get all embeddings with their chunk and item info
-        let mut stmt = conn.prepare(
+        let (conditions, filter_params) = filter.to_sql(1);
+        let mut stmt = conn.prepare(&format!(
             r#"
             SELECT
                 c.id, c.item_id, c.chunk_index, c.content, c.start_time, c.end_time,
                 e.vector, e.dimensions,
-                i.title
-            FROM embeddings e
-            JOIN chunks c ON c.id = e.chunk_id
+                i.title, c.duplicate_of, c.start_page, c.end_page
+            FROM chunks c
+            JOIN embeddings e ON e.chunk_id = COALESCE(c.duplicate_of, c.id)
             JOIN items i ON i.id = c.item_id
+            WHERE i.archived_at IS NULL{}
             "#,
-        )?;
+            conditions
+        ))?;
 
-        let mut results: Vec<SimilarityResult> = Vec::new();
+        // With the chunk each result duplicates, if any
+        let mut results: Vec<(SimilarityResult, Option<String>)> = Vec::new();
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(params_from_iter(filter_params), |row| {
             let chunk = Chunk {
                 id: row.get(0)?,
                 item_id: row.get(1)?,
                 chunk_index: row.get(2)?,
-                content: row.get(3)?,
+                content: chunk_content(row, 3)?,
                 start_time: row.get(4)?,
                 end_time: row.get(5)?,
+                start_page: row.get(10)?,
+                end_page: row.get(11)?,
             };
 
             let vector_bytes: Vec<u8> = row.get(6)?;
             let dimensions: i32 = row.get(7)?;
             let item_title: String = row.get(8)?;
+            let duplicate_of: Option<String> = row.get(9)?;
 
-            Ok((chunk, vector_bytes, dimensions, item_title))
+            Ok((chunk, vector_bytes, dimensions, item_title, duplicate_of))
         })?;
 
         for row_result in rows {
-            let (chunk, vector_bytes, dimensions, item_title) = row_result?;
+            let (chunk, vector_bytes, dimensions, item_title, duplicate_of) = row_result?;
 
             // Deserialize the vector
             let vector: Vec<f32> = vector_bytes
@@ -108,109 +207,178 @@ impl Database {
             let similarity = cosine_similarity(query_vector, &vector);
 
             if similarity >= min_sim {
-                results.push(SimilarityResult {
+                let result = SimilarityResult {
                     item_id: chunk.item_id.clone(),
                     item_title,
                     chunk,
                     similarity,
-                });
+                };
+                results.push((result, duplicate_of));
             }
         }
 
-        // Sort by similarity (descending)
-        results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap());
-
-        // Limit results
-        results.truncate(limit);
+        // Sort by similarity (descending), originals before their duplicates
+        results.sort_by(|(a, a_of), (b, b_of)| {
+            b.similarity
+                .partial_cmp(&a.similarity)
+                .unwrap()
+                .then(a_of.is_some().cmp(&b_of.is_some()))
+        });
+
+        // Only one of the same text, then limit results
+        let mut seen = HashSet::new();
+        let results: Vec<SimilarityResult> = results
+            .into_iter()
+            .filter(|(r, of)| seen.insert(of.clone().unwrap_or_else(|| r.chunk.id.clone())))
+            .map(|(r, _)| r)
+            .take(limit)
+            .collect();
 
+        self.cache.put(key, generation, Cached::Similar(results.clone()));
         Ok(results)
     }
 
     /// Hybrid search combining vector similarity and full-text search.
     ///
-    /// The final score is: `vector_weight * vector_score + (1 - vector_weight) * fts_score`
+    /// Full-text scores are BM25 relative to the best match (which scores
+    /// 1.0). The final score is:
+    /// `vector_weight * vector_score + (1 - vector_weight) * fts_score`,
+    /// counting a source that didn't find the chunk as 0. Only items
+    /// matching `filter` are searched.
     pub fn hybrid_search(
         &self,
         query: &str,
         query_vector: &[f32],
         limit: usize,
         vector_weight: f32,
-    ) -> DbResult<Vec<SimilarityResult>> {
+        filter: &SearchFilter,
+    ) -> DbResult<Vec<HybridResult>> {
+        let vector_weight = vector_weight.clamp(0.0, 1.0);
+
         // Get vector search results (more than limit to allow for combining)
-        let vector_results = self.vector_search(query_vector, limit * 2, Some(0.1))?;
+        let vector_results =
+            self.vector_search_filtered(query_vector, limit * 2, Some(0.1), filter)?;
 
         // Get FTS results
         let conn = self.conn()?;
-        let mut fts_stmt = conn.prepare(
+        let (conditions, mut filter_params) = filter.to_sql(3);
+        let mut fts_stmt = conn.prepare(&format!(
             r#"
             SELECT c.id, c.item_id, c.chunk_index, c.content, c.start_time, c.end_time,
-                   i.title, bm25(chunks_fts)
+                   i.title, bm25(chunks_fts), c.start_page, c.end_page
             FROM chunks_fts
-            JOIN chunks c ON c.id = chunks_fts.rowid
+            JOIN chunks c ON c.rowid = chunks_fts.rowid
             JOIN items i ON i.id = c.item_id
-            WHERE chunks_fts MATCH ?1
+            WHERE chunks_fts MATCH ?1 AND i.archived_at IS NULL{}
             ORDER BY bm25(chunks_fts)
             LIMIT ?2
             "#,
-        )?;
+            conditions
+        ))?;
 
-        let fts_results: Vec<(Chunk, String, f32)> = fts_stmt
-            .query_map(params![query, limit * 2], |row| {
+        let mut params = vec![Value::Text(fts::match_query(query)), Value::Integer((limit * 2) as i64)];
+        params.append(&mut filter_params);
+        let fts_results: Vec<(Chunk, String, f64)> = fts_stmt
+            .query_map(params_from_iter(params), |row| {
                 let chunk = Chunk {
                     id: row.get(0)?,
                     item_id: row.get(1)?,
                     chunk_index: row.get(2)?,
-                    content: row.get(3)?,
+                    content: chunk_content(row, 3)?,
                     start_time: row.get(4)?,
                     end_time: row.get(5)?,
+                    start_page: row.get(8)?,
+                    end_page: row.get(9)?,
                 };
                 let item_title: String = row.get(6)?;
                 let bm25_score: f64 = row.get(7)?;
-                // BM25 scores are negative, normalize to 0-1 range
-                let normalized_score = 1.0 / (1.0 + (-bm25_score as f32).exp());
-                Ok((chunk, item_title, normalized_score))
+                Ok((chunk, item_title, bm25_score))
             })?
-            .filter_map(|r| r.ok())
-            .collect();
+            .collect::<Result<Vec<_>, _>>()?;
 
-        // Combine results using a simple score fusion
-        use std::collections::HashMap;
+        // BM25 scores are negative, lower is better; scale by the best one
+        let best_bm25 = fts_results.first().map(|(_, _, score)| *score).unwrap_or(0.0);
 
-        let mut combined: HashMap<String, SimilarityResult> = HashMap::new();
+        // Combine results using a simple score fusion
+        let mut combined: HashMap<String, HybridResult> = HashMap::new();
 
         // Add vector results
         for result in vector_results {
             combined.insert(
                 result.chunk.id.clone(),
-                SimilarityResult {
-                    similarity: result.similarity * vector_weight,
-                    ..result
+                HybridResult {
+                    score: result.similarity * vector_weight,
+                    vector_score: Some(result.similarity),
+                    fts_score: None,
+                    chunk: result.chunk,
+                    item_id: result.item_id,
+                    item_title: result.item_title,
                 },
             );
         }
 
         // Add/update with FTS results
         let fts_weight = 1.0 - vector_weight;
-        for (chunk, item_title, fts_score) in fts_results {
-            let chunk_id = chunk.id.clone();
+        for (chunk, item_title, bm25_score) in fts_results {
+            let fts_score = if best_bm25 < 0.0 {
+                (bm25_score / best_bm25) as f32
+            } else {
+                1.0
+            };
             let item_id = chunk.item_id.clone();
 
             combined
-                .entry(chunk_id)
+                .entry(chunk.id.clone())
                 .and_modify(|e| {
-                    e.similarity += fts_score * fts_weight;
+                    e.score += fts_score * fts_weight;
+                    e.fts_score = Some(fts_score);
                 })
-                .or_insert(SimilarityResult {
+                .or_insert(HybridResult {
                     chunk,
-                    similarity: fts_score * fts_weight,
+                    score: fts_score * fts_weight,
+                    vector_score: None,
+                    fts_score: Some(fts_score),
                     item_id,
                     item_title,
                 });
         }
 
+        // A chunk and its duplicates are one result, with the best of both
+        // scores
+        let ids: Vec<Value> = combined.keys().cloned().map(Value::Text).collect();
+        let mut stmt = conn.prepare(&format!(
+            "SELECT id, COALESCE(duplicate_of, id) FROM chunks WHERE id IN ({})",
+            vec!["?"; ids.len()].join(", ")
+        ))?;
+        let originals: HashMap<String, String> = stmt
+            .query_map(params_from_iter(ids), |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        let original_of = |id: &String| originals.get(id).unwrap_or(id).clone();
+        let mut combined: Vec<HybridResult> = combined.into_values().collect();
+        // Originals first, so they stand for their duplicates
+        combined.sort_by_key(|r| original_of(&r.chunk.id) != r.chunk.id);
+        let mut deduplicated: HashMap<String, HybridResult> = HashMap::new();
+        for result in combined {
+            let best = |a: Option<f32>, b: Option<f32>| match (a, b) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                _ => a.or(b),
+            };
+            match deduplicated.get_mut(&original_of(&result.chunk.id)) {
+                Some(kept) => {
+                    kept.vector_score = best(kept.vector_score, result.vector_score);
+                    kept.fts_score = best(kept.fts_score, result.fts_score);
+                    kept.score = kept.vector_score.unwrap_or(0.0) * vector_weight
+                        + kept.fts_score.unwrap_or(0.0) * fts_weight;
+                }
+                None => {
+                    deduplicated.insert(original_of(&result.chunk.id), result);
+                }
+            }
+        }
+
         // Sort and limit
-        let mut results: Vec<SimilarityResult> = combined.into_values().collect();
-        results.sort_by(|a, b| b.similarity.partial_cmp(&a.similarity).unwrap());
+        let mut results: Vec<HybridResult> = deduplicated.into_values().collect();
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
         results.truncate(limit);
 
         Ok(results)
@@ -222,9 +390,10 @@ impl Database {
 
         let mut stmt = conn.prepare(
             r#"
-            SELECT c.id, c.item_id, c.chunk_index, c.content, c.start_time, c.end_time
+            SELECT c.id, c.item_id, c.chunk_index, c.content, c.start_time, c.end_time,
+                   c.start_page, c.end_page
             FROM chunks c
-            LEFT JOIN embeddings e ON e.chunk_id = c.id
+            LEFT JOIN embeddings e ON e.chunk_id = COALESCE(c.duplicate_of, c.id)
             WHERE e.chunk_id IS NULL
             ORDER BY c.item_id, c.chunk_index
             LIMIT ?1
@@ -237,9 +406,11 @@ impl Database {
                     id: row.get(0)?,
                     item_id: row.get(1)?,
                     chunk_index: row.get(2)?,
-                    content: row.get(3)?,
+                    content: chunk_content(row, 3)?,
                     start_time: row.get(4)?,
                     end_time: row.get(5)?,
+                    start_page: row.get(6)?,
+                    end_page: row.get(7)?,
                 })
             })?
             .filter_map(|r| r.ok())
@@ -248,19 +419,89 @@ impl Database {
         Ok(chunks)
     }
 
-    /// Get embedding statistics: (embedded_count, total_count).
+    /// Get an item's chunks that don't have embeddings yet.
+    pub fn get_unembedded_chunks_by_item(&self, item_id: &ItemId) -> DbResult<Vec<Chunk>> {
+        let conn = self.conn()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT c.id, c.item_id, c.chunk_index, c.content, c.start_time, c.end_time,
+                   c.start_page, c.end_page
+            FROM chunks c
+            LEFT JOIN embeddings e ON e.chunk_id = COALESCE(c.duplicate_of, c.id)
+            WHERE c.item_id = ?1 AND e.chunk_id IS NULL
+            ORDER BY c.chunk_index
+            "#,
+        )?;
+
+        let chunks = stmt.query_map(params![item_id], |row| {
+            Ok(Chunk {
+                id: row.get(0)?,
+                item_id: row.get(1)?,
+                chunk_index: row.get(2)?,
+                content: chunk_content(row, 3)?,
+                start_time: row.get(4)?,
+                end_time: row.get(5)?,
+                start_page: row.get(6)?,
+                end_page: row.get(7)?,
+            })
+        })?;
+
+        chunks.collect::<Result<Vec<_>, _>>().map_err(DbError::from)
+    }
+
+    /// Get embedding statistics: (embedded_count, total_count). Chunks
+    /// sharing another's embedding count as embedded.
     pub fn embedding_stats(&self) -> DbResult<(i64, i64)> {
         let conn = self.conn()?;
 
         let total: i64 =
             conn.query_row("SELECT COUNT(*) FROM chunks", [], |row| row.get(0))?;
 
-        let embedded: i64 =
-            conn.query_row("SELECT COUNT(*) FROM embeddings", [], |row| row.get(0))?;
+        let embedded: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM chunks c JOIN embeddings e ON e.chunk_id = COALESCE(c.duplicate_of, c.id)",
+            [],
+            |row| row.get(0),
+        )?;
 
         Ok((embedded, total))
     }
 
+    /// Compute the centroid of every embedded item's chunk vectors, leaving
+    /// out archived items.
+    pub fn item_centroids(&self) -> DbResult<HashMap<ItemId, Vec<f32>>> {
+        let conn = self.conn()?;
+
+        let mut stmt = conn.prepare(
+            r#"
+            SELECT c.item_id, e.vector, e.dimensions
+            FROM chunks c
+            JOIN embeddings e ON e.chunk_id = COALESCE(c.duplicate_of, c.id)
+            JOIN items i ON i.id = c.item_id
+            WHERE i.archived_at IS NULL
+            ORDER BY c.item_id
+            "#,
+        )?;
+
+        let mut vectors_by_item: HashMap<ItemId, Vec<Vec<f32>>> = HashMap::new();
+        let rows = stmt.query_map([], |row| {
+            let item_id: ItemId = row.get(0)?;
+            let vector_bytes: Vec<u8> = row.get(1)?;
+            let dimensions: i32 = row.get(2)?;
+            Ok((item_id, bytes_to_vector(&vector_bytes, dimensions)))
+        })?;
+
+        for row_result in rows {
+            let (item_id, vector) = row_result?;
+            vectors_by_item.entry(item_id).or_default().push(vector);
+        }
+
+        Ok(vectors_by_item
+            .into_iter()
+            .filter_map(|(item_id, vectors)| centroid(&vectors).map(|c| (item_id, c)))
+            .collect())
+    }
+
     /// Get all embeddings for vector operations.
     pub fn get_all_embeddings(&self) -> DbResult<Vec<(String, Vec<f32>)>> {
         let conn = self.conn()?;
@@ -329,6 +570,15 @@ mod tests {
         assert_eq!(cosine_similarity(&a, &b), 0.0);
     }
 
+    #[test]
+    fn test_centroid() {
+        let vectors = vec![vec![1.0, 0.0], vec![0.0, 1.0], vec![9.0]];
+        let c = centroid(&vectors).unwrap();
+        assert_eq!(c, vec![0.5, 0.5]);
+
+        assert!(centroid(&[]).is_none());
+    }
+
     #[test]
     fn test_vector_search() {
         let db = Database::open_in_memory().unwrap();
@@ -359,6 +609,90 @@ mod tests {
         assert_eq!(results[0].chunk.id, chunk1.id); // More similar to query
     }
 
+    #[test]
+    fn test_vector_search_filtered() {
+        let db = Database::open_in_memory().unwrap();
+        let tagged = Item::new(ItemType::Note, "Tagged");
+        let in_project = Item::new(ItemType::Note, "In project");
+        let other = Item::new(ItemType::Note, "Other");
+        for (item, vector) in [(&tagged, [1.0, 0.0]), (&in_project, [0.8, 0.2]), (&other, [0.9, 0.1])] {
+            db.create_item(item).unwrap();
+            let chunk = Chunk::new(item.id.clone(), 0, &item.title);
+            db.create_chunk(&chunk).unwrap();
+            db.store_embedding(&chunk.id, &vector, "test-model").unwrap();
+        }
+        db.tag_item(&tagged.id, "rust").unwrap();
+        let project = olal_core::Project::new("olal");
+        db.create_project(&project).unwrap();
+        db.add_item_to_project(&project.id, &in_project.id).unwrap();
+
+        let search = |filter: SearchFilter| -> Vec<String> {
+            db.vector_search_filtered(&[1.0, 0.0], 10, None, &filter)
+                .unwrap()
+                .into_iter()
+                .map(|r| r.item_title)
+                .collect()
+        };
+        assert_eq!(search(SearchFilter::default()).len(), 3);
+        let tag = |name: &str| SearchFilter {
+            tag: Some(name.to_string()),
+            ..Default::default()
+        };
+        assert_eq!(search(tag("rust")), vec!["Tagged"]);
+        assert_eq!(
+            search(SearchFilter {
+                project: Some("olal".to_string()),
+                ..Default::default()
+            }),
+            vec!["In project"]
+        );
+        assert_eq!(
+            search(SearchFilter {
+                item_id: Some(other.id.clone()),
+                ..Default::default()
+            }),
+            vec!["Other"]
+        );
+        // Conditions combine
+        assert!(search(SearchFilter {
+            item_id: Some(other.id.clone()),
+            ..tag("rust")
+        })
+        .is_empty());
+    }
+
+    #[test]
+    fn test_hybrid_search() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Languages");
+        db.create_item(&item).unwrap();
+
+        // Only the first chunk mentions the word; only the second is close in meaning
+        let keyword = Chunk::new(item.id.clone(), 0, "Ownership rules in Rust");
+        let meaning = Chunk::new(item.id.clone(), 1, "Who frees memory and when");
+        let neither = Chunk::new(item.id.clone(), 2, "Baking bread at home");
+        db.create_chunks(&[keyword.clone(), meaning.clone(), neither.clone()]).unwrap();
+        db.store_embedding(&keyword.id, &[0.0, 1.0, 0.0], "test-model").unwrap();
+        db.store_embedding(&meaning.id, &[1.0, 0.0, 0.0], "test-model").unwrap();
+        db.store_embedding(&neither.id, &[0.0, 0.0, 1.0], "test-model").unwrap();
+
+        let query = [0.9, 0.3, 0.0];
+        let results = db.hybrid_search("ownership", &query, 10, 0.6, &SearchFilter::default()).unwrap();
+        assert_eq!(results.len(), 2);
+
+        let by_id = |id: &str| results.iter().find(|r| r.chunk.id == id).unwrap();
+        let keyword_result = by_id(&keyword.id);
+        assert_eq!(keyword_result.fts_score, Some(1.0));
+        assert!(keyword_result.vector_score.is_some());
+        assert_eq!(by_id(&meaning.id).fts_score, None);
+
+        // Leaning on text puts the keyword match first
+        let text_heavy = db.hybrid_search("ownership", &query, 10, 0.1, &SearchFilter::default()).unwrap();
+        assert_eq!(text_heavy[0].chunk.id, keyword.id);
+        let vector_heavy = db.hybrid_search("ownership", &query, 10, 0.9, &SearchFilter::default()).unwrap();
+        assert_eq!(vector_heavy[0].chunk.id, meaning.id);
+    }
+
     #[test]
     fn test_unembedded_chunks() {
         let db = Database::open_in_memory().unwrap();
@@ -404,4 +738,23 @@ mod tests {
         assert_eq!(embedded, 2);
         assert_eq!(total, 3);
     }
+
+    #[test]
+    fn test_unembedded_chunks_by_item() {
+        let db = Database::open_in_memory().unwrap();
+        let item = Item::new(ItemType::Note, "Note");
+        let other = Item::new(ItemType::Note, "Other");
+        db.create_item(&item).unwrap();
+        db.create_item(&other).unwrap();
+
+        let first = Chunk::new(item.id.clone(), 0, "first");
+        let second = Chunk::new(item.id.clone(), 1, "second");
+        db.create_chunks(&[first.clone(), second.clone()]).unwrap();
+        db.create_chunk(&Chunk::new(other.id.clone(), 0, "elsewhere")).unwrap();
+        db.store_embedding(&first.id, &[1.0, 0.0], "test-model").unwrap();
+
+        let pending = db.get_unembedded_chunks_by_item(&item.id).unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, second.id);
+    }
 }
@@ -0,0 +1,50 @@
+/*
+ * olal.h - C ABI for the Olal knowledge base.
+ *
+ * Build with `cargo build -p olal-ffi --release`; link against
+ * target/release/libolal.{dylib,a}.
+ *
+ * Strings returned as `char *` are owned by the caller and must be released
+ * with olal_string_free(). A NULL return means the call failed; read the
+ * message with olal_last_error().
+ */
+
+#ifndef OLAL_H
+#define OLAL_H
+
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct OlalHandle OlalHandle;
+
+/* Open the knowledge base. Pass NULL to use the default database. */
+OlalHandle *olal_open(const char *db_path);
+
+/* Close a handle. NULL is a no-op. */
+void olal_close(OlalHandle *handle);
+
+/* Capture a note. `title` and comma-separated `tags` may be NULL.
+ * Returns the new item ID. */
+char *olal_capture(const OlalHandle *handle, const char *text,
+                   const char *title, const char *tags);
+
+/* Full-text search. Returns a JSON array of items. */
+char *olal_search(const OlalHandle *handle, const char *query, uint32_t limit);
+
+/* Most recent items. Returns a JSON array of items. */
+char *olal_recent(const OlalHandle *handle, uint32_t limit);
+
+/* Free a string returned by this library. NULL is a no-op. */
+void olal_string_free(char *s);
+
+/* Last error on this thread, or NULL. Valid until the next call; do not free. */
+const char *olal_last_error(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* OLAL_H */
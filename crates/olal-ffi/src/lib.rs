@@ -0,0 +1,307 @@
+//! Olal FFI - C ABI bindings for native apps.
+//!
+//! Exposes a small, stable surface (open, capture, search, recent) so a
+//! native companion app can talk to the knowledge base without spawning
+//! the CLI. See `include/olal.h` for the C declarations.
+//!
+//! Conventions:
+//! - Functions that return `*mut c_char` hand ownership to the caller, who
+//!   must release it with [`olal_string_free`]. `NULL` signals an error.
+//! - The last error message for the calling thread is available through
+//!   [`olal_last_error`].
+//! - List results are JSON arrays, so bindings only need a JSON decoder.
+
+use chrono::Utc;
+use olal_config::AppPaths;
+use olal_core::{Chunk, Item, ItemType};
+use olal_db::Database;
+use std::cell::RefCell;
+use std::ffi::{c_char, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Opaque handle to an open knowledge base.
+pub struct OlalHandle {
+    db: Database,
+}
+
+fn set_last_error(message: impl Into<String>) {
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(message).ok());
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|e| *e.borrow_mut() = None);
+}
+
+/// Run `f`, converting errors and panics into a recorded error and `None`.
+fn guard<T>(f: impl FnOnce() -> Result<T, String>) -> Option<T> {
+    clear_last_error();
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(value)) => Some(value),
+        Ok(Err(message)) => {
+            set_last_error(message);
+            None
+        }
+        Err(_) => {
+            set_last_error("internal panic");
+            None
+        }
+    }
+}
+
+/// Borrow an optional C string as `&str`.
+///
+/// # Safety
+/// `s` must be null or a valid NUL-terminated string.
+unsafe fn opt_str<'a>(s: *const c_char) -> Result<Option<&'a str>, String> {
+    if s.is_null() {
+        return Ok(None);
+    }
+    CStr::from_ptr(s)
+        .to_str()
+        .map(Some)
+        .map_err(|_| "string argument is not valid UTF-8".to_string())
+}
+
+/// Borrow a required C string as `&str`.
+///
+/// # Safety
+/// `s` must be null or a valid NUL-terminated string.
+unsafe fn req_str<'a>(s: *const c_char, name: &str) -> Result<&'a str, String> {
+    opt_str(s)?.ok_or_else(|| format!("{} must not be NULL", name))
+}
+
+fn into_c_string(s: String) -> Result<*mut c_char, String> {
+    CString::new(s)
+        .map(CString::into_raw)
+        .map_err(|_| "result contains an interior NUL byte".to_string())
+}
+
+fn items_to_json(items: &[Item]) -> Result<String, String> {
+    let values: Vec<serde_json::Value> = items
+        .iter()
+        .map(|item| {
+            serde_json::json!({
+                "id": item.id,
+                "item_type": item.item_type.as_str(),
+                "title": item.title,
+                "summary": item.summary,
+                "source_path": item.source_path,
+                "created_at": item.created_at.to_rfc3339(),
+            })
+        })
+        .collect();
+    serde_json::to_string(&values).map_err(|e| e.to_string())
+}
+
+fn handle_ref<'a>(handle: *const OlalHandle) -> Result<&'a OlalHandle, String> {
+    // SAFETY: callers pass a pointer obtained from `olal_open` (or NULL).
+    unsafe { handle.as_ref() }.ok_or_else(|| "handle must not be NULL".to_string())
+}
+
+/// Open the knowledge base.
+///
+/// Pass `NULL` for `db_path` to use the default database location (the one
+/// `olal init` creates). Returns `NULL` on failure.
+///
+/// # Safety
+/// `db_path` must be null or a valid NUL-terminated UTF-8 string.
+#[no_mangle]
+pub unsafe extern "C" fn olal_open(db_path: *const c_char) -> *mut OlalHandle {
+    guard(|| {
+        let path = match opt_str(db_path)? {
+            Some(p) => std::path::PathBuf::from(p),
+            None => {
+                AppPaths::new()
+                    .ok_or_else(|| "could not determine application directories".to_string())?
+                    .database_file
+            }
+        };
+        let db = Database::open(&path).map_err(|e| e.to_string())?;
+        Ok(Box::into_raw(Box::new(OlalHandle { db })))
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Close a handle returned by [`olal_open`]. Passing `NULL` is a no-op.
+///
+/// # Safety
+/// `handle` must be null or a pointer from `olal_open` that has not been closed.
+#[no_mangle]
+pub unsafe extern "C" fn olal_close(handle: *mut OlalHandle) {
+    if !handle.is_null() {
+        drop(Box::from_raw(handle));
+    }
+}
+
+/// Capture a quick note. `title` and `tags` (comma-separated) may be `NULL`.
+///
+/// Returns the new item's ID, or `NULL` on failure.
+///
+/// # Safety
+/// `handle` must come from `olal_open`; string arguments must be null or
+/// valid NUL-terminated UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn olal_capture(
+    handle: *const OlalHandle,
+    text: *const c_char,
+    title: *const c_char,
+    tags: *const c_char,
+) -> *mut c_char {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let text = req_str(text, "text")?;
+        let title = opt_str(title)?
+            .map(str::to_string)
+            .unwrap_or_else(|| default_title(text));
+
+        let mut item = Item::new(ItemType::Note, &title);
+        item.processed_at = Some(Utc::now());
+        item.metadata = serde_json::json!({
+            "source": "ffi",
+            "captured_at": Utc::now().to_rfc3339(),
+        });
+
+        handle.db.create_item(&item).map_err(|e| e.to_string())?;
+        handle
+            .db
+            .create_chunks(&[Chunk::new(item.id.clone(), 0, text)])
+            .map_err(|e| e.to_string())?;
+
+        if let Some(tags) = opt_str(tags)? {
+            for tag in tags.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+                handle.db.tag_item(&item.id, tag).map_err(|e| e.to_string())?;
+            }
+        }
+
+        into_c_string(item.id)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Full-text search. Returns a JSON array of items, or `NULL` on failure.
+///
+/// # Safety
+/// `handle` must come from `olal_open`; `query` must be valid NUL-terminated UTF-8.
+#[no_mangle]
+pub unsafe extern "C" fn olal_search(
+    handle: *const OlalHandle,
+    query: *const c_char,
+    limit: u32,
+) -> *mut c_char {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let query = req_str(query, "query")?;
+        let items = handle
+            .db
+            .search_items(query, Some(limit as i64))
+            .map_err(|e| e.to_string())?;
+        into_c_string(items_to_json(&items)?)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Most recent items. Returns a JSON array of items, or `NULL` on failure.
+///
+/// # Safety
+/// `handle` must come from `olal_open`.
+#[no_mangle]
+pub unsafe extern "C" fn olal_recent(handle: *const OlalHandle, limit: u32) -> *mut c_char {
+    guard(|| {
+        let handle = handle_ref(handle)?;
+        let items = handle
+            .db
+            .recent_items(Some(limit as i64))
+            .map_err(|e| e.to_string())?;
+        into_c_string(items_to_json(&items)?)
+    })
+    .unwrap_or(ptr::null_mut())
+}
+
+/// Free a string returned by this library. Passing `NULL` is a no-op.
+///
+/// # Safety
+/// `s` must be null or a string returned by this library that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn olal_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// The last error message on this thread, or `NULL` if the last call succeeded.
+///
+/// The pointer stays valid until the next call into this library on the
+/// same thread; do not free it.
+#[no_mangle]
+pub extern "C" fn olal_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(ptr::null(), |s| s.as_ptr()))
+}
+
+fn default_title(text: &str) -> String {
+    let preview: String = text.chars().take(50).collect();
+    if preview.len() < text.len() {
+        format!("{}...", preview)
+    } else if preview.is_empty() {
+        format!("Note {}", Utc::now().format("%Y-%m-%d %H:%M"))
+    } else {
+        preview
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn take_string(s: *mut c_char) -> String {
+        assert!(!s.is_null());
+        let value = unsafe { CStr::from_ptr(s) }.to_str().unwrap().to_string();
+        unsafe { olal_string_free(s) };
+        value
+    }
+
+    #[test]
+    fn test_capture_and_search() {
+        let dir = tempdir().unwrap();
+        let path = CString::new(dir.path().join("olal.db").to_str().unwrap()).unwrap();
+
+        unsafe {
+            let handle = olal_open(path.as_ptr());
+            assert!(!handle.is_null());
+
+            let text = CString::new("Swift concurrency uses actors").unwrap();
+            let tags = CString::new("swift, apple").unwrap();
+            let id = take_string(olal_capture(handle, text.as_ptr(), ptr::null(), tags.as_ptr()));
+            assert!(!id.is_empty());
+
+            let query = CString::new("actors").unwrap();
+            let json = take_string(olal_search(handle, query.as_ptr(), 10));
+            let results: serde_json::Value = serde_json::from_str(&json).unwrap();
+            assert_eq!(results[0]["id"], id);
+
+            let recent: serde_json::Value =
+                serde_json::from_str(&take_string(olal_recent(handle, 5))).unwrap();
+            assert_eq!(recent.as_array().unwrap().len(), 1);
+
+            olal_close(handle);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            let result = olal_search(ptr::null(), ptr::null(), 10);
+            assert!(result.is_null());
+        }
+        let err = olal_last_error();
+        assert!(!err.is_null());
+        let message = unsafe { CStr::from_ptr(err) }.to_str().unwrap();
+        assert!(message.contains("NULL"));
+    }
+}
@@ -0,0 +1,62 @@
+//! Chunker throughput on large documents.
+//!
+//! Run with `cargo bench -p olal-ingest --bench chunking`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use olal_ingest::{ChunkConfig, Chunker};
+
+const PARAGRAPH: &str = "Ownership is Rust's most unique feature. It enables memory safety \
+guarantees without needing a garbage collector. Each value has a single owner, and the value \
+is dropped when its owner goes out of scope. Borrowing lets code use a value without taking \
+ownership of it, and the borrow checker makes sure references never outlive their data.";
+
+/// A markdown-ish document of about `bytes` bytes.
+fn document(bytes: usize) -> String {
+    let mut doc = String::with_capacity(bytes + PARAGRAPH.len());
+    let mut section = 0;
+    while doc.len() < bytes {
+        if section % 10 == 0 {
+            doc.push_str(&format!("## Section {}\n\n", section / 10));
+        }
+        doc.push_str(PARAGRAPH);
+        doc.push_str("\n\n");
+        section += 1;
+    }
+    doc
+}
+
+fn chunk_text(c: &mut Criterion) {
+    let chunker = Chunker::new(ChunkConfig::default());
+    let item_id = "bench".to_string();
+
+    let mut group = c.benchmark_group("chunk_text");
+    for size in [100 * 1024, 1024 * 1024, 10 * 1024 * 1024] {
+        let doc = document(size);
+        group.throughput(Throughput::Bytes(doc.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(size / 1024), &doc, |b, doc| {
+            b.iter(|| chunker.chunk_text(&item_id, doc))
+        });
+    }
+    group.finish();
+}
+
+fn chunk_reader(c: &mut Criterion) {
+    let chunker = Chunker::new(ChunkConfig::default());
+    let item_id = "bench".to_string();
+    let doc = document(10 * 1024 * 1024);
+
+    let mut group = c.benchmark_group("chunk_reader");
+    group.throughput(Throughput::Bytes(doc.len() as u64));
+    group.sample_size(20);
+    group.bench_function("10240", |b| {
+        b.iter(|| {
+            chunker
+                .chunk_reader(&item_id, doc.as_bytes(), |_| Ok::<_, std::io::Error>(()))
+                .unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, chunk_text, chunk_reader);
+criterion_main!(benches);
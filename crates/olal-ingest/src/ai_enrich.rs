@@ -1,11 +1,27 @@
-//! AI-based enrichment for items (summarization, auto-tagging).
+//! AI-based enrichment for items (summarization, auto-tagging, entity extraction).
 
 use olal_config::Config;
+use olal_core::{AiSuggestion, EntityKind, SuggestionKind};
 use olal_db::Database;
-use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient};
+use crate::error::IngestResult;
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient, OllamaError};
+use serde_json::json;
 use tokio::runtime::Runtime;
 use tracing::{debug, info, warn};
 
+/// Confidence assumed for a suggested tag when the model doesn't give one.
+const DEFAULT_TAG_CONFIDENCE: f32 = 0.5;
+
+/// Content longer than this is truncated before summarization.
+const SUMMARY_INPUT_LIMIT: usize = 4000;
+
+/// Content longer than this is truncated before tagging.
+const TAGS_INPUT_LIMIT: usize = 3000;
+
+/// Content longer than this is truncated before entity extraction.
+const ENTITIES_INPUT_LIMIT: usize = 4000;
+
 /// AI enricher for generating summaries and suggesting tags.
 pub struct AiEnricher {
     client: OllamaClient,
@@ -15,19 +31,17 @@ pub struct AiEnricher {
 
 impl AiEnricher {
     /// Create a new AI enricher from config.
-    pub fn from_config(config: &Config) -> Result<Self, String> {
-        let client = OllamaClient::from_config(&config.ollama)
-            .map_err(|e| format!("Failed to create Ollama client: {}", e))?;
-
-        let rt = Runtime::new().map_err(|e| format!("Failed to create async runtime: {}", e))?;
+    pub fn from_config(config: &Config) -> IngestResult<Self> {
+        let client = OllamaClient::from_config(&config.ollama)?;
+        let rt = Runtime::new()?;
 
         // Check if Ollama is available
         let is_available = rt.block_on(client.is_available());
         if !is_available {
-            return Err(format!(
-                "Ollama is not running at {}",
-                config.ollama.host
-            ));
+            return Err(OllamaError::ServerNotRunning {
+                host: config.ollama.host.clone(),
+            }
+            .into());
         }
 
         Ok(Self {
@@ -40,16 +54,10 @@ impl AiEnricher {
     /// Generate a summary for the given content.
     pub fn generate_summary(&self, content: &str) -> Result<String, String> {
         // Truncate content if too long (aim for ~4000 chars to leave room for prompt)
-        let truncated = if content.len() > 4000 {
-            format!("{}...", &content[..4000])
-        } else {
-            content.to_string()
-        };
+        let truncated = truncate_input(content, SUMMARY_INPUT_LIMIT);
 
-        let prompt = format!(
-            "Summarize the following content in 2-3 concise sentences. Focus on the main topics and key points. Do not include any preamble like 'Here is a summary' - just provide the summary directly.\n\nContent:\n{}",
-            truncated
-        );
+        let prompt = prompts::render(Prompt::Summary, &json!({ "content": truncated }))
+            .map_err(|e| e.to_string())?;
 
         let request = GenerateRequest::new(&self.model, prompt)
             .with_options(GenerateOptions::new().with_temperature(0.3).with_num_predict(200));
@@ -65,20 +73,13 @@ impl AiEnricher {
         Ok(summary)
     }
 
-    /// Suggest tags for the given content.
-    pub fn suggest_tags(&self, content: &str, title: &str) -> Result<Vec<String>, String> {
+    /// Suggest tags for the given content, each with a confidence in 0-1.
+    pub fn suggest_tags(&self, content: &str, title: &str) -> Result<Vec<(String, f32)>, String> {
         // Truncate content if too long
-        let truncated = if content.len() > 3000 {
-            format!("{}...", &content[..3000])
-        } else {
-            content.to_string()
-        };
+        let truncated = truncate_input(content, TAGS_INPUT_LIMIT);
 
-        let prompt = format!(
-            "Based on the following content, suggest 3-5 relevant tags (single words or short phrases) that categorize this content. Return only the tags, one per line, without numbers or bullets.\n\nTitle: {}\n\nContent:\n{}",
-            title,
-            truncated
-        );
+        let prompt = prompts::render(Prompt::Tags, &json!({ "title": title, "content": truncated }))
+            .map_err(|e| e.to_string())?;
 
         let request = GenerateRequest::new(&self.model, prompt)
             .with_options(GenerateOptions::new().with_temperature(0.5).with_num_predict(100));
@@ -88,25 +89,235 @@ impl AiEnricher {
             .block_on(self.client.generate(request))
             .map_err(|e| format!("Failed to suggest tags: {}", e))?;
 
-        // Parse the response into tags
-        let tags: Vec<String> = response
-            .response
-            .lines()
-            .map(|line| {
-                // Clean up the line (remove bullets, numbers, etc.)
-                line.trim()
-                    .trim_start_matches(|c: char| c.is_numeric() || c == '.' || c == '-' || c == '*')
-                    .trim()
-                    .to_lowercase()
-            })
-            .filter(|tag| !tag.is_empty() && tag.len() < 50)
-            .take(5)
-            .collect();
+        let tags = parse_tag_response(&response.response);
 
         debug!("Suggested tags: {:?}", tags);
 
         Ok(tags)
     }
+
+    /// Extract people, organizations and topics mentioned in the content.
+    pub fn extract_entities(
+        &self,
+        content: &str,
+        title: &str,
+    ) -> Result<Vec<(EntityKind, String)>, String> {
+        let truncated = truncate_input(content, ENTITIES_INPUT_LIMIT);
+
+        let prompt = prompts::render(Prompt::Entities, &json!({ "title": title, "content": truncated }))
+            .map_err(|e| e.to_string())?;
+
+        let request = GenerateRequest::new(&self.model, prompt)
+            .with_options(GenerateOptions::new().with_temperature(0.1).with_num_predict(300));
+
+        let response = self
+            .rt
+            .block_on(self.client.generate(request))
+            .map_err(|e| format!("Failed to extract entities: {}", e))?;
+
+        let entities = parse_entity_response(&response.response);
+        debug!("Extracted entities: {:?}", entities);
+
+        Ok(entities)
+    }
+}
+
+/// Parse `tag | confidence` lines from a tag suggestion response.
+///
+/// Bullets and numbering are stripped. Lines without a confidence get
+/// [`DEFAULT_TAG_CONFIDENCE`]; at most five tags are returned.
+pub fn parse_tag_response(response: &str) -> Vec<(String, f32)> {
+    response
+        .lines()
+        .map(|line| {
+            let (tag, confidence) = match line.rsplit_once(['|', ':']) {
+                Some((tag, conf)) => match conf.trim().parse::<f32>() {
+                    Ok(c) => (tag, c.clamp(0.0, 1.0)),
+                    Err(_) => (line, DEFAULT_TAG_CONFIDENCE),
+                },
+                None => (line, DEFAULT_TAG_CONFIDENCE),
+            };
+            // Clean up the tag (remove bullets, numbers, etc.)
+            let tag = tag
+                .trim()
+                .trim_start_matches(|c: char| c.is_numeric() || c == '.' || c == '-' || c == '*')
+                .trim()
+                .to_lowercase();
+            (tag, confidence)
+        })
+        .filter(|(tag, _)| !tag.is_empty() && tag.len() < 50)
+        .take(5)
+        .collect()
+}
+
+/// `content` cut to at most `limit` bytes, on a character boundary, with
+/// "..." marking the cut.
+fn truncate_input(content: &str, limit: usize) -> String {
+    if content.len() > limit {
+        format!("{}...", &content[..content.floor_char_boundary(limit)])
+    } else {
+        content.to_string()
+    }
+}
+
+/// Heuristic confidence (0-1) for a generated summary.
+///
+/// Models don't report confidence for free text, so this penalizes the
+/// usual failure modes: refusals and preambles, very short output, and
+/// summaries of truncated input.
+pub fn summary_confidence(content: &str, summary: &str) -> f32 {
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return 0.0;
+    }
+
+    let lower = summary.to_lowercase();
+    let suspicious = [
+        "i cannot",
+        "i can't",
+        "as an ai",
+        "here is a summary",
+        "here's a summary",
+        "no content",
+    ];
+    if suspicious.iter().any(|p| lower.contains(p)) {
+        return 0.3;
+    }
+
+    let mut confidence: f32 = 0.9;
+    if summary.len() < 40 {
+        confidence -= 0.4;
+    }
+    if content.len() > SUMMARY_INPUT_LIMIT {
+        confidence -= 0.2;
+    }
+    confidence.max(0.0)
+}
+
+/// Apply a generated summary, or queue it for review if its confidence is
+/// below the configured threshold. Returns whether it was applied.
+pub fn apply_summary(
+    db: &Database,
+    item: &mut olal_core::Item,
+    content: &str,
+    summary: String,
+    config: &Config,
+) -> Result<bool, String> {
+    let confidence = summary_confidence(content, &summary);
+    if config
+        .review
+        .needs_review(confidence, config.review.summary_threshold)
+    {
+        let suggestion = AiSuggestion::new(item.id.clone(), SuggestionKind::Summary, summary, confidence);
+        db.create_suggestion(&suggestion)
+            .map_err(|e| format!("Failed to queue summary: {}", e))?;
+        return Ok(false);
+    }
+
+    item.summary = Some(summary);
+    db.update_item(item)
+        .map_err(|e| format!("Failed to save summary: {}", e))?;
+    Ok(true)
+}
+
+/// Apply suggested tags, queueing low-confidence ones for review.
+///
+/// Returns `(applied, queued)`.
+pub fn apply_tags(
+    db: &Database,
+    item: &olal_core::Item,
+    tags: &[(String, f32)],
+    config: &Config,
+) -> Result<(usize, usize), String> {
+    let mut applied = 0;
+    let mut queued = 0;
+
+    for (tag_name, confidence) in tags {
+        if config
+            .review
+            .needs_review(*confidence, config.review.tag_threshold)
+        {
+            let suggestion =
+                AiSuggestion::new(item.id.clone(), SuggestionKind::Tag, tag_name, *confidence);
+            if db
+                .create_suggestion(&suggestion)
+                .map_err(|e| format!("Failed to queue tag '{}': {}", tag_name, e))?
+            {
+                queued += 1;
+            }
+        } else {
+            db.tag_item(&item.id, tag_name)
+                .map_err(|e| format!("Failed to add tag '{}': {}", tag_name, e))?;
+            debug!("Added tag '{}' to item {}", tag_name, item.id);
+            applied += 1;
+        }
+    }
+
+    Ok((applied, queued))
+}
+
+/// Parse `KIND: Name` lines from an entity extraction response.
+///
+/// Unknown kinds and malformed lines are skipped; duplicates are removed.
+pub fn parse_entity_response(response: &str) -> Vec<(EntityKind, String)> {
+    let mut entities: Vec<(EntityKind, String)> = Vec::new();
+
+    for line in response.lines() {
+        let line = line
+            .trim()
+            .trim_start_matches(|c: char| c.is_numeric() || c == '.' || c == '-' || c == '*')
+            .trim();
+
+        let Some((kind, name)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(kind) = EntityKind::from_str(kind.trim()) else {
+            continue;
+        };
+        let name = name.trim().trim_matches(|c: char| c == '"' || c == '\'' || c == '*');
+        if name.is_empty() || name.len() > 100 {
+            continue;
+        }
+
+        if !entities
+            .iter()
+            .any(|(k, n)| *k == kind && n.eq_ignore_ascii_case(name))
+        {
+            entities.push((kind, name.to_string()));
+        }
+    }
+
+    entities
+}
+
+/// Count case-insensitive occurrences of `name` in `content` (at least 1).
+fn count_mentions(content: &str, name: &str) -> i64 {
+    let haystack = content.to_lowercase();
+    let needle = name.to_lowercase();
+    (haystack.matches(&needle).count() as i64).max(1)
+}
+
+/// Store extracted entities for an item, replacing any previous extraction.
+///
+/// Returns the number of entities recorded.
+pub fn store_entities(
+    db: &Database,
+    item: &olal_core::Item,
+    content: &str,
+    entities: &[(EntityKind, String)],
+) -> Result<usize, String> {
+    db.clear_item_entities(&item.id)
+        .map_err(|e| format!("Failed to clear entities: {}", e))?;
+
+    for (kind, name) in entities {
+        let entity = db
+            .get_or_create_entity(name, *kind)
+            .map_err(|e| format!("Failed to save entity '{}': {}", name, e))?;
+        db.add_entity_to_item(&item.id, &entity.id, count_mentions(content, name))
+            .map_err(|e| format!("Failed to link entity '{}': {}", name, e))?;
+    }
+
+    Ok(entities.len())
 }
 
 /// Enrich an item with AI-generated summary and tags.
@@ -114,6 +325,7 @@ impl AiEnricher {
 /// This function will:
 /// 1. Generate a summary if `config.processing.generate_summary` is true
 /// 2. Suggest and apply tags if `config.processing.auto_tag` is true
+/// 3. Extract entities if `config.processing.extract_entities` is true
 ///
 /// Errors are logged but don't cause the function to fail (graceful degradation).
 pub fn enrich_item(
@@ -133,7 +345,7 @@ pub fn enrich_item(
         Ok(e) => e,
         Err(e) => {
             warn!("AI enrichment unavailable: {}", e);
-            return Err(e);
+            return Err(e.to_string());
         }
     };
 
@@ -141,15 +353,12 @@ pub fn enrich_item(
 
     // Generate summary if enabled and not already present
     if config.processing.generate_summary && item.summary.is_none() {
-        match enricher.generate_summary(content) {
-            Ok(summary) => {
-                item.summary = Some(summary);
-                if let Err(e) = db.update_item(item) {
-                    warn!("Failed to save summary: {}", e);
-                } else {
-                    info!("Generated summary for item {}", item.id);
-                }
-            }
+        match enricher
+            .generate_summary(content)
+            .and_then(|summary| apply_summary(db, item, content, summary, config))
+        {
+            Ok(true) => info!("Generated summary for item {}", item.id),
+            Ok(false) => info!("Queued summary for item {} for review", item.id),
             Err(e) => {
                 warn!("Failed to generate summary: {}", e);
             }
@@ -158,23 +367,31 @@ pub fn enrich_item(
 
     // Auto-tag if enabled
     if config.processing.auto_tag {
-        match enricher.suggest_tags(content, &item.title) {
-            Ok(tags) => {
-                for tag_name in tags {
-                    if let Err(e) = db.tag_item(&item.id, &tag_name) {
-                        warn!("Failed to add tag '{}': {}", tag_name, e);
-                    } else {
-                        debug!("Added tag '{}' to item {}", tag_name, item.id);
-                    }
-                }
-                info!("Auto-tagged item {}", item.id);
-            }
+        match enricher
+            .suggest_tags(content, &item.title)
+            .and_then(|tags| apply_tags(db, item, &tags, config))
+        {
+            Ok((applied, queued)) => info!(
+                "Auto-tagged item {} ({} applied, {} queued for review)",
+                item.id, applied, queued
+            ),
             Err(e) => {
                 warn!("Failed to suggest tags: {}", e);
             }
         }
     }
 
+    // Extract entities if enabled
+    if config.processing.extract_entities {
+        match enricher
+            .extract_entities(content, &item.title)
+            .and_then(|entities| store_entities(db, item, content, &entities))
+        {
+            Ok(count) => info!("Extracted {} entities for item {}", count, item.id),
+            Err(e) => warn!("Failed to extract entities: {}", e),
+        }
+    }
+
     Ok(())
 }
 
@@ -182,22 +399,95 @@ pub fn enrich_item(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_truncate_input() {
+        assert_eq!(truncate_input("short", ENTITIES_INPUT_LIMIT), "short");
+
+        // Byte 4000 falls inside a two-byte character
+        let content = format!("x{}", "é".repeat(ENTITIES_INPUT_LIMIT));
+        let truncated = truncate_input(&content, ENTITIES_INPUT_LIMIT);
+        assert!(truncated.ends_with("é..."));
+        assert_eq!(truncated.len(), ENTITIES_INPUT_LIMIT - 1 + 3);
+
+        let emoji = "🦀".repeat(ENTITIES_INPUT_LIMIT);
+        assert!(truncate_input(&emoji, ENTITIES_INPUT_LIMIT).len() <= ENTITIES_INPUT_LIMIT + 3);
+    }
+
     #[test]
     fn test_tag_parsing() {
         // Test that tag parsing handles various formats
         let response = "1. rust\n- programming\n* software\ncoding\n  technology  ";
-        let tags: Vec<String> = response
-            .lines()
-            .map(|line| {
-                line.trim()
-                    .trim_start_matches(|c: char| c.is_numeric() || c == '.' || c == '-' || c == '*')
-                    .trim()
-                    .to_lowercase()
-            })
-            .filter(|tag| !tag.is_empty() && tag.len() < 50)
-            .take(5)
+        let tags: Vec<String> = parse_tag_response(response)
+            .into_iter()
+            .map(|(tag, _)| tag)
             .collect();
 
         assert_eq!(tags, vec!["rust", "programming", "software", "coding", "technology"]);
     }
+
+    #[test]
+    fn test_tag_confidence_parsing() {
+        let response = "Rust | 0.95\n- web dev: 0.4\nsystems | high\nc++ | 1.7";
+        assert_eq!(
+            parse_tag_response(response),
+            vec![
+                ("rust".to_string(), 0.95),
+                ("web dev".to_string(), 0.4),
+                ("systems | high".to_string(), DEFAULT_TAG_CONFIDENCE),
+                ("c++".to_string(), 1.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_low_confidence_goes_to_review() {
+        use olal_core::{Item, ItemType, SuggestionStatus};
+
+        let db = Database::open_in_memory().unwrap();
+        let config = Config::default();
+        let mut item = Item::new(ItemType::Note, "Draft");
+        db.create_item(&item).unwrap();
+
+        let tags = vec![("rust".to_string(), 0.9), ("cooking".to_string(), 0.2)];
+        assert_eq!(apply_tags(&db, &item, &tags, &config).unwrap(), (1, 1));
+        assert_eq!(db.get_item_tags(&item.id).unwrap().len(), 1);
+
+        let applied = apply_summary(&db, &mut item, "short", "I cannot summarize this.".into(), &config)
+            .unwrap();
+        assert!(!applied);
+        assert!(db.get_item(&item.id).unwrap().summary.is_none());
+
+        let pending = db
+            .list_suggestions(Some(SuggestionStatus::PendingReview), None)
+            .unwrap();
+        assert_eq!(pending.len(), 2);
+
+        // With review disabled everything is applied
+        let mut config = config;
+        config.review.enabled = false;
+        let tags = vec![("baking".to_string(), 0.1)];
+        assert_eq!(apply_tags(&db, &item, &tags, &config).unwrap(), (1, 0));
+    }
+
+    #[test]
+    fn test_entity_parsing() {
+        let response = "PERSON: Ada Lovelace\n- ORGANIZATION: Analytical Society\nTOPIC: computing\nperson: ada lovelace\nPLACE: London\nnonsense line\nTOPIC:   ";
+        let entities = parse_entity_response(response);
+
+        assert_eq!(
+            entities,
+            vec![
+                (EntityKind::Person, "Ada Lovelace".to_string()),
+                (EntityKind::Organization, "Analytical Society".to_string()),
+                (EntityKind::Topic, "computing".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_count_mentions() {
+        let content = "Ada wrote notes. ADA also designed programs.";
+        assert_eq!(count_mentions(content, "ada"), 2);
+        assert_eq!(count_mentions(content, "Babbage"), 1);
+    }
 }
@@ -0,0 +1,214 @@
+//! Unpacking `.zip` and `.tar.gz` archives for ingestion.
+
+use crate::error::{IngestError, IngestResult};
+use olal_core::ItemType;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Component, Path, PathBuf};
+
+/// A supported file unpacked from an archive.
+#[derive(Debug, Clone)]
+pub struct ArchiveMember {
+    /// The archive it came from.
+    pub archive: PathBuf,
+    /// Its path inside the archive.
+    pub entry: String,
+    /// Where it was unpacked.
+    pub path: PathBuf,
+}
+
+impl ArchiveMember {
+    /// Source path recorded for the member's item: `<archive>!/<entry>`.
+    pub fn source_path(&self) -> String {
+        format!("{}!/{}", self.archive.display(), self.entry)
+    }
+
+    /// Provenance metadata: `{"path": <archive>, "entry": <entry>}`.
+    pub fn metadata(&self) -> serde_json::Value {
+        serde_json::json!({
+            "path": self.archive.display().to_string(),
+            "entry": self.entry,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Zip,
+    Tar,
+    TarGz,
+}
+
+fn format(path: &Path) -> Option<Format> {
+    let name = path.file_name()?.to_str()?.to_lowercase();
+    if name.ends_with(".zip") {
+        Some(Format::Zip)
+    } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Some(Format::TarGz)
+    } else if name.ends_with(".tar") {
+        Some(Format::Tar)
+    } else {
+        None
+    }
+}
+
+/// Whether `path` is an archive that can be ingested (.zip, .tar, .tar.gz, .tgz).
+pub fn is_archive(path: &Path) -> bool {
+    format(path).is_some()
+}
+
+/// Whether an archive entry should be ingested: a supported file type,
+/// with no hidden components (which also skips `__MACOSX/._*` forks).
+fn wanted(entry: &Path) -> bool {
+    let hidden = entry.components().any(|c| match c {
+        Component::Normal(name) => name.to_str().is_none_or(|n| n.starts_with('.') || n == "__MACOSX"),
+        _ => false,
+    });
+    !hidden
+        && entry
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(ItemType::from_extension)
+            .is_some()
+}
+
+/// Unpack the supported files in `archive` into `dest`. Entries that would
+/// land outside `dest` are skipped.
+pub fn unpack(archive: &Path, dest: &Path) -> IngestResult<Vec<ArchiveMember>> {
+    let format = format(archive).ok_or_else(|| {
+        IngestError::UnsupportedFileType(
+            archive.file_name().and_then(|n| n.to_str()).unwrap_or("unknown").to_string(),
+        )
+    })?;
+    let archive = archive.canonicalize()?;
+    let file = BufReader::new(File::open(&archive)?);
+    let entries = match format {
+        Format::Zip => unpack_zip(file, dest).map_err(|e| parse_error(&archive, e))?,
+        Format::Tar => unpack_tar(tar::Archive::new(file), dest)?,
+        Format::TarGz => unpack_tar(tar::Archive::new(flate2::read::GzDecoder::new(file)), dest)?,
+    };
+
+    Ok(entries
+        .into_iter()
+        .map(|entry| ArchiveMember {
+            archive: archive.clone(),
+            path: dest.join(&entry),
+            entry: entry.to_string_lossy().replace('\\', "/"),
+        })
+        .collect())
+}
+
+/// Paths of the supported files in `archive`, as [`unpack`] would find them.
+pub fn list(archive: &Path) -> IngestResult<Vec<String>> {
+    let dir = tempfile::tempdir()?;
+    Ok(unpack(archive, dir.path())?.into_iter().map(|m| m.entry).collect())
+}
+
+fn parse_error(archive: &Path, e: impl ToString) -> IngestError {
+    IngestError::ParseError {
+        path: archive.to_path_buf(),
+        message: e.to_string(),
+    }
+}
+
+fn unpack_zip(file: BufReader<File>, dest: &Path) -> zip::result::ZipResult<Vec<PathBuf>> {
+    let mut zip = zip::ZipArchive::new(file)?;
+    let mut entries = Vec::new();
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        let Some(entry) = file.enclosed_name() else {
+            continue;
+        };
+        if file.is_dir() || !wanted(&entry) {
+            continue;
+        }
+        let target = dest.join(&entry);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::io::copy(&mut file, &mut File::create(&target)?)?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+fn unpack_tar<R: std::io::Read>(mut tar: tar::Archive<R>, dest: &Path) -> IngestResult<Vec<PathBuf>> {
+    let mut entries = Vec::new();
+    for entry in tar.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?.into_owned();
+        if !wanted(&path) {
+            continue;
+        }
+        // `unpack_in` refuses paths that escape `dest`
+        if entry.unpack_in(dest)? {
+            entries.push(path.components().filter(|c| matches!(c, Component::Normal(_))).collect());
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_is_archive() {
+        assert!(is_archive(Path::new("notes.zip")));
+        assert!(is_archive(Path::new("notes.TAR.GZ")));
+        assert!(is_archive(Path::new("notes.tgz")));
+        assert!(!is_archive(Path::new("notes.gz")));
+        assert!(!is_archive(Path::new("notes.md")));
+    }
+
+    #[test]
+    fn test_unpack_zip() {
+        let dir = tempdir().unwrap();
+        let archive = dir.path().join("notes.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = zip::write::SimpleFileOptions::default();
+        for (name, content) in [
+            ("docs/idea.md", "# Idea"),
+            ("docs/.hidden.md", "secret"),
+            ("__MACOSX/docs/._idea.md", "fork"),
+            ("data.bin", "binary"),
+        ] {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let dest = tempdir().unwrap();
+        let members = unpack(&archive, dest.path()).unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].entry, "docs/idea.md");
+        assert_eq!(std::fs::read_to_string(&members[0].path).unwrap(), "# Idea");
+        assert!(members[0].source_path().ends_with("notes.zip!/docs/idea.md"));
+    }
+
+    #[test]
+    fn test_unpack_tar_gz() {
+        let dir = tempdir().unwrap();
+        let archive = dir.path().join("notes.tar.gz");
+        let gz = flate2::write::GzEncoder::new(File::create(&archive).unwrap(), flate2::Compression::default());
+        let mut tar = tar::Builder::new(gz);
+        let content = b"fn main() {}";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(content.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, "src/main.rs", &content[..]).unwrap();
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let dest = tempdir().unwrap();
+        let members = unpack(&archive, dest.path()).unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].entry, "src/main.rs");
+        assert_eq!(members[0].metadata()["entry"], "src/main.rs");
+    }
+}
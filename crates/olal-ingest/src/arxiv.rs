@@ -0,0 +1,253 @@
+//! Looking up arXiv papers and downloading their PDFs.
+//!
+//! Metadata comes from the arXiv API's Atom feed
+//! (`export.arxiv.org/api/query?id_list=<id>`).
+
+use crate::bookmarks::attribute;
+use crate::error::{IngestError, IngestResult};
+use crate::web::{decode_entities, PageFetcher};
+
+/// arXiv API endpoint for looking up papers by ID.
+const API_URL: &str = "https://export.arxiv.org/api/query";
+
+/// An arXiv paper's metadata.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Paper {
+    /// ID without a version, e.g. `2405.12345` or `hep-th/9901001`.
+    pub id: String,
+    /// Version fetched, e.g. `v2`.
+    pub version: Option<String>,
+    pub title: String,
+    pub authors: Vec<String>,
+    pub abstract_text: String,
+    /// Categories, primary first, e.g. `["cs.CL", "cs.LG"]`.
+    pub categories: Vec<String>,
+    /// When the first version was published (RFC 3339).
+    pub published: Option<String>,
+}
+
+impl Paper {
+    /// The paper's abstract page, recorded as its item's source path.
+    pub fn abs_url(&self) -> String {
+        format!("https://arxiv.org/abs/{}", self.id)
+    }
+
+    /// The PDF of the fetched version.
+    pub fn pdf_url(&self) -> String {
+        format!("https://arxiv.org/pdf/{}{}", self.id, self.version.as_deref().unwrap_or_default())
+    }
+
+    /// Metadata recorded under `arxiv` on the paper's item.
+    pub fn metadata(&self) -> serde_json::Value {
+        serde_json::json!({
+            "id": self.id,
+            "version": self.version,
+            "url": self.abs_url(),
+            "authors": self.authors,
+            "abstract": self.abstract_text,
+            "categories": self.categories,
+            "published": self.published,
+        })
+    }
+}
+
+/// Normalize an arXiv ID as people paste it (`arXiv:2405.12345v2`, an
+/// abs or pdf URL) to the ID and its version, if any.
+pub fn parse_id(input: &str) -> Option<(String, Option<String>)> {
+    let mut id = input.trim().trim_end_matches('/');
+    for prefix in ["https://", "http://", "www.", "export.", "arxiv.org/abs/", "arxiv.org/pdf/"] {
+        id = id.strip_prefix(prefix).unwrap_or(id);
+    }
+    if id.get(..6).is_some_and(|prefix| prefix.eq_ignore_ascii_case("arxiv:")) {
+        id = &id[6..];
+    }
+    let id = id.strip_suffix(".pdf").unwrap_or(id);
+
+    let (base, version) = match id.rfind('v') {
+        Some(i) if i > 0 && id[i + 1..].parse::<u32>().is_ok() && id[..i].ends_with(|c: char| c.is_ascii_digit()) => {
+            (&id[..i], Some(id[i..].to_string()))
+        }
+        _ => (id, None),
+    };
+    // New style `YYMM.NNNNN`, or old style `archive/YYMMNNN`
+    let number = base.rsplit('/').next().unwrap_or_default();
+    let valid = match base.split_once('/') {
+        Some((archive, _)) => {
+            !archive.is_empty()
+                && archive.chars().all(|c| c.is_ascii_alphabetic() || c == '-' || c == '.')
+                && number.len() == 7
+                && number.chars().all(|c| c.is_ascii_digit())
+        }
+        None => match number.split_once('.') {
+            Some((month, serial)) => {
+                month.len() == 4
+                    && (4..=5).contains(&serial.len())
+                    && month.chars().chain(serial.chars()).all(|c| c.is_ascii_digit())
+            }
+            None => false,
+        },
+    };
+    valid.then(|| (base.to_string(), version))
+}
+
+/// Look up a paper's metadata.
+pub fn fetch(fetcher: &PageFetcher, input: &str) -> IngestResult<Paper> {
+    let (id, version) = parse_id(input)
+        .ok_or_else(|| IngestError::ProcessingError(format!("Not an arXiv ID: {}", input)))?;
+    let requested = format!("{}{}", id, version.as_deref().unwrap_or_default());
+    let feed = fetcher.download(&format!("{}?id_list={}", API_URL, requested))?;
+    parse_feed(&String::from_utf8_lossy(&feed))
+        .ok_or_else(|| IngestError::ProcessingError(format!("arXiv paper not found: {}", requested)))
+}
+
+/// The paper in an API response, or `None` if it has no (valid) entry.
+pub fn parse_feed(xml: &str) -> Option<Paper> {
+    let entry = element(xml, "entry")?;
+    // Unknown IDs come back as an entry whose ID is an error URL
+    let (id, version) = parse_id(&text(entry, "id")?)?;
+
+    let authors = elements(entry, "author")
+        .into_iter()
+        .filter_map(|author| text(author, "name"))
+        .collect();
+    let mut categories: Vec<String> = Vec::new();
+    for tag in ["arxiv:primary_category", "category"] {
+        for open in open_tags(entry, tag) {
+            if let Some(term) = attribute(open, "term").filter(|t| !categories.contains(t)) {
+                categories.push(term);
+            }
+        }
+    }
+
+    Some(Paper {
+        id,
+        version,
+        title: text(entry, "title")?,
+        authors,
+        abstract_text: text(entry, "summary").unwrap_or_default(),
+        categories,
+        published: text(entry, "published"),
+    })
+}
+
+/// Text of the first `tag` element in `xml`, entities decoded and
+/// whitespace collapsed.
+fn text(xml: &str, tag: &str) -> Option<String> {
+    let text = decode_entities(element(xml, tag)?);
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Content of the first `tag` element in `xml`.
+fn element<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    elements(xml, tag).into_iter().next()
+}
+
+/// Contents of the `tag` elements in `xml`.
+fn elements<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let close = format!("</{}>", tag);
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(open) = find_open(rest, tag) {
+        let Some(start) = rest[open..].find('>').map(|i| open + i + 1) else {
+            break;
+        };
+        let Some(end) = rest[start..].find(&close).map(|i| start + i) else {
+            break;
+        };
+        found.push(&rest[start..end]);
+        rest = &rest[end + close.len()..];
+    }
+    found
+}
+
+/// The `<tag ...>` openings in `xml`, without the angle brackets.
+fn open_tags<'a>(xml: &'a str, tag: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = xml;
+    while let Some(open) = find_open(rest, tag) {
+        let Some(end) = rest[open..].find('>').map(|i| open + i) else {
+            break;
+        };
+        found.push(&rest[open + 1..end]);
+        rest = &rest[end..];
+    }
+    found
+}
+
+/// Index of the next `<tag` followed by whitespace, `>` or `/`.
+fn find_open(xml: &str, tag: &str) -> Option<usize> {
+    let needle = format!("<{}", tag);
+    let mut from = 0;
+    while let Some(i) = xml[from..].find(&needle).map(|i| from + i) {
+        let after = xml[i + needle.len()..].chars().next();
+        if after.is_some_and(|c| c.is_whitespace() || c == '>' || c == '/') {
+            return Some(i);
+        }
+        from = i + needle.len();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title type="html">ArXiv Query: id_list=2405.12345</title>
+  <entry>
+    <id>http://arxiv.org/abs/2405.12345v2</id>
+    <updated>2024-06-01T00:00:00Z</updated>
+    <published>2024-05-20T17:59:59Z</published>
+    <title>Attention Is Still
+      All You Need</title>
+    <summary>  We revisit attention &amp; show it
+      still works.
+    </summary>
+    <author><name>Ada Lovelace</name></author>
+    <author><name>Alan Turing</name><arxiv:affiliation>Bletchley</arxiv:affiliation></author>
+    <link href="http://arxiv.org/abs/2405.12345v2" rel="alternate" type="text/html"/>
+    <link title="pdf" href="http://arxiv.org/pdf/2405.12345v2" rel="related" type="application/pdf"/>
+    <arxiv:primary_category xmlns:arxiv="http://arxiv.org/schemas/atom" term="cs.CL" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="cs.CL" scheme="http://arxiv.org/schemas/atom"/>
+    <category term="cs.LG" scheme="http://arxiv.org/schemas/atom"/>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_parse_feed() {
+        let paper = parse_feed(FEED).unwrap();
+        assert_eq!(paper.id, "2405.12345");
+        assert_eq!(paper.version.as_deref(), Some("v2"));
+        assert_eq!(paper.title, "Attention Is Still All You Need");
+        assert_eq!(paper.abstract_text, "We revisit attention & show it still works.");
+        assert_eq!(paper.authors, vec!["Ada Lovelace", "Alan Turing"]);
+        assert_eq!(paper.categories, vec!["cs.CL", "cs.LG"]);
+        assert_eq!(paper.published.as_deref(), Some("2024-05-20T17:59:59Z"));
+        assert_eq!(paper.pdf_url(), "https://arxiv.org/pdf/2405.12345v2");
+        assert_eq!(paper.metadata()["url"], "https://arxiv.org/abs/2405.12345");
+    }
+
+    #[test]
+    fn test_parse_feed_error() {
+        let feed = r#"<feed><entry><id>http://arxiv.org/api/errors#incorrect_id_format_for_1234</id>
+            <title>Error</title><summary>incorrect id format for 1234</summary></entry></feed>"#;
+        assert!(parse_feed(feed).is_none());
+        assert!(parse_feed("<feed></feed>").is_none());
+    }
+
+    #[test]
+    fn test_parse_id() {
+        let id = |s: &str| parse_id(s).map(|(id, v)| format!("{}{}", id, v.unwrap_or_default()));
+        assert_eq!(id("2405.12345").as_deref(), Some("2405.12345"));
+        assert_eq!(id("arXiv:2405.12345v3").as_deref(), Some("2405.12345v3"));
+        assert_eq!(id("https://arxiv.org/abs/1706.03762").as_deref(), Some("1706.03762"));
+        assert_eq!(id("https://arxiv.org/pdf/1706.03762v7.pdf").as_deref(), Some("1706.03762v7"));
+        assert_eq!(id("hep-th/9901001v1").as_deref(), Some("hep-th/9901001v1"));
+        assert_eq!(id("math.GT/0309136").as_deref(), Some("math.GT/0309136"));
+        assert_eq!(id("notes.md"), None);
+        assert_eq!(id("12345"), None);
+        assert_eq!(id("aéééé"), None);
+    }
+}
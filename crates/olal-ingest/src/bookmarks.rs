@@ -0,0 +1,318 @@
+//! Parsing exported browser bookmarks.
+//!
+//! Supports the Netscape bookmark HTML that every browser exports, Chrome's
+//! `Bookmarks` JSON file and Firefox's JSON backups. Folders are kept as a
+//! path, minus the browser's own roots ("Bookmarks bar", "Other
+//! bookmarks", ...).
+
+use crate::error::{IngestError, IngestResult};
+use crate::web::decode_entities;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::path::Path;
+
+/// Folders every browser creates; they say nothing about the bookmark.
+const ROOT_FOLDERS: &[&str] = &[
+    "bookmarks",
+    "bookmarks bar",
+    "bookmarks toolbar",
+    "bookmarks menu",
+    "other bookmarks",
+    "mobile bookmarks",
+    "favorites bar",
+    "unsorted bookmarks",
+];
+
+/// Microseconds between 1601-01-01 (Chrome's epoch) and 1970-01-01.
+const CHROME_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+/// A bookmarked page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: String,
+    /// Folder path from the top, e.g. `["Dev", "Rust"]`.
+    pub folders: Vec<String>,
+    /// Tags besides the folder's, e.g. from a read-later service.
+    pub tags: Vec<String>,
+    /// When it was bookmarked (or saved).
+    pub added_at: Option<DateTime<Utc>>,
+}
+
+impl Bookmark {
+    /// Tag for the bookmark's folder (`Dev/Rust`), if it's in one.
+    pub fn folder_tag(&self) -> Option<String> {
+        if self.folders.is_empty() {
+            return None;
+        }
+        // A "/" in a folder name would otherwise start a new tag level
+        let levels: Vec<String> = self.folders.iter().map(|f| f.replace('/', "-")).collect();
+        Some(levels.join("/"))
+    }
+}
+
+/// Parse a bookmarks export, detecting its format from the content.
+pub fn parse_file(path: &Path) -> IngestResult<Vec<Bookmark>> {
+    let content = std::fs::read_to_string(path)?;
+    parse(&content).map_err(|message| IngestError::ParseError {
+        path: path.to_path_buf(),
+        message,
+    })
+}
+
+/// Parse bookmarks from HTML or JSON export `content`.
+pub fn parse(content: &str) -> Result<Vec<Bookmark>, String> {
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    if !trimmed.starts_with('{') {
+        return Ok(parse_html(content));
+    }
+
+    let json: Value = serde_json::from_str(trimmed).map_err(|e| format!("Invalid bookmarks JSON: {}", e))?;
+    let mut bookmarks = Vec::new();
+    if let Some(roots) = json.get("roots").and_then(Value::as_object) {
+        // Chrome: the roots are folders themselves
+        for root in roots.values() {
+            walk_chrome(root, &mut Vec::new(), &mut bookmarks, true);
+        }
+    } else if json.get("children").is_some() {
+        walk_firefox(&json, &mut Vec::new(), &mut bookmarks);
+    } else {
+        return Err("Not a Chrome or Firefox bookmarks file".to_string());
+    }
+    Ok(bookmarks)
+}
+
+fn walk_chrome(node: &Value, folders: &mut Vec<String>, out: &mut Vec<Bookmark>, is_root: bool) {
+    match node.get("type").and_then(Value::as_str) {
+        Some("url") => {
+            let Some(url) = node.get("url").and_then(Value::as_str) else {
+                return;
+            };
+            let added_at = node
+                .get("date_added")
+                .and_then(Value::as_str)
+                .and_then(|micros| micros.parse::<i64>().ok())
+                .and_then(|micros| DateTime::from_timestamp_micros(micros - CHROME_EPOCH_OFFSET_MICROS));
+            push(out, url, node.get("name").and_then(Value::as_str), folders, added_at);
+        }
+        Some("folder") => {
+            let name = node.get("name").and_then(Value::as_str).unwrap_or_default();
+            let named = !is_root && !is_root_folder(name);
+            if named {
+                folders.push(name.to_string());
+            }
+            for child in node.get("children").and_then(Value::as_array).into_iter().flatten() {
+                walk_chrome(child, folders, out, false);
+            }
+            if named {
+                folders.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn walk_firefox(node: &Value, folders: &mut Vec<String>, out: &mut Vec<Bookmark>) {
+    match node.get("type").and_then(Value::as_str) {
+        Some("text/x-moz-place") => {
+            let Some(url) = node.get("uri").and_then(Value::as_str) else {
+                return;
+            };
+            // Firefox keeps internal queries ("place:...") among bookmarks
+            if url.starts_with("place:") {
+                return;
+            }
+            let added_at = node
+                .get("dateAdded")
+                .and_then(Value::as_i64)
+                .and_then(DateTime::from_timestamp_micros);
+            push(out, url, node.get("title").and_then(Value::as_str), folders, added_at);
+        }
+        Some("text/x-moz-place-container") => {
+            let name = node.get("title").and_then(Value::as_str).unwrap_or_default();
+            let named = node.get("root").is_none() && !name.is_empty() && !is_root_folder(name);
+            if named {
+                folders.push(name.to_string());
+            }
+            for child in node.get("children").and_then(Value::as_array).into_iter().flatten() {
+                walk_firefox(child, folders, out);
+            }
+            if named {
+                folders.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parse Netscape bookmark HTML: `<DT><H3>` opens a folder whose entries
+/// follow in a `<DL>`, and `<DT><A HREF=...>` is a bookmark.
+fn parse_html(html: &str) -> Vec<Bookmark> {
+    let lower = html.to_ascii_lowercase();
+    let mut bookmarks = Vec::new();
+    // Folder names, with `None` for root folders that aren't kept
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut pending_folder: Option<Option<String>> = None;
+    let mut pos = 0;
+
+    while let Some(offset) = lower[pos..].find('<') {
+        let open = pos + offset;
+        let Some(close) = lower[open..].find('>').map(|i| open + i) else {
+            break;
+        };
+        let tag = &lower[open + 1..close];
+        pos = close + 1;
+
+        if tag.starts_with("h3") {
+            let end = lower[pos..].find("</h3").map_or(lower.len(), |i| pos + i);
+            let name = decode_entities(html[pos..end].trim());
+            let is_root = tag.contains("personal_toolbar_folder") || is_root_folder(&name);
+            pending_folder = Some((!is_root).then_some(name));
+            pos = end;
+        } else if tag.starts_with("dl") {
+            folders.push(pending_folder.take().flatten());
+        } else if tag.starts_with("/dl") {
+            folders.pop();
+        } else if tag.starts_with("a ") {
+            let end = lower[pos..].find("</a").map_or(lower.len(), |i| pos + i);
+            let tag_original = &html[open + 1..close];
+            if let Some(url) = attribute(tag_original, "href") {
+                let added_at = attribute(tag_original, "add_date")
+                    .and_then(|secs| secs.parse::<i64>().ok())
+                    .and_then(|secs| DateTime::from_timestamp(secs, 0));
+                let title = decode_entities(html[pos..end].trim());
+                let path: Vec<String> = folders.iter().flatten().cloned().collect();
+                push(&mut bookmarks, &url, Some(&title), &path, added_at);
+            }
+            pos = end;
+        }
+    }
+    bookmarks
+}
+
+/// Value of attribute `name` (case-insensitive) in a tag's source.
+pub(crate) fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(i) = lower[from..].find(name) {
+        let start = from + i;
+        from = start + name.len();
+        let preceded = start == 0 || lower.as_bytes()[start - 1].is_ascii_whitespace();
+        let rest = lower[from..].trim_start();
+        if !preceded || !rest.starts_with('=') {
+            continue;
+        }
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        let (quote, value) = match value.chars().next() {
+            Some(q @ ('"' | '\'')) => (Some(q), &value[1..]),
+            _ => (None, value),
+        };
+        let end = match quote {
+            Some(q) => value.find(q),
+            None => value.find(char::is_whitespace),
+        }
+        .unwrap_or(value.len());
+        return Some(decode_entities(&value[..end]));
+    }
+    None
+}
+
+fn is_root_folder(name: &str) -> bool {
+    ROOT_FOLDERS.contains(&name.trim().to_lowercase().as_str())
+}
+
+fn push(
+    out: &mut Vec<Bookmark>,
+    url: &str,
+    title: Option<&str>,
+    folders: &[String],
+    added_at: Option<DateTime<Utc>>,
+) {
+    let url = url.trim();
+    // Bookmarklets and browser-internal pages have nothing to fetch
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return;
+    }
+    let title = title.map(str::trim).filter(|t| !t.is_empty()).unwrap_or(url);
+    out.push(Bookmark {
+        url: url.to_string(),
+        title: title.to_string(),
+        folders: folders.to_vec(),
+        tags: Vec::new(),
+        added_at,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_html() {
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000" PERSONAL_TOOLBAR_FOLDER="true">Bookmarks bar</H3>
+    <DL><p>
+        <DT><H3>Dev</H3>
+        <DL><p>
+            <DT><H3>Rust / Async</H3>
+            <DL><p>
+                <DT><A HREF="https://tokio.rs/" ADD_DATE="1700000100">Tokio &amp; friends</A>
+            </DL><p>
+            <DT><A HREF="https://doc.rust-lang.org/book/">The Book</A>
+        </DL><p>
+        <DT><A HREF="javascript:alert(1)">Bookmarklet</A>
+        <DT><A HREF="https://example.com/">Example</A>
+    </DL><p>
+</DL><p>"#;
+        let bookmarks = parse(html).unwrap();
+        assert_eq!(bookmarks.len(), 3);
+        assert_eq!(bookmarks[0].url, "https://tokio.rs/");
+        assert_eq!(bookmarks[0].title, "Tokio & friends");
+        assert_eq!(bookmarks[0].folder_tag().as_deref(), Some("Dev/Rust - Async"));
+        assert_eq!(bookmarks[0].added_at.unwrap().timestamp(), 1700000100);
+        assert_eq!(bookmarks[1].folders, vec!["Dev"]);
+        assert_eq!(bookmarks[2].folder_tag(), None);
+    }
+
+    #[test]
+    fn test_parse_chrome_json() {
+        let json = r#"{"roots": {
+            "bookmark_bar": {"type": "folder", "name": "Bookmarks bar", "children": [
+                {"type": "folder", "name": "Reading", "children": [
+                    {"type": "url", "name": "Essay", "url": "https://example.com/essay",
+                     "date_added": "13345000000000000"}
+                ]}
+            ]},
+            "other": {"type": "folder", "name": "Other bookmarks", "children": [
+                {"type": "url", "name": "", "url": "https://example.org/"}
+            ]}
+        }, "version": 1}"#;
+        let bookmarks = parse(json).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].folders, vec!["Reading"]);
+        assert_eq!(bookmarks[0].added_at.unwrap().timestamp(), 1_700_526_400);
+        assert_eq!(bookmarks[1].title, "https://example.org/");
+        assert!(bookmarks[1].folders.is_empty());
+    }
+
+    #[test]
+    fn test_parse_firefox_json() {
+        let json = r#"{"type": "text/x-moz-place-container", "root": "placesRoot", "children": [
+            {"type": "text/x-moz-place-container", "title": "menu", "root": "bookmarksMenuFolder", "children": [
+                {"type": "text/x-moz-place-container", "title": "Papers", "children": [
+                    {"type": "text/x-moz-place", "title": "Attention", "uri": "https://arxiv.org/abs/1706.03762",
+                     "dateAdded": 1700000000000000},
+                    {"type": "text/x-moz-place", "title": "Recent", "uri": "place:sort=8"}
+                ]}
+            ]}
+        ]}"#;
+        let bookmarks = parse(json).unwrap();
+        assert_eq!(bookmarks.len(), 1);
+        assert_eq!(bookmarks[0].folders, vec!["Papers"]);
+        assert_eq!(bookmarks[0].added_at.unwrap().timestamp(), 1_700_000_000);
+    }
+}
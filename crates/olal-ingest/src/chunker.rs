@@ -4,6 +4,14 @@
 //! and retrieval.
 
 use olal_core::{Chunk, ItemId};
+use std::io::{BufRead, Read};
+
+/// Bytes of text chunked at a time by [`Chunker::chunk_reader`].
+const STREAM_WINDOW: usize = 256 * 1024;
+
+/// Bytes (whitespace aside) at each end of a chunk matched against the
+/// source text by [`assign_pages`].
+const PAGE_PROBE_BYTES: usize = 64;
 
 /// Configuration for chunking.
 #[derive(Debug, Clone)]
@@ -176,6 +184,113 @@ impl Chunker {
         chunks
     }
 
+    /// Chunk edited text, keeping the `previous` chunks whose text is
+    /// unchanged and chunking only the regions between them.
+    ///
+    /// Chunking from the start would shift every boundary after an edit.
+    /// Anchoring on the unchanged chunks keeps them whole, so they keep
+    /// their IDs and embeddings; they are returned as they were, renumbered.
+    pub fn rechunk_text(&self, item_id: &ItemId, text: &str, previous: &[Chunk]) -> Vec<Chunk> {
+        if previous.is_empty() || text.trim().chars().count() <= self.config.chunk_size {
+            return self.chunk_text(item_id, text);
+        }
+
+        // The text as chunk_text sees it: trimmed paragraphs
+        let normalized = text
+            .trim()
+            .split("\n\n")
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        // Where the unchanged chunks are now, in order
+        let mut anchors: Vec<(usize, usize, &Chunk)> = Vec::new();
+        let mut from = 0;
+        for chunk in previous {
+            let Some(first) = chunk.content.chars().next() else {
+                continue;
+            };
+            if let Some(offset) = normalized[from..].find(chunk.content.as_str()) {
+                let start = from + offset;
+                anchors.push((start, start + chunk.content.len(), chunk));
+                // Chunks overlap, so the next may start inside this one
+                from = start + first.len_utf8();
+            }
+        }
+
+        let mut chunks = Vec::new();
+        let mut covered = 0;
+        for (start, end, chunk) in anchors {
+            if end <= covered {
+                continue;
+            }
+            if start > covered {
+                chunks.extend(self.chunk_text(item_id, &normalized[covered..start]));
+            }
+            chunks.push(chunk.clone());
+            covered = end;
+        }
+        chunks.extend(self.chunk_text(item_id, &normalized[covered..]));
+
+        for (index, chunk) in chunks.iter_mut().enumerate() {
+            chunk.chunk_index = index as i32;
+        }
+        chunks
+    }
+
+    /// Chunk text read incrementally from `reader`, handing each batch of
+    /// chunks to `sink` as it is produced. Returns the total chunk count.
+    ///
+    /// Memory stays bounded by the window size regardless of input length.
+    /// Windows end on paragraph breaks where possible; chunks don't overlap
+    /// across windows. Invalid UTF-8 is replaced rather than rejected.
+    pub fn chunk_reader<R, E, F>(&self, item_id: &ItemId, mut reader: R, mut sink: F) -> Result<usize, E>
+    where
+        R: BufRead,
+        E: From<std::io::Error>,
+        F: FnMut(Vec<Chunk>) -> Result<(), E>,
+    {
+        let window = STREAM_WINDOW.max(self.config.chunk_size * 4);
+        let mut buffer = String::new();
+        let mut carry = Vec::new();
+        let mut total = 0usize;
+
+        loop {
+            // Bounded read, so a file without newlines can't blow the window
+            let mut line = std::mem::take(&mut carry);
+            let read = (&mut reader).take(window as u64).read_until(b'\n', &mut line)?;
+            let at_eof = read == 0;
+
+            // Keep a multi-byte character split by the bounded read for next time
+            if let Err(e) = std::str::from_utf8(&line) {
+                if e.error_len().is_none() && !at_eof {
+                    carry = line.split_off(e.valid_up_to());
+                }
+            }
+            buffer.push_str(&String::from_utf8_lossy(&line));
+
+            let at_paragraph = !line.is_empty() && line.iter().all(u8::is_ascii_whitespace);
+            if at_eof || (buffer.len() >= window && at_paragraph) || buffer.len() >= window * 2 {
+                let mut chunks = self.chunk_text(item_id, &buffer);
+                for chunk in &mut chunks {
+                    chunk.chunk_index += total as i32;
+                }
+                total += chunks.len();
+                buffer.clear();
+                if !chunks.is_empty() {
+                    sink(chunks)?;
+                }
+            }
+
+            if at_eof {
+                break;
+            }
+        }
+
+        Ok(total)
+    }
+
     /// Force split text by character limit (for content without natural breaks like JSON).
     fn force_split_by_chars(&self, text: &str) -> Vec<String> {
         let chars: Vec<char> = text.chars().collect();
@@ -186,6 +301,9 @@ impl Chunker {
             let end = std::cmp::min(start + self.config.chunk_size, chars.len());
             let chunk: String = chars[start..end].iter().collect();
             result.push(chunk);
+            if end == chars.len() {
+                break;
+            }
             // Move forward with overlap
             start = end.saturating_sub(self.config.chunk_overlap);
             if start >= end {
@@ -270,8 +388,9 @@ impl Chunker {
                 current_start = Some(*start);
             }
 
+            // A line per segment, so labeled lines ([Screen], speakers) stay apart
             if !current_text.is_empty() {
-                current_text.push(' ');
+                current_text.push('\n');
             }
             current_text.push_str(text);
             current_end = *end;
@@ -288,10 +407,101 @@ impl Chunker {
     }
 }
 
+/// Set each chunk's page range from where its text falls in `text`, whose
+/// pages start at the byte offsets `page_starts`.
+///
+/// Chunking rewrites whitespace (and a forced split repeats its overlap),
+/// so the start and end of each chunk are found with whitespace removed.
+/// Chunks that can't be found are left without pages.
+pub fn assign_pages(chunks: &mut [Chunk], text: &str, page_starts: &[usize]) {
+    if page_starts.is_empty() {
+        return;
+    }
+
+    // The text without whitespace, and where each page starts in it
+    let mut squeezed = String::with_capacity(text.len());
+    let mut squeezed_starts = Vec::with_capacity(page_starts.len());
+    for (offset, c) in text.char_indices() {
+        while squeezed_starts.len() < page_starts.len() && page_starts[squeezed_starts.len()] <= offset {
+            squeezed_starts.push(squeezed.len());
+        }
+        if !c.is_whitespace() {
+            squeezed.push(c);
+        }
+    }
+    let page_at = |offset: usize| squeezed_starts.partition_point(|&start| start <= offset).max(1) as u32;
+
+    let mut from = 0;
+    for chunk in chunks.iter_mut() {
+        let content: String = chunk.content.chars().filter(|c| !c.is_whitespace()).collect();
+        let Some(first) = content.chars().next() else {
+            continue;
+        };
+        let head = &content[..content.floor_char_boundary(PAGE_PROBE_BYTES)];
+        let tail = &content[content.ceil_char_boundary(content.len().saturating_sub(PAGE_PROBE_BYTES))..];
+        let Some(start) = squeezed[from..].find(head).map(|i| from + i) else {
+            continue;
+        };
+        // Expect the end where the whole chunk would reach, else search on
+        let expected = start + content.len() - tail.len();
+        let end = squeezed
+            .get(expected..)
+            .and_then(|rest| rest.find(tail))
+            .map(|i| expected + i)
+            .or_else(|| squeezed[start..].find(tail).map(|i| start + i))
+            .map_or(start, |at| at + tail.len() - 1);
+
+        chunk.start_page = Some(page_at(start));
+        chunk.end_page = Some(page_at(end));
+        // Chunks overlap, so the next may start inside this one
+        from = start + first.len_utf8();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_assign_pages() {
+        let chunker = Chunker::new(ChunkConfig {
+            chunk_size: 120,
+            chunk_overlap: 20,
+            min_chunk_size: 10,
+        });
+        let pages: Vec<String> = (1..=4)
+            .map(|page| {
+                format!(
+                    "Page {} opens here. It goes on for a while so chunks cross pages.\nAnd a second line ends page {}.",
+                    page, page
+                )
+            })
+            .collect();
+        let mut text = String::new();
+        let mut starts = Vec::new();
+        for page in &pages {
+            if !text.is_empty() {
+                text.push_str("\n\n---\n\n");
+            }
+            starts.push(text.len());
+            text.push_str(page);
+        }
+
+        let mut chunks = chunker.chunk_text(&"item".to_string(), &text);
+        assign_pages(&mut chunks, &text, &starts);
+        assert!(chunks.len() > 2);
+        assert_eq!(chunks[0].start_page, Some(1));
+        assert_eq!(chunks.last().unwrap().end_page, Some(4));
+        for chunk in &chunks {
+            let (start, end) = (chunk.start_page.unwrap(), chunk.end_page.unwrap());
+            for page in 1..=4 {
+                if chunk.content.contains(&format!("Page {} opens", page)) {
+                    assert!(start <= page && page <= end, "page {} in {}-{}", page, start, end);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_small_text_single_chunk() {
         let chunker = Chunker::default_chunker();
@@ -398,4 +608,81 @@ mod tests {
         // Should handle paragraphs properly
         assert!(!chunks.is_empty());
     }
+
+    #[test]
+    fn test_rechunk_keeps_unchanged_chunks() {
+        let chunker = Chunker::new(ChunkConfig {
+            chunk_size: 100,
+            chunk_overlap: 0,
+            min_chunk_size: 10,
+        });
+        let item_id = "item1".to_string();
+        let paragraphs: Vec<String> = (1..=8)
+            .map(|n| format!("Paragraph {} of the journal, half a chunk.", n))
+            .collect();
+        let previous = chunker.chunk_text(&item_id, &paragraphs.join("\n\n"));
+
+        // A longer first paragraph shifts every later boundary when
+        // chunking from the start
+        let mut edited = paragraphs.clone();
+        edited[0] = format!("{} It now says more.", edited[0]);
+        let text = edited.join("\n\n");
+        let from_scratch = chunker.chunk_text(&item_id, &text);
+        assert!(from_scratch.iter().all(|c| previous.iter().all(|p| p.content != c.content)));
+
+        let chunks = chunker.rechunk_text(&item_id, &text, &previous);
+        let kept: Vec<&Chunk> = chunks
+            .iter()
+            .filter(|c| previous.iter().any(|p| p.id == c.id))
+            .collect();
+        assert_eq!(kept.len(), previous.len() - 1);
+        assert_eq!(
+            chunks.iter().map(|c| c.content.as_str()).collect::<Vec<_>>().join("\n\n"),
+            text
+        );
+        assert_eq!(
+            chunks.iter().map(|c| c.chunk_index).collect::<Vec<_>>(),
+            (0..chunks.len() as i32).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_chunk_reader_streams_batches() {
+        let chunker = Chunker::default_chunker();
+        let paragraph = "Streaming keeps memory bounded for very large inputs. ".repeat(10);
+        let text = format!("{}\n\n", paragraph.trim()).repeat(2000);
+
+        let mut batches = 0;
+        let mut indices = Vec::new();
+        let total = chunker
+            .chunk_reader(&"item1".to_string(), text.as_bytes(), |chunks| {
+                batches += 1;
+                indices.extend(chunks.iter().map(|c| c.chunk_index));
+                Ok::<(), std::io::Error>(())
+            })
+            .unwrap();
+
+        assert!(batches > 1, "expected several windows, got {}", batches);
+        assert_eq!(total, indices.len());
+        assert_eq!(indices, (0..total as i32).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_chunk_reader_keeps_split_characters() {
+        let chunker = Chunker::default_chunker();
+        // One long line of multi-byte characters forces bounded reads mid-character
+        let text = "é".repeat(STREAM_WINDOW);
+
+        let mut content = String::new();
+        chunker
+            .chunk_reader(&"item1".to_string(), text.as_bytes(), |chunks| {
+                for chunk in chunks {
+                    content.push_str(&chunk.content);
+                }
+                Ok::<(), std::io::Error>(())
+            })
+            .unwrap();
+
+        assert!(!content.contains('\u{FFFD}'));
+    }
 }
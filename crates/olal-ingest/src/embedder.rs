@@ -0,0 +1,101 @@
+//! Embedding generation during ingestion (`processing.auto_embed`).
+//!
+//! New chunks are embedded as soon as they're stored. Chunks that fail
+//! (Ollama down, model missing, request errors) simply stay unembedded;
+//! the queue worker retries them with [`Embedder::embed_pending`], and
+//! `olal embed --all` still works as a manual fallback.
+
+use olal_config::Config;
+use olal_core::{Chunk, ItemId};
+use olal_db::Database;
+use crate::error::IngestResult;
+use olal_ollama::{OllamaClient, OllamaError};
+use tokio::runtime::Runtime;
+use tracing::{debug, warn};
+
+/// Generates and stores chunk embeddings with the configured model.
+pub struct Embedder {
+    client: OllamaClient,
+    model: String,
+    rt: Runtime,
+}
+
+impl Embedder {
+    /// Create an embedder from config.
+    ///
+    /// Fails if Ollama isn't reachable or the embedding model isn't pulled.
+    pub fn from_config(config: &Config) -> IngestResult<Self> {
+        let client = OllamaClient::from_config(&config.ollama)?;
+        let rt = Runtime::new()?;
+
+        if !rt.block_on(client.is_available()) {
+            return Err(OllamaError::ServerNotRunning {
+                host: config.ollama.host.clone(),
+            }
+            .into());
+        }
+        let model = config.ollama.embedding_model.clone();
+        if !rt.block_on(client.has_model(&model)).unwrap_or(false) {
+            return Err(OllamaError::ModelNotFound { model }.into());
+        }
+
+        Ok(Self { client, model, rt })
+    }
+
+    /// Embed the given chunks. Returns (embedded, failed).
+    pub fn embed_chunks(&self, db: &Database, chunks: &[Chunk]) -> (usize, usize) {
+        let mut embedded = 0;
+        let mut failed = 0;
+
+        for chunk in chunks {
+            match db.reuse_embedding(chunk, &self.model) {
+                Ok(true) => {
+                    embedded += 1;
+                    continue;
+                }
+                Ok(false) => {}
+                Err(e) => warn!("Embedding cache lookup failed for chunk {}: {}", chunk.id, e),
+            }
+            let vector = self.rt.block_on(self.client.embed(&self.model, &chunk.content));
+            if let Err(e @ OllamaError::CircuitOpen { .. }) = &vector {
+                // The rest stay unembedded for the next run
+                warn!("Stopping embedding: {}", e);
+                failed += 1;
+                break;
+            }
+            let result = vector
+                .map_err(|e| e.to_string())
+                .and_then(|vector| {
+                    db.store_embedding(&chunk.id, &vector, &self.model)
+                        .map_err(|e| e.to_string())
+                });
+
+            match result {
+                Ok(()) => embedded += 1,
+                Err(e) => {
+                    warn!("Failed to embed chunk {}: {}", chunk.id, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        (embedded, failed)
+    }
+
+    /// Embed an item's chunks that don't have embeddings yet.
+    pub fn embed_item(&self, db: &Database, item_id: &ItemId) -> Result<(usize, usize), String> {
+        let chunks = db
+            .get_unembedded_chunks_by_item(item_id)
+            .map_err(|e| e.to_string())?;
+        let counts = self.embed_chunks(db, &chunks);
+        debug!("Embedded {} chunks of item {}", counts.0, item_id);
+        Ok(counts)
+    }
+
+    /// Embed up to `limit` chunks that are still missing embeddings, e.g.
+    /// after earlier failures. Returns (embedded, failed).
+    pub fn embed_pending(&self, db: &Database, limit: usize) -> IngestResult<(usize, usize)> {
+        let chunks = db.get_unembedded_chunks(limit)?;
+        Ok(self.embed_chunks(db, &chunks))
+    }
+}
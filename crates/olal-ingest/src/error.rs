@@ -1,5 +1,6 @@
 //! Error types for the ingestion pipeline.
 
+use olal_core::{Diagnostic, ErrorCode};
 use std::path::PathBuf;
 use thiserror::Error;
 
@@ -18,6 +19,9 @@ pub enum IngestError {
     #[error("Config error: {0}")]
     Config(#[from] olal_config::ConfigError),
 
+    #[error("{0}")]
+    Ollama(#[from] olal_ollama::OllamaError),
+
     #[error("File not found: {0}")]
     FileNotFound(PathBuf),
 
@@ -36,3 +40,29 @@ pub enum IngestError {
     #[error("Processing error: {0}")]
     ProcessingError(String),
 }
+
+impl Diagnostic for IngestError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            IngestError::Io(_) => ErrorCode::Io,
+            IngestError::Database(e) => e.code(),
+            IngestError::Config(e) => e.code(),
+            IngestError::Ollama(e) => e.code(),
+            IngestError::FileNotFound(_) => ErrorCode::NotFound,
+            IngestError::UnsupportedFileType(_) => ErrorCode::Unsupported,
+            IngestError::ParseError { .. }
+            | IngestError::WatchError(_)
+            | IngestError::ProcessingError(_) => ErrorCode::Processing,
+            IngestError::AlreadyProcessed(_) => ErrorCode::InvalidInput,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            IngestError::Database(e) => e.hint(),
+            IngestError::Config(e) => e.hint(),
+            IngestError::Ollama(e) => e.hint(),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,283 @@
+//! Pipeline events.
+//!
+//! The ingestor (and commands like `olal digest`) emit [`Event`]s through an
+//! [`EventEmitter`]; listeners such as the webhook notifier subscribe to it.
+//! Emitting with no listeners is free, and listeners must not block for long:
+//! they run on the emitting thread.
+
+use chrono::Utc;
+use olal_core::{Item, QueueItem};
+use serde_json::{json, Value};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+/// Something that happened in the pipeline.
+///
+/// The `Ingest*` events report a file's progress through the ingestor, for
+/// live progress displays; [`Event::ItemProcessed`] follows when it's done.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// Ingestion of a file started: file `index` (from 1) of `total` in
+    /// this run.
+    IngestStarted {
+        path: String,
+        index: usize,
+        total: usize,
+    },
+    /// A file was parsed.
+    IngestParsed {
+        path: String,
+        item_type: String,
+        title: String,
+    },
+    /// An item's chunks were stored; `kept` of them were unchanged chunks
+    /// of an updated item.
+    IngestChunked {
+        item_id: String,
+        chunk_count: usize,
+        kept: usize,
+    },
+    /// An item was enriched (summary, tags and entities, as configured).
+    IngestEnriched {
+        item_id: String,
+        summarized: bool,
+        tag_count: usize,
+    },
+    /// A file failed to ingest.
+    IngestFailed { path: String, error: String },
+    /// An item was ingested (or re-ingested) and chunked.
+    ItemProcessed {
+        item_id: String,
+        title: String,
+        item_type: String,
+        source_path: Option<String>,
+        chunk_count: usize,
+    },
+    /// A queue entry failed to process.
+    QueueFailed {
+        queue_id: String,
+        source_path: String,
+        error: String,
+        attempts: i32,
+    },
+    /// A digest was generated.
+    DigestGenerated {
+        period: String,
+        item_count: usize,
+        /// File the digest was written to, if any.
+        output: Option<String>,
+        digest: String,
+    },
+}
+
+impl Event {
+    /// Event for a finished item.
+    pub fn item_processed(item: &Item, chunk_count: usize) -> Self {
+        Event::ItemProcessed {
+            item_id: item.id.clone(),
+            title: item.title.clone(),
+            item_type: item.item_type.as_str().to_string(),
+            source_path: item.source_path.clone(),
+            chunk_count,
+        }
+    }
+
+    /// Event for an item whose chunks were stored.
+    pub fn ingest_chunked(item: &Item, chunk_count: usize, kept: usize) -> Self {
+        Event::IngestChunked {
+            item_id: item.id.clone(),
+            chunk_count,
+            kept,
+        }
+    }
+
+    /// Event for a file that failed to ingest.
+    pub fn ingest_failed(path: &Path, error: impl ToString) -> Self {
+        Event::IngestFailed {
+            path: path.display().to_string(),
+            error: error.to_string(),
+        }
+    }
+
+    /// Event for a failed queue entry.
+    pub fn queue_failed(entry: &QueueItem, error: impl Into<String>) -> Self {
+        Event::QueueFailed {
+            queue_id: entry.id.clone(),
+            source_path: entry.source_path.clone(),
+            error: error.into(),
+            attempts: entry.attempts,
+        }
+    }
+
+    /// The event's name, as used in config filters and payloads.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Event::IngestStarted { .. } => "ingest.started",
+            Event::IngestParsed { .. } => "ingest.parsed",
+            Event::IngestChunked { .. } => "ingest.chunked",
+            Event::IngestEnriched { .. } => "ingest.enriched",
+            Event::IngestFailed { .. } => "ingest.failed",
+            Event::ItemProcessed { .. } => "item.processed",
+            Event::QueueFailed { .. } => "queue.failed",
+            Event::DigestGenerated { .. } => "digest.generated",
+        }
+    }
+
+    /// JSON payload: `{"event": ..., "timestamp": ..., "data": {...}}`.
+    pub fn payload(&self) -> Value {
+        let data = match self {
+            Event::IngestStarted { path, index, total } => json!({
+                "path": path,
+                "index": index,
+                "total": total,
+            }),
+            Event::IngestParsed {
+                path,
+                item_type,
+                title,
+            } => json!({
+                "path": path,
+                "item_type": item_type,
+                "title": title,
+            }),
+            Event::IngestChunked {
+                item_id,
+                chunk_count,
+                kept,
+            } => json!({
+                "item_id": item_id,
+                "chunk_count": chunk_count,
+                "kept": kept,
+            }),
+            Event::IngestEnriched {
+                item_id,
+                summarized,
+                tag_count,
+            } => json!({
+                "item_id": item_id,
+                "summarized": summarized,
+                "tag_count": tag_count,
+            }),
+            Event::IngestFailed { path, error } => json!({
+                "path": path,
+                "error": error,
+            }),
+            Event::ItemProcessed {
+                item_id,
+                title,
+                item_type,
+                source_path,
+                chunk_count,
+            } => json!({
+                "item_id": item_id,
+                "title": title,
+                "item_type": item_type,
+                "source_path": source_path,
+                "chunk_count": chunk_count,
+            }),
+            Event::QueueFailed {
+                queue_id,
+                source_path,
+                error,
+                attempts,
+            } => json!({
+                "queue_id": queue_id,
+                "source_path": source_path,
+                "error": error,
+                "attempts": attempts,
+            }),
+            Event::DigestGenerated {
+                period,
+                item_count,
+                output,
+                digest,
+            } => json!({
+                "period": period,
+                "item_count": item_count,
+                "output": output,
+                "digest": digest,
+            }),
+        };
+
+        json!({
+            "event": self.name(),
+            "timestamp": Utc::now().to_rfc3339(),
+            "data": data,
+        })
+    }
+}
+
+type Listener = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// Delivers events to subscribed listeners. Clones share listeners.
+#[derive(Clone, Default)]
+pub struct EventEmitter {
+    listeners: Arc<RwLock<Vec<Listener>>>,
+}
+
+impl EventEmitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Call `listener` for every event emitted from now on.
+    pub fn subscribe(&self, listener: impl Fn(&Event) + Send + Sync + 'static) {
+        if let Ok(mut listeners) = self.listeners.write() {
+            listeners.push(Arc::new(listener));
+        }
+    }
+
+    /// Deliver `event` to all listeners.
+    pub fn emit(&self, event: Event) {
+        let listeners: Vec<Listener> = match self.listeners.read() {
+            Ok(listeners) => listeners.clone(),
+            Err(_) => return,
+        };
+        for listener in listeners {
+            listener(&event);
+        }
+    }
+}
+
+impl std::fmt::Debug for EventEmitter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let count = self.listeners.read().map(|l| l.len()).unwrap_or(0);
+        f.debug_struct("EventEmitter").field("listeners", &count).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::ItemType;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_emitter_delivers_to_clones() {
+        let emitter = EventEmitter::new();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        emitter.subscribe(move |event| log.lock().unwrap().push(event.name()));
+
+        let item = Item::new(ItemType::Note, "Idea");
+        emitter.clone().emit(Event::item_processed(&item, 2));
+        emitter.emit(Event::DigestGenerated {
+            period: "daily".to_string(),
+            item_count: 1,
+            output: None,
+            digest: "...".to_string(),
+        });
+
+        assert_eq!(*seen.lock().unwrap(), vec!["item.processed", "digest.generated"]);
+    }
+
+    #[test]
+    fn test_payload() {
+        let item = Item::new(ItemType::Note, "Idea");
+        let payload = Event::item_processed(&item, 3).payload();
+        assert_eq!(payload["event"], "item.processed");
+        assert_eq!(payload["data"]["title"], "Idea");
+        assert_eq!(payload["data"]["chunk_count"], 3);
+        assert!(payload["timestamp"].is_string());
+    }
+}
@@ -0,0 +1,158 @@
+//! Listing the files of a git repository for ingestion.
+//!
+//! Uses the `git` command line, so `.gitignore` and the rest of git's
+//! exclude rules apply exactly as they do for `git status`.
+
+use crate::error::{IngestError, IngestResult};
+use olal_core::ItemType;
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Bytes checked for a NUL when deciding whether a file is binary, as git does.
+const BINARY_CHECK_BYTES: usize = 8000;
+
+/// A text file in a repository at a given commit.
+#[derive(Debug, Clone)]
+pub struct RepoFile {
+    /// Absolute path to the file.
+    pub path: PathBuf,
+    /// Path relative to the repository root, with `/` separators.
+    pub relative: String,
+}
+
+/// The files of a repository to ingest, and the commit they were read at.
+#[derive(Debug, Clone)]
+pub struct RepoSnapshot {
+    /// Repository root.
+    pub root: PathBuf,
+    /// Full hash of `HEAD`.
+    pub commit: String,
+    pub files: Vec<RepoFile>,
+}
+
+impl RepoSnapshot {
+    /// Provenance metadata for `file`: repository, commit and relative path.
+    pub fn metadata(&self, file: &RepoFile) -> serde_json::Value {
+        serde_json::json!({
+            "repo": self.root.display().to_string(),
+            "commit": self.commit,
+            "path": file.relative,
+        })
+    }
+}
+
+/// Run `git` in `repo` and return its stdout.
+fn git(repo: &Path, args: &[&str]) -> IngestResult<String> {
+    if which::which("git").is_err() {
+        return Err(IngestError::ProcessingError("git not found in PATH".to_string()));
+    }
+    let output = Command::new("git").arg("-C").arg(repo).args(args).output()?;
+    if !output.status.success() {
+        return Err(IngestError::ProcessingError(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Whether the start of the file contains a NUL byte.
+pub fn is_binary(path: &Path) -> IngestResult<bool> {
+    let mut buf = Vec::with_capacity(BINARY_CHECK_BYTES);
+    File::open(path)?.take(BINARY_CHECK_BYTES as u64).read_to_end(&mut buf)?;
+    Ok(buf.contains(&0))
+}
+
+/// The supported text files in the repository containing `repo`: tracked
+/// files plus untracked ones that aren't ignored. With `since`, only files
+/// changed between that revision and `HEAD` (or since in the working tree).
+pub fn snapshot(repo: &Path, since: Option<&str>) -> IngestResult<RepoSnapshot> {
+    let root = PathBuf::from(git(repo, &["rev-parse", "--show-toplevel"])?.trim());
+    let commit = git(&root, &["rev-parse", "HEAD"])?.trim().to_string();
+
+    let listed = git(&root, &["ls-files", "--cached", "--others", "--exclude-standard", "-z"])?;
+    let mut relative: Vec<&str> = listed.split('\0').filter(|p| !p.is_empty()).collect();
+    relative.sort_unstable();
+    relative.dedup();
+    if let Some(rev) = since {
+        // Changes since `rev`, committed or not, plus new untracked files
+        let changed = git(&root, &["diff", "--name-only", "--diff-filter=ACMR", "-z", rev])?;
+        let untracked = git(&root, &["ls-files", "--others", "--exclude-standard", "-z"])?;
+        let wanted: HashSet<&str> = changed.split('\0').chain(untracked.split('\0')).collect();
+        relative.retain(|p| wanted.contains(p));
+    }
+
+    let mut files = Vec::new();
+    for rel in relative {
+        let path = root.join(rel);
+        let supported = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(ItemType::from_extension)
+            .is_some();
+        // Deleted but still in the index, or a submodule directory
+        if !supported || !path.is_file() || is_binary(&path)? {
+            continue;
+        }
+        files.push(RepoFile {
+            path,
+            relative: rel.to_string(),
+        });
+    }
+
+    Ok(RepoSnapshot { root, commit, files })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn run(repo: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(repo)
+            .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?}", args);
+    }
+
+    #[test]
+    fn test_snapshot() {
+        if which::which("git").is_err() {
+            return;
+        }
+        let dir = tempdir().unwrap();
+        let repo = dir.path();
+        run(repo, &["init", "-q"]);
+        std::fs::write(repo.join(".gitignore"), "target/\n").unwrap();
+        std::fs::create_dir_all(repo.join("src")).unwrap();
+        std::fs::create_dir_all(repo.join("target")).unwrap();
+        std::fs::write(repo.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(repo.join("target/out.rs"), "// built").unwrap();
+        std::fs::write(repo.join("logo.png"), [0x89, b'P', b'N', b'G', 0, 0]).unwrap();
+        std::fs::write(repo.join("notes.md"), "# Notes").unwrap();
+        run(repo, &["add", "."]);
+        run(repo, &["commit", "-q", "-m", "first"]);
+
+        let all = snapshot(repo, None).unwrap();
+        let files: Vec<_> = all.files.iter().map(|f| f.relative.as_str()).collect();
+        assert_eq!(files, vec!["notes.md", "src/main.rs"]);
+        assert_eq!(all.commit.len(), 40);
+        assert_eq!(all.metadata(&all.files[1])["path"], "src/main.rs");
+
+        let first = all.commit;
+        std::fs::write(repo.join("notes.md"), "# Notes\n\nMore.").unwrap();
+        std::fs::write(repo.join("new.txt"), "New").unwrap();
+        let changed = snapshot(repo, Some(&first)).unwrap();
+        let files: Vec<_> = changed.files.iter().map(|f| f.relative.as_str()).collect();
+        assert_eq!(files, vec!["new.txt", "notes.md"]);
+    }
+}
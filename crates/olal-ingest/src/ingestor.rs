@@ -1,31 +1,162 @@
 //! Main ingestion logic.
 
-use crate::chunker::{ChunkConfig, Chunker};
+use crate::archive::{self, ArchiveMember};
+use crate::arxiv;
+use crate::bookmarks::Bookmark;
+use crate::git::{self, RepoSnapshot};
+use crate::kindle::Book;
+use crate::chunker::{self, ChunkConfig, Chunker};
+use crate::embedder::Embedder;
 use crate::error::{IngestError, IngestResult};
-use crate::parsers::{self, AudioParser, ParsedDocument, VideoParser};
-use olal_core::{Chunk, Item, ItemType, QueueItem};
+use crate::events::{Event, EventEmitter};
+use crate::vision::ImageDescriber;
+use crate::web::PageFetcher;
+use crate::parsers::{
+    self, AudioParser, DocumentParser, ImageParser, ParsedDocument, PdfParser, VideoParser,
+};
+use olal_core::{Chunk, Item, ItemId, ItemType, Link, LinkType, QueueItem};
 use olal_db::Database;
-use olal_process::TranscriptSegment;
+use olal_process::{extract_thumbnail, AudioPreprocessing, TranscriptSegment, TranscriptionBackend};
 use chrono::Utc;
+use rayon::prelude::*;
 use sha2::{Digest, Sha256};
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex, OnceLock};
 use tracing::{debug, info, warn};
 
+/// New files written per transaction during batch ingestion.
+const WRITE_BATCH: usize = 32;
+
+/// Tag for items whose OCR text is unreliable and worth checking by hand.
+pub const OCR_REVIEW_TAG: &str = "review/ocr";
+
+/// Images with fewer OCR words than this are captioned by the vision model.
+const DESCRIBE_BELOW_WORDS: usize = 20;
+
+/// Timeout for arXiv requests; PDFs can be tens of megabytes.
+const ARXIV_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(120);
+
 /// Result of processing a file.
 #[derive(Debug)]
 pub struct IngestResult2 {
     /// The created item.
     pub item: Item,
-    /// The created chunks.
+    /// The created chunks (only the first batch for streamed files).
     pub chunks: Vec<Chunk>,
+    /// Total number of chunks created.
+    pub chunk_count: usize,
     /// Whether this was a re-process of an existing item.
     pub was_update: bool,
 }
 
+/// Counts from importing an export file.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ImportSummary {
+    /// Items (or, for Kindle highlights, highlights) created.
+    pub added: usize,
+    /// Kindle highlights already imported that were since extended or
+    /// given a note.
+    pub updated: usize,
+    /// Entries already in the knowledge base (or repeated in the file).
+    pub skipped: usize,
+}
+
+/// A file that has been hashed, parsed and chunked but not yet written.
+enum PreparedFile {
+    /// Content already ingested (same hash).
+    Unchanged(Item),
+    Parsed {
+        item: Item,
+        was_update: bool,
+        /// `None` for streamed files, which are chunked while writing.
+        chunks: Option<Vec<Chunk>>,
+        path: PathBuf,
+        /// The item previously at this path, when this one replaces it.
+        supersedes: Option<ItemId>,
+    },
+}
+
+impl PreparedFile {
+    /// Whether this is a plain new file that can be written with others.
+    fn is_batchable(&self) -> bool {
+        matches!(
+            self,
+            PreparedFile::Parsed {
+                was_update: false,
+                chunks: Some(_),
+                supersedes: None,
+                ..
+            }
+        )
+    }
+
+    fn content_hash(&self) -> Option<&str> {
+        match self {
+            PreparedFile::Parsed { item, .. } => item.content_hash.as_deref(),
+            PreparedFile::Unchanged(_) => None,
+        }
+    }
+}
+
+/// Where a file came from, when it's ingested from an archive or repository.
+struct Provenance {
+    /// Recorded instead of the file's own path.
+    source_path: Option<String>,
+    /// Metadata key for `value` ("archive", "git").
+    key: &'static str,
+    value: serde_json::Value,
+}
+
+/// Counts free slots for jobs that run external tools (ffmpeg, Whisper,
+/// Tesseract), so parallel parsing doesn't start one per thread.
+struct JobSlots {
+    free: Mutex<usize>,
+    released: Condvar,
+}
+
+/// A taken slot, given back when dropped.
+struct JobSlot<'a>(&'a JobSlots);
+
+impl JobSlots {
+    fn new(slots: usize) -> Self {
+        Self {
+            free: Mutex::new(slots.max(1)),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Wait for a free slot and take it.
+    fn acquire(&self) -> JobSlot<'_> {
+        let mut free = self.free.lock().unwrap_or_else(|e| e.into_inner());
+        while *free == 0 {
+            free = self.released.wait(free).unwrap_or_else(|e| e.into_inner());
+        }
+        *free -= 1;
+        JobSlot(self)
+    }
+}
+
+impl Drop for JobSlot<'_> {
+    fn drop(&mut self) {
+        *self.0.free.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+        self.0.released.notify_one();
+    }
+}
+
 /// Main ingestor for processing files.
 pub struct Ingestor {
     db: Database,
     chunker: Chunker,
+    threads: usize,
+    /// Limits media parsing to `processing.max_concurrent_jobs` at a time.
+    media_jobs: JobSlots,
+    /// Set up on first use; `None` when auto-embed is off or Ollama is unavailable.
+    embedder: OnceLock<Option<Embedder>>,
+    /// Set up on first use; `None` when image descriptions are off or unavailable.
+    describer: OnceLock<Option<ImageDescriber>>,
+    events: EventEmitter,
 }
 
 impl Ingestor {
@@ -34,9 +165,34 @@ impl Ingestor {
         Self {
             db,
             chunker: Chunker::new(chunk_config),
+            threads: 0,
+            media_jobs: JobSlots::new(olal_config::ProcessingConfig::default().max_concurrent_jobs),
+            embedder: OnceLock::new(),
+            describer: OnceLock::new(),
+            events: EventEmitter::new(),
         }
     }
 
+    /// Emit pipeline events (per-file progress, items processed, queue
+    /// failures) to `events`.
+    pub fn with_events(mut self, events: EventEmitter) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Set the number of parse threads for batch ingestion (0 = one per core).
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Set how many media files (video, audio, images, PDFs that may need
+    /// OCR) are parsed at once, however many parse threads there are.
+    pub fn with_media_jobs(mut self, jobs: usize) -> Self {
+        self.media_jobs = JobSlots::new(jobs);
+        self
+    }
+
     /// Create an ingestor with default chunking config.
     pub fn with_defaults(db: Database) -> Self {
         Self::new(db, ChunkConfig::default())
@@ -44,8 +200,337 @@ impl Ingestor {
 
     /// Ingest a single file.
     pub fn ingest_file(&self, path: &Path) -> IngestResult<IngestResult2> {
+        self.events.emit(Event::IngestStarted {
+            path: path.display().to_string(),
+            index: 1,
+            total: 1,
+        });
+        let result = self.prepare_file(path, None).and_then(|prepared| self.store_prepared(prepared));
+        if let Err(ref e) = result {
+            self.events.emit(Event::ingest_failed(path, e));
+        }
+        result
+    }
+
+    /// Ingest many files, calling `on_result` as each one finishes.
+    ///
+    /// Hashing, parsing and chunking run in parallel on a rayon pool; the
+    /// database writes stay on the calling thread, with new files committed
+    /// in batches. Results arrive in completion order, not input order.
+    pub fn ingest_files<F>(&self, paths: &[PathBuf], on_done: F) -> IngestResult<()>
+    where
+        F: FnMut(&Path, IngestResult<IngestResult2>),
+    {
+        self.ingest_files_with(paths, |_| None, on_done)
+    }
+
+    /// [`Ingestor::ingest_files`], recording each file's `provenance`.
+    fn ingest_files_with<P, F>(&self, paths: &[PathBuf], provenance: P, mut on_done: F) -> IngestResult<()>
+    where
+        P: Fn(&Path) -> Option<Provenance> + Sync,
+        F: FnMut(&Path, IngestResult<IngestResult2>),
+    {
+        let mut on_result = |path: &Path, result: IngestResult<IngestResult2>| {
+            if let Err(ref e) = result {
+                self.events.emit(Event::ingest_failed(path, e));
+            }
+            on_done(path, result)
+        };
+        let started = AtomicUsize::new(0);
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(self.threads)
+            .build()
+            .map_err(|e| IngestError::ProcessingError(format!("Failed to start worker pool: {}", e)))?;
+        let (tx, rx) = std::sync::mpsc::sync_channel(WRITE_BATCH * 2);
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                pool.install(|| {
+                    paths.par_iter().for_each_with(tx, |tx, path| {
+                        self.events.emit(Event::IngestStarted {
+                            path: path.display().to_string(),
+                            index: started.fetch_add(1, Ordering::Relaxed) + 1,
+                            total: paths.len(),
+                        });
+                        let _ = tx.send((path.clone(), self.prepare_file(path, provenance(path).as_ref())));
+                    });
+                });
+            });
+
+            let mut batch: Vec<(PathBuf, PreparedFile)> = Vec::new();
+            let mut batch_hashes = HashSet::new();
+            let mut next = rx.recv().ok();
+            while let Some((path, prepared)) = next {
+                let prepared = prepared.and_then(|p| {
+                    let hash = p.content_hash().map(str::to_string);
+                    if hash.as_ref().is_some_and(|h| batch_hashes.contains(h)) {
+                        // Same content as a file waiting in this batch
+                        self.flush_batch(&mut batch, &mut on_result);
+                        batch_hashes.clear();
+                    }
+                    // Another worker may have written this content since it was hashed
+                    self.recheck(p)
+                });
+
+                match prepared {
+                    // Plain new files are written together; everything else
+                    // (updates, duplicates, streamed files) goes through alone
+                    Ok(prepared) if prepared.is_batchable() => {
+                        if let Some(hash) = prepared.content_hash() {
+                            batch_hashes.insert(hash.to_string());
+                        }
+                        batch.push((path, prepared));
+                    }
+                    Ok(prepared) => on_result(&path, self.store_prepared(prepared)),
+                    Err(e) => on_result(&path, Err(e)),
+                }
+
+                // Flush when the batch is full or the workers have gone quiet
+                next = rx.try_recv().ok();
+                if batch.len() >= WRITE_BATCH || next.is_none() {
+                    self.flush_batch(&mut batch, &mut on_result);
+                    batch_hashes.clear();
+                    if next.is_none() {
+                        next = rx.recv().ok();
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Ingest the supported files in a .zip, .tar or .tar.gz archive, calling
+    /// `on_result` as each one finishes.
+    ///
+    /// Members are unpacked to a temporary directory and recorded with the
+    /// source path `<archive>!/<entry>` and the archive in their `archive`
+    /// metadata. With `keep_parent`, an item for the archive itself is
+    /// created (or reused) and returned, with each member linked to it as a
+    /// child.
+    pub fn ingest_archive<F>(&self, path: &Path, keep_parent: bool, mut on_result: F) -> IngestResult<Option<Item>>
+    where
+        F: FnMut(&ArchiveMember, IngestResult<IngestResult2>),
+    {
+        let dir = tempfile::tempdir()?;
+        let members = archive::unpack(path, dir.path())?;
+        let parent = if keep_parent {
+            Some(self.archive_item(path, &members)?)
+        } else {
+            None
+        };
+
+        for (i, member) in members.iter().enumerate() {
+            let source = member.source_path();
+            self.events.emit(Event::IngestStarted {
+                path: source.clone(),
+                index: i + 1,
+                total: members.len(),
+            });
+            let provenance = Provenance {
+                source_path: Some(source.clone()),
+                key: "archive",
+                value: member.metadata(),
+            };
+            let result = self
+                .prepare_file(&member.path, Some(&provenance))
+                .and_then(|prepared| self.store_prepared(prepared));
+            match (&result, &parent) {
+                (Ok(stored), Some(parent)) => {
+                    self.db
+                        .create_link(&Link::new(stored.item.id.clone(), parent.id.clone(), LinkType::Child))?;
+                }
+                (Ok(_), None) => {}
+                (Err(e), _) => self.events.emit(Event::ingest_failed(Path::new(&source), e)),
+            }
+            on_result(member, result);
+        }
+
+        Ok(parent)
+    }
+
+    /// Ingest the text files of the git repository containing `repo`,
+    /// calling `on_result` as each one finishes.
+    ///
+    /// Files ignored by git and binary files are skipped. Each item records
+    /// the repository, `HEAD` commit and its relative path in its `git`
+    /// metadata. With `since`, only files changed since that revision are
+    /// ingested. Returns the repository snapshot that was ingested.
+    pub fn ingest_git<F>(&self, repo: &Path, since: Option<&str>, on_result: F) -> IngestResult<RepoSnapshot>
+    where
+        F: FnMut(&Path, IngestResult<IngestResult2>),
+    {
+        let snapshot = git::snapshot(repo, since)?;
+        let files: HashMap<&Path, _> = snapshot.files.iter().map(|f| (f.path.as_path(), f)).collect();
+        let paths: Vec<PathBuf> = snapshot.files.iter().map(|f| f.path.clone()).collect();
+        self.ingest_files_with(
+            &paths,
+            |path| {
+                files.get(path).map(|file| Provenance {
+                    source_path: None,
+                    key: "git",
+                    value: snapshot.metadata(file),
+                })
+            },
+            on_result,
+        )?;
+        Ok(snapshot)
+    }
+
+    /// Download an arXiv paper (ID, `arXiv:` ID or URL) and ingest its PDF.
+    ///
+    /// The item is recorded under the paper's abstract page, titled as the
+    /// paper, with its authors, abstract and categories in its `arxiv`
+    /// metadata. The abstract is the initial summary, so enrichment doesn't
+    /// write another; a later version of the paper updates the same item.
+    pub fn ingest_arxiv(&self, id: &str) -> IngestResult<IngestResult2> {
+        let fetcher = PageFetcher::new(ARXIV_TIMEOUT)?;
+        let paper = arxiv::fetch(&fetcher, id)?;
+        let source = paper.abs_url();
+        self.events.emit(Event::IngestStarted {
+            path: source.clone(),
+            index: 1,
+            total: 1,
+        });
+
+        let dir = tempfile::tempdir()?;
+        let pdf = dir.path().join(format!("{}.pdf", paper.id.replace('/', "_")));
+        let provenance = Provenance {
+            source_path: Some(source.clone()),
+            key: "arxiv",
+            value: paper.metadata(),
+        };
+        let result = fetcher
+            .download(&paper.pdf_url())
+            .and_then(|bytes| Ok(std::fs::write(&pdf, bytes)?))
+            .and_then(|()| self.prepare_file(&pdf, Some(&provenance)))
+            .and_then(|mut prepared| {
+                if let PreparedFile::Parsed { item, .. } = &mut prepared {
+                    item.title = paper.title.clone();
+                    if item.summary.is_none() && !paper.abstract_text.is_empty() {
+                        item.summary = Some(paper.abstract_text.clone());
+                    }
+                }
+                self.store_prepared(prepared)
+            });
+        if let Err(ref e) = result {
+            self.events.emit(Event::ingest_failed(Path::new(&source), e));
+        }
+        result
+    }
+
+    /// The item representing an archive, listing its members as content.
+    fn archive_item(&self, path: &Path, members: &[ArchiveMember]) -> IngestResult<Item> {
         let path = path.canonicalize()?;
         let path_str = path.to_string_lossy().to_string();
+        let content_hash = self.hash_file(&path)?;
+        if let Some(existing) = self.db.find_item_by_hash(&content_hash)? {
+            return Ok(existing);
+        }
+
+        let entries: Vec<&str> = members.iter().map(|m| m.entry.as_str()).collect();
+        let metadata = serde_json::json!({ "archive": { "members": entries } });
+        let item = match self.db.find_item_by_path(&path_str)? {
+            Some(mut item) => {
+                item.content_hash = Some(content_hash);
+                item.processed_at = Some(Utc::now());
+                item.metadata = metadata;
+                self.db.update_item(&item)?;
+                item
+            }
+            None => {
+                let title = path.file_name().and_then(|n| n.to_str()).unwrap_or("Archive");
+                let mut item = Item::new(ItemType::Document, title)
+                    .with_source_path(&path_str)
+                    .with_content_hash(&content_hash);
+                item.processed_at = Some(Utc::now());
+                item.metadata = metadata;
+                self.db.create_item(&item)?;
+                item
+            }
+        };
+        self.db.set_item_content(&item.id, &entries.join("\n"))?;
+        Ok(item)
+    }
+
+    /// Write a batch of new files in one transaction, then enrich each.
+    fn flush_batch<F>(&self, batch: &mut Vec<(PathBuf, PreparedFile)>, on_result: &mut F)
+    where
+        F: FnMut(&Path, IngestResult<IngestResult2>),
+    {
+        if batch.is_empty() {
+            return;
+        }
+
+        let entries: Vec<(PathBuf, Item, Vec<Chunk>)> = batch
+            .drain(..)
+            .filter_map(|(path, prepared)| match prepared {
+                PreparedFile::Parsed { item, chunks: Some(chunks), .. } => Some((path, item, chunks)),
+                _ => None,
+            })
+            .collect();
+        let rows: Vec<(Item, Vec<Chunk>)> = entries
+            .iter()
+            .map(|(_, item, chunks)| (item.clone(), chunks.clone()))
+            .collect();
+
+        if let Err(e) = self.db.create_items_with_chunks(&rows) {
+            // Fall back to one transaction per file so one bad row doesn't sink the rest
+            warn!("Batch write failed ({}), retrying files individually", e);
+            for (path, item, chunks) in entries {
+                let prepared = PreparedFile::Parsed {
+                    item,
+                    was_update: false,
+                    chunks: Some(chunks),
+                    path: path.clone(),
+                    supersedes: None,
+                };
+                on_result(&path, self.store_prepared(prepared));
+            }
+            return;
+        }
+
+        for (path, item, chunks) in entries {
+            self.events.emit(Event::ingest_chunked(&item, chunks.len(), 0));
+            let item = self.enrich(item, &chunks);
+            self.finish(&item, chunks.len());
+            info!("Successfully ingested: {} ({} chunks)", path.display(), chunks.len());
+            on_result(
+                &path,
+                Ok(IngestResult2 {
+                    item,
+                    chunk_count: chunks.len(),
+                    chunks,
+                    was_update: false,
+                }),
+            );
+        }
+    }
+
+    /// Turn a prepared file into a duplicate if its content was stored
+    /// after it was hashed.
+    fn recheck(&self, prepared: PreparedFile) -> IngestResult<PreparedFile> {
+        if let Some(hash) = prepared.content_hash() {
+            if let Some(existing) = self.db.find_item_by_hash(hash)? {
+                return Ok(PreparedFile::Unchanged(existing));
+            }
+        }
+        Ok(prepared)
+    }
+
+    /// The CPU-bound half of ingestion: hash, parse and chunk a file.
+    ///
+    /// Reads the database to detect duplicates and updates but never writes,
+    /// so it is safe to run on many threads at once.
+    ///
+    /// With `provenance`, the file is recorded under its source path (e.g.
+    /// its path in an archive, rather than where it was unpacked) and the
+    /// provenance is added to its metadata.
+    fn prepare_file(&self, path: &Path, provenance: Option<&Provenance>) -> IngestResult<PreparedFile> {
+        let path = path.canonicalize()?;
+        let path_str = provenance
+            .and_then(|p| p.source_path.clone())
+            .unwrap_or_else(|| path.to_string_lossy().to_string());
 
         info!("Ingesting file: {}", path_str);
 
@@ -69,35 +554,50 @@ impl Ingestor {
         // Check if already processed with same hash
         if let Ok(Some(existing)) = self.db.find_item_by_hash(&content_hash) {
             debug!("File already processed with same hash: {}", path_str);
-            let chunks = self.db.get_chunks_by_item(&existing.id)?;
-            return Ok(IngestResult2 {
-                item: existing,
-                chunks,
-                was_update: false,
-            });
+            return Ok(PreparedFile::Unchanged(existing));
         }
 
-        // Check if path was previously ingested
-        let existing_item = self.db.find_item_by_path(&path_str)?;
+        // Check if path was previously ingested. Notes and documents are
+        // edited in place, so new content updates the item. Recordings and
+        // images at a known path are new captures reusing the filename
+        // (e.g. `recording.mov`): they get their own item, linked to the
+        // one they supersede, so the earlier content is kept.
+        let (existing_item, supersedes) = match self.db.find_item_by_path(&path_str)? {
+            Some(previous)
+                if matches!(item_type, ItemType::Video | ItemType::Audio | ItemType::Image) =>
+            {
+                debug!("New content at {}, superseding {}", path_str, previous.id);
+                (None, Some(previous.id))
+            }
+            existing => (existing, None),
+        };
         let was_update = existing_item.is_some();
 
-        // If updating, delete old chunks
-        if let Some(ref old_item) = existing_item {
-            debug!("Updating existing item: {}", old_item.id);
-            self.db.delete_chunks_by_item(&old_item.id)?;
-        }
-
-        // Parse the document (special handling for videos)
-        let (parsed, video_segments) = self.parse_file(&path, item_type)?;
+        // Parse the document (special handling for videos). Large text files
+        // are only described here and chunked while writing.
+        let streamed = match item_type {
+            ItemType::Video | ItemType::Audio | ItemType::Image => None,
+            _ => parsers::streaming_document(&path)?,
+        };
+        let is_streamed = streamed.is_some();
+        let (parsed, video_segments) = match streamed {
+            Some(doc) => (doc, None),
+            None => {
+                // Media parsing runs external tools; only a few at a time
+                let media = matches!(item_type, ItemType::Video | ItemType::Audio | ItemType::Image)
+                    || path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf"));
+                let _slot = media.then(|| self.media_jobs.acquire());
+                self.parse_file(&path, item_type)?
+            }
+        };
 
-        // Create or update item
-        let item = if let Some(old_item) = existing_item {
+        let mut item = if let Some(old_item) = existing_item {
+            debug!("Updating existing item: {}", old_item.id);
             let mut item = old_item;
             item.title = parsed.title.unwrap_or_else(|| item.title.clone());
             item.content_hash = Some(content_hash);
             item.processed_at = Some(Utc::now());
             item.metadata = parsed.metadata;
-            self.db.update_item(&item)?;
             item
         } else {
             let title = parsed.title.unwrap_or_else(|| {
@@ -107,68 +607,519 @@ impl Ingestor {
                     .to_string()
             });
 
-            let item = Item::new(item_type, title)
+            let mut item = Item::new(item_type, title)
                 .with_source_path(&path_str)
                 .with_content_hash(&content_hash);
-
-            let mut item = item;
             item.processed_at = Some(Utc::now());
             item.metadata = parsed.metadata;
-
-            self.db.create_item(&item)?;
             item
         };
+        if let Some(provenance) = provenance {
+            if !item.metadata.is_object() {
+                item.metadata = serde_json::json!({});
+            }
+            item.metadata[provenance.key] = provenance.value.clone();
+        }
+        self.events.emit(Event::IngestParsed {
+            path: path_str,
+            item_type: item_type.as_str().to_string(),
+            title: item.title.clone(),
+        });
+        if item_type == ItemType::Video {
+            self.create_thumbnail(&path, &mut item);
+        }
 
         // Create chunks (use transcript segments for videos if available)
-        let chunks = if let Some(segments) = video_segments {
+        let chunks = if is_streamed {
+            None
+        } else if let Some(segments) = video_segments {
             // Convert TranscriptSegment to tuple format for chunker
             let segment_tuples: Vec<(String, f64, f64)> = segments
                 .iter()
-                .map(|s| (s.text.clone(), s.start, s.end))
+                .map(|s| {
+                    let text = match s.speaker {
+                        Some(ref speaker) => format!("[{}] {}", speaker, s.text),
+                        None => s.text.clone(),
+                    };
+                    (text, s.start, s.end)
+                })
                 .collect();
-            self.chunker.chunk_transcript(&item.id, &segment_tuples)
+            Some(self.chunker.chunk_transcript(&item.id, &segment_tuples))
         } else {
-            self.chunker.chunk_text(&item.id, &parsed.content)
+            let mut chunks = if was_update {
+                let previous = self.db.get_chunks_by_item(&item.id)?;
+                self.chunker.rechunk_text(&item.id, &parsed.content, &previous)
+            } else {
+                self.chunker.chunk_text(&item.id, &parsed.content)
+            };
+            chunker::assign_pages(&mut chunks, &parsed.content, &parsed.pages);
+            Some(chunks)
         };
-        debug!("Created {} chunks for item {}", chunks.len(), item.id);
-
-        // Store chunks
-        self.db.create_chunks(&chunks)?;
 
-        // AI enrichment (summary + auto-tagging)
-        if let Ok(config) = olal_config::Config::load() {
-            let combined: String = chunks.iter().map(|c| c.content.as_str()).collect::<Vec<_>>().join(" ");
-            let mut item = item.clone();
-            if let Err(e) = crate::ai_enrich::enrich_item(&self.db, &mut item, &combined, &config) {
-                warn!("AI enrichment failed: {}", e);
-            }
-            // Use the enriched item
-            info!(
-                "Successfully ingested: {} ({} chunks)",
-                path_str,
-                chunks.len()
-            );
+        Ok(PreparedFile::Parsed {
+            item,
+            was_update,
+            chunks,
+            path,
+            supersedes,
+        })
+    }
 
-            return Ok(IngestResult2 {
+    /// The write half of ingestion: store a prepared file and enrich it.
+    fn store_prepared(&self, prepared: PreparedFile) -> IngestResult<IngestResult2> {
+        let (item, was_update, chunks, path, supersedes) = match prepared {
+            PreparedFile::Unchanged(existing) => {
+                let chunks = self.db.get_chunks_by_item(&existing.id)?;
+                return Ok(IngestResult2 {
+                    item: existing,
+                    chunk_count: chunks.len(),
+                    chunks,
+                    was_update: false,
+                });
+            }
+            PreparedFile::Parsed {
                 item,
-                chunks,
                 was_update,
-            });
+                chunks,
+                path,
+                supersedes,
+            } => (item, was_update, chunks, path, supersedes),
+        };
+
+        // If updating a streamed file, replace old chunks, keeping their
+        // embeddings for unchanged text
+        let saved = if was_update {
+            self.db.update_item(&item)?;
+            if chunks.is_none() {
+                let saved = self.db.save_embeddings(&item.id)?;
+                self.db.delete_chunks_by_item(&item.id)?;
+                Some(saved)
+            } else {
+                None
+            }
+        } else {
+            self.db.create_item(&item)?;
+            None
+        };
+        if let Some(previous) = supersedes {
+            self.db
+                .create_link(&Link::new(item.id.clone(), previous, LinkType::Supersedes))?;
+        }
+
+        // Store chunks; streamed files are chunked as they're read
+        let mut kept = 0;
+        let (chunks, chunk_count) = match chunks {
+            Some(mut chunks) if was_update => {
+                let changes = self.db.replace_chunks(&item.id, &mut chunks)?;
+                debug!(
+                    "Kept {} unchanged chunks, added {}, removed {}",
+                    changes.kept, changes.added, changes.removed
+                );
+                kept = changes.kept;
+                let count = chunks.len();
+                (chunks, count)
+            }
+            Some(chunks) => {
+                self.db.create_chunks(&chunks)?;
+                let count = chunks.len();
+                (chunks, count)
+            }
+            None => {
+                let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+                let mut first_batch = Vec::new();
+                let count = self.chunker.chunk_reader(&item.id, reader, |batch| {
+                    self.db.create_chunks(&batch)?;
+                    if first_batch.is_empty() {
+                        first_batch = batch;
+                    }
+                    Ok::<(), IngestError>(())
+                })?;
+                (first_batch, count)
+            }
+        };
+        debug!("Created {} chunks for item {}", chunk_count, item.id);
+        if let Some(saved) = saved {
+            let restored = self.db.restore_embeddings(&item.id, &saved)?;
+            debug!("Kept {} embeddings of unchanged chunks", restored);
         }
+        self.events.emit(Event::ingest_chunked(&item, chunk_count, kept));
 
+        let item = self.enrich(item, &chunks);
+        self.finish(&item, chunk_count);
         info!(
             "Successfully ingested: {} ({} chunks)",
-            path_str,
-            chunks.len()
+            path.display(),
+            chunk_count
         );
 
         Ok(IngestResult2 {
             item,
             chunks,
+            chunk_count,
             was_update,
         })
     }
 
+    /// AI enrichment (summary + auto-tagging), if configured.
+    fn enrich(&self, item: Item, chunks: &[Chunk]) -> Item {
+        let combined: String = chunks.iter().map(|c| c.content.as_str()).collect::<Vec<_>>().join(" ");
+        self.enrich_text(item, &combined)
+    }
+
+    /// AI enrichment of an item from its whole text.
+    fn enrich_text(&self, mut item: Item, content: &str) -> Item {
+        if let Ok(config) = olal_config::Config::load() {
+            match crate::ai_enrich::enrich_item(&self.db, &mut item, content, &config) {
+                Ok(()) => self.events.emit(Event::IngestEnriched {
+                    item_id: item.id.clone(),
+                    summarized: item.summary.is_some(),
+                    tag_count: self.db.get_item_tags(&item.id).map_or(0, |tags| tags.len()),
+                }),
+                Err(e) => warn!("AI enrichment failed: {}", e),
+            }
+        }
+        item
+    }
+
+    /// Steps after an item is stored and enriched: flag it for review if
+    /// needed, auto-embed, then announce it.
+    fn finish(&self, item: &Item, chunk_count: usize) {
+        self.flag_for_review(item);
+        self.auto_embed(item);
+        self.events.emit(Event::item_processed(item, chunk_count));
+    }
+
+    /// Tag items with low-confidence OCR text for review.
+    fn flag_for_review(&self, item: &Item) {
+        if item.metadata["ocr_low_confidence"].as_bool() != Some(true) {
+            return;
+        }
+        if let Err(e) = self.db.tag_item(&item.id, OCR_REVIEW_TAG) {
+            warn!("Failed to tag {} for review: {}", item.id, e);
+        }
+    }
+
+    /// Embed the item's new chunks, if `processing.auto_embed` is on.
+    ///
+    /// Failures only log a warning: unembedded chunks are picked up again
+    /// by the queue worker or `olal embed --all`.
+    fn auto_embed(&self, item: &Item) {
+        let embedder = self.embedder.get_or_init(|| {
+            let config = olal_config::Config::load().ok()?;
+            if !config.processing.auto_embed {
+                return None;
+            }
+            Embedder::from_config(&config)
+                .map_err(|e| warn!("Auto-embed unavailable: {}", e))
+                .ok()
+        });
+
+        if let Some(embedder) = embedder {
+            match embedder.embed_item(&self.db, &item.id) {
+                Ok((_, 0)) => {}
+                Ok((_, failed)) => warn!("{} chunks of {} left for embedding retry", failed, item.id),
+                Err(e) => warn!("Auto-embed failed for {}: {}", item.id, e),
+            }
+        }
+    }
+
+    /// Ingest text that doesn't come from a file, e.g. piped from stdin.
+    ///
+    /// Identical content is recognized by hash and returns the existing item.
+    /// `title` defaults to the first line of the content.
+    pub fn ingest_text(
+        &self,
+        content: &str,
+        item_type: ItemType,
+        title: Option<&str>,
+        metadata: serde_json::Value,
+    ) -> IngestResult<IngestResult2> {
+        if content.trim().is_empty() {
+            return Err(IngestError::ProcessingError("No content to ingest".to_string()));
+        }
+        if matches!(item_type, ItemType::Video | ItemType::Audio | ItemType::Image) {
+            return Err(IngestError::UnsupportedFileType(format!(
+                "{} content can't be read as text",
+                item_type
+            )));
+        }
+
+        let content_hash = hex::encode(Sha256::digest(content.as_bytes()));
+        if let Some(existing) = self.db.find_item_by_hash(&content_hash)? {
+            debug!("Text already ingested as {}", existing.id);
+            let chunks = self.db.get_chunks_by_item(&existing.id)?;
+            return Ok(IngestResult2 {
+                item: existing,
+                chunk_count: chunks.len(),
+                chunks,
+                was_update: false,
+            });
+        }
+
+        let title = title
+            .map(str::to_string)
+            .unwrap_or_else(|| title_from_text(content));
+        let mut item = Item::new(item_type, title).with_content_hash(&content_hash);
+        item.processed_at = Some(Utc::now());
+        item.metadata = metadata;
+        self.db.create_item(&item)?;
+        self.db.set_item_content(&item.id, content)?;
+
+        let chunks = self.chunker.chunk_text(&item.id, content);
+        self.db.create_chunks(&chunks)?;
+        self.events.emit(Event::ingest_chunked(&item, chunks.len(), 0));
+
+        let item = self.enrich_text(item, content);
+        self.finish(&item, chunks.len());
+
+        info!("Ingested text as {} ({} chunks)", item.id, chunks.len());
+        Ok(IngestResult2 {
+            item,
+            chunk_count: chunks.len(),
+            chunks,
+            was_update: false,
+        })
+    }
+
+    /// Replace the text of an item that has no source file, e.g. an edited note.
+    ///
+    /// Stores the new content, then re-chunks the edited regions and
+    /// re-enriches the item. Unchanged chunks keep their IDs and
+    /// embeddings; callers embed the rest.
+    pub fn replace_text(&self, item: &Item, content: &str) -> IngestResult<IngestResult2> {
+        if content.trim().is_empty() {
+            return Err(IngestError::ProcessingError("No content to store".to_string()));
+        }
+        if item.source_path.is_some() {
+            return Err(IngestError::ProcessingError(
+                "Item has a source file; edit the file and re-ingest it instead".to_string(),
+            ));
+        }
+
+        self.replace_content(item.clone(), content)
+    }
+
+    /// Store `content` as the whole text of `item`, re-chunking and
+    /// re-enriching it.
+    fn replace_content(&self, mut item: Item, content: &str) -> IngestResult<IngestResult2> {
+        item.content_hash = Some(hex::encode(Sha256::digest(content.as_bytes())));
+        item.processed_at = Some(Utc::now());
+        self.db.update_item(&item)?;
+        self.db.set_item_content(&item.id, content)?;
+
+        let previous = self.db.get_chunks_by_item(&item.id)?;
+        let mut chunks = self.chunker.rechunk_text(&item.id, content, &previous);
+        let changes = self.db.replace_chunks(&item.id, &mut chunks)?;
+        self.events.emit(Event::ingest_chunked(&item, chunks.len(), changes.kept));
+
+        let item = self.enrich(item, &chunks);
+        self.finish(&item, chunks.len());
+
+        info!("Updated text of {} ({} chunks)", item.id, chunks.len());
+        Ok(IngestResult2 {
+            item,
+            chunk_count: chunks.len(),
+            chunks,
+            was_update: true,
+        })
+    }
+
+    /// Import bookmarks as bookmark items tagged with their folder path and
+    /// tags.
+    ///
+    /// An item's content is its title and URL until the page is fetched.
+    /// With `fetch`, items are marked for [`Ingestor::fetch_bookmark`],
+    /// which `olal worker` runs in the background. URLs already in the
+    /// knowledge base are skipped.
+    pub fn import_bookmarks(&self, bookmarks: &[Bookmark], fetch: bool) -> IngestResult<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        let mut seen = HashSet::new();
+        let mut batch = Vec::new();
+        for bookmark in bookmarks {
+            if !seen.insert(bookmark.url.as_str()) || self.db.find_item_by_path(&bookmark.url)?.is_some() {
+                summary.skipped += 1;
+                continue;
+            }
+
+            let mut item = Item::new(ItemType::Bookmark, bookmark.title.as_str()).with_source_path(&bookmark.url);
+            item.processed_at = Some(Utc::now());
+            item.metadata = serde_json::json!({
+                "url": bookmark.url,
+                "folder": bookmark.folder_tag(),
+                "bookmarked_at": bookmark.added_at.map(|at| at.to_rfc3339()),
+            });
+            if fetch {
+                item.metadata["fetch"] = "pending".into();
+            }
+            let content = format!("{}\n{}", bookmark.title, bookmark.url);
+            let chunks = self.chunker.chunk_text(&item.id, &content);
+            let tags: Vec<String> = bookmark.folder_tag().into_iter().chain(bookmark.tags.iter().cloned()).collect();
+            batch.push((item, chunks, content, tags));
+        }
+
+        for group in batch.chunks(WRITE_BATCH * 16) {
+            let items: Vec<(Item, Vec<Chunk>)> =
+                group.iter().map(|(item, chunks, _, _)| (item.clone(), chunks.clone())).collect();
+            self.db.create_items_with_chunks(&items)?;
+            for (item, _, content, tags) in group {
+                self.db.set_item_content(&item.id, content)?;
+                for tag in tags {
+                    self.db.tag_item(&item.id, tag)?;
+                }
+            }
+            summary.added += group.len();
+        }
+        info!("Imported {} bookmarks ({} skipped)", summary.added, summary.skipped);
+        Ok(summary)
+    }
+
+    /// Fetch a bookmark's page and make its text the item's content.
+    ///
+    /// The bookmark's `fetch` metadata becomes "done", or "failed" with a
+    /// `fetch_error`, so it isn't fetched again either way.
+    pub fn fetch_bookmark(&self, item: &Item, fetcher: &PageFetcher) -> IngestResult<IngestResult2> {
+        let url = item
+            .source_path
+            .clone()
+            .ok_or_else(|| IngestError::ProcessingError(format!("Bookmark {} has no URL", item.id)))?;
+        let mut item = item.clone();
+        if !item.metadata.is_object() {
+            item.metadata = serde_json::json!({});
+        }
+        item.metadata["fetched_at"] = Utc::now().to_rfc3339().into();
+
+        let page = fetcher.fetch(&url).and_then(|page| {
+            if page.text.trim().is_empty() {
+                Err(IngestError::ProcessingError(format!("No text found at {}", url)))
+            } else {
+                Ok(page)
+            }
+        });
+        match page {
+            Ok(page) => {
+                if let Some(title) = page.title.filter(|_| item.title == url) {
+                    item.title = title;
+                }
+                item.metadata["fetch"] = "done".into();
+                let content = format!("{}\n{}\n\n{}", item.title, url, page.text);
+                self.replace_content(item, &content)
+            }
+            Err(e) => {
+                item.metadata["fetch"] = "failed".into();
+                item.metadata["fetch_error"] = e.to_string().into();
+                self.db.update_item(&item)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Import Kindle highlights as one document per book, with a chunk per
+    /// highlight holding its text, note and location.
+    ///
+    /// Each highlight's hash, location, page, date and note are kept, in
+    /// chunk order, in the item's `highlights` metadata, so importing the
+    /// same clippings file again only adds the highlights made since. A
+    /// highlight starting where a stored one does is that highlight, extended
+    /// or with a note added, and replaces it. Counts in the summary are of
+    /// highlights.
+    pub fn import_kindle(&self, books: &[Book]) -> IngestResult<ImportSummary> {
+        let mut summary = ImportSummary::default();
+        for book in books {
+            let existing = self.db.find_item_by_path(&book.source_path())?;
+
+            // (metadata, chunk text) of each highlight, stored ones first
+            let mut highlights: Vec<(serde_json::Value, String)> = match &existing {
+                Some(item) => {
+                    let stored = item.metadata["highlights"].as_array().cloned().unwrap_or_default();
+                    let chunks = self.db.get_chunks_by_item(&item.id)?;
+                    stored.into_iter().zip(chunks.into_iter().map(|c| c.content)).collect()
+                }
+                None => Vec::new(),
+            };
+            let (mut added, mut updated) = (0, 0);
+            for highlight in &book.highlights {
+                let hash = highlight.hash(book);
+                let start = highlight.location.map(|(start, _)| u64::from(start));
+                let stored = highlights.iter().position(|(meta, _)| match start {
+                    Some(start) => meta["location"][0].as_u64() == Some(start),
+                    None => meta["hash"] == hash.as_str(),
+                });
+
+                let mut highlight = highlight.clone();
+                if let Some(i) = stored {
+                    // Imports from before notes were kept have no `note` key
+                    let meta = &mut highlights[i].0;
+                    if meta.get("note").is_none() {
+                        meta["note"] = serde_json::Value::Null;
+                    }
+                    // A clippings file from another device may lack the note
+                    if highlight.note.is_none() {
+                        highlight.note = meta["note"].as_str().map(str::to_string);
+                    }
+                }
+                let entry = (
+                    serde_json::json!({
+                        "hash": hash,
+                        "location": highlight.location.map(|(start, end)| [start, end]),
+                        "page": highlight.page,
+                        "added_at": highlight.added_at.map(|at| at.to_string()),
+                        "note": highlight.note,
+                    }),
+                    highlight.to_text(),
+                );
+                match stored {
+                    Some(i) if highlights[i] == entry => summary.skipped += 1,
+                    Some(i) => {
+                        highlights[i] = entry;
+                        updated += 1;
+                    }
+                    None => {
+                        highlights.push(entry);
+                        added += 1;
+                    }
+                }
+            }
+            if added == 0 && updated == 0 {
+                continue;
+            }
+            summary.added += added;
+            summary.updated += updated;
+            highlights.sort_by_key(|(meta, _)| meta["location"][0].as_u64().unwrap_or(u64::MAX));
+
+            let is_new = existing.is_none();
+            let mut item = existing.unwrap_or_else(|| {
+                Item::new(ItemType::Document, book.title.as_str()).with_source_path(book.source_path())
+            });
+            if !item.metadata.is_object() {
+                item.metadata = serde_json::json!({});
+            }
+            item.metadata["author"] = book.author.clone().into();
+            item.metadata["highlights"] = highlights.iter().map(|(meta, _)| meta.clone()).collect();
+            let content = highlights.iter().map(|(_, text)| text.as_str()).collect::<Vec<_>>().join("\n\n");
+            item.content_hash = Some(hex::encode(Sha256::digest(content.as_bytes())));
+            item.processed_at = Some(Utc::now());
+
+            let mut chunks: Vec<Chunk> = highlights
+                .iter()
+                .enumerate()
+                .map(|(i, (_, text))| Chunk::new(item.id.clone(), i as i32, text.as_str()))
+                .collect();
+            if is_new {
+                self.db.create_items_with_chunks(&[(item.clone(), chunks.clone())])?;
+            } else {
+                self.db.update_item(&item)?;
+                self.db.replace_chunks(&item.id, &mut chunks)?;
+            }
+            self.db.set_item_content(&item.id, &content)?;
+            self.finish(&item, chunks.len());
+        }
+        info!(
+            "Imported {} Kindle highlights ({} updated, {} skipped)",
+            summary.added, summary.updated, summary.skipped
+        );
+        Ok(summary)
+    }
+
     /// Queue a file for processing.
     pub fn queue_file(&self, path: &Path, priority: i32) -> IngestResult<QueueItem> {
         let path = path.canonicalize()?;
@@ -202,21 +1153,25 @@ impl Ingestor {
 
     /// Process the next item in the queue.
     pub fn process_next(&self) -> IngestResult<Option<IngestResult2>> {
-        let queue_item = match self.db.dequeue()? {
-            Some(item) => item,
-            None => return Ok(None),
-        };
+        match self.db.dequeue()? {
+            Some(queue_item) => self.process_entry(&queue_item).map(Some),
+            None => Ok(None),
+        }
+    }
 
+    /// Process a dequeued entry, marking it completed or failed.
+    pub fn process_entry(&self, queue_item: &QueueItem) -> IngestResult<IngestResult2> {
         let path = Path::new(&queue_item.source_path);
 
         match self.ingest_file(path) {
             Ok(result) => {
                 self.db.mark_completed(&queue_item.id)?;
-                Ok(Some(result))
+                Ok(result)
             }
             Err(e) => {
                 warn!("Failed to process {}: {}", queue_item.source_path, e);
                 self.db.mark_failed(&queue_item.id, &e.to_string())?;
+                self.events.emit(Event::queue_failed(queue_item, e.to_string()));
                 Err(e)
             }
         }
@@ -226,11 +1181,8 @@ impl Ingestor {
     pub fn process_all(&self) -> IngestResult<Vec<IngestResult2>> {
         let mut results = Vec::new();
 
-        loop {
-            match self.process_next()? {
-                Some(result) => results.push(result),
-                None => break,
-            }
+        while let Some(result) = self.process_next()? {
+            results.push(result);
         }
 
         Ok(results)
@@ -242,7 +1194,7 @@ impl Ingestor {
         dir: &Path,
         item_type_filter: Option<ItemType>,
     ) -> IngestResult<Vec<IngestResult2>> {
-        let mut results = Vec::new();
+        let mut paths = Vec::new();
 
         for entry in walkdir::WalkDir::new(dir)
             .follow_links(true)
@@ -280,13 +1232,7 @@ impl Ingestor {
                             continue;
                         }
                     }
-
-                    match self.ingest_file(path) {
-                        Ok(result) => results.push(result),
-                        Err(e) => {
-                            warn!("Failed to ingest {:?}: {}", path, e);
-                        }
-                    }
+                    paths.push(path.to_path_buf());
                 }
                 None => {
                     debug!("Skipping unsupported file: {:?}", path);
@@ -294,6 +1240,12 @@ impl Ingestor {
             }
         }
 
+        let mut results = Vec::new();
+        self.ingest_files(&paths, |path, result| match result {
+            Ok(result) => results.push(result),
+            Err(e) => warn!("Failed to ingest {:?}: {}", path, e),
+        })?;
+
         Ok(results)
     }
 
@@ -306,8 +1258,20 @@ impl Ingestor {
     ) -> IngestResult<(ParsedDocument, Option<Vec<TranscriptSegment>>)> {
         match item_type {
             ItemType::Video => {
+                // Process the video, reading on-screen text too if OCR is on
+                let config = olal_config::Config::load().unwrap_or_default();
+                let processing = &config.processing;
+                let mut parser = VideoParser::new(&processing.whisper_model)
+                    .with_backend(transcription_backend(processing))
+                    .with_diarization(processing.diarize)
+                    .with_language(transcription_language(&config, path))
+                    .with_preprocessing(audio_preprocessing(processing));
+                if processing.ocr_enabled {
+                    parser = parser.with_frame_ocr(processing.ocr_interval_seconds);
+                }
+
                 // Check if video processing tools are available
-                let tools = VideoParser::tools_available();
+                let tools = parser.tools_available();
                 if !tools.all_available() {
                     if let Some(msg) = tools.missing_message() {
                         warn!("{}", msg);
@@ -331,15 +1295,21 @@ impl Ingestor {
                     ));
                 }
 
-                // Process the video
-                let parser = VideoParser::with_default_model();
                 let result = parser.parse(path)?;
 
                 Ok((result.document, Some(result.segments)))
             }
             ItemType::Audio => {
+                let config = olal_config::Config::load().unwrap_or_default();
+                let processing = &config.processing;
+                let parser = AudioParser::new(&processing.whisper_model)
+                    .with_backend(transcription_backend(processing))
+                    .with_diarization(processing.diarize)
+                    .with_language(transcription_language(&config, path))
+                    .with_preprocessing(audio_preprocessing(processing));
+
                 // Check if audio processing tools are available
-                let tools = AudioParser::tools_available();
+                let tools = parser.tools_available();
                 if !tools.all_available() {
                     if let Some(msg) = tools.missing_message() {
                         warn!("{}", msg);
@@ -364,28 +1334,31 @@ impl Ingestor {
                 }
 
                 // Process the audio (transcribe directly)
-                let parser = AudioParser::with_default_model();
                 let result = parser.parse(path)?;
 
                 Ok((result.document, Some(result.segments)))
             }
-            ItemType::Image => {
-                // Images need OCR processing
-                let title = path
-                    .file_stem()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("Image")
-                    .to_string();
-
-                Ok((
-                    ParsedDocument::new(format!("Image file: {}", path.display()))
-                        .with_title(title)
-                        .with_metadata(serde_json::json!({
-                            "format": "image",
-                            "needs_ocr": true,
-                        })),
-                    None,
-                ))
+            ItemType::Image => Ok((self.parse_image(path)?, None)),
+            ItemType::Document if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("pdf")) => {
+                // Scanned PDFs are OCR'd unless OCR is turned off
+                let config = olal_config::Config::load().unwrap_or_default();
+                let parser = PdfParser::new().with_ocr(config.processing.ocr_enabled);
+                let describer = config
+                    .processing
+                    .describe_pdf_figures
+                    .then(|| self.describer(&config))
+                    .flatten();
+                let doc = match describer {
+                    Some(describer) => parser.parse_with_captions(path, &|image| {
+                        describer
+                            .describe(image, "")
+                            .map(|d| d.to_text())
+                            .map_err(|e| warn!("Failed to describe a figure in {:?}: {}", path, e))
+                            .ok()
+                    })?,
+                    None => parser.parse(path)?,
+                };
+                Ok((doc, None))
             }
             _ => {
                 // Use text-based parsers
@@ -394,16 +1367,156 @@ impl Ingestor {
         }
     }
 
-    /// Calculate SHA256 hash of a file.
+    /// OCR an image, then caption it with the vision model if it has
+    /// little or no text.
+    fn parse_image(&self, path: &Path) -> IngestResult<ParsedDocument> {
+        let config = olal_config::Config::load().unwrap_or_default();
+        let ocr = config.processing.ocr_enabled && ImageParser::tools_available();
+        if config.processing.ocr_enabled && !ocr {
+            warn!("Missing tool: tesseract. Install with:\n  brew install tesseract");
+        }
+
+        let title = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("Image")
+            .to_string();
+        let parsed = if ocr {
+            ImageParser::new()
+                .parse(path)
+                .map_err(|e| warn!("{}", e))
+                .ok()
+        } else {
+            None
+        };
+        // A placeholder until OCR can run
+        let mut doc = parsed.unwrap_or_else(|| {
+            ParsedDocument::new(String::new())
+                .with_title(title)
+                .with_metadata(serde_json::json!({
+                    "format": "image",
+                    "needs_ocr": true,
+                }))
+        });
+
+        if config.processing.describe_images && doc.content.split_whitespace().count() < DESCRIBE_BELOW_WORDS {
+            if let Some(describer) = self.describer(&config) {
+                match describer.describe(path, &doc.content) {
+                    Ok(description) => {
+                        doc.content = [doc.content.trim(), &description.to_text()]
+                            .join("\n\n")
+                            .trim()
+                            .to_string();
+                        doc.metadata["caption"] = description.caption.into();
+                        doc.metadata["objects"] = description.objects.into();
+                        doc.metadata["vision_model"] = describer.model().into();
+                    }
+                    Err(e) => warn!("Failed to describe {:?}: {}", path, e),
+                }
+            }
+        }
+
+        if doc.content.is_empty() {
+            doc.content = format!("Image file: {}", path.display());
+        }
+        Ok(doc)
+    }
+
+    /// The vision model, if it's available.
+    fn describer(&self, config: &olal_config::Config) -> Option<&ImageDescriber> {
+        self.describer
+            .get_or_init(|| {
+                ImageDescriber::from_config(config)
+                    .map_err(|e| warn!("Image descriptions unavailable: {}", e))
+                    .ok()
+            })
+            .as_ref()
+    }
+
+    /// Save a representative frame of a processed video as its thumbnail
+    /// and record the path in the item's metadata.
+    fn create_thumbnail(&self, path: &Path, item: &mut Item) {
+        // Only videos that were actually probed know their duration
+        let Some(duration) = item.metadata["duration"].as_f64() else {
+            return;
+        };
+        let Some(paths) = olal_config::AppPaths::new() else {
+            return;
+        };
+
+        let file = paths.thumbnail_file(&item.id);
+        let result = std::fs::create_dir_all(paths.thumbnails_dir())
+            .map_err(|e| e.to_string())
+            .and_then(|_| extract_thumbnail(path, &file, duration).map_err(|e| e.to_string()));
+        match result {
+            Ok(()) => item.metadata["thumbnail"] = file.to_string_lossy().into(),
+            Err(e) => warn!("Failed to create thumbnail for {:?}: {}", path, e),
+        }
+    }
+
+    /// Calculate SHA256 hash of a file, streaming it so large recordings
+    /// aren't loaded into memory.
     fn hash_file(&self, path: &Path) -> IngestResult<String> {
-        let content = std::fs::read(path)?;
+        let mut file = std::fs::File::open(path)?;
         let mut hasher = Sha256::new();
-        hasher.update(&content);
+        std::io::copy(&mut file, &mut hasher)?;
         let result = hasher.finalize();
         Ok(hex::encode(result))
     }
 }
 
+/// The transcription backend named by `processing.whisper_backend`.
+fn transcription_backend(processing: &olal_config::ProcessingConfig) -> Box<dyn TranscriptionBackend> {
+    olal_process::backend(&processing.whisper_backend).unwrap_or_else(|| {
+        warn!(
+            "Unknown transcription backend '{}' (expected one of: {}), using whisper",
+            processing.whisper_backend,
+            olal_process::BACKENDS.join(", ")
+        );
+        Box::new(olal_process::WhisperCli)
+    })
+}
+
+/// The language to transcribe `path` as: the `watch.languages` entry for
+/// the closest watched directory above it, else
+/// `processing.transcription_language`. `None` detects it.
+fn transcription_language(config: &olal_config::Config, path: &Path) -> Option<String> {
+    config
+        .watch
+        .languages
+        .iter()
+        .map(|(dir, language)| (PathBuf::from(shellexpand::tilde(dir).as_ref()), language))
+        .filter(|(dir, _)| path.starts_with(dir))
+        .max_by_key(|(dir, _)| dir.components().count())
+        .map(|(_, language)| olal_process::language_setting(language))
+        .unwrap_or_else(|| olal_process::language_setting(&config.processing.transcription_language))
+}
+
+/// The audio cleanup `processing` asks for.
+fn audio_preprocessing(processing: &olal_config::ProcessingConfig) -> AudioPreprocessing {
+    AudioPreprocessing {
+        normalize: processing.normalize_audio,
+        trim_silence: processing.trim_silence,
+        silence_threshold_db: processing.silence_threshold_db,
+        min_silence_seconds: processing.min_silence_seconds,
+    }
+}
+
+/// A title from the first non-empty line of text, shortened to 60 characters.
+fn title_from_text(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(|l| l.trim().trim_start_matches('#').trim())
+        .find(|l| !l.is_empty())
+        .unwrap_or("Untitled");
+    let title: String = line.chars().take(60).collect();
+    if title.len() < line.len() {
+        format!("{}...", title)
+    } else {
+        title
+    }
+}
+
 // Add hex encoding utility
 mod hex {
     pub fn encode(bytes: impl AsRef<[u8]>) -> String {
@@ -418,8 +1531,41 @@ mod hex {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::io::Write;
-    use tempfile::{tempdir, NamedTempFile};
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_transcription_language() {
+        let mut config = olal_config::Config::default();
+        config.watch.languages.insert("/rec".to_string(), "es".to_string());
+        config.watch.languages.insert("/rec/tokyo".to_string(), "ja".to_string());
+        config.watch.languages.insert("/rec/mixed".to_string(), "auto".to_string());
+        config.processing.transcription_language = "en".to_string();
+
+        let language = |path: &str| transcription_language(&config, Path::new(path));
+        assert_eq!(language("/rec/a.mp3").as_deref(), Some("es"));
+        assert_eq!(language("/rec/tokyo/b.mp4").as_deref(), Some("ja"));
+        assert_eq!(language("/rec/mixed/c.m4a"), None);
+        assert_eq!(language("/recordings/d.mp3").as_deref(), Some("en"));
+    }
+
+    #[test]
+    fn test_job_slots_limit_concurrency() {
+        let slots = JobSlots::new(2);
+        let running = AtomicUsize::new(0);
+        let most = AtomicUsize::new(0);
+        std::thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _slot = slots.acquire();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(most.load(Ordering::SeqCst), 2);
+    }
 
     #[test]
     fn test_ingest_markdown_file() {
@@ -502,4 +1648,360 @@ mod tests {
         assert!(result2.was_update);
         assert_eq!(result1.item.id, result2.item.id);
     }
+
+    #[test]
+    fn test_update_keeps_unchanged_chunks() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::new(
+            db.clone(),
+            ChunkConfig {
+                chunk_size: 100,
+                chunk_overlap: 0,
+                min_chunk_size: 10,
+            },
+        );
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("journal.md");
+        let paragraphs: Vec<String> =
+            (1..=6).map(|n| format!("Entry {} of the journal, about half a chunk.", n)).collect();
+        std::fs::write(&file_path, paragraphs.join("\n\n")).unwrap();
+        let first = ingestor.ingest_file(&file_path).unwrap();
+        for chunk in &first.chunks {
+            db.store_embedding(&chunk.id, &[0.5, 0.25], "test-model").unwrap();
+        }
+
+        // Only the first entry changed
+        let mut edited = paragraphs.clone();
+        edited[0] = format!("{} Edited.", edited[0]);
+        std::fs::write(&file_path, edited.join("\n\n")).unwrap();
+        let second = ingestor.ingest_file(&file_path).unwrap();
+
+        assert!(second.was_update);
+        let ids = |chunks: &[Chunk]| chunks.iter().map(|c| c.id.clone()).collect::<Vec<_>>();
+        assert_eq!(ids(&second.chunks[1..]), ids(&first.chunks[1..]));
+        assert_ne!(second.chunks[0].id, first.chunks[0].id);
+        assert_eq!(db.get_unembedded_chunks_by_item(&first.item.id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_reused_media_path_keeps_history() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db.clone());
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("screenshot.png");
+
+        std::fs::write(&file_path, "first capture").unwrap();
+        let first = ingestor.ingest_file(&file_path).unwrap();
+
+        // A new capture saved under the same name
+        std::fs::write(&file_path, "second capture").unwrap();
+        let second = ingestor.ingest_file(&file_path).unwrap();
+
+        assert!(!second.was_update);
+        assert_ne!(first.item.id, second.item.id);
+        assert!(db.get_item(&first.item.id).is_ok());
+
+        let path = file_path.canonicalize().unwrap().to_string_lossy().to_string();
+        assert_eq!(db.find_items_by_path(&path).unwrap().len(), 2);
+        assert_eq!(db.find_item_by_path(&path).unwrap().unwrap().id, second.item.id);
+
+        let links = db.get_item_links(&second.item.id).unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].link_type, LinkType::Supersedes);
+        assert_eq!(links[0].target_id, first.item.id);
+    }
+
+    #[test]
+    fn test_ingest_archive() {
+        use std::io::Write;
+
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db.clone());
+
+        let dir = tempdir().unwrap();
+        let archive = dir.path().join("notes.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        for (name, content) in [("a.md", "# Alpha\n\nFirst note."), ("sub/b.txt", "Second note.")] {
+            zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+
+        let mut ingested = Vec::new();
+        let parent = ingestor
+            .ingest_archive(&archive, true, |member, result| {
+                ingested.push((member.entry.clone(), result.unwrap().item));
+            })
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(parent.title, "notes.zip");
+        assert_eq!(ingested.len(), 2);
+        let (entry, item) = &ingested[1];
+        assert_eq!(entry, "sub/b.txt");
+        assert!(item.source_path.as_deref().unwrap().ends_with("notes.zip!/sub/b.txt"));
+        assert_eq!(item.metadata["archive"]["entry"], "sub/b.txt");
+
+        let links = db.get_item_links(&item.id).unwrap();
+        assert_eq!(links[0].link_type, LinkType::Child);
+        assert_eq!(links[0].target_id, parent.id);
+
+        // Re-ingesting finds the same items
+        let again = ingestor.ingest_archive(&archive, true, |_, _| {}).unwrap().unwrap();
+        assert_eq!(again.id, parent.id);
+        assert_eq!(db.recent_items(None).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_ingest_git() {
+        if which::which("git").is_err() {
+            return;
+        }
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db);
+
+        let dir = tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(["-c", "user.name=Test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn answer() -> u32 { 42 }").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-q", "-m", "first"]);
+
+        let mut items = Vec::new();
+        let snapshot = ingestor
+            .ingest_git(dir.path(), None, |_, result| items.push(result.unwrap().item))
+            .unwrap();
+
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].item_type, ItemType::Code);
+        assert_eq!(items[0].metadata["git"]["path"], "src/lib.rs");
+        assert_eq!(items[0].metadata["git"]["commit"], snapshot.commit.as_str());
+    }
+
+    #[test]
+    fn test_import_bookmarks() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db.clone());
+
+        let bookmark = |url: &str, folders: &[&str]| Bookmark {
+            url: url.to_string(),
+            title: format!("Page at {}", url),
+            folders: folders.iter().map(|f| f.to_string()).collect(),
+            tags: Vec::new(),
+            added_at: None,
+        };
+        let bookmarks = vec![
+            bookmark("https://tokio.rs/", &["Dev", "Rust"]),
+            bookmark("https://example.com/", &[]),
+            bookmark("https://tokio.rs/", &["Elsewhere"]),
+        ];
+
+        let summary = ingestor.import_bookmarks(&bookmarks, true).unwrap();
+        assert_eq!(summary, ImportSummary { added: 2, updated: 0, skipped: 1 });
+
+        let item = db.find_item_by_path("https://tokio.rs/").unwrap().unwrap();
+        assert_eq!(item.item_type, ItemType::Bookmark);
+        assert_eq!(db.get_item_tags(&item.id).unwrap()[0].name, "Dev/Rust");
+        assert_eq!(db.bookmarks_to_fetch(10).unwrap().len(), 2);
+        assert_eq!(db.search_items("tokio", None).unwrap().len(), 1);
+
+        let again = ingestor.import_bookmarks(&bookmarks, false).unwrap();
+        assert_eq!(again, ImportSummary { added: 0, updated: 0, skipped: 3 });
+    }
+
+    #[test]
+    fn test_import_kindle() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db.clone());
+
+        let clipping = |location: &str, text: &str| {
+            format!(
+                "Dune (Frank Herbert)\n- Your Highlight at location {} | Added on Monday, March 6, 2017 9:00:00 AM\n\n{}\n==========\n",
+                location, text
+            )
+        };
+        let first = clipping("50-52", "I must not fear.") + &clipping("90-91", "The spice must flow.");
+        let summary = ingestor.import_kindle(&crate::kindle::parse(&first)).unwrap();
+        assert_eq!(summary, ImportSummary { added: 2, updated: 0, skipped: 0 });
+
+        let later = first + &clipping("70-71", "He who controls the spice controls the universe.");
+        let summary = ingestor.import_kindle(&crate::kindle::parse(&later)).unwrap();
+        assert_eq!(summary, ImportSummary { added: 1, updated: 0, skipped: 2 });
+
+        let item = db.find_item_by_path("kindle:Dune (Frank Herbert)").unwrap().unwrap();
+        assert_eq!(item.metadata["author"], "Frank Herbert");
+        let chunks = db.get_chunks_by_item(&item.id).unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1].content, "He who controls the spice controls the universe.\n(Location 70-71)");
+        assert_eq!(item.metadata["highlights"][1]["location"], serde_json::json!([70, 71]));
+
+        // Extending a highlight and adding a note to another, in a new
+        // clippings file, update them in place
+        let note = "Dune (Frank Herbert)\n- Your Note at location 91 | Added on Tuesday, March 7, 2017 9:00:00 AM\n\nSpice = oil\n==========\n";
+        let extended = clipping("50-53", "I must not fear. Fear is the mind-killer.") + &clipping("90-91", "The spice must flow.") + note;
+        let summary = ingestor.import_kindle(&crate::kindle::parse(&extended)).unwrap();
+        assert_eq!(summary, ImportSummary { added: 0, updated: 2, skipped: 0 });
+        let chunks = db.get_chunks_by_item(&item.id).unwrap();
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0].content, "I must not fear. Fear is the mind-killer.\n(Location 50-53)");
+        assert_eq!(chunks[2].content, "The spice must flow.\nNote: Spice = oil\n(Location 90-91)");
+
+        // The note survives an import of a file without it
+        let summary = ingestor.import_kindle(&crate::kindle::parse(&clipping("90-91", "The spice must flow."))).unwrap();
+        assert_eq!(summary, ImportSummary { added: 0, updated: 0, skipped: 1 });
+    }
+
+    #[test]
+    fn test_low_confidence_ocr_flagged_for_review() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db.clone());
+
+        let mut blurry = Item::new(ItemType::Image, "blurry");
+        blurry.metadata = serde_json::json!({ "ocr_confidence": 41.0, "ocr_low_confidence": true });
+        let mut sharp = Item::new(ItemType::Image, "sharp");
+        sharp.metadata = serde_json::json!({ "ocr_confidence": 95.0, "ocr_low_confidence": false });
+        for item in [&blurry, &sharp] {
+            db.create_item(item).unwrap();
+            ingestor.finish(item, 1);
+        }
+
+        let tags = db.get_item_tags(&blurry.id).unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, OCR_REVIEW_TAG);
+        assert!(db.get_item_tags(&sharp.id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_hash_file_streams() {
+        let ingestor = Ingestor::with_defaults(Database::open_in_memory().unwrap());
+
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("abc.txt");
+        std::fs::write(&file_path, "abc").unwrap();
+
+        assert_eq!(
+            ingestor.hash_file(&file_path).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_ingest_text() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db);
+
+        let content = "# Build log\n\nerror[E0382]: borrow of moved value";
+        let result = ingestor
+            .ingest_text(content, ItemType::Code, None, serde_json::json!({ "source": "stdin" }))
+            .unwrap();
+        assert_eq!(result.item.title, "Build log");
+        assert_eq!(result.item.item_type, ItemType::Code);
+        assert!(result.item.source_path.is_none());
+        assert_eq!(result.chunk_count, 1);
+
+        // Same content again is recognized
+        let again = ingestor
+            .ingest_text(content, ItemType::Code, None, serde_json::json!({}))
+            .unwrap();
+        assert_eq!(again.item.id, result.item.id);
+
+        assert!(ingestor
+            .ingest_text("   ", ItemType::Note, None, serde_json::json!({}))
+            .is_err());
+        assert!(ingestor
+            .ingest_text("frames", ItemType::Video, None, serde_json::json!({}))
+            .is_err());
+    }
+
+    #[test]
+    fn test_replace_text() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db.clone());
+
+        let original = ingestor
+            .ingest_text("first draft", ItemType::Note, Some("Idea"), serde_json::json!({}))
+            .unwrap();
+        assert_eq!(db.get_item_content(&original.item.id).unwrap().unwrap(), "first draft");
+
+        let edited = ingestor.replace_text(&original.item, "second draft").unwrap();
+        assert!(edited.was_update);
+        assert_eq!(edited.item.id, original.item.id);
+        assert_ne!(edited.item.content_hash, original.item.content_hash);
+
+        let chunks = db.get_chunks_by_item(&original.item.id).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].content, "second draft");
+        assert_eq!(db.get_item_content(&original.item.id).unwrap().unwrap(), "second draft");
+
+        let mut file_item = Item::new(ItemType::Document, "File");
+        file_item.source_path = Some("/tmp/file.md".to_string());
+        assert!(ingestor.replace_text(&file_item, "text").is_err());
+    }
+
+    #[test]
+    fn test_ingest_files_in_parallel() {
+        let db = Database::open_in_memory().unwrap();
+        let ingestor = Ingestor::with_defaults(db.clone()).with_threads(4);
+
+        let dir = tempdir().unwrap();
+        let mut paths = Vec::new();
+        for i in 0..50 {
+            let path = dir.path().join(format!("note-{}.md", i));
+            std::fs::write(&path, format!("# Note {}\n\nContent number {}.", i, i)).unwrap();
+            paths.push(path);
+        }
+        // Two files with identical content become one item
+        let copy = dir.path().join("copy.md");
+        std::fs::write(&copy, "# Note 0\n\nContent number 0.").unwrap();
+        paths.push(copy);
+
+        let mut ok = 0;
+        ingestor
+            .ingest_files(&paths, |_, result| {
+                result.unwrap();
+                ok += 1;
+            })
+            .unwrap();
+
+        assert_eq!(ok, 51);
+        assert_eq!(db.recent_items(None).unwrap().len(), 50);
+        assert_eq!(db.search_items("Content", Some(100)).unwrap().len(), 50);
+    }
+
+    #[test]
+    fn test_progress_events() {
+        let events = EventEmitter::new();
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let log = std::sync::Arc::clone(&seen);
+        events.subscribe(move |event| log.lock().unwrap().push(event.clone()));
+        let ingestor = Ingestor::with_defaults(Database::open_in_memory().unwrap()).with_events(events);
+
+        let dir = tempdir().unwrap();
+        let good = dir.path().join("good.md");
+        std::fs::write(&good, "# Good\n\nSome content.").unwrap();
+        let missing = dir.path().join("missing.md");
+        ingestor.ingest_files(&[good, missing], |_, _| {}).unwrap();
+
+        let seen = seen.lock().unwrap();
+        let names: Vec<_> = seen.iter().map(Event::name).collect();
+        assert_eq!(names.iter().filter(|n| **n == "ingest.started").count(), 2);
+        assert!(names.contains(&"ingest.parsed"));
+        assert!(names.contains(&"ingest.chunked"));
+        assert!(names.contains(&"item.processed"));
+        assert!(seen.iter().any(|e| matches!(e, Event::IngestStarted { total: 2, .. })));
+        assert!(seen
+            .iter()
+            .any(|e| matches!(e, Event::IngestFailed { path, .. } if path.ends_with("missing.md"))));
+    }
 }
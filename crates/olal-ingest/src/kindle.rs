@@ -0,0 +1,327 @@
+//! Parsing Kindle highlights from `My Clippings.txt`.
+//!
+//! Each clipping is a title line (`Title (Author)`), a line like
+//! `- Your Highlight on page 12 | Location 171-173 | Added on ...`, a blank
+//! line, the text and a `==========` separator. Notes are attached to the
+//! highlight they were made on; bookmarks are skipped.
+
+use crate::error::IngestResult;
+use chrono::NaiveDateTime;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+
+/// Separator line between clippings.
+const SEPARATOR: &str = "==========";
+
+/// Formats of the "Added on" date, newest Kindles first.
+const DATE_FORMATS: &[&str] = &["%A, %B %d, %Y %I:%M:%S %p", "%A, %d %B %Y %H:%M:%S", "%A, %d %B %y %H:%M:%S"];
+
+/// A book and its highlights, in reading order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Book {
+    pub title: String,
+    pub author: Option<String>,
+    pub highlights: Vec<Highlight>,
+}
+
+/// A highlight, or a note made without one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Highlight {
+    /// Highlighted text; empty for a note on its own.
+    pub text: String,
+    pub note: Option<String>,
+    /// Kindle location range, e.g. `(171, 173)`.
+    pub location: Option<(u32, u32)>,
+    /// Printed page, if the book has page numbers (can be roman numerals).
+    pub page: Option<String>,
+    pub added_at: Option<NaiveDateTime>,
+}
+
+impl Book {
+    /// Source path recorded for the book's item: `kindle:<Title (Author)>`.
+    pub fn source_path(&self) -> String {
+        match &self.author {
+            Some(author) => format!("kindle:{} ({})", self.title, author),
+            None => format!("kindle:{}", self.title),
+        }
+    }
+}
+
+impl Highlight {
+    /// Identity of the highlight within `book` for deduplicating
+    /// re-imports: a hash of the book and the text, so a note added later
+    /// doesn't make it a new highlight.
+    pub fn hash(&self, book: &Book) -> String {
+        let text = if self.text.is_empty() {
+            self.note.as_deref().unwrap_or_default()
+        } else {
+            &self.text
+        };
+        let digest = Sha256::digest(format!("{}\n{}", book.source_path(), text.trim()).as_bytes());
+        digest[..16].iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    /// Where the highlight is, e.g. "Location 171-173, p. 12".
+    pub fn position(&self) -> Option<String> {
+        let location = self.location.map(|(start, end)| {
+            if start == end {
+                format!("Location {}", start)
+            } else {
+                format!("Location {}-{}", start, end)
+            }
+        });
+        let page = self.page.as_ref().map(|page| format!("p. {}", page));
+        let parts: Vec<String> = location.into_iter().chain(page).collect();
+        (!parts.is_empty()).then(|| parts.join(", "))
+    }
+
+    /// The highlight as chunk text: the text, the note and the position.
+    pub fn to_text(&self) -> String {
+        let mut lines = Vec::new();
+        if !self.text.is_empty() {
+            lines.push(self.text.clone());
+        }
+        if let Some(note) = &self.note {
+            lines.push(format!("Note: {}", note));
+        }
+        if let Some(position) = self.position() {
+            lines.push(format!("({})", position));
+        }
+        lines.join("\n")
+    }
+
+    fn contains(&self, location: u32) -> bool {
+        self.location.is_some_and(|(start, end)| (start..=end).contains(&location))
+    }
+}
+
+/// Parse a `My Clippings.txt` file.
+pub fn parse_file(path: &Path) -> IngestResult<Vec<Book>> {
+    Ok(parse(&std::fs::read_to_string(path)?))
+}
+
+/// Parse the clippings format into books, in order of first appearance.
+///
+/// Extending a highlight on the Kindle leaves the old version in the file
+/// too; a highlight starting at the same location as an earlier one
+/// replaces it.
+pub fn parse(content: &str) -> Vec<Book> {
+    let mut books: Vec<Book> = Vec::new();
+    for clipping in content.split(SEPARATOR) {
+        let mut lines = clipping.lines().map(|l| l.trim_start_matches('\u{feff}').trim());
+        let Some(title_line) = lines.by_ref().find(|l| !l.is_empty()) else {
+            continue;
+        };
+        let Some(meta) = lines.next().and_then(|l| l.strip_prefix('-')) else {
+            continue;
+        };
+        let text = lines.collect::<Vec<_>>().join("\n").trim().to_string();
+        let (title, author) = split_title(title_line);
+        let book = match books.iter().position(|b| b.title == title && b.author == author) {
+            Some(i) => &mut books[i],
+            None => {
+                books.push(Book {
+                    title,
+                    author,
+                    highlights: Vec::new(),
+                });
+                books.last_mut().unwrap()
+            }
+        };
+
+        let kind = meta.split('|').next().unwrap_or_default().to_lowercase();
+        let highlight = parse_meta(meta, text);
+        if kind.contains("note") {
+            let note = highlight.text;
+            if note.is_empty() {
+                continue;
+            }
+            let at = highlight.location.map(|(_, end)| end);
+            match book.highlights.iter_mut().rev().find(|h| at.is_some_and(|at| h.contains(at))) {
+                Some(target) => target.note = Some(note),
+                None => book.highlights.push(Highlight {
+                    text: String::new(),
+                    note: Some(note),
+                    ..highlight
+                }),
+            }
+        } else if kind.contains("highlight") && !highlight.text.is_empty() {
+            let start = highlight.location.map(|(start, _)| start);
+            match book
+                .highlights
+                .iter_mut()
+                .find(|h| start.is_some() && h.location.map(|(s, _)| s) == start)
+            {
+                Some(earlier) => {
+                    let note = earlier.note.take();
+                    *earlier = Highlight { note, ..highlight };
+                }
+                None => book.highlights.push(highlight),
+            }
+        }
+    }
+
+    for book in &mut books {
+        book.highlights.sort_by_key(|h| h.location.map_or(u32::MAX, |(start, _)| start));
+    }
+    books.retain(|b| !b.highlights.is_empty());
+    books
+}
+
+/// Split `Title (Author)` on its last parenthesized group.
+fn split_title(line: &str) -> (String, Option<String>) {
+    if let Some(inner) = line.strip_suffix(')') {
+        let mut depth = 0;
+        for (i, c) in inner.char_indices().rev() {
+            match c {
+                ')' => depth += 1,
+                '(' if depth == 0 => {
+                    let title = inner[..i].trim();
+                    let author = inner[i + 1..].trim();
+                    if !title.is_empty() && !author.is_empty() {
+                        return (title.to_string(), Some(author.to_string()));
+                    }
+                    break;
+                }
+                '(' => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+    (line.to_string(), None)
+}
+
+/// The location, page and date from a clipping's second line.
+fn parse_meta(meta: &str, text: String) -> Highlight {
+    let mut highlight = Highlight {
+        text,
+        note: None,
+        location: None,
+        page: None,
+        added_at: None,
+    };
+    for part in meta.split('|').map(str::trim) {
+        let lower = part.to_lowercase();
+        if let Some(i) = lower.find("location ") {
+            highlight.location = parse_range(&part[i + "location ".len()..]);
+        }
+        if let Some(i) = lower.find("page ") {
+            highlight.page = part[i + "page ".len()..]
+                .split_whitespace()
+                .next()
+                .map(str::to_string);
+        }
+        if let Some(date) = part.strip_prefix("Added on ") {
+            highlight.added_at = DATE_FORMATS
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(date.trim(), format).ok());
+        }
+    }
+    highlight
+}
+
+/// `171-173` or `171`; the end can be abbreviated (`1171-73`).
+fn parse_range(text: &str) -> Option<(u32, u32)> {
+    let range = text.split_whitespace().next()?;
+    let (start, end) = range.split_once('-').unwrap_or((range, range));
+    let start: u32 = start.parse().ok()?;
+    let end = match end.parse::<u32>().ok()? {
+        end if end < start && end.to_string().len() < start.to_string().len() => {
+            let digits = 10u32.pow(end.to_string().len() as u32);
+            start - start % digits + end
+        }
+        end => end,
+    };
+    Some((start, end.max(start)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLIPPINGS: &str = "\u{feff}The Rust Programming Language (Steve Klabnik and Carol Nichols)
+- Your Highlight on page 12 | Location 171-173 | Added on Sunday, March 5, 2017 10:01:35 PM
+
+Ownership is Rust's most unique feature.
+==========
+Dune (Frank Herbert)
+- Your Highlight at location 50-51 | Added on Monday, March 6, 2017 9:00:00 AM
+
+I must not fear.
+==========
+Dune (Frank Herbert)
+- Your Highlight at location 50-52 | Added on Monday, March 6, 2017 9:01:00 AM
+
+I must not fear. Fear is the mind-killer.
+==========
+Dune (Frank Herbert)
+- Your Note at location 52 | Added on Monday, March 6, 2017 9:02:00 AM
+
+The litany
+==========
+Dune (Frank Herbert)
+- Your Bookmark at location 10 | Added on Monday, March 6, 2017 9:03:00 AM
+
+
+==========
+The Rust Programming Language (Steve Klabnik and Carol Nichols)
+- Your Highlight on page 5 | Location 80-81 | Added on Sunday, March 5, 2017 9:00:00 PM
+
+Rust is fast.
+==========
+";
+
+    #[test]
+    fn test_parse() {
+        let books = parse(CLIPPINGS);
+        assert_eq!(books.len(), 2);
+
+        let rust = &books[0];
+        assert_eq!(rust.title, "The Rust Programming Language");
+        assert_eq!(rust.author.as_deref(), Some("Steve Klabnik and Carol Nichols"));
+        assert_eq!(rust.highlights.len(), 2);
+        assert_eq!(rust.highlights[0].text, "Rust is fast.");
+        assert_eq!(rust.highlights[1].location, Some((171, 173)));
+        assert_eq!(rust.highlights[1].page.as_deref(), Some("12"));
+        assert_eq!(
+            rust.highlights[1].added_at.unwrap().to_string(),
+            "2017-03-05 22:01:35"
+        );
+
+        let dune = &books[1];
+        assert_eq!(dune.highlights.len(), 1);
+        assert_eq!(dune.highlights[0].text, "I must not fear. Fear is the mind-killer.");
+        assert_eq!(dune.highlights[0].note.as_deref(), Some("The litany"));
+        assert_eq!(
+            dune.highlights[0].to_text(),
+            "I must not fear. Fear is the mind-killer.\nNote: The litany\n(Location 50-52)"
+        );
+    }
+
+    #[test]
+    fn test_split_title() {
+        assert_eq!(
+            split_title("Foundation (Foundation 1) (Isaac Asimov)"),
+            ("Foundation (Foundation 1)".to_string(), Some("Isaac Asimov".to_string()))
+        );
+        assert_eq!(split_title("Untitled"), ("Untitled".to_string(), None));
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("171-173"), Some((171, 173)));
+        assert_eq!(parse_range("1171-73"), Some((1171, 1173)));
+        assert_eq!(parse_range("52"), Some((52, 52)));
+        assert_eq!(parse_range("x"), None);
+    }
+
+    #[test]
+    fn test_hash_ignores_note_and_position() {
+        let books = parse(CLIPPINGS);
+        let dune = &books[1];
+        let mut moved = dune.highlights[0].clone();
+        moved.note = None;
+        moved.location = Some((900, 901));
+        assert_eq!(moved.hash(dune), dune.highlights[0].hash(dune));
+    }
+}
@@ -3,18 +3,48 @@
 //! This crate provides:
 //! - File system watching for automatic ingestion
 //! - Document parsing (markdown, text, PDF, audio)
+//! - Unpacking .zip and .tar.gz archives for ingestion
+//! - Ingesting git repositories with commit metadata
+//! - Downloading and ingesting arXiv papers with their metadata
+//! - Importing browser bookmarks and fetching their pages
+//! - Importing Pocket and Instapaper exports
+//! - Importing Kindle highlights (My Clippings.txt)
 //! - Content chunking for RAG
 //! - Processing queue management
 //! - AI-based enrichment (summarization, auto-tagging)
+//! - Embedding new chunks as they're ingested
+//! - Scheduled knowledge-graph maintenance
+//! - Retention policies (archive, delete, compress, queue cleanup)
+//! - A long-running worker that drains the processing queue
+//! - Recurring jobs from the `[schedule]` config
+//! - Pipeline events and webhook notifications
 
 pub mod ai_enrich;
+pub mod archive;
+pub mod arxiv;
+pub mod bookmarks;
 mod chunker;
+mod embedder;
 mod error;
+pub mod events;
+pub mod git;
 mod ingestor;
+pub mod kindle;
+pub mod maintenance;
 mod parsers;
+pub mod read_later;
+pub mod retention;
+pub mod scheduler;
+pub mod vision;
 mod watcher;
+pub mod web;
+pub mod webhooks;
+mod worker;
 
 pub use chunker::{ChunkConfig, Chunker};
+pub use embedder::Embedder;
 pub use error::{IngestError, IngestResult};
-pub use ingestor::Ingestor;
+pub use events::{Event, EventEmitter};
+pub use ingestor::{ImportSummary, Ingestor, OCR_REVIEW_TAG};
 pub use watcher::{FileWatcher, WatchEvent, WatcherConfig};
+pub use worker::{process_alive, Worker, WorkerEvent, WorkerSummary};
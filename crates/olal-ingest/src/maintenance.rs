@@ -0,0 +1,309 @@
+//! Knowledge-graph maintenance (link discovery, dedupe, summaries, tags).
+//!
+//! Run by the `maintenance` job in `[schedule]` or on demand with
+//! `olal maintain`. Each run writes a "maintenance report" note summarizing
+//! what changed and what needs a human decision.
+
+use crate::ai_enrich::{apply_summary, AiEnricher};
+use crate::error::IngestResult;
+use chrono::{DateTime, Utc};
+use olal_config::Config;
+use olal_core::{Chunk, Item, ItemType, Tag};
+use olal_db::Database;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+/// Tag used for maintenance report notes.
+pub const REPORT_TAG: &str = "maintenance";
+
+/// What a maintenance run changed or recommends.
+#[derive(Debug, Default)]
+pub struct MaintenanceReport {
+    /// Number of similarity links created.
+    pub links_created: usize,
+    /// Number of weaker links queued for review.
+    pub links_queued: usize,
+    /// Groups of likely-duplicate items (titles and short IDs).
+    pub duplicates: Vec<Vec<(String, String)>>,
+    /// Titles of items whose summaries were (re)generated.
+    pub summaries_refreshed: Vec<String>,
+    /// Generated summaries queued for review (low confidence).
+    pub summaries_queued: usize,
+    /// Items still missing summaries (Ollama unavailable or failed).
+    pub summaries_pending: usize,
+    /// Suggested tag merges: (tags to merge, suggested target).
+    pub tag_merges: Vec<(Vec<String>, String)>,
+    /// Non-fatal problems encountered.
+    pub warnings: Vec<String>,
+}
+
+impl MaintenanceReport {
+    /// Whether the run changed or found anything worth reporting.
+    pub fn is_empty(&self) -> bool {
+        self.links_created == 0
+            && self.links_queued == 0
+            && self.duplicates.is_empty()
+            && self.summaries_refreshed.is_empty()
+            && self.summaries_queued == 0
+            && self.summaries_pending == 0
+            && self.tag_merges.is_empty()
+            && self.warnings.is_empty()
+    }
+
+    /// Render the report as markdown.
+    pub fn to_markdown(&self, date: DateTime<Utc>) -> String {
+        let mut out = format!("# Maintenance report {}\n\n", date.format("%Y-%m-%d"));
+
+        out.push_str("## Changes\n\n");
+        out.push_str(&format!("- Created {} similarity links\n", self.links_created));
+        if self.links_queued > 0 {
+            out.push_str(&format!(
+                "- Queued {} link suggestions for review (`olal review-ai`)\n",
+                self.links_queued
+            ));
+        }
+        out.push_str(&format!(
+            "- Refreshed {} summaries\n",
+            self.summaries_refreshed.len()
+        ));
+        for title in &self.summaries_refreshed {
+            out.push_str(&format!("  - {}\n", title));
+        }
+        if self.summaries_queued > 0 {
+            out.push_str(&format!(
+                "- Queued {} summaries for review (`olal review-ai`)\n",
+                self.summaries_queued
+            ));
+        }
+
+        out.push_str("\n## Recommendations\n\n");
+        if self.duplicates.is_empty() && self.tag_merges.is_empty() && self.summaries_pending == 0
+        {
+            out.push_str("Nothing to review.\n");
+        }
+        if !self.duplicates.is_empty() {
+            out.push_str("### Possible duplicates\n\n");
+            for group in &self.duplicates {
+                let entries: Vec<String> = group
+                    .iter()
+                    .map(|(title, id)| format!("{} [{}]", title, id))
+                    .collect();
+                out.push_str(&format!("- {}\n", entries.join(" / ")));
+            }
+            out.push('\n');
+        }
+        if !self.tag_merges.is_empty() {
+            out.push_str("### Tag consolidation\n\n");
+            for (tags, target) in &self.tag_merges {
+                out.push_str(&format!("- Merge {} into `{}`\n", tags.join(", "), target));
+            }
+            out.push('\n');
+        }
+        if self.summaries_pending > 0 {
+            out.push_str(&format!(
+                "- {} items still need summaries\n",
+                self.summaries_pending
+            ));
+        }
+
+        if !self.warnings.is_empty() {
+            out.push_str("\n## Warnings\n\n");
+            for warning in &self.warnings {
+                out.push_str(&format!("- {}\n", warning));
+            }
+        }
+
+        out
+    }
+}
+
+/// Run all maintenance passes.
+///
+/// With `dry_run`, nothing is written: link discovery and summary refresh
+/// are skipped and only recommendations are computed.
+pub fn run_maintenance(db: &Database, config: &Config, dry_run: bool) -> IngestResult<MaintenanceReport> {
+    let mut report = MaintenanceReport::default();
+    let settings = &config.maintenance;
+
+    // 1. Link discovery
+    if !dry_run {
+        let (created, queued) = db.link_similar_items_reviewed(
+            config.processing.link_similarity_threshold,
+            config.review.link_review_threshold(),
+        )?;
+        report.links_created = created;
+        report.links_queued = queued;
+        info!("Maintenance: created {} links, queued {}", created, queued);
+    }
+
+    // 2. Duplicate detection
+    report.duplicates = db
+        .find_duplicate_items()?
+        .into_iter()
+        .map(|group| {
+            group
+                .into_iter()
+                .filter(|item| !is_report(item))
+                .map(|item| (item.title, item.id.chars().take(8).collect()))
+                .collect::<Vec<_>>()
+        })
+        .filter(|group| group.len() > 1)
+        .collect();
+
+    // 3. Summary refresh
+    let missing = db.items_without_summary(settings.summary_refresh_limit as i64)?;
+    let missing: Vec<Item> = missing.into_iter().filter(|i| !is_report(i)).collect();
+    if !dry_run && !missing.is_empty() {
+        match AiEnricher::from_config(config) {
+            Ok(enricher) => {
+                for mut item in missing.iter().cloned() {
+                    let content = db
+                        .get_chunks_by_item(&item.id)?
+                        .into_iter()
+                        .map(|c| c.content)
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    match enricher
+                        .generate_summary(&content)
+                        .and_then(|summary| apply_summary(db, &mut item, &content, summary, config))
+                    {
+                        Ok(true) => report.summaries_refreshed.push(item.title.clone()),
+                        Ok(false) => report.summaries_queued += 1,
+                        Err(e) => report.warnings.push(format!("{}: {}", item.title, e)),
+                    }
+                }
+            }
+            Err(e) => {
+                warn!("Maintenance: summaries skipped: {}", e);
+                report.warnings.push(format!("Summary refresh skipped: {}", e));
+            }
+        }
+    }
+    report.summaries_pending =
+        missing.len() - report.summaries_refreshed.len() - report.summaries_queued;
+
+    // 4. Tag consolidation suggestions
+    report.tag_merges = suggest_tag_merges(&db.get_tag_counts()?);
+
+    Ok(report)
+}
+
+/// Store a report as a tagged note item. Returns the new item.
+pub fn save_report(db: &Database, report: &MaintenanceReport) -> IngestResult<Item> {
+    let now = Utc::now();
+    let mut item = Item::new(
+        ItemType::Note,
+        format!("Maintenance report {}", now.format("%Y-%m-%d")),
+    );
+    item.processed_at = Some(now);
+    item.metadata = serde_json::json!({
+        "source": "maintenance",
+        "links_created": report.links_created,
+        "duplicates": report.duplicates.len(),
+        "summaries_refreshed": report.summaries_refreshed.len(),
+        "tag_merges": report.tag_merges.len(),
+    });
+
+    db.create_item(&item)?;
+    db.create_chunks(&[Chunk::new(item.id.clone(), 0, report.to_markdown(now))])?;
+    db.tag_item(&item.id, REPORT_TAG)?;
+
+    Ok(item)
+}
+
+/// Whether an item is a saved maintenance report.
+pub fn is_report(item: &Item) -> bool {
+    item.metadata.get("source").and_then(|s| s.as_str()) == Some("maintenance")
+}
+
+/// Normalize a tag name for similarity grouping.
+fn normalize_tag(name: &str) -> String {
+    let normalized: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c == '-' || c == '_' { ' ' } else { c })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if normalized.len() > 3 && normalized.ends_with('s') && !normalized.ends_with("ss") {
+        normalized[..normalized.len() - 1].to_string()
+    } else {
+        normalized
+    }
+}
+
+/// Suggest merging tags that differ only by case, separators, or a plural `s`.
+///
+/// The most used tag in each group is suggested as the target.
+pub fn suggest_tag_merges(tag_counts: &[(Tag, i64)]) -> Vec<(Vec<String>, String)> {
+    let mut groups: HashMap<String, Vec<(&str, i64)>> = HashMap::new();
+    for (tag, count) in tag_counts {
+        groups
+            .entry(normalize_tag(&tag.name))
+            .or_default()
+            .push((tag.name.as_str(), *count));
+    }
+
+    let mut merges: Vec<(Vec<String>, String)> = groups
+        .into_values()
+        .filter(|g| g.len() > 1)
+        .map(|mut g| {
+            g.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let target = g[0].0.to_string();
+            let others = g[1..].iter().map(|(n, _)| n.to_string()).collect();
+            (others, target)
+        })
+        .collect();
+
+    merges.sort_by(|a, b| a.1.cmp(&b.1));
+    merges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_tag_merges() {
+        let counts = vec![
+            (Tag::new("machine-learning"), 5),
+            (Tag::new("Machine Learning"), 2),
+            (Tag::new("notes"), 1),
+            (Tag::new("note"), 4),
+            (Tag::new("rust"), 9),
+            (Tag::new("class"), 1),
+        ];
+
+        let merges = suggest_tag_merges(&counts);
+        assert_eq!(
+            merges,
+            vec![
+                (vec!["Machine Learning".to_string()], "machine-learning".to_string()),
+                (vec!["notes".to_string()], "note".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report_roundtrip() {
+        let db = Database::open_in_memory().unwrap();
+        db.create_item(&Item::new(ItemType::Note, "Dup")).unwrap();
+        db.create_item(&Item::new(ItemType::Note, "dup")).unwrap();
+
+        let config = Config::default();
+        let report = run_maintenance(&db, &config, true).unwrap();
+        assert_eq!(report.duplicates.len(), 1);
+
+        let item = save_report(&db, &report).unwrap();
+        let chunks = db.get_chunks_by_item(&item.id).unwrap();
+        assert!(chunks[0].content.contains("Possible duplicates"));
+        assert_eq!(db.get_item_tags(&item.id).unwrap()[0].name, REPORT_TAG);
+
+        // Reports never flag each other as duplicates
+        save_report(&db, &report).unwrap();
+        let report = run_maintenance(&db, &config, true).unwrap();
+        assert_eq!(report.duplicates.len(), 1);
+    }
+}
@@ -2,16 +2,26 @@
 
 use super::ParsedDocument;
 use crate::error::{IngestError, IngestResult};
-use olal_process::{transcribe_audio, TranscriptSegment};
+use olal_process::{
+    preprocess_audio, AudioPreprocessing, PreparedAudio, TranscribeOptions, TranscriptSegment,
+    TranscriptionBackend, WhisperCli,
+};
+use std::collections::HashSet;
 use std::path::Path;
 use tempfile::tempdir;
-use tracing::info;
+use tracing::{info, warn};
 
 /// Parser for audio files.
-/// Transcribes directly using Whisper (no audio extraction needed).
+/// Transcribes directly (no audio extraction needed).
 pub struct AudioParser {
     /// Whisper model to use (tiny, base, small, medium, large)
     whisper_model: String,
+    backend: Box<dyn TranscriptionBackend>,
+    /// Label speakers, if the backend can.
+    diarize: bool,
+    /// Language spoken; `None` detects it.
+    language: Option<String>,
+    preprocessing: AudioPreprocessing,
 }
 
 impl AudioParser {
@@ -19,12 +29,35 @@ impl AudioParser {
     pub fn new(whisper_model: impl Into<String>) -> Self {
         Self {
             whisper_model: whisper_model.into(),
+            backend: Box::new(WhisperCli),
+            diarize: false,
+            language: None,
+            preprocessing: AudioPreprocessing::default(),
         }
     }
 
-    /// Create an audio parser with the default model (base).
-    pub fn with_default_model() -> Self {
-        Self::new("base")
+    /// Transcribe with `backend` instead of the whisper CLI.
+    pub fn with_backend(mut self, backend: Box<dyn TranscriptionBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Label who is speaking, with backends that support diarization.
+    pub fn with_diarization(mut self, diarize: bool) -> Self {
+        self.diarize = diarize;
+        self
+    }
+
+    /// Transcribe as `language` instead of detecting it.
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Clean up the audio before transcribing it.
+    pub fn with_preprocessing(mut self, preprocessing: AudioPreprocessing) -> Self {
+        self.preprocessing = preprocessing;
+        self
     }
 
     /// Parse an audio file by transcribing it.
@@ -41,10 +74,23 @@ impl AudioParser {
         })?;
 
         // Transcribe directly (file is already audio)
-        info!("Transcribing with Whisper ({})...", self.whisper_model);
-        let segments = transcribe_audio(path, &self.whisper_model, temp_dir.path()).map_err(|e| {
-            IngestError::ProcessingError(format!("Failed to transcribe: {}", e))
-        })?;
+        let prepared = prepare_audio(path, temp_dir.path(), &self.preprocessing);
+        info!("Transcribing with {} ({})...", self.backend.name(), self.whisper_model);
+        let options = TranscribeOptions::for_backend(
+            self.backend.as_ref(),
+            self.whisper_model.clone(),
+            self.diarize,
+            self.language.clone(),
+        );
+        let transcript = self
+            .backend
+            .transcribe(&prepared.path, temp_dir.path(), &options)
+            .map_err(|e| {
+                IngestError::ProcessingError(format!("Failed to transcribe: {}", e))
+            })?;
+        let mut segments = transcript.segments;
+        prepared.restore_timestamps(&mut segments);
+        let language = transcript.language;
 
         info!("Transcribed {} segments", segments.len());
 
@@ -67,12 +113,19 @@ impl AudioParser {
             .and_then(|n| n.to_str())
             .map(|s| s.to_string());
 
-        let metadata = serde_json::json!({
+        let mut metadata = serde_json::json!({
             "format": "audio",
             "duration": duration,
             "segment_count": segments.len(),
             "whisper_model": self.whisper_model,
+            "transcription_backend": self.backend.name(),
+            "language": language,
+            "language_detected": language.is_some() && self.language.is_none(),
+            "speakers": speaker_count(&segments),
         });
+        if self.preprocessing.is_enabled() {
+            metadata["audio_preprocessing"] = preprocessing_metadata(&prepared);
+        }
 
         let mut doc = ParsedDocument::new(&content).with_metadata(metadata);
 
@@ -87,13 +140,50 @@ impl AudioParser {
     }
 
     /// Check if audio processing tools are available.
-    pub fn tools_available() -> ToolAvailability {
-        let whisper = which::which("whisper").is_ok();
-
-        ToolAvailability { whisper }
+    pub fn tools_available(&self) -> ToolAvailability {
+        ToolAvailability {
+            transcriber: self.backend.is_available(),
+            transcriber_tool: self.backend.tool(),
+            transcriber_hint: self.backend.install_hint(),
+        }
     }
 }
 
+/// Distinct speakers labeled in a diarized transcript.
+pub(super) fn speaker_count(segments: &[TranscriptSegment]) -> usize {
+    segments
+        .iter()
+        .filter_map(|s| s.speaker.as_deref())
+        .collect::<HashSet<_>>()
+        .len()
+}
+
+/// Run the audio cleanup, falling back to the untouched file if it fails:
+/// a worse transcript beats none.
+pub(super) fn prepare_audio(
+    path: &Path,
+    output_dir: &Path,
+    preprocessing: &AudioPreprocessing,
+) -> PreparedAudio {
+    preprocess_audio(path, output_dir, preprocessing).unwrap_or_else(|e| {
+        warn!("Audio preprocessing failed, transcribing as-is: {}", e);
+        PreparedAudio {
+            path: path.to_path_buf(),
+            removed: Vec::new(),
+            normalized: false,
+        }
+    })
+}
+
+/// What preprocessing did, for item metadata.
+pub(super) fn preprocessing_metadata(prepared: &PreparedAudio) -> serde_json::Value {
+    serde_json::json!({
+        "normalized": prepared.normalized,
+        "silences_cut": prepared.removed.len(),
+        "silence_cut_seconds": (prepared.removed_seconds() * 10.0).round() / 10.0,
+    })
+}
+
 /// Result of parsing an audio file.
 pub struct AudioParseResult {
     /// The parsed document (content + metadata).
@@ -105,23 +195,27 @@ pub struct AudioParseResult {
 /// Availability of required audio processing tools.
 #[derive(Debug)]
 pub struct ToolAvailability {
-    pub whisper: bool,
+    /// The transcription backend's tool.
+    pub transcriber: bool,
+    pub transcriber_tool: &'static str,
+    pub transcriber_hint: &'static str,
 }
 
 impl ToolAvailability {
     /// Check if all required tools are available.
     pub fn all_available(&self) -> bool {
-        self.whisper
+        self.transcriber
     }
 
     /// Get a message describing missing tools.
     pub fn missing_message(&self) -> Option<String> {
-        if self.whisper {
+        if self.transcriber {
             None
         } else {
-            Some(
-                "Missing tool: whisper. Install with:\n  pip install openai-whisper".to_string(),
-            )
+            Some(format!(
+                "Missing tool: {}. Install with:\n  {}",
+                self.transcriber_tool, self.transcriber_hint
+            ))
         }
     }
 }
@@ -132,7 +226,7 @@ mod tests {
 
     #[test]
     fn test_tool_availability() {
-        let avail = AudioParser::tools_available();
+        let avail = AudioParser::new("base").tools_available();
         // Just verify it doesn't panic
         let _ = avail.all_available();
         let _ = avail.missing_message();
@@ -0,0 +1,61 @@
+//! Image file parser with OCR support.
+
+use super::ParsedDocument;
+use crate::error::{IngestError, IngestResult};
+use olal_process::ocr_image;
+use std::path::Path;
+use tracing::info;
+
+/// Mean word confidence (0-100) below which OCR text is flagged for review.
+pub const LOW_CONFIDENCE: f32 = 60.0;
+
+/// Parser for images.
+/// Extracts the text in the image with Tesseract.
+pub struct ImageParser;
+
+impl ImageParser {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Parse an image by running OCR on it.
+    pub fn parse(&self, path: &Path) -> IngestResult<ParsedDocument> {
+        if !path.exists() {
+            return Err(IngestError::FileNotFound(path.to_path_buf()));
+        }
+
+        info!("Running OCR on image: {:?}", path);
+        let result = ocr_image(path)
+            .map_err(|e| IngestError::ProcessingError(format!("OCR failed: {}", e)))?;
+
+        let word_count = result.text.split_whitespace().count();
+        info!("OCR found {} words", word_count);
+
+        let low_confidence = word_count > 0
+            && result.confidence.is_some_and(|c| c < LOW_CONFIDENCE);
+        let metadata = serde_json::json!({
+            "format": "image",
+            "ocr_engine": "tesseract",
+            "ocr_confidence": result.confidence,
+            "ocr_word_count": word_count,
+            "ocr_low_confidence": low_confidence,
+        });
+
+        let mut doc = ParsedDocument::new(result.text).with_metadata(metadata);
+        if let Some(title) = path.file_stem().and_then(|n| n.to_str()) {
+            doc = doc.with_title(title);
+        }
+        Ok(doc)
+    }
+
+    /// Check if OCR tools are available.
+    pub fn tools_available() -> bool {
+        which::which("tesseract").is_ok()
+    }
+}
+
+impl Default for ImageParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
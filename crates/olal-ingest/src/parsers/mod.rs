@@ -1,12 +1,14 @@
 //! Document parsers for various file types.
 
 mod audio;
+mod image;
 mod markdown;
 mod pdf;
 mod text;
 mod video;
 
 pub use audio::AudioParser;
+pub use image::ImageParser;
 pub use markdown::MarkdownParser;
 pub use pdf::PdfParser;
 pub use text::TextParser;
@@ -25,6 +27,9 @@ pub struct ParsedDocument {
     pub title: Option<String>,
     /// Extracted metadata.
     pub metadata: serde_json::Value,
+    /// Byte offsets in `content` where each page starts, for paged
+    /// formats (PDF); empty otherwise.
+    pub pages: Vec<usize>,
 }
 
 impl ParsedDocument {
@@ -34,6 +39,7 @@ impl ParsedDocument {
             content: content.into(),
             title: None,
             metadata: serde_json::json!({}),
+            pages: Vec::new(),
         }
     }
 
@@ -48,6 +54,12 @@ impl ParsedDocument {
         self.metadata = metadata;
         self
     }
+
+    /// Set where each page starts in the content.
+    pub fn with_pages(mut self, pages: Vec<usize>) -> Self {
+        self.pages = pages;
+        self
+    }
 }
 
 /// Trait for document parsers.
@@ -77,6 +89,26 @@ pub fn get_parser(item_type: ItemType) -> Option<Box<dyn DocumentParser>> {
     }
 }
 
+/// Text files larger than this are chunked while being read instead of
+/// being loaded whole.
+pub const STREAM_THRESHOLD: u64 = 32 * 1024 * 1024;
+
+/// Describe a large text file for streamed ingestion.
+///
+/// Returns `None` when the file should be parsed normally: it is small, or
+/// its format (PDF) needs the whole file.
+pub fn streaming_document(path: &Path) -> IngestResult<Option<ParsedDocument>> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("");
+
+    if PdfParser::new().supports(extension) || std::fs::metadata(path)?.len() <= STREAM_THRESHOLD {
+        return Ok(None);
+    }
+    Ok(Some(TextParser::new().describe(path)?))
+}
+
 /// Parse a file based on its extension.
 pub fn parse_file(path: &Path) -> IngestResult<ParsedDocument> {
     let extension = path
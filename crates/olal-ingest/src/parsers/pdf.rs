@@ -1,17 +1,82 @@
 //! PDF document parser.
+//!
+//! Scanned PDFs, whose pages have little or no text layer, are rendered
+//! page by page and read with Tesseract instead. Simple tables (rows of
+//! aligned columns) become markdown tables, and embedded figures can be
+//! captioned and the caption added to their page.
 
+use super::image::LOW_CONFIDENCE;
 use super::{DocumentParser, ParsedDocument};
 use crate::error::{IngestError, IngestResult};
+use olal_process::{extract_pdf_images, ocr_image, rasterize_pdf, OcrResult};
 use std::path::Path;
-use tracing::debug;
+use tracing::{debug, info, warn};
+
+/// Pages averaging fewer non-whitespace characters than this are taken to
+/// be scans without a text layer.
+const SCANNED_CHARS_PER_PAGE: usize = 25;
+
+/// Resolution scanned pages are rendered at for OCR.
+const OCR_DPI: u32 = 300;
+
+/// Embedded images smaller than this on either side (icons, logos, rules)
+/// aren't figures.
+const MIN_FIGURE_SIDE: u32 = 150;
+
+/// Most figures captioned per document; each caption is a model call.
+const MAX_FIGURES: usize = 20;
+
+/// Fewest rows, header included, for aligned lines to count as a table.
+const MIN_TABLE_ROWS: usize = 3;
+
+/// Captions a figure, given its image.
+pub type CaptionFn<'a> = &'a dyn Fn(&Path) -> Option<String>;
 
 /// Parser for PDF files.
-pub struct PdfParser;
+pub struct PdfParser {
+    /// Whether to OCR scanned PDFs.
+    ocr: bool,
+}
 
 impl PdfParser {
     /// Create a new PDF parser.
     pub fn new() -> Self {
-        Self
+        Self { ocr: true }
+    }
+
+    /// Enable or disable OCR of scanned PDFs.
+    pub fn with_ocr(mut self, enabled: bool) -> Self {
+        self.ocr = enabled;
+        self
+    }
+
+    /// Check if the tools for OCR of scanned PDFs are available.
+    pub fn ocr_tools_available() -> bool {
+        which::which("pdftoppm").is_ok() && which::which("tesseract").is_ok()
+    }
+
+    /// Render each page and OCR it, returning the results in page order.
+    fn ocr_pages(&self, path: &Path) -> IngestResult<Vec<OcrResult>> {
+        if !self.ocr {
+            return Err(IngestError::ProcessingError("OCR is disabled".to_string()));
+        }
+        if !Self::ocr_tools_available() {
+            return Err(IngestError::ProcessingError(
+                "Missing tools: pdftoppm and tesseract. Install with:\n  brew install poppler tesseract"
+                    .to_string(),
+            ));
+        }
+
+        let dir = tempfile::tempdir()?;
+        let images = rasterize_pdf(path, dir.path(), OCR_DPI)
+            .map_err(|e| IngestError::ProcessingError(format!("Failed to render PDF pages: {}", e)))?;
+        info!("Running OCR on {} scanned pages of {:?}", images.len(), path);
+        images
+            .iter()
+            .map(|image| {
+                ocr_image(image).map_err(|e| IngestError::ProcessingError(format!("OCR failed: {}", e)))
+            })
+            .collect()
     }
 }
 
@@ -23,31 +88,86 @@ impl Default for PdfParser {
 
 impl DocumentParser for PdfParser {
     fn parse(&self, path: &Path) -> IngestResult<ParsedDocument> {
+        self.parse_pdf(path, None)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["pdf"]
+    }
+}
+
+impl PdfParser {
+    /// Parse a PDF, captioning its figures with `caption` (given the
+    /// figure's image) and adding each caption to its page's text.
+    pub fn parse_with_captions(
+        &self,
+        path: &Path,
+        caption: CaptionFn,
+    ) -> IngestResult<ParsedDocument> {
+        self.parse_pdf(path, Some(caption))
+    }
+
+    fn parse_pdf(
+        &self,
+        path: &Path,
+        caption: Option<CaptionFn>,
+    ) -> IngestResult<ParsedDocument> {
         if !path.exists() {
             return Err(IngestError::FileNotFound(path.to_path_buf()));
         }
 
         debug!("Parsing PDF: {:?}", path);
 
-        // Extract text from PDF
-        let content = pdf_extract::extract_text(path).map_err(|e| {
+        // Extract text page by page, remembering where each page starts
+        let mut pages = extract_pages(path).map_err(|e| {
             IngestError::ParseError {
                 path: path.to_path_buf(),
                 message: format!("Failed to extract text from PDF: {}", e),
             }
         })?;
 
-        // Clean up the extracted text
-        let content = clean_pdf_text(&content);
+        let mut metadata = serde_json::json!({ "format": "pdf" });
+        if looks_scanned(&pages) {
+            match self.ocr_pages(path) {
+                Ok(results) => {
+                    metadata = ocr_metadata(&results);
+                    pages = results.into_iter().map(|r| r.text).collect();
+                }
+                Err(e) => {
+                    warn!("{:?} looks scanned but wasn't OCR'd: {}", path, e);
+                    metadata["needs_ocr"] = true.into();
+                }
+            }
+        } else {
+            let mut tables = 0;
+            for page in &mut pages {
+                let (text, found) = format_tables(page);
+                *page = text;
+                tables += found;
+            }
+            if tables > 0 {
+                metadata["tables"] = tables.into();
+            }
 
-        // Count pages (rough estimate based on form feeds or content length)
-        let page_count = content.matches('\x0C').count().max(1);
+            // Scanned pages are images themselves, so only text PDFs have figures
+            if let Some(caption) = caption {
+                let mut figures = Vec::new();
+                for (page, text) in caption_figures(path, caption) {
+                    if let Some(page_text) = (page as usize).checked_sub(1).and_then(|i| pages.get_mut(i)) {
+                        page_text.push_str(&format!("\n\nFigure: {}", text));
+                        figures.push(serde_json::json!({ "page": page, "caption": text }));
+                    }
+                }
+                if !figures.is_empty() {
+                    metadata["figures"] = figures.into();
+                }
+            }
+        }
 
-        let metadata = serde_json::json!({
-            "format": "pdf",
-            "length": content.len(),
-            "pages": page_count,
-        });
+        let (content, page_starts) = join_pages(&pages);
+        let page_count = pages.len().max(1);
+        metadata["length"] = content.len().into();
+        metadata["pages"] = page_count.into();
 
         // Use filename as title
         let title = path
@@ -55,7 +175,9 @@ impl DocumentParser for PdfParser {
             .and_then(|n| n.to_str())
             .map(|s| s.to_string());
 
-        let mut doc = ParsedDocument::new(&content).with_metadata(metadata);
+        let mut doc = ParsedDocument::new(&content)
+            .with_metadata(metadata)
+            .with_pages(page_starts);
 
         if let Some(t) = title {
             doc = doc.with_title(t);
@@ -65,9 +187,259 @@ impl DocumentParser for PdfParser {
 
         Ok(doc)
     }
+}
 
-    fn extensions(&self) -> &[&str] {
-        &["pdf"]
+/// Caption the figures in a PDF, returning each caption with its page.
+/// Failures are logged and leave the document uncaptioned.
+fn caption_figures(path: &Path, caption: CaptionFn) -> Vec<(u32, String)> {
+    let images = tempfile::tempdir().map_err(IngestError::from).and_then(|dir| {
+        let images = extract_pdf_images(path, dir.path())
+            .map_err(|e| IngestError::ProcessingError(format!("Failed to extract images: {}", e)))?;
+        Ok((dir, images))
+    });
+    let (_dir, images) = match images {
+        Ok(images) => images,
+        Err(e) => {
+            warn!("Figures in {:?} weren't captioned: {}", path, e);
+            return Vec::new();
+        }
+    };
+
+    let figures: Vec<_> = images
+        .into_iter()
+        .filter(|image| image.width >= MIN_FIGURE_SIDE && image.height >= MIN_FIGURE_SIDE)
+        .take(MAX_FIGURES)
+        .collect();
+    info!("Captioning {} figures in {:?}", figures.len(), path);
+    figures
+        .into_iter()
+        .filter_map(|figure| Some((figure.page, caption(&figure.path)?)))
+        .collect()
+}
+
+/// Rewrite simple tables as markdown: a header line and at least
+/// [`MIN_TABLE_ROWS`] - 1 rows with a number in them, all splitting into
+/// the same columns. Column gaps left elsewhere become spaces. Returns the
+/// text and the number of tables found.
+fn format_tables(text: &str) -> (String, usize) {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out: Vec<String> = Vec::with_capacity(lines.len());
+    let mut tables = 0;
+    let mut i = 0;
+    while i < lines.len() {
+        let header = cells(lines[i]);
+        let mut rows = Vec::new();
+        if header.len() >= 2 {
+            for line in &lines[i + 1..] {
+                let row = cells(line);
+                if row.len() != header.len() || !row.iter().any(|cell| is_number(cell)) {
+                    break;
+                }
+                rows.push(row);
+            }
+        }
+
+        if rows.len() + 1 >= MIN_TABLE_ROWS {
+            out.push(String::new());
+            out.push(markdown_row(&header));
+            out.push(markdown_row(&vec!["---".to_string(); header.len()]));
+            out.extend(rows.iter().map(|row| markdown_row(row)));
+            out.push(String::new());
+            tables += 1;
+            i += rows.len() + 1;
+        } else {
+            out.push(lines[i].replace(COLUMN_BREAK, " "));
+            i += 1;
+        }
+    }
+    (out.join("\n"), tables)
+}
+
+/// A line's columns, split on the gaps [`LayoutText`] marked.
+fn cells(line: &str) -> Vec<String> {
+    line.split(COLUMN_BREAK)
+        .map(str::trim)
+        .filter(|cell| !cell.is_empty())
+        .map(|cell| cell.replace('|', "\\|"))
+        .collect()
+}
+
+fn markdown_row(cells: &[String]) -> String {
+    format!("| {} |", cells.join(" | "))
+}
+
+/// Whether a cell is a figure like `1,204`, `-3.5%` or `$12`.
+fn is_number(cell: &str) -> bool {
+    let digits: String = cell
+        .trim_start_matches(['$', '€', '£', '+', '-', '('])
+        .trim_end_matches(['%', ')'])
+        .replace(',', "");
+    digits.parse::<f64>().is_ok()
+}
+
+/// Whether extracted pages have too little text to be anything but scans.
+fn looks_scanned(pages: &[String]) -> bool {
+    let chars: usize = pages
+        .iter()
+        .map(|page| page.chars().filter(|c| !c.is_whitespace()).count())
+        .sum();
+    !pages.is_empty() && chars < SCANNED_CHARS_PER_PAGE * pages.len()
+}
+
+/// OCR metadata for a scanned PDF, matching what images record, plus the
+/// confidence and word count of each page. The overall confidence is the
+/// mean over words.
+fn ocr_metadata(results: &[OcrResult]) -> serde_json::Value {
+    let mut word_count = 0;
+    let mut weighted = 0.0;
+    let mut weighed_words = 0;
+    let mut pages = Vec::with_capacity(results.len());
+    for (i, result) in results.iter().enumerate() {
+        let words = result.text.split_whitespace().count();
+        word_count += words;
+        if let Some(confidence) = result.confidence {
+            weighted += confidence * words as f32;
+            weighed_words += words;
+        }
+        pages.push(serde_json::json!({
+            "page": i + 1,
+            "confidence": result.confidence,
+            "words": words,
+        }));
+    }
+
+    let confidence = (weighed_words > 0).then(|| weighted / weighed_words as f32);
+    serde_json::json!({
+        "format": "pdf",
+        "ocr_engine": "tesseract",
+        "ocr_confidence": confidence,
+        "ocr_word_count": word_count,
+        "ocr_low_confidence": confidence.is_some_and(|c| c < LOW_CONFIDENCE),
+        "ocr_pages": pages,
+    })
+}
+
+/// Separator between pages in the extracted text.
+const PAGE_BREAK: &str = "\n\n---\n\n";
+
+/// Clean each page's text and join them with [`PAGE_BREAK`], returning the
+/// text and the byte offset where each page starts.
+fn join_pages(pages: &[String]) -> (String, Vec<usize>) {
+    let mut content = String::new();
+    let mut starts = Vec::with_capacity(pages.len());
+    for (i, page) in pages.iter().enumerate() {
+        if i > 0 {
+            content.push_str(PAGE_BREAK);
+        }
+        starts.push(content.len());
+        content.push_str(clean_pdf_text(page).trim());
+    }
+    (content, starts)
+}
+
+/// Marks a gap between columns in extracted text.
+const COLUMN_BREAK: char = '\t';
+
+/// Horizontal gap, in ems, from which text is taken to be in another
+/// column rather than the next word.
+const COLUMN_GAP: f64 = 1.0;
+
+/// Extract the text of each page, marking wide horizontal gaps (table
+/// columns) with [`COLUMN_BREAK`].
+fn extract_pages(path: &Path) -> Result<Vec<String>, pdf_extract::OutputError> {
+    let mut doc = pdf_extract::Document::load(path)?;
+    if doc.is_encrypted() {
+        doc.decrypt("")?;
+    }
+
+    let mut pages = Vec::new();
+    for page in doc.get_pages().into_keys() {
+        let mut output = LayoutText::default();
+        // Like pdf_extract, stop at the first page that can't be read
+        if pdf_extract::output_doc_page(&doc, &mut output, page).is_err() {
+            break;
+        }
+        pages.push(output.text);
+    }
+    Ok(pages)
+}
+
+/// pdf_extract's plain text output, except that it tells word spacing from
+/// column gaps: its own output writes a single space for both.
+struct LayoutText {
+    text: String,
+    first_char: bool,
+    last_end: f64,
+    last_y: f64,
+}
+
+impl Default for LayoutText {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            first_char: false,
+            last_end: f64::MAX,
+            last_y: 0.0,
+        }
+    }
+}
+
+impl pdf_extract::OutputDev for LayoutText {
+    fn begin_page(
+        &mut self,
+        _page_num: u32,
+        _media_box: &pdf_extract::MediaBox,
+        _art_box: Option<(f64, f64, f64, f64)>,
+    ) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
+    }
+
+    fn end_page(&mut self) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
+    }
+
+    fn output_character(
+        &mut self,
+        trm: &pdf_extract::Transform,
+        width: f64,
+        _spacing: f64,
+        font_size: f64,
+        char: &str,
+    ) -> Result<(), pdf_extract::OutputError> {
+        // Font size as drawn: the side of a square of the transformed area
+        let size = font_size * (trm.m11 * trm.m22 - trm.m12 * trm.m21).abs().sqrt();
+        let (x, y) = (trm.m31, trm.m32);
+        if self.first_char {
+            let moved = (y - self.last_y).abs();
+            if moved > size * 1.5 {
+                self.text.push('\n');
+            }
+            if x < self.last_end && moved > size * 0.5 {
+                self.text.push('\n');
+            } else if x > self.last_end + size * COLUMN_GAP {
+                self.text.push(COLUMN_BREAK);
+            } else if x > self.last_end + size * 0.1 {
+                self.text.push(' ');
+            }
+        }
+        self.text.push_str(char);
+        self.first_char = false;
+        self.last_y = y;
+        self.last_end = x + width * size;
+        Ok(())
+    }
+
+    fn begin_word(&mut self) -> Result<(), pdf_extract::OutputError> {
+        self.first_char = true;
+        Ok(())
+    }
+
+    fn end_word(&mut self) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
+    }
+
+    fn end_line(&mut self) -> Result<(), pdf_extract::OutputError> {
+        Ok(())
     }
 }
 
@@ -86,7 +458,7 @@ fn clean_pdf_text(text: &str) -> String {
         })
         .join("\n")
         // Remove form feed characters used as page breaks
-        .replace('\x0C', "\n\n---\n\n")
+        .replace('\x0C', PAGE_BREAK)
 }
 
 #[cfg(test)]
@@ -100,6 +472,97 @@ mod tests {
         assert!(!cleaned.contains("\n\n\n")); // No triple newlines
     }
 
+    #[test]
+    fn test_join_pages() {
+        let pages = vec!["  First page \n".to_string(), "\nSecond page".to_string()];
+        let (content, starts) = join_pages(&pages);
+        assert_eq!(content, "First page\n\n---\n\nSecond page");
+        assert_eq!(starts, vec![0, 17]);
+        assert!(content[starts[1]..].starts_with("Second"));
+    }
+
+    #[test]
+    fn test_looks_scanned() {
+        let scanned = vec!["\n\x0C".to_string(), "  12 ".to_string(), String::new()];
+        assert!(looks_scanned(&scanned));
+        let text = vec![
+            "A page with a real text layer on it.".to_string(),
+            "And a second page, which has plenty of text as well.".to_string(),
+        ];
+        assert!(!looks_scanned(&text));
+        assert!(!looks_scanned(&[]));
+    }
+
+    #[test]
+    fn test_ocr_metadata() {
+        let results = vec![
+            OcrResult {
+                text: "one two three".to_string(),
+                confidence: Some(90.0),
+            },
+            OcrResult {
+                text: "four".to_string(),
+                confidence: Some(10.0),
+            },
+            OcrResult {
+                text: String::new(),
+                confidence: None,
+            },
+        ];
+        let metadata = ocr_metadata(&results);
+        assert_eq!(metadata["ocr_word_count"], 4);
+        assert_eq!(metadata["ocr_confidence"], 70.0);
+        assert_eq!(metadata["ocr_low_confidence"], false);
+        assert_eq!(metadata["ocr_pages"][1]["page"], 2);
+        assert_eq!(metadata["ocr_pages"][2]["confidence"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_format_tables() {
+        let text = "Quarterly results\n\
+                    Region\tQ1\tQ2\n\
+                    North\t1,204\t1,380\n\
+                    South\t-3.5%\t$12\n\
+                    All figures\tare unaudited.";
+        let (formatted, tables) = format_tables(text);
+        assert_eq!(tables, 1);
+        assert_eq!(
+            formatted,
+            "Quarterly results\n\n\
+             | Region | Q1 | Q2 |\n\
+             | --- | --- | --- |\n\
+             | North | 1,204 | 1,380 |\n\
+             | South | -3.5% | $12 |\n\n\
+             All figures are unaudited."
+        );
+    }
+
+    #[test]
+    fn test_parse_table_pdf() {
+        // A page with a title, a three-column table placed with text
+        // positioning, and a closing sentence
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/report.pdf");
+        let doc = PdfParser::new().with_ocr(false).parse(&path).unwrap();
+        assert_eq!(doc.metadata["tables"], 1);
+        assert!(
+            doc.content.contains(
+                "| Region | Q1 | Q2 |\n\
+                 | --- | --- | --- |\n\
+                 | North | 1,204 | 1,380 |\n\
+                 | South | -3.5% | $12 |"
+            ),
+            "{}",
+            doc.content
+        );
+        assert!(doc.content.contains("All figures are unaudited."), "{}", doc.content);
+    }
+
+    #[test]
+    fn test_format_tables_needs_numbers() {
+        let text = "Name\tRole\nAda\tEngineer\nAlan\tMathematician";
+        assert_eq!(format_tables(text), ("Name Role\nAda Engineer\nAlan Mathematician".to_string(), 0));
+    }
+
     #[test]
     fn test_pdf_parser_extensions() {
         let parser = PdfParser::new();
@@ -1,6 +1,6 @@
 //! Plain text document parser.
 
-use super::{DocumentParser, ParsedDocument};
+use super::{DocumentParser, MarkdownParser, ParsedDocument};
 use crate::error::{IngestError, IngestResult};
 use std::path::Path;
 
@@ -70,6 +70,45 @@ impl Default for TextParser {
     }
 }
 
+impl TextParser {
+    /// Describe a file without reading its content, for streamed ingestion.
+    ///
+    /// The returned document has empty content; the caller chunks the file
+    /// incrementally instead.
+    pub fn describe(&self, path: &Path) -> IngestResult<ParsedDocument> {
+        if !path.exists() {
+            return Err(IngestError::FileNotFound(path.to_path_buf()));
+        }
+
+        let extension = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or("");
+        let format = if Self::is_code_file(extension) {
+            "code"
+        } else if MarkdownParser::new().supports(extension) {
+            "markdown"
+        } else {
+            "text"
+        };
+
+        let mut metadata = serde_json::json!({
+            "format": format,
+            "length": std::fs::metadata(path)?.len(),
+            "streamed": true,
+        });
+        if let Some(lang) = Self::detect_language(extension) {
+            metadata["language"] = serde_json::json!(lang);
+        }
+
+        let mut doc = ParsedDocument::new("").with_metadata(metadata);
+        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+            doc = doc.with_title(name);
+        }
+        Ok(doc)
+    }
+}
+
 impl DocumentParser for TextParser {
     fn parse(&self, path: &Path) -> IngestResult<ParsedDocument> {
         if !path.exists() {
@@ -1,17 +1,30 @@
 //! Video file parser with transcription support.
 
+use super::audio::{prepare_audio, preprocessing_metadata};
 use super::ParsedDocument;
 use crate::error::{IngestError, IngestResult};
-use olal_process::{extract_audio, get_video_info, transcribe_audio, TranscriptSegment};
+use olal_process::{
+    extract_audio, extract_frames, get_video_info, ocr_images_deduplicated, AudioPreprocessing,
+    TranscribeOptions, TranscriptSegment, TranscriptionBackend, WhisperCli,
+};
 use std::path::Path;
 use tempfile::tempdir;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// Parser for video files.
-/// Extracts audio and transcribes using Whisper.
+/// Extracts audio and transcribes it, and optionally OCRs frames
+/// so on-screen text (code, slides, terminals) is searchable too.
 pub struct VideoParser {
     /// Whisper model to use (tiny, base, small, medium, large)
     whisper_model: String,
+    backend: Box<dyn TranscriptionBackend>,
+    /// Label speakers, if the backend can.
+    diarize: bool,
+    /// Language spoken; `None` detects it.
+    language: Option<String>,
+    /// Seconds between OCR'd frames; `None` skips frame OCR.
+    ocr_interval: Option<u64>,
+    preprocessing: AudioPreprocessing,
 }
 
 impl VideoParser {
@@ -19,12 +32,43 @@ impl VideoParser {
     pub fn new(whisper_model: impl Into<String>) -> Self {
         Self {
             whisper_model: whisper_model.into(),
+            backend: Box::new(WhisperCli),
+            diarize: false,
+            language: None,
+            ocr_interval: None,
+            preprocessing: AudioPreprocessing::default(),
         }
     }
 
-    /// Create a video parser with the default model (base).
-    pub fn with_default_model() -> Self {
-        Self::new("base")
+    /// Transcribe with `backend` instead of the whisper CLI.
+    pub fn with_backend(mut self, backend: Box<dyn TranscriptionBackend>) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Label who is speaking, with backends that support diarization.
+    pub fn with_diarization(mut self, diarize: bool) -> Self {
+        self.diarize = diarize;
+        self
+    }
+
+    /// OCR a frame every `interval_seconds` and merge the screen text into
+    /// the transcript. Skipped when tesseract isn't installed.
+    pub fn with_frame_ocr(mut self, interval_seconds: u64) -> Self {
+        self.ocr_interval = Some(interval_seconds.max(1));
+        self
+    }
+
+    /// Transcribe as `language` instead of detecting it.
+    pub fn with_language(mut self, language: Option<String>) -> Self {
+        self.language = language;
+        self
+    }
+
+    /// Clean up the extracted audio before transcribing it.
+    pub fn with_preprocessing(mut self, preprocessing: AudioPreprocessing) -> Self {
+        self.preprocessing = preprocessing;
+        self
     }
 
     /// Parse a video file by extracting audio and transcribing.
@@ -50,20 +94,71 @@ impl VideoParser {
             IngestError::ProcessingError(format!("Failed to create temp directory: {}", e))
         })?;
 
-        // Extract audio
-        info!("Extracting audio...");
-        let audio_path = extract_audio(path, temp_dir.path()).map_err(|e| {
-            IngestError::ProcessingError(format!("Failed to extract audio: {}", e))
-        })?;
-
-        // Transcribe
-        info!("Transcribing with Whisper ({})...", self.whisper_model);
-        let segments = transcribe_audio(&audio_path, &self.whisper_model, temp_dir.path())
-            .map_err(|e| {
-                IngestError::ProcessingError(format!("Failed to transcribe: {}", e))
+        // Screen recordings often have no audio track at all
+        let mut preprocessed = None;
+        let mut language = None;
+        let segments = if video_info.audio_codec.is_some() {
+            // Extract audio
+            info!("Extracting audio...");
+            let audio_path = extract_audio(path, temp_dir.path()).map_err(|e| {
+                IngestError::ProcessingError(format!("Failed to extract audio: {}", e))
             })?;
 
-        info!("Transcribed {} segments", segments.len());
+            // Transcribe
+            let prepared = prepare_audio(&audio_path, temp_dir.path(), &self.preprocessing);
+            info!("Transcribing with {} ({})...", self.backend.name(), self.whisper_model);
+            let options = TranscribeOptions::for_backend(
+                self.backend.as_ref(),
+                self.whisper_model.clone(),
+                self.diarize,
+                self.language.clone(),
+            );
+            let transcript = self
+                .backend
+                .transcribe(&prepared.path, temp_dir.path(), &options)
+                .map_err(|e| {
+                    IngestError::ProcessingError(format!("Failed to transcribe: {}", e))
+                })?;
+            let mut segments = transcript.segments;
+            prepared.restore_timestamps(&mut segments);
+            language = transcript.language;
+
+            info!("Transcribed {} segments", segments.len());
+            if self.preprocessing.is_enabled() {
+                preprocessed = Some(preprocessing_metadata(&prepared));
+            }
+            segments
+        } else {
+            info!("No audio track, skipping transcription");
+            Vec::new()
+        };
+
+        // OCR frames
+        let mut ocr_frames = 0;
+        let screens = match self.ocr_interval {
+            Some(interval) if which::which("tesseract").is_ok() => {
+                info!("Running OCR on frames every {}s...", interval);
+                let frames = extract_frames(path, temp_dir.path(), interval).map_err(|e| {
+                    IngestError::ProcessingError(format!("Failed to extract frames: {}", e))
+                })?;
+                ocr_frames = frames.len();
+                let screens: Vec<(f64, String)> = ocr_images_deduplicated(&frames)
+                    .map_err(|e| IngestError::ProcessingError(format!("Frame OCR failed: {}", e)))?
+                    .into_iter()
+                    .map(|(index, result)| ((index as u64 * interval) as f64, result.text))
+                    .collect();
+                info!("Found distinct screen text in {} of {} frames", screens.len(), frames.len());
+                screens
+            }
+            Some(_) => {
+                warn!("Missing tool: tesseract, skipping frame OCR");
+                Vec::new()
+            }
+            None => Vec::new(),
+        };
+        let screen_count = screens.len();
+        let transcript_count = segments.len();
+        let segments = merge_screen_text(segments, screens, video_info.duration);
 
         // Build content from segments
         let content = segments
@@ -78,7 +173,7 @@ impl VideoParser {
             .and_then(|n| n.to_str())
             .map(|s| s.to_string());
 
-        let metadata = serde_json::json!({
+        let mut metadata = serde_json::json!({
             "format": "video",
             "duration": video_info.duration,
             "width": video_info.width,
@@ -86,9 +181,19 @@ impl VideoParser {
             "video_codec": video_info.video_codec,
             "audio_codec": video_info.audio_codec,
             "fps": video_info.fps,
-            "segment_count": segments.len(),
+            "segment_count": transcript_count,
             "whisper_model": self.whisper_model,
+            "transcription_backend": self.backend.name(),
+            "language": language,
+            "language_detected": language.is_some() && self.language.is_none(),
+            "speakers": super::audio::speaker_count(&segments),
+            "ocr_interval_seconds": self.ocr_interval,
+            "ocr_frames": ocr_frames,
+            "screen_text_segments": screen_count,
         });
+        if let Some(preprocessed) = preprocessed {
+            metadata["audio_preprocessing"] = preprocessed;
+        }
 
         let mut doc = ParsedDocument::new(&content).with_metadata(metadata);
 
@@ -103,19 +208,55 @@ impl VideoParser {
     }
 
     /// Check if video processing tools are available.
-    pub fn tools_available() -> ToolAvailability {
-        let ffmpeg = which::which("ffmpeg").is_ok();
-        let ffprobe = which::which("ffprobe").is_ok();
-        let whisper = which::which("whisper").is_ok();
-
+    pub fn tools_available(&self) -> ToolAvailability {
         ToolAvailability {
-            ffmpeg,
-            ffprobe,
-            whisper,
+            ffmpeg: which::which("ffmpeg").is_ok(),
+            ffprobe: which::which("ffprobe").is_ok(),
+            transcriber: self.backend.is_available(),
+            transcriber_tool: self.backend.tool(),
+            transcriber_hint: self.backend.install_hint(),
         }
     }
 }
 
+/// Label in front of text read off the screen, so it reads apart from speech.
+const SCREEN_PREFIX: &str = "[Screen]";
+
+/// Interleave on-screen text with the transcript by time. `screens` holds
+/// (start, text) pairs in order; each lasts until the next one, or the end
+/// of the video.
+fn merge_screen_text(
+    transcript: Vec<TranscriptSegment>,
+    screens: Vec<(f64, String)>,
+    duration: f64,
+) -> Vec<TranscriptSegment> {
+    let ends: Vec<f64> = screens
+        .iter()
+        .skip(1)
+        .map(|(start, _)| *start)
+        .chain(std::iter::once(duration))
+        .collect();
+
+    let mut segments: Vec<TranscriptSegment> = screens
+        .into_iter()
+        .zip(ends)
+        .filter_map(|((start, text), end)| {
+            let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+            (!text.is_empty()).then(|| TranscriptSegment {
+                text: format!("{} {}", SCREEN_PREFIX, text),
+                start,
+                end: end.max(start),
+                speaker: None,
+                words: Vec::new(),
+            })
+        })
+        .collect();
+    segments.extend(transcript);
+    // Stable: speech stays in order, screen text goes first at equal times
+    segments.sort_by(|a, b| a.start.total_cmp(&b.start));
+    segments
+}
+
 /// Result of parsing a video file.
 pub struct VideoParseResult {
     /// The parsed document (content + metadata).
@@ -129,34 +270,43 @@ pub struct VideoParseResult {
 pub struct ToolAvailability {
     pub ffmpeg: bool,
     pub ffprobe: bool,
-    pub whisper: bool,
+    /// The transcription backend's tool.
+    pub transcriber: bool,
+    pub transcriber_tool: &'static str,
+    pub transcriber_hint: &'static str,
 }
 
 impl ToolAvailability {
     /// Check if all required tools are available.
     pub fn all_available(&self) -> bool {
-        self.ffmpeg && self.ffprobe && self.whisper
+        self.ffmpeg && self.ffprobe && self.transcriber
     }
 
     /// Get a message describing missing tools.
     pub fn missing_message(&self) -> Option<String> {
         let mut missing = Vec::new();
+        let mut hints = Vec::new();
         if !self.ffmpeg {
             missing.push("ffmpeg");
         }
         if !self.ffprobe {
             missing.push("ffprobe");
         }
-        if !self.whisper {
-            missing.push("whisper");
+        if !self.ffmpeg || !self.ffprobe {
+            hints.push("brew install ffmpeg");
+        }
+        if !self.transcriber {
+            missing.push(self.transcriber_tool);
+            hints.push(self.transcriber_hint);
         }
 
         if missing.is_empty() {
             None
         } else {
             Some(format!(
-                "Missing tools: {}. Install with:\n  brew install ffmpeg\n  pip install openai-whisper",
-                missing.join(", ")
+                "Missing tools: {}. Install with:\n  {}",
+                missing.join(", "),
+                hints.join("\n  ")
             ))
         }
     }
@@ -168,9 +318,40 @@ mod tests {
 
     #[test]
     fn test_tool_availability() {
-        let avail = VideoParser::tools_available();
+        let avail = VideoParser::new("base").tools_available();
         // Just verify it doesn't panic
         let _ = avail.all_available();
         let _ = avail.missing_message();
     }
+
+    #[test]
+    fn test_merge_screen_text() {
+        let segment = |text: &str, start: f64, end: f64| TranscriptSegment {
+            text: text.to_string(),
+            start,
+            end,
+            speaker: None,
+            words: Vec::new(),
+        };
+        let transcript = vec![segment("Let's open the config.", 2.0, 6.0), segment("Now run it.", 21.0, 24.0)];
+        let screens = vec![
+            (0.0, "fn main() {\n    run();\n}".to_string()),
+            (10.0, "   ".to_string()),
+            (20.0, "$ cargo run".to_string()),
+        ];
+
+        let merged = merge_screen_text(transcript, screens, 30.0);
+        let texts: Vec<&str> = merged.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "[Screen] fn main() { run(); }",
+                "Let's open the config.",
+                "[Screen] $ cargo run",
+                "Now run it.",
+            ]
+        );
+        assert_eq!((merged[0].start, merged[0].end), (0.0, 10.0));
+        assert_eq!((merged[2].start, merged[2].end), (20.0, 30.0));
+    }
 }
@@ -0,0 +1,244 @@
+//! Parsing Pocket and Instapaper exports into bookmarks.
+//!
+//! Saved-at times are kept, and read-later status becomes tags: archived
+//! articles get [`ARCHIVED_TAG`] and favorites (Instapaper's "Starred")
+//! get [`FAVORITE_TAG`], alongside the service's own tags.
+
+use crate::bookmarks::{attribute, Bookmark};
+use crate::error::{IngestError, IngestResult};
+use crate::web::decode_entities;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Tag for articles archived (marked read) in the read-later service.
+pub const ARCHIVED_TAG: &str = "archived";
+
+/// Tag for articles favorited or starred in the read-later service.
+pub const FAVORITE_TAG: &str = "favorite";
+
+/// Parse a Pocket export, HTML (`ril_export.html`) or CSV.
+pub fn parse_pocket_file(path: &Path) -> IngestResult<Vec<Bookmark>> {
+    let content = std::fs::read_to_string(path)?;
+    let trimmed = content.trim_start_matches('\u{feff}').trim_start();
+    if trimmed.starts_with('<') {
+        Ok(parse_pocket_html(trimmed))
+    } else {
+        parse_pocket_csv(trimmed).map_err(|message| parse_error(path, message))
+    }
+}
+
+/// Parse an Instapaper CSV export.
+pub fn parse_instapaper_file(path: &Path) -> IngestResult<Vec<Bookmark>> {
+    let content = std::fs::read_to_string(path)?;
+    parse_instapaper_csv(content.trim_start_matches('\u{feff}')).map_err(|message| parse_error(path, message))
+}
+
+fn parse_error(path: &Path, message: String) -> IngestError {
+    IngestError::ParseError {
+        path: path.to_path_buf(),
+        message,
+    }
+}
+
+/// Pocket's HTML export: an `<h1>` per list ("Unread", "Read Archive"),
+/// each followed by `<a href time_added tags>` links.
+fn parse_pocket_html(html: &str) -> Vec<Bookmark> {
+    let lower = html.to_ascii_lowercase();
+    let mut bookmarks = Vec::new();
+    let mut archived = false;
+    let mut pos = 0;
+
+    while let Some(offset) = lower[pos..].find('<') {
+        let open = pos + offset;
+        let Some(close) = lower[open..].find('>').map(|i| open + i) else {
+            break;
+        };
+        let tag = &lower[open + 1..close];
+        pos = close + 1;
+
+        if tag.starts_with("h1") {
+            let end = lower[pos..].find("</h1").map_or(lower.len(), |i| pos + i);
+            archived = lower[pos..end].contains("archive");
+            pos = end;
+        } else if tag.starts_with("a ") {
+            let end = lower[pos..].find("</a").map_or(lower.len(), |i| pos + i);
+            let source = &html[open + 1..close];
+            if let Some(url) = attribute(source, "href") {
+                let mut tags = split_tags(&attribute(source, "tags").unwrap_or_default(), ',');
+                if archived {
+                    tags.push(ARCHIVED_TAG.to_string());
+                }
+                let added_at = attribute(source, "time_added").and_then(|secs| timestamp(&secs));
+                let title = decode_entities(html[pos..end].trim());
+                bookmarks.extend(saved(&url, &title, Vec::new(), tags, added_at));
+            }
+            pos = end;
+        }
+    }
+    bookmarks
+}
+
+/// Pocket's CSV export: `title,url,time_added,tags,status`, with tags
+/// separated by `|` and status `unread` or `archive`.
+fn parse_pocket_csv(content: &str) -> Result<Vec<Bookmark>, String> {
+    let mut bookmarks = Vec::new();
+    for row in csv_rows(content)? {
+        let mut tags = split_tags(row.get("tags").map_or("", String::as_str), '|');
+        if row.get("status").is_some_and(|s| s.eq_ignore_ascii_case("archive")) {
+            tags.push(ARCHIVED_TAG.to_string());
+        }
+        if row.get("favorite").is_some_and(|f| f == "1" || f.eq_ignore_ascii_case("true")) {
+            tags.push(FAVORITE_TAG.to_string());
+        }
+        let added_at = row.get("time_added").and_then(|t| timestamp(t));
+        bookmarks.extend(saved(
+            row.get("url").map_or("", String::as_str),
+            row.get("title").map_or("", String::as_str),
+            Vec::new(),
+            tags,
+            added_at,
+        ));
+    }
+    Ok(bookmarks)
+}
+
+/// Instapaper's CSV export: `URL,Title,Selection,Folder,Timestamp` and, in
+/// newer exports, `Tags` as a JSON array. "Archive" and "Starred" folders
+/// become tags; other folders besides "Unread" are kept as folders.
+fn parse_instapaper_csv(content: &str) -> Result<Vec<Bookmark>, String> {
+    let mut bookmarks = Vec::new();
+    for row in csv_rows(content)? {
+        let mut tags = match row.get("tags").map(|t| t.trim()) {
+            Some(json) if json.starts_with('[') => serde_json::from_str(json).unwrap_or_default(),
+            Some(list) => split_tags(list, ','),
+            None => Vec::new(),
+        };
+        let mut folders = Vec::new();
+        match row.get("folder").map(|f| f.trim()).unwrap_or_default() {
+            "" | "Unread" => {}
+            "Archive" => tags.push(ARCHIVED_TAG.to_string()),
+            "Starred" => tags.push(FAVORITE_TAG.to_string()),
+            folder => folders.push(folder.to_string()),
+        }
+        let added_at = row.get("timestamp").and_then(|t| timestamp(t));
+        bookmarks.extend(saved(
+            row.get("url").map_or("", String::as_str),
+            row.get("title").map_or("", String::as_str),
+            folders,
+            tags,
+            added_at,
+        ));
+    }
+    Ok(bookmarks)
+}
+
+/// CSV records keyed by lowercased header.
+fn csv_rows(content: &str) -> Result<Vec<HashMap<String, String>>, String> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(content.as_bytes());
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Invalid CSV: {}", e))?
+        .iter()
+        .map(|h| h.trim().to_lowercase())
+        .collect();
+    if !headers.iter().any(|h| h == "url") {
+        return Err("CSV has no URL column".to_string());
+    }
+
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|e| format!("Invalid CSV: {}", e))?;
+            Ok(headers.iter().cloned().zip(record.iter().map(str::to_string)).collect())
+        })
+        .collect()
+}
+
+fn split_tags(tags: &str, separator: char) -> Vec<String> {
+    tags.split(separator)
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn timestamp(secs: &str) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(secs.trim().parse().ok()?, 0)
+}
+
+/// A bookmark for a saved article, if `url` is a web page.
+fn saved(
+    url: &str,
+    title: &str,
+    folders: Vec<String>,
+    tags: Vec<String>,
+    added_at: Option<DateTime<Utc>>,
+) -> Option<Bookmark> {
+    let url = url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return None;
+    }
+    let title = title.trim();
+    Some(Bookmark {
+        url: url.to_string(),
+        title: if title.is_empty() { url } else { title }.to_string(),
+        folders,
+        tags,
+        added_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pocket_html() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Pocket Export</title></head><body>
+<h1>Unread</h1>
+<ul>
+<li><a href="https://example.com/a" time_added="1600000000" tags="rust,async">Article &amp; more</a></li>
+</ul>
+<h1>Read Archive</h1>
+<ul>
+<li><a href="https://example.com/b" time_added="1500000000" tags="">https://example.com/b</a></li>
+</ul>
+</body></html>"#;
+        let bookmarks = parse_pocket_html(html);
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].title, "Article & more");
+        assert_eq!(bookmarks[0].tags, vec!["rust", "async"]);
+        assert_eq!(bookmarks[0].added_at.unwrap().timestamp(), 1_600_000_000);
+        assert_eq!(bookmarks[1].tags, vec![ARCHIVED_TAG]);
+    }
+
+    #[test]
+    fn test_pocket_csv() {
+        let csv = "title,url,time_added,cursor,tags,status\n\
+                   \"Hello, world\",https://example.com/a,1600000000,,rust|web,archive\n\
+                   Second,https://example.com/b,1600000001,,,unread\n";
+        let bookmarks = parse_pocket_csv(csv).unwrap();
+        assert_eq!(bookmarks.len(), 2);
+        assert_eq!(bookmarks[0].title, "Hello, world");
+        assert_eq!(bookmarks[0].tags, vec!["rust", "web", ARCHIVED_TAG]);
+        assert!(bookmarks[1].tags.is_empty());
+    }
+
+    #[test]
+    fn test_instapaper_csv() {
+        let csv = "URL,Title,Selection,Folder,Timestamp,Tags\n\
+                   https://example.com/a,Starred one,,Starred,1600000000,\"[\"\"ml\"\"]\"\n\
+                   https://example.com/b,Read one,,Archive,1600000001,\n\
+                   https://example.com/c,Filed one,,Research,1600000002,\n\
+                   https://example.com/d,,,Unread,,\n";
+        let bookmarks = parse_instapaper_csv(csv).unwrap();
+        assert_eq!(bookmarks.len(), 4);
+        assert_eq!(bookmarks[0].tags, vec!["ml", FAVORITE_TAG]);
+        assert_eq!(bookmarks[1].tags, vec![ARCHIVED_TAG]);
+        assert_eq!(bookmarks[2].folders, vec!["Research"]);
+        assert_eq!(bookmarks[3].title, "https://example.com/d");
+        assert!(bookmarks[3].added_at.is_none());
+    }
+}
@@ -0,0 +1,237 @@
+//! Retention policy engine.
+//!
+//! Applies the `[[retention.rules]]` from config: archiving, deleting or
+//! compressing old items and purging stale queue entries. Runs as the
+//! `retention` job in `[schedule]` and on demand with `olal retention`.
+
+use crate::error::{IngestError, IngestResult};
+use chrono::{DateTime, Duration, Utc};
+use olal_config::{AppPaths, Config, RetentionAction, RetentionRule};
+use olal_core::{ItemType, QueueStatus};
+use olal_db::Database;
+use tracing::{info, warn};
+
+/// What one rule matched and did.
+#[derive(Debug)]
+pub struct RuleOutcome {
+    /// Human-readable rule description.
+    pub rule: String,
+    pub action: RetentionAction,
+    /// Matched items or queue entries: (title or path, short ID).
+    pub matched: Vec<(String, String)>,
+    /// Bytes saved by compression (or that would be, in a dry run).
+    pub bytes_saved: i64,
+}
+
+/// Result of applying all retention rules.
+#[derive(Debug, Default)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub outcomes: Vec<RuleOutcome>,
+}
+
+impl RetentionReport {
+    /// Whether no rule matched anything.
+    pub fn is_empty(&self) -> bool {
+        self.outcomes.iter().all(|o| o.matched.is_empty())
+    }
+
+    /// Total items and queue entries matched across rules.
+    pub fn total_matched(&self) -> usize {
+        self.outcomes.iter().map(|o| o.matched.len()).sum()
+    }
+
+    /// Render the report as markdown.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("## Retention\n\n");
+        if self.outcomes.is_empty() {
+            out.push_str("No retention rules configured.\n");
+            return out;
+        }
+        if self.dry_run {
+            out.push_str("Dry run - nothing was changed.\n\n");
+        }
+
+        for outcome in &self.outcomes {
+            out.push_str(&format!("- {}: {} matched", outcome.rule, outcome.matched.len()));
+            if outcome.action == RetentionAction::Compress && outcome.bytes_saved > 0 {
+                out.push_str(&format!(
+                    ", {:.1} MB {}",
+                    outcome.bytes_saved as f64 / 1_048_576.0,
+                    if self.dry_run { "uncompressed" } else { "saved" }
+                ));
+            }
+            out.push('\n');
+            for (title, id) in outcome.matched.iter().take(10) {
+                out.push_str(&format!("  - {} [{}]\n", title, id));
+            }
+            if outcome.matched.len() > 10 {
+                out.push_str(&format!("  - ...and {} more\n", outcome.matched.len() - 10));
+            }
+        }
+
+        out
+    }
+}
+
+/// Apply every configured retention rule, in order.
+///
+/// With `dry_run`, reports what each rule would affect without changing
+/// anything. Deleting items doesn't remove artifacts on disk; use
+/// `olal delete --artifacts` for that.
+pub fn run_retention(db: &Database, config: &Config, dry_run: bool) -> IngestResult<RetentionReport> {
+    let now = Utc::now();
+    let mut report = RetentionReport {
+        dry_run,
+        outcomes: Vec::new(),
+    };
+
+    for rule in &config.retention.rules {
+        let outcome = apply_rule(db, rule, now, dry_run)?;
+        if !dry_run && !outcome.matched.is_empty() {
+            info!("Retention: {} ({} matched)", outcome.rule, outcome.matched.len());
+        }
+        report.outcomes.push(outcome);
+    }
+
+    Ok(report)
+}
+
+fn apply_rule(
+    db: &Database,
+    rule: &RetentionRule,
+    now: DateTime<Utc>,
+    dry_run: bool,
+) -> IngestResult<RuleOutcome> {
+    let cutoff = now - Duration::days(rule.older_than_days);
+    let mut outcome = RuleOutcome {
+        rule: rule.describe(),
+        action: rule.action,
+        matched: Vec::new(),
+        bytes_saved: 0,
+    };
+
+    if rule.action == RetentionAction::PurgeQueue {
+        let status_str = rule.status.as_deref().unwrap_or("failed");
+        let status = QueueStatus::from_str(status_str).ok_or_else(|| {
+            IngestError::ProcessingError(format!("Invalid queue status in retention rule: {}", status_str))
+        })?;
+        outcome.matched = db
+            .queue_entries_before(status, cutoff)?
+            .into_iter()
+            .map(|q| (q.source_path, q.id.chars().take(8).collect()))
+            .collect();
+        if !dry_run {
+            db.delete_queue_entries_before(status, cutoff)?;
+        }
+        return Ok(outcome);
+    }
+
+    let item_type = match rule.item_type.as_deref() {
+        Some(t) => Some(ItemType::from_str(t).ok_or_else(|| {
+            IngestError::ProcessingError(format!("Invalid item type in retention rule: {}", t))
+        })?),
+        None => None,
+    };
+    // Archived items can still be deleted or compressed, but not re-archived
+    let include_archived = rule.action != RetentionAction::Archive;
+    let candidates = db.retention_candidates(item_type, cutoff, rule.unread, include_archived)?;
+
+    for item in candidates {
+        if rule.action == RetentionAction::Compress {
+            let bytes = if dry_run {
+                db.uncompressed_chunk_bytes(&item.id)?
+            } else {
+                db.compress_item_chunks(&item.id)?
+            };
+            if bytes == 0 {
+                continue;
+            }
+            outcome.bytes_saved += bytes;
+        } else if !dry_run {
+            match rule.action {
+                RetentionAction::Archive => db.archive_item(&item.id)?,
+                RetentionAction::Delete => {
+                    db.delete_item_cascade(&item.id)?;
+                    if let Some(paths) = AppPaths::new() {
+                        if let Err(e) = paths.remove_thumbnail(&item.id) {
+                            warn!("Failed to remove thumbnail of {}: {}", item.id, e);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        outcome
+            .matched
+            .push((item.title, item.id.chars().take(8).collect()));
+    }
+
+    Ok(outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Chunk, Item, QueueItem};
+
+    fn rule(action: RetentionAction, item_type: Option<&str>, days: i64, unread: bool) -> RetentionRule {
+        RetentionRule {
+            action,
+            item_type: item_type.map(str::to_string),
+            older_than_days: days,
+            unread,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn test_retention_rules() {
+        let db = Database::open_in_memory().unwrap();
+
+        let mut bookmark = Item::new(ItemType::Bookmark, "Stale link");
+        bookmark.created_at = Utc::now() - Duration::days(100);
+        let mut video = Item::new(ItemType::Video, "Old talk");
+        video.created_at = Utc::now() - Duration::days(400);
+        db.create_item(&bookmark).unwrap();
+        db.create_item(&video).unwrap();
+        db.create_chunk(&Chunk::new(video.id.clone(), 0, "transcript words ".repeat(50)))
+            .unwrap();
+
+        let failed = QueueItem::new("/tmp/broken.mp4", ItemType::Video);
+        db.enqueue(&failed).unwrap();
+        db.mark_failed(&failed.id, "boom").unwrap();
+
+        let mut config = Config::default();
+        config.retention.rules = vec![
+            rule(RetentionAction::Archive, Some("bookmark"), 90, true),
+            rule(RetentionAction::Compress, Some("video"), 365, false),
+            rule(RetentionAction::PurgeQueue, None, 0, false),
+        ];
+
+        // Dry run reports without changing anything
+        let report = run_retention(&db, &config, true).unwrap();
+        assert_eq!(report.total_matched(), 3);
+        assert!(report.to_markdown().contains("Dry run"));
+        assert!(db.get_item(&bookmark.id).unwrap().archived_at.is_none());
+        assert_eq!(db.list_queue(None).unwrap().len(), 1);
+
+        let report = run_retention(&db, &config, false).unwrap();
+        assert_eq!(report.total_matched(), 3);
+        assert!(db.get_item(&bookmark.id).unwrap().is_archived());
+        assert_eq!(db.uncompressed_chunk_bytes(&video.id).unwrap(), 0);
+        assert!(db.list_queue(None).unwrap().is_empty());
+
+        // Nothing left to do
+        assert!(run_retention(&db, &config, false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_invalid_item_type() {
+        let db = Database::open_in_memory().unwrap();
+        let mut config = Config::default();
+        config.retention.rules = vec![rule(RetentionAction::Delete, Some("podcast"), 1, false)];
+        assert!(run_retention(&db, &config, true).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! Recurring jobs (`[schedule]` config).
+//!
+//! Decides which configured jobs are due; running them is up to the caller
+//! (`olal watch` and `olal worker` check once a minute). Last runs are kept
+//! in the database, so a restart neither repeats nor skips a job.
+//!
+//! There is no feed-sync task: olal doesn't subscribe to feeds yet.
+
+use crate::error::IngestResult;
+use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Utc, Weekday};
+use olal_config::{Config, ScheduledJob};
+use olal_db::Database;
+
+/// Check a job's timing settings.
+pub fn validate(job: &ScheduledJob) -> Result<(), String> {
+    match (&job.at, job.every_minutes) {
+        (Some(at), _) => {
+            parse_time(at)?;
+        }
+        (None, Some(0)) => return Err("every_minutes must be at least 1".to_string()),
+        (None, Some(_)) => {}
+        (None, None) => return Err("set either 'at' or 'every_minutes'".to_string()),
+    }
+    for day in &job.days {
+        parse_day(day)?;
+    }
+    Ok(())
+}
+
+/// Whether `job` should run at `now`, given when it last ran.
+///
+/// Daily jobs run once the local time passes `at`, at most once a day;
+/// interval jobs run once `every_minutes` have passed. Invalid jobs never run.
+pub fn is_due<Tz: TimeZone>(job: &ScheduledJob, last_run: Option<DateTime<Utc>>, now: &DateTime<Tz>) -> bool {
+    if validate(job).is_err() {
+        return false;
+    }
+    if !job.days.is_empty() && !job.days.iter().any(|d| parse_day(d) == Ok(now.weekday())) {
+        return false;
+    }
+
+    if let Some(at) = &job.at {
+        let Ok(time) = parse_time(at) else {
+            return false;
+        };
+        let Some(scheduled) = now
+            .timezone()
+            .from_local_datetime(&now.date_naive().and_time(time))
+            .earliest()
+        else {
+            return false;
+        };
+        return *now >= scheduled && last_run.is_none_or(|last| last < scheduled);
+    }
+
+    match (job.every_minutes, last_run) {
+        (Some(_), None) => true,
+        (Some(minutes), Some(last)) => {
+            now.with_timezone(&Utc) - last >= chrono::Duration::minutes(minutes as i64)
+        }
+        (None, _) => false,
+    }
+}
+
+/// The configured jobs due now, in config order.
+pub fn due_jobs<'a>(db: &Database, config: &'a Config, now: DateTime<Utc>) -> IngestResult<Vec<&'a ScheduledJob>> {
+    if !config.schedule.enabled {
+        return Ok(Vec::new());
+    }
+
+    let local_now = now.with_timezone(&chrono::Local);
+    let mut due = Vec::new();
+    for job in &config.schedule.jobs {
+        let last_run = db.get_schedule_run(job.name())?.map(|run| run.last_run_at);
+        if is_due(job, last_run, &local_now) {
+            due.push(job);
+        }
+    }
+    Ok(due)
+}
+
+fn parse_time(at: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(at.trim(), "%H:%M")
+        .map_err(|_| format!("invalid time '{}' (expected HH:MM)", at))
+}
+
+fn parse_day(day: &str) -> Result<Weekday, String> {
+    day.trim()
+        .parse::<Weekday>()
+        .map_err(|_| format!("invalid day '{}' (expected mon..sun)", day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_config::ScheduledTask;
+
+    fn job(at: Option<&str>, every_minutes: Option<u64>, days: &[&str]) -> ScheduledJob {
+        ScheduledJob {
+            task: ScheduledTask::Digest,
+            name: None,
+            at: at.map(String::from),
+            every_minutes,
+            days: days.iter().map(|d| d.to_string()).collect(),
+            period: None,
+            email: false,
+            limit: None,
+        }
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_daily_job() {
+        let daily = job(Some("07:30"), None, &[]);
+        // 2026-03-02 is a Monday
+        let before = utc("2026-03-02T07:00:00Z");
+        let after = utc("2026-03-02T08:00:00Z");
+
+        assert!(!is_due(&daily, None, &before));
+        assert!(is_due(&daily, None, &after));
+        // Already ran today
+        assert!(!is_due(&daily, Some(utc("2026-03-02T07:31:00Z")), &after));
+        // Last ran yesterday
+        assert!(is_due(&daily, Some(utc("2026-03-01T07:31:00Z")), &after));
+
+        let weekdays = job(Some("07:30"), None, &["tue", "wed"]);
+        assert!(!is_due(&weekdays, None, &after));
+        assert!(is_due(&weekdays, None, &utc("2026-03-03T08:00:00Z")));
+    }
+
+    #[test]
+    fn test_interval_job() {
+        let hourly = job(None, Some(60), &[]);
+        let now = utc("2026-03-02T12:00:00Z");
+
+        assert!(is_due(&hourly, None, &now));
+        assert!(!is_due(&hourly, Some(utc("2026-03-02T11:30:00Z")), &now));
+        assert!(is_due(&hourly, Some(utc("2026-03-02T11:00:00Z")), &now));
+    }
+
+    #[test]
+    fn test_invalid_jobs() {
+        assert!(validate(&job(None, None, &[])).is_err());
+        assert!(validate(&job(Some("7am"), None, &[])).is_err());
+        assert!(validate(&job(None, Some(0), &[])).is_err());
+        assert!(validate(&job(None, Some(5), &["someday"])).is_err());
+        assert!(validate(&job(Some("23:59"), None, &["Friday"])).is_ok());
+
+        assert!(!is_due(&job(None, None, &[]), None, &Utc::now()));
+    }
+}
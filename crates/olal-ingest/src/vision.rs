@@ -0,0 +1,141 @@
+//! Image descriptions from a multimodal model (`processing.describe_images`).
+//!
+//! Photos and screenshots with little text give OCR nothing to index. The
+//! image is sent to `ollama.vision_model` (llava, moondream, ...), and the
+//! caption and object list it returns become the item's searchable content.
+
+use crate::error::IngestResult;
+use base64::Engine;
+use olal_config::Config;
+use olal_ollama::prompts::{self, Prompt};
+use olal_ollama::{GenerateOptions, GenerateRequest, OllamaClient, OllamaError};
+use serde_json::json;
+use std::path::Path;
+use tokio::runtime::Runtime;
+use tracing::debug;
+
+/// What the vision model saw in an image.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDescription {
+    pub caption: String,
+    pub objects: Vec<String>,
+}
+
+impl ImageDescription {
+    /// Parse the model's `Caption:` / `Objects:` reply. Without those
+    /// labels the whole reply is taken as the caption.
+    pub fn parse(response: &str) -> Option<Self> {
+        let mut caption = Vec::new();
+        let mut objects = Vec::new();
+        for line in response.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let line = line.trim_start_matches(['*', '-', ' ']).replace("**", "");
+            if let Some(rest) = strip_label(&line, "objects:") {
+                objects.extend(
+                    rest.split(',')
+                        .map(|o| o.trim().trim_end_matches('.').to_string())
+                        .filter(|o| !o.is_empty()),
+                );
+            } else {
+                caption.push(strip_label(&line, "caption:").unwrap_or(&line).to_string());
+            }
+        }
+
+        let caption = caption.join(" ");
+        (!caption.is_empty() || !objects.is_empty()).then_some(Self { caption, objects })
+    }
+
+    /// The description as indexable text.
+    pub fn to_text(&self) -> String {
+        if self.objects.is_empty() {
+            self.caption.clone()
+        } else {
+            format!("{}\n\nObjects: {}", self.caption, self.objects.join(", "))
+        }
+    }
+}
+
+fn strip_label<'a>(line: &'a str, label: &str) -> Option<&'a str> {
+    let head = line.get(..label.len())?;
+    head.eq_ignore_ascii_case(label).then(|| line[label.len()..].trim())
+}
+
+/// Describes images with the configured vision model.
+pub struct ImageDescriber {
+    client: OllamaClient,
+    model: String,
+    rt: Runtime,
+}
+
+impl ImageDescriber {
+    /// Create a describer from config.
+    ///
+    /// Fails if Ollama isn't reachable or the vision model isn't pulled.
+    pub fn from_config(config: &Config) -> IngestResult<Self> {
+        let client = OllamaClient::from_config(&config.ollama)?;
+        let rt = Runtime::new()?;
+
+        if !rt.block_on(client.is_available()) {
+            return Err(OllamaError::ServerNotRunning {
+                host: config.ollama.host.clone(),
+            }
+            .into());
+        }
+        let model = config.ollama.vision_model.clone();
+        if !rt.block_on(client.has_model(&model)).unwrap_or(false) {
+            return Err(OllamaError::ModelNotFound { model }.into());
+        }
+
+        Ok(Self { client, model, rt })
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Caption the image at `path`. Any text OCR found goes into the prompt
+    /// as a hint.
+    pub fn describe(&self, path: &Path, ocr_text: &str) -> Result<ImageDescription, String> {
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read image: {}", e))?;
+        let image = base64::engine::general_purpose::STANDARD.encode(bytes);
+
+        let prompt = prompts::render(Prompt::ImageDescription, &json!({ "ocr_text": ocr_text.trim() }))
+            .map_err(|e| e.to_string())?;
+        let request = GenerateRequest::new(&self.model, prompt)
+            .with_images(vec![image])
+            .with_options(GenerateOptions::new().with_temperature(0.2).with_num_predict(200));
+
+        let response = self
+            .rt
+            .block_on(self.client.generate(request))
+            .map_err(|e| format!("Failed to describe image: {}", e))?;
+        debug!("Image description: {}", response.response.trim());
+
+        ImageDescription::parse(&response.response)
+            .ok_or_else(|| "Vision model returned an empty description".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_description() {
+        let description = ImageDescription::parse(
+            "Caption: A terminal showing a failed cargo build.\n**Objects:** terminal, error message, dock.\n",
+        )
+        .unwrap();
+        assert_eq!(description.caption, "A terminal showing a failed cargo build.");
+        assert_eq!(description.objects, vec!["terminal", "error message", "dock"]);
+        assert_eq!(
+            description.to_text(),
+            "A terminal showing a failed cargo build.\n\nObjects: terminal, error message, dock"
+        );
+
+        let unlabeled = ImageDescription::parse("A cat asleep on a keyboard.").unwrap();
+        assert_eq!(unlabeled.caption, "A cat asleep on a keyboard.");
+        assert!(unlabeled.objects.is_empty());
+
+        assert!(ImageDescription::parse("  \n").is_none());
+    }
+}
@@ -0,0 +1,216 @@
+//! Fetching web pages and extracting their readable text.
+//!
+//! Extraction is deliberately simple: the `<title>`, then the text of the
+//! page with scripts, styles and markup removed. It's enough to make a
+//! bookmarked article searchable and summarizable.
+
+use crate::error::{IngestError, IngestResult};
+use std::time::Duration;
+
+/// Elements whose content is never readable text.
+const SKIPPED_ELEMENTS: &[&str] = &["script", "style", "noscript", "template", "svg", "head"];
+
+/// Elements that end a line of text.
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p", "div", "br", "li", "tr", "h1", "h2", "h3", "h4", "h5", "h6", "section", "article", "blockquote",
+    "pre", "dt", "dd", "header", "footer", "table",
+];
+
+/// A fetched page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Page {
+    pub title: Option<String>,
+    pub text: String,
+}
+
+/// Fetches pages over HTTP from synchronous code.
+pub struct PageFetcher {
+    client: reqwest::Client,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl PageFetcher {
+    pub fn new(timeout: Duration) -> IngestResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(timeout)
+            .user_agent(concat!("olal/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| IngestError::ProcessingError(format!("Failed to create HTTP client: {}", e)))?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+        Ok(Self { client, runtime })
+    }
+
+    /// Fetch `url` and extract its text. HTML and plain text are supported.
+    pub fn fetch(&self, url: &str) -> IngestResult<Page> {
+        self.runtime.block_on(async {
+            let response = self.send(url).await?;
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or("text/html")
+                .to_lowercase();
+            if !content_type.starts_with("text/") && !content_type.contains("html") {
+                return Err(IngestError::UnsupportedFileType(content_type));
+            }
+            let body = response
+                .text()
+                .await
+                .map_err(|e| IngestError::ProcessingError(format!("Failed to read {}: {}", url, e)))?;
+            Ok(if content_type.starts_with("text/plain") {
+                Page { title: None, text: body }
+            } else {
+                html_to_page(&body)
+            })
+        })
+    }
+
+    /// Download `url` as-is, e.g. a PDF.
+    pub fn download(&self, url: &str) -> IngestResult<Vec<u8>> {
+        self.runtime.block_on(async {
+            let response = self.send(url).await?;
+            let bytes = response
+                .bytes()
+                .await
+                .map_err(|e| IngestError::ProcessingError(format!("Failed to read {}: {}", url, e)))?;
+            Ok(bytes.to_vec())
+        })
+    }
+
+    async fn send(&self, url: &str) -> IngestResult<reqwest::Response> {
+        self.client
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| IngestError::ProcessingError(format!("Failed to fetch {}: {}", url, e)))
+    }
+}
+
+/// The title and readable text of an HTML document.
+pub fn html_to_page(html: &str) -> Page {
+    let lower = html.to_ascii_lowercase();
+    let title = lower.find("<title").and_then(|start| {
+        let open_end = start + lower[start..].find('>')? + 1;
+        let close = open_end + lower[open_end..].find("</title")?;
+        let title = collapse_whitespace(&decode_entities(&html[open_end..close]));
+        (!title.is_empty()).then_some(title)
+    });
+
+    let mut text = String::new();
+    let mut rest = html;
+    let mut rest_lower = lower.as_str();
+    while let Some(open) = rest.find('<') {
+        text.push_str(&decode_entities(&rest[..open]));
+        let Some(close) = rest[open..].find('>').map(|i| open + i) else {
+            rest = "";
+            break;
+        };
+        let inner = &rest_lower[open + 1..close];
+        let tag = tag_name(inner);
+        let mut next = close + 1;
+        let opens_element = !inner.starts_with('/') && !inner.ends_with('/');
+        if SKIPPED_ELEMENTS.contains(&tag) && opens_element {
+            // Skip to the end of the element
+            let end_tag = format!("</{}", tag);
+            next = rest_lower[next..]
+                .find(&end_tag)
+                .and_then(|i| rest_lower[next + i..].find('>').map(|j| next + i + j + 1))
+                .unwrap_or(rest.len());
+        } else if BLOCK_ELEMENTS.contains(&tag) {
+            text.push('\n');
+        }
+        rest = &rest[next..];
+        rest_lower = &rest_lower[next..];
+    }
+    text.push_str(&decode_entities(rest));
+
+    let text = text
+        .lines()
+        .map(collapse_whitespace)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    Page { title, text }
+}
+
+/// Name of the tag in `inner` (the text between `<` and `>`), lowercased.
+fn tag_name(inner: &str) -> &str {
+    let inner = inner.trim_start_matches('/');
+    let end = inner
+        .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+        .unwrap_or(inner.len());
+    &inner[..end]
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Decode the common named entities and numeric character references.
+pub(crate) fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_to_page() {
+        let html = r#"<!DOCTYPE html><html><head><title>Rust &amp; You</title>
+            <style>body { color: red; }</style></head>
+            <body><script>var x = "<p>not text</p>";</script>
+            <h1>Ownership</h1><p>Each value has an <b>owner</b>.</p>
+            <ul><li>Borrowing</li><li>Lifetimes&nbsp;&#8212; explained</li></ul></body></html>"#;
+        let page = html_to_page(html);
+        assert_eq!(page.title.as_deref(), Some("Rust & You"));
+        assert_eq!(
+            page.text,
+            "Ownership\nEach value has an owner.\nBorrowing\nLifetimes \u{2014} explained"
+        );
+    }
+
+    #[test]
+    fn test_decode_entities() {
+        assert_eq!(decode_entities("a &lt;b&gt; &#x41;&#66; &unknown; & c"), "a <b> AB &unknown; & c");
+    }
+}
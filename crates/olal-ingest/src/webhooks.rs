@@ -0,0 +1,178 @@
+//! Webhook notifications (`[webhooks]` config).
+//!
+//! [`attach`] subscribes a notifier to an [`EventEmitter`]. Events are queued
+//! and POSTed from a background thread, so emitting never waits on the
+//! network. When the emitter (and all its clones) is dropped, the notifier
+//! delivers what's still queued before returning, so short-lived commands
+//! don't lose their notifications.
+
+use crate::events::{Event, EventEmitter};
+use olal_config::{WebhookEndpoint, WebhooksConfig};
+use std::sync::mpsc::{self, Sender};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Subscribe webhook delivery to `emitter`. Does nothing if webhooks are
+/// disabled or no endpoints are configured.
+pub fn attach(emitter: &EventEmitter, config: &WebhooksConfig) {
+    if let Some(notifier) = WebhookNotifier::start(config) {
+        emitter.subscribe(move |event| notifier.send(event));
+    }
+}
+
+/// Queues events for the delivery thread.
+struct WebhookNotifier {
+    sender: Option<Sender<Event>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WebhookNotifier {
+    fn start(config: &WebhooksConfig) -> Option<Self> {
+        if !config.enabled || config.endpoints.is_empty() {
+            return None;
+        }
+
+        let client = match reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds.max(1)))
+            .build()
+        {
+            Ok(client) => client,
+            Err(e) => {
+                warn!("Webhooks disabled: {}", e);
+                return None;
+            }
+        };
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                warn!("Webhooks disabled: {}", e);
+                return None;
+            }
+        };
+
+        let endpoints = config.endpoints.clone();
+        let (sender, receiver) = mpsc::channel::<Event>();
+        let handle = std::thread::spawn(move || {
+            for event in receiver {
+                runtime.block_on(deliver(&client, &endpoints, &event));
+            }
+        });
+
+        Some(Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+
+    fn send(&self, event: &Event) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(event.clone());
+        }
+    }
+}
+
+impl Drop for WebhookNotifier {
+    fn drop(&mut self) {
+        // Closing the channel ends the delivery loop once it's drained
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// POST `event` to every endpoint that wants it. Failures are logged.
+async fn deliver(client: &reqwest::Client, endpoints: &[WebhookEndpoint], event: &Event) {
+    let payload = event.payload();
+    for endpoint in endpoints.iter().filter(|e| e.wants(event.name())) {
+        match client.post(&endpoint.url).json(&payload).send().await {
+            Ok(response) if response.status().is_success() => {
+                debug!("Sent {} to {}", event.name(), endpoint.url);
+            }
+            Ok(response) => {
+                warn!("Webhook {} returned {} for {}", endpoint.url, response.status(), event.name());
+            }
+            Err(e) => warn!("Webhook {} failed: {}", endpoint.url, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::{Item, ItemType};
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    /// Accept `count` requests and return their bodies.
+    fn receive(listener: TcpListener, count: usize) -> JoinHandle<Vec<String>> {
+        std::thread::spawn(move || {
+            let mut bodies = Vec::new();
+            for stream in listener.incoming().take(count) {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+                bodies.push(String::from_utf8(body).unwrap());
+                stream
+                    .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")
+                    .unwrap();
+            }
+            bodies
+        })
+    }
+
+    #[test]
+    fn test_delivers_matching_events() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let server = receive(listener, 1);
+
+        let config = WebhooksConfig {
+            endpoints: vec![WebhookEndpoint {
+                url,
+                events: vec!["item.processed".to_string()],
+            }],
+            ..WebhooksConfig::default()
+        };
+        let emitter = EventEmitter::new();
+        attach(&emitter, &config);
+
+        emitter.emit(Event::DigestGenerated {
+            period: "day".to_string(),
+            item_count: 0,
+            output: None,
+            digest: String::new(),
+        });
+        emitter.emit(Event::item_processed(&Item::new(ItemType::Note, "Idea"), 1));
+        // Dropping the last emitter flushes pending deliveries
+        drop(emitter);
+
+        let bodies = server.join().unwrap();
+        assert_eq!(bodies.len(), 1);
+        let payload: serde_json::Value = serde_json::from_str(&bodies[0]).unwrap();
+        assert_eq!(payload["event"], "item.processed");
+        assert_eq!(payload["data"]["title"], "Idea");
+    }
+
+    #[test]
+    fn test_no_endpoints_no_listener() {
+        let emitter = EventEmitter::new();
+        attach(&emitter, &WebhooksConfig::default());
+        assert_eq!(format!("{:?}", emitter), "EventEmitter { listeners: 0 }");
+    }
+}
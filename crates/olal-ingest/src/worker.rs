@@ -0,0 +1,288 @@
+//! Long-running queue worker.
+//!
+//! Drains the processing queue with up to `max_concurrent_jobs` jobs at a
+//! time, sleeping while the queue is empty, until asked to stop. While idle
+//! it also retries chunks that auto-embed couldn't embed and fetches the
+//! pages of imported bookmarks, one at a time. Used by `olal worker start`.
+
+use crate::chunker::ChunkConfig;
+use crate::embedder::Embedder;
+use crate::events::EventEmitter;
+use crate::error::IngestResult;
+use crate::ingestor::Ingestor;
+use crate::web::PageFetcher;
+use olal_core::QueueItem;
+use olal_db::Database;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// How often an idle job checks for a stop request.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Chunks embedded per retry pass.
+const EMBED_RETRY_BATCH: usize = 64;
+
+/// How long a bookmark's page may take to download.
+const FETCH_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Something that happened while the worker ran.
+#[derive(Debug)]
+pub enum WorkerEvent {
+    /// Entries left in `processing` by a previous run were queued again.
+    Recovered(i64),
+    /// An entry was ingested.
+    Processed {
+        entry: QueueItem,
+        title: String,
+        chunk_count: usize,
+    },
+    /// An entry failed and was marked failed.
+    Failed { entry: QueueItem, error: String },
+    /// A retry pass embedded previously unembedded chunks.
+    Embedded { embedded: usize, failed: usize },
+    /// A bookmark's page was fetched, or failed to be.
+    Fetched { title: String, error: Option<String> },
+}
+
+/// Totals for a worker run.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct WorkerSummary {
+    pub recovered: i64,
+    pub processed: usize,
+    pub failed: usize,
+}
+
+/// Queue worker settings.
+#[derive(Debug, Clone)]
+pub struct Worker {
+    /// Number of entries processed concurrently (at least 1).
+    pub jobs: usize,
+    /// How long to wait before checking an empty queue again.
+    pub idle_interval: Duration,
+    /// How often to retry missing embeddings while idle (`None` = never).
+    pub embed_retry_interval: Option<Duration>,
+    /// Minimum time between bookmark page fetches while idle (`None` =
+    /// never).
+    pub fetch_interval: Option<Duration>,
+    pub chunk_config: ChunkConfig,
+    /// Events emitted by the worker's ingestors.
+    pub events: EventEmitter,
+}
+
+impl Worker {
+    /// Run until `stop` is set, then finish in-flight entries and return.
+    ///
+    /// Entries still marked `processing` by a process that has since died
+    /// are requeued at startup; other workers' entries are left to them.
+    pub fn run<F>(&self, db: &Database, stop: &AtomicBool, on_event: F) -> IngestResult<WorkerSummary>
+    where
+        F: Fn(WorkerEvent) + Sync,
+    {
+        // Nothing in this process has claimed an entry yet, so a claim in
+        // its name is from a dead process that had the same ID
+        let recovered = db.requeue_processing(|pid| pid != std::process::id() && process_alive(pid))?;
+        if recovered > 0 {
+            info!("Requeued {} entries interrupted by a previous run", recovered);
+            on_event(WorkerEvent::Recovered(recovered));
+        }
+
+        let processed = AtomicUsize::new(0);
+        let failed = AtomicUsize::new(0);
+        let last_embed_retry: Mutex<Option<Instant>> = Mutex::new(None);
+        let last_fetch: Mutex<Option<Instant>> = Mutex::new(None);
+
+        std::thread::scope(|scope| -> IngestResult<()> {
+            let handles: Vec<_> = (0..self.jobs.max(1))
+                .map(|_| {
+                    scope.spawn(|| -> IngestResult<()> {
+                        let ingestor = Ingestor::new(db.clone(), self.chunk_config.clone())
+                            .with_events(self.events.clone());
+                        while !stop.load(Ordering::Acquire) {
+                            let Some(entry) = db.dequeue()? else {
+                                self.retry_embeddings(db, &last_embed_retry, &on_event);
+                                self.fetch_bookmark(db, &ingestor, &last_fetch, &on_event);
+                                idle(stop, self.idle_interval);
+                                continue;
+                            };
+                            match ingestor.process_entry(&entry) {
+                                Ok(result) => {
+                                    processed.fetch_add(1, Ordering::Relaxed);
+                                    on_event(WorkerEvent::Processed {
+                                        entry,
+                                        title: result.item.title,
+                                        chunk_count: result.chunk_count,
+                                    });
+                                }
+                                Err(e) => {
+                                    failed.fetch_add(1, Ordering::Relaxed);
+                                    on_event(WorkerEvent::Failed {
+                                        entry,
+                                        error: e.to_string(),
+                                    });
+                                }
+                            }
+                        }
+                        Ok(())
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                handle.join().expect("queue worker job panicked")?;
+            }
+            Ok(())
+        })?;
+
+        Ok(WorkerSummary {
+            recovered,
+            processed: processed.into_inner(),
+            failed: failed.into_inner(),
+        })
+    }
+
+    /// Embed chunks still missing embeddings, at most once per retry interval
+    /// across all jobs.
+    fn retry_embeddings<F>(&self, db: &Database, last: &Mutex<Option<Instant>>, on_event: &F)
+    where
+        F: Fn(WorkerEvent) + Sync,
+    {
+        let Some(interval) = self.embed_retry_interval else {
+            return;
+        };
+        // Another job is already retrying
+        let Ok(mut last) = last.try_lock() else {
+            return;
+        };
+        if last.is_some_and(|at| at.elapsed() < interval) {
+            return;
+        }
+        *last = Some(Instant::now());
+
+        let config = olal_config::Config::load().unwrap_or_default();
+        let result = Embedder::from_config(&config)
+            .and_then(|embedder| embedder.embed_pending(db, EMBED_RETRY_BATCH));
+        match result {
+            Ok((0, 0)) => {}
+            Ok((embedded, failed)) => on_event(WorkerEvent::Embedded { embedded, failed }),
+            Err(e) => warn!("Embedding retry skipped: {}", e),
+        }
+    }
+
+    /// Fetch the page of the oldest bookmark waiting for one, at most once
+    /// per fetch interval across all jobs.
+    fn fetch_bookmark<F>(&self, db: &Database, ingestor: &Ingestor, last: &Mutex<Option<Instant>>, on_event: &F)
+    where
+        F: Fn(WorkerEvent) + Sync,
+    {
+        let Some(interval) = self.fetch_interval else {
+            return;
+        };
+        let Ok(mut last) = last.try_lock() else {
+            return;
+        };
+        if last.is_some_and(|at| at.elapsed() < interval) {
+            return;
+        }
+
+        let item = match db.bookmarks_to_fetch(1) {
+            Ok(items) => match items.into_iter().next() {
+                Some(item) => item,
+                None => return,
+            },
+            Err(e) => {
+                warn!("Bookmark fetch skipped: {}", e);
+                return;
+            }
+        };
+        *last = Some(Instant::now());
+
+        let result = PageFetcher::new(FETCH_TIMEOUT).and_then(|fetcher| ingestor.fetch_bookmark(&item, &fetcher));
+        on_event(match result {
+            Ok(result) => WorkerEvent::Fetched {
+                title: result.item.title,
+                error: None,
+            },
+            Err(e) => WorkerEvent::Fetched {
+                title: item.title,
+                error: Some(e.to_string()),
+            },
+        });
+    }
+}
+
+/// Sleep for `interval`, waking early if `stop` is set.
+fn idle(stop: &AtomicBool, interval: Duration) {
+    let started = Instant::now();
+    while started.elapsed() < interval && !stop.load(Ordering::Acquire) {
+        std::thread::sleep(STOP_CHECK_INTERVAL.min(interval));
+    }
+}
+
+/// Whether a process with ID `pid` is running (`kill -0`).
+pub fn process_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use olal_core::ItemType;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_worker_drains_queue() {
+        let dir = TempDir::new().unwrap();
+        let db = Database::open(dir.path().join("olal.db")).unwrap();
+
+        for name in ["a.md", "b.md", "c.md"] {
+            let path = dir.path().join(name);
+            let mut file = std::fs::File::create(&path).unwrap();
+            writeln!(file, "# {}\n\nNotes about {}", name, name).unwrap();
+            db.enqueue(&QueueItem::new(path.to_string_lossy(), ItemType::Document))
+                .unwrap();
+        }
+        db.enqueue(&QueueItem::new("/missing/file.md", ItemType::Document))
+            .unwrap();
+        // Left over from a crashed run
+        db.dequeue().unwrap().unwrap();
+
+        let worker = Worker {
+            jobs: 2,
+            idle_interval: Duration::from_millis(10),
+            embed_retry_interval: None,
+            fetch_interval: None,
+            chunk_config: ChunkConfig::default(),
+            events: EventEmitter::new(),
+        };
+        let stop = AtomicBool::new(false);
+        let done = AtomicUsize::new(0);
+        let summary = worker
+            .run(&db, &stop, |event| {
+                if !matches!(event, WorkerEvent::Recovered(_)) && done.fetch_add(1, Ordering::SeqCst) == 3 {
+                    stop.store(true, Ordering::Release);
+                }
+            })
+            .unwrap();
+
+        assert_eq!(
+            summary,
+            WorkerSummary {
+                recovered: 1,
+                processed: 3,
+                failed: 1,
+            }
+        );
+        let (pending, processing, done, failed) = db.queue_counts().unwrap();
+        assert_eq!((pending, processing, done, failed), (0, 0, 3, 1));
+    }
+}
@@ -0,0 +1,275 @@
+//! Fitting prompt content into a model's context window.
+//!
+//! Token counts are estimated without the model's tokenizer: ASCII words
+//! count one token per four characters (rounded up), every other
+//! non-whitespace character counts one. That overestimates for English
+//! text, so a prompt that fits by this count fits the real tokenizer too.
+
+use crate::rag::ContextItem;
+use crate::types::GenerateOptions;
+
+/// Context length assumed when the model's can't be queried (Ollama's default).
+pub const DEFAULT_CONTEXT_LENGTH: usize = 2048;
+
+/// Tokens held back for the model's answer: a quarter of the window,
+/// within these bounds.
+const MIN_ANSWER_TOKENS: usize = 256;
+const MAX_ANSWER_TOKENS: usize = 2048;
+
+/// Tokens assumed for a prompt's instructions when suggesting how many
+/// chunks to retrieve.
+const PROMPT_TOKENS: usize = 512;
+
+/// Most chunks [`ContextBudget::suggested_chunks`] suggests.
+const MAX_SUGGESTED_CHUNKS: usize = 20;
+
+/// A truncated chunk shorter than this isn't worth including.
+const MIN_CHUNK_TOKENS: usize = 32;
+
+/// Tokens for the "[n] From: title" framing around each context chunk.
+const CHUNK_FRAMING_TOKENS: usize = 6;
+
+/// ASCII characters per token in a word.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Estimate how many tokens `text` is.
+pub fn count_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut word: usize = 0;
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            word += 1;
+            continue;
+        }
+        tokens += word.div_ceil(CHARS_PER_TOKEN);
+        word = 0;
+        if !c.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + word.div_ceil(CHARS_PER_TOKEN)
+}
+
+/// Cut `text` to at most `max_tokens`, at the end of a sentence if any
+/// fits, otherwise after a word with "..." appended.
+pub fn truncate_to_tokens(text: &str, max_tokens: usize) -> String {
+    if count_tokens(text) <= max_tokens {
+        return text.to_string();
+    }
+
+    let end = fitting_prefix(sentences(text), max_tokens);
+    if end > 0 {
+        return text[..end].trim_end().to_string();
+    }
+
+    let budget = max_tokens.saturating_sub(1);
+    let end = fitting_prefix(text.split_inclusive(char::is_whitespace), budget);
+    format!("{}...", text[..end].trim_end())
+}
+
+/// Byte length of the longest run of `pieces` that fits in `max_tokens`.
+/// Pieces split at whitespace or after punctuation, so their counts add up.
+fn fitting_prefix<'a>(pieces: impl Iterator<Item = &'a str>, max_tokens: usize) -> usize {
+    let mut used = 0;
+    let mut end = 0;
+    for piece in pieces {
+        used += count_tokens(piece);
+        if used > max_tokens {
+            break;
+        }
+        end += piece.len();
+    }
+    end
+}
+
+/// Split `text` after each sentence: at a newline, or after '.', '!' or
+/// '?' followed by whitespace.
+fn sentences(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut chars = rest.char_indices().peekable();
+        let mut end = rest.len();
+        while let Some((i, c)) = chars.next() {
+            let next_is_space = chars.peek().is_none_or(|(_, n)| n.is_whitespace());
+            if c == '\n' || (matches!(c, '.' | '!' | '?') && next_is_space) {
+                end = i + c.len_utf8();
+                break;
+            }
+        }
+        let (sentence, remaining) = rest.split_at(end);
+        rest = remaining;
+        Some(sentence)
+    })
+}
+
+/// How much content fits in a prompt for a model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContextBudget {
+    window: usize,
+    answer: usize,
+}
+
+impl ContextBudget {
+    /// A budget for a context window of `window` tokens.
+    pub fn new(window: usize) -> Self {
+        let answer = (window / 4).clamp(MIN_ANSWER_TOKENS, MAX_ANSWER_TOKENS);
+        Self { window, answer }
+    }
+
+    /// The context window, in tokens.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Tokens held back for the answer.
+    pub fn answer_tokens(&self) -> usize {
+        self.answer
+    }
+
+    /// Generation options asking Ollama for the whole window, its default
+    /// context being smaller than most models allow, and capping the answer
+    /// at the room held back for it.
+    pub fn options(&self) -> GenerateOptions {
+        GenerateOptions::new()
+            .with_num_ctx(self.window as i32)
+            .with_num_predict(self.answer as i32)
+    }
+
+    /// How many chunks of `chunk_tokens` are worth retrieving to fill the
+    /// window.
+    pub fn suggested_chunks(&self, chunk_tokens: usize) -> usize {
+        let room = self.window.saturating_sub(self.answer + PROMPT_TOKENS);
+        (room / chunk_tokens.max(1)).clamp(1, MAX_SUGGESTED_CHUNKS)
+    }
+
+    /// Tokens left for content in `prompt` (rendered without the content),
+    /// after room for the answer.
+    pub fn available(&self, prompt: &str) -> usize {
+        self.window
+            .saturating_sub(self.answer)
+            .saturating_sub(count_tokens(prompt))
+    }
+
+    /// Cut `text` to what fits in `prompt`, or `None` if it all fits.
+    pub fn truncate(&self, prompt: &str, text: &str) -> Option<String> {
+        let available = self.available(prompt);
+        (count_tokens(text) > available).then(|| truncate_to_tokens(text, available))
+    }
+
+    /// Take as many of `items`, most relevant first, as fit in `prompt`.
+    /// The first that doesn't fit is truncated to the room left, if that's
+    /// enough to be useful; the most relevant item is always included.
+    pub fn pack(&self, prompt: &str, items: &[ContextItem]) -> Vec<ContextItem> {
+        let mut remaining = self.available(prompt);
+        let mut packed = Vec::new();
+        for item in items {
+            let framing = CHUNK_FRAMING_TOKENS + count_tokens(&item.item_title);
+            let cost = framing + count_tokens(&item.content);
+            if cost <= remaining {
+                remaining -= cost;
+                packed.push(item.clone());
+                continue;
+            }
+
+            let room = remaining.saturating_sub(framing);
+            if room >= MIN_CHUNK_TOKENS || packed.is_empty() {
+                packed.push(ContextItem {
+                    content: truncate_to_tokens(&item.content, room.max(MIN_CHUNK_TOKENS)),
+                    ..item.clone()
+                });
+            }
+            break;
+        }
+        packed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(title: &str, content: &str) -> ContextItem {
+        ContextItem {
+            content: content.to_string(),
+            similarity: 0.9,
+            item_id: "id".to_string(),
+            item_title: title.to_string(),
+            pages: None,
+        }
+    }
+
+    #[test]
+    fn test_count_tokens() {
+        assert_eq!(count_tokens(""), 0);
+        assert_eq!(count_tokens("the cat sat"), 3);
+        // 9 characters is three tokens, punctuation one each
+        assert_eq!(count_tokens("knowledge, base."), 3 + 1 + 1 + 1);
+        assert_eq!(count_tokens("日本語"), 3);
+    }
+
+    #[test]
+    fn test_truncate_to_tokens() {
+        let text = "First sentence here. Second sentence here. Third one.";
+        assert_eq!(truncate_to_tokens(text, 100), text);
+        assert_eq!(truncate_to_tokens(text, 12), "First sentence here. Second sentence here.");
+        assert_eq!(truncate_to_tokens(text, 8), "First sentence here.");
+        // No sentence fits: cut after a word
+        assert_eq!(truncate_to_tokens(text, 5), "First sentence...");
+    }
+
+    #[test]
+    fn test_truncate_multibyte() {
+        let text = "Ça va très bien. Über alles schön. ".repeat(20);
+        let cut = truncate_to_tokens(&text, 30);
+        assert!(count_tokens(&cut) <= 30);
+        assert!(cut.ends_with('.'));
+        assert_eq!(truncate_to_tokens("日本語のテキスト", 4), "...");
+    }
+
+    #[test]
+    fn test_pack() {
+        let budget = ContextBudget::new(MIN_ANSWER_TOKENS + 100);
+        let long = "A long sentence about airships and zeppelins. ".repeat(20);
+        let items = vec![
+            item("First", "Short and relevant."),
+            item("Second", &long),
+            item("Third", "Never reached."),
+        ];
+
+        let packed = budget.pack("", &items);
+        assert_eq!(packed.len(), 2);
+        assert_eq!(packed[0].content, "Short and relevant.");
+        assert!(packed[1].content.len() < long.len());
+        assert!(packed[1].content.ends_with("zeppelins."));
+
+        // The most relevant chunk is kept even when nothing fits
+        let packed = ContextBudget::new(MIN_ANSWER_TOKENS).pack("", &items[1..]);
+        assert_eq!(packed.len(), 1);
+        assert!(count_tokens(&packed[0].content) <= MIN_CHUNK_TOKENS);
+    }
+
+    #[test]
+    fn test_budget_sizes() {
+        let budget = ContextBudget::new(4096);
+        assert_eq!(budget.answer_tokens(), 1024);
+        assert_eq!(budget.suggested_chunks(512), 5);
+        assert_eq!(budget.options().num_ctx, Some(4096));
+        assert_eq!(budget.options().num_predict, Some(1024));
+
+        assert_eq!(ContextBudget::new(512).answer_tokens(), MIN_ANSWER_TOKENS);
+        assert_eq!(ContextBudget::new(512).suggested_chunks(512), 1);
+        assert_eq!(ContextBudget::new(131_072).answer_tokens(), MAX_ANSWER_TOKENS);
+        assert_eq!(ContextBudget::new(131_072).suggested_chunks(512), MAX_SUGGESTED_CHUNKS);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let budget = ContextBudget::new(MIN_ANSWER_TOKENS + 10);
+        assert_eq!(budget.truncate("Prompt:", "Fits."), None);
+        let cut = budget.truncate("Prompt:", &"Too long. ".repeat(10)).unwrap();
+        assert!(count_tokens(&cut) <= 8);
+    }
+}
@@ -1,10 +1,15 @@
 //! Ollama HTTP client.
 
+use crate::budget::{ContextBudget, DEFAULT_CONTEXT_LENGTH};
 use crate::error::{OllamaError, OllamaResult};
+use crate::retry::{self, Breaker, Permit};
 use crate::types::*;
-use olal_config::OllamaConfig;
+use olal_config::{secrets, OllamaConfig, RetryConfig};
 use futures_util::StreamExt;
-use reqwest::Client;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::{Certificate, Client, ClientBuilder, Proxy, RequestBuilder, Response};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
@@ -15,15 +20,20 @@ pub struct OllamaClient {
     client: Client,
     host: String,
     timeout: Duration,
+    retry: RetryConfig,
+    breaker: Arc<Breaker>,
+    models: Arc<Mutex<HashMap<String, ModelDetails>>>,
 }
 
 impl OllamaClient {
     /// Create a new client from configuration.
+    ///
+    /// Applies the authentication headers, proxy and TLS settings for a
+    /// remote server.
     pub fn from_config(config: &OllamaConfig) -> OllamaResult<Self> {
         let timeout = Duration::from_secs(config.timeout_seconds);
 
-        let client = Client::builder()
-            .timeout(timeout)
+        let client = remote_options(Client::builder().timeout(timeout), config)?
             .build()
             .map_err(OllamaError::Http)?;
 
@@ -31,6 +41,9 @@ impl OllamaClient {
             client,
             host: config.host.trim_end_matches('/').to_string(),
             timeout,
+            retry: config.retry.clone(),
+            breaker: Arc::default(),
+            models: Arc::default(),
         })
     }
 
@@ -48,24 +61,62 @@ impl OllamaClient {
             client,
             host: host.trim_end_matches('/').to_string(),
             timeout,
+            retry: RetryConfig::default(),
+            breaker: Arc::default(),
+            models: Arc::default(),
         })
     }
 
-    /// Check if Ollama server is available.
-    pub async fn is_available(&self) -> bool {
-        let url = format!("{}/api/tags", self.host);
-        match self.client.get(&url).send().await {
-            Ok(resp) => resp.status().is_success(),
-            Err(_) => false,
+    /// Send a request, retrying transient failures with backoff while the
+    /// circuit breaker allows. Non-success responses become errors; a 404 or
+    /// "not found" for a request naming `model` is [`OllamaError::ModelNotFound`].
+    async fn send(&self, request: RequestBuilder, model: Option<&str>) -> OllamaResult<Response> {
+        let mut attempt = 0;
+        loop {
+            match self.breaker.permit(&self.retry) {
+                Permit::Go => {}
+                Permit::Wait(wait) => {
+                    tokio::time::sleep(wait).await;
+                    continue;
+                }
+                Permit::GiveUp => {
+                    return Err(OllamaError::CircuitOpen {
+                        host: self.host.clone(),
+                        pauses: self.breaker.pauses() - 1,
+                    })
+                }
+            }
+
+            let this_try = request.try_clone().expect("requests have buffered bodies");
+            match self.send_once(this_try, model).await {
+                Ok(response) => {
+                    self.breaker.success();
+                    return Ok(response);
+                }
+                Err(e) if retry::is_transient(&e) => {
+                    if let Some(pause) = self.breaker.failure(&self.retry) {
+                        warn!(
+                            "Ollama at {} keeps failing ({}); pausing requests for {}s",
+                            self.host,
+                            e,
+                            pause.as_secs()
+                        );
+                    }
+                    if attempt >= self.retry.max_retries {
+                        return Err(e);
+                    }
+                    let delay = retry::backoff(&self.retry, attempt);
+                    debug!("Retrying in {}ms after: {}", delay.as_millis(), e);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
         }
     }
 
-    /// List all available models.
-    pub async fn list_models(&self) -> OllamaResult<Vec<ModelInfo>> {
-        let url = format!("{}/api/tags", self.host);
-        debug!("Listing models from {}", url);
-
-        let response = self.client.get(&url).send().await.map_err(|e| {
+    async fn send_once(&self, request: RequestBuilder, model: Option<&str>) -> OllamaResult<Response> {
+        let response = request.send().await.map_err(|e| {
             if e.is_connect() {
                 OllamaError::ServerNotRunning {
                     host: self.host.clone(),
@@ -79,15 +130,39 @@ impl OllamaClient {
             }
         })?;
 
-        if !response.status().is_success() {
-            let status = response.status().as_u16();
-            let text = response.text().await.unwrap_or_default();
-            return Err(OllamaError::ApiError {
-                status,
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let text = response.text().await.unwrap_or_default();
+        match model {
+            Some(model) if text.contains("not found") || status.as_u16() == 404 => {
+                Err(OllamaError::ModelNotFound {
+                    model: model.to_string(),
+                })
+            }
+            _ => Err(OllamaError::ApiError {
+                status: status.as_u16(),
                 message: text,
-            });
+            }),
         }
+    }
 
+    /// Check if Ollama server is available.
+    pub async fn is_available(&self) -> bool {
+        let url = format!("{}/api/tags", self.host);
+        match self.client.get(&url).send().await {
+            Ok(resp) => resp.status().is_success(),
+            Err(_) => false,
+        }
+    }
+
+    /// List all available models.
+    pub async fn list_models(&self) -> OllamaResult<Vec<ModelInfo>> {
+        let url = format!("{}/api/tags", self.host);
+        debug!("Listing models from {}", url);
+
+        let response = self.send(self.client.get(&url), None).await?;
         let list: ListModelsResponse = response.json().await?;
         Ok(list.models)
     }
@@ -101,6 +176,43 @@ impl OllamaClient {
         }))
     }
 
+    /// What `model` is and can do, from `/api/show`. Cached for the life
+    /// of the client.
+    pub async fn show_model(&self, model: &str) -> OllamaResult<ModelDetails> {
+        if let Some(details) = self.models.lock().unwrap().get(model) {
+            return Ok(details.clone());
+        }
+
+        let url = format!("{}/api/show", self.host);
+        debug!("Showing model {}", model);
+        let request = ShowRequest {
+            model: model.to_string(),
+        };
+        let response = self.send(self.client.post(&url).json(&request), Some(model)).await?;
+        let show: ShowResponse = response.json().await?;
+
+        let details = ModelDetails::from_show(model, &show);
+        self.models
+            .lock()
+            .unwrap()
+            .insert(model.to_string(), details.clone());
+        Ok(details)
+    }
+
+    /// How much fits in a prompt to `model`: its context length, capped at
+    /// `max_tokens` (bigger windows need more memory). Falls back to
+    /// Ollama's default context if the model can't be queried.
+    pub async fn context_budget(&self, model: &str, max_tokens: usize) -> ContextBudget {
+        let length = match self.show_model(model).await {
+            Ok(details) => details.context_length.unwrap_or(DEFAULT_CONTEXT_LENGTH),
+            Err(e) => {
+                warn!("Could not look up the context length of {}: {}", model, e);
+                DEFAULT_CONTEXT_LENGTH
+            }
+        };
+        ContextBudget::new(length.min(max_tokens))
+    }
+
     /// Generate embeddings for text.
     pub async fn embed(&self, model: &str, text: &str) -> OllamaResult<Vec<f32>> {
         let url = format!("{}/api/embeddings", self.host);
@@ -111,43 +223,7 @@ impl OllamaClient {
             prompt: text.to_string(),
         };
 
-        let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_connect() {
-                    OllamaError::ServerNotRunning {
-                        host: self.host.clone(),
-                    }
-                } else if e.is_timeout() {
-                    OllamaError::Timeout {
-                        seconds: self.timeout.as_secs(),
-                    }
-                } else {
-                    OllamaError::Http(e)
-                }
-            })?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-
-            // Check for model not found
-            if text.contains("not found") || status.as_u16() == 404 {
-                return Err(OllamaError::ModelNotFound {
-                    model: model.to_string(),
-                });
-            }
-
-            return Err(OllamaError::ApiError {
-                status: status.as_u16(),
-                message: text,
-            });
-        }
-
+        let response = self.send(self.client.post(&url).json(&request), Some(model)).await?;
         let embedding_response: EmbeddingResponse = response.json().await?;
         info!("Generated embedding with {} dimensions", embedding_response.embedding.len());
 
@@ -180,40 +256,8 @@ impl OllamaClient {
         request.stream = false;
 
         let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_connect() {
-                    OllamaError::ServerNotRunning {
-                        host: self.host.clone(),
-                    }
-                } else if e.is_timeout() {
-                    OllamaError::Timeout {
-                        seconds: self.timeout.as_secs(),
-                    }
-                } else {
-                    OllamaError::Http(e)
-                }
-            })?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-
-            if text.contains("not found") || status.as_u16() == 404 {
-                return Err(OllamaError::ModelNotFound {
-                    model: request.model,
-                });
-            }
-
-            return Err(OllamaError::ApiError {
-                status: status.as_u16(),
-                message: text,
-            });
-        }
+            .send(self.client.post(&url).json(&request), Some(&request.model))
+            .await?;
 
         let generate_response: GenerateResponse = response.json().await?;
         Ok(generate_response)
@@ -233,63 +277,26 @@ impl OllamaClient {
         request.stream = true;
 
         let response = self
-            .client
-            .post(&url)
-            .json(&request)
-            .send()
-            .await
-            .map_err(|e| {
-                if e.is_connect() {
-                    OllamaError::ServerNotRunning {
-                        host: self.host.clone(),
-                    }
-                } else if e.is_timeout() {
-                    OllamaError::Timeout {
-                        seconds: self.timeout.as_secs(),
-                    }
-                } else {
-                    OllamaError::Http(e)
-                }
-            })?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let text = response.text().await.unwrap_or_default();
-
-            if text.contains("not found") || status.as_u16() == 404 {
-                return Err(OllamaError::ModelNotFound {
-                    model: request.model,
-                });
-            }
-
-            return Err(OllamaError::ApiError {
-                status: status.as_u16(),
-                message: text,
-            });
-        }
+            .send(self.client.post(&url).json(&request), Some(&request.model))
+            .await?;
 
         let (tx, rx) = mpsc::channel(100);
 
         // Spawn task to read stream
         tokio::spawn(async move {
             let mut stream = response.bytes_stream();
+            let mut lines = LineBuffer::default();
 
             while let Some(chunk_result) = stream.next().await {
                 match chunk_result {
                     Ok(bytes) => {
-                        // Each chunk is a JSON line
-                        let text = String::from_utf8_lossy(&bytes);
-                        for line in text.lines() {
-                            if line.is_empty() {
-                                continue;
-                            }
-
-                            match serde_json::from_str::<StreamChunk>(line) {
+                        for line in lines.push(&bytes) {
+                            match serde_json::from_str::<StreamChunk>(&line) {
                                 Ok(chunk) => {
-                                    if !chunk.response.is_empty() {
-                                        if tx.send(chunk.response).await.is_err() {
-                                            return; // Receiver dropped
-                                        }
+                                    if !chunk.response.is_empty()
+                                        && tx.send(chunk.response).await.is_err()
+                                    {
+                                        return; // Receiver dropped
                                     }
                                     if chunk.done {
                                         return;
@@ -313,6 +320,71 @@ impl OllamaClient {
     }
 }
 
+/// Reassembles the JSON lines of a streamed response, which can be split
+/// across network chunks.
+#[derive(Debug, Default)]
+struct LineBuffer {
+    pending: Vec<u8>,
+}
+
+impl LineBuffer {
+    /// Add a chunk, returning the non-empty lines it completed.
+    fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        self.pending.extend_from_slice(bytes);
+        let mut lines = Vec::new();
+        while let Some(end) = self.pending.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line).trim().to_string();
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+        lines
+    }
+}
+
+/// Headers, proxy and TLS settings from the config.
+fn remote_options(mut builder: ClientBuilder, config: &OllamaConfig) -> OllamaResult<ClientBuilder> {
+    let invalid = |message: String| OllamaError::InvalidConfig(message);
+    let secret = |value: &str| secrets::resolve(value).map_err(|e| invalid(e.to_string()));
+
+    let mut headers = HeaderMap::new();
+    let api_key = secret(&config.api_key)?;
+    if !api_key.is_empty() {
+        let mut value = HeaderValue::from_str(&format!("Bearer {}", api_key))
+            .map_err(|_| invalid("ollama.api_key contains invalid characters".to_string()))?;
+        value.set_sensitive(true);
+        headers.insert(AUTHORIZATION, value);
+    }
+    for (name, value) in &config.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| invalid(format!("Invalid header name in ollama.headers: {}", name)))?;
+        let mut value = HeaderValue::from_str(&secret(value)?)
+            .map_err(|_| invalid(format!("Invalid value for header {} in ollama.headers", name)))?;
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+    builder = builder.default_headers(headers);
+
+    if !config.proxy.is_empty() {
+        let proxy = Proxy::all(&config.proxy)
+            .map_err(|e| invalid(format!("Invalid ollama.proxy '{}': {}", config.proxy, e)))?;
+        builder = builder.proxy(proxy);
+    }
+    if !config.ca_cert.is_empty() {
+        let pem = std::fs::read(&config.ca_cert)
+            .map_err(|e| invalid(format!("Failed to read ollama.ca_cert {}: {}", config.ca_cert, e)))?;
+        let cert = Certificate::from_pem(&pem)
+            .map_err(|e| invalid(format!("Invalid certificate in {}: {}", config.ca_cert, e)))?;
+        builder = builder.add_root_certificate(cert);
+    }
+    if config.insecure_tls {
+        warn!("TLS certificate verification is disabled for Ollama (ollama.insecure_tls)");
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -324,6 +396,32 @@ mod tests {
         assert!(client.is_ok());
     }
 
+    #[test]
+    fn test_remote_options() {
+        let mut config = OllamaConfig {
+            api_key: "secret".to_string(),
+            proxy: "http://proxy.local:3128".to_string(),
+            ..Default::default()
+        };
+        config.headers.insert("X-Team".to_string(), "ml".to_string());
+        assert!(OllamaClient::from_config(&config).is_ok());
+
+        config.headers.insert("Bad Header".to_string(), "x".to_string());
+        assert!(matches!(
+            OllamaClient::from_config(&config),
+            Err(OllamaError::InvalidConfig(_))
+        ));
+
+        let config = OllamaConfig {
+            ca_cert: "/no/such/ca.pem".to_string(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            OllamaClient::from_config(&config),
+            Err(OllamaError::InvalidConfig(_))
+        ));
+    }
+
     #[test]
     fn test_generate_request_builder() {
         let request = GenerateRequest::new("gpt-oss:20b", "Hello, world!")
@@ -335,4 +433,24 @@ mod tests {
         assert!(request.system.is_some());
         assert!(request.options.is_some());
     }
+
+    #[test]
+    fn test_line_buffer_joins_split_lines() {
+        let stream = concat!(
+            "{\"model\":\"m\",\"response\":\"Caf\u{e9}\",\"done\":false}\n",
+            "{\"model\":\"m\",\"response\":\"\",\"done\":true}\n",
+        );
+        // Split inside the first line, in the middle of the two-byte "é"
+        let split = stream.find('\u{e9}').unwrap() + 1;
+        let (first, second) = stream.as_bytes().split_at(split);
+
+        let mut lines = LineBuffer::default();
+        assert!(lines.push(first).is_empty());
+        let lines = lines.push(second);
+        assert_eq!(lines.len(), 2);
+
+        let chunk: StreamChunk = serde_json::from_str(&lines[0]).unwrap();
+        assert_eq!(chunk.response, "Café");
+        assert!(serde_json::from_str::<StreamChunk>(&lines[1]).unwrap().done);
+    }
 }
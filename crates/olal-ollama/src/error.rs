@@ -1,5 +1,6 @@
 //! Error types for Ollama operations.
 
+use olal_core::{Diagnostic, ErrorCode};
 use thiserror::Error;
 
 /// Errors that can occur when interacting with Ollama.
@@ -9,16 +10,20 @@ pub enum OllamaError {
     #[error("Connection error: {0}")]
     Connection(String),
 
+    /// Requests kept failing through every circuit breaker pause.
+    #[error("Ollama at {host} kept failing; gave up after pausing {pauses} times")]
+    CircuitOpen { host: String, pauses: u32 },
+
     /// Request timeout.
     #[error("Request timed out after {seconds} seconds")]
     Timeout { seconds: u64 },
 
     /// The requested model is not available.
-    #[error("Model not found: {model}. Run 'ollama pull {model}' to download it.")]
+    #[error("Model not found: {model}")]
     ModelNotFound { model: String },
 
     /// Ollama server is not running.
-    #[error("Ollama server is not running at {host}. Start it with 'ollama serve'.")]
+    #[error("Ollama is not running at {host}")]
     ServerNotRunning { host: String },
 
     /// API returned an error response.
@@ -41,6 +46,10 @@ pub enum OllamaError {
     #[error("No relevant context found for the query")]
     NoContext,
 
+    /// A prompt template couldn't be loaded or rendered.
+    #[error("Prompt template '{name}': {message}")]
+    Template { name: String, message: String },
+
     /// HTTP request error.
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
@@ -52,3 +61,42 @@ pub enum OllamaError {
 
 /// Result type for Ollama operations.
 pub type OllamaResult<T> = Result<T, OllamaError>;
+
+impl Diagnostic for OllamaError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            OllamaError::Connection(_)
+            | OllamaError::ServerNotRunning { .. }
+            | OllamaError::CircuitOpen { .. } => ErrorCode::OllamaUnavailable,
+            OllamaError::ModelNotFound { .. } => ErrorCode::ModelMissing,
+            OllamaError::InvalidConfig(_) | OllamaError::Template { .. } => ErrorCode::Config,
+            OllamaError::NoContext => ErrorCode::NotFound,
+            OllamaError::Http(e) if e.is_connect() => ErrorCode::OllamaUnavailable,
+            _ => ErrorCode::Ai,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            OllamaError::Connection(_) | OllamaError::ServerNotRunning { .. } => {
+                Some("Start it with 'ollama serve', or set ollama.host.".to_string())
+            }
+            OllamaError::ModelNotFound { model } => Some(format!("Run 'ollama pull {}' to download it.", model)),
+            OllamaError::CircuitOpen { .. } => Some(
+                "Check that Ollama is running and healthy, then run the command again; it picks up where it stopped.".to_string(),
+            ),
+            OllamaError::Timeout { .. } => {
+                Some("Raise ollama.timeout_seconds or try a smaller model.".to_string())
+            }
+            OllamaError::DimensionMismatch { .. } => Some(
+                "The embedding model changed; re-embed with 'olal embed --all'.".to_string(),
+            ),
+            OllamaError::Template { name, .. } => Some(format!(
+                "Fix the template with 'olal prompts edit {0}', or go back to the built-in one with 'olal prompts reset {0}'.",
+                name
+            )),
+            OllamaError::NoContext => Some("Ingest some content and run 'olal embed --all'.".to_string()),
+            _ => None,
+        }
+    }
+}
@@ -3,12 +3,17 @@
 //! This crate provides async clients for interacting with Ollama's API,
 //! including embedding generation, text generation, and RAG-based queries.
 
+pub mod budget;
 mod client;
 mod error;
+pub mod prompts;
 pub mod rag;
+mod retry;
 mod types;
 
+pub use budget::ContextBudget;
 pub use client::OllamaClient;
 pub use error::{OllamaError, OllamaResult};
+pub use prompts::{Prompt, PromptTemplates};
 pub use rag::{RagConfig, RagResponse, SourceReference};
 pub use types::*;
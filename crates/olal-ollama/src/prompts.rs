@@ -0,0 +1,352 @@
+//! Prompt templates.
+//!
+//! Every prompt olal sends is a [Handlebars](https://handlebarsjs.com)
+//! template. The built-in versions live in `src/prompts/`; a file named
+//! `<prompt>.hbs` in the prompts directory (`AppPaths::prompts_dir`)
+//! replaces the built-in one, so tone, language and format can be tuned
+//! without recompiling. Values are inserted as-is (no HTML escaping).
+
+use crate::error::{OllamaError, OllamaResult};
+use handlebars::Handlebars;
+use olal_config::AppPaths;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing::debug;
+
+/// A prompt olal sends to the model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Prompt {
+    Summary,
+    Tags,
+    Entities,
+    ImageDescription,
+    Rag,
+    RagSystem,
+    Rerank,
+    QueryExpansion,
+    CaptureAsk,
+    Digest,
+    YoutubeTitle,
+    YoutubeDescription,
+    YoutubeTags,
+    YoutubeChapters,
+    YoutubeThumbnails,
+    YoutubeHooks,
+    Clips,
+    Flashcards,
+    Translation,
+}
+
+impl Prompt {
+    /// Every prompt, in display order.
+    pub fn all() -> &'static [Prompt] {
+        &[
+            Prompt::Summary,
+            Prompt::Tags,
+            Prompt::Entities,
+            Prompt::ImageDescription,
+            Prompt::Rag,
+            Prompt::RagSystem,
+            Prompt::Rerank,
+            Prompt::QueryExpansion,
+            Prompt::CaptureAsk,
+            Prompt::Digest,
+            Prompt::YoutubeTitle,
+            Prompt::YoutubeDescription,
+            Prompt::YoutubeTags,
+            Prompt::YoutubeChapters,
+            Prompt::YoutubeThumbnails,
+            Prompt::YoutubeHooks,
+            Prompt::Clips,
+            Prompt::Flashcards,
+            Prompt::Translation,
+        ]
+    }
+
+    /// Template name, also the override's file stem.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Prompt::Summary => "summary",
+            Prompt::Tags => "tags",
+            Prompt::Entities => "entities",
+            Prompt::ImageDescription => "image_description",
+            Prompt::Rag => "rag",
+            Prompt::RagSystem => "rag_system",
+            Prompt::Rerank => "rerank",
+            Prompt::QueryExpansion => "query_expansion",
+            Prompt::CaptureAsk => "capture_ask",
+            Prompt::Digest => "digest",
+            Prompt::YoutubeTitle => "youtube_title",
+            Prompt::YoutubeDescription => "youtube_description",
+            Prompt::YoutubeTags => "youtube_tags",
+            Prompt::YoutubeChapters => "youtube_chapters",
+            Prompt::YoutubeThumbnails => "youtube_thumbnails",
+            Prompt::YoutubeHooks => "youtube_hooks",
+            Prompt::Clips => "clips",
+            Prompt::Flashcards => "flashcards",
+            Prompt::Translation => "translation",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim_end_matches(".hbs");
+        Prompt::all().iter().copied().find(|p| p.name() == name)
+    }
+
+    /// What the prompt is for.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Prompt::Summary => "Item summaries on ingest",
+            Prompt::Tags => "Tag suggestions on ingest",
+            Prompt::Entities => "People, organizations and topics on ingest",
+            Prompt::ImageDescription => "Captions for images with little text, on ingest",
+            Prompt::Rag => "Questions answered by 'olal ask'",
+            Prompt::RagSystem => "System prompt for 'olal ask'",
+            Prompt::Rerank => "Reranking chunks for 'olal ask'",
+            Prompt::QueryExpansion => "Sub-queries for 'olal ask --expand'",
+            Prompt::CaptureAsk => "'olal capture --ask'",
+            Prompt::Digest => "'olal digest'",
+            Prompt::YoutubeTitle => "'olal youtube' titles",
+            Prompt::YoutubeDescription => "'olal youtube' descriptions",
+            Prompt::YoutubeTags => "'olal youtube' tags",
+            Prompt::YoutubeChapters => "'olal youtube' chapters",
+            Prompt::YoutubeThumbnails => "'olal youtube --thumbnail' thumbnail ideas",
+            Prompt::YoutubeHooks => "'olal youtube --thumbnail' opening hooks",
+            Prompt::Clips => "'olal clips' suggestions",
+            Prompt::Flashcards => "'olal flashcards'",
+            Prompt::Translation => "'olal translate'",
+        }
+    }
+
+    /// Variables the template is rendered with.
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            Prompt::Summary => &["content"],
+            Prompt::Tags | Prompt::Entities => &["title", "content"],
+            Prompt::ImageDescription => &["ocr_text"],
+            Prompt::Rag => &[
+                "question",
+                "context (each: number, title, content)",
+                "history (each: question, answer)",
+            ],
+            Prompt::RagSystem => &[],
+            Prompt::Rerank => &["question", "passages (each: number, title, content)"],
+            Prompt::QueryExpansion => &["question"],
+            Prompt::CaptureAsk => &["thought", "question", "related (each: number, title, content)"],
+            Prompt::Digest => &["period", "preferences", "content"],
+            Prompt::YoutubeTitle
+            | Prompt::YoutubeDescription
+            | Prompt::YoutubeTags
+            | Prompt::YoutubeChapters
+            | Prompt::YoutubeThumbnails
+            | Prompt::YoutubeHooks => &["style", "style_instructions", "content"],
+            Prompt::Clips => &["count", "min_duration", "max_duration", "transcript"],
+            Prompt::Flashcards => &["count", "content"],
+            Prompt::Translation => &["language", "content"],
+        }
+    }
+
+    /// The template olal ships with.
+    pub fn builtin(&self) -> &'static str {
+        match self {
+            Prompt::Summary => include_str!("prompts/summary.hbs"),
+            Prompt::Tags => include_str!("prompts/tags.hbs"),
+            Prompt::Entities => include_str!("prompts/entities.hbs"),
+            Prompt::ImageDescription => include_str!("prompts/image_description.hbs"),
+            Prompt::Rag => include_str!("prompts/rag.hbs"),
+            Prompt::RagSystem => include_str!("prompts/rag_system.hbs"),
+            Prompt::Rerank => include_str!("prompts/rerank.hbs"),
+            Prompt::QueryExpansion => include_str!("prompts/query_expansion.hbs"),
+            Prompt::CaptureAsk => include_str!("prompts/capture_ask.hbs"),
+            Prompt::Digest => include_str!("prompts/digest.hbs"),
+            Prompt::YoutubeTitle => include_str!("prompts/youtube_title.hbs"),
+            Prompt::YoutubeDescription => include_str!("prompts/youtube_description.hbs"),
+            Prompt::YoutubeTags => include_str!("prompts/youtube_tags.hbs"),
+            Prompt::YoutubeChapters => include_str!("prompts/youtube_chapters.hbs"),
+            Prompt::YoutubeThumbnails => include_str!("prompts/youtube_thumbnails.hbs"),
+            Prompt::YoutubeHooks => include_str!("prompts/youtube_hooks.hbs"),
+            Prompt::Clips => include_str!("prompts/clips.hbs"),
+            Prompt::Flashcards => include_str!("prompts/flashcards.hbs"),
+            Prompt::Translation => include_str!("prompts/translation.hbs"),
+        }
+    }
+
+    /// Path of the override file in `dir`.
+    pub fn override_path(&self, dir: &Path) -> PathBuf {
+        dir.join(format!("{}.hbs", self.name()))
+    }
+}
+
+impl std::fmt::Display for Prompt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// The set of templates in use: built-ins plus any overrides.
+pub struct PromptTemplates {
+    registry: Handlebars<'static>,
+    sources: HashMap<Prompt, String>,
+    overridden: Vec<Prompt>,
+    /// Overrides that failed to load or parse, with the reason.
+    broken: HashMap<Prompt, String>,
+}
+
+impl PromptTemplates {
+    /// Only the built-in templates.
+    pub fn builtin() -> Self {
+        let mut templates = Self {
+            registry: Handlebars::new(),
+            sources: HashMap::new(),
+            overridden: Vec::new(),
+            broken: HashMap::new(),
+        };
+        templates.registry.register_escape_fn(handlebars::no_escape);
+        for prompt in Prompt::all() {
+            templates
+                .register(*prompt, prompt.builtin().to_string())
+                .expect("built-in prompt templates are valid");
+        }
+        templates
+    }
+
+    /// Built-ins, replaced by any `<prompt>.hbs` files in `dir`.
+    ///
+    /// An override that can't be read or parsed doesn't stop the others
+    /// from loading; rendering that prompt reports the problem instead.
+    pub fn load(dir: &Path) -> Self {
+        let mut templates = Self::builtin();
+        for prompt in Prompt::all() {
+            let path = prompt.override_path(dir);
+            if !path.exists() {
+                continue;
+            }
+            let result = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|source| templates.register(*prompt, source));
+            match result {
+                Ok(()) => {
+                    debug!("Using prompt override {}", path.display());
+                    templates.overridden.push(*prompt);
+                }
+                Err(e) => {
+                    templates.broken.insert(*prompt, e);
+                }
+            }
+        }
+        templates
+    }
+
+    fn register(&mut self, prompt: Prompt, source: String) -> Result<(), String> {
+        self.registry
+            .register_template_string(prompt.name(), &source)
+            .map_err(|e| e.to_string())?;
+        self.sources.insert(prompt, source);
+        Ok(())
+    }
+
+    /// Render `prompt` with `data`. Trailing whitespace is trimmed.
+    pub fn render(&self, prompt: Prompt, data: &Value) -> OllamaResult<String> {
+        if let Some(reason) = self.broken.get(&prompt) {
+            return Err(OllamaError::Template {
+                name: prompt.name().to_string(),
+                message: reason.clone(),
+            });
+        }
+        let rendered = self
+            .registry
+            .render(prompt.name(), data)
+            .map_err(|e| OllamaError::Template {
+                name: prompt.name().to_string(),
+                message: e.to_string(),
+            })?;
+        Ok(rendered.trim_end().to_string())
+    }
+
+    /// The template source in use for `prompt`.
+    pub fn source(&self, prompt: Prompt) -> &str {
+        self.sources
+            .get(&prompt)
+            .map(String::as_str)
+            .unwrap_or_else(|| prompt.builtin())
+    }
+
+    pub fn is_overridden(&self, prompt: Prompt) -> bool {
+        self.overridden.contains(&prompt)
+    }
+
+    /// Why the override for `prompt` couldn't be used, if it couldn't.
+    pub fn error(&self, prompt: Prompt) -> Option<&str> {
+        self.broken.get(&prompt).map(String::as_str)
+    }
+}
+
+/// The user's templates, loaded from the prompts directory on first use.
+pub fn templates() -> &'static PromptTemplates {
+    static TEMPLATES: OnceLock<PromptTemplates> = OnceLock::new();
+    TEMPLATES.get_or_init(|| match AppPaths::new() {
+        Some(paths) => PromptTemplates::load(&paths.prompts_dir()),
+        None => PromptTemplates::builtin(),
+    })
+}
+
+/// Render `prompt` with the user's templates.
+pub fn render(prompt: Prompt, data: &Value) -> OllamaResult<String> {
+    templates().render(prompt, data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_builtin_templates_render() {
+        let templates = PromptTemplates::builtin();
+        let prompt = templates
+            .render(Prompt::Summary, &json!({ "content": "<b>Rust & C</b>" }))
+            .unwrap();
+        assert!(prompt.starts_with("Summarize the following content"));
+        assert!(prompt.ends_with("Content:\n<b>Rust & C</b>"));
+
+        let digest = templates
+            .render(
+                Prompt::Digest,
+                &json!({ "period": "daily", "preferences": "", "content": "..." }),
+            )
+            .unwrap();
+        assert!(digest.contains("knowledge base.\n\nStructure"));
+
+        let digest = templates
+            .render(
+                Prompt::Digest,
+                &json!({ "period": "daily", "preferences": "Likes rust.", "content": "..." }),
+            )
+            .unwrap();
+        assert!(digest.contains("knowledge base.\n\nReader preferences:\nLikes rust.\n\nStructure"));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let dir = std::env::temp_dir().join(format!("olal-prompts-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(Prompt::Summary.override_path(&dir), "Resume en español:\n{{content}}\n").unwrap();
+        std::fs::write(Prompt::Tags.override_path(&dir), "{{#if title}}unclosed").unwrap();
+
+        let templates = PromptTemplates::load(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(templates.is_overridden(Prompt::Summary));
+        assert_eq!(
+            templates.render(Prompt::Summary, &json!({ "content": "Hola" })).unwrap(),
+            "Resume en español:\nHola"
+        );
+        assert!(matches!(
+            templates.render(Prompt::Tags, &json!({})),
+            Err(OllamaError::Template { .. })
+        ));
+        assert!(!templates.is_overridden(Prompt::Entities));
+    }
+}
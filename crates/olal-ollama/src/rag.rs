@@ -1,10 +1,14 @@
 //! RAG (Retrieval-Augmented Generation) query engine.
 
+use crate::budget::count_tokens;
 use crate::client::OllamaClient;
 use crate::error::{OllamaError, OllamaResult};
+use crate::prompts::{self, Prompt};
 use crate::types::{GenerateOptions, GenerateRequest};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tokio::sync::mpsc;
+use tracing::{debug, warn};
 
 /// Configuration for RAG queries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +23,42 @@ pub struct RagConfig {
     pub min_similarity: f32,
     /// Temperature for generation (0.0 to 2.0).
     pub temperature: f32,
+    /// Rerank retrieved chunks with a model before answering.
+    pub rerank: bool,
+    /// Model to use for reranking (default: `model`).
+    pub rerank_model: Option<String>,
+    /// Also search with sub-queries a model derives from the question,
+    /// fusing the results, so broad questions find all their parts.
+    pub multi_query: bool,
+    /// Largest context window to use, in tokens; the model's own limit
+    /// applies if smaller.
+    pub context_tokens: usize,
+}
+
+/// With reranking, how many times `max_context_chunks` to retrieve.
+pub const RERANK_CANDIDATE_FACTOR: usize = 4;
+
+/// Earlier exchanges get at most this fraction (1/n) of the context window.
+const HISTORY_SHARE: usize = 4;
+
+/// Most sub-queries taken from a query expansion.
+pub const MAX_SUB_QUERIES: usize = 4;
+
+/// Reciprocal rank fusion constant: a result at rank `r` (from 1) in one
+/// list scores `1 / (RRF_K + r)`. The usual 60 keeps a single first place
+/// from outweighing steady placement across lists.
+const RRF_K: f32 = 60.0;
+
+impl RagConfig {
+    /// How many chunks to retrieve: more than needed when reranking, so
+    /// the reranker has something to choose from.
+    pub fn candidate_count(&self) -> usize {
+        if self.rerank {
+            self.max_context_chunks * RERANK_CANDIDATE_FACTOR
+        } else {
+            self.max_context_chunks
+        }
+    }
 }
 
 impl Default for RagConfig {
@@ -29,6 +69,10 @@ impl Default for RagConfig {
             max_context_chunks: 5,
             min_similarity: 0.3,
             temperature: 0.7,
+            rerank: false,
+            rerank_model: None,
+            multi_query: false,
+            context_tokens: 8192,
         }
     }
 }
@@ -36,12 +80,17 @@ impl Default for RagConfig {
 /// A reference to a source used in the answer.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SourceReference {
+    /// The source's number in the prompt, which the answer cites as `[n]`.
+    pub number: usize,
     /// ID of the source item.
     pub item_id: String,
     /// Title of the source item.
     pub item_title: String,
     /// The chunk content that was used.
     pub chunk_content: String,
+    /// Pages of the source the chunk came from, e.g. "p. 12–13".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pages: Option<String>,
     /// Similarity score (0.0 to 1.0).
     pub similarity: f32,
 }
@@ -51,10 +100,50 @@ pub struct SourceReference {
 pub struct RagResponse {
     /// The generated answer.
     pub answer: String,
-    /// Sources used to generate the answer.
+    /// Sources the answer cites (all of them if it cites none).
     pub sources: Vec<SourceReference>,
 }
 
+/// Source numbers cited in `answer` as `[1]`, `[2][3]` or `[1, 2]`, in
+/// order of first citation.
+pub fn cited_numbers(answer: &str) -> Vec<usize> {
+    let mut cited = Vec::new();
+    for (start, _) in answer.match_indices('[') {
+        let rest = &answer[start + 1..];
+        let Some(end) = rest.find(']') else { break };
+        let numbers: Option<Vec<usize>> = rest[..end]
+            .split(',')
+            .map(|n| n.trim().parse().ok())
+            .collect();
+        for number in numbers.into_iter().flatten() {
+            if !cited.contains(&number) {
+                cited.push(number);
+            }
+        }
+    }
+    cited
+}
+
+/// Keep the sources `answer` cites, in their original order. If it cites
+/// none (the model ignored the instruction), all are kept.
+pub fn cited_sources(answer: &str, sources: Vec<SourceReference>) -> Vec<SourceReference> {
+    let cited = cited_numbers(answer);
+    if !sources.iter().any(|s| cited.contains(&s.number)) {
+        return sources;
+    }
+    sources
+        .into_iter()
+        .filter(|s| cited.contains(&s.number))
+        .collect()
+}
+
+/// An earlier question and answer in a conversation, for follow-ups.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    pub question: String,
+    pub answer: String,
+}
+
 /// Context item for RAG queries (from vector search results).
 #[derive(Debug, Clone)]
 pub struct ContextItem {
@@ -66,124 +155,285 @@ pub struct ContextItem {
     pub item_id: String,
     /// Title of the parent item.
     pub item_title: String,
+    /// Pages the chunk came from, e.g. "p. 12–13".
+    pub pages: Option<String>,
 }
 
-/// Build the RAG prompt with context.
-pub fn build_rag_prompt(question: &str, context: &[ContextItem]) -> String {
-    let mut prompt = String::new();
+/// Build the RAG prompt with context and any earlier exchanges.
+pub fn build_rag_prompt(
+    question: &str,
+    context: &[ContextItem],
+    history: &[Exchange],
+) -> OllamaResult<String> {
+    let context: Vec<_> = context
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            json!({
+                "number": i + 1,
+                "title": item.item_title,
+                "pages": item.pages,
+                "content": item.content,
+            })
+        })
+        .collect();
+    prompts::render(
+        Prompt::Rag,
+        &json!({ "question": question, "context": context, "history": history }),
+    )
+}
 
-    // Add context section
-    prompt.push_str("Use the following context to answer the question. If the context doesn't contain relevant information, say so.\n\n");
-    prompt.push_str("Context:\n");
-    prompt.push_str("─────────────────────────────────────\n");
+/// Build the system prompt for RAG.
+pub fn build_system_prompt() -> OllamaResult<String> {
+    prompts::render(Prompt::RagSystem, &json!({}))
+}
 
-    for (i, item) in context.iter().enumerate() {
-        prompt.push_str(&format!("\n[{}] From: {}\n", i + 1, item.item_title));
-        prompt.push_str(&item.content);
-        prompt.push_str("\n");
-    }
+/// Build the prompt asking a model to score each candidate chunk.
+pub fn build_rerank_prompt(question: &str, candidates: &[ContextItem]) -> OllamaResult<String> {
+    let passages: Vec<_> = candidates
+        .iter()
+        .enumerate()
+        .map(|(i, item)| {
+            json!({
+                "number": i + 1,
+                "title": item.item_title,
+                "content": truncate_content(&item.content, 800),
+            })
+        })
+        .collect();
+    prompts::render(Prompt::Rerank, &json!({ "question": question, "passages": passages }))
+}
 
-    prompt.push_str("\n─────────────────────────────────────\n\n");
-    prompt.push_str(&format!("Question: {}\n\n", question));
-    prompt.push_str("Answer:");
+/// Build the prompt asking a model to split `question` into sub-queries.
+pub fn build_expansion_prompt(question: &str) -> OllamaResult<String> {
+    prompts::render(Prompt::QueryExpansion, &json!({ "question": question }))
+}
 
-    prompt
+/// Parse one sub-query per line, without list markers or quotes, leaving
+/// out blanks and repeats of `question`. At most [`MAX_SUB_QUERIES`].
+fn parse_sub_queries(response: &str, question: &str) -> Vec<String> {
+    let mut queries: Vec<String> = Vec::new();
+    for line in response.lines() {
+        let query = strip_list_marker(line).trim_matches('"').trim();
+        let seen = |q: &str| q.eq_ignore_ascii_case(query);
+        if query.is_empty() || seen(question) || queries.iter().any(|q| seen(q)) {
+            continue;
+        }
+        queries.push(query.to_string());
+        if queries.len() == MAX_SUB_QUERIES {
+            break;
+        }
+    }
+    queries
 }
 
-/// Build the system prompt for RAG.
-pub fn build_system_prompt() -> String {
-    r#"You are a helpful assistant that answers questions based on the provided context from a personal knowledge base.
+/// `line` without a leading "1.", "2)", "-", "*" or "•".
+fn strip_list_marker(line: &str) -> &str {
+    let line = line.trim();
+    let after_digits = line.trim_start_matches(|c: char| c.is_ascii_digit());
+    if after_digits.len() < line.len() {
+        if let Some(rest) = after_digits.strip_prefix(['.', ')']) {
+            return rest.trim();
+        }
+    }
+    line.strip_prefix(['-', '*', '•']).unwrap_or(line).trim()
+}
 
-Guidelines:
-- Base your answers on the context provided
-- If the context doesn't contain enough information, acknowledge that
-- Be concise but thorough
-- When relevant, mention which source(s) your answer is based on
-- Do not make up information not present in the context"#
-        .to_string()
+/// Merge several ranked lists of chunks with reciprocal rank fusion: a
+/// chunk scores the sum of `1 / (60 + rank)` over the lists it's in, so
+/// chunks many queries find come first. Each keeps its best similarity.
+pub fn fuse_rankings(rankings: Vec<Vec<ContextItem>>) -> Vec<ContextItem> {
+    let mut fused: Vec<(f32, ContextItem)> = Vec::new();
+    for ranking in rankings {
+        for (rank, item) in ranking.into_iter().enumerate() {
+            let score = 1.0 / (RRF_K + rank as f32 + 1.0);
+            let existing = fused.iter_mut().find(|(_, seen)| {
+                seen.item_id == item.item_id && seen.content == item.content
+            });
+            match existing {
+                Some((total, seen)) => {
+                    *total += score;
+                    seen.similarity = seen.similarity.max(item.similarity);
+                }
+                None => fused.push((score, item)),
+            }
+        }
+    }
+    // Stable, so ties keep the order they were first found in
+    fused.sort_by(|a, b| b.0.total_cmp(&a.0));
+    fused.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Parse "[number] score" lines into one score per candidate. Candidates
+/// the model left out score 0; `None` if no line could be parsed.
+fn parse_rerank_scores(response: &str, count: usize) -> Option<Vec<f32>> {
+    let mut scores = vec![0.0; count];
+    let mut parsed = false;
+    for line in response.lines() {
+        let numbers: Vec<f32> = line
+            .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter_map(|n| n.trim_matches('.').parse().ok())
+            .collect();
+        let [number, score, ..] = numbers.as_slice() else {
+            continue;
+        };
+        let index = *number as usize;
+        if index >= 1 && index <= count {
+            scores[index - 1] = *score;
+            parsed = true;
+        }
+    }
+    parsed.then_some(scores)
 }
 
 impl OllamaClient {
-    /// Perform a RAG query with the given context.
-    pub async fn rag_query(
+    /// Have a model rewrite `question` as up to [`MAX_SUB_QUERIES`]
+    /// narrower search queries. Empty if it gave none usable.
+    pub async fn expand_query(
+        &self,
+        question: &str,
+        config: &RagConfig,
+    ) -> OllamaResult<Vec<String>> {
+        let prompt = build_expansion_prompt(question)?;
+        let request = GenerateRequest::new(&config.model, prompt)
+            .with_options(GenerateOptions::new().with_temperature(0.0));
+        let response = self.generate(request).await?;
+
+        let queries = parse_sub_queries(&response.response, question);
+        debug!("Expanded question into {:?}", queries);
+        Ok(queries)
+    }
+
+    /// Re-order retrieved chunks by how well a model thinks they answer
+    /// `question`, keeping the best `max_context_chunks`. If the model's
+    /// reply can't be parsed, the similarity order is kept.
+    pub async fn rerank(
+        &self,
+        question: &str,
+        mut candidates: Vec<ContextItem>,
+        config: &RagConfig,
+    ) -> OllamaResult<Vec<ContextItem>> {
+        if candidates.len() > 1 {
+            let prompt = build_rerank_prompt(question, &candidates)?;
+            let model = config.rerank_model.as_deref().unwrap_or(&config.model);
+            let request = GenerateRequest::new(model, prompt)
+                .with_options(GenerateOptions::new().with_temperature(0.0));
+            let response = self.generate(request).await?;
+
+            match parse_rerank_scores(&response.response, candidates.len()) {
+                Some(scores) => {
+                    let mut scored: Vec<_> = scores.into_iter().zip(candidates).collect();
+                    // Stable, so equal scores keep their similarity order
+                    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+                    candidates = scored.into_iter().map(|(_, item)| item).collect();
+                }
+                None => warn!("Could not parse reranking response, keeping similarity order"),
+            }
+        }
+
+        candidates.truncate(config.max_context_chunks);
+        Ok(candidates)
+    }
+
+    /// Build the answer request for `question`, packing as much of
+    /// `context` as fits the model's context window.
+    async fn rag_request(
         &self,
         question: &str,
         context: &[ContextItem],
+        history: &[Exchange],
         config: &RagConfig,
-    ) -> OllamaResult<RagResponse> {
+    ) -> OllamaResult<(GenerateRequest, Vec<SourceReference>)> {
         if context.is_empty() {
             return Err(OllamaError::NoContext);
         }
 
-        // Build the prompt
-        let prompt = build_rag_prompt(question, context);
-        let system = build_system_prompt();
+        let system = build_system_prompt()?;
+        let budget = self.context_budget(&config.model, config.context_tokens).await;
+        let history = recent_history(history, budget.window() / HISTORY_SHARE);
+        let overhead = format!("{}\n{}", system, build_rag_prompt(question, &[], history)?);
+        let packed = budget.pack(&overhead, context);
+        if packed.len() < context.len() {
+            debug!(
+                "{} of {} chunks fit in a {}-token context",
+                packed.len(),
+                context.len(),
+                budget.window()
+            );
+        }
 
-        // Create the request
+        let prompt = build_rag_prompt(question, &packed, history)?;
         let request = GenerateRequest::new(&config.model, prompt)
             .with_system(system)
-            .with_options(GenerateOptions::new().with_temperature(config.temperature));
-
-        // Generate the response
-        let response = self.generate(request).await?;
+            .with_options(budget.options().with_temperature(config.temperature));
 
         // Build source references
-        let sources: Vec<SourceReference> = context
+        let sources: Vec<SourceReference> = packed
             .iter()
-            .map(|c| SourceReference {
+            .enumerate()
+            .map(|(i, c)| SourceReference {
+                number: i + 1,
                 item_id: c.item_id.clone(),
                 item_title: c.item_title.clone(),
                 chunk_content: truncate_content(&c.content, 200),
+                pages: c.pages.clone(),
                 similarity: c.similarity,
             })
             .collect();
 
+        Ok((request, sources))
+    }
+
+    /// Perform a RAG query with the given context. `history` holds the
+    /// conversation so far, oldest first, when `question` is a follow-up.
+    pub async fn rag_query(
+        &self,
+        question: &str,
+        context: &[ContextItem],
+        history: &[Exchange],
+        config: &RagConfig,
+    ) -> OllamaResult<RagResponse> {
+        let (request, sources) = self.rag_request(question, context, history, config).await?;
+        let response = self.generate(request).await?;
+
         Ok(RagResponse {
+            sources: cited_sources(&response.response, sources),
             answer: response.response,
-            sources,
         })
     }
 
     /// Perform a RAG query with streaming response.
-    /// Returns a channel receiver for response chunks and the sources.
+    /// Returns a channel receiver for response chunks and all the sources;
+    /// filter them with [`cited_sources`] once the answer is complete.
     pub async fn rag_query_stream(
         &self,
         question: &str,
         context: &[ContextItem],
+        history: &[Exchange],
         config: &RagConfig,
     ) -> OllamaResult<(mpsc::Receiver<String>, Vec<SourceReference>)> {
-        if context.is_empty() {
-            return Err(OllamaError::NoContext);
-        }
-
-        // Build the prompt
-        let prompt = build_rag_prompt(question, context);
-        let system = build_system_prompt();
-
-        // Create the request
-        let request = GenerateRequest::new(&config.model, prompt)
-            .with_system(system)
-            .with_stream(true)
-            .with_options(GenerateOptions::new().with_temperature(config.temperature));
-
-        // Start streaming
-        let rx = self.generate_stream(request).await?;
-
-        // Build source references
-        let sources: Vec<SourceReference> = context
-            .iter()
-            .map(|c| SourceReference {
-                item_id: c.item_id.clone(),
-                item_title: c.item_title.clone(),
-                chunk_content: truncate_content(&c.content, 200),
-                similarity: c.similarity,
-            })
-            .collect();
+        let (request, sources) = self.rag_request(question, context, history, config).await?;
+        let rx = self.generate_stream(request.with_stream(true)).await?;
 
         Ok((rx, sources))
     }
 }
 
+/// The latest exchanges of `history` that fit in `max_tokens`.
+fn recent_history(history: &[Exchange], max_tokens: usize) -> &[Exchange] {
+    let mut used = 0;
+    let mut start = history.len();
+    while start > 0 {
+        let exchange = &history[start - 1];
+        used += count_tokens(&exchange.question) + count_tokens(&exchange.answer);
+        if used > max_tokens {
+            break;
+        }
+        start -= 1;
+    }
+    &history[start..]
+}
+
 /// Truncate content to a maximum length, adding ellipsis if needed.
 fn truncate_content(content: &str, max_len: usize) -> String {
     if content.len() <= max_len {
@@ -206,21 +456,128 @@ mod tests {
                 similarity: 0.9,
                 item_id: "id1".to_string(),
                 item_title: "README".to_string(),
+                pages: None,
             },
             ContextItem {
                 content: "It uses SQLite for storage.".to_string(),
                 similarity: 0.8,
                 item_id: "id2".to_string(),
                 item_title: "Architecture".to_string(),
+                pages: Some("p. 3".to_string()),
             },
         ];
 
-        let prompt = build_rag_prompt("What is Olal?", &context);
+        let prompt = build_rag_prompt("What is Olal?", &context, &[]).unwrap();
 
         assert!(prompt.contains("What is Olal?"));
         assert!(prompt.contains("README"));
         assert!(prompt.contains("Olal is a knowledge management system"));
         assert!(prompt.contains("Architecture"));
+        assert!(prompt.contains(
+            "─\n\n[1] From: README\nOlal is a knowledge management system.\n\n[2] From: Architecture (p. 3)\n"
+        ));
+        assert!(prompt.ends_with("storage.\n\n─────────────────────────────────────\n\nQuestion: What is Olal?\n\nAnswer:"));
+    }
+
+    #[test]
+    fn test_build_rag_prompt_with_history() {
+        let context = vec![ContextItem {
+            content: "Each value has one owner.".to_string(),
+            similarity: 0.9,
+            item_id: "id1".to_string(),
+            item_title: "Ownership".to_string(),
+            pages: None,
+        }];
+        let history = vec![Exchange {
+            question: "Who owns a value?".to_string(),
+            answer: "Exactly one owner [1].".to_string(),
+        }];
+
+        let prompt = build_rag_prompt("And when is it dropped?", &context, &history).unwrap();
+        assert!(prompt.contains(
+            "Conversation so far:\n\nQuestion: Who owns a value?\nAnswer: Exactly one owner [1].\n\nQuestion: And when is it dropped?"
+        ), "{}", prompt);
+        assert!(!build_rag_prompt("q", &context, &[]).unwrap().contains("Conversation so far"));
+    }
+
+    #[test]
+    fn test_recent_history() {
+        let exchange = |n: usize| Exchange {
+            question: format!("Question {}", n),
+            answer: "word ".repeat(10),
+        };
+        let history: Vec<Exchange> = (1..=3).map(exchange).collect();
+        // Each exchange is 13 tokens; the oldest is dropped first
+        assert_eq!(recent_history(&history, 100).len(), 3);
+        let recent = recent_history(&history, 30);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].question, "Question 2");
+        assert!(recent_history(&history, 5).is_empty());
+    }
+
+    #[test]
+    fn test_parse_rerank_scores() {
+        let response = "[2] 9\n[1] 3\n[4] 7.5\nPassage 9 is not a passage";
+        assert_eq!(
+            parse_rerank_scores(response, 4),
+            Some(vec![3.0, 9.0, 0.0, 7.5])
+        );
+        assert_eq!(parse_rerank_scores("1. 8\n2. 2", 2), Some(vec![8.0, 2.0]));
+        assert_eq!(parse_rerank_scores("They are all relevant.", 3), None);
+    }
+
+    #[test]
+    fn test_parse_sub_queries() {
+        let response = "1. Rust ownership rules\n\n2) \"borrowing and references\"\n- What is Rust ownership?\n* rust ownership rules\n- lifetimes\n- 3D drop order\n- moves";
+        assert_eq!(
+            parse_sub_queries(response, "What is Rust ownership?"),
+            vec!["Rust ownership rules", "borrowing and references", "lifetimes", "3D drop order"]
+        );
+        assert!(parse_sub_queries("", "q").is_empty());
+    }
+
+    #[test]
+    fn test_fuse_rankings() {
+        let item = |id: &str, similarity: f32| ContextItem {
+            content: format!("Content of {}", id),
+            similarity,
+            item_id: id.to_string(),
+            item_title: id.to_uppercase(),
+            pages: None,
+        };
+        let fused = fuse_rankings(vec![
+            vec![item("a", 0.9), item("b", 0.8), item("c", 0.7)],
+            vec![item("c", 0.95), item("d", 0.6)],
+            vec![item("d", 0.5), item("c", 0.4)],
+        ]);
+        let ids: Vec<&str> = fused.iter().map(|i| i.item_id.as_str()).collect();
+        // c is in every list, d in two; a beats b on rank
+        assert_eq!(ids, vec!["c", "d", "a", "b"]);
+        assert_eq!(fused[0].similarity, 0.95);
+    }
+
+    #[test]
+    fn test_cited_sources() {
+        assert_eq!(cited_numbers("Owners [2]. Borrowing [1][3], [2, 4]."), vec![2, 1, 3, 4]);
+        assert_eq!(cited_numbers("See [link] and [1"), Vec::<usize>::new());
+
+        let sources: Vec<SourceReference> = (1..=3)
+            .map(|number| SourceReference {
+                number,
+                item_id: format!("id{}", number),
+                item_title: format!("Source {}", number),
+                chunk_content: String::new(),
+                pages: None,
+                similarity: 0.5,
+            })
+            .collect();
+        let cited = cited_sources("Each value has one owner [3][1].", sources.clone());
+        let numbers: Vec<usize> = cited.iter().map(|s| s.number).collect();
+        assert_eq!(numbers, vec![1, 3]);
+
+        // Uncited answers (or citations of nothing) keep every source
+        assert_eq!(cited_sources("No citations here.", sources.clone()).len(), 3);
+        assert_eq!(cited_sources("Made up [7].", sources).len(), 3);
     }
 
     #[test]
@@ -240,5 +597,12 @@ mod tests {
         assert_eq!(config.max_context_chunks, 5);
         assert_eq!(config.min_similarity, 0.3);
         assert_eq!(config.temperature, 0.7);
+        assert_eq!(config.candidate_count(), 5);
+
+        let config = RagConfig {
+            rerank: true,
+            ..RagConfig::default()
+        };
+        assert_eq!(config.candidate_count(), 20);
     }
 }
@@ -0,0 +1,167 @@
+//! Retries with jittered exponential backoff, and a circuit breaker shared
+//! by all clones of a client.
+//!
+//! A request that fails transiently is retried up to `max_retries` times.
+//! Every failure also counts towards the breaker: after `breaker_threshold`
+//! in a row the circuit opens, and every request waits out the cooldown
+//! before trying again, so a batch pauses while Ollama restarts or loads a
+//! model instead of failing chunk after chunk. Once it has paused
+//! `breaker_max_pauses` times without a success in between, requests fail
+//! straight away with [`OllamaError::CircuitOpen`].
+
+use crate::error::OllamaError;
+use olal_config::RetryConfig;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Whether a failed request is worth retrying.
+pub(crate) fn is_transient(error: &OllamaError) -> bool {
+    match error {
+        OllamaError::Connection(_) | OllamaError::ServerNotRunning { .. } | OllamaError::Timeout { .. } => true,
+        OllamaError::ApiError { status, .. } => matches!(status, 429 | 500 | 502 | 503 | 504),
+        OllamaError::Http(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+        _ => false,
+    }
+}
+
+/// Delay before retry number `attempt` (0-based): doubling from the initial
+/// backoff up to the maximum, then randomly cut by up to half so clients
+/// don't retry in lockstep.
+pub(crate) fn backoff(config: &RetryConfig, attempt: u32) -> Duration {
+    let base = config
+        .initial_backoff_ms
+        .saturating_mul(1u64 << attempt.min(20))
+        .min(config.max_backoff_ms);
+    let jitter = random() % (base / 2 + 1);
+    Duration::from_millis(base - jitter)
+}
+
+fn random() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// Consecutive-failure circuit breaker.
+#[derive(Debug, Default)]
+pub(crate) struct Breaker {
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    failures: u32,
+    open_until: Option<Instant>,
+    pauses: u32,
+}
+
+/// What a request should do before going out.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Permit {
+    Go,
+    /// Wait this long first; the circuit is open.
+    Wait(Duration),
+    /// Give up; the circuit kept opening.
+    GiveUp,
+}
+
+impl Breaker {
+    pub fn permit(&self, config: &RetryConfig) -> Permit {
+        let state = self.state.lock().unwrap();
+        match state.open_until {
+            _ if config.breaker_threshold == 0 => Permit::Go,
+            Some(_) if state.pauses > config.breaker_max_pauses => Permit::GiveUp,
+            Some(until) => match until.checked_duration_since(Instant::now()) {
+                Some(wait) if !wait.is_zero() => Permit::Wait(wait),
+                _ => Permit::Go,
+            },
+            None => Permit::Go,
+        }
+    }
+
+    pub fn success(&self) {
+        *self.state.lock().unwrap() = BreakerState::default();
+    }
+
+    /// Record a failure. Returns the pause if this opened the circuit.
+    pub fn failure(&self, config: &RetryConfig) -> Option<Duration> {
+        let mut state = self.state.lock().unwrap();
+        state.failures += 1;
+        if config.breaker_threshold == 0 || state.failures < config.breaker_threshold {
+            return None;
+        }
+        let cooldown = Duration::from_secs(config.breaker_cooldown_seconds);
+        state.failures = 0;
+        state.pauses += 1;
+        state.open_until = Some(Instant::now() + cooldown);
+        Some(cooldown)
+    }
+
+    /// Pauses in a row so far.
+    pub fn pauses(&self) -> u32 {
+        self.state.lock().unwrap().pauses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_with_jitter() {
+        let config = RetryConfig {
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1000,
+            ..Default::default()
+        };
+        for (attempt, base) in [(0, 100), (1, 200), (2, 400), (3, 800), (4, 1000), (30, 1000)] {
+            let delay = backoff(&config, attempt).as_millis() as u64;
+            assert!(delay <= base && delay >= base / 2, "attempt {}: {}ms", attempt, delay);
+        }
+    }
+
+    #[test]
+    fn test_breaker_opens_and_gives_up() {
+        let config = RetryConfig {
+            breaker_threshold: 2,
+            breaker_cooldown_seconds: 0,
+            breaker_max_pauses: 1,
+            ..Default::default()
+        };
+        let breaker = Breaker::default();
+        assert_eq!(breaker.permit(&config), Permit::Go);
+
+        assert_eq!(breaker.failure(&config), None);
+        assert_eq!(breaker.failure(&config), Some(Duration::ZERO));
+        // The cooldown is over at once, so requests go again
+        assert_eq!(breaker.permit(&config), Permit::Go);
+
+        breaker.failure(&config);
+        breaker.failure(&config);
+        assert_eq!(breaker.pauses(), 2);
+        assert_eq!(breaker.permit(&config), Permit::GiveUp);
+
+        breaker.success();
+        assert_eq!(breaker.permit(&config), Permit::Go);
+    }
+
+    #[test]
+    fn test_breaker_waits_while_open() {
+        let config = RetryConfig {
+            breaker_threshold: 1,
+            breaker_cooldown_seconds: 60,
+            ..Default::default()
+        };
+        let breaker = Breaker::default();
+        breaker.failure(&config);
+        assert!(matches!(breaker.permit(&config), Permit::Wait(d) if d > Duration::from_secs(50)));
+    }
+
+    #[test]
+    fn test_transient_errors() {
+        assert!(is_transient(&OllamaError::ApiError { status: 503, message: String::new() }));
+        assert!(is_transient(&OllamaError::Timeout { seconds: 1 }));
+        assert!(!is_transient(&OllamaError::ApiError { status: 400, message: String::new() }));
+        assert!(!is_transient(&OllamaError::ModelNotFound { model: "x".to_string() }));
+    }
+}
@@ -33,6 +33,109 @@ pub struct EmbeddingResponse {
     pub embedding: Vec<f32>,
 }
 
+/// Request body for /api/show endpoint.
+#[derive(Debug, Clone, Serialize)]
+pub struct ShowRequest {
+    pub model: String,
+}
+
+/// Response from /api/show endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ShowResponse {
+    #[serde(default)]
+    pub details: ShowDetails,
+    /// Architecture details, keyed like `llama.context_length`.
+    #[serde(default)]
+    pub model_info: serde_json::Map<String, serde_json::Value>,
+    /// What the model can do, e.g. `completion`, `embedding`, `vision`.
+    /// Missing from older Ollama versions.
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+}
+
+/// The `details` of a /api/show response.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ShowDetails {
+    #[serde(default)]
+    pub family: String,
+    #[serde(default)]
+    pub families: Option<Vec<String>>,
+    #[serde(default)]
+    pub parameter_size: String,
+    #[serde(default)]
+    pub quantization_level: String,
+}
+
+impl ShowResponse {
+    /// The longest context the model supports, in tokens.
+    pub fn context_length(&self) -> Option<usize> {
+        self.model_info
+            .iter()
+            .find(|(key, _)| key.ends_with(".context_length"))
+            .and_then(|(_, value)| value.as_u64())
+            .map(|length| length as usize)
+    }
+}
+
+/// What a model is used for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModelUse {
+    /// Generating text: answers, summaries, tags.
+    Generate,
+    /// Generating embeddings.
+    Embed,
+}
+
+/// What a model is and can do, from /api/show.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelDetails {
+    pub name: String,
+    pub family: String,
+    pub parameter_size: String,
+    pub quantization: String,
+    /// The longest context the model supports, in tokens.
+    pub context_length: Option<usize>,
+    /// Generates embeddings.
+    pub embeds: bool,
+    /// Generates text.
+    pub generates: bool,
+}
+
+impl ModelDetails {
+    /// Read the details of `name` from its /api/show response.
+    ///
+    /// Without a capabilities list (older Ollama), BERT-family models are
+    /// taken to be embedding-only and everything else text-only.
+    pub fn from_show(name: &str, show: &ShowResponse) -> Self {
+        let (embeds, generates) = if show.capabilities.is_empty() {
+            let bert = std::iter::once(&show.details.family)
+                .chain(show.details.families.iter().flatten())
+                .any(|family| family.contains("bert"));
+            (bert, !bert)
+        } else {
+            let has = |capability: &str| show.capabilities.iter().any(|c| c == capability);
+            (has("embedding"), has("completion"))
+        };
+        Self {
+            name: name.to_string(),
+            family: show.details.family.clone(),
+            parameter_size: show.details.parameter_size.clone(),
+            quantization: show.details.quantization_level.clone(),
+            context_length: show.context_length(),
+            embeds,
+            generates,
+        }
+    }
+
+    /// Whether the model can be used for `usage`.
+    pub fn supports(&self, usage: ModelUse) -> bool {
+        match usage {
+            ModelUse::Generate => self.generates,
+            ModelUse::Embed => self.embeds,
+        }
+    }
+}
+
 /// Request body for /api/generate endpoint.
 #[derive(Debug, Clone, Serialize)]
 pub struct GenerateRequest {
@@ -48,6 +151,9 @@ pub struct GenerateRequest {
     pub stream: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub options: Option<GenerateOptions>,
+    /// Base64-encoded images, for multimodal models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub images: Option<Vec<String>>,
 }
 
 impl GenerateRequest {
@@ -61,6 +167,7 @@ impl GenerateRequest {
             context: None,
             stream: false,
             options: None,
+            images: None,
         }
     }
 
@@ -81,6 +188,12 @@ impl GenerateRequest {
         self.options = Some(options);
         self
     }
+
+    /// Attach base64-encoded images for a multimodal model to look at.
+    pub fn with_images(mut self, images: Vec<String>) -> Self {
+        self.images = Some(images);
+        self
+    }
 }
 
 /// Options for text generation.
@@ -98,6 +211,8 @@ pub struct GenerateOptions {
     pub stop: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub seed: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_ctx: Option<i32>,
 }
 
 impl GenerateOptions {
@@ -119,6 +234,11 @@ impl GenerateOptions {
         self.num_predict = Some(num_predict);
         self
     }
+
+    pub fn with_num_ctx(mut self, num_ctx: i32) -> Self {
+        self.num_ctx = Some(num_ctx);
+        self
+    }
 }
 
 /// Response from /api/generate endpoint (non-streaming).
@@ -158,3 +278,35 @@ pub struct StreamChunk {
 pub struct ErrorResponse {
     pub error: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_model_details_from_show() {
+        let show: ShowResponse = serde_json::from_value(json!({
+            "details": { "family": "llama", "parameter_size": "8.0B", "quantization_level": "Q4_0" },
+            "model_info": { "general.architecture": "llama", "llama.context_length": 131072 },
+            "capabilities": ["completion", "tools"],
+        }))
+        .unwrap();
+        let details = ModelDetails::from_show("llama3.1", &show);
+        assert_eq!(details.family, "llama");
+        assert_eq!(details.quantization, "Q4_0");
+        assert_eq!(details.context_length, Some(131072));
+        assert!(details.supports(ModelUse::Generate));
+        assert!(!details.supports(ModelUse::Embed));
+
+        // Older servers don't list capabilities
+        let show: ShowResponse = serde_json::from_value(json!({
+            "details": { "family": "nomic-bert", "families": ["nomic-bert"] },
+        }))
+        .unwrap();
+        let details = ModelDetails::from_show("nomic-embed-text", &show);
+        assert_eq!(details.context_length, None);
+        assert!(details.supports(ModelUse::Embed));
+        assert!(!details.supports(ModelUse::Generate));
+    }
+}
@@ -0,0 +1,277 @@
+//! Audio cleanup before transcription.
+//!
+//! Quiet screen-recording audio transcribes badly, and long pauses cost
+//! Whisper time without adding words. [`preprocess_audio`] runs an ffmpeg
+//! filter chain that normalizes loudness (`loudnorm`) and cuts long
+//! silences. Cut silences are remembered, so transcript timestamps can be
+//! mapped back onto the original recording with
+//! [`PreparedAudio::restore_timestamps`].
+
+use crate::error::{ProcessError, ProcessResult};
+use crate::TranscriptSegment;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info};
+
+/// Silence kept on each side of a cut, so word edges aren't clipped.
+const SILENCE_PADDING: f64 = 0.25;
+
+/// What to do to audio before transcribing it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AudioPreprocessing {
+    /// Normalize loudness (EBU R128).
+    pub normalize: bool,
+    /// Cut silences of at least `min_silence_seconds`.
+    pub trim_silence: bool,
+    /// Audio quieter than this (dBFS) counts as silence.
+    pub silence_threshold_db: f64,
+    pub min_silence_seconds: f64,
+}
+
+impl Default for AudioPreprocessing {
+    fn default() -> Self {
+        Self {
+            normalize: false,
+            trim_silence: false,
+            silence_threshold_db: -40.0,
+            min_silence_seconds: 1.0,
+        }
+    }
+}
+
+impl AudioPreprocessing {
+    /// Whether any step is on.
+    pub fn is_enabled(&self) -> bool {
+        self.normalize || self.trim_silence
+    }
+}
+
+/// Audio ready for transcription.
+#[derive(Debug, Clone)]
+pub struct PreparedAudio {
+    /// The processed file, or the input if nothing was done.
+    pub path: PathBuf,
+    /// Silences cut from the original, as (start, end) in original time.
+    pub removed: Vec<(f64, f64)>,
+    pub normalized: bool,
+}
+
+impl PreparedAudio {
+    /// Seconds of silence cut.
+    pub fn removed_seconds(&self) -> f64 {
+        self.removed.iter().map(|(start, end)| end - start).sum()
+    }
+
+    /// Map a time in the processed audio back onto the original.
+    pub fn original_time(&self, time: f64) -> f64 {
+        let mut original = time;
+        for (start, end) in &self.removed {
+            if *start <= original {
+                original += end - start;
+            } else {
+                break;
+            }
+        }
+        original
+    }
+
+    /// Shift transcript timestamps back onto the original recording.
+    pub fn restore_timestamps(&self, segments: &mut [TranscriptSegment]) {
+        if self.removed.is_empty() {
+            return;
+        }
+        for segment in segments {
+            segment.start = self.original_time(segment.start);
+            segment.end = self.original_time(segment.end).max(segment.start);
+            for word in &mut segment.words {
+                word.start = self.original_time(word.start);
+                word.end = self.original_time(word.end).max(word.start);
+            }
+        }
+    }
+}
+
+/// Run the configured filters over `audio_path`, writing the result to
+/// `output_dir`. With nothing to do, the input is returned as-is.
+pub fn preprocess_audio(
+    audio_path: &Path,
+    output_dir: &Path,
+    options: &AudioPreprocessing,
+) -> ProcessResult<PreparedAudio> {
+    let unchanged = PreparedAudio {
+        path: audio_path.to_path_buf(),
+        removed: Vec::new(),
+        normalized: false,
+    };
+    if !options.is_enabled() {
+        return Ok(unchanged);
+    }
+
+    if !audio_path.exists() {
+        return Err(ProcessError::FileNotFound(audio_path.to_path_buf()));
+    }
+
+    if which::which("ffmpeg").is_err() {
+        return Err(ProcessError::ToolNotFound {
+            tool: "ffmpeg".to_string(),
+        });
+    }
+
+    let removed = if options.trim_silence {
+        detect_silences(audio_path, options)?
+    } else {
+        Vec::new()
+    };
+
+    let mut filters = Vec::new();
+    if !removed.is_empty() {
+        let ranges: Vec<String> = removed
+            .iter()
+            .map(|(start, end)| format!("between(t,{:.3},{:.3})", start, end))
+            .collect();
+        filters.push(format!("aselect='not({})'", ranges.join("+")));
+        filters.push("asetpts=N/SR/TB".to_string());
+    }
+    if options.normalize {
+        filters.push("loudnorm=I=-16:TP=-1.5:LRA=11".to_string());
+    }
+    if filters.is_empty() {
+        return Ok(unchanged);
+    }
+
+    let stem = audio_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("audio");
+    let output_path = output_dir.join(format!("{}.prepared.wav", stem));
+
+    info!(
+        "Preprocessing audio {:?} ({} silences cut, normalize: {})",
+        audio_path,
+        removed.len(),
+        options.normalize
+    );
+
+    let output = Command::new("ffmpeg")
+        .args(["-i"])
+        .arg(audio_path)
+        .args(["-af", &filters.join(",")])
+        .args([
+            "-acodec", "pcm_s16le",
+            "-ar", "16000",  // 16kHz sample rate (good for Whisper)
+            "-ac", "1",
+            "-y",
+        ])
+        .arg(&output_path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ProcessError::FfmpegError(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    Ok(PreparedAudio {
+        path: output_path,
+        removed,
+        normalized: options.normalize,
+    })
+}
+
+/// Find long silences with ffmpeg's `silencedetect`, shrunk by the padding.
+fn detect_silences(audio_path: &Path, options: &AudioPreprocessing) -> ProcessResult<Vec<(f64, f64)>> {
+    let output = Command::new("ffmpeg")
+        .args(["-i"])
+        .arg(audio_path)
+        .args([
+            "-af",
+            &format!(
+                "silencedetect=noise={}dB:d={}",
+                options.silence_threshold_db, options.min_silence_seconds
+            ),
+            "-f", "null",
+            "-",
+        ])
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ProcessError::FfmpegError(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    let silences = parse_silences(&String::from_utf8_lossy(&output.stderr));
+    debug!("Detected {} silences", silences.len());
+    Ok(silences)
+}
+
+/// Silences from `silencedetect` log lines. A silence still open at the
+/// end of the file isn't cut: its end isn't known.
+fn parse_silences(log: &str) -> Vec<(f64, f64)> {
+    let value = |line: &str, key: &str| -> Option<f64> {
+        let rest = &line[line.find(key)? + key.len()..];
+        rest.split_whitespace().next()?.parse().ok()
+    };
+
+    let mut silences = Vec::new();
+    let mut start = None;
+    for line in log.lines() {
+        if let Some(time) = value(line, "silence_start: ") {
+            start = Some(time);
+        } else if let Some(end) = value(line, "silence_end: ") {
+            if let Some(start) = start.take() {
+                let (start, end) = (start.max(0.0) + SILENCE_PADDING, end - SILENCE_PADDING);
+                if end > start {
+                    silences.push((start, end));
+                }
+            }
+        }
+    }
+    silences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TranscriptWord;
+
+    #[test]
+    fn test_parse_silences() {
+        let log = "\
+[silencedetect @ 0x1] silence_start: 3.5
+[silencedetect @ 0x1] silence_end: 6.5 | silence_duration: 3
+size=N/A time=00:00:20.00 bitrate=N/A
+[silencedetect @ 0x1] silence_start: 10
+[silencedetect @ 0x1] silence_end: 10.4 | silence_duration: 0.4
+[silencedetect @ 0x1] silence_start: 18.2
+";
+        // The short one is all padding; the last never ends
+        assert_eq!(parse_silences(log), vec![(3.75, 6.25)]);
+    }
+
+    #[test]
+    fn test_restore_timestamps() {
+        let prepared = PreparedAudio {
+            path: PathBuf::from("a.wav"),
+            removed: vec![(2.0, 5.0), (10.0, 12.0)],
+            normalized: false,
+        };
+        assert_eq!(prepared.removed_seconds(), 5.0);
+        assert_eq!(prepared.original_time(1.0), 1.0);
+        // 3s in the cut audio is 1s after the first cut
+        assert_eq!(prepared.original_time(3.0), 6.0);
+        // 8s in the cut audio is past both cuts
+        assert_eq!(prepared.original_time(8.0), 13.0);
+
+        let mut segments = vec![TranscriptSegment {
+            text: "hello".to_string(),
+            start: 1.5,
+            end: 3.0,
+            speaker: None,
+            words: vec![TranscriptWord { text: "hello".to_string(), start: 2.5, end: 3.0 }],
+        }];
+        prepared.restore_timestamps(&mut segments);
+        assert_eq!((segments[0].start, segments[0].end), (1.5, 6.0));
+        assert_eq!((segments[0].words[0].start, segments[0].words[0].end), (5.5, 6.0));
+    }
+}
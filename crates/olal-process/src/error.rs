@@ -1,6 +1,7 @@
 //! Error types for media processing.
 
 use std::path::PathBuf;
+use olal_core::{Diagnostic, ErrorCode};
 use thiserror::Error;
 
 /// Result type for processing operations.
@@ -15,7 +16,7 @@ pub enum ProcessError {
     #[error("File not found: {0}")]
     FileNotFound(PathBuf),
 
-    #[error("Tool not found: {tool}. Please install it.")]
+    #[error("Tool not found: {tool}")]
     ToolNotFound { tool: String },
 
     #[error("FFmpeg error: {0}")]
@@ -36,3 +37,30 @@ pub enum ProcessError {
     #[error("Parse error: {0}")]
     ParseError(String),
 }
+
+impl Diagnostic for ProcessError {
+    fn code(&self) -> ErrorCode {
+        match self {
+            ProcessError::Io(_) => ErrorCode::Io,
+            ProcessError::FileNotFound(_) => ErrorCode::NotFound,
+            ProcessError::ToolNotFound { .. } => ErrorCode::ToolMissing,
+            ProcessError::UnsupportedFormat(_) => ErrorCode::Unsupported,
+            _ => ErrorCode::Processing,
+        }
+    }
+
+    fn hint(&self) -> Option<String> {
+        match self {
+            ProcessError::ToolNotFound { tool } => Some(match tool.as_str() {
+                "ffmpeg" | "ffprobe" => "Install ffmpeg (e.g. 'brew install ffmpeg').".to_string(),
+                "whisper" | "whisper-cpp" => {
+                    "Install whisper.cpp (e.g. 'brew install whisper-cpp').".to_string()
+                }
+                "tesseract" => "Install tesseract (e.g. 'brew install tesseract').".to_string(),
+                "pdftoppm" | "pdfimages" => "Install poppler (e.g. 'brew install poppler').".to_string(),
+                _ => format!("Install {} and make sure it's on your PATH.", tool),
+            }),
+            _ => None,
+        }
+    }
+}
@@ -276,10 +276,265 @@ pub fn extract_frame_at(
     Ok(())
 }
 
+/// Where in a video its thumbnail is taken, as a fraction of the duration.
+/// Far enough in to skip title cards and fade-ins.
+const THUMBNAIL_POSITION: f64 = 0.1;
+
+/// Thumbnail width in pixels; smaller videos keep their size.
+const THUMBNAIL_WIDTH: u32 = 480;
+
+/// Save a representative frame of a video as a JPEG thumbnail.
+pub fn extract_thumbnail(video_path: &Path, output_path: &Path, duration: f64) -> ProcessResult<()> {
+    if !video_path.exists() {
+        return Err(ProcessError::FileNotFound(video_path.to_path_buf()));
+    }
+
+    if which::which("ffmpeg").is_err() {
+        return Err(ProcessError::ToolNotFound {
+            tool: "ffmpeg".to_string(),
+        });
+    }
+
+    debug!("Extracting thumbnail from {:?}", video_path);
+
+    let output = Command::new("ffmpeg")
+        .args(["-ss", &format!("{:.2}", duration * THUMBNAIL_POSITION)])
+        .args(["-i"])
+        .arg(video_path)
+        .args([
+            "-vframes", "1",
+            "-vf", &format!("scale='min({},iw)':-2", THUMBNAIL_WIDTH),
+            "-q:v", "4",
+            "-y",
+        ])
+        .arg(output_path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ProcessError::FfmpegError(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// How far a stream-copied clip may be off the requested length before
+/// it's re-encoded. `-c copy` can only cut at keyframes, so with sparse
+/// keyframes the clip starts well before the requested time.
+const COPY_TOLERANCE: f64 = 1.0;
+
+/// How a clip was cut.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClipMethod {
+    /// Streams copied as-is: fast and lossless.
+    Copy,
+    /// Decoded and encoded again, for an exact cut.
+    Reencode,
+}
+
+impl ClipMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClipMethod::Copy => "copy",
+            ClipMethod::Reencode => "reencode",
+        }
+    }
+}
+
+/// Cut `start..end` (seconds) out of a video or audio file.
+///
+/// Streams are copied when that gives an accurate cut; when the copy fails
+/// or snaps to a keyframe too far from `start`, the clip is re-encoded.
+pub fn cut_clip(source: &Path, output_path: &Path, start: f64, end: f64) -> ProcessResult<ClipMethod> {
+    if !source.exists() {
+        return Err(ProcessError::FileNotFound(source.to_path_buf()));
+    }
+
+    if which::which("ffmpeg").is_err() {
+        return Err(ProcessError::ToolNotFound {
+            tool: "ffmpeg".to_string(),
+        });
+    }
+
+    let duration = end - start;
+    if duration <= 0.0 {
+        return Err(ProcessError::FfmpegError(format!(
+            "Clip ends ({:.1}s) before it starts ({:.1}s)",
+            end, start
+        )));
+    }
+
+    info!("Cutting {:.1}s-{:.1}s of {:?}", start, end, source);
+
+    let copied = run_cut(
+        source,
+        output_path,
+        start,
+        duration,
+        &["-c", "copy", "-avoid_negative_ts", "make_zero"],
+    );
+    match copied {
+        Ok(()) => {
+            let actual = get_video_info(output_path).map(|info| info.duration).unwrap_or(0.0);
+            if copy_is_accurate(duration, actual) {
+                return Ok(ClipMethod::Copy);
+            }
+            debug!(
+                "Stream copy gave {:.1}s instead of {:.1}s, re-encoding",
+                actual, duration
+            );
+        }
+        Err(e) => debug!("Stream copy failed, re-encoding: {}", e),
+    }
+
+    // Without codec options ffmpeg picks the container's default encoders
+    run_cut(source, output_path, start, duration, &[])?;
+    Ok(ClipMethod::Reencode)
+}
+
+fn run_cut(
+    source: &Path,
+    output_path: &Path,
+    start: f64,
+    duration: f64,
+    codec_args: &[&str],
+) -> ProcessResult<()> {
+    let output = Command::new("ffmpeg")
+        .args(["-ss", &format!("{:.3}", start)])
+        .args(["-i"])
+        .arg(source)
+        .args(["-t", &format!("{:.3}", duration)])
+        .args(codec_args)
+        .arg("-y")
+        .arg(output_path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ProcessError::FfmpegError(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Width and height of vertical (9:16) video.
+const VERTICAL_SIZE: (u32, u32) = (1080, 1920);
+
+/// How a landscape frame is fitted into 9:16.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerticalFit {
+    /// Fill the frame, cropping the sides.
+    Crop,
+    /// Keep the whole frame, with bars above and below.
+    Pad,
+}
+
+/// Render `start..end` (seconds) of a video as a 9:16 H.264 clip, burning
+/// in the subtitles from an SRT file if one is given.
+pub fn render_vertical(
+    source: &Path,
+    output_path: &Path,
+    start: f64,
+    end: f64,
+    fit: VerticalFit,
+    subtitles: Option<&Path>,
+) -> ProcessResult<()> {
+    if !source.exists() {
+        return Err(ProcessError::FileNotFound(source.to_path_buf()));
+    }
+
+    if which::which("ffmpeg").is_err() {
+        return Err(ProcessError::ToolNotFound {
+            tool: "ffmpeg".to_string(),
+        });
+    }
+
+    let (width, height) = VERTICAL_SIZE;
+    let mut filters = vec![match fit {
+        VerticalFit::Crop => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=increase,crop={w}:{h}",
+            w = width,
+            h = height
+        ),
+        VerticalFit::Pad => format!(
+            "scale={w}:{h}:force_original_aspect_ratio=decrease,pad={w}:{h}:(ow-iw)/2:(oh-ih)/2",
+            w = width,
+            h = height
+        ),
+    }];
+    filters.push("setsar=1".to_string());
+    if let Some(subtitles) = subtitles {
+        // Large captions in the lower third, clear of the platforms' buttons
+        filters.push(format!(
+            "subtitles='{}':force_style='Alignment=2,FontSize=14,Bold=1,Outline=2,MarginV=70'",
+            escape_filter_path(subtitles)
+        ));
+    }
+
+    info!("Rendering vertical clip {:.1}s-{:.1}s of {:?}", start, end, source);
+
+    let output = Command::new("ffmpeg")
+        .args(["-ss", &format!("{:.3}", start)])
+        .args(["-i"])
+        .arg(source)
+        .args(["-t", &format!("{:.3}", end - start)])
+        .args(["-vf", &filters.join(",")])
+        .args([
+            "-c:v", "libx264",
+            "-preset", "medium",
+            "-crf", "20",
+            "-c:a", "aac",
+            "-b:a", "160k",
+            "-movflags", "+faststart",
+            "-y",
+        ])
+        .arg(output_path)
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ProcessError::FfmpegError(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Escape a path for use inside a quoted ffmpeg filter argument. Quotes
+/// can't be escaped there, so the path must not contain any.
+fn escape_filter_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/").replace(':', "\\:")
+}
+
+/// Whether a stream-copied clip of `actual` seconds is close enough to the
+/// `expected` length.
+fn copy_is_accurate(expected: f64, actual: f64) -> bool {
+    (actual - expected).abs() <= COPY_TOLERANCE
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_copy_is_accurate() {
+        assert!(copy_is_accurate(30.0, 30.4));
+        // Snapped back to a keyframe 4 seconds early
+        assert!(!copy_is_accurate(30.0, 34.0));
+        // An empty or unreadable copy
+        assert!(!copy_is_accurate(30.0, 0.0));
+    }
+
+    #[test]
+    fn test_escape_filter_path() {
+        assert_eq!(
+            escape_filter_path(Path::new("C:\\Temp\\clip.srt")),
+            "C\\:/Temp/clip.srt"
+        );
+    }
+
     #[test]
     fn test_tool_check() {
         // Just verify the tool check doesn't panic
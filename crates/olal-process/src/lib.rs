@@ -4,18 +4,32 @@
 //! - Video processing (via FFmpeg CLI)
 //! - Audio transcription (via Whisper CLI)
 //! - OCR for images (via Tesseract CLI)
+//! - Rendering PDF pages and extracting their images (via poppler)
 //!
 //! These rely on external tools being installed on the system.
 
+mod audio;
 mod error;
 mod ffmpeg;
 mod ocr;
+mod pdf;
+mod subtitles;
 mod transcribe;
 
+pub use audio::{preprocess_audio, AudioPreprocessing, PreparedAudio};
 pub use error::{ProcessError, ProcessResult};
-pub use ffmpeg::{extract_audio, extract_frames, get_video_info, VideoInfo};
-pub use ocr::{ocr_image, OcrResult};
-pub use transcribe::{transcribe_audio, TranscriptSegment};
+pub use ffmpeg::{
+    cut_clip, extract_audio, extract_frames, extract_thumbnail, get_video_info, render_vertical,
+    ClipMethod, VerticalFit, VideoInfo,
+};
+pub use ocr::{ocr_image, ocr_images_deduplicated, OcrResult};
+pub use pdf::{extract_pdf_images, rasterize_pdf, PdfImage};
+pub use subtitles::{to_srt, word_captions, Caption, WORDS_PER_CAPTION};
+pub use transcribe::{
+    backend, language_setting, transcribe_audio, BackendCapabilities, FasterWhisper,
+    TranscribeOptions, Transcript, TranscriptSegment, TranscriptWord, TranscriptionBackend, WhisperCli, WhisperX,
+    BACKENDS,
+};
 
 /// Check if required external tools are available.
 pub fn check_dependencies() -> Vec<(&'static str, bool)> {
@@ -24,6 +38,7 @@ pub fn check_dependencies() -> Vec<(&'static str, bool)> {
         ("ffprobe", which::which("ffprobe").is_ok()),
         ("whisper", which::which("whisper").is_ok()),
         ("tesseract", which::which("tesseract").is_ok()),
+        ("pdftoppm", which::which("pdftoppm").is_ok()),
     ]
 }
 
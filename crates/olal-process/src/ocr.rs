@@ -16,6 +16,22 @@ pub struct OcrResult {
 
 /// Perform OCR on an image file.
 pub fn ocr_image(image_path: &Path) -> ProcessResult<OcrResult> {
+    debug!("Running OCR on {:?}", image_path);
+    // Automatic page segmentation with OSD
+    run_tesseract(image_path, "1")
+}
+
+/// Perform OCR optimized for code/terminal screenshots.
+#[allow(dead_code)]
+pub fn ocr_code_image(image_path: &Path) -> ProcessResult<OcrResult> {
+    debug!("Running code-optimized OCR on {:?}", image_path);
+    // Assume a uniform block of text (good for code)
+    run_tesseract(image_path, "6")
+}
+
+/// Run tesseract with page segmentation mode `psm`, reading its TSV output
+/// so word confidences come back with the text.
+fn run_tesseract(image_path: &Path, psm: &str) -> ProcessResult<OcrResult> {
     if !image_path.exists() {
         return Err(ProcessError::FileNotFound(image_path.to_path_buf()));
     }
@@ -27,14 +43,12 @@ pub fn ocr_image(image_path: &Path) -> ProcessResult<OcrResult> {
         });
     }
 
-    debug!("Running OCR on {:?}", image_path);
-
-    // Run tesseract
     let output = Command::new("tesseract")
         .arg(image_path)
         .arg("stdout")  // Output to stdout instead of file
         .args(["--oem", "3"])  // LSTM + legacy engine
-        .args(["--psm", "1"])  // Automatic page segmentation with OSD
+        .args(["--psm", psm])
+        .arg("tsv")
         .output()?;
 
     if !output.status.success() {
@@ -47,57 +61,53 @@ pub fn ocr_image(image_path: &Path) -> ProcessResult<OcrResult> {
         }
     }
 
-    let text = String::from_utf8_lossy(&output.stdout)
-        .trim()
-        .to_string();
-
-    Ok(OcrResult {
-        text,
-        confidence: None,
-    })
+    Ok(parse_tsv(&String::from_utf8_lossy(&output.stdout)))
 }
 
-/// Perform OCR optimized for code/terminal screenshots.
-#[allow(dead_code)]
-pub fn ocr_code_image(image_path: &Path) -> ProcessResult<OcrResult> {
-    if !image_path.exists() {
-        return Err(ProcessError::FileNotFound(image_path.to_path_buf()));
-    }
-
-    if which::which("tesseract").is_err() {
-        return Err(ProcessError::ToolNotFound {
-            tool: "tesseract".to_string(),
-        });
-    }
-
-    debug!("Running code-optimized OCR on {:?}", image_path);
+/// Rebuild the text from tesseract's TSV rows, one line per text line and
+/// a blank line between paragraphs. The confidence is the mean over words.
+fn parse_tsv(tsv: &str) -> OcrResult {
+    let mut text = String::new();
+    let mut confidences = Vec::new();
+    let mut last: Option<(&str, &str, &str, &str)> = None;
+
+    // level, page, block, par, line, word, left, top, width, height, conf, text
+    for row in tsv.lines().skip(1) {
+        let cols: Vec<&str> = row.splitn(12, '\t').collect();
+        if cols.len() < 12 || cols[0] != "5" {
+            continue;
+        }
+        let word = cols[11].trim();
+        if word.is_empty() {
+            continue;
+        }
 
-    // Use PSM 6 for uniform block of text (good for code)
-    let output = Command::new("tesseract")
-        .arg(image_path)
-        .arg("stdout")
-        .args(["--oem", "3"])
-        .args(["--psm", "6"])  // Assume uniform block of text
-        .output()?;
+        let line = (cols[1], cols[2], cols[3], cols[4]);
+        match last {
+            Some(prev) if prev == line => text.push(' '),
+            Some(prev) if (prev.0, prev.1, prev.2) == (line.0, line.1, line.2) => text.push('\n'),
+            Some(_) => text.push_str("\n\n"),
+            None => {}
+        }
+        text.push_str(word);
+        last = Some(line);
 
-    if !output.status.success() && output.stdout.is_empty() {
-        return Err(ProcessError::OcrError(
-            String::from_utf8_lossy(&output.stderr).to_string(),
-        ));
+        if let Ok(conf) = cols[10].parse::<f32>() {
+            if conf >= 0.0 {
+                confidences.push(conf);
+            }
+        }
     }
 
-    let text = String::from_utf8_lossy(&output.stdout)
-        .trim()
-        .to_string();
-
-    Ok(OcrResult {
-        text,
-        confidence: None,
-    })
+    let confidence = (!confidences.is_empty())
+        .then(|| confidences.iter().sum::<f32>() / confidences.len() as f32);
+    OcrResult { text, confidence }
 }
 
 /// Perform OCR on multiple images and deduplicate similar text.
-#[allow(dead_code)]
+///
+/// Returns the index of each image whose text differs from every earlier
+/// one (frames showing the same screen are dropped), with its result.
 pub fn ocr_images_deduplicated(image_paths: &[impl AsRef<Path>]) -> ProcessResult<Vec<(usize, OcrResult)>> {
     let mut results: Vec<(usize, OcrResult)> = Vec::new();
     let mut seen_texts: Vec<String> = Vec::new();
@@ -121,7 +131,6 @@ pub fn ocr_images_deduplicated(image_paths: &[impl AsRef<Path>]) -> ProcessResul
 }
 
 /// Check if text is similar to any in the list (basic deduplication).
-#[allow(dead_code)]
 fn is_similar_to_any(text: &str, others: &[String]) -> bool {
     if text.is_empty() {
         return true;  // Skip empty text
@@ -136,7 +145,6 @@ fn is_similar_to_any(text: &str, others: &[String]) -> bool {
 }
 
 /// Check if two texts are similar (simple heuristic).
-#[allow(dead_code)]
 fn is_similar(a: &str, b: &str) -> bool {
     if a == b {
         return true;
@@ -180,6 +188,24 @@ mod tests {
         assert!(!is_similar("hello", "goodbye"));
     }
 
+    #[test]
+    fn test_parse_tsv() {
+        let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t800\t600\t-1\t
+5\t1\t1\t1\t1\t1\t10\t10\t50\t20\t96.5\tcargo
+5\t1\t1\t1\t1\t2\t70\t10\t50\t20\t91.5\tbuild
+5\t1\t1\t1\t2\t1\t10\t40\t50\t20\t88\tFinished
+5\t1\t2\t1\t1\t1\t10\t90\t50\t20\t40\tdone
+5\t1\t2\t1\t1\t2\t10\t90\t50\t20\t-1\t 
+";
+        let result = parse_tsv(tsv);
+        assert_eq!(result.text, "cargo build\nFinished\n\ndone");
+        assert_eq!(result.confidence, Some(79.0));
+
+        let empty = parse_tsv("level\tpage_num\n");
+        assert!(empty.text.is_empty() && empty.confidence.is_none());
+    }
+
     #[test]
     fn test_tool_check() {
         let _ = which::which("tesseract");
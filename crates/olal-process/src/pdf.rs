@@ -0,0 +1,220 @@
+//! Rendering PDF pages to images and extracting the images embedded in
+//! them (via poppler's `pdftoppm` and `pdfimages`).
+
+use crate::error::{ProcessError, ProcessResult};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::{debug, info};
+
+/// File name prefix of the rendered pages.
+const PAGE_PREFIX: &str = "page";
+
+/// File name prefix of the extracted images.
+const IMAGE_PREFIX: &str = "image";
+
+/// An image embedded in a PDF, written out as a PNG.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfImage {
+    /// Page the image is on, from 1.
+    pub page: u32,
+    pub path: PathBuf,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+/// Render every page of a PDF as a PNG in `output_dir`, at `dpi` dots per
+/// inch (300 suits OCR).
+///
+/// Returns the images in page order.
+pub fn rasterize_pdf(pdf_path: &Path, output_dir: &Path, dpi: u32) -> ProcessResult<Vec<PathBuf>> {
+    if !pdf_path.exists() {
+        return Err(ProcessError::FileNotFound(pdf_path.to_path_buf()));
+    }
+
+    if which::which("pdftoppm").is_err() {
+        return Err(ProcessError::ToolNotFound {
+            tool: "pdftoppm".to_string(),
+        });
+    }
+
+    info!("Rendering pages of {:?} at {} dpi", pdf_path, dpi);
+
+    let output = Command::new("pdftoppm")
+        .args(["-r", &dpi.to_string()])
+        .arg("-png")
+        .arg(pdf_path)
+        .arg(output_dir.join(PAGE_PREFIX))
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ProcessError::ProcessFailed {
+            code: output.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+
+    let pages = page_images(output_dir)?;
+    debug!("Rendered {} pages", pages.len());
+    Ok(pages)
+}
+
+/// Write the images embedded in a PDF to `output_dir` as PNGs.
+///
+/// Returns them in page order. Soft masks and stencils (the transparency
+/// of other images) are left out; icons and rules aren't, so filter by
+/// size to keep just the figures.
+pub fn extract_pdf_images(pdf_path: &Path, output_dir: &Path) -> ProcessResult<Vec<PdfImage>> {
+    if !pdf_path.exists() {
+        return Err(ProcessError::FileNotFound(pdf_path.to_path_buf()));
+    }
+
+    if which::which("pdfimages").is_err() {
+        return Err(ProcessError::ToolNotFound {
+            tool: "pdfimages".to_string(),
+        });
+    }
+
+    info!("Extracting images from {:?}", pdf_path);
+
+    // The image types, by the number each image's file name ends in
+    let list = Command::new("pdfimages").arg("-list").arg(pdf_path).output()?;
+    if !list.status.success() {
+        return Err(ProcessError::ProcessFailed {
+            code: list.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&list.stderr).to_string(),
+        });
+    }
+    let pictures = pictures(&String::from_utf8_lossy(&list.stdout));
+
+    // -p puts the page number in each file name
+    let output = Command::new("pdfimages")
+        .args(["-png", "-p"])
+        .arg(pdf_path)
+        .arg(output_dir.join(IMAGE_PREFIX))
+        .output()?;
+
+    if !output.status.success() {
+        return Err(ProcessError::ProcessFailed {
+            code: output.status.code().unwrap_or(-1),
+            stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+
+    let mut images = Vec::new();
+    for entry in std::fs::read_dir(output_dir)? {
+        let path = entry?.path();
+        // image-<page>-<number>.png
+        let numbers = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix(IMAGE_PREFIX)?.strip_prefix('-')?.strip_suffix(".png"))
+            .and_then(|n| n.split_once('-'))
+            .and_then(|(page, number)| Some((page.parse::<u32>().ok()?, number.parse::<u32>().ok()?)));
+        let Some((page, number)) = numbers.filter(|(_, number)| pictures.contains(number)) else {
+            continue;
+        };
+        let Some((width, height)) = png_size(&path) else {
+            continue;
+        };
+        images.push((number, PdfImage { page, path, width, height }));
+    }
+    images.sort_by_key(|(number, image)| (image.page, *number));
+
+    debug!("Extracted {} images", images.len());
+    Ok(images.into_iter().map(|(_, image)| image).collect())
+}
+
+/// The numbers of the images that are pictures (type `image`) in
+/// `pdfimages -list` output, leaving out masks and stencils.
+fn pictures(list: &str) -> HashSet<u32> {
+    // page num type width height color comp bpc enc interp object ID ...
+    list.lines()
+        .filter_map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            match columns.as_slice() {
+                [_, num, "image", ..] => num.parse().ok(),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+/// Width and height from a PNG's header.
+fn png_size(path: &Path) -> Option<(u32, u32)> {
+    let mut header = [0u8; 24];
+    std::io::Read::read_exact(&mut std::fs::File::open(path).ok()?, &mut header).ok()?;
+    if &header[..8] != b"\x89PNG\r\n\x1a\n" || &header[12..16] != b"IHDR" {
+        return None;
+    }
+    let width = u32::from_be_bytes(header[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(header[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+/// The rendered pages in `dir` (`page-1.png`, or `page-01.png` and so on
+/// for longer documents), sorted by page number.
+fn page_images(dir: &Path) -> ProcessResult<Vec<PathBuf>> {
+    let mut pages: Vec<(u32, PathBuf)> = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let number = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .and_then(|n| n.strip_prefix(PAGE_PREFIX)?.strip_prefix('-')?.strip_suffix(".png"))
+            .and_then(|n| n.parse().ok());
+        if let Some(number) = number {
+            pages.push((number, path));
+        }
+    }
+    pages.sort();
+    Ok(pages.into_iter().map(|(_, path)| path).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_images_sorted_by_number() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["page-10.png", "page-02.png", "page-1.png", "notes.txt"] {
+            std::fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let names: Vec<String> = page_images(dir.path())
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["page-1.png", "page-02.png", "page-10.png"]);
+    }
+
+    #[test]
+    fn test_pictures() {
+        let list = "\
+page   num  type   width height color comp bpc  enc interp  object ID x-ppi y-ppi size ratio
+--------------------------------------------------------------------------------------------
+   1     0 image     640   480  rgb     3   8  jpeg   no        12  0   150   150 41.2K 4.6%
+   1     1 smask     640   480  gray    1   8  image  no        12  0   150   150 2210B 0.7%
+   2     2 stencil    32    32  -       1   1  image  no        15  0    72    72   12B 9.4%
+   2     3 image     800   600  icc     3   8  image  no        18  0   150   150  120K 8.5%
+";
+        assert_eq!(pictures(list), HashSet::from([0, 3]));
+    }
+
+    #[test]
+    fn test_png_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(640u32.to_be_bytes());
+        png.extend(480u32.to_be_bytes());
+        let path = dir.path().join("image-001-000.png");
+        std::fs::write(&path, &png).unwrap();
+        assert_eq!(png_size(&path), Some((640, 480)));
+
+        std::fs::write(&path, b"not a png at all, but long enough").unwrap();
+        assert_eq!(png_size(&path), None);
+    }
+}
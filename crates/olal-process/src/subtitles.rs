@@ -0,0 +1,134 @@
+//! Subtitles for rendered clips.
+//!
+//! Transcripts are stored per segment, not per word, so word timing is
+//! estimated by spreading each segment's words evenly over its duration.
+//! Short captions of a few words at a time are what short-form video
+//! expects.
+
+use crate::TranscriptSegment;
+
+/// Words shown together in one caption.
+pub const WORDS_PER_CAPTION: usize = 3;
+
+/// A caption shown from `start` to `end` seconds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Caption {
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+/// Split transcript segments into captions of `words_per_caption` words,
+/// timed by the words' own timestamps when the segment has them, else by
+/// each word's estimated position in its segment.
+pub fn word_captions(segments: &[TranscriptSegment], words_per_caption: usize) -> Vec<Caption> {
+    let words_per_caption = words_per_caption.max(1);
+    let mut captions = Vec::new();
+
+    for segment in segments {
+        if !segment.words.is_empty() {
+            captions.extend(segment.words.chunks(words_per_caption).map(|group| Caption {
+                start: group[0].start,
+                end: group[group.len() - 1].end,
+                text: group.iter().map(|w| w.text.as_str()).collect::<Vec<_>>().join(" "),
+            }));
+            continue;
+        }
+
+        let words: Vec<&str> = segment.text.split_whitespace().collect();
+        if words.is_empty() || segment.end <= segment.start {
+            continue;
+        }
+        let per_word = (segment.end - segment.start) / words.len() as f64;
+
+        for (i, group) in words.chunks(words_per_caption).enumerate() {
+            let first = i * words_per_caption;
+            captions.push(Caption {
+                start: segment.start + first as f64 * per_word,
+                end: segment.start + (first + group.len()) as f64 * per_word,
+                text: group.join(" "),
+            });
+        }
+    }
+    captions
+}
+
+/// Captions as an SRT file.
+pub fn to_srt(captions: &[Caption]) -> String {
+    captions
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            format!(
+                "{}\n{} --> {}\n{}\n",
+                i + 1,
+                srt_time(c.start),
+                srt_time(c.end),
+                c.text
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `HH:MM:SS,mmm`
+fn srt_time(seconds: f64) -> String {
+    let millis = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02},{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TranscriptWord;
+
+    #[test]
+    fn test_word_captions() {
+        let segments = vec![TranscriptSegment {
+            text: "one two three four".to_string(),
+            start: 10.0,
+            end: 14.0,
+            speaker: None,
+            words: Vec::new(),
+        }];
+        let captions = word_captions(&segments, 3);
+        assert_eq!(captions.len(), 2);
+        assert_eq!(captions[0].text, "one two three");
+        assert_eq!((captions[0].start, captions[0].end), (10.0, 13.0));
+        assert_eq!(captions[1].text, "four");
+        assert_eq!((captions[1].start, captions[1].end), (13.0, 14.0));
+
+        // Real word timings win over the even spread
+        let word = |text: &str, start: f64, end: f64| TranscriptWord { text: text.to_string(), start, end };
+        let segments = vec![TranscriptSegment {
+            text: "one two three".to_string(),
+            start: 10.0,
+            end: 14.0,
+            speaker: None,
+            words: vec![word("one", 10.2, 10.5), word("two", 11.0, 11.4), word("three", 12.8, 13.6)],
+        }];
+        let captions = word_captions(&segments, 2);
+        assert_eq!(captions[0].text, "one two");
+        assert_eq!((captions[0].start, captions[0].end), (10.2, 11.4));
+        assert_eq!((captions[1].start, captions[1].end), (12.8, 13.6));
+    }
+
+    #[test]
+    fn test_to_srt() {
+        let captions = vec![
+            Caption { start: 0.0, end: 1.5, text: "Hello there".to_string() },
+            Caption { start: 3661.25, end: 3662.0, text: "Bye".to_string() },
+        ];
+        assert_eq!(
+            to_srt(&captions),
+            "1\n00:00:00,000 --> 00:00:01,500\nHello there\n\n\
+             2\n01:01:01,250 --> 01:01:02,000\nBye\n"
+        );
+    }
+}
@@ -4,10 +4,10 @@ use crate::error::{ProcessError, ProcessResult};
 use serde::Deserialize;
 use std::path::Path;
 use std::process::Command;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
 /// A segment of transcribed audio.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct TranscriptSegment {
     /// The transcribed text.
     pub text: String,
@@ -15,13 +15,36 @@ pub struct TranscriptSegment {
     pub start: f64,
     /// End time in seconds.
     pub end: f64,
+    /// Who is speaking (e.g. `SPEAKER_00`), from backends that diarize.
+    pub speaker: Option<String>,
+    /// Timings of the segment's words, when the backend gave them.
+    pub words: Vec<TranscriptWord>,
+}
+
+/// A word of a transcript segment, with its own timing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TranscriptWord {
+    pub text: String,
+    /// Start time in seconds.
+    pub start: f64,
+    /// End time in seconds.
+    pub end: f64,
+}
+
+/// A transcribed recording.
+#[derive(Debug, Clone, Default)]
+pub struct Transcript {
+    pub segments: Vec<TranscriptSegment>,
+    /// Language code of the speech (e.g. `en`, `es`), as given or detected.
+    pub language: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct WhisperJsonOutput {
-    #[allow(dead_code)]
-    text: String,
     segments: Vec<WhisperSegment>,
+    /// Detected (or forced) language.
+    #[serde(default)]
+    language: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -29,38 +52,277 @@ struct WhisperSegment {
     text: String,
     start: f64,
     end: f64,
+    #[serde(default)]
+    speaker: Option<String>,
+    #[serde(default)]
+    words: Vec<WhisperWord>,
 }
 
-/// Transcribe an audio file using Whisper.
-///
-/// Requires the `whisper` CLI to be installed (pip install openai-whisper).
-pub fn transcribe_audio(
+/// Words come with `--word_timestamps` (or WhisperX's alignment), which
+/// leaves a word without times when it can't be aligned (e.g. numerals).
+#[derive(Debug, Deserialize)]
+struct WhisperWord {
+    word: String,
+    #[serde(default)]
+    start: Option<f64>,
+    #[serde(default)]
+    end: Option<f64>,
+}
+
+/// What a transcription backend can do beyond plain segments.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BackendCapabilities {
+    /// Timestamps for individual words.
+    pub word_timestamps: bool,
+    /// Speaker labels on segments.
+    pub diarization: bool,
+}
+
+/// Settings for one transcription run.
+#[derive(Debug, Clone)]
+pub struct TranscribeOptions {
+    /// Model size or name (tiny, base, small, medium, large, ...).
+    pub model: String,
+    /// Label speakers, if the backend can.
+    pub diarize: bool,
+    /// Language spoken; `None` lets the model detect it.
+    pub language: Option<String>,
+    /// Time individual words, if the backend can.
+    pub word_timestamps: bool,
+}
+
+impl TranscribeOptions {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            diarize: false,
+            language: None,
+            word_timestamps: false,
+        }
+    }
+
+    /// Options for `backend`: word timestamps whenever it has them, and
+    /// diarization only if it can (with a warning when it can't).
+    pub fn for_backend(
+        backend: &dyn TranscriptionBackend,
+        model: impl Into<String>,
+        diarize: bool,
+        language: Option<String>,
+    ) -> Self {
+        let capabilities = backend.capabilities();
+        if diarize && !capabilities.diarization {
+            warn!(
+                "{} can't label speakers; set processing.whisper_backend = \"whisperx\" to diarize",
+                backend.name()
+            );
+        }
+        Self {
+            model: model.into(),
+            diarize: diarize && capabilities.diarization,
+            language,
+            word_timestamps: capabilities.word_timestamps,
+        }
+    }
+}
+
+/// The language setting as an option: empty or `auto` means detect.
+pub fn language_setting(value: &str) -> Option<String> {
+    let value = value.trim().to_lowercase();
+    (!value.is_empty() && value != "auto").then_some(value)
+}
+
+/// A speech-to-text engine run as an external tool.
+pub trait TranscriptionBackend: Send + Sync {
+    /// Name used in `processing.whisper_backend`.
+    fn name(&self) -> &'static str;
+
+    /// The executable the backend runs.
+    fn tool(&self) -> &'static str;
+
+    /// How to install the tool.
+    fn install_hint(&self) -> &'static str;
+
+    fn capabilities(&self) -> BackendCapabilities;
+
+    /// Whether the tool is installed.
+    fn is_available(&self) -> bool {
+        which::which(self.tool()).is_ok()
+    }
+
+    /// Transcribe `audio_path`, writing intermediate files to `output_dir`.
+    fn transcribe(
+        &self,
+        audio_path: &Path,
+        output_dir: &Path,
+        options: &TranscribeOptions,
+    ) -> ProcessResult<Transcript>;
+}
+
+/// The original OpenAI `whisper` CLI (`pip install openai-whisper`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhisperCli;
+
+/// faster-whisper (CTranslate2) through the whisper-compatible
+/// `whisper-ctranslate2` CLI: same output, several times faster on CPU.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FasterWhisper;
+
+/// WhisperX: faster-whisper plus forced alignment for accurate word
+/// timestamps, and speaker diarization (needs a Hugging Face token in
+/// `HF_TOKEN`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhisperX;
+
+/// Names accepted by [`backend`].
+pub const BACKENDS: &[&str] = &["whisper", "faster-whisper", "whisperx"];
+
+/// The backend called `name` (see [`BACKENDS`]).
+pub fn backend(name: &str) -> Option<Box<dyn TranscriptionBackend>> {
+    match name.trim().to_lowercase().replace('_', "-").as_str() {
+        "whisper" | "openai-whisper" => Some(Box::new(WhisperCli)),
+        "faster-whisper" | "whisper-ctranslate2" => Some(Box::new(FasterWhisper)),
+        "whisperx" => Some(Box::new(WhisperX)),
+        _ => None,
+    }
+}
+
+impl TranscriptionBackend for WhisperCli {
+    fn name(&self) -> &'static str {
+        "whisper"
+    }
+
+    fn tool(&self) -> &'static str {
+        "whisper"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "pip install openai-whisper"
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            word_timestamps: true,
+            diarization: false,
+        }
+    }
+
+    fn transcribe(
+        &self,
+        audio_path: &Path,
+        output_dir: &Path,
+        options: &TranscribeOptions,
+    ) -> ProcessResult<Transcript> {
+        let words: &[&str] = if options.word_timestamps { &["--word_timestamps", "True"] } else { &[] };
+        run_whisper_style(self, audio_path, output_dir, options, words)
+    }
+}
+
+impl TranscriptionBackend for FasterWhisper {
+    fn name(&self) -> &'static str {
+        "faster-whisper"
+    }
+
+    fn tool(&self) -> &'static str {
+        "whisper-ctranslate2"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "pip install whisper-ctranslate2"
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            word_timestamps: true,
+            diarization: false,
+        }
+    }
+
+    fn transcribe(
+        &self,
+        audio_path: &Path,
+        output_dir: &Path,
+        options: &TranscribeOptions,
+    ) -> ProcessResult<Transcript> {
+        // Skip stretches without speech instead of hallucinating over them
+        let mut args = vec!["--vad_filter", "True"];
+        if options.word_timestamps {
+            args.extend(["--word_timestamps", "True"]);
+        }
+        run_whisper_style(self, audio_path, output_dir, options, &args)
+    }
+}
+
+impl TranscriptionBackend for WhisperX {
+    fn name(&self) -> &'static str {
+        "whisperx"
+    }
+
+    fn tool(&self) -> &'static str {
+        "whisperx"
+    }
+
+    fn install_hint(&self) -> &'static str {
+        "pip install whisperx"
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            word_timestamps: true,
+            diarization: true,
+        }
+    }
+
+    fn transcribe(
+        &self,
+        audio_path: &Path,
+        output_dir: &Path,
+        options: &TranscribeOptions,
+    ) -> ProcessResult<Transcript> {
+        // Word timings come from WhisperX's alignment, which always runs
+        let diarize: &[&str] = if options.diarize { &["--diarize"] } else { &[] };
+        run_whisper_style(self, audio_path, output_dir, options, diarize)
+    }
+}
+
+/// Run a backend whose CLI follows whisper's (`--model`, `--output_format
+/// json`, `--output_dir`), then read the `<stem>.json` it writes.
+fn run_whisper_style(
+    backend: &dyn TranscriptionBackend,
     audio_path: &Path,
-    model: &str,
     output_dir: &Path,
-) -> ProcessResult<Vec<TranscriptSegment>> {
+    options: &TranscribeOptions,
+    extra_args: &[&str],
+) -> ProcessResult<Transcript> {
     if !audio_path.exists() {
         return Err(ProcessError::FileNotFound(audio_path.to_path_buf()));
     }
 
-    // Check if whisper is available
-    if which::which("whisper").is_err() {
+    // Check if the backend is available
+    if !backend.is_available() {
         return Err(ProcessError::ToolNotFound {
-            tool: "whisper".to_string(),
+            tool: backend.tool().to_string(),
         });
     }
 
-    info!("Transcribing {:?} with model '{}'", audio_path, model);
+    info!(
+        "Transcribing {:?} with {} (model '{}')",
+        audio_path,
+        backend.name(),
+        options.model
+    );
 
-    // Run whisper
-    let output = Command::new("whisper")
+    let mut command = Command::new(backend.tool());
+    command
         .arg(audio_path)
-        .args(["--model", model])
+        .args(["--model", &options.model])
         .args(["--output_format", "json"])
         .args(["--output_dir"])
-        .arg(output_dir)
-        .args(["--language", "en"])  // Default to English
-        .output()?;
+        .arg(output_dir);
+    // Without --language, whisper detects it from the first 30 seconds
+    if let Some(language) = &options.language {
+        command.args(["--language", language]);
+    }
+    let output = command.args(extra_args).output()?;
 
     if !output.status.success() {
         return Err(ProcessError::TranscriptionError(
@@ -76,28 +338,84 @@ pub fn transcribe_audio(
     let json_path = output_dir.join(format!("{}.json", stem));
 
     if !json_path.exists() {
-        return Err(ProcessError::TranscriptionError(
-            "Whisper output file not found".to_string(),
-        ));
+        return Err(ProcessError::TranscriptionError(format!(
+            "{} output file not found",
+            backend.name()
+        )));
+    }
+
+    let mut transcript = parse_transcript(&std::fs::read_to_string(&json_path)?)?;
+    if transcript.language.is_none() {
+        transcript.language = options.language.clone();
     }
+    debug!(
+        "Transcribed {} segments (language: {})",
+        transcript.segments.len(),
+        transcript.language.as_deref().unwrap_or("unknown")
+    );
+    Ok(transcript)
+}
 
-    // Parse the JSON output
-    let json_content = std::fs::read_to_string(&json_path)?;
-    let whisper_output: WhisperJsonOutput = serde_json::from_str(&json_content)
-        .map_err(|e| ProcessError::ParseError(format!("Failed to parse Whisper output: {}", e)))?;
+/// A transcript from whisper-format JSON. WhisperX adds a `speaker` to each
+/// segment when diarizing.
+///
+/// When every word is timed, a segment is trimmed to its first and last
+/// word: whisper's segment bounds often include the silence around them.
+fn parse_transcript(json: &str) -> ProcessResult<Transcript> {
+    let output: WhisperJsonOutput = serde_json::from_str(json)
+        .map_err(|e| ProcessError::ParseError(format!("Failed to parse transcript: {}", e)))?;
 
-    let segments: Vec<TranscriptSegment> = whisper_output
+    let segments = output
         .segments
         .into_iter()
-        .map(|s| TranscriptSegment {
-            text: s.text.trim().to_string(),
-            start: s.start,
-            end: s.end,
+        .map(|s| {
+            let words: Vec<TranscriptWord> = s
+                .words
+                .into_iter()
+                .filter_map(|w| {
+                    let text = w.word.trim();
+                    Some(TranscriptWord {
+                        text: text.to_string(),
+                        start: w.start?,
+                        end: w.end?,
+                    })
+                    .filter(|_| !text.is_empty())
+                })
+                .collect();
+            let all_timed = words.len() == s.text.split_whitespace().count();
+            let (start, end) = match (words.first(), words.last()) {
+                (Some(first), Some(last)) if all_timed => {
+                    let start = first.start.max(s.start);
+                    (start, last.end.min(s.end).max(start))
+                }
+                _ => (s.start, s.end),
+            };
+            TranscriptSegment {
+                text: s.text.trim().to_string(),
+                start,
+                end,
+                speaker: s.speaker,
+                words,
+            }
         })
         .collect();
+    Ok(Transcript {
+        segments,
+        language: output.language.filter(|l| !l.is_empty()),
+    })
+}
 
-    debug!("Transcribed {} segments", segments.len());
-    Ok(segments)
+/// Transcribe an audio file using Whisper, detecting the language.
+///
+/// Requires the `whisper` CLI to be installed (pip install openai-whisper).
+pub fn transcribe_audio(
+    audio_path: &Path,
+    model: &str,
+    output_dir: &Path,
+) -> ProcessResult<Vec<TranscriptSegment>> {
+    WhisperCli
+        .transcribe(audio_path, output_dir, &TranscribeOptions::new(model))
+        .map(|t| t.segments)
 }
 
 /// Transcribe using insanely-fast-whisper for faster processing.
@@ -146,22 +464,9 @@ fn transcribe_with_insanely_fast(
     let json_content = std::fs::read_to_string(&output_path)?;
 
     // Try parsing as whisper format first
-    if let Ok(whisper_output) = serde_json::from_str::<WhisperJsonOutput>(&json_content) {
-        return Ok(whisper_output
-            .segments
-            .into_iter()
-            .map(|s| TranscriptSegment {
-                text: s.text.trim().to_string(),
-                start: s.start,
-                end: s.end,
-            })
-            .collect());
-    }
-
-    // If that fails, try other formats or return error
-    Err(ProcessError::ParseError(
-        "Failed to parse transcription output".to_string(),
-    ))
+    parse_transcript(&json_content)
+        .map(|t| t.segments)
+        .map_err(|_| ProcessError::ParseError("Failed to parse transcription output".to_string()))
 }
 
 /// Get the full transcript text from segments.
@@ -202,11 +507,15 @@ mod tests {
                 text: "Hello".to_string(),
                 start: 0.0,
                 end: 1.0,
+                speaker: None,
+                words: Vec::new(),
             },
             TranscriptSegment {
                 text: "world".to_string(),
                 start: 1.0,
                 end: 2.0,
+                speaker: None,
+                words: Vec::new(),
             },
         ];
 
@@ -220,11 +529,15 @@ mod tests {
                 text: "Hello".to_string(),
                 start: 0.0,
                 end: 1.0,
+                speaker: None,
+                words: Vec::new(),
             },
             TranscriptSegment {
                 text: "world".to_string(),
                 start: 65.0,
                 end: 66.0,
+                speaker: None,
+                words: Vec::new(),
             },
         ];
 
@@ -232,4 +545,49 @@ mod tests {
         assert!(formatted.contains("[00:00] Hello"));
         assert!(formatted.contains("[01:05] world"));
     }
+
+    #[test]
+    fn test_parse_transcript() {
+        // openai-whisper and whisper-ctranslate2
+        let whisper = r#"{"text": " Hola.", "segments": [{"id": 0, "start": 0.0, "end": 1.5, "text": " Hola."}], "language": "es"}"#;
+        let transcript = parse_transcript(whisper).unwrap();
+        assert_eq!(transcript.segments[0].text, "Hola.");
+        assert_eq!(transcript.segments[0].speaker, None);
+        assert_eq!(transcript.language.as_deref(), Some("es"));
+
+        // whisperx: no top-level text, speakers when diarizing
+        let whisperx = r#"{"segments": [{"start": 0.5, "end": 2.0, "text": " Welcome back.", "speaker": "SPEAKER_01",
+            "words": [{"word": "Welcome", "start": 0.5, "end": 1.0}]}], "word_segments": []}"#;
+        let transcript = parse_transcript(whisperx).unwrap();
+        assert_eq!(transcript.segments[0].speaker.as_deref(), Some("SPEAKER_01"));
+        assert_eq!((transcript.segments[0].start, transcript.segments[0].end), (0.5, 2.0));
+        assert_eq!(transcript.language, None);
+        assert_eq!(transcript.segments[0].words[0].text, "Welcome");
+
+        // Word timestamps trim the silence around a segment
+        let words = r#"{"segments": [{"start": 3.0, "end": 8.0, "text": " Two words",
+            "words": [{"word": " Two", "start": 3.4, "end": 3.8}, {"word": " words", "start": 3.9, "end": 4.5}]}]}"#;
+        let transcript = parse_transcript(words).unwrap();
+        assert_eq!((transcript.segments[0].start, transcript.segments[0].end), (3.4, 4.5));
+        assert_eq!(transcript.segments[0].words.len(), 2);
+
+        assert!(parse_transcript("not json").is_err());
+    }
+
+    #[test]
+    fn test_language_setting() {
+        assert_eq!(language_setting("auto"), None);
+        assert_eq!(language_setting(""), None);
+        assert_eq!(language_setting(" DE "), Some("de".to_string()));
+    }
+
+    #[test]
+    fn test_backend_by_name() {
+        for name in BACKENDS {
+            assert_eq!(backend(name).unwrap().name(), *name);
+        }
+        assert_eq!(backend("faster_whisper").unwrap().tool(), "whisper-ctranslate2");
+        assert!(backend("whisperx").unwrap().capabilities().diarization);
+        assert!(backend("vosk").is_none());
+    }
 }